@@ -0,0 +1,66 @@
+//! Guard against cancellation checks slowing down parsing
+//!
+//! Run with `cargo bench --bench cancellation_overhead`. Parses the same
+//! generated source without a token and with a token that never fires, and
+//! fails if the token costs more than `MAX_OVERHEAD` of the plain parse.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use apexrust::{parse, CancellationToken, Parser};
+
+const STATEMENTS: usize = 30_000;
+const RUNS: u32 = 9;
+const MAX_OVERHEAD: f64 = 0.10;
+
+/// Methods of 100 assignment statements each
+fn generated_class(statements: usize) -> String {
+    let mut source = String::from("public class Generated {\n");
+    for method in 0..statements / 100 {
+        source.push_str(&format!("    public void run{}() {{\n", method));
+        source.push_str("        Integer total = 0;\n");
+        for i in 0..100 {
+            source.push_str(&format!("        total = total + {};\n", i));
+        }
+        source.push_str("    }\n");
+    }
+    source.push_str("}\n");
+    source
+}
+
+fn best_of(mut run: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let source = generated_class(STATEMENTS);
+
+    let plain = best_of(|| {
+        black_box(parse(black_box(&source)).expect("generated source must parse"));
+    });
+    let token = CancellationToken::new();
+    let with_token = best_of(|| {
+        let mut parser = Parser::new(black_box(&source)).with_cancellation(token.clone());
+        black_box(parser.parse().expect("generated source must parse"));
+    });
+
+    let overhead = with_token.as_secs_f64() / plain.as_secs_f64() - 1.0;
+    println!(
+        "no token {:.2} ms, idle token {:.2} ms, overhead {:+.1}%",
+        plain.as_secs_f64() * 1e3,
+        with_token.as_secs_f64() * 1e3,
+        overhead * 100.0
+    );
+    assert!(
+        overhead < MAX_OVERHEAD,
+        "cancellation checks cost {:.1}% of parse time",
+        overhead * 100.0
+    );
+}
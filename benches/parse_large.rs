@@ -0,0 +1,52 @@
+//! Parse-time regression benchmark for machine-generated Apex
+//!
+//! Run with `cargo bench --bench parse_large`. Prints the time to parse one
+//! method of N statements for growing N; the per-statement cost should stay
+//! flat as N grows.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use apexrust::parse;
+
+const SIZES: [usize; 3] = [10_000, 20_000, 30_000];
+const RUNS: u32 = 5;
+
+/// One method with `statements` assignment statements
+fn generated_class(statements: usize) -> String {
+    let mut source = String::from("public class Generated {\n    public void run() {\n");
+    source.push_str("        Integer total = 0;\n");
+    for i in 0..statements {
+        source.push_str(&format!("        total = total + {};\n", i));
+    }
+    source.push_str("    }\n}\n");
+    source
+}
+
+fn best_of(source: &str) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(parse(black_box(source)).expect("generated source must parse"));
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    println!(
+        "{:>10} {:>12} {:>14}",
+        "statements", "best (ms)", "ns/statement"
+    );
+    for statements in SIZES {
+        let source = generated_class(statements);
+        let elapsed = best_of(&source);
+        println!(
+            "{:>10} {:>12.2} {:>14.0}",
+            statements,
+            elapsed.as_secs_f64() * 1e3,
+            elapsed.as_nanos() as f64 / statements as f64
+        );
+    }
+}
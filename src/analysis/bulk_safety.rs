@@ -0,0 +1,547 @@
+//! Queries and DML that run once per loop iteration
+//!
+//! Apex governor limits cap a transaction at 100 queries and 150 DML
+//! statements, so a query inside a loop over trigger records fails as soon
+//! as a bulk load sends 200 of them. [`find_loop_operations`] reports every
+//! query and DML statement inside a loop body, and every call from a loop
+//! body to a method of the same unit that runs one, following calls up to
+//! [`BulkSafetyOptions::call_depth`] deep.
+//!
+//! For the canonical offender, a query filtered on a field of the loop
+//! variable,
+//!
+//! ```apex
+//! for (Contact c : contacts) {
+//!     Account acc = [SELECT Id, Name FROM Account WHERE Id = :c.AccountId];
+//! }
+//! ```
+//!
+//! the diagnostic carries a [`BulkificationSuggestion`]: collect the keys
+//! into a set before the loop, query once with `IN`, and index the results
+//! by the key:
+//!
+//! ```apex
+//! Set<Id> accountIds = new Set<Id>();
+//! for (Contact c : contacts) {
+//!     accountIds.add(c.AccountId);
+//! }
+//! Map<Id, Account> accountsById = new Map<Id, Account>([SELECT Id, Name FROM Account WHERE Id IN :accountIds]);
+//! ```
+//!
+//! after which the loop reads `accountsById.get(c.AccountId)`. Suggestions
+//! are never applied; they are for editors to offer as quick fixes.
+
+use super::Parent;
+use crate::ast::printer;
+use crate::ast::visitor::{walk_block, Visitor};
+use crate::ast::{
+    BinaryExpr, BinaryOp, Block, ClassDeclaration, ClassMember, CompilationUnit, Expression,
+    ForEachStatement, MethodCallExpr, NodeId, SelectField, SoqlQuery, Statement, TypeDeclaration,
+};
+use crate::lexer::Span;
+
+/// How far [`find_loop_operations`] looks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkSafetyOptions {
+    /// Calls to follow from a loop body into methods of the same unit; 0
+    /// reports only queries and DML written in the loop itself
+    pub call_depth: usize,
+}
+
+impl Default for BulkSafetyOptions {
+    fn default() -> Self {
+        Self { call_depth: 1 }
+    }
+}
+
+/// What runs once per iteration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopOperation {
+    /// Inline SOQL or SOSL, or `Database.query()` and the like
+    Query,
+    /// A DML statement or `Database.insert()` and the like
+    Dml,
+}
+
+/// A query or DML statement that runs once per loop iteration
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkSafetyDiagnostic {
+    /// Class or trigger name, dotted for inner classes
+    pub class: String,
+    /// Enclosing method, constructor or property; `static` for static blocks
+    pub method: String,
+    pub operation: LoopOperation,
+    /// Methods called on the way to the operation, outermost first; empty
+    /// when the operation is in the loop itself
+    pub call_chain: Vec<String>,
+    pub message: String,
+    /// The operation, or the call leading to it
+    pub span: Span,
+    /// The innermost loop whose body contains `span`
+    pub loop_span: Span,
+    pub suggestion: Option<BulkificationSuggestion>,
+}
+
+/// Rewrite of a per-iteration query into one query before the loop
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkificationSuggestion {
+    /// Set the keys are collected into, such as `accountIds`
+    pub key_set: String,
+    /// Map the results are indexed into, such as `accountsById`
+    pub result_map: String,
+    /// Statements collecting the keys, to go before the loop
+    pub collect: String,
+    /// The single query, filtering with `IN` on the key set
+    pub query: String,
+    /// Statements running `query` and filling the map, to follow `collect`
+    pub index: String,
+    /// What the loop uses instead of the query: the record when keyed by
+    /// Id, otherwise the list of matching records
+    pub lookup: String,
+}
+
+/// Queries and DML statements run once per loop iteration in `unit`
+pub fn find_loop_operations(
+    unit: &CompilationUnit,
+    options: &BulkSafetyOptions,
+) -> Vec<BulkSafetyDiagnostic> {
+    let graph = CallGraph::new(unit);
+    let mut diagnostics = Vec::new();
+    for body in &graph.bodies {
+        for site in &body.sites {
+            let Some(looped) = site.looped else {
+                continue;
+            };
+            let (operation, call_chain, span, suggestion) = match site.kind {
+                SiteKind::Operation(operation, span, query) => {
+                    let suggestion = match (looped, query) {
+                        (Statement::ForEach(each), Some(query)) => suggest(each, query),
+                        _ => None,
+                    };
+                    (operation, Vec::new(), span, suggestion)
+                }
+                SiteKind::Call(call) if options.call_depth > 0 => {
+                    let Some(callee) = graph.resolve(&body.class, call) else {
+                        continue;
+                    };
+                    let Some((operation, mut chain)) =
+                        graph.reaches(callee, options.call_depth - 1)
+                    else {
+                        continue;
+                    };
+                    chain.insert(0, graph.bodies[callee].method.clone());
+                    (operation, chain, call.span, None)
+                }
+                SiteKind::Call(_) => continue,
+            };
+
+            let what = match operation {
+                LoopOperation::Query => "query",
+                LoopOperation::Dml => "DML statement",
+            };
+            let message = if call_chain.is_empty() {
+                format!("{} inside a loop runs once per iteration", what)
+            } else {
+                format!(
+                    "call to {}() inside a loop runs a {} once per iteration",
+                    call_chain.join("() -> "),
+                    what
+                )
+            };
+            diagnostics.push(BulkSafetyDiagnostic {
+                class: body.class.clone(),
+                method: body.method.clone(),
+                operation,
+                call_chain,
+                message,
+                span,
+                loop_span: looped.span(),
+                suggestion,
+            });
+        }
+    }
+    diagnostics.sort_by_key(|d| d.span.start);
+    diagnostics
+}
+
+/// A body of code, its queries, DML and calls
+struct Body<'a> {
+    class: String,
+    method: String,
+    /// Argument count when the body is a method callable by name
+    arity: Option<usize>,
+    sites: Vec<Site<'a>>,
+}
+
+struct Site<'a> {
+    kind: SiteKind<'a>,
+    /// Innermost loop whose body contains the site
+    looped: Option<&'a Statement>,
+}
+
+#[derive(Clone, Copy)]
+enum SiteKind<'a> {
+    /// A query or DML, with the inline query when there is one
+    Operation(LoopOperation, Span, Option<&'a SoqlQuery>),
+    Call(&'a MethodCallExpr),
+}
+
+/// Bodies in a unit, with calls resolved between them by name and arity
+struct CallGraph<'a> {
+    bodies: Vec<Body<'a>>,
+    /// Simple class name and dotted name of every class in the unit
+    classes: Vec<(String, String)>,
+}
+
+impl<'a> CallGraph<'a> {
+    fn new(unit: &'a CompilationUnit) -> Self {
+        let mut graph = Self {
+            bodies: Vec::new(),
+            classes: Vec::new(),
+        };
+        for declaration in &unit.declarations {
+            match declaration {
+                TypeDeclaration::Class(class) => graph.add_class(class, &class.name),
+                TypeDeclaration::Trigger(trigger) => graph.bodies.push(Body {
+                    class: trigger.name.clone(),
+                    method: trigger.name.clone(),
+                    arity: None,
+                    sites: sites(&trigger.body),
+                }),
+                _ => {}
+            }
+        }
+        graph
+    }
+
+    fn add_class(&mut self, class: &'a ClassDeclaration, name: &str) {
+        self.classes.push((class.name.clone(), name.to_string()));
+        let mut push = |method: &str, arity: Option<usize>, block: &'a Block| {
+            self.bodies.push(Body {
+                class: name.to_string(),
+                method: method.to_string(),
+                arity,
+                sites: sites(block),
+            })
+        };
+        for member in &class.members {
+            match member {
+                ClassMember::Method(method) => {
+                    if let Some(body) = &method.body {
+                        push(&method.name, Some(method.parameters.len()), body);
+                    }
+                }
+                ClassMember::Constructor(ctor) => push(&ctor.name, None, &ctor.body),
+                ClassMember::Property(prop) => {
+                    for accessor in [&prop.getter, &prop.setter].into_iter().flatten() {
+                        if let Some(body) = &accessor.body {
+                            push(&prop.name, None, body);
+                        }
+                    }
+                }
+                ClassMember::StaticBlock(block) => push("static", None, block),
+                _ => {}
+            }
+        }
+        for member in &class.members {
+            if let ClassMember::InnerClass(inner) = member {
+                self.add_class(inner, &format!("{}.{}", name, inner.name));
+            }
+        }
+    }
+
+    /// The method a call made from `class` runs: one of the class's own for
+    /// an unqualified or `this.` call, or a static of another class in the
+    /// unit called through its name
+    fn resolve(&self, class: &str, call: &MethodCallExpr) -> Option<usize> {
+        let target = match call.object {
+            None | Some(Expression::This(..)) => class,
+            Some(Expression::Identifier(ref name, _, _)) => self
+                .classes
+                .iter()
+                .find(|(simple, _)| simple.eq_ignore_ascii_case(name))
+                .map(|(_, dotted)| dotted.as_str())?,
+            _ => return None,
+        };
+        self.bodies.iter().position(|body| {
+            body.class == target
+                && body.method.eq_ignore_ascii_case(&call.name)
+                && body.arity == Some(call.arguments.len())
+        })
+    }
+
+    /// The first operation body `index` runs, directly or through up to
+    /// `depth` further calls, with the methods called on the way
+    fn reaches(&self, index: usize, depth: usize) -> Option<(LoopOperation, Vec<String>)> {
+        let body = &self.bodies[index];
+        let direct = body.sites.iter().find_map(|site| match site.kind {
+            SiteKind::Operation(operation, ..) => Some(operation),
+            SiteKind::Call(_) => None,
+        });
+        if let Some(operation) = direct {
+            return Some((operation, Vec::new()));
+        }
+        if depth == 0 {
+            return None;
+        }
+        body.sites.iter().find_map(|site| {
+            let SiteKind::Call(call) = site.kind else {
+                return None;
+            };
+            let callee = self.resolve(&body.class, call)?;
+            let (operation, mut chain) = self.reaches(callee, depth - 1)?;
+            chain.insert(0, self.bodies[callee].method.clone());
+            Some((operation, chain))
+        })
+    }
+}
+
+/// Queries, DML and calls in a block, each with its innermost loop
+fn sites(block: &Block) -> Vec<Site<'_>> {
+    #[derive(Default)]
+    struct Sites<'a> {
+        parents: Vec<Parent<'a>>,
+        sites: Vec<Site<'a>>,
+    }
+
+    impl<'a> Visitor<'a> for Sites<'a> {
+        fn visit_statement(&mut self, stmt: &'a Statement) {
+            self.parents.push(Parent::Statement(stmt));
+        }
+
+        fn leave_statement(&mut self, _: &'a Statement) {
+            self.parents.pop();
+        }
+
+        fn visit_expression(&mut self, expr: &'a Expression) {
+            if let Some(kind) = site_kind(expr, self.parents.last()) {
+                let span = expr.span();
+                let looped = self.parents.iter().rev().find_map(|parent| match parent {
+                    Parent::Statement(stmt) => loop_body(stmt)
+                        .filter(|body| contains(body.span(), span))
+                        .map(|_| *stmt),
+                    Parent::Expression(_) => None,
+                });
+                self.sites.push(Site { kind, looped });
+            }
+            self.parents.push(Parent::Expression(expr));
+        }
+
+        fn leave_expression(&mut self, _: &'a Expression) {
+            self.parents.pop();
+        }
+    }
+
+    let mut sites = Sites::default();
+    walk_block(&mut sites, block);
+    sites.sites
+}
+
+/// What `expr` runs, given its innermost enclosing node
+fn site_kind<'a>(expr: &'a Expression, parent: Option<&Parent<'a>>) -> Option<SiteKind<'a>> {
+    let kind = match (expr, parent) {
+        (_, Some(Parent::Statement(Statement::Dml(dml))))
+            if std::ptr::eq(&dml.expression, expr) =>
+        {
+            SiteKind::Operation(LoopOperation::Dml, dml.span, None)
+        }
+        (Expression::Soql(query), _) => {
+            SiteKind::Operation(LoopOperation::Query, query.span, Some(query.as_ref()))
+        }
+        (Expression::Sosl(query), _) => SiteKind::Operation(LoopOperation::Query, query.span, None),
+        (Expression::MethodCall(call), _) => match database_operation(call) {
+            Some(operation) => SiteKind::Operation(operation, call.span, None),
+            None => SiteKind::Call(call),
+        },
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// `Database.query()`, `Database.insert()` and the like
+fn database_operation(call: &MethodCallExpr) -> Option<LoopOperation> {
+    match call.object {
+        Some(Expression::Identifier(ref name, _, _)) if name.eq_ignore_ascii_case("Database") => {}
+        _ => return None,
+    }
+    match call.name.to_lowercase().as_str() {
+        "query" | "querywithbinds" | "countquery" | "getquerylocator" => Some(LoopOperation::Query),
+        "insert" | "update" | "upsert" | "delete" | "undelete" | "merge" | "convertlead" => {
+            Some(LoopOperation::Dml)
+        }
+        _ => None,
+    }
+}
+
+fn loop_body(stmt: &Statement) -> Option<&Statement> {
+    match stmt {
+        Statement::For(s) => Some(&s.body),
+        Statement::ForEach(s) => Some(&s.body),
+        Statement::While(s) => Some(&s.body),
+        Statement::DoWhile(s) => Some(&s.body),
+        _ => None,
+    }
+}
+
+fn contains(outer: Span, inner: Span) -> bool {
+    outer.start <= inner.start && inner.start < outer.end
+}
+
+/// The collected-keys rewrite of `query`, when it is filtered by a field of
+/// the loop variable with `Field = :x.Key` and nothing else depends on the
+/// iteration
+fn suggest(each: &ForEachStatement, query: &SoqlQuery) -> Option<BulkificationSuggestion> {
+    let plain_fields = query
+        .select_clause
+        .iter()
+        .all(|field| matches!(field, SelectField::Field(_)));
+    if !plain_fields
+        || !query.group_by_clause.is_empty()
+        || query.limit_clause.is_some()
+        || query.offset_clause.is_some()
+    {
+        return None;
+    }
+
+    let var = &each.variable;
+    let mut conjuncts = Vec::new();
+    flatten_and(query.where_clause.as_ref()?, &mut conjuncts);
+    let keyed: Vec<(&str, &str)> = conjuncts
+        .iter()
+        .filter_map(|conjunct| loop_key_filter(conjunct, var))
+        .collect();
+    let [(field, key)] = keyed[..] else {
+        return None;
+    };
+    let other_binds = conjuncts
+        .iter()
+        .filter(|conjunct| loop_key_filter(conjunct, var).is_none())
+        .any(|conjunct| binds_loop_variable(conjunct, var));
+    if other_binds {
+        return None;
+    }
+
+    let iterable = printer::expression(&each.iterable);
+    if iterable.contains('?') {
+        return None;
+    }
+    let key_type = if field.to_lowercase().ends_with("id") {
+        "Id"
+    } else {
+        "String"
+    };
+    let object = &query.from_clause;
+    let key_set = format!("{}s", lower_first(key));
+    let by_id = field.eq_ignore_ascii_case("Id");
+    let result_map = format!("{}sBy{}", lower_first(object), field);
+
+    let mut rewritten = query.clone();
+    if let Some(ref mut filter) = rewritten.where_clause {
+        replace_key_filter(filter, var, &key_set);
+    }
+    let selected = rewritten.select_clause.iter().any(
+        |selected| matches!(selected, SelectField::Field(name) if name.eq_ignore_ascii_case(field)),
+    );
+    if !by_id && !selected {
+        rewritten
+            .select_clause
+            .push(SelectField::Field(field.to_string()));
+    }
+    let replacement = format!("[{}]", printer::soql(&rewritten));
+
+    let collect = format!(
+        "Set<{key_type}> {key_set} = new Set<{key_type}>();\n\
+         for ({ty} {var} : {iterable}) {{\n    {key_set}.add({var}.{key});\n}}",
+        ty = printer::type_ref(&each.type_ref),
+    );
+    let index = if by_id {
+        format!("Map<Id, {object}> {result_map} = new Map<Id, {object}>({replacement});")
+    } else {
+        format!(
+            "Map<{key_type}, List<{object}>> {result_map} = new Map<{key_type}, List<{object}>>();\n\
+             for ({object} record : {replacement}) {{\n    \
+             if (!{result_map}.containsKey(record.{field})) {{\n        \
+             {result_map}.put(record.{field}, new List<{object}>());\n    }}\n    \
+             {result_map}.get(record.{field}).add(record);\n}}"
+        )
+    };
+
+    Some(BulkificationSuggestion {
+        lookup: format!("{}.get({}.{})", result_map, var, key),
+        key_set,
+        result_map,
+        collect,
+        query: replacement,
+        index,
+    })
+}
+
+fn flatten_and<'a>(expr: &'a Expression, conjuncts: &mut Vec<&'a Expression>) {
+    match expr {
+        Expression::Binary(binary) if binary.operator == BinaryOp::And => {
+            flatten_and(&binary.left, conjuncts);
+            flatten_and(&binary.right, conjuncts);
+        }
+        _ => conjuncts.push(expr),
+    }
+}
+
+/// `(Field, Key)` for a `Field = :var.Key` filter on a field of the queried
+/// object
+fn loop_key_filter<'e>(expr: &'e Expression, var: &str) -> Option<(&'e str, &'e str)> {
+    let Expression::Binary(binary) = expr else {
+        return None;
+    };
+    let (Expression::Identifier(field, _, _), Expression::BindVariable(bind, _, _)) =
+        (&binary.left, &binary.right)
+    else {
+        return None;
+    };
+    let (root, key) = bind.split_once('.')?;
+    let simple = !field.contains('.') && !key.contains('.');
+    (binary.operator == BinaryOp::Equal && simple && root.eq_ignore_ascii_case(var))
+        .then_some((field.as_str(), key))
+}
+
+fn binds_loop_variable(expr: &Expression, var: &str) -> bool {
+    match expr {
+        Expression::BindVariable(bind, _, _) => bind
+            .split('.')
+            .next()
+            .is_some_and(|root| root.eq_ignore_ascii_case(var)),
+        Expression::Binary(binary) => {
+            binds_loop_variable(&binary.left, var) || binds_loop_variable(&binary.right, var)
+        }
+        Expression::Unary(unary) => binds_loop_variable(&unary.operand, var),
+        Expression::Parenthesized(inner, _, _) => binds_loop_variable(inner, var),
+        _ => false,
+    }
+}
+
+/// Turn the `Field = :var.Key` conjunct into `Field IN :keySet`
+fn replace_key_filter(expr: &mut Expression, var: &str, key_set: &str) {
+    if let Some((field, _)) = loop_key_filter(expr, var) {
+        *expr = Expression::Binary(Box::new(BinaryExpr {
+            left: Expression::Identifier(field.to_string(), expr.span(), NodeId::DUMMY),
+            operator: BinaryOp::In,
+            right: Expression::BindVariable(key_set.to_string(), expr.span(), NodeId::DUMMY),
+            span: expr.span(),
+            id: expr.id(),
+        }));
+        return;
+    }
+    if let Expression::Binary(binary) = expr {
+        if binary.operator == BinaryOp::And {
+            replace_key_filter(&mut binary.left, var, key_set);
+            replace_key_filter(&mut binary.right, var, key_set);
+        }
+    }
+}
+
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,569 @@
+//! What data a unit mutates, and what guards each mutation
+//!
+//! For change review and compliance, [`analyze`] lists every DML statement
+//! and `Database.insert()`-style call with the object it writes and the
+//! guards around it:
+//!
+//! - whether an object permission check for the operation, such as
+//!   `Schema.sObjectType.Account.isUpdateable()`, dominates the site in its
+//!   method (see [`dominance`](super::dominance))
+//! - whether the DML runs in user mode (`update as user`,
+//!   `AccessLevel.USER_MODE`) or writes records read `WITH USER_MODE`
+//! - whether it sits inside an `if` or `switch`, so a delete outside any
+//!   condition stands out
+//! - the sharing declared by the enclosing class
+//!
+//! The target object comes from the declared type of the operand, looking
+//! through `List<>` and arrays, so it is best effort: `update records;` on a
+//! `List<SObject>` or on a method's return value is [`DmlTarget::Unknown`].
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use super::dominance::{contains, dominates, Check};
+use crate::ast::visitor::{walk_block, Visitor};
+use crate::ast::{
+    Block, ClassDeclaration, ClassMember, CompilationUnit, DmlAccessLevel, DmlOperation,
+    Expression, ForInit, Parameter, SharingModifier, SoqlWithClause, Statement, TypeDeclaration,
+    TypeRef,
+};
+use crate::lexer::Span;
+use crate::sql::SalesforceSchema;
+
+/// The object a DML site writes
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum DmlTarget {
+    Object(String),
+    /// The operand's type is `SObject`, not an SObject, or not declared
+    /// anywhere the analysis can see
+    Unknown,
+}
+
+/// One DML statement or `Database` DML call
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DmlSite {
+    /// Class or trigger name, dotted for inner classes
+    pub class: String,
+    /// Enclosing method, constructor or property; `static` for static blocks
+    pub method: String,
+    pub operation: DmlOperation,
+    pub target: DmlTarget,
+    /// Whether every object permission the operation needs is checked
+    /// before the site on all paths through the method
+    pub permission_checked: bool,
+    /// Whether the DML runs in user mode or its records were read with
+    /// `WITH USER_MODE`
+    pub user_mode: bool,
+    /// Whether the site is inside an `if` or `switch`
+    pub conditional: bool,
+    /// Sharing declared by the enclosing class; `None` for triggers and
+    /// classes that declare none
+    pub sharing: Option<SharingModifier>,
+    pub line: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub span: Span,
+}
+
+/// Every DML site in a compilation unit, in source order
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DmlReport {
+    pub sites: Vec<DmlSite>,
+}
+
+impl DmlReport {
+    /// Objects the unit writes with `operation`
+    pub fn objects(&self, operation: DmlOperation) -> BTreeSet<&str> {
+        self.sites
+            .iter()
+            .filter(|site| site.operation == operation)
+            .filter_map(|site| match &site.target {
+                DmlTarget::Object(name) => Some(name.as_str()),
+                DmlTarget::Unknown => None,
+            })
+            .collect()
+    }
+
+    /// Deletes that run whenever their method does
+    pub fn unconditional_deletes(&self) -> impl Iterator<Item = &DmlSite> {
+        self.sites
+            .iter()
+            .filter(|site| site.operation == DmlOperation::Delete && !site.conditional)
+    }
+
+    /// Sites with neither a permission check nor user mode
+    pub fn unguarded(&self) -> impl Iterator<Item = &DmlSite> {
+        self.sites
+            .iter()
+            .filter(|site| !site.permission_checked && !site.user_mode)
+    }
+}
+
+/// One line per site:
+/// `AccountService.archive line 12: delete Account, unchecked, unconditional, with sharing`
+impl fmt::Display for DmlReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for site in &self.sites {
+            let target = match &site.target {
+                DmlTarget::Object(name) => name.as_str(),
+                DmlTarget::Unknown => "unknown object",
+            };
+            write!(
+                f,
+                "{}.{} line {}: {} {}",
+                site.class,
+                site.method,
+                site.line,
+                keyword(site.operation),
+                target
+            )?;
+            f.write_str(if site.permission_checked {
+                ", permission checked"
+            } else {
+                ", unchecked"
+            })?;
+            if site.user_mode {
+                f.write_str(", user mode")?;
+            }
+            f.write_str(if site.conditional {
+                ", conditional"
+            } else {
+                ", unconditional"
+            })?;
+            match site.sharing {
+                Some(SharingModifier::WithSharing) => f.write_str(", with sharing")?,
+                Some(SharingModifier::WithoutSharing) => f.write_str(", without sharing")?,
+                Some(SharingModifier::InheritedSharing) => f.write_str(", inherited sharing")?,
+                None => {}
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn keyword(operation: DmlOperation) -> &'static str {
+    match operation {
+        DmlOperation::Insert => "insert",
+        DmlOperation::Update => "update",
+        DmlOperation::Upsert => "upsert",
+        DmlOperation::Delete => "delete",
+        DmlOperation::Undelete => "undelete",
+        DmlOperation::Merge => "merge",
+    }
+}
+
+/// List the DML sites in `unit`; with a schema, targets are reported by
+/// their API name and types the schema does not know are
+/// [`DmlTarget::Unknown`]
+pub fn analyze(unit: &CompilationUnit, schema: Option<&SalesforceSchema>) -> DmlReport {
+    let mut report = DmlReport::default();
+    for declaration in &unit.declarations {
+        match declaration {
+            TypeDeclaration::Class(class) => {
+                analyze_class(class, &class.name, &HashMap::new(), schema, &mut report)
+            }
+            TypeDeclaration::Trigger(trigger) => Body {
+                class: &trigger.name,
+                method: &trigger.name,
+                sharing: None,
+                trigger_object: Some(&trigger.object),
+                schema,
+                types: HashMap::new(),
+            }
+            .analyze(&trigger.body, &[], &mut report),
+            _ => {}
+        }
+    }
+    report.sites.sort_by_key(|site| site.span.start);
+    report
+}
+
+fn analyze_class<'a>(
+    class: &'a ClassDeclaration,
+    name: &str,
+    outer_fields: &HashMap<String, &'a TypeRef>,
+    schema: Option<&SalesforceSchema>,
+    report: &mut DmlReport,
+) {
+    let mut fields = outer_fields.clone();
+    for member in &class.members {
+        match member {
+            ClassMember::Field(field) => {
+                for declarator in &field.declarators {
+                    fields.insert(declarator.name.to_lowercase(), &field.type_ref);
+                }
+            }
+            ClassMember::Property(prop) => {
+                fields.insert(prop.name.to_lowercase(), &prop.type_ref);
+            }
+            _ => {}
+        }
+    }
+
+    let body = |method: &'a str| Body {
+        class: name,
+        method,
+        sharing: class.modifiers.sharing,
+        trigger_object: None,
+        schema,
+        types: fields.clone(),
+    };
+    for member in &class.members {
+        match member {
+            ClassMember::Method(method) => {
+                if let Some(block) = &method.body {
+                    body(&method.name).analyze(block, &method.parameters, report);
+                }
+            }
+            ClassMember::Constructor(ctor) => {
+                body(&ctor.name).analyze(&ctor.body, &ctor.parameters, report)
+            }
+            ClassMember::Property(prop) => {
+                for accessor in [&prop.getter, &prop.setter].into_iter().flatten() {
+                    if let Some(block) = &accessor.body {
+                        body(&prop.name).analyze(block, &[], report);
+                    }
+                }
+            }
+            ClassMember::StaticBlock(block) => body("static").analyze(block, &[], report),
+            _ => {}
+        }
+    }
+
+    for member in &class.members {
+        if let ClassMember::InnerClass(inner) = member {
+            let name = format!("{}.{}", name, inner.name);
+            analyze_class(inner, &name, &fields, schema, report);
+        }
+    }
+}
+
+/// A method body and what it can see
+struct Body<'a> {
+    class: &'a str,
+    method: &'a str,
+    sharing: Option<SharingModifier>,
+    /// The object of the trigger the body belongs to
+    trigger_object: Option<&'a str>,
+    schema: Option<&'a SalesforceSchema>,
+    /// Declared types of fields, parameters and locals, by lowercased name
+    types: HashMap<String, &'a TypeRef>,
+}
+
+/// A DML operation, its records, whether it runs in user mode, and where
+type Site<'a> = (DmlOperation, &'a Expression, bool, Span);
+
+/// What analyzing a body needs from its statements and expressions
+struct Scan<'a, 't> {
+    types: &'t mut HashMap<String, &'a TypeRef>,
+    /// Initializers of and assignments to locals, by lowercased name
+    initializers: HashMap<String, Vec<&'a Expression>>,
+    /// Spans of conditionally run branches
+    conditions: Vec<Span>,
+    /// DML statements
+    statements: Vec<Site<'a>>,
+    /// `Database` DML calls
+    calls: Vec<Site<'a>>,
+}
+
+impl<'a> Visitor<'a> for Scan<'a, '_> {
+    fn visit_statement(&mut self, stmt: &'a Statement) {
+        match stmt {
+            Statement::LocalVariable(var) => {
+                for declarator in &var.declarators {
+                    let name = declarator.name.to_lowercase();
+                    self.types.insert(name.clone(), &var.type_ref);
+                    if let Some(init) = &declarator.initializer {
+                        self.initializers.entry(name).or_default().push(init);
+                    }
+                }
+            }
+            Statement::For(s) => {
+                if let Some(ForInit::Variables(var)) = &s.init {
+                    for declarator in &var.declarators {
+                        self.types
+                            .insert(declarator.name.to_lowercase(), &var.type_ref);
+                    }
+                }
+            }
+            Statement::ForEach(s) => {
+                self.types.insert(s.variable.to_lowercase(), &s.type_ref);
+            }
+            Statement::If(s) => {
+                self.conditions.push(s.then_branch.span());
+                self.conditions
+                    .extend(s.else_branch.as_ref().map(|e| e.span()));
+            }
+            Statement::Switch(s) => self
+                .conditions
+                .extend(s.when_clauses.iter().map(|w| w.span)),
+            Statement::Dml(dml) => {
+                let as_user = dml.access_level == Some(DmlAccessLevel::User);
+                self.statements
+                    .push((dml.operation, &dml.expression, as_user, dml.span));
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_expression(&mut self, expr: &'a Expression) {
+        if let Expression::Assignment(assign) = expr {
+            if let Expression::Identifier(name, _, _) = &assign.target {
+                self.initializers
+                    .entry(name.to_lowercase())
+                    .or_default()
+                    .push(&assign.value);
+            }
+        }
+        let Expression::MethodCall(call) = expr else {
+            return;
+        };
+        let on_database = matches!(
+            &call.object,
+            Some(Expression::Identifier(name, _, _)) if name.eq_ignore_ascii_case("Database")
+        );
+        let operation = match call.name.to_lowercase().as_str() {
+            "insert" => DmlOperation::Insert,
+            "update" => DmlOperation::Update,
+            "upsert" => DmlOperation::Upsert,
+            "delete" => DmlOperation::Delete,
+            "undelete" => DmlOperation::Undelete,
+            "merge" => DmlOperation::Merge,
+            _ => return,
+        };
+        if let (true, Some(operand)) = (on_database, call.arguments.first()) {
+            let as_user = call.arguments.iter().any(is_user_mode_level);
+            self.calls.push((operation, operand, as_user, call.span));
+        }
+    }
+}
+
+impl<'a> Body<'a> {
+    fn analyze(mut self, block: &'a Block, parameters: &'a [Parameter], report: &mut DmlReport) {
+        for parameter in parameters {
+            self.types
+                .insert(parameter.name.to_lowercase(), &parameter.type_ref);
+        }
+        let mut scan = Scan {
+            types: &mut self.types,
+            initializers: HashMap::new(),
+            conditions: Vec::new(),
+            statements: Vec::new(),
+            calls: Vec::new(),
+        };
+        walk_block(&mut scan, block);
+        let Scan {
+            initializers,
+            conditions,
+            statements,
+            calls,
+            ..
+        } = scan;
+        let sites = statements.into_iter().chain(calls);
+
+        let user_mode_records: HashSet<String> = initializers
+            .iter()
+            .filter(|(_, values)| values.iter().all(|value| reads_in_user_mode(value)))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        for (operation, operand, as_user, span) in sites {
+            let target = self.target(operand);
+            let permission_checked = permissions(operation).iter().all(|&permission| {
+                let check = PermissionCheck {
+                    permission,
+                    object: match &target {
+                        DmlTarget::Object(name) => Some(name),
+                        DmlTarget::Unknown => None,
+                    },
+                    initializers: &initializers,
+                };
+                dominates(block, span, &check)
+            });
+            let user_mode = as_user
+                || reads_in_user_mode(operand)
+                || matches!(operand, Expression::Identifier(name, _, _) if user_mode_records.contains(&name.to_lowercase()));
+            report.sites.push(DmlSite {
+                class: self.class.to_string(),
+                method: self.method.to_string(),
+                operation,
+                target,
+                permission_checked,
+                user_mode,
+                conditional: conditions.iter().any(|&c| contains(c, span)),
+                sharing: self.sharing,
+                line: span.line,
+                span,
+            });
+        }
+    }
+
+    /// The object `operand` holds records of
+    fn target(&self, operand: &Expression) -> DmlTarget {
+        let name = match self.record_type(operand) {
+            Some(name) if !name.eq_ignore_ascii_case("SObject") => name,
+            _ => return DmlTarget::Unknown,
+        };
+        match self.schema {
+            Some(schema) => match schema.get_object(&name) {
+                Some(object) => DmlTarget::Object(object.name.clone()),
+                None => DmlTarget::Unknown,
+            },
+            None => DmlTarget::Object(name),
+        }
+    }
+
+    fn record_type(&self, expr: &Expression) -> Option<String> {
+        match expr {
+            Expression::Identifier(name, _, _) => self.declared(name),
+            Expression::FieldAccess(access) => match &access.object {
+                Expression::This(..) => self.declared(&access.field),
+                // `Trigger.new`, `Trigger.old`
+                Expression::Identifier(name, _, _) if name.eq_ignore_ascii_case("Trigger") => {
+                    self.trigger_object.map(str::to_string)
+                }
+                _ => None,
+            },
+            Expression::New(new) => element_type(&new.type_ref),
+            Expression::NewArray(new) => Some(new.element_type.name.clone()),
+            Expression::Cast(cast) => element_type(&cast.type_ref),
+            Expression::Parenthesized(inner, _, _) => self.record_type(inner),
+            Expression::ArrayAccess(access) => self.record_type(&access.array),
+            Expression::ListLiteral(items, ..) => self.record_type(items.first()?),
+            Expression::Soql(query) => Some(query.from_clause.clone()),
+            // `accountsById.values()`, `Trigger.newMap.values()`
+            Expression::MethodCall(call) if call.name.eq_ignore_ascii_case("values") => {
+                match call.object.as_ref()? {
+                    Expression::Identifier(name, _, _) => {
+                        let map = self.types.get(&name.to_lowercase())?;
+                        map.type_arguments.get(1).map(|value| value.name.clone())
+                    }
+                    object => self.record_type(object),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn declared(&self, name: &str) -> Option<String> {
+        element_type(self.types.get(&name.to_lowercase())?)
+    }
+}
+
+/// The record type of a variable of type `type_ref`
+fn element_type(type_ref: &TypeRef) -> Option<String> {
+    if type_ref.is_array {
+        return Some(type_ref.name.clone());
+    }
+    match type_ref.name.to_lowercase().as_str() {
+        "list" | "set" => type_ref.type_arguments.first().map(|t| t.name.clone()),
+        "map" => None,
+        _ => Some(type_ref.name.clone()),
+    }
+}
+
+/// Describe methods that must pass before `operation`
+fn permissions(operation: DmlOperation) -> &'static [&'static str] {
+    match operation {
+        DmlOperation::Insert => &["iscreateable"],
+        DmlOperation::Update => &["isupdateable"],
+        DmlOperation::Upsert => &["iscreateable", "isupdateable"],
+        DmlOperation::Delete => &["isdeletable"],
+        DmlOperation::Undelete => &["isundeletable"],
+        DmlOperation::Merge => &["isupdateable", "isdeletable"],
+    }
+}
+
+/// `AccessLevel.USER_MODE`
+fn is_user_mode_level(expr: &Expression) -> bool {
+    matches!(
+        expr,
+        Expression::FieldAccess(access)
+            if access.field.eq_ignore_ascii_case("USER_MODE")
+                && matches!(&access.object, Expression::Identifier(name, _, _) if name.eq_ignore_ascii_case("AccessLevel"))
+    )
+}
+
+fn reads_in_user_mode(expr: &Expression) -> bool {
+    matches!(expr, Expression::Soql(query) if query.with_clause == Some(SoqlWithClause::UserMode))
+}
+
+/// `describe.isUpdateable()` and the like, for one object
+struct PermissionCheck<'c, 'a> {
+    /// Lowercased describe method name
+    permission: &'static str,
+    /// The DML target; checks of any object count when it is unknown
+    object: Option<&'c str>,
+    initializers: &'c HashMap<String, Vec<&'a Expression>>,
+}
+
+impl Check for PermissionCheck<'_, '_> {
+    fn is_check(&self, expr: &Expression) -> bool {
+        let Expression::MethodCall(call) = expr else {
+            return false;
+        };
+        if !call.name.eq_ignore_ascii_case(self.permission) || !call.arguments.is_empty() {
+            return false;
+        }
+        match call.object.as_ref().and_then(|o| self.described(o)) {
+            Some(Some(described)) => self
+                .object
+                .is_none_or(|object| object.eq_ignore_ascii_case(&described)),
+            Some(None) => true,
+            None => false,
+        }
+    }
+}
+
+impl PermissionCheck<'_, '_> {
+    /// For an object describe, the object when it can be named:
+    /// `Schema.sObjectType.Account`, `Account.sObjectType.getDescribe()`,
+    /// `Schema.getGlobalDescribe().get('Account').getDescribe()`, or a local
+    /// holding one of those
+    fn described(&self, expr: &Expression) -> Option<Option<String>> {
+        match expr {
+            Expression::FieldAccess(access) => {
+                let Expression::FieldAccess(parent) = &access.object else {
+                    return None;
+                };
+                parent
+                    .field
+                    .eq_ignore_ascii_case("sObjectType")
+                    .then(|| Some(access.field.clone()))
+            }
+            Expression::MethodCall(call) if call.name.eq_ignore_ascii_case("getDescribe") => {
+                match call.object.as_ref() {
+                    Some(Expression::FieldAccess(access))
+                        if access.field.eq_ignore_ascii_case("sObjectType") =>
+                    {
+                        Some(match &access.object {
+                            Expression::Identifier(name, _, _) => Some(name.clone()),
+                            _ => None,
+                        })
+                    }
+                    Some(Expression::MethodCall(get)) if get.name.eq_ignore_ascii_case("get") => {
+                        Some(match get.arguments.first() {
+                            Some(Expression::String(name, _, _)) => Some(name.clone()),
+                            _ => None,
+                        })
+                    }
+                    _ => Some(None),
+                }
+            }
+            Expression::Identifier(name, _, _) => match self.initializers.get(&name.to_lowercase())
+            {
+                Some(values) if values.len() == 1 => self.described(values[0]),
+                _ => None,
+            },
+            Expression::Parenthesized(inner, _, _) => self.described(inner),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Whether a check runs before a statement on every path through a method,
+//! judged by statement order: a guard clause or assertion earlier in an
+//! enclosing block, or the condition of an enclosing `if`. Used by the
+//! dynamic-object validation in `dynamic_soql` and by the DML report.
+
+use crate::ast::{BinaryOp, Block, Expression, Statement, UnaryOp};
+use crate::lexer::Span;
+
+/// An expression test that is true only when the property being checked
+/// holds
+pub(crate) trait Check {
+    fn is_check(&self, expr: &Expression) -> bool;
+
+    /// True whenever the expression is: a check, possibly `&&`-ed with more
+    fn passes(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::Parenthesized(inner, _, _) => self.passes(inner),
+            Expression::Binary(binary) if binary.operator == BinaryOp::And => {
+                self.passes(&binary.left) || self.passes(&binary.right)
+            }
+            _ => self.is_check(expr),
+        }
+    }
+
+    /// False whenever the check fails: `!check`, `check == false`, or either
+    /// side of an `||` being such
+    fn fails(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::Parenthesized(inner, _, _) => self.fails(inner),
+            Expression::Unary(unary) if unary.operator == UnaryOp::Not => {
+                self.passes(&unary.operand)
+            }
+            Expression::Binary(binary) => match binary.operator {
+                BinaryOp::Or => self.fails(&binary.left) || self.fails(&binary.right),
+                BinaryOp::Equal | BinaryOp::ExactEqual => {
+                    (self.passes(&binary.left)
+                        && matches!(binary.right, Expression::Boolean(false, _, _)))
+                        || (self.passes(&binary.right)
+                            && matches!(binary.left, Expression::Boolean(false, _, _)))
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// `if (!check) { throw ...; }` or `System.assert(check)`
+    fn is_guard(&self, stmt: &Statement) -> bool {
+        match stmt {
+            Statement::If(if_stmt) => {
+                if_stmt.else_branch.is_none()
+                    && exits(&if_stmt.then_branch)
+                    && self.fails(&if_stmt.condition)
+            }
+            Statement::Expression(expr) => match &expr.expression {
+                Expression::MethodCall(call) => {
+                    matches!(call.name.to_lowercase().as_str(), "assert" | "istrue")
+                        && call.arguments.first().is_some_and(|arg| self.passes(arg))
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Statement that always leaves the method or throws
+fn exits(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Throw(_) | Statement::Return(_) => true,
+        Statement::Block(block) => block.statements.last().is_some_and(exits),
+        _ => false,
+    }
+}
+
+/// Spans end at the start of the following token, so an `if` followed by
+/// `update x;` overlaps the `update`; only whole containment counts
+pub(crate) fn contains(outer: Span, inner: Span) -> bool {
+    outer.start <= inner.start && inner.end <= outer.end
+}
+
+/// Whether `check` dominates `target`, which lies in `block`
+pub(crate) fn dominates(block: &Block, target: Span, check: &dyn Check) -> bool {
+    for stmt in &block.statements {
+        if contains(stmt.span(), target) {
+            return dominates_in_statement(stmt, target, check);
+        }
+        if check.is_guard(stmt) {
+            return true;
+        }
+    }
+    false
+}
+
+fn dominates_in_statement(stmt: &Statement, target: Span, check: &dyn Check) -> bool {
+    let nested = |stmt: &Statement| dominates_in_statement(stmt, target, check);
+    match stmt {
+        Statement::Block(block) => dominates(block, target, check),
+        Statement::If(if_stmt) => {
+            if contains(if_stmt.then_branch.span(), target) {
+                check.passes(&if_stmt.condition) || nested(&if_stmt.then_branch)
+            } else if let Some(else_branch) = &if_stmt.else_branch {
+                contains(else_branch.span(), target)
+                    && (check.fails(&if_stmt.condition) || nested(else_branch))
+            } else {
+                false
+            }
+        }
+        Statement::While(while_stmt) => {
+            contains(while_stmt.body.span(), target)
+                && (check.passes(&while_stmt.condition) || nested(&while_stmt.body))
+        }
+        Statement::For(for_stmt) => {
+            contains(for_stmt.body.span(), target) && nested(&for_stmt.body)
+        }
+        Statement::ForEach(foreach) => {
+            contains(foreach.body.span(), target) && nested(&foreach.body)
+        }
+        Statement::DoWhile(do_while) => {
+            contains(do_while.body.span(), target) && nested(&do_while.body)
+        }
+        Statement::Switch(switch) => switch
+            .when_clauses
+            .iter()
+            .find(|when| contains(when.block.span, target))
+            .is_some_and(|when| dominates(&when.block, target, check)),
+        Statement::Try(try_stmt) => std::iter::once(&try_stmt.try_block)
+            .chain(try_stmt.catch_clauses.iter().map(|c| &c.block))
+            .chain(try_stmt.finally_block.as_ref())
+            .find(|block| contains(block.span, target))
+            .is_some_and(|block| dominates(block, target, check)),
+        Statement::RunAs(run_as) => dominates(&run_as.body, target, check),
+        Statement::Labeled(labeled) => nested(&labeled.body),
+        _ => false,
+    }
+}
@@ -0,0 +1,587 @@
+//! Dynamic SOQL that splices object or field names from variables
+//!
+//! `Database.query('SELECT Id FROM ' + objectName)` lets whoever controls
+//! `objectName` pick the object, and a typo only shows up at run time. This
+//! lint finds names spliced into dynamic queries and checks that the method
+//! validates them first, against `Schema.getGlobalDescribe()` or a set of
+//! literal names:
+//!
+//! ```apex
+//! if (!Schema.getGlobalDescribe().containsKey(objectName)) {
+//!     throw new QueryException('Unknown object ' + objectName);
+//! }
+//! return Database.query('SELECT Id FROM ' + objectName);
+//! ```
+//!
+//! There is no control-flow graph; a check counts when it runs before the
+//! query on every path through the method, judged by statement order:
+//!
+//! - a guard clause (`if (!check) { throw/return }`) or an assertion in a
+//!   block that encloses the query, before the statement holding it
+//! - the condition of an `if` the query is nested in, when the query is on
+//!   the branch where the check passed
+//!
+//! A check buried in an earlier statement's branch or loop body does not
+//! count.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "transpile")]
+use crate::analysis::dominance::contains;
+use crate::analysis::dominance::{dominates, Check};
+use crate::ast::visitor::{walk_block, Visitor};
+use crate::ast::{
+    AssignmentExpr, AssignmentOp, BinaryOp, Block, ClassDeclaration, ClassMember, CompilationUnit,
+    Expression, MethodCallExpr, Statement, TypeDeclaration,
+};
+use crate::lexer::Span;
+
+/// Where a spliced variable lands in the query text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamePosition {
+    /// After `FROM`
+    Object,
+    /// In the SELECT list or after `ORDER BY` / `GROUP BY`
+    Field,
+}
+
+/// A variable spliced into a dynamic query as an object or field name
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicName {
+    pub variable: String,
+    pub position: NamePosition,
+    /// Whether a check that runs before the query validates the variable
+    pub validated: bool,
+}
+
+/// A `Database.query()` (or `countQuery`, `queryWithBinds`,
+/// `getQueryLocator`) call whose query text splices in names
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicQuery {
+    /// Class or trigger name, dotted for inner classes
+    pub class: String,
+    /// Enclosing method, constructor or property; `static` for static blocks
+    pub method: String,
+    pub names: Vec<DynamicName>,
+    pub span: Span,
+}
+
+/// A spliced name that reaches the query unvalidated
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicSoqlDiagnostic {
+    pub class: String,
+    pub method: String,
+    pub variable: String,
+    pub message: String,
+    pub span: Span,
+}
+
+/// Dynamic queries in a compilation unit, plus unvalidated names
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DynamicSoqlReport {
+    pub queries: Vec<DynamicQuery>,
+    pub diagnostics: Vec<DynamicSoqlDiagnostic>,
+}
+
+/// Find dynamic queries that splice in object or field names and check each
+/// name is validated before the query runs
+pub fn find_dynamic_soql(unit: &CompilationUnit) -> DynamicSoqlReport {
+    let mut report = DynamicSoqlReport::default();
+    for body in bodies(unit) {
+        let analysis = Analysis::new(body.block, &body.fields);
+        for (call, names) in analysis.queries() {
+            let names: Vec<DynamicName> = names
+                .into_iter()
+                .map(|(variable, position)| DynamicName {
+                    validated: dominates(
+                        body.block,
+                        call.span,
+                        &NameCheck {
+                            analysis: &analysis,
+                            var: &variable,
+                        },
+                    ),
+                    variable,
+                    position,
+                })
+                .collect();
+
+            for name in names.iter().filter(|name| !name.validated) {
+                let what = match name.position {
+                    NamePosition::Object => "object",
+                    NamePosition::Field => "field",
+                };
+                report.diagnostics.push(DynamicSoqlDiagnostic {
+                    class: body.class.clone(),
+                    method: body.method.clone(),
+                    variable: name.variable.clone(),
+                    message: format!(
+                        "{} name '{}' is spliced into dynamic SOQL without being validated first",
+                        what, name.variable
+                    ),
+                    span: call.span,
+                });
+            }
+            report.queries.push(DynamicQuery {
+                class: body.class.clone(),
+                method: body.method.clone(),
+                names,
+                span: call.span,
+            });
+        }
+    }
+    report
+}
+
+/// Object-name variables to check against an allow-list before the
+/// statement at `statement` runs
+#[cfg(feature = "transpile")]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ObjectGuard {
+    pub statement: Span,
+    pub variables: Vec<String>,
+}
+
+/// Guards for every dynamic query splicing in an object name, whether or not
+/// the Apex validates it
+#[cfg(feature = "transpile")]
+pub(crate) fn object_guards(unit: &CompilationUnit) -> Vec<ObjectGuard> {
+    let mut guards: Vec<ObjectGuard> = Vec::new();
+    for body in bodies(unit) {
+        let analysis = Analysis::new(body.block, &body.fields);
+        for (call, names) in analysis.queries() {
+            let variables: Vec<String> = names
+                .into_iter()
+                .filter(|(_, position)| *position == NamePosition::Object)
+                .map(|(variable, _)| variable)
+                .collect();
+            if variables.is_empty() {
+                continue;
+            }
+            let Some(statement) = body
+                .block
+                .statements
+                .iter()
+                .find(|stmt| contains(stmt.span(), call.span))
+                .map(|stmt| insertion_point(stmt, call.span).unwrap_or(stmt.span()))
+            else {
+                continue;
+            };
+
+            match guards.iter_mut().find(|g| g.statement == statement) {
+                Some(guard) => {
+                    for variable in variables {
+                        if !guard.variables.contains(&variable) {
+                            guard.variables.push(variable);
+                        }
+                    }
+                }
+                None => guards.push(ObjectGuard {
+                    statement,
+                    variables,
+                }),
+            }
+        }
+    }
+    guards
+}
+
+/// A block of code and where it lives
+struct Body<'a> {
+    class: String,
+    method: String,
+    block: &'a Block,
+    /// Field initializers visible from the body, by lowercased name
+    fields: HashMap<String, &'a Expression>,
+}
+
+fn bodies(unit: &CompilationUnit) -> Vec<Body<'_>> {
+    let mut bodies = Vec::new();
+    for declaration in &unit.declarations {
+        match declaration {
+            TypeDeclaration::Class(class) => {
+                collect_bodies(class, &class.name, &HashMap::new(), &mut bodies)
+            }
+            TypeDeclaration::Trigger(trigger) => bodies.push(Body {
+                class: trigger.name.clone(),
+                method: trigger.name.clone(),
+                block: &trigger.body,
+                fields: HashMap::new(),
+            }),
+            _ => {}
+        }
+    }
+    bodies
+}
+
+fn collect_bodies<'a>(
+    class: &'a ClassDeclaration,
+    name: &str,
+    outer_fields: &HashMap<String, &'a Expression>,
+    bodies: &mut Vec<Body<'a>>,
+) {
+    let mut fields = outer_fields.clone();
+    for member in &class.members {
+        if let ClassMember::Field(field) = member {
+            for declarator in &field.declarators {
+                if let Some(init) = &declarator.initializer {
+                    fields.insert(declarator.name.to_lowercase(), init);
+                }
+            }
+        }
+    }
+
+    let mut push = |method: &str, block: &'a Block| {
+        bodies.push(Body {
+            class: name.to_string(),
+            method: method.to_string(),
+            block,
+            fields: fields.clone(),
+        })
+    };
+    for member in &class.members {
+        match member {
+            ClassMember::Method(method) => {
+                if let Some(body) = &method.body {
+                    push(&method.name, body);
+                }
+            }
+            ClassMember::Constructor(ctor) => push(&ctor.name, &ctor.body),
+            ClassMember::Property(prop) => {
+                for accessor in [&prop.getter, &prop.setter].into_iter().flatten() {
+                    if let Some(body) = &accessor.body {
+                        push(&prop.name, body);
+                    }
+                }
+            }
+            ClassMember::StaticBlock(block) => push("static", block),
+            _ => {}
+        }
+    }
+
+    for member in &class.members {
+        if let ClassMember::InnerClass(inner) = member {
+            collect_bodies(inner, &format!("{}.{}", name, inner.name), &fields, bodies);
+        }
+    }
+}
+
+/// A piece of query text
+enum Part {
+    Text(String),
+    Name(String),
+    Other,
+}
+
+/// A write to a local variable
+struct Write<'a> {
+    name: String,
+    append: bool,
+    value: &'a Expression,
+    start: usize,
+}
+
+/// Local initializers and the writes to locals in a body
+struct Writes<'a> {
+    bindings: HashMap<String, &'a Expression>,
+    writes: Vec<Write<'a>>,
+}
+
+impl<'a> Visitor<'a> for Writes<'a> {
+    fn visit_statement(&mut self, stmt: &'a Statement) {
+        let Statement::LocalVariable(var) = stmt else {
+            return;
+        };
+        for declarator in &var.declarators {
+            if let Some(init) = &declarator.initializer {
+                let name = declarator.name.to_lowercase();
+                self.bindings.insert(name.clone(), init);
+                self.writes.push(Write {
+                    name,
+                    append: false,
+                    value: init,
+                    start: var.span.start,
+                });
+            }
+        }
+    }
+
+    fn visit_assignment(&mut self, assign: &'a AssignmentExpr) {
+        let Expression::Identifier(name, _, _) = &assign.target else {
+            return;
+        };
+        let append = match assign.operator {
+            AssignmentOp::Assign => false,
+            AssignmentOp::AddAssign => true,
+            _ => return,
+        };
+        self.writes.push(Write {
+            name: name.to_lowercase(),
+            append,
+            value: &assign.value,
+            start: assign.span.start,
+        });
+    }
+}
+
+/// What one body's queries are built from
+struct Analysis<'a> {
+    block: &'a Block,
+    /// Field and local initializers by lowercased name, for resolving the
+    /// receivers of `containsKey()` / `contains()` checks
+    bindings: HashMap<String, &'a Expression>,
+    /// Writes to locals in source order
+    writes: Vec<Write<'a>>,
+}
+
+/// Expansion depth for query variables built from other variables
+const MAX_EXPANSION_DEPTH: usize = 8;
+
+impl<'a> Analysis<'a> {
+    fn new(block: &'a Block, fields: &HashMap<String, &'a Expression>) -> Self {
+        let mut writes = Writes {
+            bindings: fields.clone(),
+            writes: Vec::new(),
+        };
+        walk_block(&mut writes, block);
+        writes.writes.sort_by_key(|w| w.start);
+
+        Self {
+            block,
+            bindings: writes.bindings,
+            writes: writes.writes,
+        }
+    }
+
+    /// Query calls in the body with the names spliced into each
+    fn queries(&self) -> Vec<(&'a MethodCallExpr, Vec<(String, NamePosition)>)> {
+        #[derive(Default)]
+        struct QueryCalls<'a>(Vec<&'a MethodCallExpr>);
+
+        impl<'a> Visitor<'a> for QueryCalls<'a> {
+            fn visit_method_call(&mut self, call: &'a MethodCallExpr) {
+                if is_query_call(call) {
+                    self.0.push(call);
+                }
+            }
+        }
+
+        let mut calls = QueryCalls::default();
+        walk_block(&mut calls, self.block);
+
+        calls
+            .0
+            .into_iter()
+            .filter_map(|call| {
+                let mut parts = Vec::new();
+                self.expand(&call.arguments[0], call.span.start, 0, &mut parts);
+                let names = spliced_names(&parts);
+                (!names.is_empty()).then_some((call, names))
+            })
+            .collect()
+    }
+
+    /// Flatten a string expression into text and spliced names, following
+    /// local variables through the writes made before `before`
+    fn expand(&self, expr: &Expression, before: usize, depth: usize, parts: &mut Vec<Part>) {
+        match expr {
+            Expression::String(text, _, _) => parts.push(Part::Text(text.clone())),
+            Expression::Parenthesized(inner, _, _) => self.expand(inner, before, depth, parts),
+            Expression::Binary(binary) if binary.operator == BinaryOp::Add => {
+                self.expand(&binary.left, before, depth, parts);
+                self.expand(&binary.right, before, depth, parts);
+            }
+            Expression::Identifier(name, _, _) => {
+                let key = name.to_lowercase();
+                let writes: Vec<&Write> = self
+                    .writes
+                    .iter()
+                    .filter(|w| w.name == key && w.start < before)
+                    .collect();
+                if writes.is_empty() || depth >= MAX_EXPANSION_DEPTH {
+                    parts.push(Part::Name(name.clone()));
+                    return;
+                }
+
+                let mut value = Vec::new();
+                for write in writes {
+                    if !write.append {
+                        value.clear();
+                    }
+                    self.expand(write.value, write.start, depth + 1, &mut value);
+                }
+                parts.extend(value);
+            }
+            _ => parts.push(Part::Other),
+        }
+    }
+
+    /// `Schema.getGlobalDescribe()`, or a variable initialized from it
+    fn is_describe_map(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::MethodCall(call) => call.name.eq_ignore_ascii_case("getGlobalDescribe"),
+            Expression::Identifier(name, _, _) => self
+                .bindings
+                .get(&name.to_lowercase())
+                .is_some_and(|init| matches!(init, Expression::MethodCall(call) if call.name.eq_ignore_ascii_case("getGlobalDescribe"))),
+            _ => false,
+        }
+    }
+
+    /// A set or list literal of strings, or a variable initialized with one
+    fn is_literal_names(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::SetLiteral(items, ..) | Expression::ListLiteral(items, ..) => {
+                !items.is_empty()
+                    && items
+                        .iter()
+                        .all(|item| matches!(item, Expression::String(..)))
+            }
+            Expression::Identifier(name, _, _) => {
+                self.bindings.get(&name.to_lowercase()).is_some_and(|init| {
+                    matches!(
+                        init,
+                        Expression::SetLiteral(..) | Expression::ListLiteral(..)
+                    ) && self.is_literal_names(init)
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// An expression that is true only when `var` is a known name
+    fn is_check(&self, expr: &Expression, var: &str) -> bool {
+        match expr {
+            Expression::Parenthesized(inner, _, _) => self.is_check(inner, var),
+            Expression::Binary(binary) => match binary.operator {
+                BinaryOp::Or => {
+                    self.is_check(&binary.left, var) && self.is_check(&binary.right, var)
+                }
+                BinaryOp::Equal | BinaryOp::ExactEqual => {
+                    (is_var(&binary.left, var) && matches!(binary.right, Expression::String(..)))
+                        || (is_var(&binary.right, var)
+                            && matches!(binary.left, Expression::String(..)))
+                }
+                _ => false,
+            },
+            Expression::MethodCall(call) => {
+                let name = call.name.to_lowercase();
+                let (Some(receiver), [arg]) = (&call.object, call.arguments.as_slice()) else {
+                    return false;
+                };
+                match name.as_str() {
+                    "containskey" => is_var(arg, var) && self.is_describe_map(receiver),
+                    "contains" => is_var(arg, var) && self.is_literal_names(receiver),
+                    "equals" | "equalsignorecase" => {
+                        (is_var(receiver, var) && matches!(arg, Expression::String(..)))
+                            || (is_var(arg, var) && matches!(receiver, Expression::String(..)))
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A check that `var` names a known object or field
+struct NameCheck<'c, 'a> {
+    analysis: &'c Analysis<'a>,
+    var: &'c str,
+}
+
+impl Check for NameCheck<'_, '_> {
+    fn is_check(&self, expr: &Expression) -> bool {
+        self.analysis.is_check(expr, self.var)
+    }
+}
+
+fn is_query_call(call: &MethodCallExpr) -> bool {
+    let on_database = matches!(
+        &call.object,
+        Some(Expression::Identifier(name, _, _)) if name.eq_ignore_ascii_case("Database")
+    );
+    on_database
+        && matches!(
+            call.name.to_lowercase().as_str(),
+            "query" | "querywithbinds" | "countquery" | "getquerylocator"
+        )
+        && !call.arguments.is_empty()
+}
+
+/// The variables spliced in as names, by the query text just before them
+fn spliced_names(parts: &[Part]) -> Vec<(String, NamePosition)> {
+    let mut names: Vec<(String, NamePosition)> = Vec::new();
+    for pair in parts.windows(2) {
+        let (Part::Text(text), Part::Name(name)) = (&pair[0], &pair[1]) else {
+            continue;
+        };
+        let text = text.trim_end().to_uppercase();
+        let ends_with_word = |word: &str| {
+            text.strip_suffix(word)
+                .is_some_and(|rest| rest.is_empty() || rest.ends_with(char::is_whitespace))
+        };
+        let position = if ends_with_word("FROM") {
+            NamePosition::Object
+        } else if ends_with_word("SELECT") || ends_with_word("BY") || text.ends_with(',') {
+            NamePosition::Field
+        } else {
+            continue;
+        };
+        if !names
+            .iter()
+            .any(|(n, p)| n.eq_ignore_ascii_case(name) && *p == position)
+        {
+            names.push((name.clone(), position));
+        }
+    }
+    names
+}
+
+/// `var`, possibly normalized with `toLowerCase()` and friends
+fn is_var(expr: &Expression, var: &str) -> bool {
+    match expr {
+        Expression::Identifier(name, _, _) => name.eq_ignore_ascii_case(var),
+        Expression::MethodCall(call) if call.arguments.is_empty() => {
+            matches!(
+                call.name.to_lowercase().as_str(),
+                "tolowercase" | "touppercase" | "trim"
+            ) && call.object.as_ref().is_some_and(|obj| is_var(obj, var))
+        }
+        _ => false,
+    }
+}
+
+/// Innermost statement nested in `stmt` that holds `target`; `None` when
+/// `target` is in `stmt`'s own expressions
+#[cfg(feature = "transpile")]
+fn insertion_point(stmt: &Statement, target: Span) -> Option<Span> {
+    let children: Vec<&Statement> = match stmt {
+        Statement::Block(block) => block.statements.iter().collect(),
+        Statement::If(if_stmt) => std::iter::once(if_stmt.then_branch.as_ref())
+            .chain(if_stmt.else_branch.as_deref())
+            .collect(),
+        Statement::For(s) => vec![&s.body],
+        Statement::ForEach(s) => vec![&s.body],
+        Statement::While(s) => vec![&s.body],
+        Statement::DoWhile(s) => vec![&s.body],
+        Statement::Switch(s) => s
+            .when_clauses
+            .iter()
+            .flat_map(|when| &when.block.statements)
+            .collect(),
+        Statement::Try(t) => std::iter::once(&t.try_block)
+            .chain(t.catch_clauses.iter().map(|c| &c.block))
+            .chain(t.finally_block.as_ref())
+            .flat_map(|block| &block.statements)
+            .collect(),
+        Statement::RunAs(r) => r.body.statements.iter().collect(),
+        Statement::Labeled(s) => vec![&s.body],
+        _ => Vec::new(),
+    };
+    let child = children
+        .into_iter()
+        .find(|child| contains(child.span(), target))?;
+
+    Some(insertion_point(child, target).unwrap_or(child.span()))
+}
@@ -0,0 +1,823 @@
+//! SObject field reads and writes checked against their query and schema
+//!
+//! Two mistakes compile but fail on the platform: reading a field the query
+//! that produced a record did not select, which throws `SObjectException`,
+//! and assigning a literal of the wrong type to a field.
+//! [`check_field_usage`] reports both.
+//!
+//! What a record selected is known when its value provably came from a
+//! query, by assignment or by iterating over the query (or over a list
+//! assigned from one):
+//!
+//! ```apex
+//! Account acc = [SELECT Name, Owner.Name FROM Account LIMIT 1];
+//! String owner = acc.Owner.Name;   // selected through the parent path
+//! Id ownerId = acc.Owner.Id;       // a parent's Id comes with its fields
+//! String industry = acc.Industry;  // reported: Industry was not selected
+//! ```
+//!
+//! Tracking is per method and conservative. Reassigning a variable, setting
+//! a field on it with `put`, or passing it to a method or constructor
+//! forgets its selection; after an `if` only what both branches agree on is
+//! kept, and a loop body only starts from what nothing in the loop changes.
+//! Literal assignments, both `opp.CloseDate = 'tomorrow'` and
+//! `new Opportunity(CloseDate = 'tomorrow')`, are checked for every variable
+//! whose declared type is in the schema.
+
+use std::collections::{HashMap, HashSet};
+
+use super::Parent;
+use crate::ast::visitor::{walk_compilation_unit, walk_expression, walk_statement, Visitor};
+use crate::ast::{
+    AssignmentOp, Block, ClassDeclaration, ClassMember, CompilationUnit, Expression,
+    ForEachStatement, ForInit, LocalVariableDeclaration, MethodCallExpr, Parameter, SelectField,
+    SideTable, SoqlQuery, Statement, TypeDeclaration, TypeRef, UnaryOp,
+};
+use crate::lexer::Span;
+use crate::sql::{SalesforceFieldType, SalesforceSchema};
+
+/// A field read or literal assignment that fails at run time
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldUsageDiagnostic {
+    /// Class or trigger name, dotted for inner classes
+    pub class: String,
+    /// Enclosing method, constructor or property; `static` for static blocks
+    pub method: String,
+    pub kind: FieldUsageKind,
+    pub message: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldUsageKind {
+    /// `variable.field` read although the query `variable` came from did
+    /// not select it; `field` is dotted for parent fields
+    UnselectedField { variable: String, field: String },
+    /// A literal that cannot be stored in a field of type `expected`
+    TypeMismatch {
+        object: String,
+        field: String,
+        expected: SalesforceFieldType,
+    },
+}
+
+/// Unselected field reads and mistyped literal assignments in `unit`
+pub fn check_field_usage(
+    unit: &CompilationUnit,
+    schema: &SalesforceSchema,
+) -> Vec<FieldUsageDiagnostic> {
+    let selections = selections(unit);
+    let mut diagnostics = Vec::new();
+    for declaration in &unit.declarations {
+        match declaration {
+            TypeDeclaration::Class(class) => {
+                check_class(class, &class.name, schema, &selections, &mut diagnostics)
+            }
+            TypeDeclaration::Trigger(trigger) => {
+                let mut checker =
+                    Checker::new(schema, &selections, &trigger.name, &trigger.name, &[]);
+                checker.block(&trigger.body, &mut Flow::default());
+                diagnostics.append(&mut checker.diagnostics);
+            }
+            _ => {}
+        }
+    }
+    diagnostics.sort_by_key(|d| d.span.start);
+    diagnostics
+}
+
+fn check_class(
+    class: &ClassDeclaration,
+    name: &str,
+    schema: &SalesforceSchema,
+    selections: &SideTable<Selection>,
+    diagnostics: &mut Vec<FieldUsageDiagnostic>,
+) {
+    let mut check = |method: &str, parameters: &[Parameter], body: &Block| {
+        let mut checker = Checker::new(schema, selections, name, method, parameters);
+        checker.block(body, &mut Flow::default());
+        diagnostics.append(&mut checker.diagnostics);
+    };
+    for member in &class.members {
+        match member {
+            ClassMember::Method(method) => {
+                if let Some(body) = &method.body {
+                    check(&method.name, &method.parameters, body);
+                }
+            }
+            ClassMember::Constructor(ctor) => check(&ctor.name, &ctor.parameters, &ctor.body),
+            ClassMember::Property(prop) => {
+                for accessor in [&prop.getter, &prop.setter].into_iter().flatten() {
+                    if let Some(body) = &accessor.body {
+                        check(&prop.name, &[], body);
+                    }
+                }
+            }
+            ClassMember::StaticBlock(block) => check("static", &[], block),
+            _ => {}
+        }
+    }
+    for member in &class.members {
+        if let ClassMember::InnerClass(inner) = member {
+            check_class(
+                inner,
+                &format!("{}.{}", name, inner.name),
+                schema,
+                selections,
+                diagnostics,
+            );
+        }
+    }
+}
+
+/// The selection of every query in `unit` that selects records, by query
+fn selections(unit: &CompilationUnit) -> SideTable<Selection> {
+    struct Queries(SideTable<Selection>);
+
+    impl<'ast> Visitor<'ast> for Queries {
+        fn visit_soql_query(&mut self, query: &'ast SoqlQuery) {
+            if let Some(selection) = Selection::of(query) {
+                self.0.insert(query.id, selection);
+            }
+        }
+    }
+
+    let mut queries = Queries(SideTable::new());
+    walk_compilation_unit(&mut queries, unit);
+    queries.0
+}
+
+/// Fields a query selected, lowercased, with parent fields as dotted paths
+#[derive(Debug, Clone, PartialEq)]
+struct Selection {
+    object: String,
+    fields: HashSet<String>,
+    /// Child subqueries and TYPEOF relationships, readable as a whole
+    relationships: HashSet<String>,
+}
+
+impl Selection {
+    /// `None` for aggregate queries, whose rows are not records, and for
+    /// `FIELDS()` selections
+    fn of(query: &SoqlQuery) -> Option<Self> {
+        if !query.group_by_clause.is_empty() {
+            return None;
+        }
+        let mut fields = HashSet::new();
+        let mut relationships = HashSet::new();
+        for item in &query.select_clause {
+            match item {
+                SelectField::Field(field) => {
+                    fields.insert(field.to_lowercase());
+                }
+                SelectField::SubQuery(subquery) => {
+                    relationships.insert(subquery.from_clause.to_lowercase());
+                }
+                SelectField::TypeOf(typeof_clause) => {
+                    relationships.insert(typeof_clause.field.to_lowercase());
+                }
+                SelectField::AggregateFunction { .. } | SelectField::Fields(_) => return None,
+            }
+        }
+        Some(Self {
+            object: query.from_clause.clone(),
+            fields,
+            relationships,
+        })
+    }
+
+    /// Whether reading the lowercased dotted `path` finds a value
+    fn covers(&self, path: &str) -> bool {
+        let has_fields_under = |parent: &str| {
+            let prefix = format!("{}.", parent);
+            self.fields.iter().any(|field| field.starts_with(&prefix))
+        };
+        if path == "id" || self.fields.contains(path) || has_fields_under(path) {
+            return true;
+        }
+        if path.strip_suffix(".id").is_some_and(has_fields_under) {
+            return true;
+        }
+        let root = path.split('.').next().unwrap_or(path);
+        self.relationships.contains(root)
+    }
+
+    fn intersect(&mut self, other: &Selection) {
+        self.fields.retain(|field| other.fields.contains(field));
+        self.relationships
+            .retain(|relationship| other.relationships.contains(relationship));
+    }
+}
+
+/// What is known at a point of a method body, keyed by lowercased variable
+#[derive(Debug, Clone, Default)]
+struct Flow {
+    /// Records that came from a query
+    records: HashMap<String, Selection>,
+    /// Lists holding the results of a query
+    lists: HashMap<String, Selection>,
+}
+
+impl Flow {
+    fn forget(&mut self, var: &str) {
+        let var = var.to_lowercase();
+        self.records.remove(&var);
+        self.lists.remove(&var);
+    }
+
+    /// Knowledge holding on both of two paths that join
+    fn meet(mut self, other: &Flow) -> Flow {
+        self.records
+            .retain(|var, selection| match other.records.get(var) {
+                Some(theirs) if theirs.object == selection.object => {
+                    selection.intersect(theirs);
+                    true
+                }
+                _ => false,
+            });
+        self.lists
+            .retain(|var, selection| other.lists.get(var) == Some(selection));
+        self
+    }
+}
+
+/// Something an expression does, in evaluation order
+enum Event<'e> {
+    /// `var.path` read
+    Read {
+        var: &'e str,
+        path: String,
+        span: Span,
+    },
+    /// `var = value` or a compound assignment
+    Assign {
+        var: &'e str,
+        operator: AssignmentOp,
+        value: &'e Expression,
+    },
+    /// `var.path = value`
+    FieldWrite {
+        var: &'e str,
+        path: String,
+        value: &'e Expression,
+        span: Span,
+    },
+    /// `new Type(Field = value)`
+    ConstructorField {
+        object: &'e str,
+        field: &'e str,
+        value: &'e Expression,
+        span: Span,
+    },
+    /// `var` passed to a method or constructor, or the receiver of `put`
+    Passed(&'e str),
+}
+
+struct Checker<'a> {
+    schema: &'a SalesforceSchema,
+    selections: &'a SideTable<Selection>,
+    class: String,
+    method: String,
+    /// Declared types of parameters, locals and loop variables
+    types: HashMap<String, TypeRef>,
+    diagnostics: Vec<FieldUsageDiagnostic>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(
+        schema: &'a SalesforceSchema,
+        selections: &'a SideTable<Selection>,
+        class: &str,
+        method: &str,
+        parameters: &[Parameter],
+    ) -> Self {
+        Self {
+            schema,
+            selections,
+            class: class.to_string(),
+            method: method.to_string(),
+            types: parameters
+                .iter()
+                .map(|p| (p.name.to_lowercase(), p.type_ref.clone()))
+                .collect(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn block(&mut self, block: &Block, flow: &mut Flow) {
+        for stmt in &block.statements {
+            self.statement(stmt, flow);
+        }
+    }
+
+    fn statement(&mut self, stmt: &Statement, flow: &mut Flow) {
+        match stmt {
+            Statement::Block(block) => self.block(block, flow),
+            Statement::LocalVariable(var) => {
+                for declarator in &var.declarators {
+                    self.declare(&declarator.name, &var.type_ref, flow);
+                    if let Some(init) = &declarator.initializer {
+                        self.expression(init, flow);
+                        self.bind(&declarator.name, init, flow);
+                    }
+                }
+            }
+            Statement::Expression(stmt) => self.expression(&stmt.expression, flow),
+            Statement::If(s) => {
+                self.expression(&s.condition, flow);
+                let mut then_flow = flow.clone();
+                self.statement(&s.then_branch, &mut then_flow);
+                if let Some(else_branch) = &s.else_branch {
+                    self.statement(else_branch, flow);
+                }
+                *flow = then_flow.meet(flow);
+            }
+            Statement::For(_)
+            | Statement::ForEach(_)
+            | Statement::While(_)
+            | Statement::DoWhile(_) => self.loop_statement(stmt, flow),
+            Statement::Switch(s) => {
+                self.expression(&s.expression, flow);
+                for when in &s.when_clauses {
+                    self.block(&when.block, &mut flow.clone());
+                }
+                forget_changed(stmt, flow);
+            }
+            Statement::Try(t) => {
+                self.block(&t.try_block, &mut flow.clone());
+                // A catch or finally block may start anywhere in the try
+                // block, so only what the try block leaves alone holds
+                let mut after_try = flow.clone();
+                for changed in changed_names(t.try_block.statements.iter()) {
+                    after_try.forget(&changed);
+                }
+                for catch in &t.catch_clauses {
+                    self.block(&catch.block, &mut after_try.clone());
+                }
+                if let Some(finally) = &t.finally_block {
+                    self.block(finally, &mut after_try.clone());
+                }
+                forget_changed(stmt, flow);
+            }
+            Statement::Return(r) => {
+                if let Some(value) = &r.value {
+                    self.expression(value, flow);
+                }
+            }
+            Statement::Throw(t) => self.expression(&t.exception, flow),
+            Statement::Dml(dml) => self.expression(&dml.expression, flow),
+            Statement::RunAs(run_as) => {
+                self.expression(&run_as.user, flow);
+                self.block(&run_as.body, flow);
+            }
+            Statement::Labeled(s) => self.statement(&s.body, flow),
+            Statement::Break(_) | Statement::Continue(_) | Statement::Empty(..) => {}
+        }
+    }
+
+    fn loop_statement(&mut self, stmt: &Statement, flow: &mut Flow) {
+        // Each iteration starts after the ones before it, so only what the
+        // loop never changes is known on entry
+        forget_changed(stmt, flow);
+        match stmt {
+            Statement::For(s) => {
+                match &s.init {
+                    Some(ForInit::Variables(var)) => {
+                        for declarator in &var.declarators {
+                            self.declare(&declarator.name, &var.type_ref, flow);
+                            if let Some(init) = &declarator.initializer {
+                                self.expression(init, flow);
+                            }
+                        }
+                    }
+                    Some(ForInit::Expressions(exprs)) => {
+                        for expr in exprs {
+                            self.expression(expr, flow);
+                        }
+                    }
+                    None => {}
+                }
+                let mut body_flow = flow.clone();
+                if let Some(condition) = &s.condition {
+                    self.expression(condition, &mut body_flow);
+                }
+                self.statement(&s.body, &mut body_flow);
+                for update in &s.update {
+                    self.expression(update, &mut body_flow);
+                }
+            }
+            Statement::ForEach(s) => {
+                self.expression(&s.iterable, flow);
+                self.declare(&s.variable, &s.type_ref, flow);
+                let mut body_flow = flow.clone();
+                let selection = match unparenthesized(&s.iterable) {
+                    Expression::Soql(query) => self.selections.get(query.id).cloned(),
+                    Expression::Identifier(name, _, _) => {
+                        flow.lists.get(&name.to_lowercase()).cloned()
+                    }
+                    _ => None,
+                };
+                let reassigned = changed_names(std::iter::once(s.body.as_ref()))
+                    .contains(&s.variable.to_lowercase());
+                if let (Some(selection), false) = (selection, reassigned) {
+                    body_flow
+                        .records
+                        .insert(s.variable.to_lowercase(), selection);
+                }
+                self.statement(&s.body, &mut body_flow);
+            }
+            Statement::While(s) => {
+                self.expression(&s.condition, flow);
+                self.statement(&s.body, &mut flow.clone());
+            }
+            Statement::DoWhile(s) => {
+                let mut body_flow = flow.clone();
+                self.statement(&s.body, &mut body_flow);
+                self.expression(&s.condition, &mut body_flow);
+            }
+            _ => {}
+        }
+    }
+
+    fn declare(&mut self, name: &str, type_ref: &TypeRef, flow: &mut Flow) {
+        self.types.insert(name.to_lowercase(), type_ref.clone());
+        flow.forget(name);
+    }
+
+    /// Record what `var` holds after `var = value`
+    fn bind(&self, var: &str, value: &Expression, flow: &mut Flow) {
+        let Expression::Soql(query) = unparenthesized(value) else {
+            return;
+        };
+        let (Some(selection), Some(type_ref)) = (
+            self.selections.get(query.id).cloned(),
+            self.types.get(&var.to_lowercase()),
+        ) else {
+            return;
+        };
+        if type_ref.is_array || type_ref.name.eq_ignore_ascii_case("List") {
+            flow.lists.insert(var.to_lowercase(), selection);
+        } else if type_ref.name.eq_ignore_ascii_case(&selection.object)
+            || type_ref.name.eq_ignore_ascii_case("SObject")
+        {
+            flow.records.insert(var.to_lowercase(), selection);
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression, flow: &mut Flow) {
+        let events = events(expr);
+
+        // Reads see the values from before the expression's assignments
+        for event in &events {
+            let Event::Read { var, path, span } = event else {
+                continue;
+            };
+            let Some(selection) = flow.records.get(&var.to_lowercase()) else {
+                continue;
+            };
+            if !selection.covers(&path.to_lowercase()) {
+                self.report(
+                    FieldUsageKind::UnselectedField {
+                        variable: var.to_string(),
+                        field: path.clone(),
+                    },
+                    format!(
+                        "{}.{} is read but the query {} came from does not select it",
+                        var, path, var
+                    ),
+                    *span,
+                );
+            }
+        }
+
+        for event in events {
+            match event {
+                Event::Read { .. } => {}
+                Event::Assign {
+                    var,
+                    operator,
+                    value,
+                } => {
+                    flow.forget(var);
+                    if operator == AssignmentOp::Assign {
+                        self.bind(var, value, flow);
+                    }
+                }
+                Event::FieldWrite {
+                    var,
+                    path,
+                    value,
+                    span,
+                } => {
+                    let object = self.object_of(var, flow);
+                    if let Some(object) = object {
+                        if !path.contains('.') {
+                            self.check_literal(&object, &path, value, span);
+                        }
+                    }
+                    if let Some(selection) = flow.records.get_mut(&var.to_lowercase()) {
+                        selection.fields.insert(path.to_lowercase());
+                    }
+                }
+                Event::ConstructorField {
+                    object,
+                    field,
+                    value,
+                    span,
+                } => self.check_literal(object, field, value, span),
+                Event::Passed(var) => flow.forget(var),
+            }
+        }
+    }
+
+    /// The SObject type of `var`, from its declaration or the query it
+    /// came from
+    fn object_of(&self, var: &str, flow: &Flow) -> Option<String> {
+        let var = var.to_lowercase();
+        match self.types.get(&var) {
+            Some(type_ref) if type_ref.is_array || type_ref.name.eq_ignore_ascii_case("List") => {
+                None
+            }
+            Some(type_ref) if !type_ref.name.eq_ignore_ascii_case("SObject") => {
+                Some(type_ref.name.clone())
+            }
+            _ => flow.records.get(&var).map(|s| s.object.clone()),
+        }
+    }
+
+    fn check_literal(&mut self, object: &str, field: &str, value: &Expression, span: Span) {
+        let Some(describe) = self.schema.get_object(object) else {
+            return;
+        };
+        let Some(field) = describe.get_field(field) else {
+            return;
+        };
+        let Some(literal) = Literal::of(value) else {
+            return;
+        };
+        if literal.fits(field.field_type) {
+            return;
+        }
+        let message = format!(
+            "{} literal assigned to {}.{}, a {:?} field",
+            literal.name(),
+            describe.name,
+            field.name,
+            field.field_type
+        );
+        self.report(
+            FieldUsageKind::TypeMismatch {
+                object: describe.name.clone(),
+                field: field.name.clone(),
+                expected: field.field_type,
+            },
+            message,
+            span,
+        );
+    }
+
+    fn report(&mut self, kind: FieldUsageKind, message: String, span: Span) {
+        self.diagnostics.push(FieldUsageDiagnostic {
+            class: self.class.clone(),
+            method: self.method.clone(),
+            kind,
+            message,
+            span,
+        });
+    }
+}
+
+/// Kind of a literal assigned to a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Literal {
+    String,
+    Number,
+    Boolean,
+}
+
+impl Literal {
+    fn of(expr: &Expression) -> Option<Self> {
+        match unparenthesized(expr) {
+            Expression::String(..) => Some(Literal::String),
+            Expression::Integer(..) | Expression::Long(..) | Expression::Double(..) => {
+                Some(Literal::Number)
+            }
+            Expression::Unary(unary) if unary.operator == UnaryOp::Negate => {
+                Self::of(&unary.operand).filter(|literal| *literal == Literal::Number)
+            }
+            Expression::Boolean(..) => Some(Literal::Boolean),
+            _ => None,
+        }
+    }
+
+    fn fits(self, field_type: SalesforceFieldType) -> bool {
+        use SalesforceFieldType as T;
+        match field_type {
+            T::Boolean => self == Literal::Boolean,
+            T::Date | T::DateTime | T::Time => false,
+            // Compound fields are read-only
+            T::Address | T::Location => true,
+            t if t.is_numeric() => self == Literal::Number,
+            _ => self == Literal::String,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Literal::String => "String",
+            Literal::Number => "Number",
+            Literal::Boolean => "Boolean",
+        }
+    }
+}
+
+/// Reads, writes and calls in `expr`, in evaluation order
+fn events(expr: &Expression) -> Vec<Event<'_>> {
+    let mut events = Events::default();
+    walk_expression(&mut events, expr);
+    events.events
+}
+
+#[derive(Default)]
+struct Events<'a> {
+    parents: Vec<Parent<'a>>,
+    events: Vec<Event<'a>>,
+}
+
+impl<'a> Visitor<'a> for Events<'a> {
+    fn visit_expression(&mut self, expr: &'a Expression) {
+        self.event(expr);
+        self.parents.push(Parent::Expression(expr));
+    }
+
+    fn leave_expression(&mut self, _: &'a Expression) {
+        self.parents.pop();
+    }
+}
+
+impl<'a> Events<'a> {
+    fn event(&mut self, expr: &'a Expression) {
+        let parent = self.parents.last();
+        match expr {
+            Expression::FieldAccess(_) | Expression::SafeNavigation(_) => {
+                // Only the full path, not each prefix of it
+                let prefix = matches!(
+                    parent,
+                    Some(Parent::Expression(
+                        Expression::FieldAccess(_) | Expression::SafeNavigation(_)
+                    ))
+                );
+                let Some((var, path)) = field_path(expr).filter(|_| !prefix) else {
+                    return;
+                };
+                let assigned = match parent {
+                    Some(Parent::Expression(Expression::Assignment(assignment)))
+                        if std::ptr::eq(&assignment.target, expr) =>
+                    {
+                        Some(assignment.operator)
+                    }
+                    _ => None,
+                };
+                if assigned != Some(AssignmentOp::Assign) {
+                    self.events.push(Event::Read {
+                        var,
+                        path,
+                        span: expr.span(),
+                    });
+                }
+            }
+            Expression::Assignment(assignment) => {
+                if let Some(Parent::Expression(Expression::New(new))) = parent {
+                    if let Expression::Identifier(field, _, _) = &assignment.target {
+                        self.events.push(Event::ConstructorField {
+                            object: &new.type_ref.name,
+                            field,
+                            value: &assignment.value,
+                            span: assignment.span,
+                        });
+                    }
+                    return;
+                }
+                match &assignment.target {
+                    Expression::Identifier(var, _, _) => self.events.push(Event::Assign {
+                        var,
+                        operator: assignment.operator,
+                        value: &assignment.value,
+                    }),
+                    target => {
+                        if let Some((var, path)) = field_path(target) {
+                            self.events.push(Event::FieldWrite {
+                                var,
+                                path,
+                                value: &assignment.value,
+                                span: assignment.span,
+                            });
+                        }
+                    }
+                }
+            }
+            Expression::MethodCall(call) => {
+                self.events.extend(passed(&call.arguments));
+                self.events.extend(put_receiver(call).map(Event::Passed));
+            }
+            Expression::New(new) => self.events.extend(passed(&new.arguments)),
+            _ => {}
+        }
+    }
+}
+
+fn passed(arguments: &[Expression]) -> impl Iterator<Item = Event<'_>> {
+    arguments.iter().filter_map(|argument| match argument {
+        Expression::Identifier(var, _, _) => Some(Event::Passed(var)),
+        _ => None,
+    })
+}
+
+/// `var` in `var.put(...)` and `var.putSObject(...)`, which set fields the
+/// query did not select
+fn put_receiver(call: &MethodCallExpr) -> Option<&str> {
+    if !call.name.eq_ignore_ascii_case("put") && !call.name.eq_ignore_ascii_case("putsobject") {
+        return None;
+    }
+    match &call.object {
+        Some(Expression::Identifier(var, _, _)) if !var.contains('.') => Some(var),
+        _ => None,
+    }
+}
+
+/// `(var, "Owner.Name")` for `var.Owner.Name`
+fn field_path(expr: &Expression) -> Option<(&str, String)> {
+    let (object, field) = match expr {
+        Expression::FieldAccess(access) => (&access.object, &access.field),
+        Expression::SafeNavigation(access) => (&access.object, &access.field),
+        _ => return None,
+    };
+    match object {
+        Expression::Identifier(var, _, _) if !var.contains('.') => Some((var, field.clone())),
+        object => {
+            let (var, path) = field_path(object)?;
+            Some((var, format!("{}.{}", path, field)))
+        }
+    }
+}
+
+/// Variables a statement declares, assigns, `put`s into or passes to a
+/// method
+fn changed_names<'s>(stmts: impl Iterator<Item = &'s Statement>) -> HashSet<String> {
+    #[derive(Default)]
+    struct Changed(HashSet<String>);
+
+    impl<'ast> Visitor<'ast> for Changed {
+        fn visit_local_variable(&mut self, var: &'ast LocalVariableDeclaration) {
+            let names = var.declarators.iter().map(|d| d.name.to_lowercase());
+            self.0.extend(names);
+        }
+
+        fn visit_for_each(&mut self, s: &'ast ForEachStatement) {
+            self.0.insert(s.variable.to_lowercase());
+        }
+
+        fn visit_expression(&mut self, expr: &'ast Expression) {
+            let arguments = match expr {
+                Expression::Assignment(assignment) => {
+                    if let Expression::Identifier(var, _, _) = &assignment.target {
+                        self.0.insert(var.to_lowercase());
+                    }
+                    return;
+                }
+                Expression::MethodCall(call) => {
+                    if let Some(var) = put_receiver(call) {
+                        self.0.insert(var.to_lowercase());
+                    }
+                    &call.arguments
+                }
+                Expression::New(new) => &new.arguments,
+                _ => return,
+            };
+            for argument in arguments {
+                if let Expression::Identifier(var, _, _) = argument {
+                    self.0.insert(var.to_lowercase());
+                }
+            }
+        }
+    }
+
+    let mut changed = Changed::default();
+    for stmt in stmts {
+        walk_statement(&mut changed, stmt);
+    }
+    changed.0
+}
+
+fn forget_changed(stmt: &Statement, flow: &mut Flow) {
+    for name in changed_names(std::iter::once(stmt)) {
+        flow.forget(&name);
+    }
+}
+
+fn unparenthesized(expr: &Expression) -> &Expression {
+    match expr {
+        Expression::Parenthesized(inner, _, _) => unparenthesized(inner),
+        expr => expr,
+    }
+}
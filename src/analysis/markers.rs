@@ -0,0 +1,551 @@
+//! Deprecation and TODO markers
+//!
+//! `@Deprecated` classes and methods are collected from the AST, and every
+//! call in the analyzed units that resolves to a deprecated method (or
+//! `new` of a deprecated class) is reported as a lint diagnostic. Calls are
+//! resolved by receiver type and then by overload, so deprecating one
+//! overload does not flag callers of its siblings.
+//!
+//! `TODO`/`FIXME` markers are read from the comment tokens of
+//! [`tokenize_with_trivia`].
+
+use std::collections::HashMap;
+
+use crate::ast::visitor::{walk_block, Visitor};
+use crate::ast::{
+    Annotation, CatchClause, ClassDeclaration, ClassMember, CompilationUnit, Expression,
+    ForEachStatement, LocalVariableDeclaration, MethodCallExpr, MethodDeclaration, NewExpr,
+    TypeDeclaration,
+};
+use crate::lexer::{tokenize_with_trivia, Span, TokenKind};
+use crate::parser::{parse, ParseResult};
+
+/// What a deprecation marker is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeprecatedKind {
+    Class,
+    Method,
+}
+
+/// A class or method annotated `@Deprecated`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeprecatedItem {
+    /// Index of the compilation unit declaring it
+    pub unit: usize,
+    pub kind: DeprecatedKind,
+    /// `Class` or `Class.method(ParamType, ...)`; inner classes are dotted
+    pub name: String,
+    pub span: Span,
+}
+
+/// A use of a deprecated class or method
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeprecationDiagnostic {
+    /// Index of the compilation unit containing the call
+    pub unit: usize,
+    /// The deprecated item, as in [`DeprecatedItem::name`]
+    pub target: String,
+    pub message: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TodoKind {
+    Todo,
+    Fixme,
+}
+
+/// A `// TODO:` or `// FIXME(owner): ...` comment
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodoComment {
+    /// Index of the source the comment was found in
+    pub unit: usize,
+    pub kind: TodoKind,
+    /// Name given in parentheses after the marker, e.g. `TODO(jane)`
+    pub owner: Option<String>,
+    pub text: String,
+    /// Byte range from the marker to the end of its line
+    pub span: Span,
+}
+
+/// Deprecations, their call sites and TODO comments across a set of units
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MarkerReport {
+    pub deprecated: Vec<DeprecatedItem>,
+    pub diagnostics: Vec<DeprecationDiagnostic>,
+    pub todos: Vec<TodoComment>,
+}
+
+impl MarkerReport {
+    /// Parse each source and collect every marker; indices in the report
+    /// refer to positions in `sources`
+    pub fn from_sources(sources: &[&str]) -> ParseResult<MarkerReport> {
+        let units = sources
+            .iter()
+            .map(|source| parse(source))
+            .collect::<ParseResult<Vec<_>>>()?;
+        let mut report = find_deprecations(&units);
+        for (unit, source) in sources.iter().enumerate() {
+            report.todos.extend(
+                find_todos(source)
+                    .into_iter()
+                    .map(|todo| TodoComment { unit, ..todo }),
+            );
+        }
+        Ok(report)
+    }
+}
+
+/// Whether the annotations include `@Deprecated`
+pub fn is_deprecated(annotations: &[Annotation]) -> bool {
+    annotations
+        .iter()
+        .any(|a| a.name.eq_ignore_ascii_case("Deprecated"))
+}
+
+/// Collect deprecated classes and methods and the calls to them
+///
+/// The report's `todos` are left empty; see [`find_todos`].
+pub fn find_deprecations(units: &[CompilationUnit]) -> MarkerReport {
+    let mut index = ClassIndex::default();
+    for (unit, cu) in units.iter().enumerate() {
+        for declaration in &cu.declarations {
+            if let TypeDeclaration::Class(class) = declaration {
+                index.add(unit, class, None);
+            }
+        }
+    }
+
+    let mut report = MarkerReport::default();
+    for info in &index.classes {
+        if info.deprecated {
+            report.deprecated.push(DeprecatedItem {
+                unit: info.unit,
+                kind: DeprecatedKind::Class,
+                name: info.name.clone(),
+                span: info.decl.span,
+            });
+        }
+        for method in info.methods().filter(|m| is_deprecated(&m.annotations)) {
+            report.deprecated.push(DeprecatedItem {
+                unit: info.unit,
+                kind: DeprecatedKind::Method,
+                name: signature(&info.name, method),
+                span: method.span,
+            });
+        }
+    }
+
+    for info in &index.classes {
+        CallChecker::new(&index, info).check(&mut report.diagnostics);
+    }
+    report
+}
+
+/// Scan source text for `TODO` and `FIXME` comments
+///
+/// The marker must start the comment (or a line of a block comment) and be
+/// upper case, so prose that merely mentions a todo is not picked up.
+pub fn find_todos(source: &str) -> Vec<TodoComment> {
+    let mut todos = Vec::new();
+    for token in tokenize_with_trivia(source) {
+        // Offset of the text after the `//`, `/*` or `/**` delimiter
+        let (start, comment) = match token.kind {
+            TokenKind::LineComment(text) | TokenKind::BlockComment(text) => {
+                (token.span.start + 2, text)
+            }
+            TokenKind::DocComment(text) => (token.span.start + 3, text),
+            _ => continue,
+        };
+        let mut offset = 0;
+        for line in comment.split_inclusive('\n') {
+            let line_start = start + offset;
+            offset += line.len();
+
+            let body = line.trim_end_matches(['\n', '\r']);
+            let trimmed = body.trim_start_matches(|c: char| c.is_whitespace() || c == '*');
+            if let Some(todo) = parse_todo(trimmed) {
+                let marker_start = line_start + (body.len() - trimmed.len());
+                let marker_end = line_start + body.trim_end().len();
+                todos.push(TodoComment {
+                    span: Span::new(marker_start, marker_end),
+                    ..todo
+                });
+            }
+        }
+    }
+    todos
+}
+
+/// Parse `TODO(owner): text` at the start of `line`; the span is filled in
+/// by the caller
+fn parse_todo(line: &str) -> Option<TodoComment> {
+    let (kind, rest) = if let Some(rest) = line.strip_prefix("TODO") {
+        (TodoKind::Todo, rest)
+    } else if let Some(rest) = line.strip_prefix("FIXME") {
+        (TodoKind::Fixme, rest)
+    } else {
+        return None;
+    };
+    if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    let (owner, rest) = match rest.strip_prefix('(').and_then(|r| r.split_once(')')) {
+        Some((owner, rest)) => (Some(owner.trim().to_string()), rest),
+        None => (None, rest),
+    };
+    let text = rest.trim_start().trim_start_matches(':').trim();
+
+    Some(TodoComment {
+        unit: 0,
+        kind,
+        owner: owner.filter(|o| !o.is_empty()),
+        text: text.to_string(),
+        span: Span::new(0, 0),
+    })
+}
+
+/// `Class.method(Type, ...)`
+fn signature(class: &str, method: &MethodDeclaration) -> String {
+    let params: Vec<&str> = method
+        .parameters
+        .iter()
+        .map(|p| p.type_ref.name.as_str())
+        .collect();
+    format!("{}.{}({})", class, method.name, params.join(", "))
+}
+
+struct ClassInfo<'a> {
+    unit: usize,
+    /// Dotted name for inner classes
+    name: String,
+    decl: &'a ClassDeclaration,
+    deprecated: bool,
+}
+
+impl<'a> ClassInfo<'a> {
+    fn methods(&self) -> impl Iterator<Item = &'a MethodDeclaration> {
+        self.decl.members.iter().filter_map(|m| match m {
+            ClassMember::Method(method) => Some(method),
+            _ => None,
+        })
+    }
+}
+
+/// Every class in the analyzed units, by simple and dotted name
+#[derive(Default)]
+struct ClassIndex<'a> {
+    classes: Vec<ClassInfo<'a>>,
+    by_name: HashMap<String, usize>,
+}
+
+impl<'a> ClassIndex<'a> {
+    fn add(&mut self, unit: usize, class: &'a ClassDeclaration, outer: Option<&str>) {
+        let name = match outer {
+            Some(outer) => format!("{}.{}", outer, class.name),
+            None => class.name.clone(),
+        };
+        let id = self.classes.len();
+        self.by_name.insert(name.to_lowercase(), id);
+        self.by_name.entry(class.name.to_lowercase()).or_insert(id);
+        self.classes.push(ClassInfo {
+            unit,
+            name: name.clone(),
+            decl: class,
+            deprecated: is_deprecated(&class.annotations),
+        });
+
+        for member in &class.members {
+            if let ClassMember::InnerClass(inner) = member {
+                self.add(unit, inner, Some(&name));
+            }
+        }
+    }
+
+    fn get(&self, type_name: &str) -> Option<&ClassInfo<'a>> {
+        let lower = type_name.to_lowercase();
+        self.by_name
+            .get(&lower)
+            .or_else(|| self.by_name.get(lower.rsplit('.').next()?))
+            .map(|&id| &self.classes[id])
+    }
+}
+
+/// Resolves the calls made inside one class
+struct CallChecker<'i, 'a> {
+    index: &'i ClassIndex<'a>,
+    class: &'i ClassInfo<'a>,
+    /// Declared types of fields and properties, keyed by lowercase name
+    fields: HashMap<String, String>,
+}
+
+impl<'i, 'a> CallChecker<'i, 'a> {
+    fn new(index: &'i ClassIndex<'a>, class: &'i ClassInfo<'a>) -> Self {
+        let mut fields = HashMap::new();
+        for member in &class.decl.members {
+            match member {
+                ClassMember::Field(field) => {
+                    for d in &field.declarators {
+                        fields.insert(d.name.to_lowercase(), field.type_ref.name.clone());
+                    }
+                }
+                ClassMember::Property(prop) => {
+                    fields.insert(prop.name.to_lowercase(), prop.type_ref.name.clone());
+                }
+                _ => {}
+            }
+        }
+        Self {
+            index,
+            class,
+            fields,
+        }
+    }
+
+    fn check(&self, diagnostics: &mut Vec<DeprecationDiagnostic>) {
+        for member in &self.class.decl.members {
+            match member {
+                ClassMember::Method(method) => {
+                    if let Some(ref body) = method.body {
+                        let params = method
+                            .parameters
+                            .iter()
+                            .map(|p| (&p.name, &p.type_ref.name));
+                        self.check_body(body, params, diagnostics);
+                    }
+                }
+                ClassMember::Constructor(ctor) => {
+                    let params = ctor.parameters.iter().map(|p| (&p.name, &p.type_ref.name));
+                    self.check_body(&ctor.body, params, diagnostics);
+                }
+                ClassMember::Property(prop) => {
+                    let accessors = prop.getter.iter().chain(prop.setter.iter());
+                    for body in accessors.filter_map(|a| a.body.as_ref()) {
+                        self.check_body(body, std::iter::empty(), diagnostics);
+                    }
+                }
+                ClassMember::StaticBlock(block) => {
+                    self.check_body(block, std::iter::empty(), diagnostics);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_body<'p>(
+        &self,
+        body: &crate::ast::Block,
+        params: impl Iterator<Item = (&'p String, &'p String)>,
+        diagnostics: &mut Vec<DeprecationDiagnostic>,
+    ) {
+        /// Declared type names of the body's locals
+        struct Scope(HashMap<String, String>);
+
+        impl<'ast> Visitor<'ast> for Scope {
+            fn visit_local_variable(&mut self, var: &'ast LocalVariableDeclaration) {
+                for d in &var.declarators {
+                    self.0
+                        .insert(d.name.to_lowercase(), var.type_ref.name.clone());
+                }
+            }
+
+            fn visit_for_each(&mut self, s: &'ast ForEachStatement) {
+                self.0
+                    .insert(s.variable.to_lowercase(), s.type_ref.name.clone());
+            }
+
+            fn visit_catch_clause(&mut self, c: &'ast CatchClause) {
+                // A union's variable only has the members of Exception
+                let type_name = if c.alternative_types.is_empty() {
+                    c.exception_type.name.clone()
+                } else {
+                    "Exception".to_string()
+                };
+                self.0.insert(c.variable.to_lowercase(), type_name);
+            }
+        }
+
+        /// Calls to deprecated methods and `new` of deprecated classes
+        struct Uses<'c, 'i, 'a> {
+            checker: &'c CallChecker<'i, 'a>,
+            scope: &'c HashMap<String, String>,
+            diagnostics: &'c mut Vec<DeprecationDiagnostic>,
+        }
+
+        impl<'ast> Visitor<'ast> for Uses<'_, '_, '_> {
+            fn visit_method_call(&mut self, call: &'ast MethodCallExpr) {
+                if let Some(target) = self.checker.resolve_call(call, self.scope) {
+                    self.diagnostics.push(DeprecationDiagnostic {
+                        unit: self.checker.class.unit,
+                        message: format!("call to deprecated method {}", target),
+                        target,
+                        span: call.span,
+                    });
+                }
+            }
+
+            fn visit_new(&mut self, new: &'ast NewExpr) {
+                let index = self.checker.index;
+                if let Some(class) = index.get(&new.type_ref.name).filter(|c| c.deprecated) {
+                    self.diagnostics.push(DeprecationDiagnostic {
+                        unit: self.checker.class.unit,
+                        target: class.name.clone(),
+                        message: format!("use of deprecated class {}", class.name),
+                        span: new.span,
+                    });
+                }
+            }
+        }
+
+        // Locals are scoped to the whole body; Apex forbids shadowing, so a
+        // name maps to one type per method
+        let mut scope = Scope(
+            params
+                .map(|(name, ty)| (name.to_lowercase(), ty.clone()))
+                .collect(),
+        );
+        walk_block(&mut scope, body);
+
+        let mut uses = Uses {
+            checker: self,
+            scope: &scope.0,
+            diagnostics,
+        };
+        walk_block(&mut uses, body);
+    }
+
+    /// The deprecated method `call` resolves to, if any
+    fn resolve_call(
+        &self,
+        call: &MethodCallExpr,
+        scope: &HashMap<String, String>,
+    ) -> Option<String> {
+        let class = match &call.object {
+            None | Some(Expression::This(..)) => self.class,
+            Some(receiver) => self.index.get(&self.type_of(receiver, scope)?)?,
+        };
+
+        let candidates: Vec<&MethodDeclaration> = class
+            .methods()
+            .filter(|m| m.name.eq_ignore_ascii_case(&call.name))
+            .filter(|m| m.parameters.len() == call.arguments.len())
+            .collect();
+        let arg_types: Vec<Option<String>> = call
+            .arguments
+            .iter()
+            .map(|arg| self.type_of(arg, scope))
+            .collect();
+
+        let compatible: Vec<&MethodDeclaration> = candidates
+            .into_iter()
+            .filter(|m| overload_matches(m, &arg_types, assignable))
+            .collect();
+        let method = match compatible.as_slice() {
+            [] => return None,
+            [only] => *only,
+            _ if compatible.iter().all(|m| is_deprecated(&m.annotations)) => compatible[0],
+            _ => {
+                // Prefer the overload whose parameters match the known
+                // argument types exactly; give up when that is ambiguous
+                let exact: Vec<_> = compatible
+                    .iter()
+                    .filter(|m| overload_matches(m, &arg_types, same_type))
+                    .collect();
+                match exact.as_slice() {
+                    [only] => **only,
+                    _ => return None,
+                }
+            }
+        };
+
+        is_deprecated(&method.annotations).then(|| signature(&class.name, method))
+    }
+
+    /// Static type of an expression, as far as it can be told locally
+    fn type_of(&self, expr: &Expression, scope: &HashMap<String, String>) -> Option<String> {
+        let ty = match expr {
+            Expression::Boolean(..) => "Boolean",
+            Expression::Integer(..) => "Integer",
+            Expression::Long(..) => "Long",
+            Expression::Double(..) => "Decimal",
+            Expression::String(..) => "String",
+            Expression::This(..) => &self.class.name,
+            Expression::New(new) => &new.type_ref.name,
+            Expression::Cast(cast) => &cast.type_ref.name,
+            Expression::Parenthesized(inner, _, _) => return self.type_of(inner, scope),
+            Expression::Identifier(name, _, _) => {
+                let lower = name.to_lowercase();
+                // A class name used as a receiver is a static call
+                return scope
+                    .get(&lower)
+                    .or_else(|| self.fields.get(&lower))
+                    .cloned()
+                    .or_else(|| self.index.get(name).map(|c| c.name.clone()));
+            }
+            Expression::FieldAccess(access) => match &access.object {
+                // `Outer.Inner` used as a static receiver
+                Expression::Identifier(outer, _, _) => {
+                    let dotted = format!("{}.{}", outer, access.field);
+                    return self.index.get(&dotted).map(|c| c.name.clone());
+                }
+                Expression::This(..) => {
+                    return self.fields.get(&access.field.to_lowercase()).cloned();
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(ty.to_string())
+    }
+}
+
+fn overload_matches(
+    method: &MethodDeclaration,
+    arg_types: &[Option<String>],
+    accepts: fn(&str, &str) -> bool,
+) -> bool {
+    method.parameters.iter().zip(arg_types).all(|(param, arg)| {
+        arg.as_ref()
+            .is_none_or(|arg| accepts(&param.type_ref.name, arg))
+    })
+}
+
+fn same_type(param: &str, arg: &str) -> bool {
+    param.eq_ignore_ascii_case(arg)
+}
+
+/// Whether an argument of type `arg` can be passed for `param`, including
+/// Apex's implicit numeric widening
+fn assignable(param: &str, arg: &str) -> bool {
+    let param = param.to_lowercase();
+    let arg = arg.to_lowercase();
+    param == arg
+        || param == "object"
+        || matches!(
+            (arg.as_str(), param.as_str()),
+            ("integer", "long" | "decimal" | "double")
+                | ("long", "decimal" | "double")
+                | ("string", "id")
+        )
+        || !is_builtin_type(&param) && !is_builtin_type(&arg)
+}
+
+/// Types whose compatibility can be decided by name alone; anything else
+/// may be related through inheritance, which is not tracked here
+fn is_builtin_type(name: &str) -> bool {
+    matches!(
+        name,
+        "boolean"
+            | "integer"
+            | "long"
+            | "decimal"
+            | "double"
+            | "string"
+            | "id"
+            | "date"
+            | "datetime"
+            | "time"
+            | "blob"
+    )
+}
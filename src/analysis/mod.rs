@@ -0,0 +1,24 @@
+//! Analyses over parsed Apex shared by the SQL converter, the transpiler and
+//! tooling
+
+pub mod bulk_safety;
+#[cfg(feature = "sql")]
+pub mod dml_report;
+pub(crate) mod dominance;
+pub mod dynamic_soql;
+#[cfg(feature = "sql")]
+pub mod field_usage;
+pub mod markers;
+pub mod soql_context;
+pub mod test_inventory;
+
+use crate::ast::{Expression, Statement};
+
+/// Statement or expression enclosing a visited expression, for visitors
+/// that push one in `visit_statement` and `visit_expression` and pop it in
+/// the matching `leave_*`
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Parent<'a> {
+    Statement(&'a Statement),
+    Expression(&'a Expression),
+}
@@ -0,0 +1,310 @@
+//! Where inline SOQL queries appear
+//!
+//! The same query is consumed differently depending on what encloses it:
+//! assigned to a List it is fetched in bulk, iterated by a for-each loop it
+//! can be streamed, and assigned to a single SObject it must return exactly
+//! one row. [`classify`] reports every inline query in a compilation unit
+//! with that context, judged from its nearest enclosing statement or
+//! expression (parentheses are looked through).
+//!
+//! ```
+//! use apexrust::analysis::soql_context::{classify, SoqlContext};
+//!
+//! let unit = apexrust::parse(r#"
+//!     public class Sample {
+//!         public List<Contact> load() {
+//!             return [SELECT Name FROM Contact];
+//!         }
+//!     }
+//! "#).unwrap();
+//!
+//! let usages = classify(&unit);
+//! assert_eq!(usages[0].context, SoqlContext::ReturnValue);
+//! ```
+
+use std::collections::HashMap;
+
+use super::Parent;
+use crate::ast::visitor::{walk_block, walk_expression, Visitor};
+use crate::ast::{
+    AssignmentOp, Block, ClassDeclaration, ClassMember, CompilationUnit, Expression,
+    ForEachStatement, LocalVariableDeclaration, Parameter, SoqlQuery, Statement, TypeDeclaration,
+    TypeRef,
+};
+use crate::lexer::Span;
+
+/// An inline query and the context it appears in
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoqlUsage<'a> {
+    pub query: &'a SoqlQuery,
+    pub span: Span,
+    pub context: SoqlContext,
+}
+
+/// How the result of a query is consumed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SoqlContext {
+    /// `for (Account a : [SELECT ...])`
+    ForEachIteration,
+    /// Assigned to a `List<T>` or `T[]` variable, field or property
+    ListAssignment { var: String },
+    /// Assigned to a single SObject variable, field or property; Apex
+    /// throws when it matches no rows
+    SingleRecordAssignment { var: String },
+    /// Passed directly to a method or constructor. The callee is dotted as
+    /// written (`Database.insert`, `helper.process`) or `new Type` for a
+    /// constructor.
+    MethodArgument { callee: String },
+    /// `return [SELECT ...]`
+    ReturnValue,
+    /// Anything else, including assignments to targets of unknown type
+    Other,
+}
+
+/// Every inline query in `unit` with its context, in source order
+pub fn classify(unit: &CompilationUnit) -> Vec<SoqlUsage<'_>> {
+    let mut usages = Vec::new();
+    for decl in &unit.declarations {
+        match decl {
+            TypeDeclaration::Class(class) => classify_class(class, &mut usages),
+            TypeDeclaration::Trigger(trigger) => {
+                classify_body(&trigger.body, &[], &HashMap::new(), &mut usages)
+            }
+            TypeDeclaration::Interface(_) | TypeDeclaration::Enum(_) => {}
+        }
+    }
+    usages.sort_by_key(|usage| usage.span.start);
+    usages
+}
+
+fn classify_class<'a>(class: &'a ClassDeclaration, usages: &mut Vec<SoqlUsage<'a>>) {
+    let mut field_types = HashMap::new();
+    for member in &class.members {
+        match member {
+            ClassMember::Field(field) => {
+                for declarator in &field.declarators {
+                    field_types.insert(declarator.name.to_lowercase(), &field.type_ref);
+                }
+            }
+            ClassMember::Property(property) => {
+                field_types.insert(property.name.to_lowercase(), &property.type_ref);
+            }
+            _ => {}
+        }
+    }
+
+    for member in &class.members {
+        match member {
+            ClassMember::Field(field) => {
+                for declarator in &field.declarators {
+                    let Some(ref init) = declarator.initializer else {
+                        continue;
+                    };
+                    let mut queries = Queries {
+                        types: &field_types,
+                        outermost: assignment_context(&field.type_ref, &declarator.name),
+                        parents: Vec::new(),
+                        usages,
+                    };
+                    walk_expression(&mut queries, init);
+                }
+            }
+            ClassMember::Method(method) => {
+                if let Some(ref body) = method.body {
+                    classify_body(body, &method.parameters, &field_types, usages);
+                }
+            }
+            ClassMember::Constructor(ctor) => {
+                classify_body(&ctor.body, &ctor.parameters, &field_types, usages);
+            }
+            ClassMember::Property(property) => {
+                let accessors = property.getter.iter().chain(&property.setter);
+                for body in accessors.filter_map(|accessor| accessor.body.as_ref()) {
+                    classify_body(body, &[], &field_types, usages);
+                }
+            }
+            ClassMember::StaticBlock(body) => classify_body(body, &[], &field_types, usages),
+            ClassMember::InnerClass(inner) => classify_class(inner, usages),
+            ClassMember::InnerInterface(_) | ClassMember::InnerEnum(_) => {}
+        }
+    }
+}
+
+fn classify_body<'a>(
+    body: &'a Block,
+    parameters: &'a [Parameter],
+    field_types: &HashMap<String, &'a TypeRef>,
+    usages: &mut Vec<SoqlUsage<'a>>,
+) {
+    /// Declared types of the body's locals
+    struct Locals<'a>(HashMap<String, &'a TypeRef>);
+
+    impl<'a> Visitor<'a> for Locals<'a> {
+        fn visit_local_variable(&mut self, local: &'a LocalVariableDeclaration) {
+            for declarator in &local.declarators {
+                self.0
+                    .insert(declarator.name.to_lowercase(), &local.type_ref);
+            }
+        }
+
+        fn visit_for_each(&mut self, s: &'a ForEachStatement) {
+            self.0.insert(s.variable.to_lowercase(), &s.type_ref);
+        }
+    }
+
+    let mut locals = Locals(field_types.clone());
+    for parameter in parameters {
+        locals
+            .0
+            .insert(parameter.name.to_lowercase(), &parameter.type_ref);
+    }
+    walk_block(&mut locals, body);
+
+    let mut queries = Queries {
+        types: &locals.0,
+        outermost: SoqlContext::Other,
+        parents: Vec::new(),
+        usages,
+    };
+    walk_block(&mut queries, body);
+}
+
+/// Inline queries with their context, judged from the enclosing nodes
+struct Queries<'a, 'u> {
+    types: &'u HashMap<String, &'a TypeRef>,
+    /// Context of a query that nothing encloses, such as a whole field
+    /// initializer
+    outermost: SoqlContext,
+    parents: Vec<Parent<'a>>,
+    usages: &'u mut Vec<SoqlUsage<'a>>,
+}
+
+impl<'a> Visitor<'a> for Queries<'a, '_> {
+    fn visit_statement(&mut self, stmt: &'a Statement) {
+        self.parents.push(Parent::Statement(stmt));
+    }
+
+    fn leave_statement(&mut self, _: &'a Statement) {
+        self.parents.pop();
+    }
+
+    fn visit_expression(&mut self, expr: &'a Expression) {
+        if let Expression::Soql(query) = expr {
+            let context = match enclosing(expr, &self.parents) {
+                None => self.outermost.clone(),
+                Some(found) => parent_context(found, self.types),
+            };
+            self.usages.push(usage(query, context));
+        }
+        self.parents.push(Parent::Expression(expr));
+    }
+
+    fn leave_expression(&mut self, _: &'a Expression) {
+        self.parents.pop();
+    }
+}
+
+fn usage(query: &SoqlQuery, context: SoqlContext) -> SoqlUsage<'_> {
+    SoqlUsage {
+        query,
+        span: query.span,
+        context,
+    }
+}
+
+/// The nearest parent of `expr` that is not a parenthesis, and the child of
+/// that parent containing `expr`
+fn enclosing<'a, 'p>(
+    expr: &'a Expression,
+    parents: &'p [Parent<'a>],
+) -> Option<(&'a Expression, &'p Parent<'a>)> {
+    let mut child = expr;
+    for parent in parents.iter().rev() {
+        match parent {
+            Parent::Expression(outer @ Expression::Parenthesized(..)) => child = outer,
+            _ => return Some((child, parent)),
+        }
+    }
+    None
+}
+
+fn parent_context(
+    (child, parent): (&Expression, &Parent<'_>),
+    types: &HashMap<String, &TypeRef>,
+) -> SoqlContext {
+    match parent {
+        Parent::Statement(Statement::ForEach(_)) => SoqlContext::ForEachIteration,
+        Parent::Statement(Statement::Return(_)) => SoqlContext::ReturnValue,
+        Parent::Statement(Statement::LocalVariable(local)) => local
+            .declarators
+            .iter()
+            .find(|d| {
+                d.initializer
+                    .as_ref()
+                    .is_some_and(|init| std::ptr::eq(init, child))
+            })
+            .map_or(SoqlContext::Other, |d| {
+                assignment_context(&local.type_ref, &d.name)
+            }),
+        Parent::Expression(Expression::Assignment(assign))
+            if assign.operator == AssignmentOp::Assign && std::ptr::eq(&assign.value, child) =>
+        {
+            let name = match assign.target {
+                Expression::Identifier(ref name, _, _) => name,
+                Expression::FieldAccess(ref access)
+                    if matches!(access.object, Expression::This(..)) =>
+                {
+                    &access.field
+                }
+                _ => return SoqlContext::Other,
+            };
+            types
+                .get(&name.to_lowercase())
+                .map_or(SoqlContext::Other, |ty| assignment_context(ty, name))
+        }
+        Parent::Expression(Expression::MethodCall(call))
+            if !call.object.as_ref().is_some_and(|o| std::ptr::eq(o, child)) =>
+        {
+            let callee = match call.object.as_ref().and_then(dotted_name) {
+                Some(object) => format!("{}.{}", object, call.name),
+                None => call.name.clone(),
+            };
+            SoqlContext::MethodArgument { callee }
+        }
+        Parent::Expression(Expression::New(new)) => SoqlContext::MethodArgument {
+            callee: format!("new {}", new.type_ref.name),
+        },
+        _ => SoqlContext::Other,
+    }
+}
+
+/// Context of a query assigned to a variable of the given declared type
+fn assignment_context(type_ref: &TypeRef, var: &str) -> SoqlContext {
+    let var = var.to_string();
+    if type_ref.is_array || type_ref.name.eq_ignore_ascii_case("List") {
+        SoqlContext::ListAssignment { var }
+    } else if is_single_record_type(type_ref) {
+        SoqlContext::SingleRecordAssignment { var }
+    } else {
+        SoqlContext::Other
+    }
+}
+
+/// Whether a variable of this type holds a single query row: not a
+/// collection, and not the Integer a `SELECT COUNT()` assigns
+fn is_single_record_type(type_ref: &TypeRef) -> bool {
+    let name = type_ref.name.to_lowercase();
+    !matches!(name.as_str(), "set" | "map" | "integer" | "object")
+}
+
+/// `a.b.c` for a chain of identifiers, `None` for anything else
+fn dotted_name(expr: &Expression) -> Option<String> {
+    match expr {
+        Expression::Identifier(name, _, _) => Some(name.clone()),
+        Expression::This(..) => Some("this".to_string()),
+        Expression::FieldAccess(access) => {
+            Some(format!("{}.{}", dotted_name(&access.object)?, access.field))
+        }
+        _ => None,
+    }
+}
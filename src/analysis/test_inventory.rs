@@ -0,0 +1,169 @@
+//! Inventory of Apex test classes and methods
+//!
+//! Used by CI to decide which classes can run in parallel. A method is a test
+//! only when it says so, either with `@isTest` or the legacy `testMethod`
+//! modifier; living in an `@isTest` class is not enough, since test classes
+//! routinely hold setup helpers.
+
+use crate::ast::{
+    Annotation, ClassDeclaration, ClassMember, CompilationUnit, Expression, MethodDeclaration,
+    TypeDeclaration,
+};
+use crate::lexer::Span;
+
+/// A class that is annotated `@isTest` or declares test methods
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestClass {
+    /// Class name, dotted for inner classes (e.g. "Outer.Inner")
+    pub name: String,
+    /// Whether the class itself carries `@isTest`
+    pub is_test_class: bool,
+    /// `@isTest(isParallel=true)`
+    pub is_parallel: bool,
+    /// `@isTest(seeAllData=true)`
+    pub see_all_data: bool,
+    pub methods: Vec<TestMethod>,
+    pub span: Span,
+}
+
+/// A method marked as a test
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestMethod {
+    pub name: String,
+    pub marker: TestMarker,
+    /// `@isTest(seeAllData=true)` on the method itself
+    pub see_all_data: bool,
+    pub span: Span,
+}
+
+/// How a method declares itself a test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestMarker {
+    /// `@isTest`
+    Annotation,
+    /// Legacy `testMethod` modifier
+    Modifier,
+}
+
+/// A test method Apex would reject
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestDiagnostic {
+    pub class: String,
+    pub method: String,
+    pub message: String,
+    pub span: Span,
+}
+
+/// Test classes found in a compilation unit, plus signature problems
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TestInventory {
+    pub classes: Vec<TestClass>,
+    pub diagnostics: Vec<TestDiagnostic>,
+}
+
+impl TestInventory {
+    /// Look up a test class by name (case-insensitive)
+    pub fn get_class(&self, name: &str) -> Option<&TestClass> {
+        self.classes
+            .iter()
+            .find(|class| class.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Collect the test classes and methods declared in `unit`
+pub fn find_tests(unit: &CompilationUnit) -> TestInventory {
+    let mut inventory = TestInventory::default();
+    for declaration in &unit.declarations {
+        if let TypeDeclaration::Class(class) = declaration {
+            collect_class(class, &class.name, &mut inventory);
+        }
+    }
+    inventory
+}
+
+fn collect_class(class: &ClassDeclaration, name: &str, inventory: &mut TestInventory) {
+    let is_test = find_is_test(&class.annotations);
+    let mut methods = Vec::new();
+
+    for member in &class.members {
+        match member {
+            ClassMember::Method(method) => {
+                if let Some(test) = test_method(method) {
+                    check_signature(method, name, &mut inventory.diagnostics);
+                    methods.push(test);
+                }
+            }
+            ClassMember::InnerClass(inner) => {
+                collect_class(inner, &format!("{}.{}", name, inner.name), inventory);
+            }
+            _ => {}
+        }
+    }
+
+    if is_test.is_none() && methods.is_empty() {
+        return;
+    }
+    inventory.classes.push(TestClass {
+        name: name.to_string(),
+        is_test_class: is_test.is_some(),
+        is_parallel: is_test.is_some_and(|a| flag(a, "isParallel")),
+        see_all_data: is_test.is_some_and(|a| flag(a, "seeAllData")),
+        methods,
+        span: class.span,
+    });
+}
+
+fn test_method(method: &MethodDeclaration) -> Option<TestMethod> {
+    let annotation = find_is_test(&method.annotations);
+    let marker = if annotation.is_some() {
+        TestMarker::Annotation
+    } else if method.modifiers.is_testmethod {
+        TestMarker::Modifier
+    } else {
+        return None;
+    };
+    Some(TestMethod {
+        name: method.name.clone(),
+        marker,
+        see_all_data: annotation.is_some_and(|a| flag(a, "seeAllData")),
+        span: method.span,
+    })
+}
+
+fn check_signature(method: &MethodDeclaration, class: &str, diagnostics: &mut Vec<TestDiagnostic>) {
+    let mut problems = Vec::new();
+    if !method.modifiers.is_static {
+        problems.push("must be static");
+    }
+    if !method.return_type.name.eq_ignore_ascii_case("void") {
+        problems.push("must return void");
+    }
+    if !method.parameters.is_empty() {
+        problems.push("must not take parameters");
+    }
+
+    for problem in problems {
+        diagnostics.push(TestDiagnostic {
+            class: class.to_string(),
+            method: method.name.clone(),
+            message: format!("test method '{}' {}", method.name, problem),
+            span: method.span,
+        });
+    }
+}
+
+fn find_is_test(annotations: &[Annotation]) -> Option<&Annotation> {
+    annotations
+        .iter()
+        .find(|a| a.name.eq_ignore_ascii_case("isTest"))
+}
+
+/// Value of a boolean annotation parameter, false when absent
+fn flag(annotation: &Annotation, name: &str) -> bool {
+    annotation.parameters.iter().any(|p| {
+        p.name
+            .as_deref()
+            .is_some_and(|n| n.eq_ignore_ascii_case(name))
+            && matches!(p.value, Expression::Boolean(true, _, _))
+    })
+}
@@ -1,13 +1,34 @@
+pub mod fold;
+pub mod node_id;
+pub mod printer;
+pub mod visitor;
+
+pub use node_id::{NodeId, NodeIdMap, SideTable};
+pub use visitor::Visitor;
+
 use crate::lexer::Span;
 
 /// A compilation unit - the top-level AST node representing a single Apex file
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CompilationUnit {
     pub declarations: Vec<TypeDeclaration>,
+    /// Id for the next node added to the unit; every id below it is taken
+    pub next_id: NodeId,
+}
+
+impl CompilationUnit {
+    /// A fresh id for a node built after parsing
+    pub fn next_node_id(&mut self) -> NodeId {
+        let id = self.next_id;
+        self.next_id = NodeId(id.0 + 1);
+        id
+    }
 }
 
 /// A type declaration (class, interface, enum, or trigger)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TypeDeclaration {
     Class(ClassDeclaration),
     Interface(InterfaceDeclaration),
@@ -17,6 +38,7 @@ pub enum TypeDeclaration {
 
 /// Access modifiers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AccessModifier {
     #[default]
     Private,
@@ -27,6 +49,11 @@ pub enum AccessModifier {
 
 /// Sharing modifiers for classes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum SharingModifier {
     WithSharing,
     WithoutSharing,
@@ -35,6 +62,7 @@ pub enum SharingModifier {
 
 /// Class modifiers
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassModifiers {
     pub access: AccessModifier,
     pub is_abstract: bool,
@@ -44,6 +72,7 @@ pub struct ClassModifiers {
 
 /// Method/property modifiers (also used for inner classes)
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemberModifiers {
     pub access: AccessModifier,
     pub is_static: bool,
@@ -59,13 +88,16 @@ pub struct MemberModifiers {
 
 /// An annotation (e.g., @isTest, @AuraEnabled)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Annotation {
     pub name: String,
     pub parameters: Vec<AnnotationParameter>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnnotationParameter {
     pub name: Option<String>,
     pub value: Expression,
@@ -73,6 +105,7 @@ pub struct AnnotationParameter {
 
 /// Class declaration
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassDeclaration {
     pub annotations: Vec<Annotation>,
     pub modifiers: ClassModifiers,
@@ -82,10 +115,12 @@ pub struct ClassDeclaration {
     pub implements: Vec<TypeRef>,
     pub members: Vec<ClassMember>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Interface declaration
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterfaceDeclaration {
     pub annotations: Vec<Annotation>,
     pub access: AccessModifier,
@@ -94,29 +129,35 @@ pub struct InterfaceDeclaration {
     pub extends: Vec<TypeRef>,
     pub members: Vec<InterfaceMember>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Enum declaration
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnumDeclaration {
     pub annotations: Vec<Annotation>,
     pub access: AccessModifier,
     pub name: String,
     pub values: Vec<String>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Trigger declaration
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TriggerDeclaration {
     pub name: String,
     pub object: String,
     pub events: Vec<TriggerEvent>,
     pub body: Block,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TriggerEvent {
     BeforeInsert,
     BeforeUpdate,
@@ -129,18 +170,22 @@ pub enum TriggerEvent {
 
 /// Type parameter (generics)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TypeParameter {
     pub name: String,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Type reference (e.g., String, List<Account>, Map<String, Integer>)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TypeRef {
     pub name: String,
     pub type_arguments: Vec<TypeRef>,
     pub is_array: bool,
     pub span: Span,
+    pub id: NodeId,
 }
 
 impl TypeRef {
@@ -150,12 +195,14 @@ impl TypeRef {
             type_arguments: vec![],
             is_array: false,
             span,
+            id: NodeId::DUMMY,
         }
     }
 }
 
 /// Class member (field, method, property, constructor, inner class, etc.)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ClassMember {
     Field(FieldDeclaration),
     Method(MethodDeclaration),
@@ -169,30 +216,36 @@ pub enum ClassMember {
 
 /// Interface member (method signature)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InterfaceMember {
     Method(MethodSignature),
 }
 
 /// Field declaration
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FieldDeclaration {
     pub annotations: Vec<Annotation>,
     pub modifiers: MemberModifiers,
     pub type_ref: TypeRef,
     pub declarators: Vec<VariableDeclarator>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Variable declarator (name and optional initializer)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VariableDeclarator {
     pub name: String,
     pub initializer: Option<Expression>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Method declaration
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MethodDeclaration {
     pub annotations: Vec<Annotation>,
     pub modifiers: MemberModifiers,
@@ -202,20 +255,95 @@ pub struct MethodDeclaration {
     pub parameters: Vec<Parameter>,
     pub body: Option<Block>,
     pub span: Span,
+    pub id: NodeId,
+}
+
+/// How the platform runs a method asynchronously
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AsyncKind {
+    /// `@future`, with `callout=true` when it may make HTTP callouts
+    Future { callout: bool },
+    /// `execute(QueueableContext)` of a `Queueable` class
+    Queueable,
+    /// `start`, `execute` or `finish` of a `Database.Batchable` class
+    Batch,
+    /// `execute(SchedulableContext)` of a `Schedulable` class
+    Schedulable,
+}
+
+impl MethodDeclaration {
+    /// Async entry point this method is, given the interfaces its class
+    /// implements, or `None` for a method that runs synchronously
+    pub fn async_kind(&self, implements: &[TypeRef]) -> Option<AsyncKind> {
+        if let Some(future) = self
+            .annotations
+            .iter()
+            .find(|a| a.name.eq_ignore_ascii_case("future"))
+        {
+            let callout = future.parameters.iter().any(|p| {
+                p.name
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case("callout"))
+                    && matches!(p.value, Expression::Boolean(true, _, _))
+            });
+            return Some(AsyncKind::Future { callout });
+        }
+
+        let implements_any = |names: &[&str]| {
+            implements
+                .iter()
+                .any(|t| names.iter().any(|n| t.name.eq_ignore_ascii_case(n)))
+        };
+        // A class may implement several of these interfaces, each with its
+        // own `execute`, so the context parameter tells them apart
+        let context = self
+            .parameters
+            .first()
+            .map(|p| p.type_ref.name.to_lowercase());
+        let context = context.as_deref().map(|c| {
+            c.trim_start_matches("database.")
+                .trim_start_matches("system.")
+        });
+        let name = self.name.to_lowercase();
+        let is_execute = name == "execute" && self.parameters.len() == 1;
+
+        if context == Some("batchablecontext")
+            && implements_any(&["Database.Batchable"])
+            && matches!(name.as_str(), "start" | "execute" | "finish")
+        {
+            Some(AsyncKind::Batch)
+        } else if is_execute
+            && context == Some("queueablecontext")
+            && implements_any(&["Queueable", "System.Queueable"])
+        {
+            Some(AsyncKind::Queueable)
+        } else if is_execute
+            && context == Some("schedulablecontext")
+            && implements_any(&["Schedulable", "System.Schedulable"])
+        {
+            Some(AsyncKind::Schedulable)
+        } else {
+            None
+        }
+    }
 }
 
 /// Method signature (for interfaces)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MethodSignature {
     pub annotations: Vec<Annotation>,
     pub return_type: TypeRef,
     pub name: String,
     pub parameters: Vec<Parameter>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Constructor declaration
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConstructorDeclaration {
     pub annotations: Vec<Annotation>,
     pub modifiers: MemberModifiers,
@@ -225,17 +353,21 @@ pub struct ConstructorDeclaration {
     pub chained_constructor: Option<ConstructorChain>,
     pub body: Block,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Constructor chaining call: this(...) or super(...)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConstructorChain {
     pub kind: ConstructorChainKind,
     pub arguments: Vec<Expression>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConstructorChainKind {
     This,
     Super,
@@ -243,6 +375,7 @@ pub enum ConstructorChainKind {
 
 /// Property declaration
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PropertyDeclaration {
     pub annotations: Vec<Annotation>,
     pub modifiers: MemberModifiers,
@@ -251,34 +384,43 @@ pub struct PropertyDeclaration {
     pub getter: Option<PropertyAccessor>,
     pub setter: Option<PropertyAccessor>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PropertyAccessor {
+    /// Access is the property's unless the accessor names its own
     pub modifiers: MemberModifiers,
     pub body: Option<Block>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Method/constructor parameter
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Parameter {
     pub annotations: Vec<Annotation>,
     pub is_final: bool,
     pub type_ref: TypeRef,
     pub name: String,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Block of statements
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
     pub statements: Vec<Statement>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Statement
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Statement {
     Block(Block),
     LocalVariable(LocalVariableDeclaration),
@@ -295,45 +437,104 @@ pub enum Statement {
     Continue(ContinueStatement),
     Try(TryStatement),
     Dml(DmlStatement),
-    Empty(Span),
+    RunAs(RunAsStatement),
+    Labeled(LabeledStatement),
+    Empty(Span, NodeId),
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Block(s) => s.span,
+            Statement::LocalVariable(s) => s.span,
+            Statement::Expression(s) => s.span,
+            Statement::If(s) => s.span,
+            Statement::For(s) => s.span,
+            Statement::ForEach(s) => s.span,
+            Statement::While(s) => s.span,
+            Statement::DoWhile(s) => s.span,
+            Statement::Switch(s) => s.span,
+            Statement::Return(s) => s.span,
+            Statement::Throw(s) => s.span,
+            Statement::Break(s) => s.span,
+            Statement::Continue(s) => s.span,
+            Statement::Try(s) => s.span,
+            Statement::Dml(s) => s.span,
+            Statement::RunAs(s) => s.span,
+            Statement::Labeled(s) => s.span,
+            Statement::Empty(s, _) => *s,
+        }
+    }
+
+    pub fn id(&self) -> NodeId {
+        match self {
+            Statement::Block(s) => s.id,
+            Statement::LocalVariable(s) => s.id,
+            Statement::Expression(s) => s.id,
+            Statement::If(s) => s.id,
+            Statement::For(s) => s.id,
+            Statement::ForEach(s) => s.id,
+            Statement::While(s) => s.id,
+            Statement::DoWhile(s) => s.id,
+            Statement::Switch(s) => s.id,
+            Statement::Return(s) => s.id,
+            Statement::Throw(s) => s.id,
+            Statement::Break(s) => s.id,
+            Statement::Continue(s) => s.id,
+            Statement::Try(s) => s.id,
+            Statement::Dml(s) => s.id,
+            Statement::RunAs(s) => s.id,
+            Statement::Labeled(s) => s.id,
+            Statement::Empty(_, id) => *id,
+        }
+    }
 }
 
 /// Local variable declaration
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LocalVariableDeclaration {
     pub is_final: bool,
     pub type_ref: TypeRef,
     pub declarators: Vec<VariableDeclarator>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Expression statement
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExpressionStatement {
     pub expression: Expression,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// If statement
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IfStatement {
     pub condition: Expression,
     pub then_branch: Box<Statement>,
     pub else_branch: Option<Box<Statement>>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Traditional for loop
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForStatement {
     pub init: Option<ForInit>,
     pub condition: Option<Expression>,
     pub update: Vec<Expression>,
     pub body: Box<Statement>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ForInit {
     Variables(LocalVariableDeclaration),
     Expressions(Vec<Expression>),
@@ -341,46 +542,57 @@ pub enum ForInit {
 
 /// For-each loop
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForEachStatement {
     pub type_ref: TypeRef,
     pub variable: String,
     pub iterable: Expression,
     pub body: Box<Statement>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// While loop
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WhileStatement {
     pub condition: Expression,
     pub body: Box<Statement>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Do-while loop
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DoWhileStatement {
     pub body: Box<Statement>,
     pub condition: Expression,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Switch statement (Apex-style with when)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SwitchStatement {
     pub expression: Expression,
     pub when_clauses: Vec<WhenClause>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WhenClause {
     pub values: WhenValue,
     pub block: Block,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WhenValue {
     Literals(Vec<Expression>),
     Type { type_ref: TypeRef, variable: String },
@@ -389,57 +601,102 @@ pub enum WhenValue {
 
 /// Return statement
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReturnStatement {
     pub value: Option<Expression>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Throw statement
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ThrowStatement {
     pub exception: Expression,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Break statement
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BreakStatement {
+    /// Target of `break outer;`
+    pub label: Option<String>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Continue statement
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ContinueStatement {
+    /// Target of `continue outer;`
+    pub label: Option<String>,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+/// A loop or block with a label (`outer: for (...) { ... }`) that
+/// `break`/`continue` can name
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabeledStatement {
+    pub label: String,
+    pub body: Box<Statement>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// Try statement
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TryStatement {
     pub try_block: Block,
     pub catch_clauses: Vec<CatchClause>,
     pub finally_block: Option<Block>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchClause {
+    /// The caught type, or the first of a union such as
+    /// `DmlException | QueryException`
     pub exception_type: TypeRef,
+    /// The remaining types of a union, in source order
+    pub alternative_types: Vec<TypeRef>,
     pub variable: String,
     pub block: Block,
     pub span: Span,
+    pub id: NodeId,
+}
+
+impl CatchClause {
+    /// Every caught type, starting with `exception_type`
+    pub fn exception_types(&self) -> impl Iterator<Item = &TypeRef> {
+        std::iter::once(&self.exception_type).chain(&self.alternative_types)
+    }
 }
 
 /// DML statement
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DmlStatement {
     pub operation: DmlOperation,
     pub expression: Expression,
     pub access_level: Option<DmlAccessLevel>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum DmlOperation {
     Insert,
     Update,
@@ -451,26 +708,38 @@ pub enum DmlOperation {
 
 /// DML access level (as system / as user)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DmlAccessLevel {
     System,
     User,
 }
 
+/// `System.runAs(user) { ... }`, which runs a test block as another user
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunAsStatement {
+    pub user: Expression,
+    pub body: Block,
+    pub span: Span,
+    pub id: NodeId,
+}
+
 /// Expression
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expression {
-    // Literals
-    Null(Span),
-    Boolean(bool, Span),
-    Integer(i64, Span),
-    Long(i64, Span),
-    Double(f64, Span),
-    String(String, Span),
+    // Literals; numeric ones keep their source text (`0x1F`, `1.50`)
+    Null(Span, NodeId),
+    Boolean(bool, Span, NodeId),
+    Integer(i64, String, Span, NodeId),
+    Long(i64, String, Span, NodeId),
+    Double(f64, String, Span, NodeId),
+    String(String, Span, NodeId),
 
     // Identifiers and access
-    Identifier(String, Span),
-    This(Span),
-    Super(Span),
+    Identifier(String, Span, NodeId),
+    This(Span, NodeId),
+    Super(Span, NodeId),
 
     // Member access
     FieldAccess(Box<FieldAccessExpr>),
@@ -497,42 +766,48 @@ pub enum Expression {
     Assignment(Box<AssignmentExpr>),
 
     // Increment/Decrement
-    PostIncrement(Box<Expression>, Span),
-    PostDecrement(Box<Expression>, Span),
-    PreIncrement(Box<Expression>, Span),
-    PreDecrement(Box<Expression>, Span),
+    PostIncrement(Box<Expression>, Span, NodeId),
+    PostDecrement(Box<Expression>, Span, NodeId),
+    PreIncrement(Box<Expression>, Span, NodeId),
+    PreDecrement(Box<Expression>, Span, NodeId),
 
     // SOQL/SOSL
     Soql(Box<SoqlQuery>),
     Sosl(Box<SoslQuery>),
+    /// `(SELECT ...)` after `IN` or `NOT IN` in a SOQL WHERE clause: a
+    /// semi-join or anti-join
+    SoqlSubquery(Box<SoqlQuery>),
 
     // SOQL bind variable (:varName)
-    BindVariable(String, Span),
+    BindVariable(String, Span, NodeId),
+    // SOQL bind of anything more than a variable path (:ids.keySet())
+    BindExpression(Box<Expression>, Span, NodeId),
 
     // Parenthesized
-    Parenthesized(Box<Expression>, Span),
+    Parenthesized(Box<Expression>, Span, NodeId),
 
-    // List/Set/Map literals
-    ListLiteral(Vec<Expression>, Span),
-    SetLiteral(Vec<Expression>, Span),
-    MapLiteral(Vec<(Expression, Expression)>, Span),
+    // List/Set/Map literals, with the collection type written after `new`
+    // (none for `[a, b]`)
+    ListLiteral(Vec<Expression>, Option<TypeRef>, Span, NodeId),
+    SetLiteral(Vec<Expression>, Option<TypeRef>, Span, NodeId),
+    MapLiteral(Vec<(Expression, Expression)>, Option<TypeRef>, Span, NodeId),
 
     // Type literal (e.g., List<Account>.class, String.class)
-    TypeLiteral(TypeRef, Span),
+    TypeLiteral(TypeRef, Span, NodeId),
 }
 
 impl Expression {
     pub fn span(&self) -> Span {
         match self {
-            Expression::Null(s) => *s,
-            Expression::Boolean(_, s) => *s,
-            Expression::Integer(_, s) => *s,
-            Expression::Long(_, s) => *s,
-            Expression::Double(_, s) => *s,
-            Expression::String(_, s) => *s,
-            Expression::Identifier(_, s) => *s,
-            Expression::This(s) => *s,
-            Expression::Super(s) => *s,
+            Expression::Null(s, _) => *s,
+            Expression::Boolean(_, s, _) => *s,
+            Expression::Integer(.., s, _) => *s,
+            Expression::Long(.., s, _) => *s,
+            Expression::Double(.., s, _) => *s,
+            Expression::String(_, s, _) => *s,
+            Expression::Identifier(_, s, _) => *s,
+            Expression::This(s, _) => *s,
+            Expression::Super(s, _) => *s,
             Expression::FieldAccess(e) => e.span,
             Expression::ArrayAccess(e) => e.span,
             Expression::SafeNavigation(e) => e.span,
@@ -547,82 +822,143 @@ impl Expression {
             Expression::Instanceof(e) => e.span,
             Expression::Cast(e) => e.span,
             Expression::Assignment(e) => e.span,
-            Expression::PostIncrement(_, s) => *s,
-            Expression::PostDecrement(_, s) => *s,
-            Expression::PreIncrement(_, s) => *s,
-            Expression::PreDecrement(_, s) => *s,
+            Expression::PostIncrement(_, s, _) => *s,
+            Expression::PostDecrement(_, s, _) => *s,
+            Expression::PreIncrement(_, s, _) => *s,
+            Expression::PreDecrement(_, s, _) => *s,
             Expression::Soql(e) => e.span,
             Expression::Sosl(e) => e.span,
-            Expression::BindVariable(_, s) => *s,
-            Expression::Parenthesized(_, s) => *s,
-            Expression::ListLiteral(_, s) => *s,
-            Expression::SetLiteral(_, s) => *s,
-            Expression::MapLiteral(_, s) => *s,
-            Expression::TypeLiteral(_, s) => *s,
+            Expression::SoqlSubquery(e) => e.span,
+            Expression::BindVariable(_, s, _) => *s,
+            Expression::BindExpression(_, s, _) => *s,
+            Expression::Parenthesized(_, s, _) => *s,
+            Expression::ListLiteral(.., s, _) => *s,
+            Expression::SetLiteral(.., s, _) => *s,
+            Expression::MapLiteral(.., s, _) => *s,
+            Expression::TypeLiteral(_, s, _) => *s,
+        }
+    }
+
+    pub fn id(&self) -> NodeId {
+        match self {
+            Expression::Null(.., id) => *id,
+            Expression::Boolean(.., id) => *id,
+            Expression::Integer(.., id) => *id,
+            Expression::Long(.., id) => *id,
+            Expression::Double(.., id) => *id,
+            Expression::String(.., id) => *id,
+            Expression::Identifier(.., id) => *id,
+            Expression::This(.., id) => *id,
+            Expression::Super(.., id) => *id,
+            Expression::FieldAccess(e) => e.id,
+            Expression::ArrayAccess(e) => e.id,
+            Expression::SafeNavigation(e) => e.id,
+            Expression::MethodCall(e) => e.id,
+            Expression::New(e) => e.id,
+            Expression::NewArray(e) => e.id,
+            Expression::NewMap(e) => e.id,
+            Expression::Unary(e) => e.id,
+            Expression::Binary(e) => e.id,
+            Expression::Ternary(e) => e.id,
+            Expression::NullCoalesce(e) => e.id,
+            Expression::Instanceof(e) => e.id,
+            Expression::Cast(e) => e.id,
+            Expression::Assignment(e) => e.id,
+            Expression::PostIncrement(.., id) => *id,
+            Expression::PostDecrement(.., id) => *id,
+            Expression::PreIncrement(.., id) => *id,
+            Expression::PreDecrement(.., id) => *id,
+            Expression::Soql(e) => e.id,
+            Expression::Sosl(e) => e.id,
+            Expression::SoqlSubquery(e) => e.id,
+            Expression::BindVariable(.., id) => *id,
+            Expression::BindExpression(.., id) => *id,
+            Expression::Parenthesized(.., id) => *id,
+            Expression::ListLiteral(.., id) => *id,
+            Expression::SetLiteral(.., id) => *id,
+            Expression::MapLiteral(.., id) => *id,
+            Expression::TypeLiteral(.., id) => *id,
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FieldAccessExpr {
     pub object: Expression,
     pub field: String,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArrayAccessExpr {
     pub array: Expression,
     pub index: Expression,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SafeNavigationExpr {
     pub object: Expression,
     pub field: String,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MethodCallExpr {
     pub object: Option<Expression>,
     pub name: String,
     pub type_arguments: Vec<TypeRef>,
     pub arguments: Vec<Expression>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewExpr {
     pub type_ref: TypeRef,
     pub arguments: Vec<Expression>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewArrayExpr {
     pub element_type: TypeRef,
     pub size: Option<Expression>,
     pub initializer: Option<Vec<Expression>>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewMapExpr {
     pub type_ref: TypeRef,
     pub initializer: Option<Vec<(Expression, Expression)>>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnaryExpr {
     pub operator: UnaryOp,
     pub operand: Expression,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnaryOp {
     Negate,     // -
     Not,        // !
@@ -630,14 +966,17 @@ pub enum UnaryOp {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BinaryExpr {
     pub left: Expression,
     pub operator: BinaryOp,
     pub right: Expression,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryOp {
     // Arithmetic
     Add,
@@ -669,6 +1008,8 @@ pub enum BinaryOp {
     UnsignedRightShift,
 
     // SOQL-specific
+    /// A string literal pattern keeps its `\\`, `\%` and `\_` escapes, so
+    /// a literal backslash stays apart from one escaping a wildcard
     Like,
     In,
     NotIn,
@@ -677,43 +1018,54 @@ pub enum BinaryOp {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TernaryExpr {
     pub condition: Expression,
     pub then_expr: Expression,
     pub else_expr: Expression,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NullCoalesceExpr {
     pub left: Expression,
     pub right: Expression,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InstanceofExpr {
     pub expression: Expression,
     pub type_ref: TypeRef,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CastExpr {
     pub type_ref: TypeRef,
     pub expression: Expression,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AssignmentExpr {
     pub target: Expression,
     pub operator: AssignmentOp,
     pub value: Expression,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AssignmentOp {
     Assign,
     AddAssign,
@@ -731,22 +1083,102 @@ pub enum AssignmentOp {
 
 /// SOQL Query
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoqlQuery {
     pub select_clause: Vec<SelectField>,
     pub from_clause: String,
+    /// `USING SCOPE mine` and the like; Salesforce-only
+    pub using_scope: Option<String>,
     pub where_clause: Option<Expression>,
     pub with_clause: Option<SoqlWithClause>,
     pub group_by_clause: Vec<String>,
+    /// `GROUP BY ROLLUP(...)` or `CUBE(...)` over `group_by_clause`
+    pub grouping_sets: Option<GroupingSets>,
     pub having_clause: Option<Expression>,
     pub order_by_clause: Vec<OrderByField>,
     pub limit_clause: Option<Expression>,
     pub offset_clause: Option<Expression>,
     pub for_clause: Option<ForClause>,
     pub span: Span,
+    pub id: NodeId,
+}
+
+impl SoqlQuery {
+    /// Whether this fetches at most one record by primary key:
+    /// `WHERE Id = :recordId` (or a literal Id), optionally `LIMIT 1`, with
+    /// no other filter, grouping, aggregate or offset
+    pub fn is_single_record_by_id(&self) -> bool {
+        let mut filter = match self.where_clause {
+            Some(ref filter) => filter,
+            None => return false,
+        };
+        while let Expression::Parenthesized(inner, _, _) = filter {
+            filter = inner;
+        }
+        let by_id = match filter {
+            Expression::Binary(binary) => match (&binary.left, &binary.right) {
+                (
+                    Expression::Identifier(field, _, _),
+                    Expression::BindVariable(..) | Expression::String(..),
+                ) => {
+                    matches!(binary.operator, BinaryOp::Equal | BinaryOp::ExactEqual)
+                        && field.eq_ignore_ascii_case("Id")
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+        let limit_one = match self.limit_clause {
+            None => true,
+            Some(Expression::Integer(limit, _, _, _)) => limit == 1,
+            Some(_) => false,
+        };
+        by_id && limit_one && self.offset_clause.is_none() && !self.is_aggregate()
+    }
+
+    /// Whether this is `SELECT COUNT() FROM ...`, which returns the number
+    /// of matching records rather than the records
+    pub fn is_count_query(&self) -> bool {
+        let [SelectField::AggregateFunction { name, field, .. }] = &self.select_clause[..] else {
+            return false;
+        };
+        name.eq_ignore_ascii_case("COUNT") && field.is_empty() && self.group_by_clause.is_empty()
+    }
+
+    /// Whether rows are groups or aggregates over the matching records
+    /// rather than the records themselves
+    pub fn is_aggregate(&self) -> bool {
+        !self.group_by_clause.is_empty()
+            || self
+                .select_clause
+                .iter()
+                .any(|field| matches!(field, SelectField::AggregateFunction { .. }))
+    }
+}
+
+/// Subtotal rows added by `GROUP BY ROLLUP(...)` or `GROUP BY CUBE(...)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroupingSets {
+    /// Subtotals for each prefix of the grouped fields, plus a grand total
+    Rollup,
+    /// Subtotals for every combination of the grouped fields
+    Cube,
+}
+
+impl GroupingSets {
+    /// The keyword before the parenthesized fields
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupingSets::Rollup => "ROLLUP",
+            GroupingSets::Cube => "CUBE",
+        }
+    }
 }
 
 /// SOQL WITH clause for security/sharing enforcement
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SoqlWithClause {
     SecurityEnforced,
     UserMode,
@@ -754,6 +1186,7 @@ pub enum SoqlWithClause {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SelectField {
     Field(String),
     SubQuery(Box<SoqlQuery>),
@@ -763,9 +1196,43 @@ pub enum SelectField {
         field: String,
         alias: Option<String>,
     },
+    /// `FIELDS(ALL)`, `FIELDS(STANDARD)` or `FIELDS(CUSTOM)`
+    Fields(FieldsScope),
+}
+
+/// Which fields a `FIELDS()` select item stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FieldsScope {
+    All,
+    Standard,
+    Custom,
+}
+
+impl FieldsScope {
+    /// The keyword inside the parentheses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldsScope::All => "ALL",
+            FieldsScope::Standard => "STANDARD",
+            FieldsScope::Custom => "CUSTOM",
+        }
+    }
+
+    /// Whether the field with this API name is selected, telling custom
+    /// fields apart by their `__c` suffix
+    pub fn includes(&self, field: &str) -> bool {
+        let custom = field.to_ascii_lowercase().ends_with("__c");
+        match self {
+            FieldsScope::All => true,
+            FieldsScope::Standard => !custom,
+            FieldsScope::Custom => custom,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TypeOfClause {
     pub field: String,
     pub when_clauses: Vec<TypeOfWhen>,
@@ -773,12 +1240,14 @@ pub struct TypeOfClause {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TypeOfWhen {
     pub type_name: String,
     pub fields: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrderByField {
     pub field: String,
     pub ascending: bool,
@@ -786,6 +1255,7 @@ pub struct OrderByField {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ForClause {
     View,
     Reference,
@@ -794,6 +1264,7 @@ pub enum ForClause {
 
 /// SOSL Query
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoslQuery {
     pub search_term: String,
     pub search_group: Option<SearchGroup>,
@@ -801,9 +1272,11 @@ pub struct SoslQuery {
     pub with_clauses: Vec<SoslWithClause>,
     pub limit_clause: Option<Expression>,
     pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SearchGroup {
     AllFields,
     NameFields,
@@ -813,6 +1286,7 @@ pub enum SearchGroup {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoslReturning {
     pub object: String,
     pub fields: Vec<String>,
@@ -822,6 +1296,7 @@ pub struct SoslReturning {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SoslWithClause {
     DataCategory(String, String), // Category group and category
     Network(String),
@@ -0,0 +1,706 @@
+//! Rewriting a compilation unit by value
+//!
+//! A [`Folder`] takes each node and returns its replacement. The default
+//! methods rebuild the node from its folded children through the `fold_*`
+//! functions of this module, so an override handles the nodes it cares
+//! about and calls the matching function to keep descending:
+//!
+//! ```
+//! use apexrust::ast::fold::{self, Folder};
+//! use apexrust::{parse, Expression};
+//!
+//! /// `System.debug(...)` calls become `Log.debug(...)`
+//! struct Logging;
+//!
+//! impl Folder for Logging {
+//!     fn fold_expression(&mut self, expr: Expression) -> Expression {
+//!         match fold::fold_expression(self, expr) {
+//!             Expression::MethodCall(mut call) if call.name == "debug" => {
+//!                 if let Some(Expression::Identifier(ref mut class, ..)) = call.object {
+//!                     if class == "System" {
+//!                         *class = "Log".to_string();
+//!                     }
+//!                 }
+//!                 Expression::MethodCall(call)
+//!             }
+//!             other => other,
+//!         }
+//!     }
+//! }
+//!
+//! let unit = parse("public class A { void m() { System.debug('x'); } }").unwrap();
+//! let unit = Logging.fold_compilation_unit(unit);
+//! ```
+//!
+//! A bind to a variable path, `:acc.Name`, is folded as the `Identifier`
+//! and `FieldAccess` expressions it reads, then turned back into a
+//! `BindVariable`, or a `BindExpression` if the rewrite made it more than
+//! a path; renaming a variable or field therefore reaches SOQL too.
+//!
+//! Every [`NodeId`] passes through [`Folder::fold_node_id`], parents before
+//! children in source order, and is kept unless that is overridden. A
+//! folder that builds nodes can give them [`NodeId::DUMMY`] and run through
+//! [`fold_with_ids`], which numbers them and any subtree the folder copied.
+
+use super::*;
+use crate::parser::bind_path;
+
+/// Node-by-node rewriting; each method returns the node it is given,
+/// rebuilt from folded children, unless overridden
+pub trait Folder {
+    fn fold_compilation_unit(&mut self, unit: CompilationUnit) -> CompilationUnit {
+        fold_compilation_unit(self, unit)
+    }
+
+    fn fold_type_declaration(&mut self, declaration: TypeDeclaration) -> TypeDeclaration {
+        fold_type_declaration(self, declaration)
+    }
+
+    fn fold_class(&mut self, class: ClassDeclaration) -> ClassDeclaration {
+        fold_class(self, class)
+    }
+
+    fn fold_class_member(&mut self, member: ClassMember) -> ClassMember {
+        fold_class_member(self, member)
+    }
+
+    fn fold_block(&mut self, block: Block) -> Block {
+        fold_block(self, block)
+    }
+
+    fn fold_statement(&mut self, stmt: Statement) -> Statement {
+        fold_statement(self, stmt)
+    }
+
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        fold_expression(self, expr)
+    }
+
+    fn fold_soql_query(&mut self, query: SoqlQuery) -> SoqlQuery {
+        fold_soql_query(self, query)
+    }
+
+    fn fold_sosl_query(&mut self, query: SoslQuery) -> SoslQuery {
+        fold_sosl_query(self, query)
+    }
+
+    /// The id of every node in the unit, type references, annotations and
+    /// parameters included
+    fn fold_node_id(&mut self, id: NodeId) -> NodeId {
+        id
+    }
+}
+
+/// Fold `unit` and [renumber](CompilationUnit::renumber) it, returning the
+/// ids of copied nodes mapped to those of their originals so results kept
+/// in a [`SideTable`] can [follow](SideTable::extend_to_copies)
+pub fn fold_with_ids<F: Folder + ?Sized>(
+    folder: &mut F,
+    unit: CompilationUnit,
+) -> (CompilationUnit, NodeIdMap) {
+    let mut unit = folder.fold_compilation_unit(unit);
+    let copies = unit.renumber();
+    (unit, copies)
+}
+
+pub fn fold_compilation_unit<F: Folder + ?Sized>(
+    folder: &mut F,
+    unit: CompilationUnit,
+) -> CompilationUnit {
+    CompilationUnit {
+        declarations: unit
+            .declarations
+            .into_iter()
+            .map(|d| folder.fold_type_declaration(d))
+            .collect(),
+        next_id: unit.next_id,
+    }
+}
+
+pub fn fold_type_declaration<F: Folder + ?Sized>(
+    folder: &mut F,
+    declaration: TypeDeclaration,
+) -> TypeDeclaration {
+    match declaration {
+        TypeDeclaration::Class(class) => TypeDeclaration::Class(folder.fold_class(class)),
+        TypeDeclaration::Interface(interface) => {
+            TypeDeclaration::Interface(fold_interface(folder, interface))
+        }
+        TypeDeclaration::Enum(declaration) => TypeDeclaration::Enum(fold_enum(folder, declaration)),
+        TypeDeclaration::Trigger(mut trigger) => {
+            trigger.id = folder.fold_node_id(trigger.id);
+            trigger.body = folder.fold_block(trigger.body);
+            TypeDeclaration::Trigger(trigger)
+        }
+    }
+}
+
+pub fn fold_class<F: Folder + ?Sized>(
+    folder: &mut F,
+    mut class: ClassDeclaration,
+) -> ClassDeclaration {
+    class.id = folder.fold_node_id(class.id);
+    class.annotations = fold_annotations(folder, class.annotations);
+    class.type_parameters = fold_type_parameters(folder, class.type_parameters);
+    class.extends = class.extends.map(|t| fold_type_ref(folder, t));
+    class.implements = fold_type_refs(folder, class.implements);
+    class.members = class
+        .members
+        .into_iter()
+        .map(|m| folder.fold_class_member(m))
+        .collect();
+    class
+}
+
+pub fn fold_class_member<F: Folder + ?Sized>(folder: &mut F, member: ClassMember) -> ClassMember {
+    match member {
+        ClassMember::Field(mut field) => {
+            field.id = folder.fold_node_id(field.id);
+            field.annotations = fold_annotations(folder, field.annotations);
+            field.type_ref = fold_type_ref(folder, field.type_ref);
+            field.declarators = fold_declarators(folder, field.declarators);
+            ClassMember::Field(field)
+        }
+        ClassMember::Method(mut method) => {
+            method.id = folder.fold_node_id(method.id);
+            method.annotations = fold_annotations(folder, method.annotations);
+            method.return_type = fold_type_ref(folder, method.return_type);
+            method.type_parameters = fold_type_parameters(folder, method.type_parameters);
+            method.parameters = fold_parameters(folder, method.parameters);
+            method.body = method.body.map(|body| folder.fold_block(body));
+            ClassMember::Method(method)
+        }
+        ClassMember::Constructor(mut constructor) => {
+            constructor.id = folder.fold_node_id(constructor.id);
+            constructor.annotations = fold_annotations(folder, constructor.annotations);
+            constructor.parameters = fold_parameters(folder, constructor.parameters);
+            if let Some(ref mut chain) = constructor.chained_constructor {
+                chain.id = folder.fold_node_id(chain.id);
+                chain.arguments = fold_expressions(folder, std::mem::take(&mut chain.arguments));
+            }
+            constructor.body = folder.fold_block(constructor.body);
+            ClassMember::Constructor(constructor)
+        }
+        ClassMember::Property(mut property) => {
+            property.id = folder.fold_node_id(property.id);
+            property.annotations = fold_annotations(folder, property.annotations);
+            property.type_ref = fold_type_ref(folder, property.type_ref);
+            for accessor in [&mut property.getter, &mut property.setter]
+                .into_iter()
+                .flatten()
+            {
+                accessor.id = folder.fold_node_id(accessor.id);
+                accessor.body = accessor.body.take().map(|body| folder.fold_block(body));
+            }
+            ClassMember::Property(property)
+        }
+        ClassMember::StaticBlock(block) => ClassMember::StaticBlock(folder.fold_block(block)),
+        ClassMember::InnerClass(class) => ClassMember::InnerClass(folder.fold_class(class)),
+        ClassMember::InnerInterface(interface) => {
+            ClassMember::InnerInterface(fold_interface(folder, interface))
+        }
+        ClassMember::InnerEnum(declaration) => {
+            ClassMember::InnerEnum(fold_enum(folder, declaration))
+        }
+    }
+}
+
+pub fn fold_block<F: Folder + ?Sized>(folder: &mut F, mut block: Block) -> Block {
+    block.id = folder.fold_node_id(block.id);
+    block.statements = block
+        .statements
+        .into_iter()
+        .map(|s| folder.fold_statement(s))
+        .collect();
+    block
+}
+
+pub fn fold_statement<F: Folder + ?Sized>(folder: &mut F, stmt: Statement) -> Statement {
+    match stmt {
+        Statement::Block(block) => Statement::Block(folder.fold_block(block)),
+        Statement::LocalVariable(local) => Statement::LocalVariable(fold_local(folder, local)),
+        Statement::Expression(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.expression = folder.fold_expression(s.expression);
+            Statement::Expression(s)
+        }
+        Statement::If(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.condition = folder.fold_expression(s.condition);
+            s.then_branch = fold_boxed(folder, s.then_branch);
+            s.else_branch = s.else_branch.map(|e| fold_boxed(folder, e));
+            Statement::If(s)
+        }
+        Statement::For(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.init = s.init.map(|init| match init {
+                ForInit::Variables(local) => ForInit::Variables(fold_local(folder, local)),
+                ForInit::Expressions(exprs) => {
+                    ForInit::Expressions(fold_expressions(folder, exprs))
+                }
+            });
+            s.condition = s.condition.map(|c| folder.fold_expression(c));
+            s.update = fold_expressions(folder, s.update);
+            s.body = fold_boxed(folder, s.body);
+            Statement::For(s)
+        }
+        Statement::ForEach(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.type_ref = fold_type_ref(folder, s.type_ref);
+            s.iterable = folder.fold_expression(s.iterable);
+            s.body = fold_boxed(folder, s.body);
+            Statement::ForEach(s)
+        }
+        Statement::While(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.condition = folder.fold_expression(s.condition);
+            s.body = fold_boxed(folder, s.body);
+            Statement::While(s)
+        }
+        Statement::DoWhile(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.body = fold_boxed(folder, s.body);
+            s.condition = folder.fold_expression(s.condition);
+            Statement::DoWhile(s)
+        }
+        Statement::Switch(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.expression = folder.fold_expression(s.expression);
+            s.when_clauses = s
+                .when_clauses
+                .into_iter()
+                .map(|mut when| {
+                    when.id = folder.fold_node_id(when.id);
+                    when.values = match when.values {
+                        WhenValue::Literals(values) => {
+                            WhenValue::Literals(fold_expressions(folder, values))
+                        }
+                        WhenValue::Type { type_ref, variable } => WhenValue::Type {
+                            type_ref: fold_type_ref(folder, type_ref),
+                            variable,
+                        },
+                        WhenValue::Else => WhenValue::Else,
+                    };
+                    when.block = folder.fold_block(when.block);
+                    when
+                })
+                .collect();
+            Statement::Switch(s)
+        }
+        Statement::Return(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.value = s.value.map(|v| folder.fold_expression(v));
+            Statement::Return(s)
+        }
+        Statement::Throw(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.exception = folder.fold_expression(s.exception);
+            Statement::Throw(s)
+        }
+        Statement::Break(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            Statement::Break(s)
+        }
+        Statement::Continue(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            Statement::Continue(s)
+        }
+        Statement::Try(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.try_block = folder.fold_block(s.try_block);
+            s.catch_clauses = s
+                .catch_clauses
+                .into_iter()
+                .map(|mut catch| {
+                    catch.id = folder.fold_node_id(catch.id);
+                    catch.exception_type = fold_type_ref(folder, catch.exception_type);
+                    catch.alternative_types = fold_type_refs(folder, catch.alternative_types);
+                    catch.block = folder.fold_block(catch.block);
+                    catch
+                })
+                .collect();
+            s.finally_block = s.finally_block.map(|f| folder.fold_block(f));
+            Statement::Try(s)
+        }
+        Statement::Dml(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.expression = folder.fold_expression(s.expression);
+            Statement::Dml(s)
+        }
+        Statement::RunAs(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.user = folder.fold_expression(s.user);
+            s.body = folder.fold_block(s.body);
+            Statement::RunAs(s)
+        }
+        Statement::Labeled(mut s) => {
+            s.id = folder.fold_node_id(s.id);
+            s.body = fold_boxed(folder, s.body);
+            Statement::Labeled(s)
+        }
+        Statement::Empty(span, id) => Statement::Empty(span, folder.fold_node_id(id)),
+    }
+}
+
+pub fn fold_expression<F: Folder + ?Sized>(folder: &mut F, expr: Expression) -> Expression {
+    match expr {
+        Expression::Null(span, id) => Expression::Null(span, folder.fold_node_id(id)),
+        Expression::Boolean(value, span, id) => {
+            Expression::Boolean(value, span, folder.fold_node_id(id))
+        }
+        Expression::Integer(value, text, span, id) => {
+            Expression::Integer(value, text, span, folder.fold_node_id(id))
+        }
+        Expression::Long(value, text, span, id) => {
+            Expression::Long(value, text, span, folder.fold_node_id(id))
+        }
+        Expression::Double(value, text, span, id) => {
+            Expression::Double(value, text, span, folder.fold_node_id(id))
+        }
+        Expression::String(value, span, id) => {
+            Expression::String(value, span, folder.fold_node_id(id))
+        }
+        Expression::Identifier(name, span, id) => {
+            Expression::Identifier(name, span, folder.fold_node_id(id))
+        }
+        Expression::This(span, id) => Expression::This(span, folder.fold_node_id(id)),
+        Expression::Super(span, id) => Expression::Super(span, folder.fold_node_id(id)),
+        Expression::BindVariable(path, span, id) => fold_bind_variable(folder, &path, span, id),
+        Expression::FieldAccess(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.object = folder.fold_expression(e.object);
+            Expression::FieldAccess(e)
+        }
+        Expression::ArrayAccess(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.array = folder.fold_expression(e.array);
+            e.index = folder.fold_expression(e.index);
+            Expression::ArrayAccess(e)
+        }
+        Expression::SafeNavigation(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.object = folder.fold_expression(e.object);
+            Expression::SafeNavigation(e)
+        }
+        Expression::MethodCall(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.object = e.object.map(|o| folder.fold_expression(o));
+            e.type_arguments = fold_type_refs(folder, e.type_arguments);
+            e.arguments = fold_expressions(folder, e.arguments);
+            Expression::MethodCall(e)
+        }
+        Expression::New(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.type_ref = fold_type_ref(folder, e.type_ref);
+            e.arguments = fold_expressions(folder, e.arguments);
+            Expression::New(e)
+        }
+        Expression::NewArray(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.element_type = fold_type_ref(folder, e.element_type);
+            e.size = e.size.map(|s| folder.fold_expression(s));
+            e.initializer = e.initializer.map(|items| fold_expressions(folder, items));
+            Expression::NewArray(e)
+        }
+        Expression::NewMap(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.type_ref = fold_type_ref(folder, e.type_ref);
+            e.initializer = e.initializer.map(|pairs| fold_pairs(folder, pairs));
+            Expression::NewMap(e)
+        }
+        Expression::Unary(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.operand = folder.fold_expression(e.operand);
+            Expression::Unary(e)
+        }
+        Expression::Binary(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.left = folder.fold_expression(e.left);
+            e.right = folder.fold_expression(e.right);
+            Expression::Binary(e)
+        }
+        Expression::Ternary(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.condition = folder.fold_expression(e.condition);
+            e.then_expr = folder.fold_expression(e.then_expr);
+            e.else_expr = folder.fold_expression(e.else_expr);
+            Expression::Ternary(e)
+        }
+        Expression::NullCoalesce(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.left = folder.fold_expression(e.left);
+            e.right = folder.fold_expression(e.right);
+            Expression::NullCoalesce(e)
+        }
+        Expression::Instanceof(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.expression = folder.fold_expression(e.expression);
+            e.type_ref = fold_type_ref(folder, e.type_ref);
+            Expression::Instanceof(e)
+        }
+        Expression::Cast(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.type_ref = fold_type_ref(folder, e.type_ref);
+            e.expression = folder.fold_expression(e.expression);
+            Expression::Cast(e)
+        }
+        Expression::Assignment(mut e) => {
+            e.id = folder.fold_node_id(e.id);
+            e.target = folder.fold_expression(e.target);
+            e.value = folder.fold_expression(e.value);
+            Expression::Assignment(e)
+        }
+        Expression::PostIncrement(e, span, id) => {
+            let id = folder.fold_node_id(id);
+            Expression::PostIncrement(Box::new(folder.fold_expression(*e)), span, id)
+        }
+        Expression::PostDecrement(e, span, id) => {
+            let id = folder.fold_node_id(id);
+            Expression::PostDecrement(Box::new(folder.fold_expression(*e)), span, id)
+        }
+        Expression::PreIncrement(e, span, id) => {
+            let id = folder.fold_node_id(id);
+            Expression::PreIncrement(Box::new(folder.fold_expression(*e)), span, id)
+        }
+        Expression::PreDecrement(e, span, id) => {
+            let id = folder.fold_node_id(id);
+            Expression::PreDecrement(Box::new(folder.fold_expression(*e)), span, id)
+        }
+        Expression::Parenthesized(e, span, id) => {
+            let id = folder.fold_node_id(id);
+            Expression::Parenthesized(Box::new(folder.fold_expression(*e)), span, id)
+        }
+        Expression::BindExpression(e, span, id) => {
+            let id = folder.fold_node_id(id);
+            Expression::BindExpression(Box::new(folder.fold_expression(*e)), span, id)
+        }
+        Expression::Soql(query) => Expression::Soql(Box::new(folder.fold_soql_query(*query))),
+        Expression::SoqlSubquery(query) => {
+            Expression::SoqlSubquery(Box::new(folder.fold_soql_query(*query)))
+        }
+        Expression::Sosl(query) => Expression::Sosl(Box::new(folder.fold_sosl_query(*query))),
+        Expression::ListLiteral(items, ty, span, id) => {
+            let id = folder.fold_node_id(id);
+            let ty = ty.map(|ty| fold_type_ref(folder, ty));
+            Expression::ListLiteral(fold_expressions(folder, items), ty, span, id)
+        }
+        Expression::SetLiteral(items, ty, span, id) => {
+            let id = folder.fold_node_id(id);
+            let ty = ty.map(|ty| fold_type_ref(folder, ty));
+            Expression::SetLiteral(fold_expressions(folder, items), ty, span, id)
+        }
+        Expression::MapLiteral(pairs, ty, span, id) => {
+            let id = folder.fold_node_id(id);
+            let ty = ty.map(|ty| fold_type_ref(folder, ty));
+            Expression::MapLiteral(fold_pairs(folder, pairs), ty, span, id)
+        }
+        Expression::TypeLiteral(type_ref, span, id) => {
+            let id = folder.fold_node_id(id);
+            Expression::TypeLiteral(fold_type_ref(folder, type_ref), span, id)
+        }
+    }
+}
+
+/// Fold the WHERE, HAVING, LIMIT and OFFSET expressions of a query and of
+/// its subqueries
+pub fn fold_soql_query<F: Folder + ?Sized>(folder: &mut F, mut query: SoqlQuery) -> SoqlQuery {
+    query.id = folder.fold_node_id(query.id);
+    query.select_clause = query
+        .select_clause
+        .into_iter()
+        .map(|field| match field {
+            SelectField::SubQuery(subquery) => {
+                SelectField::SubQuery(Box::new(folder.fold_soql_query(*subquery)))
+            }
+            other => other,
+        })
+        .collect();
+    query.where_clause = query.where_clause.map(|e| folder.fold_expression(e));
+    query.having_clause = query.having_clause.map(|e| folder.fold_expression(e));
+    query.limit_clause = query.limit_clause.map(|e| folder.fold_expression(e));
+    query.offset_clause = query.offset_clause.map(|e| folder.fold_expression(e));
+    query
+}
+
+pub fn fold_sosl_query<F: Folder + ?Sized>(folder: &mut F, mut query: SoslQuery) -> SoslQuery {
+    query.id = folder.fold_node_id(query.id);
+    for returning in &mut query.returning {
+        returning.where_clause = returning
+            .where_clause
+            .take()
+            .map(|e| folder.fold_expression(e));
+    }
+    query.limit_clause = query.limit_clause.map(|e| folder.fold_expression(e));
+    query
+}
+
+/// Fold `:a.b.c` as the expression `a.b.c`, whose nodes exist only while
+/// folding
+fn fold_bind_variable<F: Folder + ?Sized>(
+    folder: &mut F,
+    path: &str,
+    span: Span,
+    id: NodeId,
+) -> Expression {
+    let id = folder.fold_node_id(id);
+    let mut segments = path.split('.');
+    let root = Expression::Identifier(
+        segments.next().unwrap_or_default().to_string(),
+        span,
+        NodeId::DUMMY,
+    );
+    let read = segments.fold(root, |object, field| {
+        Expression::FieldAccess(Box::new(FieldAccessExpr {
+            object,
+            field: field.to_string(),
+            span,
+            id: NodeId::DUMMY,
+        }))
+    });
+    let folded = folder.fold_expression(read);
+    match bind_path(&folded) {
+        Some(path) => Expression::BindVariable(path, span, id),
+        None => Expression::BindExpression(Box::new(folded), span, id),
+    }
+}
+
+// Declarations and types hold no statements, so the trait leaves them to
+// these helpers
+
+fn fold_interface<F: Folder + ?Sized>(
+    folder: &mut F,
+    mut interface: InterfaceDeclaration,
+) -> InterfaceDeclaration {
+    interface.id = folder.fold_node_id(interface.id);
+    interface.annotations = fold_annotations(folder, interface.annotations);
+    interface.type_parameters = fold_type_parameters(folder, interface.type_parameters);
+    interface.extends = fold_type_refs(folder, interface.extends);
+    interface.members = interface
+        .members
+        .into_iter()
+        .map(|InterfaceMember::Method(mut method)| {
+            method.id = folder.fold_node_id(method.id);
+            method.annotations = fold_annotations(folder, method.annotations);
+            method.return_type = fold_type_ref(folder, method.return_type);
+            method.parameters = fold_parameters(folder, method.parameters);
+            InterfaceMember::Method(method)
+        })
+        .collect();
+    interface
+}
+
+fn fold_enum<F: Folder + ?Sized>(
+    folder: &mut F,
+    mut declaration: EnumDeclaration,
+) -> EnumDeclaration {
+    declaration.id = folder.fold_node_id(declaration.id);
+    declaration.annotations = fold_annotations(folder, declaration.annotations);
+    declaration
+}
+
+fn fold_annotations<F: Folder + ?Sized>(
+    folder: &mut F,
+    annotations: Vec<Annotation>,
+) -> Vec<Annotation> {
+    annotations
+        .into_iter()
+        .map(|mut annotation| {
+            annotation.id = folder.fold_node_id(annotation.id);
+            annotation.parameters = annotation
+                .parameters
+                .into_iter()
+                .map(|mut parameter| {
+                    parameter.value = folder.fold_expression(parameter.value);
+                    parameter
+                })
+                .collect();
+            annotation
+        })
+        .collect()
+}
+
+fn fold_type_parameters<F: Folder + ?Sized>(
+    folder: &mut F,
+    parameters: Vec<TypeParameter>,
+) -> Vec<TypeParameter> {
+    parameters
+        .into_iter()
+        .map(|mut parameter| {
+            parameter.id = folder.fold_node_id(parameter.id);
+            parameter
+        })
+        .collect()
+}
+
+fn fold_parameters<F: Folder + ?Sized>(
+    folder: &mut F,
+    parameters: Vec<Parameter>,
+) -> Vec<Parameter> {
+    parameters
+        .into_iter()
+        .map(|mut parameter| {
+            parameter.id = folder.fold_node_id(parameter.id);
+            parameter.annotations = fold_annotations(folder, parameter.annotations);
+            parameter.type_ref = fold_type_ref(folder, parameter.type_ref);
+            parameter
+        })
+        .collect()
+}
+
+fn fold_type_ref<F: Folder + ?Sized>(folder: &mut F, mut type_ref: TypeRef) -> TypeRef {
+    type_ref.id = folder.fold_node_id(type_ref.id);
+    type_ref.type_arguments = fold_type_refs(folder, type_ref.type_arguments);
+    type_ref
+}
+
+fn fold_type_refs<F: Folder + ?Sized>(folder: &mut F, type_refs: Vec<TypeRef>) -> Vec<TypeRef> {
+    type_refs
+        .into_iter()
+        .map(|t| fold_type_ref(folder, t))
+        .collect()
+}
+
+fn fold_boxed<F: Folder + ?Sized>(folder: &mut F, stmt: Box<Statement>) -> Box<Statement> {
+    Box::new(folder.fold_statement(*stmt))
+}
+
+fn fold_expressions<F: Folder + ?Sized>(folder: &mut F, exprs: Vec<Expression>) -> Vec<Expression> {
+    exprs
+        .into_iter()
+        .map(|e| folder.fold_expression(e))
+        .collect()
+}
+
+fn fold_pairs<F: Folder + ?Sized>(
+    folder: &mut F,
+    pairs: Vec<(Expression, Expression)>,
+) -> Vec<(Expression, Expression)> {
+    pairs
+        .into_iter()
+        .map(|(key, value)| (folder.fold_expression(key), folder.fold_expression(value)))
+        .collect()
+}
+
+fn fold_local<F: Folder + ?Sized>(
+    folder: &mut F,
+    mut local: LocalVariableDeclaration,
+) -> LocalVariableDeclaration {
+    local.id = folder.fold_node_id(local.id);
+    local.type_ref = fold_type_ref(folder, local.type_ref);
+    local.declarators = fold_declarators(folder, local.declarators);
+    local
+}
+
+fn fold_declarators<F: Folder + ?Sized>(
+    folder: &mut F,
+    declarators: Vec<VariableDeclarator>,
+) -> Vec<VariableDeclarator> {
+    declarators
+        .into_iter()
+        .map(|mut declarator| {
+            declarator.id = folder.fold_node_id(declarator.id);
+            declarator.initializer = declarator.initializer.map(|e| folder.fold_expression(e));
+            declarator
+        })
+        .collect()
+}
@@ -0,0 +1,218 @@
+//! Node identity and per-node analysis results
+//!
+//! The parser gives every node with a span a [`NodeId`] unique within its
+//! compilation unit, so an analysis can record what it finds about a node
+//! in a [`SideTable`] instead of a map keyed by spans, which repeat for
+//! nodes a rewrite synthesizes, or a new AST field:
+//!
+//! ```
+//! use apexrust::ast::visitor::{walk_compilation_unit, Visitor};
+//! use apexrust::{parse, MethodCallExpr, SideTable};
+//!
+//! /// Argument count of every call
+//! #[derive(Default)]
+//! struct Arity(SideTable<usize>);
+//!
+//! impl<'ast> Visitor<'ast> for Arity {
+//!     fn visit_method_call(&mut self, call: &'ast MethodCallExpr) {
+//!         self.0.insert(call.id, call.arguments.len());
+//!     }
+//! }
+//!
+//! let unit = parse("public class A { void m() { System.debug(load()); } }").unwrap();
+//! let mut arity = Arity::default();
+//! walk_compilation_unit(&mut arity, &unit);
+//! assert_eq!(arity.0.values().copied().collect::<Vec<_>>(), [0, 1]);
+//! ```
+//!
+//! Nodes built after parsing take a fresh id from
+//! [`CompilationUnit::next_node_id`], or [`NodeId::DUMMY`] where the unit
+//! is out of reach; [`fold::fold_with_ids`] replaces those and any ids a
+//! rewrite duplicated.
+
+use std::collections::{HashMap, HashSet};
+
+use super::fold::{self, Folder};
+use super::*;
+
+/// Identity of an AST node within its compilation unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct NodeId(pub u32);
+
+impl NodeId {
+    /// Placeholder for a node built without its compilation unit at hand
+    pub const DUMMY: NodeId = NodeId(u32::MAX);
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Ids a renumbering gave to copies of a node, each mapped to the id of the
+/// node it was copied from
+pub type NodeIdMap = HashMap<NodeId, NodeId>;
+
+/// Values attached to nodes by id, stored densely since a unit's ids run
+/// from zero
+#[derive(Debug, Clone, PartialEq)]
+pub struct SideTable<T> {
+    entries: Vec<Option<T>>,
+    len: usize,
+}
+
+impl<T> Default for SideTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SideTable<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Attach `value` to `id`, returning the value it replaces
+    ///
+    /// # Panics
+    ///
+    /// If `id` is [`NodeId::DUMMY`], which names no single node.
+    pub fn insert(&mut self, id: NodeId, value: T) -> Option<T> {
+        assert_ne!(id, NodeId::DUMMY, "SideTable keys must be assigned ids");
+        if id.index() >= self.entries.len() {
+            self.entries.resize_with(id.index() + 1, || None);
+        }
+        let previous = self.entries[id.index()].replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.entries.get(id.index())?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.entries.get_mut(id.index())?.as_mut()
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        let removed = self.entries.get_mut(id.index())?.take();
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Entries in id order
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| Some((NodeId(index as u32), value.as_ref()?)))
+    }
+
+    /// Values in id order
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter().flatten()
+    }
+}
+
+impl<T: Clone> SideTable<T> {
+    /// Give each copy in `map` the value of the node it was copied from
+    pub fn extend_to_copies(&mut self, map: &NodeIdMap) {
+        for (&copy, &original) in map {
+            if let Some(value) = self.get(original).cloned() {
+                self.insert(copy, value);
+            }
+        }
+    }
+}
+
+impl<T> FromIterator<(NodeId, T)> for SideTable<T> {
+    fn from_iter<I: IntoIterator<Item = (NodeId, T)>>(iter: I) -> Self {
+        let mut table = Self::new();
+        for (id, value) in iter {
+            table.insert(id, value);
+        }
+        table
+    }
+}
+
+impl CompilationUnit {
+    /// Give fresh ids to [`NodeId::DUMMY`] nodes, to nodes holding an id
+    /// the unit never handed out, and to each repeat of an id after the
+    /// first in source order, which a rewrite that copies a subtree leaves
+    ///
+    /// Returns each repeat's new id mapped to the id it repeated.
+    pub fn renumber(&mut self) -> NodeIdMap {
+        let mut renumber = Renumber {
+            limit: self.next_id,
+            next: self.next_id.0,
+            seen: HashSet::new(),
+            copies: NodeIdMap::new(),
+        };
+        let unit = std::mem::replace(
+            self,
+            CompilationUnit {
+                declarations: Vec::new(),
+                next_id: self.next_id,
+            },
+        );
+        *self = renumber.fold_compilation_unit(unit);
+        self.next_id = NodeId(renumber.next);
+        renumber.copies
+    }
+}
+
+struct Renumber {
+    limit: NodeId,
+    next: u32,
+    seen: HashSet<NodeId>,
+    copies: NodeIdMap,
+}
+
+impl Folder for Renumber {
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        match expr {
+            // Only the bind itself is a node; folding would expand its path
+            Expression::BindVariable(path, span, id) => {
+                Expression::BindVariable(path, span, self.fold_node_id(id))
+            }
+            other => fold::fold_expression(self, other),
+        }
+    }
+
+    fn fold_node_id(&mut self, id: NodeId) -> NodeId {
+        let handed_out = id < self.limit;
+        if handed_out && self.seen.insert(id) {
+            return id;
+        }
+        let fresh = NodeId(self.next);
+        self.next += 1;
+        if handed_out {
+            self.copies.insert(fresh, id);
+        }
+        fresh
+    }
+}
@@ -0,0 +1,1144 @@
+//! Printing a compilation unit back to Apex source
+//!
+//! [`to_apex`] emits source that parses back to the same AST, spans aside:
+//!
+//! ```
+//! use apexrust::ast::printer::{to_apex, PrintOptions};
+//! use apexrust::parse;
+//!
+//! let unit = parse("public class A{Integer n=1;void m(){if(n>0)n--;}}").unwrap();
+//! let apex = to_apex(&unit, &PrintOptions::default());
+//! assert_eq!(
+//!     apex,
+//!     "public class A {\n    Integer n = 1;\n\n    void m() {\n        if (n > 0)\n            n--;\n    }\n}\n"
+//! );
+//! ```
+//!
+//! Comments are not part of the AST and are lost. Grouping comes from the
+//! `Parenthesized` nodes the parser keeps; no parentheses are added for
+//! precedence. `private`, the default access, is left implicit, and an
+//! accessor only names its access when it differs from its property's.
+//! Collection literals print back with the type written after `new`; an
+//! untyped `[a, b]` initializing a declaration takes the declared type.
+
+use super::*;
+
+/// Where an opening brace goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BraceStyle {
+    /// `if (x) {`
+    #[default]
+    SameLine,
+    /// `if (x)` with `{` on a line of its own
+    NextLine,
+}
+
+/// Options for printing Apex
+#[derive(Debug, Clone)]
+pub struct PrintOptions {
+    /// Indent string (default: 4 spaces)
+    pub indent: String,
+    pub brace_style: BraceStyle,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            indent: "    ".to_string(),
+            brace_style: BraceStyle::SameLine,
+        }
+    }
+}
+
+/// Apex source for `unit`, one declaration after another
+pub fn to_apex(unit: &CompilationUnit, options: &PrintOptions) -> String {
+    let mut printer = Printer {
+        out: String::new(),
+        options,
+        depth: 0,
+    };
+    for (i, declaration) in unit.declarations.iter().enumerate() {
+        if i > 0 {
+            printer.out.push('\n');
+        }
+        printer.type_declaration(declaration);
+        printer.out.push('\n');
+    }
+    printer.out
+}
+
+struct Printer<'a> {
+    out: String,
+    options: &'a PrintOptions,
+    depth: usize,
+}
+
+impl Printer<'_> {
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    /// Start a new line at the current depth
+    fn line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        for _ in 0..self.depth {
+            self.out.push_str(&self.options.indent);
+        }
+    }
+
+    fn open_brace(&mut self) {
+        match self.options.brace_style {
+            BraceStyle::SameLine => self.write(" {"),
+            BraceStyle::NextLine => {
+                self.line();
+                self.write("{");
+            }
+        }
+        self.depth += 1;
+    }
+
+    fn close_brace(&mut self) {
+        self.depth -= 1;
+        self.line();
+        self.write("}");
+    }
+
+    /// Keyword that continues after a closing brace: `} else`, `} catch`
+    fn continuation(&mut self, keyword: &str) {
+        match self.options.brace_style {
+            BraceStyle::SameLine => self.write(" "),
+            BraceStyle::NextLine => self.line(),
+        }
+        self.write(keyword);
+    }
+
+    // ==================== Declarations ====================
+
+    fn type_declaration(&mut self, declaration: &TypeDeclaration) {
+        match declaration {
+            TypeDeclaration::Class(class) => self.class(class),
+            TypeDeclaration::Interface(interface) => self.interface(interface),
+            TypeDeclaration::Enum(declaration) => self.enumeration(declaration),
+            TypeDeclaration::Trigger(trigger) => self.trigger(trigger),
+        }
+    }
+
+    fn annotations(&mut self, annotations: &[Annotation]) {
+        for annotation in annotations {
+            self.line();
+            let text = annotation_text(annotation);
+            self.write(&text);
+        }
+    }
+
+    fn class(&mut self, class: &ClassDeclaration) {
+        self.annotations(&class.annotations);
+        self.line();
+        let modifiers = &class.modifiers;
+        let mut words = access_words(modifiers.access);
+        if let Some(sharing) = modifiers.sharing {
+            words.push(sharing_text(sharing));
+        }
+        if modifiers.is_abstract {
+            words.push("abstract");
+        }
+        if modifiers.is_virtual {
+            words.push("virtual");
+        }
+        words.push("class");
+        self.write(&words.join(" "));
+        self.write(" ");
+        self.write(&class.name);
+        self.type_parameters(&class.type_parameters);
+        if let Some(ref extends) = class.extends {
+            self.write(" extends ");
+            self.write(&type_ref(extends));
+        }
+        if !class.implements.is_empty() {
+            self.write(" implements ");
+            self.write(&type_list(&class.implements));
+        }
+        self.open_brace();
+        let mut previous: Option<&ClassMember> = None;
+        for member in &class.members {
+            // Fields stay together; everything else gets a blank line
+            let adjacent_fields = matches!(
+                (previous, member),
+                (Some(ClassMember::Field(_)), ClassMember::Field(_))
+            );
+            if previous.is_some() && !adjacent_fields {
+                self.write("\n\n");
+            }
+            self.class_member(member);
+            previous = Some(member);
+        }
+        self.close_brace();
+    }
+
+    fn type_parameters(&mut self, parameters: &[TypeParameter]) {
+        if !parameters.is_empty() {
+            let names: Vec<&str> = parameters.iter().map(|p| p.name.as_str()).collect();
+            self.write(&format!("<{}>", names.join(", ")));
+        }
+    }
+
+    fn interface(&mut self, interface: &InterfaceDeclaration) {
+        self.annotations(&interface.annotations);
+        self.line();
+        let mut words = access_words(interface.access);
+        words.push("interface");
+        self.write(&words.join(" "));
+        self.write(" ");
+        self.write(&interface.name);
+        self.type_parameters(&interface.type_parameters);
+        if !interface.extends.is_empty() {
+            self.write(" extends ");
+            self.write(&type_list(&interface.extends));
+        }
+        self.open_brace();
+        for InterfaceMember::Method(signature) in &interface.members {
+            self.annotations(&signature.annotations);
+            self.line();
+            self.write(&type_ref(&signature.return_type));
+            self.write(" ");
+            self.write(&signature.name);
+            self.parameters(&signature.parameters);
+            self.write(";");
+        }
+        self.close_brace();
+    }
+
+    fn enumeration(&mut self, declaration: &EnumDeclaration) {
+        self.annotations(&declaration.annotations);
+        self.line();
+        let mut words = access_words(declaration.access);
+        words.push("enum");
+        self.write(&words.join(" "));
+        self.write(" ");
+        self.write(&declaration.name);
+        self.open_brace();
+        for (i, value) in declaration.values.iter().enumerate() {
+            self.line();
+            self.write(value);
+            if i + 1 < declaration.values.len() {
+                self.write(",");
+            }
+        }
+        self.close_brace();
+    }
+
+    fn trigger(&mut self, trigger: &TriggerDeclaration) {
+        self.line();
+        let events: Vec<&str> = trigger.events.iter().map(|e| trigger_event(*e)).collect();
+        self.write(&format!(
+            "trigger {} on {} ({})",
+            trigger.name,
+            trigger.object,
+            events.join(", ")
+        ));
+        self.block(&trigger.body);
+    }
+
+    fn class_member(&mut self, member: &ClassMember) {
+        match member {
+            ClassMember::Field(field) => {
+                self.annotations(&field.annotations);
+                self.line();
+                self.write(&member_modifiers(&field.modifiers));
+                self.write(&type_ref(&field.type_ref));
+                self.write(" ");
+                self.write(&declarators(&field.type_ref, &field.declarators));
+                self.write(";");
+            }
+            ClassMember::Method(method) => {
+                self.annotations(&method.annotations);
+                self.line();
+                self.write(&member_modifiers(&method.modifiers));
+                if !method.type_parameters.is_empty() {
+                    self.type_parameters(&method.type_parameters);
+                    self.write(" ");
+                }
+                self.write(&type_ref(&method.return_type));
+                self.write(" ");
+                self.write(&method.name);
+                self.parameters(&method.parameters);
+                match method.body {
+                    Some(ref body) => self.block(body),
+                    None => self.write(";"),
+                }
+            }
+            ClassMember::Constructor(constructor) => {
+                self.annotations(&constructor.annotations);
+                self.line();
+                self.write(&member_modifiers(&constructor.modifiers));
+                self.write(&constructor.name);
+                self.parameters(&constructor.parameters);
+                self.open_brace();
+                if let Some(ref chain) = constructor.chained_constructor {
+                    self.line();
+                    let keyword = match chain.kind {
+                        ConstructorChainKind::This => "this",
+                        ConstructorChainKind::Super => "super",
+                    };
+                    self.write(&format!("{}({});", keyword, arguments(&chain.arguments)));
+                }
+                for statement in &constructor.body.statements {
+                    self.statement(statement);
+                }
+                self.close_brace();
+            }
+            ClassMember::Property(property) => self.property(property),
+            ClassMember::StaticBlock(block) => {
+                self.line();
+                self.write("static");
+                self.block(block);
+            }
+            ClassMember::InnerClass(class) => self.class(class),
+            ClassMember::InnerInterface(interface) => self.interface(interface),
+            ClassMember::InnerEnum(declaration) => self.enumeration(declaration),
+        }
+    }
+
+    fn property(&mut self, property: &PropertyDeclaration) {
+        self.annotations(&property.annotations);
+        self.line();
+        self.write(&member_modifiers(&property.modifiers));
+        self.write(&type_ref(&property.type_ref));
+        self.write(" ");
+        self.write(&property.name);
+
+        let accessors = [("get", &property.getter), ("set", &property.setter)];
+        let automatic = accessors
+            .iter()
+            .all(|(_, accessor)| accessor.as_ref().is_none_or(|a| a.body.is_none()));
+        if automatic {
+            // `{ get; set; }`
+            self.write(" {");
+            for (keyword, accessor) in accessors {
+                if let Some(accessor) = accessor {
+                    self.write(" ");
+                    self.write(&accessor_modifiers(accessor, property));
+                    self.write(keyword);
+                    self.write(";");
+                }
+            }
+            self.write(" }");
+            return;
+        }
+
+        self.open_brace();
+        for (keyword, accessor) in accessors {
+            if let Some(accessor) = accessor {
+                self.line();
+                self.write(&accessor_modifiers(accessor, property));
+                self.write(keyword);
+                match accessor.body {
+                    Some(ref body) => self.block(body),
+                    None => self.write(";"),
+                }
+            }
+        }
+        self.close_brace();
+    }
+
+    fn parameters(&mut self, parameters: &[Parameter]) {
+        let parameters: Vec<String> = parameters
+            .iter()
+            .map(|parameter| {
+                let mut text = String::new();
+                for annotation in &parameter.annotations {
+                    text.push_str(&annotation_text(annotation));
+                    text.push(' ');
+                }
+                if parameter.is_final {
+                    text.push_str("final ");
+                }
+                text.push_str(&type_ref(&parameter.type_ref));
+                text.push(' ');
+                text.push_str(&parameter.name);
+                text
+            })
+            .collect();
+        self.write(&format!("({})", parameters.join(", ")));
+    }
+
+    // ==================== Statements ====================
+
+    /// `{ ... }` after the text already on the line
+    fn block(&mut self, block: &Block) {
+        self.open_brace();
+        for statement in &block.statements {
+            self.statement(statement);
+        }
+        self.close_brace();
+    }
+
+    /// The body of an `if`, loop or label; returns whether it ended in a
+    /// closing brace
+    fn body(&mut self, body: &Statement) -> bool {
+        match body {
+            Statement::Block(block) => {
+                self.block(block);
+                true
+            }
+            other => {
+                self.depth += 1;
+                self.statement(other);
+                self.depth -= 1;
+                false
+            }
+        }
+    }
+
+    /// Keyword that follows a body: after its brace, or on the next line
+    fn after_body(&mut self, braced: bool, keyword: &str) {
+        if braced {
+            self.continuation(keyword);
+        } else {
+            self.line();
+            self.write(keyword);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        self.line();
+        self.statement_inline(statement);
+    }
+
+    /// A statement starting at the current position on the line
+    fn statement_inline(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Block(block) => {
+                self.write("{");
+                self.depth += 1;
+                for statement in &block.statements {
+                    self.statement(statement);
+                }
+                self.close_brace();
+            }
+            Statement::LocalVariable(local) => {
+                self.write(&local_variable(local));
+                self.write(";");
+            }
+            Statement::Expression(statement) => {
+                self.write(&expression(&statement.expression));
+                self.write(";");
+            }
+            Statement::If(statement) => {
+                self.write(&format!("if ({})", expression(&statement.condition)));
+                let braced = self.body(&statement.then_branch);
+                if let Some(ref else_branch) = statement.else_branch {
+                    self.after_body(braced, "else");
+                    match **else_branch {
+                        Statement::If(_) => {
+                            self.write(" ");
+                            self.statement_inline(else_branch);
+                        }
+                        ref other => {
+                            self.body(other);
+                        }
+                    }
+                }
+            }
+            Statement::For(statement) => {
+                let mut header = String::from("for (");
+                match statement.init {
+                    Some(ForInit::Variables(ref local)) => header.push_str(&local_variable(local)),
+                    Some(ForInit::Expressions(ref exprs)) => header.push_str(&arguments(exprs)),
+                    None => {}
+                }
+                header.push(';');
+                if let Some(ref condition) = statement.condition {
+                    header.push(' ');
+                    header.push_str(&expression(condition));
+                }
+                header.push(';');
+                if !statement.update.is_empty() {
+                    header.push(' ');
+                    header.push_str(&arguments(&statement.update));
+                }
+                header.push(')');
+                self.write(&header);
+                self.body(&statement.body);
+            }
+            Statement::ForEach(statement) => {
+                self.write(&format!(
+                    "for ({} {} : {})",
+                    type_ref(&statement.type_ref),
+                    statement.variable,
+                    expression(&statement.iterable)
+                ));
+                self.body(&statement.body);
+            }
+            Statement::While(statement) => {
+                self.write(&format!("while ({})", expression(&statement.condition)));
+                self.body(&statement.body);
+            }
+            Statement::DoWhile(statement) => {
+                self.write("do");
+                let braced = self.body(&statement.body);
+                self.after_body(braced, "while");
+                self.write(&format!(" ({});", expression(&statement.condition)));
+            }
+            Statement::Switch(statement) => {
+                self.write(&format!("switch on {}", expression(&statement.expression)));
+                self.open_brace();
+                for clause in &statement.when_clauses {
+                    self.line();
+                    let values = match clause.values {
+                        WhenValue::Literals(ref values) => arguments(values),
+                        WhenValue::Type {
+                            ref type_ref,
+                            ref variable,
+                        } => format!("{} {}", self::type_ref(type_ref), variable),
+                        WhenValue::Else => "else".to_string(),
+                    };
+                    self.write(&format!("when {}", values));
+                    self.block(&clause.block);
+                }
+                self.close_brace();
+            }
+            Statement::Return(statement) => match statement.value {
+                Some(ref value) => self.write(&format!("return {};", expression(value))),
+                None => self.write("return;"),
+            },
+            Statement::Throw(statement) => {
+                self.write(&format!("throw {};", expression(&statement.exception)));
+            }
+            Statement::Break(statement) => self.jump("break", statement.label.as_deref()),
+            Statement::Continue(statement) => self.jump("continue", statement.label.as_deref()),
+            Statement::Try(statement) => {
+                self.write("try");
+                self.block(&statement.try_block);
+                for clause in &statement.catch_clauses {
+                    let types: Vec<String> = clause.exception_types().map(type_ref).collect();
+                    self.continuation(&format!(
+                        "catch ({} {})",
+                        types.join(" | "),
+                        clause.variable
+                    ));
+                    self.block(&clause.block);
+                }
+                if let Some(ref finally_block) = statement.finally_block {
+                    self.continuation("finally");
+                    self.block(finally_block);
+                }
+            }
+            Statement::Dml(statement) => {
+                let operation = match statement.operation {
+                    DmlOperation::Insert => "insert",
+                    DmlOperation::Update => "update",
+                    DmlOperation::Upsert => "upsert",
+                    DmlOperation::Delete => "delete",
+                    DmlOperation::Undelete => "undelete",
+                    DmlOperation::Merge => "merge",
+                };
+                self.write(operation);
+                match statement.access_level {
+                    Some(DmlAccessLevel::System) => self.write(" as system"),
+                    Some(DmlAccessLevel::User) => self.write(" as user"),
+                    None => {}
+                }
+                self.write(&format!(" {};", expression(&statement.expression)));
+            }
+            Statement::RunAs(statement) => {
+                self.write(&format!("System.runAs({})", expression(&statement.user)));
+                self.block(&statement.body);
+            }
+            Statement::Labeled(statement) => {
+                self.write(&format!("{}: ", statement.label));
+                match *statement.body {
+                    Statement::Block(ref block) => {
+                        self.write("{");
+                        self.depth += 1;
+                        for statement in &block.statements {
+                            self.statement(statement);
+                        }
+                        self.close_brace();
+                    }
+                    ref other => self.statement_inline(other),
+                }
+            }
+            Statement::Empty(..) => self.write(";"),
+        }
+    }
+
+    fn jump(&mut self, keyword: &str, label: Option<&str>) {
+        match label {
+            Some(label) => self.write(&format!("{} {};", keyword, label)),
+            None => self.write(&format!("{};", keyword)),
+        }
+    }
+}
+
+fn access_words(access: AccessModifier) -> Vec<&'static str> {
+    match access {
+        AccessModifier::Private => vec![],
+        AccessModifier::Public => vec!["public"],
+        AccessModifier::Protected => vec!["protected"],
+        AccessModifier::Global => vec!["global"],
+    }
+}
+
+fn sharing_text(sharing: SharingModifier) -> &'static str {
+    match sharing {
+        SharingModifier::WithSharing => "with sharing",
+        SharingModifier::WithoutSharing => "without sharing",
+        SharingModifier::InheritedSharing => "inherited sharing",
+    }
+}
+
+/// Modifiers followed by a space, or nothing for a private member
+fn member_modifiers(modifiers: &MemberModifiers) -> String {
+    let mut words = access_words(modifiers.access);
+    if let Some(sharing) = modifiers.sharing {
+        words.push(sharing_text(sharing));
+    }
+    let flags = [
+        (modifiers.is_abstract, "abstract"),
+        (modifiers.is_virtual, "virtual"),
+        (modifiers.is_override, "override"),
+        (modifiers.is_static, "static"),
+        (modifiers.is_final, "final"),
+        (modifiers.is_transient, "transient"),
+        (modifiers.is_testmethod, "testMethod"),
+        (modifiers.is_webservice, "webservice"),
+    ];
+    words.extend(flags.iter().filter(|(set, _)| *set).map(|(_, word)| *word));
+    words.iter().map(|word| format!("{} ", word)).collect()
+}
+
+/// An accessor's modifiers, with its access only when it narrows the
+/// property's
+fn accessor_modifiers(accessor: &PropertyAccessor, property: &PropertyDeclaration) -> String {
+    let access = accessor.modifiers.access;
+    let rest = member_modifiers(&MemberModifiers {
+        access: AccessModifier::Private,
+        ..accessor.modifiers.clone()
+    });
+    if access == property.modifiers.access {
+        return rest;
+    }
+    let word = access_words(access).first().copied().unwrap_or("private");
+    format!("{} {}", word, rest)
+}
+
+fn annotation_text(annotation: &Annotation) -> String {
+    if annotation.parameters.is_empty() {
+        return format!("@{}", annotation.name);
+    }
+    let parameters: Vec<String> = annotation
+        .parameters
+        .iter()
+        .map(|parameter| match parameter.name {
+            Some(ref name) => format!("{}={}", name, expression(&parameter.value)),
+            None => expression(&parameter.value),
+        })
+        .collect();
+    format!("@{}({})", annotation.name, parameters.join(" "))
+}
+
+fn trigger_event(event: TriggerEvent) -> &'static str {
+    match event {
+        TriggerEvent::BeforeInsert => "before insert",
+        TriggerEvent::BeforeUpdate => "before update",
+        TriggerEvent::BeforeDelete => "before delete",
+        TriggerEvent::AfterInsert => "after insert",
+        TriggerEvent::AfterUpdate => "after update",
+        TriggerEvent::AfterDelete => "after delete",
+        TriggerEvent::AfterUndelete => "after undelete",
+    }
+}
+
+/// Declarators of a variable or field of type `ty`
+fn declarators(ty: &TypeRef, declarators: &[VariableDeclarator]) -> String {
+    let declarators: Vec<String> = declarators
+        .iter()
+        .map(|declarator| match declarator.initializer {
+            Some(ref value) => format!("{} = {}", declarator.name, initial_value(ty, value)),
+            None => declarator.name.clone(),
+        })
+        .collect();
+    declarators.join(", ")
+}
+
+/// A list or set literal written without a type, assigned to a
+/// declaration, takes the declared type rather than `Object`
+fn initial_value(ty: &TypeRef, value: &Expression) -> String {
+    let is_list = ty.is_array || ty.name == "List";
+    match value {
+        Expression::ListLiteral(items, None, ..) if is_list => {
+            format!("new {}{}", type_ref(ty), initializer(items))
+        }
+        Expression::SetLiteral(items, None, ..) if ty.name == "Set" && !ty.is_array => {
+            format!("new {}{}", type_ref(ty), initializer(items))
+        }
+        other => expression(other),
+    }
+}
+
+fn local_variable(local: &LocalVariableDeclaration) -> String {
+    let keyword = if local.is_final { "final " } else { "" };
+    format!(
+        "{}{} {}",
+        keyword,
+        type_ref(&local.type_ref),
+        declarators(&local.type_ref, &local.declarators)
+    )
+}
+
+fn type_list(types: &[TypeRef]) -> String {
+    types.iter().map(type_ref).collect::<Vec<_>>().join(", ")
+}
+
+// ==================== Expressions ====================
+
+fn arguments(arguments: &[Expression]) -> String {
+    arguments
+        .iter()
+        .map(expression)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Apex type name with its type arguments, as written
+pub(crate) fn type_ref(ty: &TypeRef) -> String {
+    let mut s = ty.name.clone();
+    if !ty.type_arguments.is_empty() {
+        let args: Vec<String> = ty.type_arguments.iter().map(type_ref).collect();
+        s.push_str(&format!("<{}>", args.join(", ")));
+    }
+    if ty.is_array {
+        s.push_str("[]");
+    }
+    s
+}
+
+fn string_literal(value: &str) -> String {
+    let mut text = String::with_capacity(value.len() + 2);
+    text.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' => text.push_str("\\'"),
+            '\\' => text.push_str("\\\\"),
+            '\n' => text.push_str("\\n"),
+            '\r' => text.push_str("\\r"),
+            '\t' => text.push_str("\\t"),
+            c => text.push(c),
+        }
+    }
+    text.push('\'');
+    text
+}
+
+/// A LIKE pattern, whose backslashes are already escapes (see
+/// [`BinaryOp::Like`])
+fn like_pattern(pattern: &str) -> String {
+    string_literal(pattern).replace("\\\\", "\\")
+}
+
+/// The written type of a collection literal, or `fallback` for one built
+/// without a type
+fn literal_type(ty: &Option<TypeRef>, fallback: &str) -> String {
+    ty.as_ref().map_or_else(|| fallback.to_string(), type_ref)
+}
+
+/// The object of a member access; a generic type literal stands for its
+/// type there, `List<String>.class` being only the whole expression
+fn receiver(object: &Expression) -> String {
+    match object {
+        Expression::TypeLiteral(ty, _, _) => type_ref(ty),
+        other => expression(other),
+    }
+}
+
+fn binary_operator(operator: BinaryOp) -> &'static str {
+    match operator {
+        BinaryOp::Add => "+",
+        BinaryOp::Subtract => "-",
+        BinaryOp::Multiply => "*",
+        BinaryOp::Divide => "/",
+        BinaryOp::Modulo => "%",
+        BinaryOp::Equal => "==",
+        BinaryOp::NotEqual => "!=",
+        BinaryOp::ExactEqual => "===",
+        BinaryOp::ExactNotEqual => "!==",
+        BinaryOp::LessThan => "<",
+        BinaryOp::GreaterThan => ">",
+        BinaryOp::LessOrEqual => "<=",
+        BinaryOp::GreaterOrEqual => ">=",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+        BinaryOp::BitwiseAnd => "&",
+        BinaryOp::BitwiseOr => "|",
+        BinaryOp::BitwiseXor => "^",
+        BinaryOp::LeftShift => "<<",
+        BinaryOp::RightShift => ">>",
+        BinaryOp::UnsignedRightShift => ">>>",
+        BinaryOp::Like => "LIKE",
+        BinaryOp::In => "IN",
+        BinaryOp::NotIn => "NOT IN",
+        BinaryOp::Includes => "INCLUDES",
+        BinaryOp::Excludes => "EXCLUDES",
+    }
+}
+
+fn assignment_operator(operator: AssignmentOp) -> &'static str {
+    match operator {
+        AssignmentOp::Assign => "=",
+        AssignmentOp::AddAssign => "+=",
+        AssignmentOp::SubAssign => "-=",
+        AssignmentOp::MulAssign => "*=",
+        AssignmentOp::DivAssign => "/=",
+        AssignmentOp::ModAssign => "%=",
+        AssignmentOp::AndAssign => "&=",
+        AssignmentOp::OrAssign => "|=",
+        AssignmentOp::XorAssign => "^=",
+        AssignmentOp::LeftShiftAssign => "<<=",
+        AssignmentOp::RightShiftAssign => ">>=",
+        AssignmentOp::UnsignedRightShiftAssign => ">>>=",
+    }
+}
+
+fn initializer(items: &[Expression]) -> String {
+    format!("{{{}}}", arguments(items))
+}
+
+fn map_initializer(entries: &[(Expression, Expression)]) -> String {
+    let entries: Vec<String> = entries
+        .iter()
+        .map(|(key, value)| format!("{} => {}", expression(key), expression(value)))
+        .collect();
+    format!("{{{}}}", entries.join(", "))
+}
+
+/// Apex source for an expression
+pub(crate) fn expression(expr: &Expression) -> String {
+    match expr {
+        Expression::Null(..) => "null".to_string(),
+        Expression::Boolean(b, _, _) => b.to_string(),
+        Expression::Integer(_, raw, _, _)
+        | Expression::Long(_, raw, _, _)
+        | Expression::Double(_, raw, _, _) => raw.clone(),
+        Expression::String(s, _, _) => string_literal(s),
+        Expression::Identifier(name, _, _) => name.clone(),
+        Expression::This(..) => "this".to_string(),
+        Expression::Super(..) => "super".to_string(),
+        Expression::FieldAccess(access) => {
+            format!("{}.{}", receiver(&access.object), access.field)
+        }
+        Expression::ArrayAccess(access) => {
+            format!(
+                "{}[{}]",
+                expression(&access.array),
+                expression(&access.index)
+            )
+        }
+        Expression::SafeNavigation(access) => {
+            format!("{}?.{}", expression(&access.object), access.field)
+        }
+        Expression::MethodCall(call) => {
+            let args = arguments(&call.arguments);
+            match call.object {
+                // `a?.m()` is a call on the safe navigation `a?.m`
+                Some(Expression::SafeNavigation(ref safe)) if safe.field == call.name => {
+                    format!("{}?.{}({})", expression(&safe.object), call.name, args)
+                }
+                Some(ref object) => format!("{}.{}({})", receiver(object), call.name, args),
+                None => format!("{}({})", call.name, args),
+            }
+        }
+        Expression::New(new) => {
+            format!(
+                "new {}({})",
+                type_ref(&new.type_ref),
+                arguments(&new.arguments)
+            )
+        }
+        Expression::NewArray(array) => {
+            let element = type_ref(&array.element_type);
+            match (&array.size, &array.initializer) {
+                (Some(size), _) => format!("new {}[{}]", element, expression(size)),
+                (None, Some(items)) => format!("new {}[]{}", element, initializer(items)),
+                (None, None) => format!("new {}[]", element),
+            }
+        }
+        Expression::NewMap(map) => match map.initializer {
+            Some(ref entries) => {
+                format!(
+                    "new {}{}",
+                    type_ref(&map.type_ref),
+                    map_initializer(entries)
+                )
+            }
+            None => format!("new {}()", type_ref(&map.type_ref)),
+        },
+        Expression::Unary(unary) => {
+            let operand = expression(&unary.operand);
+            let operator = match unary.operator {
+                UnaryOp::Negate => "-",
+                UnaryOp::Not => "!",
+                UnaryOp::BitwiseNot => "~",
+            };
+            // `- -x` and `- --x` must not run together into `--`
+            if unary.operator == UnaryOp::Negate && operand.starts_with('-') {
+                format!("{} {}", operator, operand)
+            } else {
+                format!("{}{}", operator, operand)
+            }
+        }
+        Expression::Binary(binary) => format!(
+            "{} {} {}",
+            expression(&binary.left),
+            binary_operator(binary.operator),
+            expression(&binary.right)
+        ),
+        Expression::Ternary(ternary) => format!(
+            "{} ? {} : {}",
+            expression(&ternary.condition),
+            expression(&ternary.then_expr),
+            expression(&ternary.else_expr)
+        ),
+        Expression::NullCoalesce(nc) => {
+            format!("{} ?? {}", expression(&nc.left), expression(&nc.right))
+        }
+        Expression::Instanceof(instanceof) => format!(
+            "{} instanceof {}",
+            expression(&instanceof.expression),
+            type_ref(&instanceof.type_ref)
+        ),
+        Expression::Cast(cast) => {
+            format!(
+                "({}) {}",
+                type_ref(&cast.type_ref),
+                expression(&cast.expression)
+            )
+        }
+        Expression::Assignment(assignment) => format!(
+            "{} {} {}",
+            expression(&assignment.target),
+            assignment_operator(assignment.operator),
+            expression(&assignment.value)
+        ),
+        Expression::PostIncrement(operand, _, _) => format!("{}++", expression(operand)),
+        Expression::PostDecrement(operand, _, _) => format!("{}--", expression(operand)),
+        Expression::PreIncrement(operand, _, _) => format!("++{}", expression(operand)),
+        Expression::PreDecrement(operand, _, _) => format!("--{}", expression(operand)),
+        Expression::Soql(query) => format!("[{}]", soql(query)),
+        Expression::Sosl(query) => format!("[{}]", sosl(query)),
+        Expression::SoqlSubquery(query) => format!("({})", soql(query)),
+        Expression::BindVariable(path, _, _) => format!(":{}", path),
+        Expression::BindExpression(bound, _, _) => format!(":{}", expression(bound)),
+        Expression::Parenthesized(inner, _, _) => format!("({})", expression(inner)),
+        Expression::ListLiteral(items, ty, ..) => {
+            format!(
+                "new {}{}",
+                literal_type(ty, "List<Object>"),
+                initializer(items)
+            )
+        }
+        Expression::SetLiteral(items, ty, ..) => {
+            format!(
+                "new {}{}",
+                literal_type(ty, "Set<Object>"),
+                initializer(items)
+            )
+        }
+        Expression::MapLiteral(entries, ty, ..) => format!(
+            "new {}{}",
+            literal_type(ty, "Map<Object, Object>"),
+            map_initializer(entries)
+        ),
+        Expression::TypeLiteral(ty, _, _) => format!("{}.class", type_ref(ty)),
+    }
+}
+
+// ==================== SOQL and SOSL ====================
+
+/// Query text without brackets
+pub(crate) fn soql(query: &SoqlQuery) -> String {
+    let fields: Vec<String> = query.select_clause.iter().map(select_field).collect();
+    let mut text = format!("SELECT {} FROM {}", fields.join(", "), query.from_clause);
+    if let Some(ref scope) = query.using_scope {
+        text.push_str(&format!(" USING SCOPE {}", scope));
+    }
+    if let Some(ref filter) = query.where_clause {
+        text.push_str(&format!(" WHERE {}", soql_expression(filter)));
+    }
+    if let Some(with) = query.with_clause {
+        text.push_str(match with {
+            SoqlWithClause::SecurityEnforced => " WITH SECURITY_ENFORCED",
+            SoqlWithClause::UserMode => " WITH USER_MODE",
+            SoqlWithClause::SystemMode => " WITH SYSTEM_MODE",
+        });
+    }
+    match query.grouping_sets {
+        Some(sets) => text.push_str(&format!(
+            " GROUP BY {}({})",
+            sets.as_str(),
+            query.group_by_clause.join(", ")
+        )),
+        None if !query.group_by_clause.is_empty() => {
+            text.push_str(&format!(" GROUP BY {}", query.group_by_clause.join(", ")));
+        }
+        None => {}
+    }
+    if let Some(ref having) = query.having_clause {
+        text.push_str(&format!(" HAVING {}", soql_expression(having)));
+    }
+    if !query.order_by_clause.is_empty() {
+        text.push_str(&format!(" ORDER BY {}", order_by(&query.order_by_clause)));
+    }
+    if let Some(ref limit) = query.limit_clause {
+        text.push_str(&format!(" LIMIT {}", soql_expression(limit)));
+    }
+    if let Some(ref offset) = query.offset_clause {
+        text.push_str(&format!(" OFFSET {}", soql_expression(offset)));
+    }
+    if let Some(for_clause) = query.for_clause {
+        text.push_str(match for_clause {
+            ForClause::View => " FOR VIEW",
+            ForClause::Reference => " FOR REFERENCE",
+            ForClause::Update => " FOR UPDATE",
+        });
+    }
+    text
+}
+
+fn select_field(field: &SelectField) -> String {
+    match field {
+        SelectField::Field(name) => name.clone(),
+        SelectField::SubQuery(query) => format!("({})", soql(query)),
+        SelectField::TypeOf(clause) => {
+            let mut text = format!("TYPEOF {}", clause.field);
+            for when in &clause.when_clauses {
+                text.push_str(&format!(
+                    " WHEN {} THEN {}",
+                    when.type_name,
+                    when.fields.join(", ")
+                ));
+            }
+            if let Some(ref fields) = clause.else_fields {
+                text.push_str(&format!(" ELSE {}", fields.join(", ")));
+            }
+            text.push_str(" END");
+            text
+        }
+        SelectField::AggregateFunction { name, field, alias } => match alias {
+            Some(alias) => format!("{}({}) {}", name, field, alias),
+            None => format!("{}({})", name, field),
+        },
+        SelectField::Fields(scope) => format!("FIELDS({})", scope.as_str()),
+    }
+}
+
+fn order_by(fields: &[OrderByField]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let mut text = field.field.clone();
+            if !field.ascending {
+                text.push_str(" DESC");
+            }
+            match field.nulls_first {
+                Some(true) => text.push_str(" NULLS FIRST"),
+                Some(false) => text.push_str(" NULLS LAST"),
+                None => {}
+            }
+            text
+        })
+        .collect();
+    fields.join(", ")
+}
+
+/// A WHERE or HAVING condition, or a value in one; Apex syntax inside binds
+fn soql_expression(expr: &Expression) -> String {
+    match expr {
+        Expression::Binary(binary) => {
+            let right = match (&binary.operator, &binary.right) {
+                // `IN ('a', 'b')` keeps its values as an array initializer
+                (BinaryOp::In | BinaryOp::NotIn, Expression::NewArray(array))
+                    if array.size.is_none() =>
+                {
+                    let values: Vec<String> = array
+                        .initializer
+                        .iter()
+                        .flatten()
+                        .map(soql_expression)
+                        .collect();
+                    format!("({})", values.join(", "))
+                }
+                (BinaryOp::Like, Expression::String(pattern, _, _)) => like_pattern(pattern),
+                (_, right) => soql_expression(right),
+            };
+            let operator = match binary.operator {
+                BinaryOp::Equal => "=",
+                BinaryOp::And => "AND",
+                BinaryOp::Or => "OR",
+                other => binary_operator(other),
+            };
+            format!("{} {} {}", soql_expression(&binary.left), operator, right)
+        }
+        Expression::Unary(unary) if unary.operator == UnaryOp::Not => {
+            format!("NOT {}", soql_expression(&unary.operand))
+        }
+        Expression::Parenthesized(inner, _, _) => format!("({})", soql_expression(inner)),
+        Expression::MethodCall(call) if call.object.is_none() => {
+            let args: Vec<String> = call.arguments.iter().map(soql_expression).collect();
+            format!("{}({})", call.name, args.join(", "))
+        }
+        other => expression(other),
+    }
+}
+
+fn sosl(query: &SoslQuery) -> String {
+    let mut text = format!("FIND {}", string_literal(&query.search_term));
+    if let Some(group) = query.search_group {
+        text.push_str(match group {
+            SearchGroup::AllFields => " IN ALL FIELDS",
+            SearchGroup::NameFields => " IN NAME FIELDS",
+            SearchGroup::EmailFields => " IN EMAIL FIELDS",
+            SearchGroup::PhoneFields => " IN PHONE FIELDS",
+            SearchGroup::SidebarFields => " IN SIDEBAR FIELDS",
+        });
+    }
+    if !query.returning.is_empty() {
+        let returning: Vec<String> = query.returning.iter().map(sosl_returning).collect();
+        text.push_str(&format!(" RETURNING {}", returning.join(", ")));
+    }
+    for with in &query.with_clauses {
+        text.push_str(&match with {
+            SoslWithClause::DataCategory(group, category) => {
+                format!(" WITH DATA CATEGORY {} AT {}", group, category)
+            }
+            SoslWithClause::Network(network) => format!(" WITH NETWORK = {}", network),
+            SoslWithClause::Snippet => " WITH SNIPPET".to_string(),
+            SoslWithClause::SpellCorrection => " WITH SPELLCORRECTION".to_string(),
+        });
+    }
+    if let Some(ref limit) = query.limit_clause {
+        text.push_str(&format!(" LIMIT {}", soql_expression(limit)));
+    }
+    text
+}
+
+fn sosl_returning(returning: &SoslReturning) -> String {
+    let mut clauses = vec![returning.fields.join(", ")];
+    if let Some(ref filter) = returning.where_clause {
+        clauses.push(format!("WHERE {}", soql_expression(filter)));
+    }
+    if !returning.order_by.is_empty() {
+        clauses.push(format!("ORDER BY {}", order_by(&returning.order_by)));
+    }
+    if let Some(limit) = returning.limit_clause {
+        clauses.push(format!("LIMIT {}", limit));
+    }
+    clauses.retain(|clause| !clause.is_empty());
+    if clauses.is_empty() {
+        returning.object.clone()
+    } else {
+        format!("{}({})", returning.object, clauses.join(" "))
+    }
+}
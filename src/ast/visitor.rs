@@ -0,0 +1,474 @@
+//! Read-only traversal of a whole compilation unit
+//!
+//! Implement [`Visitor`] for the nodes an analysis cares about and hand it
+//! to one of the `walk_*` functions, which visit every node below the one
+//! given, parents before children. The `visit_*` methods do nothing by
+//! default and never need to recurse themselves:
+//!
+//! ```
+//! use apexrust::ast::visitor::{walk_compilation_unit, Visitor};
+//! use apexrust::{parse, MethodCallExpr};
+//!
+//! #[derive(Default)]
+//! struct Calls(Vec<String>);
+//!
+//! impl<'ast> Visitor<'ast> for Calls {
+//!     fn visit_method_call(&mut self, call: &'ast MethodCallExpr) {
+//!         self.0.push(call.name.clone());
+//!     }
+//! }
+//!
+//! let unit = parse("public class A { void m() { System.debug(load()); } }").unwrap();
+//! let mut calls = Calls::default();
+//! walk_compilation_unit(&mut calls, &unit);
+//! assert_eq!(calls.0, ["debug", "load"]);
+//! ```
+//!
+//! Every expression is reached, including field initializers, property
+//! accessor bodies, constructor chain arguments, and the WHERE, HAVING,
+//! LIMIT and OFFSET expressions of SOQL queries with the binds in them.
+//! Nodes carry their [`NodeId`] for keying a [`SideTable`]; identifiers and
+//! binds, which have no node struct, are given theirs.
+
+use super::*;
+
+/// Callbacks for the nodes of an AST, each a no-op unless overridden
+///
+/// `visit_statement` and `visit_expression` see every statement and
+/// expression; the variant methods see the same node again, unwrapped.
+/// `leave_statement` and `leave_expression` see them once more after
+/// everything inside them, for visitors keeping a stack of enclosing nodes.
+#[allow(unused_variables)]
+pub trait Visitor<'ast> {
+    fn visit_compilation_unit(&mut self, unit: &'ast CompilationUnit) {}
+    fn visit_class(&mut self, class: &'ast ClassDeclaration) {}
+    fn visit_interface(&mut self, interface: &'ast InterfaceDeclaration) {}
+    fn visit_enum(&mut self, declaration: &'ast EnumDeclaration) {}
+    fn visit_trigger(&mut self, trigger: &'ast TriggerDeclaration) {}
+
+    fn visit_field(&mut self, field: &'ast FieldDeclaration) {}
+    fn visit_method(&mut self, method: &'ast MethodDeclaration) {}
+    fn visit_method_signature(&mut self, method: &'ast MethodSignature) {}
+    fn visit_constructor(&mut self, constructor: &'ast ConstructorDeclaration) {}
+    fn visit_property(&mut self, property: &'ast PropertyDeclaration) {}
+    fn visit_property_accessor(&mut self, accessor: &'ast PropertyAccessor) {}
+    fn visit_parameter(&mut self, parameter: &'ast Parameter) {}
+    fn visit_variable_declarator(&mut self, declarator: &'ast VariableDeclarator) {}
+    fn visit_block(&mut self, block: &'ast Block) {}
+
+    fn visit_statement(&mut self, stmt: &'ast Statement) {}
+    fn visit_local_variable(&mut self, local: &'ast LocalVariableDeclaration) {}
+    fn visit_expression_statement(&mut self, stmt: &'ast ExpressionStatement) {}
+    fn visit_if(&mut self, stmt: &'ast IfStatement) {}
+    fn visit_for(&mut self, stmt: &'ast ForStatement) {}
+    fn visit_for_each(&mut self, stmt: &'ast ForEachStatement) {}
+    fn visit_while(&mut self, stmt: &'ast WhileStatement) {}
+    fn visit_do_while(&mut self, stmt: &'ast DoWhileStatement) {}
+    fn visit_switch(&mut self, stmt: &'ast SwitchStatement) {}
+    fn visit_when_clause(&mut self, when: &'ast WhenClause) {}
+    fn visit_return(&mut self, stmt: &'ast ReturnStatement) {}
+    fn visit_throw(&mut self, stmt: &'ast ThrowStatement) {}
+    fn visit_break(&mut self, stmt: &'ast BreakStatement) {}
+    fn visit_continue(&mut self, stmt: &'ast ContinueStatement) {}
+    fn visit_try(&mut self, stmt: &'ast TryStatement) {}
+    fn visit_catch_clause(&mut self, catch: &'ast CatchClause) {}
+    fn visit_dml(&mut self, stmt: &'ast DmlStatement) {}
+    fn visit_run_as(&mut self, stmt: &'ast RunAsStatement) {}
+    fn visit_labeled(&mut self, stmt: &'ast LabeledStatement) {}
+    fn leave_statement(&mut self, stmt: &'ast Statement) {}
+
+    fn visit_expression(&mut self, expr: &'ast Expression) {}
+    fn visit_field_access(&mut self, expr: &'ast FieldAccessExpr) {}
+    fn visit_array_access(&mut self, expr: &'ast ArrayAccessExpr) {}
+    fn visit_safe_navigation(&mut self, expr: &'ast SafeNavigationExpr) {}
+    fn visit_method_call(&mut self, expr: &'ast MethodCallExpr) {}
+    fn visit_new(&mut self, expr: &'ast NewExpr) {}
+    fn visit_new_array(&mut self, expr: &'ast NewArrayExpr) {}
+    fn visit_new_map(&mut self, expr: &'ast NewMapExpr) {}
+    fn visit_unary(&mut self, expr: &'ast UnaryExpr) {}
+    fn visit_binary(&mut self, expr: &'ast BinaryExpr) {}
+    fn visit_ternary(&mut self, expr: &'ast TernaryExpr) {}
+    fn visit_null_coalesce(&mut self, expr: &'ast NullCoalesceExpr) {}
+    fn visit_instanceof(&mut self, expr: &'ast InstanceofExpr) {}
+    fn visit_cast(&mut self, expr: &'ast CastExpr) {}
+    fn visit_assignment(&mut self, expr: &'ast AssignmentExpr) {}
+    fn visit_identifier(&mut self, name: &'ast str, span: Span, id: NodeId) {}
+    fn visit_bind_variable(&mut self, name: &'ast str, span: Span, id: NodeId) {}
+    fn leave_expression(&mut self, expr: &'ast Expression) {}
+
+    fn visit_soql_query(&mut self, query: &'ast SoqlQuery) {}
+    fn visit_sosl_query(&mut self, query: &'ast SoslQuery) {}
+}
+
+pub fn walk_compilation_unit<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    unit: &'ast CompilationUnit,
+) {
+    visitor.visit_compilation_unit(unit);
+    for declaration in &unit.declarations {
+        walk_type_declaration(visitor, declaration);
+    }
+}
+
+pub fn walk_type_declaration<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    declaration: &'ast TypeDeclaration,
+) {
+    match declaration {
+        TypeDeclaration::Class(class) => walk_class(visitor, class),
+        TypeDeclaration::Interface(interface) => walk_interface(visitor, interface),
+        TypeDeclaration::Enum(e) => visitor.visit_enum(e),
+        TypeDeclaration::Trigger(trigger) => {
+            visitor.visit_trigger(trigger);
+            walk_block(visitor, &trigger.body);
+        }
+    }
+}
+
+pub fn walk_class<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, class: &'ast ClassDeclaration) {
+    visitor.visit_class(class);
+    for member in &class.members {
+        walk_class_member(visitor, member);
+    }
+}
+
+pub fn walk_interface<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    interface: &'ast InterfaceDeclaration,
+) {
+    visitor.visit_interface(interface);
+    for InterfaceMember::Method(method) in &interface.members {
+        visitor.visit_method_signature(method);
+        for parameter in &method.parameters {
+            visitor.visit_parameter(parameter);
+        }
+    }
+}
+
+pub fn walk_class_member<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    member: &'ast ClassMember,
+) {
+    match member {
+        ClassMember::Field(field) => {
+            visitor.visit_field(field);
+            for declarator in &field.declarators {
+                walk_variable_declarator(visitor, declarator);
+            }
+        }
+        ClassMember::Method(method) => {
+            visitor.visit_method(method);
+            for parameter in &method.parameters {
+                visitor.visit_parameter(parameter);
+            }
+            if let Some(ref body) = method.body {
+                walk_block(visitor, body);
+            }
+        }
+        ClassMember::Constructor(constructor) => {
+            visitor.visit_constructor(constructor);
+            for parameter in &constructor.parameters {
+                visitor.visit_parameter(parameter);
+            }
+            if let Some(ref chain) = constructor.chained_constructor {
+                for argument in &chain.arguments {
+                    walk_expression(visitor, argument);
+                }
+            }
+            walk_block(visitor, &constructor.body);
+        }
+        ClassMember::Property(property) => {
+            visitor.visit_property(property);
+            for accessor in [&property.getter, &property.setter].into_iter().flatten() {
+                visitor.visit_property_accessor(accessor);
+                if let Some(ref body) = accessor.body {
+                    walk_block(visitor, body);
+                }
+            }
+        }
+        ClassMember::StaticBlock(block) => walk_block(visitor, block),
+        ClassMember::InnerClass(class) => walk_class(visitor, class),
+        ClassMember::InnerInterface(interface) => walk_interface(visitor, interface),
+        ClassMember::InnerEnum(e) => visitor.visit_enum(e),
+    }
+}
+
+fn walk_variable_declarator<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    declarator: &'ast VariableDeclarator,
+) {
+    visitor.visit_variable_declarator(declarator);
+    if let Some(ref initializer) = declarator.initializer {
+        walk_expression(visitor, initializer);
+    }
+}
+
+fn walk_local_variable<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    local: &'ast LocalVariableDeclaration,
+) {
+    visitor.visit_local_variable(local);
+    for declarator in &local.declarators {
+        walk_variable_declarator(visitor, declarator);
+    }
+}
+
+pub fn walk_block<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, block: &'ast Block) {
+    visitor.visit_block(block);
+    for stmt in &block.statements {
+        walk_statement(visitor, stmt);
+    }
+}
+
+pub fn walk_statement<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast Statement) {
+    visitor.visit_statement(stmt);
+    match stmt {
+        Statement::Block(block) => walk_block(visitor, block),
+        Statement::LocalVariable(local) => walk_local_variable(visitor, local),
+        Statement::Expression(s) => {
+            visitor.visit_expression_statement(s);
+            walk_expression(visitor, &s.expression);
+        }
+        Statement::If(s) => {
+            visitor.visit_if(s);
+            walk_expression(visitor, &s.condition);
+            walk_statement(visitor, &s.then_branch);
+            if let Some(ref else_branch) = s.else_branch {
+                walk_statement(visitor, else_branch);
+            }
+        }
+        Statement::For(s) => {
+            visitor.visit_for(s);
+            match &s.init {
+                Some(ForInit::Variables(local)) => walk_local_variable(visitor, local),
+                Some(ForInit::Expressions(exprs)) => {
+                    for expr in exprs {
+                        walk_expression(visitor, expr);
+                    }
+                }
+                None => {}
+            }
+            if let Some(ref condition) = s.condition {
+                walk_expression(visitor, condition);
+            }
+            for update in &s.update {
+                walk_expression(visitor, update);
+            }
+            walk_statement(visitor, &s.body);
+        }
+        Statement::ForEach(s) => {
+            visitor.visit_for_each(s);
+            walk_expression(visitor, &s.iterable);
+            walk_statement(visitor, &s.body);
+        }
+        Statement::While(s) => {
+            visitor.visit_while(s);
+            walk_expression(visitor, &s.condition);
+            walk_statement(visitor, &s.body);
+        }
+        Statement::DoWhile(s) => {
+            visitor.visit_do_while(s);
+            walk_statement(visitor, &s.body);
+            walk_expression(visitor, &s.condition);
+        }
+        Statement::Switch(s) => {
+            visitor.visit_switch(s);
+            walk_expression(visitor, &s.expression);
+            for when in &s.when_clauses {
+                visitor.visit_when_clause(when);
+                if let WhenValue::Literals(ref values) = when.values {
+                    for value in values {
+                        walk_expression(visitor, value);
+                    }
+                }
+                walk_block(visitor, &when.block);
+            }
+        }
+        Statement::Return(s) => {
+            visitor.visit_return(s);
+            if let Some(ref value) = s.value {
+                walk_expression(visitor, value);
+            }
+        }
+        Statement::Throw(s) => {
+            visitor.visit_throw(s);
+            walk_expression(visitor, &s.exception);
+        }
+        Statement::Break(s) => visitor.visit_break(s),
+        Statement::Continue(s) => visitor.visit_continue(s),
+        Statement::Try(s) => {
+            visitor.visit_try(s);
+            walk_block(visitor, &s.try_block);
+            for catch in &s.catch_clauses {
+                visitor.visit_catch_clause(catch);
+                walk_block(visitor, &catch.block);
+            }
+            if let Some(ref finally) = s.finally_block {
+                walk_block(visitor, finally);
+            }
+        }
+        Statement::Dml(s) => {
+            visitor.visit_dml(s);
+            walk_expression(visitor, &s.expression);
+        }
+        Statement::RunAs(s) => {
+            visitor.visit_run_as(s);
+            walk_expression(visitor, &s.user);
+            walk_block(visitor, &s.body);
+        }
+        Statement::Labeled(s) => {
+            visitor.visit_labeled(s);
+            walk_statement(visitor, &s.body);
+        }
+        Statement::Empty(..) => {}
+    }
+    visitor.leave_statement(stmt);
+}
+
+pub fn walk_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expr: &'ast Expression) {
+    visitor.visit_expression(expr);
+    match expr {
+        Expression::Identifier(name, span, id) => visitor.visit_identifier(name, *span, *id),
+        Expression::BindVariable(name, span, id) => visitor.visit_bind_variable(name, *span, *id),
+        Expression::FieldAccess(e) => {
+            visitor.visit_field_access(e);
+            walk_expression(visitor, &e.object);
+        }
+        Expression::ArrayAccess(e) => {
+            visitor.visit_array_access(e);
+            walk_expression(visitor, &e.array);
+            walk_expression(visitor, &e.index);
+        }
+        Expression::SafeNavigation(e) => {
+            visitor.visit_safe_navigation(e);
+            walk_expression(visitor, &e.object);
+        }
+        Expression::MethodCall(e) => {
+            visitor.visit_method_call(e);
+            if let Some(ref object) = e.object {
+                walk_expression(visitor, object);
+            }
+            for argument in &e.arguments {
+                walk_expression(visitor, argument);
+            }
+        }
+        Expression::New(e) => {
+            visitor.visit_new(e);
+            for argument in &e.arguments {
+                walk_expression(visitor, argument);
+            }
+        }
+        Expression::NewArray(e) => {
+            visitor.visit_new_array(e);
+            if let Some(ref size) = e.size {
+                walk_expression(visitor, size);
+            }
+            for item in e.initializer.iter().flatten() {
+                walk_expression(visitor, item);
+            }
+        }
+        Expression::NewMap(e) => {
+            visitor.visit_new_map(e);
+            for (key, value) in e.initializer.iter().flatten() {
+                walk_expression(visitor, key);
+                walk_expression(visitor, value);
+            }
+        }
+        Expression::Unary(e) => {
+            visitor.visit_unary(e);
+            walk_expression(visitor, &e.operand);
+        }
+        Expression::Binary(e) => {
+            visitor.visit_binary(e);
+            walk_expression(visitor, &e.left);
+            walk_expression(visitor, &e.right);
+        }
+        Expression::Ternary(e) => {
+            visitor.visit_ternary(e);
+            walk_expression(visitor, &e.condition);
+            walk_expression(visitor, &e.then_expr);
+            walk_expression(visitor, &e.else_expr);
+        }
+        Expression::NullCoalesce(e) => {
+            visitor.visit_null_coalesce(e);
+            walk_expression(visitor, &e.left);
+            walk_expression(visitor, &e.right);
+        }
+        Expression::Instanceof(e) => {
+            visitor.visit_instanceof(e);
+            walk_expression(visitor, &e.expression);
+        }
+        Expression::Cast(e) => {
+            visitor.visit_cast(e);
+            walk_expression(visitor, &e.expression);
+        }
+        Expression::Assignment(e) => {
+            visitor.visit_assignment(e);
+            walk_expression(visitor, &e.target);
+            walk_expression(visitor, &e.value);
+        }
+        Expression::PostIncrement(e, _, _)
+        | Expression::PostDecrement(e, _, _)
+        | Expression::PreIncrement(e, _, _)
+        | Expression::PreDecrement(e, _, _)
+        | Expression::Parenthesized(e, _, _)
+        | Expression::BindExpression(e, _, _) => walk_expression(visitor, e),
+        Expression::Soql(query) | Expression::SoqlSubquery(query) => {
+            walk_soql_query(visitor, query)
+        }
+        Expression::Sosl(query) => walk_sosl_query(visitor, query),
+        Expression::ListLiteral(items, ..) | Expression::SetLiteral(items, ..) => {
+            for item in items {
+                walk_expression(visitor, item);
+            }
+        }
+        Expression::MapLiteral(pairs, ..) => {
+            for (key, value) in pairs {
+                walk_expression(visitor, key);
+                walk_expression(visitor, value);
+            }
+        }
+        Expression::Null(..)
+        | Expression::Boolean(..)
+        | Expression::Integer(..)
+        | Expression::Long(..)
+        | Expression::Double(..)
+        | Expression::String(..)
+        | Expression::This(..)
+        | Expression::Super(..)
+        | Expression::TypeLiteral(..) => {}
+    }
+    visitor.leave_expression(expr);
+}
+
+/// Walk a query and its subqueries; the field names in SELECT, GROUP BY
+/// and ORDER BY are strings, not expressions
+pub fn walk_soql_query<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, query: &'ast SoqlQuery) {
+    visitor.visit_soql_query(query);
+    for field in &query.select_clause {
+        if let SelectField::SubQuery(subquery) = field {
+            walk_soql_query(visitor, subquery);
+        }
+    }
+    let clauses = [
+        &query.where_clause,
+        &query.having_clause,
+        &query.limit_clause,
+        &query.offset_clause,
+    ];
+    for expr in clauses.into_iter().flatten() {
+        walk_expression(visitor, expr);
+    }
+}
+
+pub fn walk_sosl_query<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, query: &'ast SoslQuery) {
+    visitor.visit_sosl_query(query);
+    for returning in &query.returning {
+        if let Some(ref filter) = returning.where_clause {
+            walk_expression(visitor, filter);
+        }
+    }
+    if let Some(ref limit) = query.limit_clause {
+        walk_expression(visitor, limit);
+    }
+}
@@ -0,0 +1,72 @@
+//! Cooperative cancellation for long-running parse, convert and transpile
+//! calls
+//!
+//! A [`CancellationToken`] is handed to a `Parser`, `SoqlToSqlConverter` or
+//! `Transpiler` through its `with_cancellation` builder. The work checks the
+//! token at natural boundaries (declarations, class members, batches of
+//! statements, queries, emitted classes) and stops with a `Cancelled` error
+//! once it fires. Without a token nothing is checked.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Shared flag that asks running work to stop, optionally with a deadline
+///
+/// Clones share the flag, so one clone can be cancelled from another thread
+/// while the work holding the other clone is running.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// A token that only fires when [`cancel`](Self::cancel) is called
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that also fires once `timeout` has elapsed, measured on the
+    /// monotonic clock
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            cancelled: Arc::default(),
+            deadline: Instant::now().checked_add(timeout),
+        }
+    }
+
+    /// Ask the work holding this token (or any clone of it) to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the token was cancelled or its deadline has passed
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_is_shared_between_clones() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+
+        clone.cancel();
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_timeout_fires_after_deadline() {
+        assert!(CancellationToken::with_timeout(Duration::ZERO).is_cancelled());
+        assert!(!CancellationToken::with_timeout(Duration::from_secs(3600)).is_cancelled());
+    }
+}
@@ -0,0 +1,240 @@
+//! Where "now" comes from, for output that depends on the date
+//!
+//! SOQL date literals such as `TODAY` and `LAST_N_DAYS:30` and the
+//! `CreatedDate` of seeded fixture records all depend on the current time.
+//! Each takes a [`ClockSource`] instead of reading the system clock, so a
+//! test or a reproducible build can pin it:
+//!
+//! ```
+//! use apexrust::{ClockSource, Timestamp};
+//!
+//! let clock = ClockSource::Fixed(Timestamp::from_ymd(2024, 2, 29).unwrap());
+//! assert_eq!(clock.now().iso_date(), "2024-02-29");
+//! assert_eq!(clock.now().iso_datetime(), "2024-02-29T00:00:00Z");
+//! ```
+//!
+//! [`ClockSource::System`] is the default. Generated SQL then leaves the
+//! date to the database (`CURRENT_DATE`, `date('now')`), evaluated when the
+//! query runs; any other source is read once, at conversion, and its date
+//! is written into the SQL as a literal.
+//!
+//! Transpiled code never reads a clock itself: `System.now()` and
+//! `Date.today()` become calls to the runtime's `now()` and `today()`, which
+//! the runtime can pin the same way.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A UTC instant, in whole seconds since the Unix epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    seconds: i64,
+}
+
+impl Timestamp {
+    pub fn from_unix_seconds(seconds: i64) -> Self {
+        Self { seconds }
+    }
+
+    /// Midnight UTC on a calendar date; `None` if the date does not exist
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Self> {
+        Self::from_ymd_hms(year, month, day, 0, 0, 0)
+    }
+
+    /// A UTC date and time; `None` if either does not exist
+    pub fn from_ymd_hms(
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+    ) -> Option<Self> {
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return None;
+        }
+        let days = days_from_civil(year as i64, month as i64, day as i64);
+        let seconds = days * 86_400 + (hour * 3600 + minute * 60 + second) as i64;
+        Some(Self { seconds })
+    }
+
+    pub fn unix_seconds(self) -> i64 {
+        self.seconds
+    }
+
+    /// `2024-06-30`
+    pub fn iso_date(self) -> String {
+        let (year, month, day) = civil_from_days(self.seconds.div_euclid(86_400));
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    /// `2024-06-30T09:00:00Z`, the form Salesforce gives datetimes in
+    pub fn iso_datetime(self) -> String {
+        let time = self.seconds.rem_euclid(86_400);
+        format!(
+            "{}T{:02}:{:02}:{:02}Z",
+            self.iso_date(),
+            time / 3600,
+            time / 60 % 60,
+            time % 60
+        )
+    }
+
+    fn from_system_time(time: SystemTime) -> Self {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(before) => -(before.duration().as_secs() as i64),
+        };
+        Self { seconds }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.iso_datetime())
+    }
+}
+
+/// A caller-supplied source of the current time, for clocks that advance
+/// under a test's control
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> Timestamp;
+}
+
+/// The current time used by conversion and fixture loading
+#[derive(Debug, Clone, Default)]
+pub enum ClockSource {
+    /// The system clock, or the database's when the time ends up in SQL
+    #[default]
+    System,
+    /// Always the given instant
+    Fixed(Timestamp),
+    /// Whatever the clock reports when asked
+    Custom(Arc<dyn Clock>),
+}
+
+impl ClockSource {
+    /// The current time by this source
+    pub fn now(&self) -> Timestamp {
+        match self {
+            ClockSource::System => Timestamp::from_system_time(SystemTime::now()),
+            ClockSource::Fixed(timestamp) => *timestamp,
+            ClockSource::Custom(clock) => clock.now(),
+        }
+    }
+
+    /// Whether this is the system clock, which generated SQL defers to the
+    /// database for
+    pub fn is_system(&self) -> bool {
+        matches!(self, ClockSource::System)
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Conversions between days since 1970-01-01 and proleptic Gregorian dates,
+// after Howard Hinnant's `days_from_civil` and `civil_from_days`
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    #[test]
+    fn test_calendar_round_trip() {
+        let epoch = Timestamp::from_ymd(1970, 1, 1).unwrap();
+        assert_eq!(epoch.unix_seconds(), 0);
+        assert_eq!(
+            Timestamp::from_ymd_hms(2024, 6, 30, 9, 5, 7)
+                .unwrap()
+                .iso_datetime(),
+            "2024-06-30T09:05:07Z"
+        );
+        assert_eq!(
+            Timestamp::from_unix_seconds(-1).iso_datetime(),
+            "1969-12-31T23:59:59Z"
+        );
+        for (year, month, day) in [(2000, 2, 29), (1900, 3, 1), (2024, 12, 31), (1600, 1, 1)] {
+            let date = Timestamp::from_ymd(year, month, day).unwrap();
+            assert_eq!(
+                date.iso_date(),
+                format!("{:04}-{:02}-{:02}", year, month, day)
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_dates_that_do_not_exist() {
+        assert!(Timestamp::from_ymd(2023, 2, 29).is_none());
+        assert!(Timestamp::from_ymd(1900, 2, 29).is_none());
+        assert!(Timestamp::from_ymd(2024, 4, 31).is_none());
+        assert!(Timestamp::from_ymd(2024, 13, 1).is_none());
+        assert!(Timestamp::from_ymd_hms(2024, 1, 1, 24, 0, 0).is_none());
+    }
+
+    #[derive(Debug, Default)]
+    struct Ticking(AtomicI64);
+
+    impl Clock for Ticking {
+        fn now(&self) -> Timestamp {
+            Timestamp::from_unix_seconds(self.0.fetch_add(86_400, Ordering::SeqCst))
+        }
+    }
+
+    #[test]
+    fn test_clock_sources() {
+        let fixed = ClockSource::Fixed(Timestamp::from_ymd(2024, 2, 29).unwrap());
+        assert_eq!(fixed.now(), fixed.now());
+        assert!(!fixed.is_system());
+        assert!(ClockSource::default().is_system());
+
+        let ticking = ClockSource::Custom(Arc::new(Ticking::default()));
+        assert_eq!(ticking.now().iso_date(), "1970-01-01");
+        assert_eq!(ticking.clone().now().iso_date(), "1970-01-02");
+    }
+}
@@ -0,0 +1,482 @@
+//! Quick fixes for diagnostics, as text edits
+//!
+//! A [`CodeActions`] registry maps rule ids to [`ActionProvider`]s. Given a
+//! [`Diagnostic`], the source and its AST, each provider registered for the
+//! diagnostic's rule (or for every rule, under `"*"`) may offer a
+//! [`CodeAction`]. The built-in providers are:
+//!
+//! - wrap the diagnosed statement in `try { } catch (Exception e) { }`,
+//!   offered for every rule
+//! - guard the statement with `if (x != null)` for the record `x` a field
+//!   is read from ([`UNSELECTED_FIELD`])
+//! - apply the [`BulkificationSuggestion`] of a query in a loop
+//!   ([`BULK_SAFETY`])
+//!
+//! An action is only returned when its edits do not overlap and the edited
+//! source still parses. Statements and expressions are located by the spans
+//! the parser gave them, which run to the end of the token after the node;
+//! [`ActionContext::range`] trims that token off.
+
+use std::ops::Range;
+
+use crate::analysis::bulk_safety::{BulkSafetyDiagnostic, BulkificationSuggestion};
+use crate::ast::printer;
+use crate::ast::visitor::{walk_compilation_unit, Visitor};
+use crate::ast::{
+    CatchClause, CompilationUnit, Expression, ForEachStatement, LocalVariableDeclaration, NodeId,
+    Parameter, Statement,
+};
+use crate::lexer::{tokenize, Span, Token};
+
+/// Rule id of [`find_loop_operations`](crate::analysis::bulk_safety::find_loop_operations)
+/// diagnostics
+pub const BULK_SAFETY: &str = "bulk-safety";
+/// Rule id of unselected field reads reported by `check_field_usage`
+pub const UNSELECTED_FIELD: &str = "unselected-field";
+/// Rule id of mistyped field literals reported by `check_field_usage`
+pub const FIELD_TYPE: &str = "field-type";
+
+/// Registers a provider for every rule
+pub const ANY_RULE: &str = "*";
+
+/// What a code action is asked to fix
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Id of the rule that reported it, such as [`BULK_SAFETY`]
+    pub rule: String,
+    /// The reported node, spanned as the analysis spans it
+    pub span: Span,
+    /// The loop a query runs in, with its rewrite, for [`BULK_SAFETY`]
+    pub bulkification: Option<(Span, BulkificationSuggestion)>,
+}
+
+impl Diagnostic {
+    pub fn new(rule: &str, span: Span) -> Self {
+        Self {
+            rule: rule.to_string(),
+            span,
+            bulkification: None,
+        }
+    }
+}
+
+impl From<&BulkSafetyDiagnostic> for Diagnostic {
+    fn from(diagnostic: &BulkSafetyDiagnostic) -> Self {
+        Self {
+            rule: BULK_SAFETY.to_string(),
+            span: diagnostic.span,
+            // A suggestion rewrites a query written in the loop itself
+            bulkification: diagnostic
+                .suggestion
+                .clone()
+                .filter(|_| diagnostic.call_chain.is_empty())
+                .map(|suggestion| (diagnostic.loop_span, suggestion)),
+        }
+    }
+}
+
+#[cfg(feature = "sql")]
+impl From<&crate::analysis::field_usage::FieldUsageDiagnostic> for Diagnostic {
+    fn from(diagnostic: &crate::analysis::field_usage::FieldUsageDiagnostic) -> Self {
+        use crate::analysis::field_usage::FieldUsageKind;
+
+        let rule = match diagnostic.kind {
+            FieldUsageKind::UnselectedField { .. } => UNSELECTED_FIELD,
+            FieldUsageKind::TypeMismatch { .. } => FIELD_TYPE,
+        };
+        Self::new(rule, diagnostic.span)
+    }
+}
+
+/// Replace the bytes `range` of the source with `new_text`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_text: String,
+}
+
+/// A fix an editor can offer, such as "Wrap in try-catch"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeAction {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// What a provider sees of a diagnostic
+pub struct ActionContext<'a> {
+    pub source: &'a str,
+    pub unit: &'a CompilationUnit,
+    pub diagnostic: &'a Diagnostic,
+    tokens: Vec<Token>,
+}
+
+impl ActionContext<'_> {
+    /// Source bytes of a parsed node: `span` without the token it runs into
+    pub fn range(&self, span: Span) -> Option<Range<usize>> {
+        let mut inside = self
+            .tokens
+            .iter()
+            .filter(|t| t.span.start >= span.start && t.span.end <= span.end);
+        let first = inside.next()?;
+        let last = inside.rev().nth(1).unwrap_or(first);
+        Some(first.span.start..last.span.end)
+    }
+
+    /// Whitespace before the first token on the line holding `offset`
+    pub fn line_indent(&self, offset: usize) -> &str {
+        let line_start = self.source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line = &self.source[line_start..];
+        &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+    }
+
+    /// One level of indentation as the file writes it: a tab, or the
+    /// smallest indent of any line (four spaces if none is indented)
+    pub fn indent_unit(&self) -> String {
+        let indents = self
+            .source
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| {
+                let trimmed = l.trim_start_matches([' ', '\t']);
+                &l[..l.len() - trimmed.len()]
+            });
+        let mut smallest: Option<usize> = None;
+        for indent in indents {
+            if indent.starts_with('\t') {
+                return "\t".to_string();
+            }
+            if !indent.is_empty() {
+                smallest = Some(smallest.map_or(indent.len(), |s| s.min(indent.len())));
+            }
+        }
+        " ".repeat(smallest.unwrap_or(4))
+    }
+
+    /// Innermost statement holding the diagnosed node
+    pub fn statement(&self) -> Option<&Statement> {
+        enclosing_statement(self.unit, self.diagnostic.span)
+    }
+}
+
+/// Offers a fix for a diagnostic, or `None` when it does not apply
+pub trait ActionProvider {
+    fn title(&self) -> String;
+    fn edits(&self, context: &ActionContext<'_>) -> Option<Vec<TextEdit>>;
+}
+
+/// Action providers by the rule id they handle
+pub struct CodeActions {
+    providers: Vec<(String, Box<dyn ActionProvider>)>,
+}
+
+impl CodeActions {
+    /// A registry without providers
+    pub fn new() -> Self {
+        Self {
+            providers: Vec::new(),
+        }
+    }
+
+    /// Offer `provider` for diagnostics of `rule`, or of every rule for
+    /// [`ANY_RULE`]
+    pub fn register(&mut self, rule: &str, provider: impl ActionProvider + 'static) {
+        self.providers.push((rule.to_string(), Box::new(provider)));
+    }
+
+    /// The fixes offered for `diagnostic`, rule-specific ones first
+    pub fn actions(
+        &self,
+        source: &str,
+        unit: &CompilationUnit,
+        diagnostic: &Diagnostic,
+    ) -> Vec<CodeAction> {
+        let context = ActionContext {
+            source,
+            unit,
+            diagnostic,
+            tokens: tokenize(source),
+        };
+        let specific = self
+            .providers
+            .iter()
+            .filter(|(rule, _)| *rule == diagnostic.rule);
+        let general = self.providers.iter().filter(|(rule, _)| rule == ANY_RULE);
+        specific
+            .chain(general)
+            .filter_map(|(_, provider)| {
+                let edits = provider.edits(&context)?;
+                validate(source, &edits).then(|| CodeAction {
+                    title: provider.title(),
+                    edits,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for CodeActions {
+    /// The built-in providers
+    fn default() -> Self {
+        let mut actions = Self::new();
+        actions.register(BULK_SAFETY, BulkifyQuery);
+        actions.register(UNSELECTED_FIELD, NullGuard);
+        actions.register(ANY_RULE, WrapInTryCatch);
+        actions
+    }
+}
+
+/// `source` with `edits` applied; the edits must not overlap
+pub fn apply_edits(source: &str, edits: &[TextEdit]) -> String {
+    let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+    sorted.sort_by_key(|edit| edit.range.start);
+    let mut result = String::with_capacity(source.len());
+    let mut copied = 0;
+    for edit in sorted {
+        result.push_str(&source[copied..edit.range.start]);
+        result.push_str(&edit.new_text);
+        copied = edit.range.end;
+    }
+    result.push_str(&source[copied..]);
+    result
+}
+
+/// Whether `edits` are disjoint and leave source that parses
+fn validate(source: &str, edits: &[TextEdit]) -> bool {
+    let mut ranges: Vec<&Range<usize>> = edits.iter().map(|edit| &edit.range).collect();
+    ranges.sort_by_key(|range| range.start);
+    let disjoint = ranges.windows(2).all(|pair| pair[0].end <= pair[1].start);
+    let in_bounds = ranges.iter().all(|range| {
+        range.start <= range.end
+            && range.end <= source.len()
+            && source.is_char_boundary(range.start)
+            && source.is_char_boundary(range.end)
+    });
+    disjoint && in_bounds && crate::parse(&apply_edits(source, edits)).is_ok()
+}
+
+/// `try { <statement> } catch (Exception e) { System.debug(...); }`
+pub struct WrapInTryCatch;
+
+impl ActionProvider for WrapInTryCatch {
+    fn title(&self) -> String {
+        "Wrap in try-catch".to_string()
+    }
+
+    fn edits(&self, context: &ActionContext<'_>) -> Option<Vec<TextEdit>> {
+        let statement = context.statement()?;
+        let range = context.range(statement.span())?;
+        let indent = context.line_indent(range.start);
+        let unit = context.indent_unit();
+        let variable = unused_name(context.unit, "e");
+
+        let body = indent_lines(&context.source[range.clone()], &unit);
+        let new_text = format!(
+            "try {{\n{indent}{unit}{body}\n{indent}}} catch (Exception {variable}) {{\n\
+             {indent}{unit}System.debug(LoggingLevel.ERROR, {variable}.getMessage());\n{indent}}}"
+        );
+        Some(vec![TextEdit { range, new_text }])
+    }
+}
+
+/// `if (acc != null) { <statement> }` around a statement reading a field
+/// of `acc`; a declaration is split so the variable stays in scope
+pub struct NullGuard;
+
+impl ActionProvider for NullGuard {
+    fn title(&self) -> String {
+        "Add null check".to_string()
+    }
+
+    fn edits(&self, context: &ActionContext<'_>) -> Option<Vec<TextEdit>> {
+        let read = field_access_at(context.unit, context.diagnostic.span)?;
+        let Expression::FieldAccess(access) = read else {
+            return None;
+        };
+        let condition = null_checks(&access.object)?.join(" && ");
+
+        let statement = context.statement()?;
+        let range = context.range(statement.span())?;
+        let indent = context.line_indent(range.start);
+        let unit = context.indent_unit();
+
+        let new_text = match statement {
+            Statement::LocalVariable(local) => {
+                let (declaration, assignment) = split_declaration(context, local, &range)?;
+                format!(
+                    "{declaration}\n{indent}if ({condition}) {{\n{indent}{unit}{assignment}\n{indent}}}"
+                )
+            }
+            _ => {
+                let body = indent_lines(&context.source[range.clone()], &unit);
+                format!("if ({condition}) {{\n{indent}{unit}{body}\n{indent}}}")
+            }
+        };
+        Some(vec![TextEdit { range, new_text }])
+    }
+}
+
+/// Collect the keys before the loop, query once, and read the results by
+/// key where the query was
+pub struct BulkifyQuery;
+
+impl ActionProvider for BulkifyQuery {
+    fn title(&self) -> String {
+        "Query once before the loop".to_string()
+    }
+
+    fn edits(&self, context: &ActionContext<'_>) -> Option<Vec<TextEdit>> {
+        let (loop_span, suggestion) = context.diagnostic.bulkification.as_ref()?;
+        let loop_range = context.range(*loop_span)?;
+        let query = context.range(context.diagnostic.span)?;
+        // The query's span starts at SELECT; replace its brackets too
+        let open = context.source[..query.start]
+            .trim_end()
+            .strip_suffix('[')?
+            .len();
+        let after = &context.source[query.end..];
+        let close = query.end + after.len() - after.trim_start().strip_prefix(']')?.len();
+        let query_range = open..close;
+        let indent = context.line_indent(loop_range.start);
+
+        let setup = format!("{}\n{}", suggestion.collect, suggestion.index);
+        let mut before: String = setup
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let indent = if i == 0 { "" } else { indent };
+                format!("{}{}\n", indent, line)
+            })
+            .collect();
+        before.push_str(indent);
+
+        Some(vec![
+            TextEdit {
+                range: loop_range.start..loop_range.start,
+                new_text: before,
+            },
+            TextEdit {
+                range: query_range,
+                new_text: suggestion.lookup.clone(),
+            },
+        ])
+    }
+}
+
+/// `text` with `unit` added to every line after the first
+fn indent_lines(text: &str, unit: &str) -> String {
+    text.replace('\n', &format!("\n{}", unit))
+}
+
+/// `acc != null` for `acc`, and each parent on the way for `acc.Owner`
+fn null_checks(record: &Expression) -> Option<Vec<String>> {
+    let mut checks = match record {
+        Expression::Identifier(..) => Vec::new(),
+        Expression::FieldAccess(access) => null_checks(&access.object)?,
+        _ => return None,
+    };
+    checks.push(format!("{} != null", printer::expression(record)));
+    Some(checks)
+}
+
+/// `Type name;` and `name = value;` for a single-variable declaration
+fn split_declaration(
+    context: &ActionContext<'_>,
+    local: &LocalVariableDeclaration,
+    range: &Range<usize>,
+) -> Option<(String, String)> {
+    let [declarator] = local.declarators.as_slice() else {
+        return None;
+    };
+    let value = context.range(declarator.initializer.as_ref()?.span())?;
+    let declared = context.source[range.start..value.start]
+        .trim_end()
+        .strip_suffix('=')?
+        .trim_end();
+    Some((
+        format!("{};", declared),
+        format!("{} = {};", declarator.name, &context.source[value]),
+    ))
+}
+
+/// `base`, or `base1`, `base2`... when the unit already uses that name
+fn unused_name(unit: &CompilationUnit, base: &str) -> String {
+    #[derive(Default)]
+    struct Names(Vec<String>);
+
+    impl<'ast> Visitor<'ast> for Names {
+        fn visit_parameter(&mut self, parameter: &'ast Parameter) {
+            self.0.push(parameter.name.to_lowercase());
+        }
+
+        fn visit_local_variable(&mut self, local: &'ast LocalVariableDeclaration) {
+            let names = local.declarators.iter().map(|d| d.name.to_lowercase());
+            self.0.extend(names);
+        }
+
+        fn visit_for_each(&mut self, stmt: &'ast ForEachStatement) {
+            self.0.push(stmt.variable.to_lowercase());
+        }
+
+        fn visit_catch_clause(&mut self, catch: &'ast CatchClause) {
+            self.0.push(catch.variable.to_lowercase());
+        }
+
+        fn visit_identifier(&mut self, name: &'ast str, _: Span, _: NodeId) {
+            self.0.push(name.to_lowercase());
+        }
+    }
+
+    let mut names = Names::default();
+    walk_compilation_unit(&mut names, unit);
+    let taken = |name: &str| names.0.iter().any(|n| *n == name.to_lowercase());
+    if !taken(base) {
+        return base.to_string();
+    }
+    (1..)
+        .map(|i| format!("{}{}", base, i))
+        .find(|name| !taken(name))
+        .expect("some suffix is free")
+}
+
+fn enclosing_statement(unit: &CompilationUnit, span: Span) -> Option<&Statement> {
+    struct Innermost<'ast> {
+        span: Span,
+        found: Option<&'ast Statement>,
+    }
+
+    impl<'ast> Visitor<'ast> for Innermost<'ast> {
+        fn visit_statement(&mut self, stmt: &'ast Statement) {
+            let outer = stmt.span();
+            let holds = outer.start <= self.span.start && self.span.end <= outer.end;
+            if holds && !matches!(stmt, Statement::Block(_)) {
+                self.found = Some(stmt);
+            }
+        }
+    }
+
+    let mut innermost = Innermost { span, found: None };
+    walk_compilation_unit(&mut innermost, unit);
+    innermost.found
+}
+
+fn field_access_at(unit: &CompilationUnit, span: Span) -> Option<&Expression> {
+    struct At<'ast> {
+        span: Span,
+        found: Option<&'ast Expression>,
+    }
+
+    impl<'ast> Visitor<'ast> for At<'ast> {
+        fn visit_expression(&mut self, expr: &'ast Expression) {
+            if self.found.is_none()
+                && matches!(expr, Expression::FieldAccess(_))
+                && expr.span() == self.span
+            {
+                self.found = Some(expr);
+            }
+        }
+    }
+
+    let mut at = At { span, found: None };
+    walk_compilation_unit(&mut at, unit);
+    at.found
+}
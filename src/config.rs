@@ -0,0 +1,628 @@
+//! Project configuration from `apexion.toml`
+//!
+//! A [`Config`] collects the defaults a project wants for parsing, linting,
+//! SOQL conversion and transpilation, plus which files belong to the project:
+//!
+//! ```toml
+//! [parser]
+//! recovery = true
+//!
+//! [lint.dynamic-soql]
+//! enabled = false
+//!
+//! [sql]
+//! dialect = "sqlite"
+//! filter_deleted = true
+//!
+//! [transpile]
+//! typescript = false
+//! static_cache = "request"
+//!
+//! [files]
+//! include = ["force-app/**/*.cls", "force-app/**/*.trigger"]
+//! exclude = ["**/*Test.cls"]
+//! ```
+//!
+//! Every setting is optional, so configurations layer: the file is
+//! overridden by `APEXION_*` environment variables ([`Config::from_env`]),
+//! which are overridden by command-line flags, each layer applied with
+//! [`Config::merge`]. Unknown keys are reported as warnings rather than
+//! errors so older tools keep reading newer files.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::ast::{CompilationUnit, NodeId};
+use crate::cancel::CancellationToken;
+use crate::parser::{ParseError, Parser};
+use crate::sql::{
+    BindVariableMode, CapabilityMode, CollationMode, ConversionConfig, IdComparison,
+    RecordTypeStrategy, SqlDialect, SqliteQualifier,
+};
+use crate::transpile::{StaticCacheStrategy, TranspileOptions};
+
+/// File name looked up at the project root
+pub const CONFIG_FILE_NAME: &str = "apexion.toml";
+
+/// Lint rules that can be configured under `[lint.<rule>]`
+pub const LINT_RULES: &[&str] = &["dynamic-soql", "deprecated", "todo"];
+
+/// Files selected when `[files] include` is not set
+const DEFAULT_INCLUDE: &[&str] = &["**/*.cls", "**/*.trigger"];
+
+/// Layered project configuration; `None` means "not set by this layer"
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub parser: ParserConfig,
+    /// Per-rule settings keyed by rule name (see [`LINT_RULES`])
+    pub lint: BTreeMap<String, LintRuleConfig>,
+    pub sql: SqlConfig,
+    pub transpile: TranspileConfig,
+    pub files: FilesConfig,
+}
+
+/// `[parser]`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ParserConfig {
+    /// Keep going after a syntax error to report further diagnostics, with
+    /// [`Parser::parse_with_recovery`]; otherwise parsing a file stops at its first
+    /// error
+    pub recovery: Option<bool>,
+}
+
+/// `[lint.<rule>]`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LintRuleConfig {
+    pub enabled: Option<bool>,
+    /// Rule-specific settings, passed through as written
+    #[serde(flatten)]
+    pub settings: BTreeMap<String, toml::Value>,
+}
+
+/// `[sql]`: defaults for [`ConversionConfig`]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SqlConfig {
+    pub dialect: Option<SqlDialect>,
+    pub bind_mode: Option<BindVariableMode>,
+    pub filter_deleted: Option<bool>,
+    pub deleted_column: Option<String>,
+    pub max_relationship_depth: Option<u8>,
+    /// `"case-insensitive"`, or `{ explicit = "<collation>" }`
+    pub string_collation: Option<CollationMode>,
+    /// Schema every table is qualified with
+    pub schema: Option<String>,
+    /// Per-object schemas keyed by API name
+    pub schema_overrides: BTreeMap<String, String>,
+    pub sqlite_qualifier: Option<SqliteQualifier>,
+    pub capability_mode: Option<CapabilityMode>,
+    /// `"join"`, or `{ denormalized-column = { column = "<column>" } }`
+    pub record_type_strategy: Option<RecordTypeStrategy>,
+    /// `"exact"`, `"normalize-to-18"` or `"case-insensitive"`
+    pub id_comparison: Option<IdComparison>,
+}
+
+/// `[transpile]`: defaults for [`TranspileOptions`]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TranspileConfig {
+    pub typescript: Option<bool>,
+    pub include_imports: Option<bool>,
+    pub indent: Option<String>,
+    pub async_database: Option<bool>,
+    pub static_cache: Option<StaticCacheStrategy>,
+    pub allowed_objects: Option<Vec<String>>,
+    pub split_trigger_events: Option<bool>,
+    pub split_class_threshold: Option<usize>,
+    pub test_context: Option<bool>,
+    /// `[transpile.env_flags]`: `"System.isBatch" = false`
+    pub env_flags: BTreeMap<String, bool>,
+}
+
+/// `[files]`: glob patterns relative to the project root, `/`-separated
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FilesConfig {
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+}
+
+/// Errors loading a configuration or the files it selects
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    #[error("Cannot read {path}: {message}")]
+    Io { path: PathBuf, message: String },
+
+    #[error("Invalid configuration: {0}")]
+    Invalid(String),
+
+    #[error("Invalid value for {variable}: {message}")]
+    InvalidEnv { variable: String, message: String },
+
+    #[error("Invalid file pattern '{pattern}': {message}")]
+    InvalidPattern { pattern: String, message: String },
+
+    #[error("Project parse cancelled")]
+    Cancelled,
+}
+
+/// Non-fatal problems found while loading a configuration
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigWarning {
+    /// Key not understood by this version, as a dotted path
+    UnknownKey(String),
+    /// `[lint.<rule>]` naming a rule that does not exist
+    UnknownLintRule(String),
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigWarning::UnknownKey(key) => write!(f, "Unknown configuration key '{}'", key),
+            ConfigWarning::UnknownLintRule(rule) => write!(f, "Unknown lint rule '{}'", rule),
+        }
+    }
+}
+
+/// Environment variables read by [`Config::from_env`] and the settings
+/// they override
+pub const ENV_VARS: &[(&str, &str, &str)] = &[
+    ("APEXION_PARSER_RECOVERY", "parser", "recovery"),
+    ("APEXION_SQL_DIALECT", "sql", "dialect"),
+    ("APEXION_SQL_SCHEMA", "sql", "schema"),
+    ("APEXION_SQL_FILTER_DELETED", "sql", "filter_deleted"),
+    ("APEXION_SQL_BIND_MODE", "sql", "bind_mode"),
+    ("APEXION_SQL_STRING_COLLATION", "sql", "string_collation"),
+    ("APEXION_SQL_ID_COMPARISON", "sql", "id_comparison"),
+    ("APEXION_TRANSPILE_TYPESCRIPT", "transpile", "typescript"),
+    (
+        "APEXION_TRANSPILE_STATIC_CACHE",
+        "transpile",
+        "static_cache",
+    ),
+];
+
+/// Known keys per section, for unknown-key warnings
+const KNOWN_KEYS: &[(&str, &[&str])] = &[
+    ("parser", &["recovery"]),
+    (
+        "sql",
+        &[
+            "dialect",
+            "bind_mode",
+            "filter_deleted",
+            "deleted_column",
+            "max_relationship_depth",
+            "string_collation",
+            "schema",
+            "schema_overrides",
+            "sqlite_qualifier",
+            "capability_mode",
+            "record_type_strategy",
+            "id_comparison",
+        ],
+    ),
+    (
+        "transpile",
+        &[
+            "typescript",
+            "include_imports",
+            "indent",
+            "async_database",
+            "static_cache",
+            "allowed_objects",
+            "split_trigger_events",
+            "split_class_threshold",
+            "test_context",
+            "env_flags",
+        ],
+    ),
+    ("files", &["include", "exclude"]),
+];
+
+impl Config {
+    /// Parse `apexion.toml` contents
+    pub fn from_toml_str(source: &str) -> Result<(Config, Vec<ConfigWarning>), ConfigError> {
+        let table: toml::Table =
+            toml::from_str(source).map_err(|e| ConfigError::Invalid(e.message().to_string()))?;
+        let warnings = unknown_keys(&table);
+        let config = table
+            .try_into()
+            .map_err(|e: toml::de::Error| ConfigError::Invalid(e.message().to_string()))?;
+        Ok((config, warnings))
+    }
+
+    /// Read and parse a configuration file
+    pub fn load(path: &Path) -> Result<(Config, Vec<ConfigWarning>), ConfigError> {
+        let source = std::fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+        Self::from_toml_str(&source)
+    }
+
+    /// Load `apexion.toml` from `root` if there is one
+    pub fn discover(root: &Path) -> Result<Option<(Config, Vec<ConfigWarning>)>, ConfigError> {
+        let path = root.join(CONFIG_FILE_NAME);
+        if path.is_file() {
+            Self::load(&path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Settings from the process environment; see [`ENV_VARS`]
+    pub fn from_env() -> Result<Config, ConfigError> {
+        Self::from_env_vars(std::env::vars())
+    }
+
+    /// Settings from `(name, value)` pairs; names outside [`ENV_VARS`] are
+    /// ignored
+    pub fn from_env_vars<I, K, V>(vars: I) -> Result<Config, ConfigError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut config = Config::default();
+        for (name, value) in vars {
+            let Some(&(variable, section, key)) =
+                ENV_VARS.iter().find(|(v, _, _)| *v == name.as_ref())
+            else {
+                continue;
+            };
+            let value = value.as_ref().trim();
+            let value = match value.to_lowercase().as_str() {
+                "true" | "1" | "yes" => toml::Value::Boolean(true),
+                "false" | "0" | "no" => toml::Value::Boolean(false),
+                _ => toml::Value::String(value.to_string()),
+            };
+            let mut section_table = toml::Table::new();
+            section_table.insert(key.to_string(), value);
+            let mut table = toml::Table::new();
+            table.insert(section.to_string(), toml::Value::Table(section_table));
+            let layer: Config =
+                table
+                    .try_into()
+                    .map_err(|e: toml::de::Error| ConfigError::InvalidEnv {
+                        variable: variable.to_string(),
+                        message: e.message().to_string(),
+                    })?;
+            config = config.merge(layer);
+        }
+        Ok(config)
+    }
+
+    /// Layer `other` on top of `self`: every setting `other` sets wins
+    pub fn merge(self, other: Config) -> Config {
+        let mut lint = self.lint;
+        for (rule, settings) in other.lint {
+            let entry = lint.entry(rule).or_default();
+            entry.enabled = settings.enabled.or(entry.enabled);
+            entry.settings.extend(settings.settings);
+        }
+        let mut schema_overrides = self.sql.schema_overrides;
+        schema_overrides.extend(other.sql.schema_overrides);
+        let mut env_flags = self.transpile.env_flags;
+        env_flags.extend(other.transpile.env_flags);
+
+        Config {
+            parser: ParserConfig {
+                recovery: other.parser.recovery.or(self.parser.recovery),
+            },
+            lint,
+            sql: SqlConfig {
+                dialect: other.sql.dialect.or(self.sql.dialect),
+                bind_mode: other.sql.bind_mode.or(self.sql.bind_mode),
+                filter_deleted: other.sql.filter_deleted.or(self.sql.filter_deleted),
+                deleted_column: other.sql.deleted_column.or(self.sql.deleted_column),
+                max_relationship_depth: other
+                    .sql
+                    .max_relationship_depth
+                    .or(self.sql.max_relationship_depth),
+                string_collation: other.sql.string_collation.or(self.sql.string_collation),
+                schema: other.sql.schema.or(self.sql.schema),
+                schema_overrides,
+                sqlite_qualifier: other.sql.sqlite_qualifier.or(self.sql.sqlite_qualifier),
+                capability_mode: other.sql.capability_mode.or(self.sql.capability_mode),
+                record_type_strategy: other
+                    .sql
+                    .record_type_strategy
+                    .or(self.sql.record_type_strategy),
+                id_comparison: other.sql.id_comparison.or(self.sql.id_comparison),
+            },
+            transpile: TranspileConfig {
+                typescript: other.transpile.typescript.or(self.transpile.typescript),
+                include_imports: other
+                    .transpile
+                    .include_imports
+                    .or(self.transpile.include_imports),
+                indent: other.transpile.indent.or(self.transpile.indent),
+                async_database: other
+                    .transpile
+                    .async_database
+                    .or(self.transpile.async_database),
+                static_cache: other.transpile.static_cache.or(self.transpile.static_cache),
+                allowed_objects: other
+                    .transpile
+                    .allowed_objects
+                    .or(self.transpile.allowed_objects),
+                split_trigger_events: other
+                    .transpile
+                    .split_trigger_events
+                    .or(self.transpile.split_trigger_events),
+                split_class_threshold: other
+                    .transpile
+                    .split_class_threshold
+                    .or(self.transpile.split_class_threshold),
+                test_context: other.transpile.test_context.or(self.transpile.test_context),
+                env_flags,
+            },
+            files: FilesConfig {
+                include: other.files.include.or(self.files.include),
+                exclude: other.files.exclude.or(self.files.exclude),
+            },
+        }
+    }
+
+    /// Whether a lint rule is enabled; rules are on unless disabled
+    pub fn lint_enabled(&self, rule: &str) -> bool {
+        self.lint.get(rule).and_then(|r| r.enabled).unwrap_or(true)
+    }
+
+    /// [`ConversionConfig`] defaults with the `[sql]` settings applied
+    pub fn conversion_config(&self) -> ConversionConfig {
+        let mut config = ConversionConfig::default();
+        let sql = &self.sql;
+        if let Some(dialect) = sql.dialect {
+            config.dialect = dialect;
+        }
+        if let Some(mode) = sql.bind_mode {
+            config.bind_mode = mode;
+        }
+        if let Some(filter_deleted) = sql.filter_deleted {
+            config.filter_deleted = filter_deleted;
+        }
+        if let Some(ref column) = sql.deleted_column {
+            config.deleted_column = column.clone();
+        }
+        if let Some(depth) = sql.max_relationship_depth {
+            config.max_relationship_depth = depth;
+        }
+        if let Some(ref collation) = sql.string_collation {
+            config.string_collation = collation.clone();
+        }
+        config.schema_qualifier = sql.schema.clone();
+        config.schema_overrides = sql
+            .schema_overrides
+            .iter()
+            .map(|(object, schema)| (object.clone(), schema.clone()))
+            .collect();
+        if let Some(qualifier) = sql.sqlite_qualifier {
+            config.sqlite_qualifier = qualifier;
+        }
+        if let Some(mode) = sql.capability_mode {
+            config.capability_mode = mode;
+        }
+        if let Some(ref strategy) = sql.record_type_strategy {
+            config.record_type_strategy = strategy.clone();
+        }
+        if let Some(comparison) = sql.id_comparison {
+            config.id_comparison = comparison;
+        }
+        config
+    }
+
+    /// [`TranspileOptions`] defaults with the `[transpile]` settings applied
+    pub fn transpile_options(&self) -> TranspileOptions {
+        let mut options = TranspileOptions::default();
+        let transpile = &self.transpile;
+        if let Some(typescript) = transpile.typescript {
+            options.typescript = typescript;
+        }
+        if let Some(include_imports) = transpile.include_imports {
+            options.include_imports = include_imports;
+        }
+        if let Some(ref indent) = transpile.indent {
+            options.indent = indent.clone();
+        }
+        if let Some(async_database) = transpile.async_database {
+            options.async_database = async_database;
+        }
+        if let Some(strategy) = transpile.static_cache {
+            options.static_cache_strategy = strategy;
+        }
+        if transpile.allowed_objects.is_some() {
+            options.allowed_objects = transpile.allowed_objects.clone();
+        }
+        if let Some(split) = transpile.split_trigger_events {
+            options.split_trigger_events = split;
+        }
+        if transpile.split_class_threshold.is_some() {
+            options.split_class_threshold = transpile.split_class_threshold;
+        }
+        if let Some(test_context) = transpile.test_context {
+            options.test_context = test_context;
+        }
+        options.env_flags = transpile
+            .env_flags
+            .iter()
+            .map(|(name, value)| (name.clone(), *value))
+            .collect();
+        options
+    }
+
+    /// Files under `root` matching `[files] include` (by default every
+    /// `.cls` and `.trigger`) and no `exclude` pattern, sorted
+    pub fn select_files(&self, root: &Path) -> Result<Vec<PathBuf>, ConfigError> {
+        let include = match self.files.include {
+            Some(ref patterns) => compile_patterns(patterns)?,
+            None => compile_patterns(DEFAULT_INCLUDE)?,
+        };
+        let exclude = compile_patterns(self.files.exclude.as_deref().unwrap_or_default())?;
+
+        let mut files = Vec::new();
+        collect_files(root, root, &include, &exclude, &mut files)?;
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// A project source file and its parse result
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectFile {
+    pub path: PathBuf,
+    /// Declarations that parsed; empty after an error unless recovering
+    pub unit: CompilationUnit,
+    /// Syntax errors, at most one unless `[parser] recovery` is set
+    pub errors: Vec<ParseError>,
+}
+
+/// Parse every file of the project at `root`
+///
+/// Files are selected by `config`, falling back to the `apexion.toml` found
+/// at `root` and then to every `.cls` and `.trigger` file. With `[parser]
+/// recovery = true` each file reports all of its syntax errors.
+///
+/// A `cancellation` token is checked before each file and handed to its
+/// parser; once it fires the parse stops with [`ConfigError::Cancelled`].
+pub fn parse_project(
+    root: &Path,
+    config: Option<&Config>,
+    cancellation: Option<&CancellationToken>,
+) -> Result<Vec<ProjectFile>, ConfigError> {
+    let discovered;
+    let config = match config {
+        Some(config) => config,
+        None => {
+            discovered = Config::discover(root)?
+                .map(|(config, _)| config)
+                .unwrap_or_default();
+            &discovered
+        }
+    };
+
+    let recovery = config.parser.recovery.unwrap_or(false);
+    config
+        .select_files(root)?
+        .into_iter()
+        .map(|path| {
+            if cancellation.is_some_and(CancellationToken::is_cancelled) {
+                return Err(ConfigError::Cancelled);
+            }
+            let source = std::fs::read_to_string(&path).map_err(|e| io_error(&path, e))?;
+            let mut parser = Parser::new(&source);
+            if let Some(token) = cancellation {
+                parser = parser.with_cancellation(token.clone());
+            }
+            let (unit, errors) = if recovery {
+                parser.parse_with_recovery()
+            } else {
+                match parser.parse() {
+                    Ok(unit) => (unit, Vec::new()),
+                    Err(error) => {
+                        let unit = CompilationUnit {
+                            declarations: Vec::new(),
+                            next_id: NodeId(0),
+                        };
+                        (unit, vec![error])
+                    }
+                }
+            };
+            if matches!(errors.last(), Some(ParseError::Cancelled(_))) {
+                return Err(ConfigError::Cancelled);
+            }
+            Ok(ProjectFile { path, unit, errors })
+        })
+        .collect()
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> ConfigError {
+    ConfigError::Io {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    }
+}
+
+fn unknown_keys(table: &toml::Table) -> Vec<ConfigWarning> {
+    let mut warnings = Vec::new();
+    for (section, value) in table {
+        if section == "lint" {
+            if let toml::Value::Table(rules) = value {
+                for rule in rules.keys() {
+                    if !LINT_RULES.contains(&rule.as_str()) {
+                        warnings.push(ConfigWarning::UnknownLintRule(rule.clone()));
+                    }
+                }
+            }
+            continue;
+        }
+        let Some((_, known)) = KNOWN_KEYS.iter().find(|(name, _)| name == section) else {
+            warnings.push(ConfigWarning::UnknownKey(section.clone()));
+            continue;
+        };
+        if let toml::Value::Table(keys) = value {
+            for key in keys.keys() {
+                if !known.contains(&key.as_str()) {
+                    warnings.push(ConfigWarning::UnknownKey(format!("{}.{}", section, key)));
+                }
+            }
+        }
+    }
+    warnings
+}
+
+fn compile_patterns<S: AsRef<str>>(patterns: &[S]) -> Result<Vec<glob::Pattern>, ConfigError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            glob::Pattern::new(pattern.as_ref()).map_err(|e| ConfigError::InvalidPattern {
+                pattern: pattern.as_ref().to_string(),
+                message: e.msg.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    include: &[glob::Pattern],
+    exclude: &[glob::Pattern],
+    files: &mut Vec<PathBuf>,
+) -> Result<(), ConfigError> {
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+    let entries = std::fs::read_dir(dir).map_err(|e| io_error(dir, e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| io_error(dir, e))?;
+        let path = entry.path();
+        // Symlinked directories are not followed, so a link back up the
+        // tree cannot loop
+        let file_type = entry.file_type().map_err(|e| io_error(&path, e))?;
+        if file_type.is_dir() {
+            collect_files(root, &path, include, exclude, files)?;
+            continue;
+        }
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let matches = |patterns: &[glob::Pattern]| {
+            patterns.iter().any(|p| p.matches_with(&relative, options))
+        };
+        if matches(include) && !matches(exclude) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
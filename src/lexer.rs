@@ -2,21 +2,104 @@ use logos::Logos;
 use std::fmt;
 
 /// Span represents a range in the source code
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// `line` and `column` locate `start`, both counting from 1. The column
+/// counts characters, so a tab is one column, and `\r\n` ends a line like
+/// `\n`. Spans not made by the lexer or a [`LineIndex`] leave them at 0.
+/// They follow from `start`, so comparisons ignore them, and serialization
+/// leaves them out: a span is written as `{"start": 10, "end": 14}`.
+#[derive(Debug, Clone, Copy, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span {
     pub start: usize,
     pub end: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub line: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub column: usize,
+}
+
+impl PartialEq for Span {
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start && self.end == other.end
+    }
 }
 
 impl Span {
-    pub fn new(start: usize, end: usize) -> Self {
-        Self { start, end }
+    pub const fn new(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end,
+            line: 0,
+            column: 0,
+        }
     }
 
     pub fn merge(self, other: Span) -> Span {
+        let first = if other.start < self.start {
+            other
+        } else {
+            self
+        };
         Span {
-            start: self.start.min(other.start),
             end: self.end.max(other.end),
+            ..first
+        }
+    }
+}
+
+/// `3:9` (line:column), or the byte offset when the position is unknown
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "offset {}", self.start)
+        } else {
+            write!(f, "{}:{}", self.line, self.column)
+        }
+    }
+}
+
+/// Where each line of a source starts, for locating byte offsets
+///
+/// The lexer fills in token positions itself; this is for offsets kept
+/// without a position, or spans built by hand.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// Line and column of `span.start`, counted as in [`Span`]
+    pub fn position(&self, span: Span) -> (usize, usize) {
+        let offset = span.start.min(self.source.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.source[line_start..]
+            .char_indices()
+            .take_while(|(i, _)| line_start + i < offset)
+            .count();
+        (line, column + 1)
+    }
+
+    /// The span `start..end` with its line and column filled in
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        let (line, column) = self.position(Span::new(start, end));
+        Span {
+            start,
+            end,
+            line,
+            column,
         }
     }
 }
@@ -395,7 +478,7 @@ pub enum TokenKind {
     #[regex(r"[0-9]+\.[0-9]+([eE][+-]?[0-9]+)?", |lex| lex.slice().parse::<f64>().ok())]
     DoubleLiteral(f64),
 
-    #[regex(r"'([^'\\]|\\.)*'", parse_string)]
+    #[token("'", lex_string)]
     StringLiteral(String),
 
     // Identifiers
@@ -406,15 +489,41 @@ pub enum TokenKind {
     #[regex(r"@[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice()[1..].to_string())]
     Annotation(String),
 
+    // Comments - skipped by the lexer, only produced by `tokenize_with_trivia`
+    /// `// text`
+    LineComment(String),
+    /// `/* text */`
+    BlockComment(String),
+    /// `/** text */` (ApexDoc)
+    DocComment(String),
+
     // End of file
     Eof,
 }
 
-fn parse_string(lex: &mut logos::Lexer<TokenKind>) -> Option<String> {
-    let slice = lex.slice();
-    // Remove surrounding quotes and unescape
-    let inner = &slice[1..slice.len() - 1];
-    let mut result = String::new();
+/// Scan a string literal after its opening quote
+///
+/// Done by hand because the equivalent regex (`'([^'\\]|\\.)*'`) makes the
+/// generated lexer recurse per character, overflowing the stack of test
+/// threads on literals of a few hundred KB in debug builds.
+fn lex_string(lex: &mut logos::Lexer<TokenKind>) -> Option<String> {
+    let rest = lex.remainder();
+    let bytes = rest.as_bytes();
+    let mut len = 0;
+    loop {
+        match bytes.get(len)? {
+            b'\'' => break,
+            b'\\' if matches!(bytes.get(len + 1), None | Some(b'\n')) => return None,
+            b'\\' => len += 2,
+            _ => len += 1,
+        }
+    }
+    lex.bump(len + 1);
+    Some(unescape(&rest[..len]))
+}
+
+fn unescape(inner: &str) -> String {
+    let mut result = String::with_capacity(inner.len());
     let mut chars = inner.chars().peekable();
     while let Some(c) = chars.next() {
         if c == '\\' {
@@ -435,7 +544,39 @@ fn parse_string(lex: &mut logos::Lexer<TokenKind>) -> Option<String> {
             result.push(c);
         }
     }
-    Some(result)
+    result
+}
+
+/// The body of a string literal used as a SOQL LIKE pattern: escapes are
+/// resolved as in a plain string except `\\`, `\%` and `\_`, which stay
+/// escaped so a literal backslash can't be mistaken for one escaping a
+/// wildcard; the pattern reads the same in SQL under `ESCAPE '\'`
+pub(crate) fn unescape_like_pattern(inner: &str) -> String {
+    let mut result = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(c @ ('\\' | '%' | '_')) => {
+                result.push('\\');
+                result.push(c);
+            }
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some(c @ ('\'' | '"')) => result.push(c),
+            // An unknown escape keeps its backslash, escaped in turn
+            Some(c) => {
+                result.push_str("\\\\");
+                result.push(c);
+            }
+            None => result.push_str("\\\\"),
+        }
+    }
+    result
 }
 
 fn parse_long(lex: &mut logos::Lexer<TokenKind>) -> Option<i64> {
@@ -525,11 +666,176 @@ impl fmt::Display for TokenKind {
     }
 }
 
+/// How the parser treats a token where a name may appear
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenClass {
+    /// A keyword that never names anything: `if`, `public`, `this`
+    ReservedAlways,
+    /// A keyword that also names variables, fields, methods and SOQL
+    /// fields: `Id`, `order`, `insert`, `when`
+    ContextualKeyword,
+    OperatorOrPunct,
+    /// Identifiers, literals, annotations, comments and end of input
+    Other,
+}
+
+/// Declares the keyword table: [`KEYWORDS`], [`TokenKind::class`] and
+/// [`TokenKind::keyword_text`]. The class match has no wildcard, so a new
+/// token does not compile until it is classified here.
+macro_rules! token_classes {
+    (
+        keywords { $($keyword:ident => $text:literal, $class:ident;)* }
+        punctuation { $($punct:ident),* $(,)? }
+        other { $($other:pat),* $(,)? }
+    ) => {
+        /// Every keyword token with the text it has when used as a name
+        pub const KEYWORDS: &[(TokenKind, &str, TokenClass)] =
+            &[$((TokenKind::$keyword, $text, TokenClass::$class)),*];
+
+        impl TokenKind {
+            pub fn class(&self) -> TokenClass {
+                match self {
+                    $(TokenKind::$keyword => TokenClass::$class,)*
+                    $(TokenKind::$punct)|* => TokenClass::OperatorOrPunct,
+                    $($other)|* => TokenClass::Other,
+                }
+            }
+
+            /// Text of a keyword used as a name, in its usual casing
+            pub fn keyword_text(&self) -> Option<&'static str> {
+                match self {
+                    $(TokenKind::$keyword => Some($text),)*
+                    _ => None,
+                }
+            }
+
+            /// Whether the token can name a variable, field or method
+            pub fn is_name(&self) -> bool {
+                matches!(self, TokenKind::Identifier(_))
+                    || self.class() == TokenClass::ContextualKeyword
+            }
+        }
+    };
+}
+
+token_classes! {
+    keywords {
+        Public => "public", ReservedAlways;
+        Private => "private", ReservedAlways;
+        Protected => "protected", ReservedAlways;
+        Global => "global", ReservedAlways;
+        Abstract => "abstract", ReservedAlways;
+        Virtual => "virtual", ReservedAlways;
+        Override => "override", ReservedAlways;
+        Static => "static", ReservedAlways;
+        Final => "final", ReservedAlways;
+        Transient => "transient", ReservedAlways;
+        WithSharing => "with sharing", ReservedAlways;
+        WithoutSharing => "without sharing", ReservedAlways;
+        InheritedSharing => "inherited sharing", ReservedAlways;
+        Class => "class", ReservedAlways;
+        Interface => "interface", ReservedAlways;
+        Enum => "enum", ReservedAlways;
+        Trigger => "Trigger", ContextualKeyword;
+        If => "if", ReservedAlways;
+        Else => "else", ReservedAlways;
+        For => "for", ReservedAlways;
+        While => "while", ReservedAlways;
+        Do => "do", ReservedAlways;
+        Switch => "switch", ReservedAlways;
+        When => "when", ContextualKeyword;
+        Break => "break", ReservedAlways;
+        Continue => "continue", ReservedAlways;
+        Return => "return", ReservedAlways;
+        Throw => "throw", ReservedAlways;
+        Try => "try", ReservedAlways;
+        Catch => "catch", ReservedAlways;
+        Finally => "finally", ReservedAlways;
+        Extends => "extends", ReservedAlways;
+        Implements => "implements", ReservedAlways;
+        This => "this", ReservedAlways;
+        Super => "super", ReservedAlways;
+        New => "new", ReservedAlways;
+        Instanceof => "instanceof", ReservedAlways;
+        Void => "void", ReservedAlways;
+        Boolean => "Boolean", ContextualKeyword;
+        Integer => "Integer", ContextualKeyword;
+        Long => "Long", ContextualKeyword;
+        Double => "Double", ContextualKeyword;
+        Decimal => "Decimal", ContextualKeyword;
+        StringType => "String", ContextualKeyword;
+        Blob => "Blob", ContextualKeyword;
+        Date => "Date", ContextualKeyword;
+        Datetime => "Datetime", ContextualKeyword;
+        Time => "Time", ContextualKeyword;
+        Id => "Id", ContextualKeyword;
+        Object => "Object", ContextualKeyword;
+        List => "List", ContextualKeyword;
+        Map => "Map", ContextualKeyword;
+        Select => "select", ContextualKeyword;
+        From => "from", ContextualKeyword;
+        Where => "where", ContextualKeyword;
+        Find => "find", ContextualKeyword;
+        Returning => "returning", ContextualKeyword;
+        Order => "order", ContextualKeyword;
+        By => "by", ContextualKeyword;
+        Limit => "limit", ContextualKeyword;
+        Offset => "offset", ContextualKeyword;
+        Asc => "asc", ContextualKeyword;
+        Desc => "desc", ContextualKeyword;
+        Nulls => "nulls", ContextualKeyword;
+        First => "first", ContextualKeyword;
+        Last => "last", ContextualKeyword;
+        Group => "group", ContextualKeyword;
+        Having => "having", ContextualKeyword;
+        And => "and", ContextualKeyword;
+        Or => "or", ContextualKeyword;
+        Not => "not", ContextualKeyword;
+        In => "in", ContextualKeyword;
+        Like => "like", ContextualKeyword;
+        Includes => "includes", ContextualKeyword;
+        Excludes => "excludes", ContextualKeyword;
+        Insert => "insert", ContextualKeyword;
+        Update => "update", ContextualKeyword;
+        Upsert => "upsert", ContextualKeyword;
+        Delete => "delete", ContextualKeyword;
+        Undelete => "undelete", ContextualKeyword;
+        Merge => "merge", ContextualKeyword;
+        Before => "before", ContextualKeyword;
+        After => "after", ContextualKeyword;
+        On => "on", ContextualKeyword;
+        Null => "null", ReservedAlways;
+        True => "true", ReservedAlways;
+        False => "false", ReservedAlways;
+        Get => "get", ContextualKeyword;
+        Set => "Set", ContextualKeyword;
+        TestMethod => "testmethod", ReservedAlways;
+        WebService => "webservice", ReservedAlways;
+    }
+    punctuation {
+        Plus, Minus, Star, Slash, Percent, EqEq, NotEq, LtGt, EqEqEq, NotEqEq, Lt, Gt, LtEq,
+        GtEq, AndAnd, OrOr, Bang, Amp, Pipe, Caret, Tilde, LtLt, GtGt, GtGtGt, Eq, PlusEq,
+        MinusEq, StarEq, SlashEq, AmpEq, PipeEq, CaretEq, PercentEq, LtLtEq, GtGtEq, GtGtGtEq,
+        PlusPlus, MinusMinus, Question, QuestionDot, QuestionQuestion, Arrow, LParen, RParen,
+        LBrace, RBrace, LBracket, RBracket, Semicolon, Comma, Dot, Colon, At
+    }
+    other {
+        TokenKind::HexLiteral(_), TokenKind::BinaryLiteral(_), TokenKind::OctalLiteral(_),
+        TokenKind::IntegerLiteral(_), TokenKind::LongLiteral(_), TokenKind::HexLongLiteral(_),
+        TokenKind::DoubleLiteral(_), TokenKind::StringLiteral(_), TokenKind::Identifier(_),
+        TokenKind::Annotation(_), TokenKind::LineComment(_), TokenKind::BlockComment(_),
+        TokenKind::DocComment(_), TokenKind::Eof,
+    }
+}
+
 /// Lexer for Apex source code
 pub struct Lexer<'a> {
     inner: logos::Lexer<'a, TokenKind>,
     peeked: Option<Token>,
     peeked2: Option<Token>,
+    /// Offset, line and column of the last token start; tokens come in
+    /// source order, so each position is counted on from the previous one
+    cursor: (usize, usize, usize),
 }
 
 impl<'a> Lexer<'a> {
@@ -538,6 +844,7 @@ impl<'a> Lexer<'a> {
             inner: TokenKind::lexer(source),
             peeked: None,
             peeked2: None,
+            cursor: (0, 1, 1),
         }
     }
 
@@ -545,13 +852,35 @@ impl<'a> Lexer<'a> {
         match self.inner.next() {
             Some(Ok(kind)) => {
                 let span = self.inner.span();
-                Token::new(kind, Span::new(span.start, span.end))
+                Token::new(kind, self.span(span.start, span.end))
             }
             Some(Err(_)) => {
                 // Skip invalid token and try next
                 self.read_next()
             }
-            None => Token::new(TokenKind::Eof, Span::new(0, 0)),
+            None => {
+                let end = self.source().len();
+                Token::new(TokenKind::Eof, self.span(end, end))
+            }
+        }
+    }
+
+    fn span(&mut self, start: usize, end: usize) -> Span {
+        let (offset, mut line, mut column) = self.cursor;
+        for ch in self.inner.source()[offset..start].chars() {
+            if ch == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        self.cursor = (start, line, column);
+        Span {
+            start,
+            end,
+            line,
+            column,
         }
     }
 
@@ -586,23 +915,85 @@ impl<'a> Lexer<'a> {
     pub fn source(&self) -> &'a str {
         self.inner.source()
     }
+
+    /// Stream the remaining tokens, ending with a single `Eof`
+    pub fn tokens(self) -> Tokens<'a> {
+        Tokens {
+            lexer: self,
+            done: false,
+        }
+    }
+}
+
+/// Iterator over a [`Lexer`]'s tokens; see [`Lexer::tokens`]
+pub struct Tokens<'a> {
+    lexer: Lexer<'a>,
+    done: bool,
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        if self.done {
+            return None;
+        }
+        let token = self.lexer.next_token();
+        self.done = token.kind == TokenKind::Eof;
+        Some(token)
+    }
 }
 
+impl std::iter::FusedIterator for Tokens<'_> {}
+
 /// Tokenize an entire source string into a vector of tokens
 pub fn tokenize(source: &str) -> Vec<Token> {
-    let mut lexer = Lexer::new(source);
+    Lexer::new(source).tokens().collect()
+}
+
+/// Tokenize like [`tokenize`], keeping comments as `LineComment`,
+/// `BlockComment` and `DocComment` tokens in source order
+pub fn tokenize_with_trivia(source: &str) -> Vec<Token> {
+    let index = LineIndex::new(source);
     let mut tokens = Vec::new();
-    loop {
-        let token = lexer.next_token();
-        let is_eof = token.kind == TokenKind::Eof;
+    let mut last_end = 0;
+    for token in tokenize(source) {
+        scan_comments(&index, last_end, token.span.start, &mut tokens);
+        last_end = token.span.end.max(last_end);
         tokens.push(token);
-        if is_eof {
-            break;
-        }
     }
     tokens
 }
 
+/// Collect the comments in `source[start..end]`, a stretch the lexer skipped
+fn scan_comments(index: &LineIndex, start: usize, end: usize, tokens: &mut Vec<Token>) {
+    let source = index.source;
+    let mut i = start;
+    while i < end {
+        let rest = &source[i..end];
+        if let Some(text) = rest.strip_prefix("//") {
+            let len = text.find('\n').unwrap_or(text.len());
+            tokens.push(Token::new(
+                TokenKind::LineComment(text[..len].to_string()),
+                index.span(i, i + 2 + len),
+            ));
+            i += 2 + len;
+        } else if let Some(text) = rest.strip_prefix("/*") {
+            let len = text.find("*/").unwrap_or(text.len());
+            let comment_end = (i + 2 + len + 2).min(end);
+            let kind = match text[..len].strip_prefix('*') {
+                // `/**/` is an empty block comment, not an empty doc comment
+                Some(doc) if len > 0 => TokenKind::DocComment(doc.to_string()),
+                _ => TokenKind::BlockComment(text[..len].to_string()),
+            };
+            tokens.push(Token::new(kind, index.span(i, comment_end)));
+            i = comment_end;
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -636,6 +1027,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_numbers() {
         let tokens = tokenize("42 100L 3.14");
         assert!(matches!(tokens[0].kind, TokenKind::IntegerLiteral(42)));
@@ -672,6 +1064,26 @@ mod tests {
         assert_eq!(tokens[1].kind, TokenKind::Class);
     }
 
+    #[test]
+    fn test_trivia_distinguishes_doc_comments() {
+        let tokens = tokenize_with_trivia("/** doc */\n/* note */\npublic class A {} // end");
+        let kinds: Vec<&TokenKind> = tokens.iter().map(|t| &t.kind).collect();
+
+        assert_eq!(kinds[0], &TokenKind::DocComment(" doc ".to_string()));
+        assert_eq!(kinds[1], &TokenKind::BlockComment(" note ".to_string()));
+        assert_eq!(kinds[2], &TokenKind::Public);
+        assert_eq!(kinds[7], &TokenKind::LineComment(" end".to_string()));
+        assert_eq!(kinds[8], &TokenKind::Eof);
+        assert_eq!(tokens[0].span, Span::new(0, 10));
+    }
+
+    #[test]
+    fn test_trivia_empty_block_comment_is_not_doc() {
+        let tokens = tokenize_with_trivia("/**/ class");
+        assert_eq!(tokens[0].kind, TokenKind::BlockComment(String::new()));
+        assert_eq!(tokens[1].kind, TokenKind::Class);
+    }
+
     #[test]
     fn test_annotation() {
         let tokens = tokenize("@isTest public class");
@@ -1,12 +1,90 @@
+//! Apex parser, SOQL to SQL converter and Apex to TypeScript transpiler
+//!
+//! Cargo features select which parts are compiled:
+//!
+//! - `parser` (default): lexer, AST, parser, test inventory, markers report,
+//!   dynamic SOQL and loop query lints, SOQL context analysis and SOQL
+//!   query builder
+//! - `sql` (default): SOQL to SQL conversion and DDL generation
+//! - `standard-objects` (default): built-in Sales Cloud schema
+//! - `transpile` (default): Apex to TypeScript transpiler
+//! - `wasm`: WebAssembly bindings (enables everything above)
+//! - `config`: `apexion.toml` project configuration (enables `sql` and
+//!   `transpile`)
+//! - `serde`: `Serialize`/`Deserialize` for the AST and the DML report
+//!
+//! Build only the parser with `--no-default-features --features parser`.
+
+#[cfg(feature = "parser")]
+pub mod analysis;
+#[cfg(feature = "parser")]
 pub mod ast;
+pub mod cancel;
+pub mod clock;
+#[cfg(feature = "parser")]
+pub mod codeactions;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "parser")]
 pub mod lexer;
+#[cfg(feature = "parser")]
 pub mod parser;
+#[cfg(feature = "parser")]
+pub mod soql_builder;
+#[cfg(feature = "sql")]
 pub mod sql;
+#[cfg(feature = "transpile")]
 pub mod transpile;
 
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
+#[cfg(feature = "parser")]
 pub use ast::*;
-pub use lexer::{tokenize, Lexer, Span, Token, TokenKind};
-pub use parser::{parse, ParseError, ParseResult, Parser};
+pub use cancel::CancellationToken;
+pub use clock::{Clock, ClockSource, Timestamp};
+#[cfg(feature = "parser")]
+pub use lexer::{tokenize, tokenize_with_trivia, Lexer, LineIndex, Span, Token, TokenKind, Tokens};
+#[cfg(feature = "parser")]
+pub use parser::{
+    parse, parse_soql, parse_sosl, parse_with_recovery, FoundToken, ParseError, ParseResult, Parser,
+};
+
+#[cfg(test)]
+mod feature_tests {
+    #[cfg(feature = "parser")]
+    const SOURCE: &str =
+        "public class Demo { public List<Account> load() { return [SELECT Id FROM Account]; } }";
+
+    #[cfg(feature = "parser")]
+    #[test]
+    fn test_parser_feature() {
+        let cu = crate::parse(SOURCE).unwrap();
+        assert_eq!(cu.declarations.len(), 1);
+    }
+
+    #[cfg(feature = "sql")]
+    #[test]
+    fn test_sql_feature() {
+        use crate::sql::{DdlGenerator, SObjectDescribe, SqlDialect};
+
+        let ddl =
+            DdlGenerator::new(SqlDialect::Sqlite).generate_table(&SObjectDescribe::new("Account"));
+        assert!(ddl.contains("CREATE TABLE"));
+    }
+
+    #[cfg(feature = "standard-objects")]
+    #[test]
+    fn test_standard_objects_feature() {
+        let schema = crate::sql::create_sales_cloud_schema();
+        assert!(schema.has_object("Account"));
+    }
+
+    #[cfg(feature = "transpile")]
+    #[test]
+    fn test_transpile_feature() {
+        let cu = crate::parse(SOURCE).unwrap();
+        let ts = crate::transpile::transpile(&cu).unwrap();
+        assert!(ts.contains("class Demo"));
+    }
+}
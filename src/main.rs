@@ -18,6 +18,14 @@ public class AccountService {
         return [SELECT Id, Name, Industry FROM Account WHERE IsActive__c = true LIMIT 100];
     }
 
+    // Method with DML
+    public void archive() {
+        if (!Schema.sObjectType.Account.isUpdateable()) {
+            throw new NoAccessException();
+        }
+        update accounts;
+    }
+
     // Method with control flow
     public void processAccounts(List<Account> accs) {
         for (Account acc : accs) {
@@ -118,6 +126,12 @@ public interface IAccountProcessor {
                     }
                 }
             }
+
+            #[cfg(feature = "sql")]
+            {
+                println!("--- DML Report ---");
+                print!("{}", apexrust::analysis::dml_report::analyze(&cu, None));
+            }
         }
         Err(e) => {
             eprintln!("Parse error: {}", e);
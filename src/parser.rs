@@ -1,46 +1,304 @@
+use std::fmt;
+
 use crate::ast::*;
-use crate::lexer::{Lexer, Span, Token, TokenKind};
+use crate::cancel::CancellationToken;
+use crate::lexer::{unescape_like_pattern, Lexer, Span, Token, TokenKind};
 use thiserror::Error;
 
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum ParseError {
-    #[error("Unexpected token: expected {expected}, found {found} at {span:?}")]
+    #[error("Unexpected token: expected {expected}, found {found} at {span}")]
     UnexpectedToken {
         expected: String,
-        found: String,
+        found: FoundToken,
         span: Span,
     },
     #[error("Unexpected end of input")]
     UnexpectedEof,
-    #[error("Invalid expression at {0:?}")]
+    #[error("Invalid expression at {0}")]
     InvalidExpression(Span),
-    #[error("Invalid statement at {0:?}")]
+    #[error("Invalid statement at {0}")]
     InvalidStatement(Span),
-    #[error("Invalid type at {0:?}")]
+    #[error("Invalid type at {0}")]
     InvalidType(Span),
+    #[error("Unexpected {found} after the last declaration at {span}")]
+    TrailingTokens { found: FoundToken, span: Span },
+    #[error("Parsing cancelled at {0}")]
+    Cancelled(Span),
+}
+
+impl ParseError {
+    /// Where the error was found; its `line` and `column` give the position
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ParseError::UnexpectedToken { span, .. } | ParseError::TrailingTokens { span, .. } => {
+                Some(*span)
+            }
+            ParseError::InvalidExpression(span)
+            | ParseError::InvalidStatement(span)
+            | ParseError::InvalidType(span)
+            | ParseError::Cancelled(span) => Some(*span),
+            ParseError::UnexpectedEof => None,
+        }
+    }
 }
 
 pub type ParseResult<T> = Result<T, ParseError>;
 
+/// Longest token text (string literal, identifier) quoted in a parse error
+pub const MAX_FOUND_LEN: usize = 64;
+
+/// Statements parsed between cancellation checks
+const STATEMENTS_PER_CHECK: u32 = 256;
+
+/// What the parser ran into instead of the expected token
+///
+/// Text payloads are cut to [`MAX_FOUND_LEN`] characters when the error is
+/// built, so a huge string literal is never copied or formatted in full; the
+/// description itself is only rendered by `Display`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FoundToken {
+    Token(TokenKind),
+    Text(&'static str),
+}
+
+impl FoundToken {
+    fn new(kind: &TokenKind) -> Self {
+        let kind = match kind {
+            TokenKind::StringLiteral(s) => TokenKind::StringLiteral(truncate(s)),
+            TokenKind::Identifier(s) => TokenKind::Identifier(truncate(s)),
+            TokenKind::Annotation(s) => TokenKind::Annotation(truncate(s)),
+            other => other.clone(),
+        };
+        FoundToken::Token(kind)
+    }
+
+    /// The token the parser stopped at, if the error came from one
+    pub fn kind(&self) -> Option<&TokenKind> {
+        match self {
+            FoundToken::Token(kind) => Some(kind),
+            FoundToken::Text(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for FoundToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FoundToken::Token(kind) => write!(f, "{:?}", kind),
+            FoundToken::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_FOUND_LEN) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
     current: Token,
+    cancellation: Option<CancellationToken>,
+    /// Statements parsed since the last cancellation check
+    unchecked_statements: u32,
+    /// `{` tokens consumed minus `}` tokens consumed
+    depth: u32,
+    /// Errors skipped over, set only while
+    /// [`parse_with_recovery`](Self::parse_with_recovery) runs
+    recovered: Option<Vec<ParseError>>,
+    /// Id the next node gets
+    next_node: u32,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(source: &'a str) -> Self {
         let mut lexer = Lexer::new(source);
         let current = lexer.next_token();
-        Self { lexer, current }
+        Self {
+            lexer,
+            current,
+            cancellation: None,
+            unchecked_statements: 0,
+            depth: 0,
+            recovered: None,
+            next_node: 0,
+        }
+    }
+
+    /// Stop with [`ParseError::Cancelled`] once `token` fires
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Stop with [`ParseError::Cancelled`] once `timeout` has elapsed
+    pub fn with_timeout(self, timeout: std::time::Duration) -> Self {
+        self.with_cancellation(CancellationToken::with_timeout(timeout))
     }
 
     /// Parse a complete compilation unit
     pub fn parse(&mut self) -> ParseResult<CompilationUnit> {
         let mut declarations = Vec::new();
         while !self.is_at_end() {
+            // Stray tokens after a complete declaration are reported where
+            // they start, not wherever declaration parsing gives up on them
+            if !declarations.is_empty() && !self.at_type_declaration() {
+                return Err(ParseError::TrailingTokens {
+                    found: FoundToken::new(&self.current.kind),
+                    span: self.current.span,
+                });
+            }
+            self.check_cancelled()?;
             declarations.push(self.parse_type_declaration()?);
         }
-        Ok(CompilationUnit { declarations })
+        Ok(CompilationUnit {
+            declarations,
+            next_id: NodeId(self.next_node),
+        })
+    }
+
+    /// Parse a SOQL query on its own, as stored outside Apex, without the
+    /// `[` `]` around it
+    ///
+    /// The query must be the whole input; it parses to the same AST as when
+    /// embedded, with spans relative to the query text.
+    pub fn parse_soql(&mut self) -> ParseResult<SoqlQuery> {
+        let query = self.parse_soql_query()?;
+        self.expect_end_of_query()?;
+        Ok(query)
+    }
+
+    /// Parse a SOSL search on its own; see [`parse_soql`](Self::parse_soql)
+    pub fn parse_sosl(&mut self) -> ParseResult<SoslQuery> {
+        let query = self.parse_sosl_query()?;
+        self.expect_end_of_query()?;
+        Ok(query)
+    }
+
+    fn expect_end_of_query(&self) -> ParseResult<()> {
+        if self.is_at_end() {
+            return Ok(());
+        }
+        Err(ParseError::UnexpectedToken {
+            expected: "end of query".to_string(),
+            found: FoundToken::new(&self.current.kind),
+            span: self.current.span,
+        })
+    }
+
+    /// Parse a compilation unit, reporting every syntax error instead of
+    /// stopping at the first
+    ///
+    /// After an error the parser resumes at the next statement of the same
+    /// block, the next member of the same class or the next top-level
+    /// declaration, whichever encloses the error most closely. The construct
+    /// that failed is left out of the returned unit; everything else parses
+    /// as it would with [`parse`](Self::parse). Cancellation still ends the
+    /// parse, with [`ParseError::Cancelled`] as the last error.
+    pub fn parse_with_recovery(&mut self) -> (CompilationUnit, Vec<ParseError>) {
+        self.recovered = Some(Vec::new());
+        let mut declarations = Vec::new();
+        let mut first = true;
+        while !self.is_at_end() {
+            let result = if !first && !self.at_type_declaration() {
+                Err(ParseError::TrailingTokens {
+                    found: FoundToken::new(&self.current.kind),
+                    span: self.current.span,
+                })
+            } else {
+                self.check_cancelled()
+                    .and_then(|_| self.parse_type_declaration())
+            };
+            first = false;
+            match result {
+                Ok(declaration) => declarations.push(declaration),
+                Err(error) => {
+                    let cancelled = matches!(error, ParseError::Cancelled(_));
+                    self.recovered.get_or_insert_with(Vec::new).push(error);
+                    if cancelled {
+                        break;
+                    }
+                    self.skip_to_type_declaration();
+                }
+            }
+        }
+        let errors = self.recovered.take().unwrap_or_default();
+        (
+            CompilationUnit {
+                declarations,
+                next_id: NodeId(self.next_node),
+            },
+            errors,
+        )
+    }
+
+    /// Record `error` and skip past the statement or member it is in when
+    /// recovering, otherwise return it
+    fn recover(&mut self, error: ParseError, depth: u32) -> ParseResult<()> {
+        match self.recovered {
+            Some(ref mut errors) if !matches!(error, ParseError::Cancelled(_)) => {
+                errors.push(error);
+                self.synchronize(depth);
+                Ok(())
+            }
+            _ => Err(error),
+        }
+    }
+
+    /// Skip past the next `;` or `}` at brace depth `depth`, or up to the
+    /// `}` closing the block the failed construct is in
+    fn synchronize(&mut self, depth: u32) {
+        loop {
+            match self.current.kind {
+                TokenKind::Eof => return,
+                TokenKind::RBrace if self.depth == depth => return,
+                TokenKind::Semicolon if self.depth == depth => {
+                    self.advance();
+                    return;
+                }
+                TokenKind::RBrace => {
+                    self.advance();
+                    if self.depth == depth {
+                        return;
+                    }
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    /// Skip at least one token, then up to the next top-level declaration
+    fn skip_to_type_declaration(&mut self) {
+        self.advance();
+        while !self.is_at_end() && (self.depth > 0 || !self.at_type_declaration()) {
+            self.advance();
+        }
+    }
+
+    /// Whether the current token can begin a top-level declaration
+    fn at_type_declaration(&self) -> bool {
+        matches!(
+            self.current.kind,
+            TokenKind::Annotation(_)
+                | TokenKind::Trigger
+                | TokenKind::Class
+                | TokenKind::Interface
+                | TokenKind::Enum
+                | TokenKind::Public
+                | TokenKind::Private
+                | TokenKind::Protected
+                | TokenKind::Global
+                | TokenKind::Abstract
+                | TokenKind::Virtual
+                | TokenKind::WithSharing
+                | TokenKind::WithoutSharing
+                | TokenKind::InheritedSharing
+        )
     }
 
     // ==================== Helper Methods ====================
@@ -50,8 +308,12 @@ impl<'a> Parser<'a> {
     }
 
     fn advance(&mut self) -> Token {
-        let token = std::mem::replace(&mut self.current, self.lexer.next_token());
-        token
+        match self.current.kind {
+            TokenKind::LBrace => self.depth += 1,
+            TokenKind::RBrace => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+        std::mem::replace(&mut self.current, self.lexer.next_token())
     }
 
     fn check(&self, kind: &TokenKind) -> bool {
@@ -69,7 +331,7 @@ impl<'a> Parser<'a> {
         } else {
             Err(ParseError::UnexpectedToken {
                 expected: expected.to_string(),
-                found: format!("{:?}", self.current.kind),
+                found: FoundToken::new(&self.current.kind),
                 span: self.current.span,
             })
         }
@@ -88,6 +350,26 @@ impl<'a> Parser<'a> {
         self.current.span
     }
 
+    /// Stamp a node with the next id of the unit being parsed
+    fn next_id(&mut self) -> NodeId {
+        let id = NodeId(self.next_node);
+        self.next_node += 1;
+        id
+    }
+
+    fn check_cancelled(&self) -> ParseResult<()> {
+        match &self.cancellation {
+            Some(token) if token.is_cancelled() => Err(ParseError::Cancelled(self.current.span)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Source text of the current token
+    fn current_text(&self) -> String {
+        let span = self.current.span;
+        self.lexer.source()[span.start..span.end].to_string()
+    }
+
     // ==================== Type Declarations ====================
 
     fn parse_type_declaration(&mut self) -> ParseResult<TypeDeclaration> {
@@ -114,7 +396,7 @@ impl<'a> Parser<'a> {
                 .map(TypeDeclaration::Enum),
             _ => Err(ParseError::UnexpectedToken {
                 expected: "class, interface, or enum".to_string(),
-                found: format!("{:?}", self.current.kind),
+                found: FoundToken::new(&self.current.kind),
                 span: self.current.span,
             }),
         }
@@ -140,6 +422,7 @@ impl<'a> Parser<'a> {
                 name,
                 parameters,
                 span,
+                id: self.next_id(),
             });
         }
         Ok(annotations)
@@ -341,6 +624,7 @@ impl<'a> Parser<'a> {
             implements,
             members,
             span: start.merge(end),
+            id: self.next_id(),
         })
     }
 
@@ -374,6 +658,7 @@ impl<'a> Parser<'a> {
             extends,
             members,
             span: start.merge(end),
+            id: self.next_id(),
         })
     }
 
@@ -411,6 +696,7 @@ impl<'a> Parser<'a> {
             name,
             values,
             span: start.merge(end),
+            id: self.next_id(),
         })
     }
 
@@ -434,6 +720,7 @@ impl<'a> Parser<'a> {
             events,
             body,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         })
     }
 
@@ -448,7 +735,7 @@ impl<'a> Parser<'a> {
             } else {
                 return Err(ParseError::UnexpectedToken {
                     expected: "before or after".to_string(),
-                    found: format!("{:?}", self.current.kind),
+                    found: FoundToken::new(&self.current.kind),
                     span: self.current.span,
                 });
             };
@@ -483,7 +770,7 @@ impl<'a> Parser<'a> {
                     if is_before {
                         return Err(ParseError::UnexpectedToken {
                             expected: "after undelete (before undelete is not valid)".to_string(),
-                            found: "before undelete".to_string(),
+                            found: FoundToken::Text("before undelete"),
                             span: self.current.span,
                         });
                     }
@@ -492,7 +779,7 @@ impl<'a> Parser<'a> {
                 _ => {
                     return Err(ParseError::UnexpectedToken {
                         expected: "insert, update, delete, or undelete".to_string(),
-                        found: format!("{:?}", self.current.kind),
+                        found: FoundToken::new(&self.current.kind),
                         span: self.current.span,
                     })
                 }
@@ -520,6 +807,7 @@ impl<'a> Parser<'a> {
             params.push(TypeParameter {
                 name,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             });
 
             if !self.match_token(&TokenKind::Comma) {
@@ -570,8 +858,13 @@ impl<'a> Parser<'a> {
             Vec::new()
         };
 
-        let is_array = if self.match_token(&TokenKind::LBracket) {
-            self.consume(&TokenKind::RBracket, "]")?;
+        // `Type[]`; a `[` with anything else inside indexes an expression
+        // (`values[i] = v;`) that was only tentatively parsed as a type
+        let is_array = if self.check(&TokenKind::LBracket)
+            && matches!(self.lexer.peek().kind, TokenKind::RBracket)
+        {
+            self.advance();
+            self.advance();
             true
         } else {
             false
@@ -582,6 +875,7 @@ impl<'a> Parser<'a> {
             type_arguments,
             is_array,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         })
     }
 
@@ -597,6 +891,7 @@ impl<'a> Parser<'a> {
                 self.current.kind = TokenKind::Gt;
                 // Adjust the span to only cover the first >
                 self.current.span.start += 1;
+                self.current.span.column += 1;
                 Ok(())
             }
             TokenKind::GtGtGt => {
@@ -604,23 +899,26 @@ impl<'a> Parser<'a> {
                 self.current.kind = TokenKind::GtGt;
                 // Adjust the span to only cover the first >
                 self.current.span.start += 1;
+                self.current.span.column += 1;
                 Ok(())
             }
             TokenKind::GtEq => {
                 // Replace >= with =
                 self.current.kind = TokenKind::Eq;
                 self.current.span.start += 1;
+                self.current.span.column += 1;
                 Ok(())
             }
             TokenKind::GtGtEq => {
                 // Replace >>= with >=
                 self.current.kind = TokenKind::GtEq;
                 self.current.span.start += 1;
+                self.current.span.column += 1;
                 Ok(())
             }
             _ => Err(ParseError::UnexpectedToken {
                 expected: ">".to_string(),
-                found: format!("{:?}", self.current.kind),
+                found: FoundToken::new(&self.current.kind),
                 span: self.current.span,
             }),
         }
@@ -763,10 +1061,15 @@ impl<'a> Parser<'a> {
     // ==================== Class Members ====================
 
     fn parse_class_members(&mut self) -> ParseResult<Vec<ClassMember>> {
-        let mut members = Vec::new();
+        let mut members = Vec::with_capacity(8);
+        let depth = self.depth;
 
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
-            members.push(self.parse_class_member()?);
+            self.check_cancelled()?;
+            match self.parse_class_member() {
+                Ok(member) => members.push(member),
+                Err(error) => self.recover(error, depth)?,
+            }
         }
 
         Ok(members)
@@ -831,11 +1134,17 @@ impl<'a> Parser<'a> {
             _ => {}
         }
 
+        // Generic method: `<K, V>` before the return type
+        let type_parameters = self.parse_type_parameters()?;
+
         // Parse type (or constructor name)
         let type_ref = self.parse_type_ref()?;
 
         // Check if this is a constructor (type followed directly by '(')
-        if self.check(&TokenKind::LParen) && type_ref.type_arguments.is_empty() {
+        if self.check(&TokenKind::LParen)
+            && type_ref.type_arguments.is_empty()
+            && type_parameters.is_empty()
+        {
             // This is a constructor - the "type" we parsed is actually the constructor name
             return self.parse_constructor_rest(
                 annotations,
@@ -848,11 +1157,19 @@ impl<'a> Parser<'a> {
         // Get name (for methods, fields, properties)
         let name = self.parse_identifier()?;
 
+        if !type_parameters.is_empty() && !self.check(&TokenKind::LParen) {
+            return Err(ParseError::UnexpectedToken {
+                expected: "(".to_string(),
+                found: FoundToken::new(&self.current.kind),
+                span: self.current.span,
+            });
+        }
+
         // Determine if this is a method, field, or property
         match &self.current.kind {
             TokenKind::LParen => {
                 // Method
-                self.parse_method_rest(annotations, modifiers, type_ref, name)
+                self.parse_method_rest(annotations, modifiers, type_parameters, type_ref, name)
                     .map(ClassMember::Method)
             }
             TokenKind::LBrace => {
@@ -867,7 +1184,7 @@ impl<'a> Parser<'a> {
             }
             _ => Err(ParseError::UnexpectedToken {
                 expected: "(, {, =, or ;".to_string(),
-                found: format!("{:?}", self.current.kind),
+                found: FoundToken::new(&self.current.kind),
                 span: self.current.span,
             }),
         }
@@ -911,14 +1228,15 @@ impl<'a> Parser<'a> {
                     kind,
                     arguments,
                     span: chain_start.merge(self.current_span()),
+                    id: self.next_id(),
                 })
             } else {
                 // Not constructor chaining - it's this.field or similar
                 // Create the this/super expression and parse the rest as a statement
                 let base_expr = if is_this {
-                    Expression::This(chain_start)
+                    Expression::This(chain_start, self.next_id())
                 } else {
-                    Expression::Super(chain_start)
+                    Expression::Super(chain_start, self.next_id())
                 };
 
                 // Continue parsing this as an expression (field access, method call, etc.)
@@ -930,6 +1248,7 @@ impl<'a> Parser<'a> {
                 let stmt = Statement::Expression(ExpressionStatement {
                     expression: full_expr,
                     span: chain_start.merge(self.current_span()),
+                    id: self.next_id(),
                 });
 
                 let mut statements = vec![stmt];
@@ -943,6 +1262,7 @@ impl<'a> Parser<'a> {
                 let body = Block {
                     statements,
                     span: start.merge(block_end),
+                    id: self.next_id(),
                 };
 
                 return Ok(ClassMember::Constructor(ConstructorDeclaration {
@@ -953,6 +1273,7 @@ impl<'a> Parser<'a> {
                     chained_constructor: None,
                     body,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 }));
             }
         } else {
@@ -960,7 +1281,8 @@ impl<'a> Parser<'a> {
         };
 
         // Parse remaining statements
-        let mut statements = Vec::new();
+        // Skip the first few reallocations for typical block sizes
+        let mut statements = Vec::with_capacity(8);
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
             statements.push(self.parse_statement()?);
         }
@@ -971,6 +1293,7 @@ impl<'a> Parser<'a> {
         let body = Block {
             statements,
             span: start.merge(block_end),
+            id: self.next_id(),
         };
 
         Ok(ClassMember::Constructor(ConstructorDeclaration {
@@ -981,6 +1304,7 @@ impl<'a> Parser<'a> {
             chained_constructor,
             body,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -988,10 +1312,13 @@ impl<'a> Parser<'a> {
         &mut self,
         annotations: Vec<Annotation>,
         modifiers: MemberModifiers,
+        type_parameters: Vec<TypeParameter>,
         return_type: TypeRef,
         name: String,
     ) -> ParseResult<MethodDeclaration> {
-        let start = return_type.span;
+        let start = type_parameters
+            .first()
+            .map_or(return_type.span, |param| param.span);
         self.consume(&TokenKind::LParen, "(")?;
         let parameters = self.parse_parameters()?;
         self.consume(&TokenKind::RParen, ")")?;
@@ -1007,10 +1334,11 @@ impl<'a> Parser<'a> {
             modifiers,
             return_type,
             name,
-            type_parameters: Vec::new(),
+            type_parameters,
             parameters,
             body,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         })
     }
 
@@ -1028,7 +1356,15 @@ impl<'a> Parser<'a> {
         let mut setter = None;
 
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
-            let accessor_modifiers = self.parse_member_modifiers()?;
+            let access_written = matches!(
+                self.current.kind,
+                TokenKind::Public | TokenKind::Private | TokenKind::Protected | TokenKind::Global
+            );
+            let mut accessor_modifiers = self.parse_member_modifiers()?;
+            if !access_written {
+                // An accessor without its own access has the property's
+                accessor_modifiers.access = modifiers.access;
+            }
             let accessor_start = self.current_span();
 
             if self.match_token(&TokenKind::Get) {
@@ -1041,6 +1377,7 @@ impl<'a> Parser<'a> {
                     modifiers: accessor_modifiers,
                     body,
                     span: accessor_start.merge(self.current_span()),
+                    id: self.next_id(),
                 });
             } else if self.match_token(&TokenKind::Set) {
                 let body = if self.match_token(&TokenKind::Semicolon) {
@@ -1052,11 +1389,12 @@ impl<'a> Parser<'a> {
                     modifiers: accessor_modifiers,
                     body,
                     span: accessor_start.merge(self.current_span()),
+                    id: self.next_id(),
                 });
             } else {
                 return Err(ParseError::UnexpectedToken {
                     expected: "get or set".to_string(),
-                    found: format!("{:?}", self.current.kind),
+                    found: FoundToken::new(&self.current.kind),
                     span: self.current.span,
                 });
             }
@@ -1073,6 +1411,7 @@ impl<'a> Parser<'a> {
             getter,
             setter,
             span: start.merge(end),
+            id: self.next_id(),
         })
     }
 
@@ -1096,6 +1435,7 @@ impl<'a> Parser<'a> {
             name: first_name,
             initializer: first_init,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         });
 
         // Additional declarators
@@ -1111,6 +1451,7 @@ impl<'a> Parser<'a> {
                 name,
                 initializer,
                 span: decl_start.merge(self.current_span()),
+                id: self.next_id(),
             });
         }
 
@@ -1122,6 +1463,7 @@ impl<'a> Parser<'a> {
             type_ref,
             declarators,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         })
     }
 
@@ -1144,6 +1486,7 @@ impl<'a> Parser<'a> {
                 name,
                 parameters,
                 span: return_type.span.merge(self.current_span()),
+                id: self.next_id(),
             }));
         }
 
@@ -1170,6 +1513,7 @@ impl<'a> Parser<'a> {
                 type_ref,
                 name,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             });
 
             if !self.match_token(&TokenKind::Comma) {
@@ -1181,65 +1525,15 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_identifier(&mut self) -> ParseResult<String> {
-        // Many keywords in Apex can also be used as identifiers in certain contexts
-        // (field names, variable names, method names, etc.)
-        // Apex is permissive - most keywords can be used as identifiers except a few
+        // Apex is permissive: keywords the lexer's table classes as
+        // contextual also name variables, fields and methods
         let name = match &self.current.kind {
             TokenKind::Identifier(name) => name.clone(),
-            // Keywords that can be used as identifiers in Apex
-            TokenKind::Id => "Id".to_string(),
-            TokenKind::First => "first".to_string(),
-            TokenKind::Last => "last".to_string(),
-            TokenKind::Order => "order".to_string(),
-            TokenKind::Group => "group".to_string(),
-            TokenKind::Limit => "limit".to_string(),
-            TokenKind::Offset => "offset".to_string(),
-            TokenKind::Date => "Date".to_string(),
-            TokenKind::Time => "Time".to_string(),
-            TokenKind::Trigger => "Trigger".to_string(),
-            TokenKind::Object => "Object".to_string(),
-            TokenKind::Set => "Set".to_string(),
-            TokenKind::Map => "Map".to_string(),
-            TokenKind::List => "List".to_string(),
-            // Common keywords used as method names
-            TokenKind::Get => "get".to_string(),
-            TokenKind::Insert => "insert".to_string(),
-            TokenKind::Update => "update".to_string(),
-            TokenKind::Delete => "delete".to_string(),
-            TokenKind::Upsert => "upsert".to_string(),
-            TokenKind::Undelete => "undelete".to_string(),
-            TokenKind::Merge => "merge".to_string(),
-            // .class property access (Type.class)
-            TokenKind::Class => "class".to_string(),
-            // Other keywords that can be identifiers
-            TokenKind::Integer => "Integer".to_string(),
-            TokenKind::Long => "Long".to_string(),
-            TokenKind::Double => "Double".to_string(),
-            TokenKind::Decimal => "Decimal".to_string(),
-            TokenKind::StringType => "String".to_string(),
-            TokenKind::Boolean => "Boolean".to_string(),
-            TokenKind::Blob => "Blob".to_string(),
-            TokenKind::Datetime => "Datetime".to_string(),
-            TokenKind::After => "after".to_string(),
-            TokenKind::Before => "before".to_string(),
-            TokenKind::On => "on".to_string(),
-            TokenKind::By => "by".to_string(),
-            TokenKind::Having => "having".to_string(),
-            TokenKind::Select => "select".to_string(),
-            TokenKind::From => "from".to_string(),
-            TokenKind::Where => "where".to_string(),
-            TokenKind::And => "and".to_string(),
-            TokenKind::Or => "or".to_string(),
-            TokenKind::In => "in".to_string(),
-            TokenKind::Like => "like".to_string(),
-            TokenKind::New => "new".to_string(),
-            TokenKind::Not => "not".to_string(),
-            TokenKind::Null => "null".to_string(),
-            TokenKind::Void => "void".to_string(),
+            kind if kind.is_name() => kind.keyword_text().unwrap_or_default().to_string(),
             _ => {
                 return Err(ParseError::UnexpectedToken {
                     expected: "identifier".to_string(),
-                    found: format!("{:?}", self.current.kind),
+                    found: FoundToken::new(&self.current.kind),
                     span: self.current.span,
                 });
             }
@@ -1248,6 +1542,18 @@ impl<'a> Parser<'a> {
         Ok(name)
     }
 
+    /// Parse the name after `.` or `?.`, where reserved keywords name
+    /// members too: `Trigger.new`, `Account.class`
+    fn parse_member_name(&mut self) -> ParseResult<String> {
+        match self.current.kind.keyword_text() {
+            Some(text) => {
+                self.advance();
+                Ok(text.to_string())
+            }
+            None => self.parse_identifier(),
+        }
+    }
+
     // ==================== Statements ====================
 
     fn parse_block(&mut self) -> ParseResult<Block> {
@@ -1255,8 +1561,19 @@ impl<'a> Parser<'a> {
         self.consume(&TokenKind::LBrace, "{")?;
 
         let mut statements = Vec::new();
+        let depth = self.depth;
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
-            statements.push(self.parse_statement()?);
+            if self.cancellation.is_some() {
+                self.unchecked_statements += 1;
+                if self.unchecked_statements == STATEMENTS_PER_CHECK {
+                    self.unchecked_statements = 0;
+                    self.check_cancelled()?;
+                }
+            }
+            match self.parse_statement() {
+                Ok(statement) => statements.push(statement),
+                Err(error) => self.recover(error, depth)?,
+            }
         }
 
         let end = self.current_span();
@@ -1265,10 +1582,31 @@ impl<'a> Parser<'a> {
         Ok(Block {
             statements,
             span: start.merge(end),
+            id: self.next_id(),
         })
     }
 
     fn parse_statement(&mut self) -> ParseResult<Statement> {
+        let is_dml = matches!(
+            self.current.kind,
+            TokenKind::Insert
+                | TokenKind::Update
+                | TokenKind::Upsert
+                | TokenKind::Delete
+                | TokenKind::Undelete
+                | TokenKind::Merge
+        );
+        if is_dml && !self.next_continues_name() {
+            return self.parse_dml_statement();
+        }
+        // `name :` cannot start any other statement; a ternary's colon
+        // always follows an operand
+        if matches!(self.current.kind, TokenKind::Identifier(_))
+            && self.lexer.peek().kind == TokenKind::Colon
+        {
+            return self.parse_labeled_statement();
+        }
+
         match &self.current.kind {
             TokenKind::LBrace => self.parse_block().map(Statement::Block),
             TokenKind::If => self.parse_if_statement(),
@@ -1281,16 +1619,10 @@ impl<'a> Parser<'a> {
             TokenKind::Break => self.parse_break_statement(),
             TokenKind::Continue => self.parse_continue_statement(),
             TokenKind::Try => self.parse_try_statement(),
-            TokenKind::Insert
-            | TokenKind::Update
-            | TokenKind::Upsert
-            | TokenKind::Delete
-            | TokenKind::Undelete
-            | TokenKind::Merge => self.parse_dml_statement(),
             TokenKind::Semicolon => {
                 let span = self.current_span();
                 self.advance();
-                Ok(Statement::Empty(span))
+                Ok(Statement::Empty(span, self.next_id()))
             }
             TokenKind::Final => self.parse_local_variable_declaration(),
             _ => {
@@ -1301,6 +1633,25 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Whether the token after a DML keyword shows the keyword is a name
+    /// being used (`insert = true;`, `update.add(acc);`) rather than a
+    /// statement
+    fn next_continues_name(&mut self) -> bool {
+        matches!(
+            self.lexer.peek().kind,
+            TokenKind::Eq
+                | TokenKind::PlusEq
+                | TokenKind::MinusEq
+                | TokenKind::StarEq
+                | TokenKind::SlashEq
+                | TokenKind::Dot
+                | TokenKind::QuestionDot
+                | TokenKind::PlusPlus
+                | TokenKind::MinusMinus
+                | TokenKind::Semicolon
+        )
+    }
+
     fn parse_if_statement(&mut self) -> ParseResult<Statement> {
         let start = self.current_span();
         self.consume(&TokenKind::If, "if")?;
@@ -1320,6 +1671,7 @@ impl<'a> Parser<'a> {
             then_branch,
             else_branch,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -1335,6 +1687,18 @@ impl<'a> Parser<'a> {
         // Look for a type followed by identifier followed by colon
         if self.is_type_start() {
             let type_ref = self.parse_type_ref()?;
+
+            // `for (i = 0, j = n; ...)`: the "type" began an init expression
+            if !self.current.kind.is_name() {
+                let first = self.type_ref_to_expression(type_ref)?;
+                let mut exprs = vec![self.parse_expression_rest(first)?];
+                while self.match_token(&TokenKind::Comma) {
+                    exprs.push(self.parse_expression()?);
+                }
+                self.consume(&TokenKind::Semicolon, ";")?;
+                return self.parse_traditional_for_rest(start, Some(ForInit::Expressions(exprs)));
+            }
+
             let variable = self.parse_identifier()?;
 
             if self.match_token(&TokenKind::Colon) {
@@ -1349,6 +1713,7 @@ impl<'a> Parser<'a> {
                     iterable,
                     body,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 }));
             }
 
@@ -1363,6 +1728,7 @@ impl<'a> Parser<'a> {
                 name: variable,
                 initializer,
                 span: type_ref.span.merge(self.current_span()),
+                id: self.next_id(),
             }];
 
             while self.match_token(&TokenKind::Comma) {
@@ -1376,6 +1742,7 @@ impl<'a> Parser<'a> {
                     name,
                     initializer: init,
                     span: self.current_span(),
+                    id: self.next_id(),
                 });
             }
 
@@ -1386,6 +1753,7 @@ impl<'a> Parser<'a> {
                 type_ref,
                 declarators,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }));
 
             return self.parse_traditional_for_rest(start, init);
@@ -1430,6 +1798,7 @@ impl<'a> Parser<'a> {
             update,
             body,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -1445,6 +1814,7 @@ impl<'a> Parser<'a> {
             condition,
             body,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -1462,6 +1832,7 @@ impl<'a> Parser<'a> {
             body,
             condition,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -1481,6 +1852,7 @@ impl<'a> Parser<'a> {
                 values,
                 block,
                 span: when_start.merge(self.current_span()),
+                id: self.next_id(),
             });
         }
 
@@ -1491,6 +1863,7 @@ impl<'a> Parser<'a> {
             expression,
             when_clauses,
             span: start.merge(end),
+            id: self.next_id(),
         }))
     }
 
@@ -1518,7 +1891,8 @@ impl<'a> Parser<'a> {
             // This handles cases like: when SPRING { } where SPRING is an enum value
             if type_ref.type_arguments.is_empty() && !type_ref.is_array {
                 // Simple identifier, treat as expression
-                let expr = Expression::Identifier(type_ref.name.clone(), type_ref.span);
+                let expr =
+                    Expression::Identifier(type_ref.name.clone(), type_ref.span, type_ref.id);
                 let mut literals = vec![expr];
                 while self.match_token(&TokenKind::Comma) {
                     literals.push(self.parse_expression()?);
@@ -1528,7 +1902,7 @@ impl<'a> Parser<'a> {
                 // Complex type but no variable - this is an error
                 return Err(ParseError::UnexpectedToken {
                     expected: "identifier".to_string(),
-                    found: format!("{:?}", self.current.kind),
+                    found: FoundToken::new(&self.current.kind),
                     span: self.current.span,
                 });
             }
@@ -1560,6 +1934,7 @@ impl<'a> Parser<'a> {
         Ok(Statement::Return(ReturnStatement {
             value,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -1572,26 +1947,68 @@ impl<'a> Parser<'a> {
         Ok(Statement::Throw(ThrowStatement {
             exception,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
+    /// `label: for (...) { ... }`; only loops and blocks can be labeled
+    fn parse_labeled_statement(&mut self) -> ParseResult<Statement> {
+        let start = self.current_span();
+        let label = self.parse_identifier()?;
+        self.consume(&TokenKind::Colon, ":")?;
+
+        if !matches!(
+            self.current.kind,
+            TokenKind::For | TokenKind::While | TokenKind::Do | TokenKind::LBrace
+        ) {
+            return Err(ParseError::UnexpectedToken {
+                expected: "loop or block after label".to_string(),
+                found: FoundToken::new(&self.current.kind),
+                span: self.current.span,
+            });
+        }
+        let body = Box::new(self.parse_statement()?);
+
+        Ok(Statement::Labeled(LabeledStatement {
+            label,
+            body,
+            span: start.merge(self.current_span()),
+            id: self.next_id(),
+        }))
+    }
+
+    /// The label after `break` or `continue`, if any
+    fn parse_jump_label(&mut self) -> ParseResult<Option<String>> {
+        if matches!(self.current.kind, TokenKind::Identifier(_)) {
+            Ok(Some(self.parse_identifier()?))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn parse_break_statement(&mut self) -> ParseResult<Statement> {
         let start = self.current_span();
         self.consume(&TokenKind::Break, "break")?;
+        let label = self.parse_jump_label()?;
         self.consume(&TokenKind::Semicolon, ";")?;
 
         Ok(Statement::Break(BreakStatement {
+            label,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
     fn parse_continue_statement(&mut self) -> ParseResult<Statement> {
         let start = self.current_span();
         self.consume(&TokenKind::Continue, "continue")?;
+        let label = self.parse_jump_label()?;
         self.consume(&TokenKind::Semicolon, ";")?;
 
         Ok(Statement::Continue(ContinueStatement {
+            label,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -1605,15 +2022,21 @@ impl<'a> Parser<'a> {
             let catch_start = self.current_span();
             self.consume(&TokenKind::LParen, "(")?;
             let exception_type = self.parse_type_ref()?;
+            let mut alternative_types = Vec::new();
+            while self.match_token(&TokenKind::Pipe) {
+                alternative_types.push(self.parse_type_ref()?);
+            }
             let variable = self.parse_identifier()?;
             self.consume(&TokenKind::RParen, ")")?;
             let block = self.parse_block()?;
 
             catch_clauses.push(CatchClause {
                 exception_type,
+                alternative_types,
                 variable,
                 block,
                 span: catch_start.merge(self.current_span()),
+                id: self.next_id(),
             });
         }
 
@@ -1628,6 +2051,7 @@ impl<'a> Parser<'a> {
             catch_clauses,
             finally_block,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -1677,6 +2101,7 @@ impl<'a> Parser<'a> {
             expression,
             access_level,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -1698,6 +2123,7 @@ impl<'a> Parser<'a> {
                 name,
                 initializer,
                 span: decl_start.merge(self.current_span()),
+                id: self.next_id(),
             });
 
             if !self.match_token(&TokenKind::Comma) {
@@ -1712,6 +2138,7 @@ impl<'a> Parser<'a> {
             type_ref,
             declarators,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -1727,19 +2154,15 @@ impl<'a> Parser<'a> {
         let start = self.current_span();
 
         // Check if this looks like it could be a type
-        if self.is_definite_type_start() {
-            // Known type keywords are definitely types
-            let type_ref = self.parse_type_ref()?;
-            return self.parse_local_var_after_type(start, type_ref);
-        }
-
-        // For identifiers, we need to be more careful
-        if let TokenKind::Identifier(_) = &self.current.kind {
+        // Type keywords and identifiers start a declaration when a name
+        // follows; otherwise they name something in an expression
+        // (`Integer.valueOf(s);`, `Date = today;`)
+        if self.is_definite_type_start() || matches!(self.current.kind, TokenKind::Identifier(_)) {
             // Parse the potential type
             let type_ref = self.parse_type_ref()?;
 
-            // Now check what follows - if it's an identifier, this is a variable declaration
-            if let TokenKind::Identifier(_) = &self.current.kind {
+            // Now check what follows - if it's a name, this is a variable declaration
+            if self.current.kind.is_name() {
                 return self.parse_local_var_after_type(start, type_ref);
             }
 
@@ -1747,21 +2170,39 @@ impl<'a> Parser<'a> {
             // We need to convert the type_ref back to an expression and continue parsing
             let expr = self.type_ref_to_expression(type_ref)?;
             let full_expr = self.parse_expression_rest(expr)?;
-            self.consume(&TokenKind::Semicolon, ";")?;
-
-            return Ok(Statement::Expression(ExpressionStatement {
-                expression: full_expr,
-                span: start.merge(self.current_span()),
-            }));
+            return self.finish_expression_statement(start, full_expr);
         }
 
         // Parse as expression statement
         let expression = self.parse_expression()?;
-        self.consume(&TokenKind::Semicolon, ";")?;
+        self.finish_expression_statement(start, expression)
+    }
 
+    /// End a statement that began with an expression: either `;`, or the
+    /// block of a `System.runAs(user) { ... }`
+    fn finish_expression_statement(
+        &mut self,
+        start: Span,
+        expression: Expression,
+    ) -> ParseResult<Statement> {
+        if self.check(&TokenKind::LBrace) {
+            if let Some(user) = run_as_user(&expression) {
+                let user = user.clone();
+                let body = self.parse_block()?;
+                return Ok(Statement::RunAs(RunAsStatement {
+                    user,
+                    span: start.merge(body.span),
+                    body,
+                    id: self.next_id(),
+                }));
+            }
+        }
+
+        self.consume(&TokenKind::Semicolon, ";")?;
         Ok(Statement::Expression(ExpressionStatement {
             expression,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
@@ -1806,6 +2247,7 @@ impl<'a> Parser<'a> {
                 name,
                 initializer,
                 span: decl_start.merge(self.current_span()),
+                id: self.next_id(),
             });
 
             if !self.match_token(&TokenKind::Comma) {
@@ -1820,25 +2262,32 @@ impl<'a> Parser<'a> {
             type_ref,
             declarators,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))
     }
 
-    fn type_ref_to_expression(&self, type_ref: TypeRef) -> ParseResult<Expression> {
+    fn type_ref_to_expression(&mut self, type_ref: TypeRef) -> ParseResult<Expression> {
         // Convert a simple type reference back to an identifier expression
         // This is used when we incorrectly parsed an expression as a type
         if type_ref.type_arguments.is_empty() && !type_ref.is_array {
             // If the name contains dots (qualified name), decompose into field accesses
             let parts: Vec<&str> = type_ref.name.split('.').collect();
             if parts.len() == 1 {
-                Ok(Expression::Identifier(type_ref.name, type_ref.span))
+                Ok(Expression::Identifier(
+                    type_ref.name,
+                    type_ref.span,
+                    type_ref.id,
+                ))
             } else {
                 // Build a chain of field accesses: a.b.c becomes FieldAccess(FieldAccess(a, b), c)
-                let mut expr = Expression::Identifier(parts[0].to_string(), type_ref.span);
+                let mut expr =
+                    Expression::Identifier(parts[0].to_string(), type_ref.span, type_ref.id);
                 for part in &parts[1..] {
                     expr = Expression::FieldAccess(Box::new(FieldAccessExpr {
                         object: expr,
                         field: part.to_string(),
                         span: type_ref.span,
+                        id: self.next_id(),
                     }));
                 }
                 Ok(expr)
@@ -1860,7 +2309,7 @@ impl<'a> Parser<'a> {
             match &self.current.kind {
                 TokenKind::LParen => {
                     // Method call on the identifier
-                    if let Expression::Identifier(name, _) = expr {
+                    if let Expression::Identifier(name, _, _) = expr {
                         self.advance();
                         let arguments = self.parse_arguments()?;
                         self.consume(&TokenKind::RParen, ")")?;
@@ -1871,6 +2320,7 @@ impl<'a> Parser<'a> {
                             type_arguments: Vec::new(),
                             arguments,
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                     } else {
                         break;
@@ -1878,7 +2328,7 @@ impl<'a> Parser<'a> {
                 }
                 TokenKind::Dot => {
                     self.advance();
-                    let name = self.parse_identifier()?;
+                    let name = self.parse_member_name()?;
 
                     if self.check(&TokenKind::LParen) {
                         self.advance();
@@ -1891,19 +2341,21 @@ impl<'a> Parser<'a> {
                             type_arguments: Vec::new(),
                             arguments,
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                     } else {
                         expr = Expression::FieldAccess(Box::new(FieldAccessExpr {
                             object: expr,
                             field: name,
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                     }
                 }
                 TokenKind::QuestionDot => {
                     // Safe navigation: obj?.field or obj?.method()
                     self.advance();
-                    let name = self.parse_identifier()?;
+                    let name = self.parse_member_name()?;
 
                     if self.check(&TokenKind::LParen) {
                         self.advance();
@@ -1914,6 +2366,7 @@ impl<'a> Parser<'a> {
                             object: expr,
                             field: name.clone(),
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                         expr = Expression::MethodCall(Box::new(MethodCallExpr {
                             object: Some(safe_obj),
@@ -1921,12 +2374,14 @@ impl<'a> Parser<'a> {
                             type_arguments: Vec::new(),
                             arguments,
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                     } else {
                         expr = Expression::SafeNavigation(Box::new(SafeNavigationExpr {
                             object: expr,
                             field: name,
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                     }
                 }
@@ -1938,17 +2393,18 @@ impl<'a> Parser<'a> {
                         array: expr,
                         index,
                         span: start.merge(self.current_span()),
+                        id: self.next_id(),
                     }));
                 }
                 TokenKind::PlusPlus => {
                     self.advance();
-                    expr =
-                        Expression::PostIncrement(Box::new(expr), start.merge(self.current_span()));
+                    let span = start.merge(self.current_span());
+                    expr = Expression::PostIncrement(Box::new(expr), span, self.next_id());
                 }
                 TokenKind::MinusMinus => {
                     self.advance();
-                    expr =
-                        Expression::PostDecrement(Box::new(expr), start.merge(self.current_span()));
+                    let span = start.merge(self.current_span());
+                    expr = Expression::PostDecrement(Box::new(expr), span, self.next_id());
                 }
                 _ => break,
             }
@@ -1983,6 +2439,7 @@ impl<'a> Parser<'a> {
                         operator: assign_op,
                         value: right,
                         span: start.merge(self.current_span()),
+                        id: self.next_id(),
                     }));
                     continue;
                 }
@@ -2024,6 +2481,7 @@ impl<'a> Parser<'a> {
                         operator: binary_op,
                         right,
                         span: start.merge(self.current_span()),
+                        id: self.next_id(),
                     }));
                     continue;
                 }
@@ -2039,6 +2497,7 @@ impl<'a> Parser<'a> {
                         expression: left,
                         type_ref,
                         span: start.merge(self.current_span()),
+                        id: self.next_id(),
                     }));
                     continue;
                 }
@@ -2131,6 +2590,7 @@ impl<'a> Parser<'a> {
                 operator,
                 value,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             })))
         } else {
             Ok(expr)
@@ -2151,6 +2611,7 @@ impl<'a> Parser<'a> {
                 then_expr,
                 else_expr,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             })))
         } else {
             Ok(condition)
@@ -2167,6 +2628,7 @@ impl<'a> Parser<'a> {
                 left,
                 right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }));
         }
 
@@ -2184,6 +2646,7 @@ impl<'a> Parser<'a> {
                 operator: BinaryOp::Or,
                 right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }));
         }
 
@@ -2201,6 +2664,7 @@ impl<'a> Parser<'a> {
                 operator: BinaryOp::And,
                 right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }));
         }
 
@@ -2218,6 +2682,7 @@ impl<'a> Parser<'a> {
                 operator: BinaryOp::BitwiseOr,
                 right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }));
         }
 
@@ -2235,6 +2700,7 @@ impl<'a> Parser<'a> {
                 operator: BinaryOp::BitwiseXor,
                 right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }));
         }
 
@@ -2252,6 +2718,7 @@ impl<'a> Parser<'a> {
                 operator: BinaryOp::BitwiseAnd,
                 right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }));
         }
 
@@ -2279,6 +2746,7 @@ impl<'a> Parser<'a> {
                     operator,
                     right,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 }));
             } else {
                 break;
@@ -2309,6 +2777,7 @@ impl<'a> Parser<'a> {
                     operator,
                     right,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 }));
             } else if self.match_token(&TokenKind::Instanceof) {
                 let type_ref = self.parse_type_ref()?;
@@ -2316,6 +2785,7 @@ impl<'a> Parser<'a> {
                     expression: left,
                     type_ref,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 }));
             } else {
                 break;
@@ -2345,6 +2815,7 @@ impl<'a> Parser<'a> {
                     operator,
                     right,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 }));
             } else {
                 break;
@@ -2373,6 +2844,7 @@ impl<'a> Parser<'a> {
                     operator,
                     right,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 }));
             } else {
                 break;
@@ -2402,6 +2874,7 @@ impl<'a> Parser<'a> {
                     operator,
                     right,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 }));
             } else {
                 break;
@@ -2420,6 +2893,7 @@ impl<'a> Parser<'a> {
             return Ok(Expression::PreIncrement(
                 Box::new(operand),
                 start.merge(self.current_span()),
+                self.next_id(),
             ));
         }
         if self.match_token(&TokenKind::MinusMinus) {
@@ -2427,6 +2901,7 @@ impl<'a> Parser<'a> {
             return Ok(Expression::PreDecrement(
                 Box::new(operand),
                 start.merge(self.current_span()),
+                self.next_id(),
             ));
         }
 
@@ -2445,6 +2920,7 @@ impl<'a> Parser<'a> {
                 operator,
                 operand,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             })));
         }
 
@@ -2500,6 +2976,7 @@ impl<'a> Parser<'a> {
             return Ok(Some(Expression::Parenthesized(
                 Box::new(expr),
                 start.merge(self.current_span()),
+                self.next_id(),
             )));
         }
 
@@ -2516,6 +2993,7 @@ impl<'a> Parser<'a> {
             return Ok(Some(Expression::Parenthesized(
                 Box::new(full_expr),
                 start.merge(self.current_span()),
+                self.next_id(),
             )));
         }
 
@@ -2524,21 +3002,31 @@ impl<'a> Parser<'a> {
         // Now check what follows - if it's an expression-starting token, this is a cast
         // Otherwise, it was a parenthesized type reference (which shouldn't happen)
 
-        let is_expression_start = matches!(
-            &self.current.kind,
-            TokenKind::Identifier(_) | TokenKind::IntegerLiteral(_) | TokenKind::LongLiteral(_)
-            | TokenKind::DoubleLiteral(_) | TokenKind::StringLiteral(_) | TokenKind::True
-            | TokenKind::False | TokenKind::Null | TokenKind::This | TokenKind::Super
-            | TokenKind::New | TokenKind::LParen | TokenKind::Bang | TokenKind::Minus
-            | TokenKind::Plus | TokenKind::PlusPlus | TokenKind::MinusMinus | TokenKind::Tilde
-            | TokenKind::LBracket | TokenKind::HexLiteral(_) | TokenKind::BinaryLiteral(_)
-            | TokenKind::OctalLiteral(_)
-            // Keywords that can start expressions (used as identifiers in some contexts)
-            | TokenKind::Trigger | TokenKind::Map | TokenKind::List | TokenKind::Set
-            | TokenKind::Object | TokenKind::Id | TokenKind::Date | TokenKind::Datetime
-            | TokenKind::Time | TokenKind::Integer | TokenKind::Long | TokenKind::Double
-            | TokenKind::Decimal | TokenKind::StringType | TokenKind::Boolean | TokenKind::Blob
-        );
+        let is_expression_start = self.current.kind.is_name()
+            || matches!(
+                &self.current.kind,
+                TokenKind::IntegerLiteral(_)
+                    | TokenKind::LongLiteral(_)
+                    | TokenKind::DoubleLiteral(_)
+                    | TokenKind::StringLiteral(_)
+                    | TokenKind::True
+                    | TokenKind::False
+                    | TokenKind::Null
+                    | TokenKind::This
+                    | TokenKind::Super
+                    | TokenKind::New
+                    | TokenKind::LParen
+                    | TokenKind::Bang
+                    | TokenKind::Minus
+                    | TokenKind::Plus
+                    | TokenKind::PlusPlus
+                    | TokenKind::MinusMinus
+                    | TokenKind::Tilde
+                    | TokenKind::LBracket
+                    | TokenKind::HexLiteral(_)
+                    | TokenKind::BinaryLiteral(_)
+                    | TokenKind::OctalLiteral(_)
+            );
 
         if is_expression_start {
             // This is a cast expression
@@ -2547,6 +3035,7 @@ impl<'a> Parser<'a> {
                 type_ref,
                 expression: operand,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }))))
         } else {
             // This was just a parenthesized type reference - convert to identifier
@@ -2554,6 +3043,7 @@ impl<'a> Parser<'a> {
             Ok(Some(Expression::Parenthesized(
                 Box::new(expr),
                 start.merge(self.current_span()),
+                self.next_id(),
             )))
         }
     }
@@ -2579,6 +3069,7 @@ impl<'a> Parser<'a> {
                 left: expr,
                 right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }));
         }
 
@@ -2593,6 +3084,7 @@ impl<'a> Parser<'a> {
                 then_expr,
                 else_expr,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             })));
         }
 
@@ -2614,7 +3106,7 @@ impl<'a> Parser<'a> {
             match &self.current.kind {
                 TokenKind::Dot => {
                     self.advance();
-                    let name = self.parse_identifier()?;
+                    let name = self.parse_member_name()?;
 
                     // Check if it's a method call
                     if self.check(&TokenKind::LParen) {
@@ -2628,18 +3120,20 @@ impl<'a> Parser<'a> {
                             type_arguments: Vec::new(),
                             arguments,
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                     } else {
                         expr = Expression::FieldAccess(Box::new(FieldAccessExpr {
                             object: expr,
                             field: name,
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                     }
                 }
                 TokenKind::QuestionDot => {
                     self.advance();
-                    let name = self.parse_identifier()?;
+                    let name = self.parse_member_name()?;
 
                     // Check if it's a safe method call obj?.method()
                     if self.check(&TokenKind::LParen) {
@@ -2653,6 +3147,7 @@ impl<'a> Parser<'a> {
                             object: expr,
                             field: name.clone(),
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                         expr = Expression::MethodCall(Box::new(MethodCallExpr {
                             object: Some(safe_obj),
@@ -2660,12 +3155,14 @@ impl<'a> Parser<'a> {
                             type_arguments: Vec::new(),
                             arguments,
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                     } else {
                         expr = Expression::SafeNavigation(Box::new(SafeNavigationExpr {
                             object: expr,
                             field: name,
                             span: start.merge(self.current_span()),
+                            id: self.next_id(),
                         }));
                     }
                 }
@@ -2677,17 +3174,18 @@ impl<'a> Parser<'a> {
                         array: expr,
                         index,
                         span: start.merge(self.current_span()),
+                        id: self.next_id(),
                     }));
                 }
                 TokenKind::PlusPlus => {
                     self.advance();
-                    expr =
-                        Expression::PostIncrement(Box::new(expr), start.merge(self.current_span()));
+                    let span = start.merge(self.current_span());
+                    expr = Expression::PostIncrement(Box::new(expr), span, self.next_id());
                 }
                 TokenKind::MinusMinus => {
                     self.advance();
-                    expr =
-                        Expression::PostDecrement(Box::new(expr), start.merge(self.current_span()));
+                    let span = start.merge(self.current_span());
+                    expr = Expression::PostDecrement(Box::new(expr), span, self.next_id());
                 }
                 TokenKind::Lt => {
                     // This could be:
@@ -2701,7 +3199,7 @@ impl<'a> Parser<'a> {
                     //
                     // To avoid breaking normal comparisons like `x < 10`, we only attempt
                     // type literal parsing for known collection types.
-                    if let Expression::Identifier(type_name, id_span) = &expr {
+                    if let Expression::Identifier(type_name, id_span, _) = &expr {
                         // Only attempt type literal parsing for known generic types
                         let is_known_generic_type = matches!(
                             type_name.as_str(),
@@ -2772,27 +3270,30 @@ impl<'a> Parser<'a> {
                 type_arguments,
                 is_array: false,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             };
 
             return Ok(Some(Expression::TypeLiteral(
                 type_ref,
                 start.merge(self.current_span()),
+                self.next_id(),
             )));
         }
 
         // Type<T>.something - could be static method or field
         // Create a TypeLiteral and wrap it in field access
         // The postfix parsing will handle method calls
-        let member_name = self.parse_identifier()?;
+        let member_name = self.parse_member_name()?;
 
         let type_ref = TypeRef {
             name: type_name,
             type_arguments,
             is_array: false,
             span: start,
+            id: self.next_id(),
         };
 
-        let type_expr = Expression::TypeLiteral(type_ref, start);
+        let type_expr = Expression::TypeLiteral(type_ref, start, self.next_id());
 
         // Check if it's a method call
         if self.check(&TokenKind::LParen) {
@@ -2806,6 +3307,7 @@ impl<'a> Parser<'a> {
                 type_arguments: Vec::new(),
                 arguments,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }))));
         }
 
@@ -2814,6 +3316,7 @@ impl<'a> Parser<'a> {
             object: type_expr,
             field: member_name,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         }))))
     }
 
@@ -2823,46 +3326,49 @@ impl<'a> Parser<'a> {
         match &self.current.kind {
             TokenKind::Null => {
                 self.advance();
-                Ok(Expression::Null(start))
+                Ok(Expression::Null(start, self.next_id()))
             }
             TokenKind::True => {
                 self.advance();
-                Ok(Expression::Boolean(true, start))
+                Ok(Expression::Boolean(true, start, self.next_id()))
             }
             TokenKind::False => {
                 self.advance();
-                Ok(Expression::Boolean(false, start))
+                Ok(Expression::Boolean(false, start, self.next_id()))
             }
             TokenKind::IntegerLiteral(n)
             | TokenKind::HexLiteral(n)
             | TokenKind::BinaryLiteral(n)
             | TokenKind::OctalLiteral(n) => {
                 let n = *n;
+                let raw = self.current_text();
                 self.advance();
-                Ok(Expression::Integer(n, start))
+                Ok(Expression::Integer(n, raw, start, self.next_id()))
             }
             TokenKind::LongLiteral(n) | TokenKind::HexLongLiteral(n) => {
                 let n = *n;
+                let raw = self.current_text();
                 self.advance();
-                Ok(Expression::Long(n, start))
+                Ok(Expression::Long(n, raw, start, self.next_id()))
             }
             TokenKind::DoubleLiteral(n) => {
                 let n = *n;
+                let raw = self.current_text();
                 self.advance();
-                Ok(Expression::Double(n, start))
+                Ok(Expression::Double(n, raw, start, self.next_id()))
             }
             TokenKind::StringLiteral(s) => {
                 let s = s.clone();
                 self.advance();
-                Ok(Expression::String(s, start))
+                Ok(Expression::String(s, start, self.next_id()))
             }
             TokenKind::This => {
                 self.advance();
-                Ok(Expression::This(start))
+                Ok(Expression::This(start, self.next_id()))
             }
             TokenKind::Super => {
                 self.advance();
-                Ok(Expression::Super(start))
+                Ok(Expression::Super(start, self.next_id()))
             }
             TokenKind::New => {
                 self.parse_new_expression()
@@ -2874,36 +3380,14 @@ impl<'a> Parser<'a> {
                 Ok(Expression::Parenthesized(
                     Box::new(expr),
                     start.merge(self.current_span()),
+                    self.next_id(),
                 ))
             }
             TokenKind::LBracket => {
                 // SOQL query or array initializer
                 self.parse_soql_or_array()
             }
-            TokenKind::Identifier(_)
-            | TokenKind::Id
-            | TokenKind::First
-            | TokenKind::Last
-            | TokenKind::Order
-            | TokenKind::Group
-            | TokenKind::Limit
-            | TokenKind::Offset
-            | TokenKind::Date
-            | TokenKind::Time
-            | TokenKind::Trigger
-            | TokenKind::Object
-            | TokenKind::Set
-            | TokenKind::Map
-            | TokenKind::List
-            // Type keywords that can be used as class names (for static method calls like String.isBlank())
-            | TokenKind::StringType
-            | TokenKind::Integer
-            | TokenKind::Long
-            | TokenKind::Double
-            | TokenKind::Decimal
-            | TokenKind::Boolean
-            | TokenKind::Datetime
-            | TokenKind::Blob => {
+            kind if kind.is_name() => {
                 let name = self.parse_identifier()?;
 
                 // Check for method call
@@ -2918,9 +3402,10 @@ impl<'a> Parser<'a> {
                         type_arguments: Vec::new(),
                         arguments,
                         span: start.merge(self.current_span()),
+                        id: self.next_id(),
                     })))
                 } else {
-                    Ok(Expression::Identifier(name, start))
+                    Ok(Expression::Identifier(name, start, self.next_id()))
                 }
             }
             _ => Err(ParseError::InvalidExpression(start)),
@@ -2953,6 +3438,7 @@ impl<'a> Parser<'a> {
                     size: None,
                     initializer,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 })))
             } else {
                 // new Type[size]
@@ -2964,6 +3450,7 @@ impl<'a> Parser<'a> {
                     size: Some(size),
                     initializer: None,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 })))
             }
         } else if self.match_token(&TokenKind::LBrace) {
@@ -2976,6 +3463,7 @@ impl<'a> Parser<'a> {
                     type_ref,
                     initializer: Some(initializer),
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 })))
             } else {
                 // List or Set literal
@@ -2985,12 +3473,16 @@ impl<'a> Parser<'a> {
                 if type_ref.name == "Set" || type_ref.name.ends_with(".Set") {
                     Ok(Expression::SetLiteral(
                         items,
+                        Some(type_ref),
                         start.merge(self.current_span()),
+                        self.next_id(),
                     ))
                 } else {
                     Ok(Expression::ListLiteral(
                         items,
+                        Some(type_ref),
                         start.merge(self.current_span()),
+                        self.next_id(),
                     ))
                 }
             }
@@ -3004,6 +3496,7 @@ impl<'a> Parser<'a> {
                 type_ref,
                 arguments,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             })))
         }
     }
@@ -3092,7 +3585,9 @@ impl<'a> Parser<'a> {
             self.consume(&TokenKind::RBracket, "]")?;
             Ok(Expression::ListLiteral(
                 items,
+                None,
                 start.merge(self.current_span()),
+                self.next_id(),
             ))
         }
     }
@@ -3108,6 +3603,9 @@ impl<'a> Parser<'a> {
         self.consume(&TokenKind::From, "FROM")?;
         let from_clause = self.parse_soql_identifier()?;
 
+        // Optional USING SCOPE clause (USING SCOPE mine, team, everything, ...)
+        let using_scope = self.parse_soql_using_scope()?;
+
         // Optional WHERE clause
         let where_clause = if self.match_token(&TokenKind::Where) {
             Some(self.parse_soql_condition()?)
@@ -3118,12 +3616,20 @@ impl<'a> Parser<'a> {
         // Optional WITH clause (WITH SECURITY_ENFORCED, WITH USER_MODE, WITH SYSTEM_MODE)
         let with_clause = self.parse_soql_with_clause()?;
 
-        // Optional GROUP BY clause
-        let group_by_clause = if self.match_token(&TokenKind::Group) {
+        // Optional GROUP BY clause, possibly ROLLUP(...) or CUBE(...)
+        let (group_by_clause, grouping_sets) = if self.match_token(&TokenKind::Group) {
             self.consume(&TokenKind::By, "BY")?;
-            self.parse_group_by_fields()?
+            match self.parse_grouping_sets_keyword() {
+                Some(sets) => {
+                    self.consume(&TokenKind::LParen, "(")?;
+                    let fields = self.parse_group_by_fields()?;
+                    self.consume(&TokenKind::RParen, ")")?;
+                    (fields, Some(sets))
+                }
+                None => (self.parse_group_by_fields()?, None),
+            }
         } else {
-            Vec::new()
+            (Vec::new(), None)
         };
 
         // Optional HAVING clause (only valid with GROUP BY)
@@ -3186,15 +3692,18 @@ impl<'a> Parser<'a> {
         Ok(SoqlQuery {
             select_clause,
             from_clause,
+            using_scope,
             where_clause,
             with_clause,
             group_by_clause,
+            grouping_sets,
             having_clause,
             order_by_clause,
             limit_clause,
             offset_clause,
             for_clause,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         })
     }
 
@@ -3210,6 +3719,42 @@ impl<'a> Parser<'a> {
         Ok(fields)
     }
 
+    /// Consume `ROLLUP` or `CUBE` when followed by `(`; neither is reserved,
+    /// so a field with either name still groups normally
+    fn parse_grouping_sets_keyword(&mut self) -> Option<GroupingSets> {
+        let TokenKind::Identifier(s) = &self.current.kind else {
+            return None;
+        };
+        let sets = match s.to_lowercase().as_str() {
+            "rollup" => GroupingSets::Rollup,
+            "cube" => GroupingSets::Cube,
+            _ => return None,
+        };
+        if self.lexer.peek().kind != TokenKind::LParen {
+            return None;
+        }
+        self.advance();
+        Some(sets)
+    }
+
+    fn parse_soql_using_scope(&mut self) -> ParseResult<Option<String>> {
+        // USING and SCOPE are not reserved tokens
+        let is_keyword = |kind: &TokenKind, keyword: &str| matches!(kind, TokenKind::Identifier(s) if s.eq_ignore_ascii_case(keyword));
+        if !is_keyword(&self.current.kind, "using") {
+            return Ok(None);
+        }
+        self.advance();
+        if !is_keyword(&self.current.kind, "scope") {
+            return Err(ParseError::UnexpectedToken {
+                expected: "SCOPE".to_string(),
+                found: FoundToken::new(&self.current.kind),
+                span: self.current.span,
+            });
+        }
+        self.advance();
+        Ok(Some(self.parse_soql_identifier()?))
+    }
+
     fn parse_soql_with_clause(&mut self) -> ParseResult<Option<SoqlWithClause>> {
         // Check for WITH keyword (as identifier since it's not a reserved token)
         if let TokenKind::Identifier(s) = &self.current.kind {
@@ -3266,6 +3811,16 @@ impl<'a> Parser<'a> {
                         }
                         continue;
                     }
+                    if s.eq_ignore_ascii_case("fields")
+                        && self.lexer.peek().kind == TokenKind::LParen
+                    {
+                        let scope = self.parse_fields_scope()?;
+                        fields.push(SelectField::Fields(scope));
+                        if !self.match_token(&TokenKind::Comma) {
+                            break;
+                        }
+                        continue;
+                    }
                 }
                 // Regular field or relationship field (e.g., Account.Name, Contact__r.Email)
                 let name = self.parse_soql_field_path()?;
@@ -3289,7 +3844,7 @@ impl<'a> Parser<'a> {
             let lower = s.to_lowercase();
             matches!(
                 lower.as_str(),
-                "count" | "sum" | "avg" | "min" | "max" | "count_distinct"
+                "count" | "sum" | "avg" | "min" | "max" | "count_distinct" | "grouping"
             )
         } else {
             false
@@ -3297,12 +3852,34 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_aggregate_function(&mut self) -> ParseResult<SelectField> {
+        let (name, field) = self.parse_aggregate_call()?;
+
+        // Optional alias
+        let alias = if let TokenKind::Identifier(s) = &self.current.kind {
+            // Check if it's an alias (not a keyword)
+            if !self.is_soql_keyword() {
+                let a = s.clone();
+                self.advance();
+                Some(a)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        Ok(SelectField::AggregateFunction { name, field, alias })
+    }
+
+    /// Parse `COUNT(Id)`, `SUM(Amount)` or `COUNT()` into the function name
+    /// and field path (empty for `COUNT()`)
+    fn parse_aggregate_call(&mut self) -> ParseResult<(String, String)> {
         let name = if let TokenKind::Identifier(s) = &self.current.kind {
             s.clone()
         } else {
             return Err(ParseError::UnexpectedToken {
                 expected: "aggregate function".to_string(),
-                found: format!("{:?}", self.current.kind),
+                found: FoundToken::new(&self.current.kind),
                 span: self.current.span,
             });
         };
@@ -3318,22 +3895,32 @@ impl<'a> Parser<'a> {
         };
 
         self.consume(&TokenKind::RParen, ")")?;
+        Ok((name, field))
+    }
 
-        // Optional alias
-        let alias = if let TokenKind::Identifier(s) = &self.current.kind {
-            // Check if it's an alias (not a keyword)
-            if !self.is_soql_keyword() {
-                let a = s.clone();
-                self.advance();
-                Some(a)
-            } else {
-                None
+    /// Parse `FIELDS(ALL)`, `FIELDS(STANDARD)` or `FIELDS(CUSTOM)`
+    fn parse_fields_scope(&mut self) -> ParseResult<FieldsScope> {
+        // Skip "FIELDS"
+        self.advance();
+        self.consume(&TokenKind::LParen, "(")?;
+
+        let span = self.current.span;
+        let found = FoundToken::new(&self.current.kind);
+        let scope = match self.parse_soql_identifier()?.to_lowercase().as_str() {
+            "all" => FieldsScope::All,
+            "standard" => FieldsScope::Standard,
+            "custom" => FieldsScope::Custom,
+            _ => {
+                return Err(ParseError::UnexpectedToken {
+                    expected: "ALL, STANDARD or CUSTOM".to_string(),
+                    found,
+                    span,
+                });
             }
-        } else {
-            None
         };
 
-        Ok(SelectField::AggregateFunction { name, field, alias })
+        self.consume(&TokenKind::RParen, ")")?;
+        Ok(scope)
     }
 
     fn is_soql_keyword(&self) -> bool {
@@ -3435,6 +4022,7 @@ impl<'a> Parser<'a> {
                 operator: BinaryOp::Or,
                 right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }));
         }
 
@@ -3452,6 +4040,7 @@ impl<'a> Parser<'a> {
                 operator: BinaryOp::And,
                 right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             }));
         }
 
@@ -3466,6 +4055,7 @@ impl<'a> Parser<'a> {
                 operator: UnaryOp::Not,
                 operand: expr,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             })));
         }
         self.parse_soql_comparison()
@@ -3474,12 +4064,17 @@ impl<'a> Parser<'a> {
     fn parse_soql_comparison(&mut self) -> ParseResult<Expression> {
         let start = self.current_span();
 
-        // Handle parenthesized expressions
+        // Handle parenthesized expressions; the parens are kept so the
+        // converted SQL groups conditions exactly as written
         if self.check(&TokenKind::LParen) {
             self.advance();
             let expr = self.parse_soql_condition()?;
             self.consume(&TokenKind::RParen, ")")?;
-            return Ok(expr);
+            return Ok(Expression::Parenthesized(
+                Box::new(expr),
+                start.merge(self.current_span()),
+                self.next_id(),
+            ));
         }
 
         let left = self.parse_soql_expression()?;
@@ -3500,88 +4095,29 @@ impl<'a> Parser<'a> {
         } else if self.match_token(&TokenKind::Like) {
             Some(BinaryOp::Like)
         } else if self.match_token(&TokenKind::In) {
-            // IN operator - parse list of values OR bind variable
-            // Apex supports both: IN ('a', 'b') and IN :collectionVariable
-            if self.check(&TokenKind::Colon) {
-                // Bind variable: IN :variableName
-                let right = self.parse_soql_expression()?;
-                return Ok(Expression::Binary(Box::new(BinaryExpr {
-                    left,
-                    operator: BinaryOp::In,
-                    right,
-                    span: start.merge(self.current_span()),
-                })));
-            }
-            self.consume(&TokenKind::LParen, "(")?;
-            let mut values = Vec::new();
-            loop {
-                values.push(self.parse_soql_expression()?);
-                if !self.match_token(&TokenKind::Comma) {
-                    break;
-                }
-            }
-            self.consume(&TokenKind::RParen, ")")?;
-            // Create a special IN expression using Binary with a list
+            let right = self.parse_soql_in_operand(start)?;
             return Ok(Expression::Binary(Box::new(BinaryExpr {
                 left,
                 operator: BinaryOp::In,
-                right: Expression::NewArray(Box::new(NewArrayExpr {
-                    element_type: TypeRef {
-                        name: "Object".to_string(),
-                        type_arguments: Vec::new(),
-                        is_array: false,
-                        span: start,
-                    },
-                    size: None,
-                    initializer: Some(values),
-                    span: start.merge(self.current_span()),
-                })),
+                right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             })));
         } else if self.match_token(&TokenKind::Not) {
             // NOT IN
             if self.match_token(&TokenKind::In) {
-                // NOT IN operator - parse list of values OR bind variable
-                // Apex supports both: NOT IN ('a', 'b') and NOT IN :collectionVariable
-                if self.check(&TokenKind::Colon) {
-                    // Bind variable: NOT IN :variableName
-                    let right = self.parse_soql_expression()?;
-                    return Ok(Expression::Binary(Box::new(BinaryExpr {
-                        left,
-                        operator: BinaryOp::NotIn,
-                        right,
-                        span: start.merge(self.current_span()),
-                    })));
-                }
-                self.consume(&TokenKind::LParen, "(")?;
-                let mut values = Vec::new();
-                loop {
-                    values.push(self.parse_soql_expression()?);
-                    if !self.match_token(&TokenKind::Comma) {
-                        break;
-                    }
-                }
-                self.consume(&TokenKind::RParen, ")")?;
+                let right = self.parse_soql_in_operand(start)?;
                 return Ok(Expression::Binary(Box::new(BinaryExpr {
                     left,
                     operator: BinaryOp::NotIn,
-                    right: Expression::NewArray(Box::new(NewArrayExpr {
-                        element_type: TypeRef {
-                            name: "Object".to_string(),
-                            type_arguments: Vec::new(),
-                            is_array: false,
-                            span: start,
-                        },
-                        size: None,
-                        initializer: Some(values),
-                        span: start.merge(self.current_span()),
-                    })),
+                    right,
                     span: start.merge(self.current_span()),
+                    id: self.next_id(),
                 })));
             }
             return Err(ParseError::UnexpectedToken {
                 expected: "IN".to_string(),
-                found: format!("{:?}", self.current.kind),
+                found: FoundToken::new(&self.current.kind),
                 span: self.current.span,
             });
         } else if self.match_token(&TokenKind::Includes) {
@@ -3593,34 +4129,81 @@ impl<'a> Parser<'a> {
         };
 
         if let Some(op) = operator {
-            let right = self.parse_soql_expression()?;
+            let right = match self.current.kind {
+                TokenKind::StringLiteral(_) if op == BinaryOp::Like => self.parse_like_pattern(),
+                _ => self.parse_soql_expression()?,
+            };
             Ok(Expression::Binary(Box::new(BinaryExpr {
                 left,
                 operator: op,
                 right,
                 span: start.merge(self.current_span()),
+                id: self.next_id(),
             })))
         } else {
             Ok(left)
         }
     }
 
+    /// Parse the string literal after `LIKE`, keeping the escapes that tell
+    /// a literal backslash, `%` or `_` from a wildcard
+    fn parse_like_pattern(&mut self) -> Expression {
+        let span = self.current_span();
+        let raw = self.current_text();
+        self.advance();
+        let pattern = unescape_like_pattern(&raw[1..raw.len() - 1]);
+        Expression::String(pattern, span, self.next_id())
+    }
+
+    /// Parse what follows `IN` or `NOT IN`: a bind variable (`:ids`), a
+    /// semi-join (`(SELECT AccountId FROM Contact)`) or a list of values,
+    /// kept as an array initializer (`('a', 'b')`)
+    fn parse_soql_in_operand(&mut self, start: Span) -> ParseResult<Expression> {
+        if self.check(&TokenKind::Colon) {
+            return self.parse_soql_expression();
+        }
+        self.consume(&TokenKind::LParen, "(")?;
+        if self.check(&TokenKind::Select) {
+            let subquery = self.parse_soql_query()?;
+            self.consume(&TokenKind::RParen, ")")?;
+            return Ok(Expression::SoqlSubquery(Box::new(subquery)));
+        }
+        let mut values = Vec::new();
+        loop {
+            values.push(self.parse_soql_expression()?);
+            if !self.match_token(&TokenKind::Comma) {
+                break;
+            }
+        }
+        self.consume(&TokenKind::RParen, ")")?;
+        Ok(Expression::NewArray(Box::new(NewArrayExpr {
+            element_type: TypeRef {
+                name: "Object".to_string(),
+                type_arguments: Vec::new(),
+                is_array: false,
+                span: start,
+                id: self.next_id(),
+            },
+            size: None,
+            initializer: Some(values),
+            span: start.merge(self.current_span()),
+            id: self.next_id(),
+        })))
+    }
+
     /// Parse a SOQL expression (can include bind variables)
     fn parse_soql_expression(&mut self) -> ParseResult<Expression> {
         let start = self.current_span();
 
-        // Check for bind variable :varName or :varName.field
+        // Check for bind variable :varName or :varName.field, or a bind
+        // expression such as :ids.keySet() or :getLimit()
         if self.match_token(&TokenKind::Colon) {
-            let mut var_name = self.parse_identifier()?;
-            // Allow dotted field access in bind variables: :record.Id, :account.Name
-            while self.match_token(&TokenKind::Dot) {
-                let field = self.parse_identifier()?;
-                var_name = format!("{}.{}", var_name, field);
-            }
-            return Ok(Expression::BindVariable(
-                var_name,
-                start.merge(self.current_span()),
-            ));
+            let bound = self.parse_postfix()?;
+            let span = start.merge(self.current_span());
+            return Ok(match bind_path(&bound) {
+                Some(path) => Expression::BindVariable(path, span, self.next_id()),
+                None => Expression::BindExpression(Box::new(bound), span, self.next_id()),
+            });
         }
 
         // Check for date literals like TODAY, LAST_N_DAYS:n
@@ -3638,6 +4221,7 @@ impl<'a> Parser<'a> {
                         return Ok(Expression::Identifier(
                             format!("{}:{}", literal, n),
                             start.merge(self.current_span()),
+                            self.next_id(),
                         ));
                     }
                 }
@@ -3645,43 +4229,68 @@ impl<'a> Parser<'a> {
                 return Ok(Expression::Identifier(
                     literal,
                     start.merge(self.current_span()),
+                    self.next_id(),
                 ));
             }
         }
 
+        // Aggregates in HAVING: COUNT(Id) > 1
+        let is_aggregate =
+            self.is_aggregate_function() && self.lexer.peek().kind == TokenKind::LParen;
+
         // Parse regular expression (literals, field paths, etc.)
         match &self.current.kind {
             TokenKind::IntegerLiteral(n) => {
                 let n = *n;
+                let raw = self.current_text();
                 self.advance();
-                Ok(Expression::Integer(n, start))
+                Ok(Expression::Integer(n, raw, start, self.next_id()))
             }
             TokenKind::LongLiteral(n) => {
                 let n = *n;
+                let raw = self.current_text();
                 self.advance();
-                Ok(Expression::Long(n, start))
+                Ok(Expression::Long(n, raw, start, self.next_id()))
             }
             TokenKind::DoubleLiteral(n) => {
                 let n = *n;
+                let raw = self.current_text();
                 self.advance();
-                Ok(Expression::Double(n, start))
+                Ok(Expression::Double(n, raw, start, self.next_id()))
             }
             TokenKind::StringLiteral(s) => {
                 let s = s.clone();
                 self.advance();
-                Ok(Expression::String(s, start))
+                Ok(Expression::String(s, start, self.next_id()))
             }
             TokenKind::True => {
                 self.advance();
-                Ok(Expression::Boolean(true, start))
+                Ok(Expression::Boolean(true, start, self.next_id()))
             }
             TokenKind::False => {
                 self.advance();
-                Ok(Expression::Boolean(false, start))
+                Ok(Expression::Boolean(false, start, self.next_id()))
             }
             TokenKind::Null => {
                 self.advance();
-                Ok(Expression::Null(start))
+                Ok(Expression::Null(start, self.next_id()))
+            }
+            _ if is_aggregate => {
+                let (name, field) = self.parse_aggregate_call()?;
+                let span = start.merge(self.current_span());
+                let arguments = if field.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![Expression::Identifier(field, span, self.next_id())]
+                };
+                Ok(Expression::MethodCall(Box::new(MethodCallExpr {
+                    object: None,
+                    name,
+                    type_arguments: Vec::new(),
+                    arguments,
+                    span,
+                    id: self.next_id(),
+                })))
             }
             _ => {
                 // Field path
@@ -3689,34 +4298,23 @@ impl<'a> Parser<'a> {
                 Ok(Expression::Identifier(
                     path,
                     start.merge(self.current_span()),
+                    self.next_id(),
                 ))
             }
         }
     }
 
-    /// Parse an identifier in SOQL context, where many keywords can be used as field names
+    /// Parse an identifier in SOQL context, where contextual keywords can
+    /// be object and field names, kept as written
     fn parse_soql_identifier(&mut self) -> ParseResult<String> {
-        let name = match &self.current.kind {
-            TokenKind::Identifier(name) => name.clone(),
-            // Keywords that can be field names in SOQL
-            TokenKind::Id => "Id".to_string(),
-            TokenKind::Date => "Date".to_string(),
-            TokenKind::Time => "Time".to_string(),
-            TokenKind::Object => "Object".to_string(),
-            TokenKind::Order => "Order".to_string(),
-            TokenKind::Group => "Group".to_string(),
-            TokenKind::Limit => "Limit".to_string(),
-            TokenKind::Offset => "Offset".to_string(),
-            TokenKind::First => "First".to_string(),
-            TokenKind::Last => "Last".to_string(),
-            _ => {
-                return Err(ParseError::UnexpectedToken {
-                    expected: "identifier".to_string(),
-                    found: format!("{:?}", self.current.kind),
-                    span: self.current.span,
-                });
-            }
-        };
+        if !self.current.kind.is_name() {
+            return Err(ParseError::UnexpectedToken {
+                expected: "identifier".to_string(),
+                found: FoundToken::new(&self.current.kind),
+                span: self.current.span,
+            });
+        }
+        let name = self.current_text();
         self.advance();
         Ok(name)
     }
@@ -3794,7 +4392,7 @@ impl<'a> Parser<'a> {
         } else {
             return Err(ParseError::UnexpectedToken {
                 expected: "search term".to_string(),
-                found: format!("{:?}", self.current.kind),
+                found: FoundToken::new(&self.current.kind),
                 span: self.current.span,
             });
         };
@@ -3844,6 +4442,7 @@ impl<'a> Parser<'a> {
             with_clauses,
             limit_clause,
             span: start.merge(self.current_span()),
+            id: self.next_id(),
         })
     }
 
@@ -3994,6 +4593,35 @@ impl<'a> Parser<'a> {
     }
 }
 
+/// The user argument of a `System.runAs(user)` call
+fn run_as_user(expr: &Expression) -> Option<&Expression> {
+    match expr {
+        Expression::MethodCall(call)
+            if call.name.eq_ignore_ascii_case("runAs") && call.arguments.len() == 1 =>
+        {
+            match &call.object {
+                Some(Expression::Identifier(name, _, _)) if name.eq_ignore_ascii_case("System") => {
+                    call.arguments.first()
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Dotted name a bind reads, `record.Id` for `:record.Id`, or `None` for a
+/// bind that calls or indexes
+pub(crate) fn bind_path(expr: &Expression) -> Option<String> {
+    match expr {
+        Expression::Identifier(name, _, _) => Some(name.clone()),
+        Expression::FieldAccess(access) => {
+            bind_path(&access.object).map(|object| format!("{}.{}", object, access.field))
+        }
+        _ => None,
+    }
+}
+
 /// Check if an identifier is a SOQL date literal
 fn is_soql_date_literal(s: &str) -> bool {
     matches!(
@@ -4044,10 +4672,54 @@ pub fn parse(source: &str) -> ParseResult<CompilationUnit> {
     parser.parse()
 }
 
+/// Parse a standalone SOQL query string; see [`Parser::parse_soql`]
+pub fn parse_soql(query: &str) -> ParseResult<SoqlQuery> {
+    Parser::new(query).parse_soql()
+}
+
+/// Parse a standalone SOSL search string; see [`Parser::parse_sosl`]
+pub fn parse_sosl(query: &str) -> ParseResult<SoslQuery> {
+    Parser::new(query).parse_sosl()
+}
+
+/// Parse an Apex source string, collecting every syntax error; see
+/// [`Parser::parse_with_recovery`]
+pub fn parse_with_recovery(source: &str) -> (CompilationUnit, Vec<ParseError>) {
+    Parser::new(source).parse_with_recovery()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_error_truncates_huge_string_literal() {
+        let literal = "x".repeat(100 * 1024);
+        let source = format!(
+            "public class A {{ void m() {{ Integer n = 1 '{}'; }} }}",
+            literal
+        );
+
+        let err = parse(&source).unwrap_err();
+        let ParseError::UnexpectedToken { ref found, .. } = err else {
+            panic!("expected UnexpectedToken, got {:?}", err);
+        };
+        assert!(matches!(
+            found.kind(),
+            Some(TokenKind::StringLiteral(s)) if s.len() == MAX_FOUND_LEN + 3
+        ));
+
+        let message = err.to_string();
+        assert!(message.len() < 200, "{} byte error message", message.len());
+        assert!(message.contains("xxx..."));
+    }
+
+    #[test]
+    fn test_error_keeps_short_tokens_intact() {
+        let err = parse("public class A { void m() { Integer n = 1 foo; } }").unwrap_err();
+        assert!(err.to_string().contains("found Identifier(\"foo\")"));
+    }
+
     #[test]
     fn test_parse_simple_class() {
         let source = r#"
@@ -0,0 +1,165 @@
+//! Building SOQL queries in code
+//!
+//! [`SoqlQueryBuilder`] assembles the same [`SoqlQuery`] AST the parser
+//! produces, so a query built in Rust converts to SQL without going through
+//! query text:
+//!
+//! ```
+//! use apexrust::soql_builder::{bind, SoqlQueryBuilder};
+//!
+//! let query = SoqlQueryBuilder::new()
+//!     .select(&["Id", "Name"])
+//!     .from("Account")
+//!     .where_eq("Industry", bind("ind"))
+//!     .limit(10)
+//!     .build();
+//!
+//! assert_eq!(query.from_clause, "Account");
+//! assert_eq!(query.select_clause.len(), 2);
+//! ```
+//!
+//! Built nodes carry empty spans since they have no source, and
+//! [`NodeId::DUMMY`] ids until placed in a compilation unit.
+
+use crate::ast::{BinaryExpr, BinaryOp, Expression, NodeId, OrderByField, SelectField};
+use crate::ast::{SoqlQuery, SoqlWithClause};
+use crate::lexer::Span;
+
+const NO_SPAN: Span = Span::new(0, 0);
+
+/// A `:name` bind variable
+pub fn bind(name: &str) -> Expression {
+    Expression::BindVariable(name.to_string(), NO_SPAN, NodeId::DUMMY)
+}
+
+/// A string literal
+pub fn string(value: &str) -> Expression {
+    Expression::String(value.to_string(), NO_SPAN, NodeId::DUMMY)
+}
+
+/// An integer literal
+pub fn integer(value: i64) -> Expression {
+    Expression::Integer(value, value.to_string(), NO_SPAN, NodeId::DUMMY)
+}
+
+/// A field reference; relationship paths stay dotted (`Account.Name`) as
+/// the parser keeps them
+pub fn field(path: &str) -> Expression {
+    Expression::Identifier(path.to_string(), NO_SPAN, NodeId::DUMMY)
+}
+
+/// Builder for a [`SoqlQuery`]
+///
+/// Conditions added with the `where_*` methods are joined with `AND`.
+#[derive(Debug, Clone, Default)]
+pub struct SoqlQueryBuilder {
+    select: Vec<SelectField>,
+    from: String,
+    condition: Option<Expression>,
+    with: Option<SoqlWithClause>,
+    order_by: Vec<OrderByField>,
+    limit: Option<Expression>,
+    offset: Option<Expression>,
+}
+
+impl SoqlQueryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add fields to the SELECT list
+    pub fn select(mut self, fields: &[&str]) -> Self {
+        self.select
+            .extend(fields.iter().map(|f| SelectField::Field(f.to_string())));
+        self
+    }
+
+    /// Add a child relationship subquery to the SELECT list
+    pub fn select_subquery(mut self, subquery: SoqlQuery) -> Self {
+        self.select.push(SelectField::SubQuery(Box::new(subquery)));
+        self
+    }
+
+    /// Set the queried object
+    pub fn from(mut self, object: &str) -> Self {
+        self.from = object.to_string();
+        self
+    }
+
+    /// Add a `field = value` condition
+    pub fn where_eq(self, field_path: &str, value: Expression) -> Self {
+        self.where_op(field_path, BinaryOp::Equal, value)
+    }
+
+    /// Add a `field <op> value` condition, such as `BinaryOp::Like` or
+    /// `BinaryOp::In` with a bind variable
+    pub fn where_op(self, field_path: &str, operator: BinaryOp, value: Expression) -> Self {
+        self.where_expr(Expression::Binary(Box::new(BinaryExpr {
+            left: field(field_path),
+            operator,
+            right: value,
+            span: NO_SPAN,
+            id: NodeId::DUMMY,
+        })))
+    }
+
+    /// Add an arbitrary condition
+    pub fn where_expr(mut self, condition: Expression) -> Self {
+        self.condition = Some(match self.condition.take() {
+            None => condition,
+            Some(left) => Expression::Binary(Box::new(BinaryExpr {
+                left,
+                operator: BinaryOp::And,
+                right: condition,
+                span: NO_SPAN,
+                id: NodeId::DUMMY,
+            })),
+        });
+        self
+    }
+
+    /// Set the `WITH SECURITY_ENFORCED` / `USER_MODE` / `SYSTEM_MODE` clause
+    pub fn with(mut self, clause: SoqlWithClause) -> Self {
+        self.with = Some(clause);
+        self
+    }
+
+    /// Add an ORDER BY field
+    pub fn order_by(mut self, field: &str, ascending: bool) -> Self {
+        self.order_by.push(OrderByField {
+            field: field.to_string(),
+            ascending,
+            nulls_first: None,
+        });
+        self
+    }
+
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(integer(limit));
+        self
+    }
+
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.offset = Some(integer(offset));
+        self
+    }
+
+    pub fn build(self) -> SoqlQuery {
+        SoqlQuery {
+            select_clause: self.select,
+            from_clause: self.from,
+            using_scope: None,
+            where_clause: self.condition,
+            with_clause: self.with,
+            group_by_clause: Vec::new(),
+            grouping_sets: None,
+            having_clause: None,
+            order_by_clause: self.order_by,
+            limit_clause: self.limit,
+            offset_clause: self.offset,
+            for_clause: None,
+            span: NO_SPAN,
+            id: NodeId::DUMMY,
+        }
+    }
+}
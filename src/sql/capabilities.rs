@@ -0,0 +1,105 @@
+//! SOQL restrictions of big objects and external objects
+//!
+//! Salesforce rejects some otherwise valid SOQL depending on the kind of the
+//! queried object. Big objects (`__b`) only accept filters that follow their
+//! index, leading field first, and have no LIKE, NOT IN, INCLUDES or
+//! EXCLUDES. External objects (`__x`) have no aggregate functions. The SQL
+//! backend would run such queries happily, so they are flagged here instead
+//! of surfacing as surprises once the code is deployed.
+
+use crate::ast::{BinaryOp, Expression, SelectField, SoqlQuery};
+
+use super::schema::ObjectKind;
+
+/// Messages describing each restriction `query` breaks for an object of
+/// `kind`, in query order
+///
+/// `index_fields` is the big object index, leading field first; the index
+/// check is skipped when it is empty.
+pub fn capability_violations(
+    kind: ObjectKind,
+    index_fields: &[String],
+    query: &SoqlQuery,
+) -> Vec<String> {
+    let mut violations = Vec::new();
+    match kind {
+        ObjectKind::BigObject => {
+            if let Some(ref where_expr) = query.where_clause {
+                let mut filtered = Vec::new();
+                big_object_filters(where_expr, &mut filtered, &mut violations);
+                if !index_fields.is_empty() {
+                    check_index_prefix(index_fields, &filtered, &mut violations);
+                }
+            }
+        }
+        ObjectKind::External => {
+            for field in &query.select_clause {
+                if let SelectField::AggregateFunction { name, .. } = field {
+                    violations.push(format!(
+                        "aggregate {}() is not supported on external objects",
+                        name.to_uppercase()
+                    ));
+                }
+            }
+        }
+        ObjectKind::Standard | ObjectKind::Custom => {}
+    }
+    violations
+}
+
+/// Collect the fields filtered in a big object WHERE clause, flagging the
+/// operators big objects reject along the way
+fn big_object_filters(expr: &Expression, filtered: &mut Vec<String>, violations: &mut Vec<String>) {
+    match expr {
+        Expression::Binary(binary) if binary.operator == BinaryOp::And => {
+            big_object_filters(&binary.left, filtered, violations);
+            big_object_filters(&binary.right, filtered, violations);
+        }
+        Expression::Binary(binary) => {
+            let unsupported = match binary.operator {
+                BinaryOp::Like => Some("LIKE"),
+                BinaryOp::NotIn => Some("NOT IN"),
+                BinaryOp::Includes => Some("INCLUDES"),
+                BinaryOp::Excludes => Some("EXCLUDES"),
+                _ => None,
+            };
+            if let Some(operator) = unsupported {
+                violations.push(format!("{} is not supported on big objects", operator));
+            }
+            if let Expression::Identifier(field, _, _) = &binary.left {
+                if !filtered.iter().any(|f| f.eq_ignore_ascii_case(field)) {
+                    filtered.push(field.clone());
+                }
+            }
+        }
+        Expression::Parenthesized(inner, _, _) => big_object_filters(inner, filtered, violations),
+        _ => {}
+    }
+}
+
+/// Big object filters must cover a leading prefix of the index: the first
+/// index field, then optionally the second, and so on without gaps
+fn check_index_prefix(index_fields: &[String], filtered: &[String], violations: &mut Vec<String>) {
+    let is_filtered = |index: &String| filtered.iter().any(|f| f.eq_ignore_ascii_case(index));
+    let prefix = index_fields.iter().take_while(|i| is_filtered(i)).count();
+
+    for field in filtered {
+        match index_fields
+            .iter()
+            .position(|i| i.eq_ignore_ascii_case(field))
+        {
+            None => violations.push(format!(
+                "'{}' is not an index field of the big object (index: {})",
+                field,
+                index_fields.join(", ")
+            )),
+            Some(position) if position > prefix => violations.push(format!(
+                "filter on '{}' skips index field '{}'; big object filters must follow the index ({}) from its first field",
+                field,
+                index_fields[prefix],
+                index_fields.join(", ")
+            )),
+            Some(_) => {}
+        }
+    }
+}
@@ -1,16 +1,23 @@
 //! SOQL to SQL converter
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use crate::ast::printer;
 use crate::ast::{
-    BinaryOp, Expression, ForClause, OrderByField, SelectField, SoqlQuery, SoqlWithClause,
-    TypeOfClause,
+    BinaryOp, Expression, FieldsScope, ForClause, GroupingSets, OrderByField, SelectField,
+    SoqlQuery, SoqlWithClause, TypeOfClause,
 };
 
-use super::date_literals::{expand_date_literal, is_date_literal};
-use super::dialect::{get_dialect, SqlDialect, SqlDialectImpl};
-use super::error::{ConversionError, ConversionResult, ConversionWarning};
-use super::schema::SalesforceSchema;
+use crate::cancel::CancellationToken;
+use crate::clock::ClockSource;
+use crate::lexer::Span;
+
+use super::capabilities::capability_violations;
+use super::date_literals::{expand_date_literal_at, is_date_literal};
+use super::dialect::{get_dialect, resolve_qualifier, SqlDialect, SqlDialectImpl, SqliteQualifier};
+use super::error::{ConversionError, ConversionResult, ConversionWarning, WarningKind};
+use super::ids::normalize_id;
+use super::schema::{ObjectKind, SalesforceFieldType, SalesforceSchema};
 
 /// Result of SOQL to SQL conversion
 #[derive(Debug, Clone)]
@@ -25,6 +32,92 @@ pub struct SqlConversion {
     pub warnings: Vec<ConversionWarning>,
     /// Security mode from WITH clause (if any)
     pub security_mode: Option<SecurityMode>,
+    /// How many rows the query can return
+    pub result_shape: ResultShape,
+    /// Dialect the SQL was generated for
+    pub dialect: SqlDialect,
+}
+
+impl SqlConversion {
+    /// Warnings of one kind, in the order they were reported
+    pub fn warnings_of_kind(&self, kind: WarningKind) -> impl Iterator<Item = &ConversionWarning> {
+        self.warnings
+            .iter()
+            .filter(move |warning| warning.kind() == kind)
+    }
+
+    /// Render the SQL with bind placeholders replaced by readable values.
+    ///
+    /// Each placeholder becomes a quoted stand-in annotated with the original
+    /// Apex bind name, e.g. `$1` -> `'<accountId>' /* :accountId */`. The
+    /// result is meant for copy-paste debugging only; use `sql` and
+    /// `parameters` for execution.
+    pub fn to_debug_sql(&self) -> String {
+        self.substitute_placeholders(|param| format!("'<{0}>' /* :{0} */", param.original_name))
+    }
+
+    /// Render an EXPLAIN statement for the query, for inspecting its plan
+    /// against a development database.
+    ///
+    /// Postgres gets `EXPLAIN (ANALYZE, BUFFERS)` and MySQL `EXPLAIN ANALYZE`,
+    /// or a plain `EXPLAIN` when `analyze` is false; SQLite always gets
+    /// `EXPLAIN QUERY PLAN`, which never runs the query. Placeholders are
+    /// replaced with sample values chosen from the parameter metadata (see
+    /// [`SqlParameter::sample`]), so the plan reflects the shape of the query
+    /// rather than real data.
+    pub fn explain_sql(&self, analyze: bool) -> String {
+        let prefix = match self.dialect {
+            SqlDialect::Postgres if analyze => "EXPLAIN (ANALYZE, BUFFERS)",
+            SqlDialect::Postgres => "EXPLAIN",
+            SqlDialect::Sqlite => "EXPLAIN QUERY PLAN",
+            SqlDialect::Mysql if analyze => "EXPLAIN ANALYZE",
+            SqlDialect::Mysql => "EXPLAIN",
+        };
+        let sql = self.substitute_placeholders(|param| param.sample(self.dialect));
+        format!("{} {}", prefix, sql)
+    }
+
+    fn substitute_placeholders(&self, render: impl Fn(&SqlParameter) -> String) -> String {
+        // Longest placeholders first so "$10" is not matched as "$1" + "0"
+        let mut params: Vec<&SqlParameter> = self.parameters.iter().collect();
+        params.sort_by_key(|p| std::cmp::Reverse(p.placeholder.len()));
+        // Every MySQL placeholder is `?`, taken by the parameters in order
+        let mut positional = self.parameters.iter().filter(|p| p.placeholder == "?");
+
+        let mut out = String::with_capacity(self.sql.len());
+        let mut rest = self.sql.as_str();
+        'scan: while !rest.is_empty() {
+            // Placeholder text inside a literal or quoted identifier is data
+            let quoted = quoted_len(rest, self.dialect == SqlDialect::Mysql);
+            if quoted > 0 {
+                out.push_str(&rest[..quoted]);
+                rest = &rest[quoted..];
+                continue;
+            }
+            for param in &params {
+                if let Some(after) = rest.strip_prefix(param.placeholder.as_str()) {
+                    let ends_mid_name = after
+                        .chars()
+                        .next()
+                        .map(|c| c.is_ascii_alphanumeric() || c == '_')
+                        .unwrap_or(false);
+                    if !ends_mid_name {
+                        let param = match param.placeholder.as_str() {
+                            "?" => positional.next().unwrap_or(param),
+                            _ => param,
+                        };
+                        out.push_str(&render(param));
+                        rest = after;
+                        continue 'scan;
+                    }
+                }
+            }
+            let ch = rest.chars().next().unwrap();
+            out.push(ch);
+            rest = &rest[ch.len_utf8()..];
+        }
+        out
+    }
 }
 
 /// A bind parameter in the generated SQL
@@ -32,10 +125,39 @@ pub struct SqlConversion {
 pub struct SqlParameter {
     /// Parameter name in SQL (e.g., "p1")
     pub name: String,
-    /// Placeholder in SQL (e.g., "$1" for Postgres, "?1" for SQLite)
+    /// Placeholder in SQL (e.g., "$1" for Postgres, "?1" for SQLite, "?" for
+    /// MySQL)
     pub placeholder: String,
     /// Original Apex variable name
     pub original_name: String,
+    /// Type of the field the parameter is compared with, when known
+    pub value_type: Option<SalesforceFieldType>,
+    /// Whether the parameter binds a list (`IN :ids`)
+    pub collection: bool,
+    /// How the caller prepares Id values: normalized with
+    /// [`normalize_id`](super::normalize_id), or upper-cased for
+    /// collections (scalars are wrapped in `UPPER()` by the SQL itself).
+    /// Always `Exact` for parameters not compared with an Id field.
+    pub id_comparison: IdComparison,
+}
+
+impl SqlParameter {
+    /// A stand-in literal for plan inspection, commented as a sample: `0`
+    /// for numbers, a boolean for checkboxes, `'…'` otherwise, wrapped in a
+    /// one-element list for collections
+    pub fn sample(&self, dialect: SqlDialect) -> String {
+        let dialect = get_dialect(dialect);
+        let value = match self.value_type {
+            Some(field_type) if field_type.is_numeric() => "0",
+            Some(SalesforceFieldType::Boolean) => dialect.boolean_literal(true),
+            _ => "'…'",
+        };
+        if self.collection {
+            format!("({}) /* sample for :{} */", value, self.original_name)
+        } else {
+            format!("{} /* sample for :{} */", value, self.original_name)
+        }
+    }
 }
 
 /// Security mode from SOQL WITH clause
@@ -46,16 +168,170 @@ pub enum SecurityMode {
     SystemMode,
 }
 
+/// Rows a converted query can return
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResultShape {
+    /// Any number of rows
+    #[default]
+    Rows,
+    /// At most one row, fetched by primary key (`WHERE Id = :recordId`),
+    /// so runtimes can use a keyed lookup instead of running the SQL
+    SingleRecordByPk,
+    /// One row with one unnamed column, the count of `SELECT COUNT()`,
+    /// which Apex reads as an Integer
+    Scalar,
+    /// `AggregateResult` rows: groups, or aggregates over every record
+    Aggregate,
+}
+
+impl ResultShape {
+    /// The shape of `query`'s result
+    pub fn of(query: &SoqlQuery) -> Self {
+        if query.is_count_query() {
+            ResultShape::Scalar
+        } else if query.is_aggregate() {
+            ResultShape::Aggregate
+        } else if query.is_single_record_by_id() {
+            ResultShape::SingleRecordByPk
+        } else {
+            ResultShape::Rows
+        }
+    }
+}
+
 /// How to handle bind variables in generated SQL
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum BindVariableMode {
-    /// Replace :var with $1, $2 (Postgres) or ?1, ?2 (SQLite)
+    /// Replace :var with $1, $2 (Postgres), ?1, ?2 (SQLite) or ? (MySQL)
     #[default]
     Parameterized,
     /// Replace :var with a placeholder string for debugging
     Placeholder,
 }
 
+/// How string-typed ORDER BY and GROUP BY keys are collated
+///
+/// Salesforce orders text case-insensitively, while Postgres columns usually
+/// use a case-sensitive collation and SQLite defaults to BINARY. WHERE
+/// comparisons are never affected.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum CollationMode {
+    /// Leave keys to the column's collation
+    #[default]
+    DatabaseDefault,
+    /// `LOWER(key)` on Postgres, `key COLLATE NOCASE` on SQLite
+    CaseInsensitive,
+    /// `key COLLATE "<name>"`, e.g. an ICU collation on Postgres
+    Explicit(String),
+}
+
+/// What happens when a query breaks a big object or external object
+/// restriction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum CapabilityMode {
+    /// Fail with [`ConversionError::UnsupportedForObject`]
+    #[default]
+    Error,
+    /// Convert anyway and report [`ConversionWarning::UnsupportedForObject`]
+    Warning,
+}
+
+/// Warning kinds that fail the conversion or are dropped from
+/// [`SqlConversion::warnings`]
+///
+/// A kind in both sets is promoted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WarningFilter {
+    /// Kinds returned as [`ConversionError::PromotedWarning`]
+    pub promote: HashSet<WarningKind>,
+    /// Kinds left out of the conversion's warnings
+    pub suppress: HashSet<WarningKind>,
+}
+
+impl WarningFilter {
+    pub fn promote(mut self, kind: WarningKind) -> Self {
+        self.promote.insert(kind);
+        self
+    }
+
+    pub fn suppress(mut self, kind: WarningKind) -> Self {
+        self.suppress.insert(kind);
+        self
+    }
+
+    /// Drop suppressed warnings, or fail on the first promoted one
+    fn apply(&self, warnings: Vec<ConversionWarning>) -> ConversionResult<Vec<ConversionWarning>> {
+        let mut kept = Vec::with_capacity(warnings.len());
+        for warning in warnings {
+            if self.promote.contains(&warning.kind()) {
+                return Err(ConversionError::PromotedWarning(warning));
+            }
+            if !self.suppress.contains(&warning.kind()) {
+                kept.push(warning);
+            }
+        }
+        Ok(kept)
+    }
+}
+
+/// How `RecordType.DeveloperName` and `RecordType.Name` are read
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum RecordTypeStrategy {
+    /// Join the `RecordType` table like any other parent
+    #[default]
+    Join,
+    /// Read a column on the record's own table that holds the record type's
+    /// developer name, as warehouses often denormalize it; `RecordTypeId`
+    /// is unaffected
+    DenormalizedColumn { column: String },
+}
+
+/// How Id and lookup columns are compared
+///
+/// 15-character Ids are case-sensitive, while warehouses loaded by different
+/// tools often mix 15- and 18-character Ids in inconsistent casing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum IdComparison {
+    /// Compare values as written
+    #[default]
+    Exact,
+    /// Columns hold 18-character Ids; Id literals are converted with
+    /// [`normalize_id`](super::normalize_id) and parameters are flagged for
+    /// the caller to do the same
+    #[cfg_attr(feature = "config", serde(rename = "normalize-to-18"))]
+    NormalizeTo18,
+    /// `UPPER(column) = UPPER(value)` for Id-typed columns, including the
+    /// JOIN ON conditions of relationship traversal. Such comparisons can't
+    /// use a plain index; see [`DdlGenerator::with_id_comparison`](super::DdlGenerator::with_id_comparison)
+    /// for matching expression indexes.
+    CaseInsensitive,
+}
+
 /// Configuration for SOQL to SQL conversion
 #[derive(Debug, Clone)]
 pub struct ConversionConfig {
@@ -64,9 +340,39 @@ pub struct ConversionConfig {
     /// How to handle bind variables
     pub bind_mode: BindVariableMode,
     /// Whether to include soft-delete filter (WHERE is_deleted = false)
+    ///
+    /// Applies to the root table, every joined parent table (inside the
+    /// JOIN ON condition) and every child subquery, skipping objects whose
+    /// describe has no `IsDeleted` field.
     pub filter_deleted: bool,
+    /// Column used by the soft-delete filter (e.g. "is_deleted", "_fivetran_deleted")
+    pub deleted_column: String,
     /// Maximum query depth for relationship traversal
     pub max_relationship_depth: u8,
+    /// Collation for ORDER BY / GROUP BY keys on text fields (needs a schema
+    /// to tell which fields are text)
+    pub string_collation: CollationMode,
+    /// Schema (namespace) every table reference is qualified with, e.g.
+    /// `"org_a"."account"`
+    pub schema_qualifier: Option<String>,
+    /// Per-object schema overrides keyed by API name, for shared tables that
+    /// live outside `schema_qualifier`
+    pub schema_overrides: HashMap<String, String>,
+    /// How qualifiers are applied when targeting SQLite
+    pub sqlite_qualifier: SqliteQualifier,
+    /// Whether big object / external object restrictions fail the conversion
+    /// or only warn
+    pub capability_mode: CapabilityMode,
+    /// Whether record type names come from a join or a local column
+    pub record_type_strategy: RecordTypeStrategy,
+    /// Warning kinds to promote to errors or suppress
+    pub warning_filter: WarningFilter,
+    /// How Id and lookup columns are compared
+    pub id_comparison: IdComparison,
+    /// The current date for date literals such as `TODAY`; the default
+    /// leaves it to the database at run time, any other source writes its
+    /// date into the SQL
+    pub clock: ClockSource,
 }
 
 impl Default for ConversionConfig {
@@ -75,7 +381,17 @@ impl Default for ConversionConfig {
             dialect: SqlDialect::Postgres,
             bind_mode: BindVariableMode::Parameterized,
             filter_deleted: false,
+            deleted_column: "is_deleted".to_string(),
             max_relationship_depth: 5,
+            string_collation: CollationMode::DatabaseDefault,
+            schema_qualifier: None,
+            schema_overrides: HashMap::new(),
+            sqlite_qualifier: SqliteQualifier::Ignore,
+            capability_mode: CapabilityMode::Error,
+            record_type_strategy: RecordTypeStrategy::Join,
+            warning_filter: WarningFilter::default(),
+            id_comparison: IdComparison::Exact,
+            clock: ClockSource::System,
         }
     }
 }
@@ -87,8 +403,12 @@ pub struct SoqlToSqlConverter<'a> {
     config: ConversionConfig,
     /// Current FROM object context
     current_object: Option<String>,
+    /// Span of the query being converted, for errors on fields without one
+    query_span: Option<Span>,
     /// Table alias counter for joins
     alias_counter: u32,
+    /// Prefix of new table aliases: `t`, or `s` inside a semi-join
+    alias_prefix: &'static str,
     /// Collected parameters
     parameters: Vec<SqlParameter>,
     /// Collected warnings
@@ -97,8 +417,59 @@ pub struct SoqlToSqlConverter<'a> {
     joins: Vec<JoinClause>,
     /// Column aliases for SELECT
     column_map: HashMap<String, String>,
-    /// Table aliases for objects
-    table_aliases: HashMap<String, String>,
+    /// Aliases of the root table and joined tables
+    aliases: AliasRegistry,
+    /// Lowercased GROUP BY paths whose key was wrapped for collation
+    collated_group_keys: HashSet<String>,
+    cancellation: Option<CancellationToken>,
+    /// Unknown field/relationship errors recorded instead of returned, set
+    /// only while [`convert_collecting`](Self::convert_collecting) runs
+    collected_errors: Option<Vec<ConversionError>>,
+}
+
+/// A parent relationship resolved from the schema
+#[derive(Debug, Clone)]
+struct ParentLookup {
+    /// Referenced objects (more than one for polymorphic lookups)
+    targets: Vec<String>,
+    /// FK column on the child table
+    column: String,
+    /// Discriminator column for polymorphic lookups
+    type_column: Option<String>,
+}
+
+/// Table aliases of the query being converted
+///
+/// Root tables and joined tables are registered separately so a join key
+/// can never shadow an object name or the other way around.
+#[derive(Debug, Clone, Default)]
+struct AliasRegistry {
+    /// Lowercased object name of the FROM table (or a subquery's child
+    /// table) to its alias
+    roots: HashMap<String, String>,
+    /// Parent joins to their alias
+    joins: HashMap<JoinKey, String>,
+}
+
+/// Identity of a parent join: the same relationship field read from two
+/// different tables (`Owner` on Contact and on Account) is two joins
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct JoinKey {
+    /// Alias of the table holding the FK
+    from_alias: String,
+    /// FK column on that table
+    join_field: String,
+    /// Joined object, set for polymorphic lookups which join each target
+    target: Option<String>,
+}
+
+impl AliasRegistry {
+    fn is_registered(&self, alias: &str) -> bool {
+        self.roots
+            .values()
+            .chain(self.joins.values())
+            .any(|a| a == alias)
+    }
 }
 
 /// A JOIN clause to be added to the query
@@ -119,12 +490,17 @@ impl<'a> SoqlToSqlConverter<'a> {
             dialect,
             config,
             current_object: None,
+            query_span: None,
             alias_counter: 0,
+            alias_prefix: "t",
             parameters: Vec::new(),
             warnings: Vec::new(),
             joins: Vec::new(),
             column_map: HashMap::new(),
-            table_aliases: HashMap::new(),
+            aliases: AliasRegistry::default(),
+            collated_group_keys: HashSet::new(),
+            cancellation: None,
+            collected_errors: None,
         }
     }
 
@@ -136,90 +512,144 @@ impl<'a> SoqlToSqlConverter<'a> {
             dialect,
             config,
             current_object: None,
+            query_span: None,
             alias_counter: 0,
+            alias_prefix: "t",
             parameters: Vec::new(),
             warnings: Vec::new(),
             joins: Vec::new(),
             column_map: HashMap::new(),
-            table_aliases: HashMap::new(),
+            aliases: AliasRegistry::default(),
+            collated_group_keys: HashSet::new(),
+            cancellation: None,
+            collected_errors: None,
+        }
+    }
+
+    /// Stop with [`ConversionError::Cancelled`] once `token` fires
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Stop with [`ConversionError::Cancelled`] once `timeout` has elapsed
+    pub fn with_timeout(self, timeout: std::time::Duration) -> Self {
+        self.with_cancellation(CancellationToken::with_timeout(timeout))
+    }
+
+    /// Convert a query, collecting every unknown field and relationship
+    /// instead of stopping at the first
+    ///
+    /// Unlike [`convert`](Self::convert), fields missing from a schema
+    /// object are reported as [`ConversionError::UnknownField`] rather than
+    /// falling back to their snake_case column. The SQL is only returned
+    /// when nothing was reported; any other error ends the conversion and is
+    /// appended to the list.
+    pub fn convert_collecting(
+        &mut self,
+        query: &SoqlQuery,
+    ) -> (Option<SqlConversion>, Vec<ConversionError>) {
+        self.collected_errors = Some(Vec::new());
+        let result = self.convert(query);
+        let mut errors = self.collected_errors.take().unwrap_or_default();
+        match result {
+            Ok(conversion) if errors.is_empty() => (Some(conversion), errors),
+            Ok(_) => (None, errors),
+            Err(error) => {
+                errors.push(error);
+                (None, errors)
+            }
         }
     }
 
     /// Convert a SOQL query to SQL
     pub fn convert(&mut self, query: &SoqlQuery) -> ConversionResult<SqlConversion> {
+        self.check_cancelled()?;
+
         // Reset state
         self.parameters.clear();
         self.warnings.clear();
         self.joins.clear();
         self.column_map.clear();
-        self.table_aliases.clear();
+        self.aliases = AliasRegistry::default();
+        self.collated_group_keys.clear();
         self.alias_counter = 0;
 
         // Set current object context
         self.current_object = Some(query.from_clause.clone());
+        self.query_span = Some(query.span);
+        self.check_capabilities(query)?;
 
         // Build query parts - FROM first to establish main table alias
         let from_sql = self.convert_from_clause(&query.from_clause)?;
+        if query.using_scope.is_some() {
+            self.warnings.push(ConversionWarning::SalesforceOnlyClause {
+                clause: "USING SCOPE".to_string(),
+            });
+        }
+
+        // Where the collated key is an expression, the select list can only
+        // use it as grouped, so fields grouped under it are read through MIN()
+        if self.dialect.group_key_wraps_expr() {
+            self.collated_group_keys = query
+                .group_by_clause
+                .iter()
+                .filter(|path| self.collation_applies(path))
+                .map(|path| path.to_lowercase())
+                .collect();
+        }
         let select_sql = self.convert_select_clause(&query.select_clause)?;
 
         // Handle WITH clause (security)
-        let security_mode = query.with_clause.map(|w| {
-            let mode = match w {
-                SoqlWithClause::SecurityEnforced => {
-                    self.warnings.push(ConversionWarning::SecurityClauseRemoved(
-                        "SECURITY_ENFORCED".to_string(),
-                    ));
-                    SecurityMode::SecurityEnforced
-                }
-                SoqlWithClause::UserMode => {
-                    self.warnings.push(ConversionWarning::SecurityClauseRemoved(
-                        "USER_MODE".to_string(),
-                    ));
-                    SecurityMode::UserMode
-                }
-                SoqlWithClause::SystemMode => {
-                    self.warnings.push(ConversionWarning::SecurityClauseRemoved(
-                        "SYSTEM_MODE".to_string(),
-                    ));
-                    SecurityMode::SystemMode
-                }
-            };
-            mode
+        let security_mode = query.with_clause.map(|w| match w {
+            SoqlWithClause::SecurityEnforced => {
+                self.warnings
+                    .push(ConversionWarning::SecurityClauseRemoved {
+                        clause: "SECURITY_ENFORCED".to_string(),
+                    });
+                SecurityMode::SecurityEnforced
+            }
+            SoqlWithClause::UserMode => {
+                self.warnings
+                    .push(ConversionWarning::SecurityClauseRemoved {
+                        clause: "USER_MODE".to_string(),
+                    });
+                SecurityMode::UserMode
+            }
+            SoqlWithClause::SystemMode => {
+                self.warnings
+                    .push(ConversionWarning::SecurityClauseRemoved {
+                        clause: "SYSTEM_MODE".to_string(),
+                    });
+                SecurityMode::SystemMode
+            }
         });
 
         // Build WHERE clause
         let where_sql = if let Some(ref where_expr) = query.where_clause {
-            Some(self.convert_expression(where_expr)?)
+            Some(self.convert_condition(where_expr)?)
         } else {
             None
         };
 
-        // Add soft-delete filter if configured
-        let where_sql = if self.config.filter_deleted {
-            let main_alias = self.get_table_alias(&query.from_clause);
-            let delete_filter = format!(
-                "{}.is_deleted = {}",
-                main_alias,
-                self.dialect.boolean_literal(false)
-            );
-            match where_sql {
-                Some(w) => Some(format!("({}) AND {}", w, delete_filter)),
-                None => Some(delete_filter),
-            }
-        } else {
-            where_sql
+        // Add row filters (soft-delete) for the root table
+        let main_alias = self.get_table_alias(&query.from_clause);
+        let where_sql = match (where_sql, self.row_filter(&query.from_clause, &main_alias)) {
+            (Some(w), Some(filter)) => Some(format!("({}) AND {}", w, filter)),
+            (None, Some(filter)) => Some(filter),
+            (w, None) => w,
         };
 
         // GROUP BY
         let group_by_sql = if !query.group_by_clause.is_empty() {
-            Some(self.convert_group_by(&query.group_by_clause)?)
+            Some(self.convert_group_by(&query.group_by_clause, query.grouping_sets)?)
         } else {
             None
         };
 
         // HAVING
         let having_sql = if let Some(ref having_expr) = query.having_clause {
-            Some(self.convert_expression(having_expr)?)
+            Some(self.convert_condition(having_expr)?)
         } else {
             None
         };
@@ -233,14 +663,14 @@ impl<'a> SoqlToSqlConverter<'a> {
 
         // LIMIT
         let limit_sql = if let Some(ref limit_expr) = query.limit_clause {
-            Some(self.convert_expression(limit_expr)?)
+            Some(self.convert_row_count(limit_expr)?)
         } else {
             None
         };
 
         // OFFSET
         let offset_sql = if let Some(ref offset_expr) = query.offset_clause {
-            Some(self.convert_expression(offset_expr)?)
+            Some(self.convert_row_count(offset_expr)?)
         } else {
             None
         };
@@ -283,53 +713,64 @@ impl<'a> SoqlToSqlConverter<'a> {
             sql.push_str(&format!("\n{}", f));
         }
 
+        if self.config.bind_mode == BindVariableMode::Parameterized
+            && self.dialect.positional_parameters()
+        {
+            sql = self.bind_positionally(&sql);
+        }
+
+        let warnings = self
+            .config
+            .warning_filter
+            .apply(std::mem::take(&mut self.warnings))?;
+
         Ok(SqlConversion {
             sql,
             parameters: std::mem::take(&mut self.parameters),
             column_map: std::mem::take(&mut self.column_map),
-            warnings: std::mem::take(&mut self.warnings),
+            warnings,
             security_mode,
+            dialect: self.config.dialect,
+            result_shape: ResultShape::of(query),
         })
     }
 
     /// Convert SELECT clause
     fn convert_select_clause(&mut self, fields: &[SelectField]) -> ConversionResult<String> {
+        check_select_combination(fields)?;
         let mut columns = Vec::new();
+        // Salesforce names unaliased aggregates expr0, expr1, ... in order
+        let mut unaliased = 0;
 
         for field in fields {
             match field {
-                SelectField::Field(path) => {
-                    let (sql, alias) = self.convert_field_path(path)?;
-                    if &alias != path {
-                        columns.push(format!(
-                            "{} AS {}",
-                            sql,
-                            self.dialect.quote_identifier(&alias)
-                        ));
-                    } else {
-                        columns.push(sql);
+                SelectField::Field(path) => self.convert_select_path(path, &mut columns)?,
+                SelectField::Fields(scope) => {
+                    let object = self.current_object.clone().unwrap();
+                    for path in self.fields_in_scope(&object, *scope)? {
+                        self.convert_select_path(&path, &mut columns)?;
                     }
-                    self.column_map.insert(path.clone(), alias);
                 }
                 SelectField::AggregateFunction { name, field, alias } => {
-                    // Handle COUNT() with no field or COUNT(*)
-                    let agg_sql =
-                        if name.to_uppercase() == "COUNT" && (field.is_empty() || field == "*") {
-                            "COUNT(*)".to_string()
-                        } else {
-                            let (field_sql, _) = self.convert_field_path(field)?;
-                            format!("{}({})", name.to_uppercase(), field_sql)
-                        };
-                    if let Some(a) = alias {
-                        columns.push(format!(
-                            "{} AS {}",
-                            agg_sql,
-                            self.dialect.quote_identifier(a)
-                        ));
-                        self.column_map.insert(a.clone(), a.clone());
-                    } else {
-                        columns.push(agg_sql);
-                    }
+                    let agg_sql = self.convert_aggregate(name, field)?;
+                    let alias = match alias {
+                        Some(a) => a.clone(),
+                        // A bare COUNT() returns a number, not columns
+                        None if field.is_empty() || field == "*" => {
+                            columns.push(agg_sql);
+                            continue;
+                        }
+                        None => {
+                            unaliased += 1;
+                            format!("expr{}", unaliased - 1)
+                        }
+                    };
+                    columns.push(format!(
+                        "{} AS {}",
+                        agg_sql,
+                        self.dialect.quote_identifier(&alias)
+                    ));
+                    self.column_map.insert(alias.clone(), alias);
                 }
                 SelectField::SubQuery(subquery) => {
                     let subquery_sql = self.convert_subquery(subquery)?;
@@ -345,15 +786,93 @@ impl<'a> SoqlToSqlConverter<'a> {
         Ok(columns.join(", "))
     }
 
-    /// Convert a field path (e.g., "Id", "Account.Name", "Account.Owner.Name")
-    fn convert_field_path(&mut self, path: &str) -> ConversionResult<(String, String)> {
+    /// Add the column for a selected field path
+    fn convert_select_path(
+        &mut self,
+        path: &str,
+        columns: &mut Vec<String>,
+    ) -> ConversionResult<()> {
+        let (sql, alias) = self.convert_field_path(path, None)?;
+        // A field read across polymorphic targets has no column name of its
+        // own, so it is named after its path
+        let named = alias != path || sql.starts_with("COALESCE(");
+        if self.collated_group_keys.contains(&path.to_lowercase()) {
+            // Keep the name the bare column would have had
+            let name = if named {
+                alias.as_str()
+            } else {
+                sql.rsplit('.').next().unwrap_or(&sql)
+            };
+            columns.push(format!(
+                "MIN({}) AS {}",
+                sql,
+                self.dialect.quote_identifier(name)
+            ));
+        } else if named {
+            columns.push(format!(
+                "{} AS {}",
+                sql,
+                self.dialect.quote_identifier(&alias)
+            ));
+        } else {
+            columns.push(sql);
+        }
+        self.column_map.insert(path.to_string(), alias);
+        Ok(())
+    }
+
+    /// The fields `FIELDS(scope)` selects from `object`: Id and Name first,
+    /// then the rest by API name
+    fn fields_in_scope(&self, object: &str, scope: FieldsScope) -> ConversionResult<Vec<String>> {
+        let schema = self
+            .schema
+            .ok_or_else(|| ConversionError::SchemaRequired("FIELDS()".to_string()))?;
+        let describe = schema
+            .get_object(object)
+            .ok_or_else(|| ConversionError::UnknownObject(object.to_string()))?;
+        let mut fields: Vec<String> = describe
+            .fields()
+            .filter(|field| scope.includes(&field.name))
+            .map(|field| field.name.clone())
+            .collect();
+        fields.sort_by_key(|name| (name != "Id", name != "Name", name.clone()));
+        Ok(fields)
+    }
+
+    /// Convert an aggregate call such as `SUM(Amount)`; `field` is empty
+    /// for `COUNT()`
+    fn convert_aggregate(&mut self, name: &str, field: &str) -> ConversionResult<String> {
+        // Handle COUNT() with no field or COUNT(*)
+        if name.eq_ignore_ascii_case("COUNT") && (field.is_empty() || field == "*") {
+            return Ok("COUNT(*)".to_string());
+        }
+        let (field_sql, _) = self.convert_field_path(field, None)?;
+        // GROUPING() takes the grouping key itself, collation wrapper and all
+        if name.eq_ignore_ascii_case("GROUPING") {
+            return Ok(format!(
+                "GROUPING({})",
+                self.collation_key(field_sql, field)
+            ));
+        }
+        Ok(format!("{}({})", name.to_uppercase(), field_sql))
+    }
+
+    /// Convert a field path (e.g., "Id", "Account.Name", "Account.Owner.Name");
+    /// `span` locates the path when the AST keeps one
+    fn convert_field_path(
+        &mut self,
+        path: &str,
+        span: Option<Span>,
+    ) -> ConversionResult<(String, String)> {
         let parts: Vec<&str> = path.split('.').collect();
 
         if parts.len() == 1 {
             // Simple field
-            let main_alias = self.get_table_alias(self.current_object.as_ref().unwrap());
-            let column = self.get_column_name(self.current_object.as_ref().unwrap(), parts[0])?;
-            return Ok((format!("{}.{}", main_alias, column), parts[0].to_string()));
+            let object = self.current_object.clone().unwrap();
+            let main_alias = self.get_table_alias(&object);
+            self.collect_unknown_field(&object, parts[0], span);
+            let column = self.get_column_name(&object, parts[0])?;
+            return Ok((self.column_ref(&main_alias, &column), parts[0].to_string()));
         }
 
         // Relationship traversal - need schema
@@ -368,16 +887,9 @@ impl<'a> SoqlToSqlConverter<'a> {
         let mut current_obj = self.current_object.clone().unwrap();
         let mut current_alias = self.get_table_alias(&current_obj);
 
-        for (i, part) in parts[..parts.len() - 1].iter().enumerate() {
-            // Find the relationship field
-            let (ref_object, join_field) = self.resolve_relationship(&current_obj, part)?;
-
-            // Check if we already have a join for this relationship
-            let join_alias = self.get_or_create_join(&current_alias, &ref_object, &join_field)?;
-
-            current_obj = ref_object;
-            current_alias = join_alias;
+        let final_field = parts.last().unwrap();
 
+        for (i, part) in parts[..parts.len() - 1].iter().enumerate() {
             // Check depth
             if i as u8 >= self.config.max_relationship_depth {
                 return Err(ConversionError::RelationshipDepthExceeded {
@@ -385,21 +897,82 @@ impl<'a> SoqlToSqlConverter<'a> {
                     actual: i as u8 + 1,
                 });
             }
+
+            if i + 2 == parts.len() {
+                if let Some(column) = self.denormalized_record_type(part, final_field) {
+                    return Ok((self.column_ref(&current_alias, &column), path.to_string()));
+                }
+            }
+
+            // Find the relationship field
+            let lookup = match self.resolve_relationship(&current_obj, part, span) {
+                Ok(lookup) => lookup,
+                Err(error) => {
+                    // Nothing past an unknown relationship can be checked
+                    let placeholder = format!("{}.{}", current_alias, to_snake_case(part));
+                    return self
+                        .collect(error, placeholder)
+                        .map(|sql| (sql, path.to_string()));
+                }
+            };
+
+            if let Some(ref type_column) = lookup.type_column {
+                // Polymorphic parents can only be read, not traversed further,
+                // since each target has its own relationships
+                if i + 2 != parts.len() {
+                    return Err(ConversionError::UnsupportedSoqlFeature(format!(
+                        "traversal through polymorphic relationship '{}' in '{}' (use TYPEOF)",
+                        part, path
+                    )));
+                }
+                let sql = self.convert_polymorphic_field(
+                    &current_alias,
+                    part,
+                    &lookup,
+                    type_column,
+                    final_field,
+                )?;
+                return Ok((sql, path.to_string()));
+            }
+
+            // Check if we already have a join for this relationship
+            let ref_object = lookup.targets[0].clone();
+            let join_alias =
+                self.get_or_create_join(&current_alias, &ref_object, &lookup.column, None)?;
+
+            current_obj = ref_object;
+            current_alias = join_alias;
         }
 
         // Get the final field
-        let final_field = parts.last().unwrap();
+        self.collect_unknown_field(&current_obj, final_field, span);
         let column = self.get_column_name(&current_obj, final_field)?;
 
-        Ok((format!("{}.{}", current_alias, column), path.to_string()))
+        Ok((self.column_ref(&current_alias, &column), path.to_string()))
     }
 
-    /// Resolve a relationship name to the referenced object and join field
+    /// The local column standing in for `RecordType.<field>` under
+    /// [`RecordTypeStrategy::DenormalizedColumn`]
+    fn denormalized_record_type(&self, relationship: &str, field: &str) -> Option<String> {
+        match &self.config.record_type_strategy {
+            RecordTypeStrategy::DenormalizedColumn { column }
+                if relationship.eq_ignore_ascii_case("RecordType")
+                    && (field.eq_ignore_ascii_case("DeveloperName")
+                        || field.eq_ignore_ascii_case("Name")) =>
+            {
+                Some(column.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolve a relationship name to the referenced object(s) and join field
     fn resolve_relationship(
         &self,
         from_object: &str,
         relationship_name: &str,
-    ) -> ConversionResult<(String, String)> {
+        span: Option<Span>,
+    ) -> ConversionResult<ParentLookup> {
         let schema = self.schema.ok_or_else(|| {
             ConversionError::SchemaRequired(format!("relationship: {}", relationship_name))
         })?;
@@ -414,27 +987,101 @@ impl<'a> SoqlToSqlConverter<'a> {
                 if rel_name.eq_ignore_ascii_case(relationship_name) {
                     if let Some(ref refs) = field.reference_to {
                         if !refs.is_empty() {
-                            return Ok((refs[0].clone(), field.column_name.clone()));
+                            return Ok(ParentLookup {
+                                targets: refs.clone(),
+                                column: field.column_name.clone(),
+                                type_column: field.type_column_name(),
+                            });
                         }
                     }
                 }
             }
         }
 
-        Err(ConversionError::NotARelationship(
-            relationship_name.to_string(),
-        ))
+        Err(ConversionError::NotARelationship {
+            relationship: relationship_name.to_string(),
+            span: span.or(self.query_span),
+        })
+    }
+
+    /// Read a field through a polymorphic relationship without TYPEOF
+    ///
+    /// Every target declaring the field gets its own LEFT JOIN keyed by the
+    /// discriminator column, and the per-target columns are merged with
+    /// COALESCE (at most one of them is non-NULL for any row).
+    fn convert_polymorphic_field(
+        &mut self,
+        from_alias: &str,
+        relationship_name: &str,
+        lookup: &ParentLookup,
+        type_column: &str,
+        field_name: &str,
+    ) -> ConversionResult<String> {
+        let schema = self
+            .schema
+            .ok_or_else(|| ConversionError::SchemaRequired("polymorphic join".to_string()))?;
+
+        let mut columns = Vec::new();
+        let mut missing = Vec::new();
+        for target in &lookup.targets {
+            let Some(field) = schema
+                .get_object(target)
+                .and_then(|obj| obj.get_field(field_name))
+            else {
+                missing.push(target.clone());
+                continue;
+            };
+            let alias =
+                self.get_or_create_join(from_alias, target, &lookup.column, Some(type_column))?;
+            columns.push(self.column_ref(&alias, &field.column_name));
+        }
+
+        if columns.is_empty() {
+            return Err(ConversionError::PolymorphicFieldNotFound {
+                relationship: relationship_name.to_string(),
+                field: field_name.to_string(),
+                targets: lookup.targets.clone(),
+            });
+        }
+        if !missing.is_empty() {
+            let warning = ConversionWarning::PolymorphicFieldMissingOnTargets {
+                relationship: relationship_name.to_string(),
+                field: field_name.to_string(),
+                targets: missing,
+            };
+            // The same path may appear in SELECT, WHERE and ORDER BY
+            if !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
+            }
+        }
+
+        if columns.len() == 1 {
+            Ok(columns.remove(0))
+        } else {
+            Ok(format!("COALESCE({})", columns.join(", ")))
+        }
     }
 
     /// Get or create a JOIN for a relationship
+    ///
+    /// Joins are always LEFT JOINs, even for relationships referenced only
+    /// in WHERE. A comparison against a missing parent evaluates to NULL, so
+    /// `Account.Industry = 'Tech'` still drops orphans, while each side of
+    /// `Account.Industry = 'Tech' OR Owner.Name = 'Alice'` keeps rows whose
+    /// other parent is absent (an INNER JOIN would silently drop them).
+    /// Joins are keyed by source alias and FK column, so SELECT, WHERE and
+    /// ORDER BY references to the same relationship share one join.
+    ///
     /// from_alias: the alias of the current table (e.g., "t0" for contact)
     /// to_object: the object being joined to (e.g., "Account")
     /// join_field: the FK field on the from table (e.g., "account_id")
+    /// type_column: discriminator on the from table for polymorphic lookups
     fn get_or_create_join(
         &mut self,
         from_alias: &str,
         to_object: &str,
         join_field: &str,
+        type_column: Option<&str>,
     ) -> ConversionResult<String> {
         let schema = self
             .schema
@@ -445,24 +1092,44 @@ impl<'a> SoqlToSqlConverter<'a> {
             .ok_or_else(|| ConversionError::UnknownObject(to_object.to_string()))?;
 
         // Check if we already have this join
-        let join_key = format!("{}.{}", from_alias, join_field);
-        if let Some(alias) = self.table_aliases.get(&join_key) {
+        // Polymorphic lookups join once per target object
+        let join_key = JoinKey {
+            from_alias: from_alias.to_string(),
+            join_field: join_field.to_string(),
+            target: type_column.map(|_| to_object.to_lowercase()),
+        };
+        if let Some(alias) = self.aliases.joins.get(&join_key) {
             return Ok(alias.clone());
         }
 
         // Create new join
         let alias = self.next_alias();
-        let table = self.dialect.quote_identifier(&to_obj.table_name);
+        let table = self.table_ref(to_object, &to_obj.table_name);
 
         // JOIN condition: from_table.fk_field = to_table.id
+        // Row filters go into the ON clause so a filtered-out parent yields
+        // NULL parent fields instead of dropping the child row.
+        let mut condition = self.id_equality(
+            &format!("{}.{}", from_alias, join_field),
+            &format!("{}.id", alias),
+        );
+        if let Some(type_column) = type_column {
+            condition.push_str(&format!(
+                " AND {}.{} = '{}'",
+                from_alias, type_column, to_object
+            ));
+        }
+        if let Some(filter) = self.row_filter(to_object, &alias) {
+            condition.push_str(&format!(" AND {}", filter));
+        }
         self.joins.push(JoinClause {
             join_type: "LEFT JOIN",
             table,
             alias: alias.clone(),
-            condition: format!("{}.{} = {}.id", from_alias, join_field, alias),
+            condition,
         });
 
-        self.table_aliases.insert(join_key, alias.clone());
+        self.aliases.joins.insert(join_key, alias.clone());
         Ok(alias)
     }
 
@@ -480,25 +1147,125 @@ impl<'a> SoqlToSqlConverter<'a> {
         };
 
         let alias = self.next_alias();
-        self.table_aliases
+        self.aliases
+            .roots
             .insert(object_name.to_lowercase(), alias.clone());
 
         Ok(format!(
             "{} {}",
-            self.dialect.quote_identifier(&table_name),
+            self.table_ref(object_name, &table_name),
             alias
         ))
     }
 
+    /// Apply the restrictions of the queried object's kind, which comes from
+    /// the schema or, without one, from the API name suffix
+    fn check_capabilities(&mut self, query: &SoqlQuery) -> ConversionResult<()> {
+        let object = &query.from_clause;
+        let violations = match self.schema.and_then(|s| s.get_object(object)) {
+            Some(describe) => {
+                capability_violations(describe.object_kind, &describe.index_fields, query)
+            }
+            None => capability_violations(ObjectKind::from_api_name(object), &[], query),
+        };
+
+        for reason in violations {
+            match self.config.capability_mode {
+                CapabilityMode::Error => {
+                    return Err(ConversionError::UnsupportedForObject {
+                        object: object.clone(),
+                        reason,
+                    })
+                }
+                CapabilityMode::Warning => {
+                    self.warnings.push(ConversionWarning::UnsupportedForObject {
+                        object: object.clone(),
+                        reason,
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Record `error` and carry on with `placeholder` while collecting,
+    /// otherwise fail with it
+    fn collect<T>(&mut self, error: ConversionError, placeholder: T) -> ConversionResult<T> {
+        match self.collected_errors.as_mut() {
+            Some(errors) => {
+                if !errors.iter().any(|e| e.same_as(&error)) {
+                    errors.push(error);
+                }
+                Ok(placeholder)
+            }
+            None => Err(error),
+        }
+    }
+
+    /// While collecting, record a field the schema object lacks
+    fn collect_unknown_field(&mut self, object: &str, field: &str, span: Option<Span>) {
+        if self.collected_errors.is_none() {
+            return;
+        }
+        let Some(describe) = self.schema.and_then(|s| s.get_object(object)) else {
+            return;
+        };
+        if !describe.has_field(field) {
+            let error = ConversionError::UnknownField {
+                object: describe.name.clone(),
+                field: field.to_string(),
+                span: span.or(self.query_span),
+            };
+            let _ = self.collect(error, ());
+        }
+    }
+
+    fn check_cancelled(&self) -> ConversionResult<()> {
+        match &self.cancellation {
+            Some(token) if token.is_cancelled() => Err(ConversionError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// Quoted table reference for an object, qualified with its schema
+    fn table_ref(&mut self, object: &str, table: &str) -> String {
+        let qualifier = resolve_qualifier(
+            self.config.schema_qualifier.as_deref(),
+            &self.config.schema_overrides,
+            object,
+        )
+        .map(str::to_string);
+        let qualifier = match qualifier {
+            Some(schema)
+                if self.config.dialect == SqlDialect::Sqlite
+                    && self.config.sqlite_qualifier == SqliteQualifier::Ignore =>
+            {
+                let warning = ConversionWarning::SchemaQualifierIgnored { schema };
+                if !self.warnings.contains(&warning) {
+                    self.warnings.push(warning);
+                }
+                None
+            }
+            qualifier => qualifier,
+        };
+        self.dialect.qualified_table(qualifier.as_deref(), table)
+    }
+
     /// Convert an expression
     fn convert_expression(&mut self, expr: &Expression) -> ConversionResult<String> {
         match expr {
-            Expression::Null(_) => Ok("NULL".to_string()),
-            Expression::Boolean(b, _) => Ok(self.dialect.boolean_literal(*b).to_string()),
-            Expression::Integer(i, _) => Ok(i.to_string()),
-            Expression::Long(l, _) => Ok(l.to_string()),
-            Expression::Double(d, _) => Ok(d.to_string()),
-            Expression::String(s, _) => {
+            Expression::Null(..) => Ok("NULL".to_string()),
+            Expression::Boolean(b, _, _) => Ok(self.dialect.boolean_literal(*b).to_string()),
+            // SQL has no hex/octal/binary literals, so only plain decimal
+            // text is reused
+            Expression::Integer(_, raw, _, _) if raw.bytes().all(|b| b.is_ascii_digit()) => {
+                Ok(raw.clone())
+            }
+            Expression::Integer(i, _, _, _) => Ok(i.to_string()),
+            Expression::Long(l, _, _, _) => Ok(l.to_string()),
+            Expression::Double(_, raw, _, _) if is_plain_decimal(raw) => Ok(raw.clone()),
+            Expression::Double(d, _, _, _) => Ok(format_decimal(*d)),
+            Expression::String(s, _, _) => {
                 // Check if this is a date literal
                 if is_date_literal(s) {
                     // This is used in WHERE context, we need a field expression
@@ -507,21 +1274,24 @@ impl<'a> SoqlToSqlConverter<'a> {
                     // will call expand_date_literal
                     Ok(format!("DATE_LITERAL:{}", s))
                 } else {
-                    // Regular string - escape single quotes
-                    Ok(format!("'{}'", s.replace('\'', "''")))
+                    // Regular string, escaped for the dialect
+                    Ok(self.dialect.string_literal(s))
                 }
             }
-            Expression::Identifier(name, _) => {
+            Expression::Identifier(name, span, _) => {
                 // Check if it's a date literal
                 if is_date_literal(name) {
                     Ok(format!("DATE_LITERAL:{}", name))
                 } else {
                     // It's a field reference
-                    let (sql, _) = self.convert_field_path(name)?;
+                    let (sql, _) = self.convert_field_path(name, Some(*span))?;
                     Ok(sql)
                 }
             }
-            Expression::BindVariable(name, _) => self.add_parameter(name),
+            Expression::BindVariable(name, _, _) => self.add_parameter(name),
+            Expression::BindExpression(bound, _, _) => {
+                self.add_parameter(&printer::expression(bound))
+            }
             Expression::Binary(binary) => {
                 self.convert_binary_expression(&binary.left, binary.operator, &binary.right)
             }
@@ -533,11 +1303,12 @@ impl<'a> SoqlToSqlConverter<'a> {
                     crate::ast::UnaryOp::BitwiseNot => Ok(format!("~({})", operand)),
                 }
             }
-            Expression::Parenthesized(inner, _) => {
+            Expression::Parenthesized(inner, _, _) => {
                 let inner_sql = self.convert_expression(inner)?;
                 Ok(format!("({})", inner_sql))
             }
-            Expression::ListLiteral(items, _) | Expression::SetLiteral(items, _) => {
+            Expression::SoqlSubquery(query) => self.convert_semi_join(query, false),
+            Expression::ListLiteral(items, ..) | Expression::SetLiteral(items, ..) => {
                 let converted: Result<Vec<_>, _> =
                     items.iter().map(|e| self.convert_expression(e)).collect();
                 Ok(format!("({})", converted?.join(", ")))
@@ -555,6 +1326,15 @@ impl<'a> SoqlToSqlConverter<'a> {
                     Ok("()".to_string())
                 }
             }
+            // Aggregates in HAVING, parsed as calls with a field path argument
+            Expression::MethodCall(call) if call.object.is_none() => match &call.arguments[..] {
+                [] => self.convert_aggregate(&call.name, ""),
+                [Expression::Identifier(field, _, _)] => self.convert_aggregate(&call.name, field),
+                _ => Err(ConversionError::InvalidExpression(format!(
+                    "Unsupported arguments to {} in SOQL",
+                    call.name
+                ))),
+            },
             Expression::FieldAccess(fa) => {
                 // Convert to dotted path
                 let obj = self.convert_expression(&fa.object)?;
@@ -567,6 +1347,111 @@ impl<'a> SoqlToSqlConverter<'a> {
         }
     }
 
+    /// Convert a WHERE or HAVING predicate
+    ///
+    /// A bare field where a condition is expected is compared to true, since
+    /// not every dialect accepts a column reference as a predicate.
+    fn convert_condition(&mut self, expr: &Expression) -> ConversionResult<String> {
+        match expr {
+            Expression::Identifier(name, span, _) if !is_date_literal(name) => {
+                let (field, _) = self.convert_field_path(name, Some(*span))?;
+                Ok(format!(
+                    "{} = {}",
+                    field,
+                    self.dialect.boolean_literal(true)
+                ))
+            }
+            Expression::Binary(binary)
+                if matches!(binary.operator, BinaryOp::And | BinaryOp::Or) =>
+            {
+                // Right first, matching convert_binary_expression's parameter order
+                let right = self.convert_condition(&binary.right)?;
+                let left = self.convert_condition(&binary.left)?;
+                let op = if binary.operator == BinaryOp::And {
+                    "AND"
+                } else {
+                    "OR"
+                };
+                Ok(format!("{} {} {}", left, op, right))
+            }
+            Expression::Unary(unary) if unary.operator == crate::ast::UnaryOp::Not => {
+                Ok(format!("NOT ({})", self.convert_condition(&unary.operand)?))
+            }
+            Expression::Parenthesized(inner, _, _) => {
+                Ok(format!("({})", self.convert_condition(inner)?))
+            }
+            _ => self.convert_expression(expr),
+        }
+    }
+
+    /// Operands of a comparison with an Id-typed field, per
+    /// [`ConversionConfig::id_comparison`]
+    fn id_operands(
+        &self,
+        field: String,
+        value: &Expression,
+        value_sql: String,
+        collection: bool,
+    ) -> (String, String) {
+        let literals = match value {
+            Expression::String(id, _, _) => Some(vec![id.as_str()]),
+            Expression::ListLiteral(items, ..) | Expression::SetLiteral(items, ..) => {
+                string_items(items)
+            }
+            Expression::NewArray(new_array) => {
+                new_array.initializer.as_deref().and_then(string_items)
+            }
+            _ => None,
+        };
+        let render = |ids: Vec<String>| {
+            if collection {
+                format!("({})", ids.join(", "))
+            } else {
+                ids.join(", ")
+            }
+        };
+
+        match self.config.id_comparison {
+            IdComparison::Exact => (field, value_sql),
+            IdComparison::NormalizeTo18 => match literals {
+                Some(ids) => {
+                    let ids = ids
+                        .into_iter()
+                        .map(|id| {
+                            let normalized = normalize_id(id);
+                            self.dialect
+                                .string_literal(normalized.as_deref().unwrap_or(id))
+                        })
+                        .collect();
+                    (field, render(ids))
+                }
+                None => (field, value_sql),
+            },
+            IdComparison::CaseInsensitive => {
+                let value_sql = match literals {
+                    Some(ids) => render(
+                        ids.into_iter()
+                            .map(|id| format!("UPPER({})", self.dialect.string_literal(id)))
+                            .collect(),
+                    ),
+                    // A bound collection can't be wrapped; the caller
+                    // upper-cases its values (SqlParameter::id_comparison)
+                    None if collection => value_sql,
+                    None => format!("UPPER({})", value_sql),
+                };
+                (format!("UPPER({})", field), value_sql)
+            }
+        }
+    }
+
+    /// `left = right` for two Id columns, per [`ConversionConfig::id_comparison`]
+    fn id_equality(&self, left: &str, right: &str) -> String {
+        match self.config.id_comparison {
+            IdComparison::CaseInsensitive => format!("UPPER({}) = UPPER({})", left, right),
+            IdComparison::Exact | IdComparison::NormalizeTo18 => format!("{} = {}", left, right),
+        }
+    }
+
     /// Convert a binary expression
     fn convert_binary_expression(
         &mut self,
@@ -575,16 +1460,74 @@ impl<'a> SoqlToSqlConverter<'a> {
         right: &Expression,
     ) -> ConversionResult<String> {
         // Check for date literal on the right side
-        let right_str = self.convert_expression(right)?;
+        let first_param = self.parameters.len();
+        let right_str = match (op, right) {
+            (BinaryOp::NotIn, Expression::SoqlSubquery(query)) => {
+                self.convert_semi_join(query, true)?
+            }
+            _ => self.convert_expression(right)?,
+        };
+        if let (
+            Expression::Identifier(path, _, _),
+            Expression::BindVariable(..) | Expression::BindExpression(..),
+        ) = (left, right)
+        {
+            let value_type = self.field_type(path);
+            let collection = matches!(
+                op,
+                BinaryOp::In | BinaryOp::NotIn | BinaryOp::Includes | BinaryOp::Excludes
+            );
+            self.describe_parameter(first_param, value_type, collection);
+        }
 
         if let Some(date_literal) = right_str.strip_prefix("DATE_LITERAL:") {
             // This is a date literal comparison
             let left_str = self.convert_expression(left)?;
-            return expand_date_literal(date_literal, &left_str, self.dialect.as_ref());
+            return expand_date_literal_at(
+                date_literal,
+                &left_str,
+                self.dialect.as_ref(),
+                &self.config.clock,
+            );
         }
 
         let left_str = self.convert_expression(left)?;
 
+        // SOQL `= null` matches missing values; SQL `= NULL` never matches
+        if let Expression::Null(..) = right {
+            match op {
+                BinaryOp::Equal | BinaryOp::ExactEqual => {
+                    return Ok(format!("{} IS NULL", left_str));
+                }
+                BinaryOp::NotEqual | BinaryOp::ExactNotEqual => {
+                    return Ok(format!("{} IS NOT NULL", left_str));
+                }
+                _ => {}
+            }
+        }
+
+        let (left_str, right_str) = match left {
+            Expression::Identifier(path, _, _)
+                if matches!(
+                    op,
+                    BinaryOp::Equal
+                        | BinaryOp::NotEqual
+                        | BinaryOp::ExactEqual
+                        | BinaryOp::ExactNotEqual
+                        | BinaryOp::In
+                        | BinaryOp::NotIn
+                ) && self.field_type(path).is_some_and(|t| t.is_id()) =>
+            {
+                self.id_operands(
+                    left_str,
+                    right,
+                    right_str,
+                    matches!(op, BinaryOp::In | BinaryOp::NotIn),
+                )
+            }
+            _ => (left_str, right_str),
+        };
+
         let sql_op = match op {
             BinaryOp::Equal => "=",
             BinaryOp::NotEqual => "!=",
@@ -596,7 +1539,20 @@ impl<'a> SoqlToSqlConverter<'a> {
             BinaryOp::GreaterOrEqual => ">=",
             BinaryOp::And => "AND",
             BinaryOp::Or => "OR",
-            BinaryOp::Like => "LIKE",
+            BinaryOp::Like => {
+                // Salesforce matches a literal %, _ or \ written as \%, \_
+                // or \\; SQL only honors those escapes with an ESCAPE clause
+                if let Expression::String(pattern, _, _) = right {
+                    let escaped = pattern.contains('\\');
+                    if let Some(escape) = self.dialect.like_escape().filter(|_| escaped) {
+                        return Ok(format!(
+                            "{} LIKE {} ESCAPE '{}'",
+                            left_str, right_str, escape
+                        ));
+                    }
+                }
+                "LIKE"
+            }
             BinaryOp::In => "IN",
             BinaryOp::NotIn => "NOT IN",
             BinaryOp::Add => "+",
@@ -647,15 +1603,9 @@ impl<'a> SoqlToSqlConverter<'a> {
             .map(|v| {
                 // Check if the value is at start, middle, or end of the semicolon-separated list
                 format!(
-                    "({} = '{}' OR {} LIKE '{}' OR {} LIKE '{}' OR {} LIKE '{}')",
-                    field,
-                    v,
-                    field,
-                    format!("{};%", v),
-                    field,
-                    format!("%;{}", v),
-                    field,
-                    format!("%;{};%", v)
+                    "({f} = '{v}' OR {f} LIKE '{v};%' OR {f} LIKE '%;{v}' OR {f} LIKE '%;{v};%')",
+                    f = field,
+                    v = v
                 )
             })
             .collect();
@@ -670,12 +1620,29 @@ impl<'a> SoqlToSqlConverter<'a> {
     }
 
     /// Convert GROUP BY clause
-    fn convert_group_by(&mut self, fields: &[String]) -> ConversionResult<String> {
+    fn convert_group_by(
+        &mut self,
+        fields: &[String],
+        grouping_sets: Option<GroupingSets>,
+    ) -> ConversionResult<String> {
         let converted: Result<Vec<_>, _> = fields
             .iter()
-            .map(|f| self.convert_field_path(f).map(|(sql, _)| sql))
+            .map(|f| {
+                let (sql, _) = self.convert_field_path(f, None)?;
+                Ok(self.collation_key(sql, f))
+            })
             .collect();
-        Ok(converted?.join(", "))
+        let keys = converted?.join(", ");
+        match grouping_sets {
+            Some(sets) => self.dialect.grouping_sets(sets, &keys).ok_or_else(|| {
+                ConversionError::UnsupportedSoqlFeature(format!(
+                    "GROUP BY {} on {:?}",
+                    sets.as_str(),
+                    self.config.dialect
+                ))
+            }),
+            None => Ok(keys),
+        }
     }
 
     /// Convert ORDER BY clause
@@ -683,25 +1650,61 @@ impl<'a> SoqlToSqlConverter<'a> {
         let converted: Result<Vec<_>, _> = fields
             .iter()
             .map(|f| {
-                let (field_sql, _) = self.convert_field_path(&f.field)?;
-                let mut sql = field_sql;
-                if !f.ascending {
-                    sql.push_str(" DESC");
-                }
-                if let Some(nulls_first) = f.nulls_first {
-                    sql.push(' ');
-                    sql.push_str(if nulls_first {
-                        self.dialect.nulls_first()
-                    } else {
-                        self.dialect.nulls_last()
-                    });
-                }
-                Ok(sql)
+                let (field_sql, _) = self.convert_field_path(&f.field, None)?;
+                let key = self.collation_key(field_sql, &f.field);
+                Ok(match f.nulls_first {
+                    Some(nulls_first) => self.dialect.order_nulls(&key, !f.ascending, nulls_first),
+                    None if f.ascending => key,
+                    None => format!("{} DESC", key),
+                })
             })
             .collect();
         Ok(converted?.join(", "))
     }
 
+    /// Apply [`ConversionConfig::string_collation`] to an ORDER BY or
+    /// GROUP BY key
+    fn collation_key(&self, sql: String, path: &str) -> String {
+        if !self.collation_applies(path) {
+            return sql;
+        }
+        match &self.config.string_collation {
+            CollationMode::DatabaseDefault => sql,
+            CollationMode::CaseInsensitive => self.dialect.case_insensitive_key(&sql),
+            CollationMode::Explicit(name) => self.dialect.collate(&sql, name),
+        }
+    }
+
+    fn collation_applies(&self, path: &str) -> bool {
+        self.config.string_collation != CollationMode::DatabaseDefault
+            && self.field_type(path).is_some_and(|t| t.is_text())
+    }
+
+    /// Schema type of a field path, following parent relationships
+    ///
+    /// For polymorphic parents the first target declaring the field wins.
+    fn field_type(&self, path: &str) -> Option<SalesforceFieldType> {
+        let schema = self.schema?;
+        let (relationships, field) = match path.rsplit_once('.') {
+            Some((relationships, field)) => (Some(relationships), field),
+            None => (None, path),
+        };
+
+        let mut objects = vec![self.current_object.clone()?];
+        for part in relationships.into_iter().flat_map(|r| r.split('.')) {
+            let lookup = objects
+                .iter()
+                .find_map(|obj| self.resolve_relationship(obj, part, None).ok())?;
+            objects = lookup.targets;
+        }
+        objects.iter().find_map(|obj| {
+            schema
+                .get_object(obj)
+                .and_then(|o| o.get_field(field))
+                .map(|f| f.field_type)
+        })
+    }
+
     /// Convert FOR clause
     fn convert_for_clause(
         &mut self,
@@ -718,15 +1721,15 @@ impl<'a> SoqlToSqlConverter<'a> {
                 }
             }
             Some(ForClause::View) => {
-                self.warnings.push(ConversionWarning::SalesforceOnlyClause(
-                    "FOR VIEW".to_string(),
-                ));
+                self.warnings.push(ConversionWarning::SalesforceOnlyClause {
+                    clause: "FOR VIEW".to_string(),
+                });
                 Ok(None)
             }
             Some(ForClause::Reference) => {
-                self.warnings.push(ConversionWarning::SalesforceOnlyClause(
-                    "FOR REFERENCE".to_string(),
-                ));
+                self.warnings.push(ConversionWarning::SalesforceOnlyClause {
+                    clause: "FOR REFERENCE".to_string(),
+                });
                 Ok(None)
             }
         }
@@ -734,6 +1737,7 @@ impl<'a> SoqlToSqlConverter<'a> {
 
     /// Convert a child relationship subquery
     fn convert_subquery(&mut self, subquery: &SoqlQuery) -> ConversionResult<String> {
+        self.check_cancelled()?;
         let schema = self
             .schema
             .ok_or_else(|| ConversionError::SchemaRequired("subquery".to_string()))?;
@@ -746,14 +1750,13 @@ impl<'a> SoqlToSqlConverter<'a> {
             .get_object(parent_obj)
             .ok_or_else(|| ConversionError::UnknownObject(parent_obj.to_string()))?;
 
-        let child_rel = obj
-            .get_child_relationship(&subquery.from_clause)
-            .ok_or_else(|| {
-                ConversionError::UnknownChildRelationship(
-                    subquery.from_clause.clone(),
-                    parent_obj.clone(),
-                )
-            })?;
+        let Some(child_rel) = obj.get_child_relationship(&subquery.from_clause) else {
+            let error = ConversionError::UnknownChildRelationship(
+                subquery.from_clause.clone(),
+                parent_obj.clone(),
+            );
+            return self.collect(error, "NULL".to_string());
+        };
 
         let child_object = &child_rel.child_object;
         let child_field = &child_rel.field;
@@ -763,74 +1766,81 @@ impl<'a> SoqlToSqlConverter<'a> {
             .get_object(child_object)
             .ok_or_else(|| ConversionError::UnknownObject(child_object.to_string()))?;
 
-        let child_table = &child_obj.table_name;
+        let child_table = self.table_ref(child_object, &child_obj.table_name);
         let child_alias = self.next_alias();
 
         // Build subquery SELECT fields as JSON object
-        let field_pairs: Vec<(String, String)> = subquery
-            .select_clause
-            .iter()
-            .filter_map(|sf| match sf {
-                SelectField::Field(f) => {
-                    let col = self.get_column_name(child_object, f).ok()?;
-                    Some((f.clone(), format!("{}.{}", child_alias, col)))
+        let mut paths = Vec::new();
+        for sf in &subquery.select_clause {
+            match sf {
+                SelectField::Field(f) => paths.push(f.clone()),
+                SelectField::Fields(scope) => {
+                    paths.extend(self.fields_in_scope(child_object, *scope)?)
                 }
-                _ => None, // Skip complex fields in subquery for now
+                _ => {} // Skip complex fields in subquery for now
+            }
+        }
+        let field_pairs: Vec<(String, String)> = paths
+            .into_iter()
+            .filter_map(|f| {
+                let col = self.get_column_name(child_object, &f).ok()?;
+                Some((f, format!("{}.{}", child_alias, col)))
             })
             .collect();
 
         let json_obj = self.dialect.json_object(&field_pairs);
         let json_agg = self.dialect.json_array_agg(&json_obj);
 
-        // Build correlated subquery
-        let mut subquery_sql = format!(
-            "(SELECT {} FROM {} {} WHERE {}.{} = {}.id",
-            json_agg,
-            self.dialect.quote_identifier(child_table),
-            child_alias,
-            child_alias,
-            to_snake_case(child_field),
-            parent_alias
-        );
+        // WHERE and ORDER BY resolve fields against the child object, and
+        // the parent lookups they reach are joined inside the subquery
+        let old_obj = self.current_object.replace(child_object.clone());
+        let old_aliases = std::mem::take(&mut self.aliases);
+        let old_joins = std::mem::take(&mut self.joins);
+        self.aliases
+            .roots
+            .insert(child_object.to_lowercase(), child_alias.clone());
+
+        let where_sql = match subquery.where_clause {
+            Some(ref where_expr) => Some(self.convert_condition(where_expr)?),
+            None => None,
+        };
+        let order_sql = if subquery.order_by_clause.is_empty() {
+            None
+        } else {
+            Some(self.convert_order_by(&subquery.order_by_clause)?)
+        };
 
-        // Add WHERE clause if present
-        if let Some(ref where_expr) = subquery.where_clause {
-            // Save and swap context
-            let old_obj = self.current_object.take();
-            let old_aliases = std::mem::take(&mut self.table_aliases);
+        self.current_object = old_obj;
+        self.aliases = old_aliases;
+        let joins = std::mem::replace(&mut self.joins, old_joins);
 
-            self.current_object = Some(child_object.clone());
-            self.table_aliases
-                .insert(child_object.to_lowercase(), child_alias.clone());
+        // Build correlated subquery
+        let mut subquery_sql = format!("(SELECT {} FROM {} {}", json_agg, child_table, child_alias);
+        for join in &joins {
+            subquery_sql.push_str(&format!(
+                " {} {} {} ON {}",
+                join.join_type, join.table, join.alias, join.condition
+            ));
+        }
+        let correlation = self.id_equality(
+            &format!("{}.{}", child_alias, to_snake_case(child_field)),
+            &format!("{}.id", parent_alias),
+        );
+        subquery_sql.push_str(&format!(" WHERE {}", correlation));
 
-            let where_sql = self.convert_expression(where_expr)?;
+        if let Some(filter) = self.row_filter(child_object, &child_alias) {
+            subquery_sql.push_str(&format!(" AND {}", filter));
+        }
+        if let Some(where_sql) = where_sql {
             subquery_sql.push_str(&format!(" AND {}", where_sql));
-
-            // Restore context
-            self.current_object = old_obj;
-            self.table_aliases = old_aliases;
         }
-
-        // Add ORDER BY if present
-        if !subquery.order_by_clause.is_empty() {
-            // Save context
-            let old_obj = self.current_object.take();
-            let old_aliases = std::mem::take(&mut self.table_aliases);
-
-            self.current_object = Some(child_object.clone());
-            self.table_aliases
-                .insert(child_object.to_lowercase(), child_alias.clone());
-
-            let order_sql = self.convert_order_by(&subquery.order_by_clause)?;
+        if let Some(order_sql) = order_sql {
             subquery_sql.push_str(&format!(" ORDER BY {}", order_sql));
-
-            self.current_object = old_obj;
-            self.table_aliases = old_aliases;
         }
 
         // Add LIMIT if present
         if let Some(ref limit_expr) = subquery.limit_clause {
-            let limit_sql = self.convert_expression(limit_expr)?;
+            let limit_sql = self.convert_row_count(limit_expr)?;
             subquery_sql.push_str(&format!(" LIMIT {}", limit_sql));
         }
 
@@ -843,6 +1853,77 @@ impl<'a> SoqlToSqlConverter<'a> {
         Ok(subquery_sql)
     }
 
+    /// Convert the subquery of a semi-join or anti-join,
+    /// `Id IN (SELECT ContactId FROM CampaignMember)`, to a parenthesized
+    /// SELECT of one column. Like a child subquery it has its own aliases,
+    /// and the parent lookups it reaches are joined inside it. Those aliases
+    /// (`s0`, `s1`, ...) leave the outer query's numbering as if the
+    /// semi-join weren't there, since it can't refer to outer tables.
+    ///
+    /// An anti-join (`NOT IN`) skips NULLs in the selected column: SQL's
+    /// `NOT IN` matches nothing once the list holds a NULL, while SOQL
+    /// ignores records without a value. No guard is added when the
+    /// subquery's WHERE already requires `field != null`.
+    fn convert_semi_join(&mut self, query: &SoqlQuery, anti: bool) -> ConversionResult<String> {
+        self.check_cancelled()?;
+        let [SelectField::Field(field)] = &query.select_clause[..] else {
+            return Err(ConversionError::UnsupportedSoqlFeature(
+                "semi-join subqueries must select exactly one field".to_string(),
+            ));
+        };
+
+        let old_obj = self.current_object.replace(query.from_clause.clone());
+        let old_aliases = std::mem::take(&mut self.aliases);
+        let old_joins = std::mem::take(&mut self.joins);
+        let old_counter = std::mem::replace(&mut self.alias_counter, 0);
+        let old_prefix = std::mem::replace(&mut self.alias_prefix, "s");
+
+        let from_sql = self.convert_from_clause(&query.from_clause)?;
+        let alias = self.get_table_alias(&query.from_clause);
+        let (column, _) = self.convert_field_path(field, None)?;
+        let selected = if self.config.id_comparison == IdComparison::CaseInsensitive
+            && self.field_type(field).is_some_and(|t| t.is_id())
+        {
+            format!("UPPER({})", column)
+        } else {
+            column.clone()
+        };
+        let mut conditions = Vec::new();
+        if let Some(ref where_expr) = query.where_clause {
+            conditions.push(self.convert_condition(where_expr)?);
+        }
+        let filtered = query
+            .where_clause
+            .as_ref()
+            .is_some_and(|where_expr| excludes_null(where_expr, field));
+        if anti && !filtered {
+            conditions.push(format!("{} IS NOT NULL", column));
+        }
+        conditions.extend(self.row_filter(&query.from_clause, &alias));
+        if query.where_clause.is_some() && conditions.len() > 1 {
+            conditions[0] = format!("({})", conditions[0]);
+        }
+
+        self.current_object = old_obj;
+        self.aliases = old_aliases;
+        self.alias_counter = old_counter;
+        self.alias_prefix = old_prefix;
+        let joins = std::mem::replace(&mut self.joins, old_joins);
+
+        let mut sql = format!("(SELECT {} FROM {}", selected, from_sql);
+        for join in &joins {
+            sql.push_str(&format!(
+                " {} {} {} ON {}",
+                join.join_type, join.table, join.alias, join.condition
+            ));
+        }
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+        sql.push(')');
+        Ok(sql)
+    }
+
     /// Convert TYPEOF clause for polymorphic fields
     fn convert_typeof(&mut self, typeof_clause: &TypeOfClause) -> ConversionResult<String> {
         let schema = self
@@ -861,6 +1942,7 @@ impl<'a> SoqlToSqlConverter<'a> {
                 .ok_or_else(|| ConversionError::UnknownField {
                     object: parent_obj.to_string(),
                     field: typeof_clause.field.clone(),
+                    span: self.query_span,
                 })?;
 
         if !field.is_polymorphic {
@@ -869,9 +1951,11 @@ impl<'a> SoqlToSqlConverter<'a> {
 
         // Get the type discriminator column
         let type_column = format!(
-            "{}.{}_type",
+            "{}.{}",
             parent_alias,
-            to_snake_case(&typeof_clause.field)
+            field
+                .type_column_name()
+                .unwrap_or_else(|| format!("{}_type", field.column_name))
         );
         let id_column = format!("{}.{}", parent_alias, field.column_name);
 
@@ -886,14 +1970,21 @@ impl<'a> SoqlToSqlConverter<'a> {
 
             // Create join for this type
             let alias = self.next_alias();
+            let mut condition = format!(
+                "{} AND {} = '{}'",
+                self.id_equality(&id_column, &format!("{}.id", alias)),
+                type_column,
+                type_name
+            );
+            if let Some(filter) = self.row_filter(type_name, &alias) {
+                condition.push_str(&format!(" AND {}", filter));
+            }
+            let table = self.table_ref(type_name, &type_obj.table_name);
             self.joins.push(JoinClause {
                 join_type: "LEFT JOIN",
-                table: self.dialect.quote_identifier(&type_obj.table_name),
+                table,
                 alias: alias.clone(),
-                condition: format!(
-                    "{} = {}.id AND {} = '{}'",
-                    id_column, alias, type_column, type_name
-                ),
+                condition,
             });
 
             for field_name in &when_clause.fields {
@@ -931,6 +2022,41 @@ impl<'a> SoqlToSqlConverter<'a> {
         Ok(case_exprs.join(", "))
     }
 
+    /// Build the row-level filter for a table alias, if any applies.
+    ///
+    /// Used for the root table, joined parents and child subqueries alike so
+    /// that every table in the generated SQL sees the same visibility rules.
+    /// This is also where USER_MODE row security predicates belong once the
+    /// runtime provides them.
+    ///
+    /// The soft-delete predicate is only added for objects whose describe has
+    /// an `IsDeleted` field; objects the schema does not know are left
+    /// unfiltered with a warning.
+    fn row_filter(&mut self, object: &str, alias: &str) -> Option<String> {
+        if !self.config.filter_deleted {
+            return None;
+        }
+        match self.schema.and_then(|schema| schema.get_object(object)) {
+            Some(describe) if describe.has_field("IsDeleted") => {}
+            Some(_) => return None,
+            None => {
+                let warning = ConversionWarning::DeletedFilterSkipped {
+                    object: object.to_string(),
+                };
+                if !self.warnings.contains(&warning) {
+                    self.warnings.push(warning);
+                }
+                return None;
+            }
+        }
+        Some(format!(
+            "{}.{} = {}",
+            alias,
+            self.dialect.quote_identifier(&self.config.deleted_column),
+            self.dialect.boolean_literal(false)
+        ))
+    }
+
     /// Add a parameter and return its placeholder
     fn add_parameter(&mut self, name: &str) -> ConversionResult<String> {
         let index = self.parameters.len() + 1;
@@ -943,22 +2069,93 @@ impl<'a> SoqlToSqlConverter<'a> {
             name: format!("p{}", index),
             placeholder: placeholder.clone(),
             original_name: name.to_string(),
+            value_type: None,
+            collection: false,
+            id_comparison: IdComparison::Exact,
         });
 
+        // Operands are converted right to left, so positional placeholders
+        // are marked with their index and put in SQL order at the end
+        if placeholder == "?" && self.dialect.positional_parameters() {
+            return Ok(format!("{0}{1}{0}", POSITION_MARKER, index));
+        }
         Ok(placeholder)
     }
 
+    /// Replace the markers left by [`add_parameter`](Self::add_parameter)
+    /// with `?` and list the parameters in the order they are bound
+    fn bind_positionally(&mut self, sql: &str) -> String {
+        let mut out = String::with_capacity(sql.len());
+        let mut bound = Vec::with_capacity(self.parameters.len());
+        let mut parts = sql.split(POSITION_MARKER);
+        out.push_str(parts.next().unwrap_or_default());
+        while let (Some(index), Some(rest)) = (parts.next(), parts.next()) {
+            let param = index
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| self.parameters.get(index - 1));
+            if let Some(param) = param {
+                bound.push(SqlParameter {
+                    name: format!("p{}", bound.len() + 1),
+                    ..param.clone()
+                });
+            }
+            out.push('?');
+            out.push_str(rest);
+        }
+        self.parameters = bound;
+        out
+    }
+
+    /// Record what a just-added bind parameter is compared with
+    fn describe_parameter(
+        &mut self,
+        index: usize,
+        value_type: Option<SalesforceFieldType>,
+        collection: bool,
+    ) {
+        if let Some(param) = self.parameters.get_mut(index) {
+            param.value_type = value_type;
+            param.collection = collection;
+            if value_type.is_some_and(|t| t.is_id()) {
+                param.id_comparison = self.config.id_comparison;
+            }
+        }
+    }
+
+    /// Convert a LIMIT or OFFSET value, whose binds are always integers
+    fn convert_row_count(&mut self, expr: &Expression) -> ConversionResult<String> {
+        let first_param = self.parameters.len();
+        let sql = self.convert_expression(expr)?;
+        if let Expression::BindVariable(..) | Expression::BindExpression(..) = expr {
+            self.describe_parameter(first_param, Some(SalesforceFieldType::Integer), false);
+        }
+        Ok(sql)
+    }
+
     /// Get the table alias for an object
     fn get_table_alias(&self, object_name: &str) -> String {
-        self.table_aliases
+        self.aliases
+            .roots
             .get(&object_name.to_lowercase())
             .cloned()
-            .unwrap_or_else(|| format!("t{}", self.alias_counter))
+            .unwrap_or_else(|| format!("{}{}", self.alias_prefix, self.alias_counter))
+    }
+
+    /// `alias.column`, for an alias of a table in the query
+    fn column_ref(&self, alias: &str, column: &str) -> String {
+        debug_assert!(
+            self.aliases.is_registered(alias),
+            "column {} references unregistered alias {}",
+            column,
+            alias
+        );
+        format!("{}.{}", alias, column)
     }
 
     /// Generate next table alias
     fn next_alias(&mut self) -> String {
-        let alias = format!("t{}", self.alias_counter);
+        let alias = format!("{}{}", self.alias_prefix, self.alias_counter);
         self.alias_counter += 1;
         alias
     }
@@ -977,6 +2174,115 @@ impl<'a> SoqlToSqlConverter<'a> {
     }
 }
 
+/// Salesforce rejects child subqueries next to aggregates: an aggregate
+/// collapses the parent rows the subquery would hang off
+fn check_select_combination(fields: &[SelectField]) -> ConversionResult<()> {
+    let subquery = fields.iter().find_map(|f| match f {
+        SelectField::SubQuery(q) => Some(q),
+        _ => None,
+    });
+    let aggregate = fields.iter().find_map(|f| match f {
+        SelectField::AggregateFunction { name, .. } => Some(name),
+        _ => None,
+    });
+    match (subquery, aggregate) {
+        (Some(subquery), Some(aggregate)) => Err(ConversionError::InvalidSelectCombination {
+            relationship: subquery.from_clause.clone(),
+            aggregate: aggregate.to_uppercase(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Whether a literal is written as `digits.digits`, which every dialect
+/// reads exactly as written
+fn is_plain_decimal(raw: &str) -> bool {
+    match raw.split_once('.') {
+        Some((whole, fraction)) => {
+            !whole.is_empty()
+                && !fraction.is_empty()
+                && whole
+                    .bytes()
+                    .chain(fraction.bytes())
+                    .all(|b| b.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// Format a double as a SQL numeric literal with an explicit decimal point,
+/// falling back to exponent notation only for magnitudes too large or small
+/// to spell out
+pub(super) fn format_decimal(value: f64) -> String {
+    let magnitude = value.abs();
+    if magnitude != 0.0 && !(1e-6..1e16).contains(&magnitude) {
+        return format!("{:e}", value);
+    }
+    let text = value.to_string();
+    if text.contains('.') {
+        text
+    } else {
+        format!("{}.0", text)
+    }
+}
+
+/// Length of the string literal or quoted identifier that `sql` starts
+/// with, quotes included, or 0 when it starts with neither
+///
+/// A doubled quote inside reads as a closing quote followed by an opening
+/// one, so only a backslash (in MySQL string literals) needs handling.
+fn quoted_len(sql: &str, backslash_escapes: bool) -> usize {
+    let quote = match sql.chars().next() {
+        Some(quote @ ('\'' | '"' | '`')) => quote,
+        _ => return 0,
+    };
+    let mut chars = sql.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        if c == quote {
+            return i + 1;
+        }
+        if c == '\\' && backslash_escapes && quote == '\'' {
+            chars.next();
+        }
+    }
+    sql.len()
+}
+
+/// Brackets the index of a positional placeholder until the SQL is complete
+const POSITION_MARKER: char = '\u{0}';
+
+/// Whether `condition` only holds when `field` is not null, by requiring
+/// `field != null` in one of its top-level AND terms
+fn excludes_null(condition: &Expression, field: &str) -> bool {
+    match condition {
+        Expression::Parenthesized(inner, _, _) => excludes_null(inner, field),
+        Expression::Binary(binary) => match binary.operator {
+            BinaryOp::And => {
+                excludes_null(&binary.left, field) || excludes_null(&binary.right, field)
+            }
+            BinaryOp::NotEqual | BinaryOp::ExactNotEqual => matches!(
+                (&binary.left, &binary.right),
+                (Expression::Identifier(path, _, _), Expression::Null(..))
+                    | (Expression::Null(..), Expression::Identifier(path, _, _))
+                    if path.eq_ignore_ascii_case(field)
+            ),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The values of a list made only of string literals
+fn string_items(items: &[Expression]) -> Option<Vec<&str>> {
+    items
+        .iter()
+        .map(|item| match item {
+            Expression::String(s, _, _) => Some(s.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Convert a Salesforce API name to snake_case for SQL
 fn to_snake_case(s: &str) -> String {
     let mut result = String::with_capacity(s.len() + 4);
@@ -1019,6 +2325,18 @@ pub fn convert_soql(
     converter.convert(query)
 }
 
+/// Count warnings per kind across a batch of conversions, e.g. every query
+/// in a project
+pub fn warning_counts<'a>(
+    conversions: impl IntoIterator<Item = &'a SqlConversion>,
+) -> BTreeMap<WarningKind, usize> {
+    let mut counts = BTreeMap::new();
+    for warning in conversions.into_iter().flat_map(|c| &c.warnings) {
+        *counts.entry(warning.kind()).or_insert(0) += 1;
+    }
+    counts
+}
+
 /// Convert SOQL without schema (only works for simple single-object queries)
 pub fn convert_soql_simple(
     query: &SoqlQuery,
@@ -1095,6 +2413,48 @@ mod tests {
         assert!(result.sql.contains("?1"));
     }
 
+    #[test]
+    fn test_to_debug_sql() {
+        let soql = extract_soql(
+            "SELECT Id FROM Account WHERE Name = :accountName AND Id IN :ids LIMIT :lim",
+        );
+        let result = convert_soql_simple(&soql, SqlDialect::Postgres).unwrap();
+        let debug = result.to_debug_sql();
+
+        assert!(debug.contains("t0.name = '<accountName>' /* :accountName */"));
+        assert!(debug.contains("t0.id IN '<ids>' /* :ids */"));
+        assert!(debug.contains("LIMIT '<lim>' /* :lim */"));
+        assert!(!debug.contains("$1"));
+        // The executable SQL is untouched
+        assert!(result.sql.contains("$1"));
+    }
+
+    #[test]
+    fn test_to_debug_sql_multi_digit_placeholders() {
+        let conditions: Vec<String> = (1..=10).map(|i| format!("Name = :v{}", i)).collect();
+        let soql = extract_soql(&format!(
+            "SELECT Id FROM Account WHERE {}",
+            conditions.join(" OR ")
+        ));
+        let result = convert_soql_simple(&soql, SqlDialect::Sqlite).unwrap();
+        let debug = result.to_debug_sql();
+
+        assert!(debug.contains("'<v10>' /* :v10 */"));
+        assert!(!debug.contains("'<v1>'0"));
+        assert!(!debug.contains("?1"));
+    }
+
+    #[test]
+    fn test_format_decimal() {
+        assert_eq!(format_decimal(1e10), "10000000000.0");
+        assert_eq!(format_decimal(1.5e-3), "0.0015");
+        assert_eq!(format_decimal(-2.0), "-2.0");
+        assert_eq!(format_decimal(0.0), "0.0");
+        assert_eq!(format_decimal(1e300), "1e300");
+        assert!(is_plain_decimal("1.50"));
+        assert!(!is_plain_decimal("1.5e3"));
+    }
+
     #[test]
     fn test_order_by() {
         let soql = extract_soql("SELECT Id FROM Account ORDER BY Name DESC NULLS LAST");
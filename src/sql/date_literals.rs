@@ -2,6 +2,7 @@
 
 use super::dialect::{DateUnit, SqlDialectImpl};
 use super::error::{ConversionError, ConversionResult};
+use crate::clock::ClockSource;
 
 /// Expand a SOQL date literal to a SQL expression
 ///
@@ -11,44 +12,63 @@ pub fn expand_date_literal(
     literal: &str,
     field_expr: &str,
     dialect: &dyn SqlDialectImpl,
+) -> ConversionResult<String> {
+    expand_date_literal_at(literal, field_expr, dialect, &ClockSource::System)
+}
+
+/// Expand a SOQL date literal relative to the date `clock` gives: the
+/// database's current date for [`ClockSource::System`], otherwise the
+/// clock's date as a literal
+pub fn expand_date_literal_at(
+    literal: &str,
+    field_expr: &str,
+    dialect: &dyn SqlDialectImpl,
+    clock: &ClockSource,
+) -> ConversionResult<String> {
+    let today = if clock.is_system() {
+        dialect.current_date().to_string()
+    } else {
+        dialect.date_literal(&clock.now().iso_date())
+    };
+    expand(literal, field_expr, dialect, &today)
+}
+
+/// Expand a date literal with `today` as the SQL for the current date
+fn expand(
+    literal: &str,
+    field_expr: &str,
+    dialect: &dyn SqlDialectImpl,
+    today: &str,
 ) -> ConversionResult<String> {
     let lower = literal.to_lowercase();
 
     // Try to parse N-style literals first (e.g., LAST_N_DAYS:30)
-    if let Some(result) = try_parse_n_literal(&lower, field_expr, dialect)? {
+    if let Some(result) = try_parse_n_literal(&lower, field_expr, dialect, today)? {
         return Ok(result);
     }
 
     // Handle simple date literals
     match lower.as_str() {
-        "today" => Ok(format!(
-            "DATE({}) = {}",
-            field_expr,
-            dialect.current_date()
-        )),
+        "today" => Ok(format!("DATE({}) = {}", field_expr, today)),
         "yesterday" => Ok(format!(
             "DATE({}) = {}",
             field_expr,
-            dialect.date_sub(dialect.current_date(), 1, DateUnit::Day)
+            dialect.date_sub(today, 1, DateUnit::Day)
         )),
         "tomorrow" => Ok(format!(
             "DATE({}) = {}",
             field_expr,
-            dialect.date_add(dialect.current_date(), 1, DateUnit::Day)
+            dialect.date_add(today, 1, DateUnit::Day)
         )),
         "this_week" => Ok(format!(
             "{} >= {} AND {} < {}",
             field_expr,
-            dialect.date_trunc(DateUnit::Week, dialect.current_date()),
+            dialect.date_trunc(DateUnit::Week, today),
             field_expr,
-            dialect.date_add(
-                &dialect.date_trunc(DateUnit::Week, dialect.current_date()),
-                7,
-                DateUnit::Day
-            )
+            dialect.date_add(&dialect.date_trunc(DateUnit::Week, today), 7, DateUnit::Day)
         )),
         "last_week" => {
-            let week_start = dialect.date_trunc(DateUnit::Week, dialect.current_date());
+            let week_start = dialect.date_trunc(DateUnit::Week, today);
             Ok(format!(
                 "{} >= {} AND {} < {}",
                 field_expr,
@@ -58,7 +78,7 @@ pub fn expand_date_literal(
             ))
         }
         "next_week" => {
-            let week_start = dialect.date_trunc(DateUnit::Week, dialect.current_date());
+            let week_start = dialect.date_trunc(DateUnit::Week, today);
             let next_week_start = dialect.date_add(&week_start, 7, DateUnit::Day);
             Ok(format!(
                 "{} >= {} AND {} < {}",
@@ -71,16 +91,16 @@ pub fn expand_date_literal(
         "this_month" => Ok(format!(
             "{} >= {} AND {} < {}",
             field_expr,
-            dialect.date_trunc(DateUnit::Month, dialect.current_date()),
+            dialect.date_trunc(DateUnit::Month, today),
             field_expr,
             dialect.date_add(
-                &dialect.date_trunc(DateUnit::Month, dialect.current_date()),
+                &dialect.date_trunc(DateUnit::Month, today),
                 1,
                 DateUnit::Month
             )
         )),
         "last_month" => {
-            let month_start = dialect.date_trunc(DateUnit::Month, dialect.current_date());
+            let month_start = dialect.date_trunc(DateUnit::Month, today);
             Ok(format!(
                 "{} >= {} AND {} < {}",
                 field_expr,
@@ -90,7 +110,7 @@ pub fn expand_date_literal(
             ))
         }
         "next_month" => {
-            let month_start = dialect.date_trunc(DateUnit::Month, dialect.current_date());
+            let month_start = dialect.date_trunc(DateUnit::Month, today);
             let next_month_start = dialect.date_add(&month_start, 1, DateUnit::Month);
             Ok(format!(
                 "{} >= {} AND {} < {}",
@@ -103,16 +123,16 @@ pub fn expand_date_literal(
         "this_quarter" => Ok(format!(
             "{} >= {} AND {} < {}",
             field_expr,
-            dialect.date_trunc(DateUnit::Quarter, dialect.current_date()),
+            dialect.date_trunc(DateUnit::Quarter, today),
             field_expr,
             dialect.date_add(
-                &dialect.date_trunc(DateUnit::Quarter, dialect.current_date()),
+                &dialect.date_trunc(DateUnit::Quarter, today),
                 3,
                 DateUnit::Month
             )
         )),
         "last_quarter" => {
-            let quarter_start = dialect.date_trunc(DateUnit::Quarter, dialect.current_date());
+            let quarter_start = dialect.date_trunc(DateUnit::Quarter, today);
             Ok(format!(
                 "{} >= {} AND {} < {}",
                 field_expr,
@@ -122,7 +142,7 @@ pub fn expand_date_literal(
             ))
         }
         "next_quarter" => {
-            let quarter_start = dialect.date_trunc(DateUnit::Quarter, dialect.current_date());
+            let quarter_start = dialect.date_trunc(DateUnit::Quarter, today);
             let next_quarter_start = dialect.date_add(&quarter_start, 3, DateUnit::Month);
             Ok(format!(
                 "{} >= {} AND {} < {}",
@@ -135,16 +155,16 @@ pub fn expand_date_literal(
         "this_year" => Ok(format!(
             "{} >= {} AND {} < {}",
             field_expr,
-            dialect.date_trunc(DateUnit::Year, dialect.current_date()),
+            dialect.date_trunc(DateUnit::Year, today),
             field_expr,
             dialect.date_add(
-                &dialect.date_trunc(DateUnit::Year, dialect.current_date()),
+                &dialect.date_trunc(DateUnit::Year, today),
                 1,
                 DateUnit::Year
             )
         )),
         "last_year" => {
-            let year_start = dialect.date_trunc(DateUnit::Year, dialect.current_date());
+            let year_start = dialect.date_trunc(DateUnit::Year, today);
             Ok(format!(
                 "{} >= {} AND {} < {}",
                 field_expr,
@@ -154,7 +174,7 @@ pub fn expand_date_literal(
             ))
         }
         "next_year" => {
-            let year_start = dialect.date_trunc(DateUnit::Year, dialect.current_date());
+            let year_start = dialect.date_trunc(DateUnit::Year, today);
             let next_year_start = dialect.date_add(&year_start, 1, DateUnit::Year);
             Ok(format!(
                 "{} >= {} AND {} < {}",
@@ -169,7 +189,7 @@ pub fn expand_date_literal(
             // Fiscal periods depend on org configuration
             // For now, treat them as calendar periods
             let non_fiscal = lower.replace("_fiscal", "");
-            expand_date_literal(&non_fiscal, field_expr, dialect)
+            expand(&non_fiscal, field_expr, dialect, today)
         }
         _ => Err(ConversionError::UnknownDateLiteral(literal.to_string())),
     }
@@ -180,6 +200,7 @@ fn try_parse_n_literal(
     literal: &str,
     field_expr: &str,
     dialect: &dyn SqlDialectImpl,
+    today: &str,
 ) -> ConversionResult<Option<String>> {
     // Parse patterns like LAST_N_DAYS:30, NEXT_N_MONTHS:6
     let patterns = [
@@ -211,18 +232,18 @@ fn try_parse_n_literal(
                 return Ok(Some(format!(
                     "{} >= {} AND {} < {}",
                     field_expr,
-                    dialect.current_date(),
+                    today,
                     field_expr,
-                    dialect.date_add(dialect.current_date(), n, unit)
+                    dialect.date_add(today, n, unit)
                 )));
             } else {
                 // LAST_N: from N units ago to now
                 return Ok(Some(format!(
                     "{} >= {} AND {} < {}",
                     field_expr,
-                    dialect.date_sub(dialect.current_date(), n, unit),
+                    dialect.date_sub(today, n, unit),
                     field_expr,
-                    dialect.current_date()
+                    today
                 )));
             }
         }
@@ -236,7 +257,7 @@ fn try_parse_n_literal(
         return Ok(Some(format!(
             "DATE({}) = {}",
             field_expr,
-            dialect.date_sub(dialect.current_date(), n, DateUnit::Day)
+            dialect.date_sub(today, n, DateUnit::Day)
         )));
     }
 
@@ -1,11 +1,51 @@
 //! DDL generation for Salesforce schema
 
-use super::dialect::{get_dialect, SqlDialect, SqlDialectImpl};
-use super::schema::{FieldDescribe, SObjectDescribe, SalesforceFieldType, SalesforceSchema};
+use std::collections::{HashMap, HashSet};
+
+use super::converter::{format_decimal, IdComparison};
+use super::dialect::{get_dialect, resolve_qualifier, SqlDialect, SqlDialectImpl, SqliteQualifier};
+use super::schema::{
+    FieldDefault, FieldDescribe, SObjectDescribe, SalesforceFieldType, SalesforceSchema,
+};
+
+/// What a [`DdlGenerator`] emits besides tables and columns
+#[derive(Debug, Clone)]
+pub struct DdlOptions {
+    /// FOREIGN KEY constraints for Lookup and Master-Detail fields with a
+    /// single target. Turn off for data loaded out of dependency order.
+    pub foreign_keys: bool,
+    /// CREATE INDEX statements in [`DdlGenerator::generate_schema`] for
+    /// lookup columns (polymorphic ones included), Name, the system
+    /// timestamps and IsDeleted
+    pub indexes: bool,
+}
+
+impl Default for DdlOptions {
+    fn default() -> Self {
+        Self {
+            foreign_keys: true,
+            indexes: true,
+        }
+    }
+}
 
 /// Generator for SQL DDL (CREATE TABLE, etc.)
 pub struct DdlGenerator {
     dialect: Box<dyn SqlDialectImpl>,
+    schema_qualifier: Option<String>,
+    schema_overrides: HashMap<String, String>,
+    sqlite_qualifier: SqliteQualifier,
+    id_comparison: IdComparison,
+    options: DdlOptions,
+}
+
+/// A FOREIGN KEY from a lookup column to its parent's Id
+struct ForeignKey {
+    column: String,
+    /// Parent object name
+    target: String,
+    /// Quoted parent table
+    table: String,
 }
 
 impl DdlGenerator {
@@ -13,72 +53,178 @@ impl DdlGenerator {
     pub fn new(dialect: SqlDialect) -> Self {
         Self {
             dialect: get_dialect(dialect),
+            schema_qualifier: None,
+            schema_overrides: HashMap::new(),
+            sqlite_qualifier: SqliteQualifier::Ignore,
+            id_comparison: IdComparison::Exact,
+            options: DdlOptions::default(),
+        }
+    }
+
+    /// Replace the generator's [`DdlOptions`]
+    pub fn with_options(mut self, options: DdlOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Whether lookups get FOREIGN KEY constraints (on by default)
+    pub fn with_foreign_keys(mut self, enabled: bool) -> Self {
+        self.options.foreign_keys = enabled;
+        self
+    }
+
+    /// Whether [`Self::generate_schema`] indexes lookup and commonly
+    /// filtered columns (on by default)
+    pub fn with_indexes(mut self, enabled: bool) -> Self {
+        self.options.indexes = enabled;
+        self
+    }
+
+    /// Qualify every table with a schema (namespace), e.g. `"org_a"."account"`
+    pub fn with_schema_qualifier(mut self, schema: impl Into<String>) -> Self {
+        self.schema_qualifier = Some(schema.into());
+        self
+    }
+
+    /// Place one object's table in a different schema than the default
+    pub fn with_schema_override(
+        mut self,
+        object: impl Into<String>,
+        schema: impl Into<String>,
+    ) -> Self {
+        self.schema_overrides.insert(object.into(), schema.into());
+        self
+    }
+
+    /// How qualifiers are applied when targeting SQLite (ignored by default)
+    pub fn with_sqlite_qualifier(mut self, mode: SqliteQualifier) -> Self {
+        self.sqlite_qualifier = mode;
+        self
+    }
+
+    /// Index Id and lookup columns for queries converted with the same
+    /// [`IdComparison`]: `CaseInsensitive` compares `UPPER(column)`, which
+    /// only an expression index on `UPPER(column)` serves
+    pub fn with_id_comparison(mut self, mode: IdComparison) -> Self {
+        self.id_comparison = mode;
+        self
+    }
+
+    /// Schema an object's table lives in, if it should be qualified
+    fn qualifier(&self, object: &str) -> Option<&str> {
+        if self.dialect.dialect() == SqlDialect::Sqlite
+            && self.sqlite_qualifier == SqliteQualifier::Ignore
+        {
+            return None;
         }
+        resolve_qualifier(
+            self.schema_qualifier.as_deref(),
+            &self.schema_overrides,
+            object,
+        )
+    }
+
+    /// Quoted, schema-qualified table name for an object
+    fn table_ref(&self, object: &SObjectDescribe) -> String {
+        self.dialect
+            .qualified_table(self.qualifier(&object.name), &object.table_name)
     }
 
     /// Generate CREATE TABLE statement for an SObject
+    ///
+    /// Lookup and Master-Detail fields reference their parent's table,
+    /// which must already exist outside SQLite; [`Self::generate_schema`]
+    /// orders tables for that.
     pub fn generate_table(&self, object: &SObjectDescribe) -> String {
-        let mut sql = format!(
-            "CREATE TABLE {} (\n",
-            self.dialect.quote_identifier(&object.table_name)
-        );
+        self.create_table(object, &self.foreign_keys(object, None))
+    }
 
-        let mut columns = Vec::new();
-        let mut constraints = Vec::new();
-
-        // Sort fields for consistent output
-        let mut fields: Vec<_> = object.fields().collect();
-        fields.sort_by(|a, b| {
-            // Put Id first, then Name, then sort alphabetically
-            match (a.name.as_str(), b.name.as_str()) {
-                ("Id", _) => std::cmp::Ordering::Less,
-                (_, "Id") => std::cmp::Ordering::Greater,
-                ("Name", _) => std::cmp::Ordering::Less,
-                (_, "Name") => std::cmp::Ordering::Greater,
-                _ => a.name.cmp(&b.name),
-            }
-        });
+    fn create_table(&self, object: &SObjectDescribe, foreign_keys: &[ForeignKey]) -> String {
+        let mut sql = format!("CREATE TABLE {} (\n", self.table_ref(object));
 
-        for field in fields {
+        let mut columns = Vec::new();
+        for field in sorted_fields(object) {
             let col_def = self.generate_column(field);
             columns.push(format!("    {}", col_def));
 
-            // Add foreign key constraints for lookup fields
-            if field.is_relationship() && !field.is_polymorphic {
-                if let Some(ref refs) = field.reference_to {
-                    if let Some(ref_obj) = refs.first() {
-                        // Only add FK if it's not a self-reference to avoid issues
-                        // Self-references are valid but need the table to exist first
-                        let ref_table = to_snake_case(ref_obj);
-                        constraints.push(format!(
-                            "    FOREIGN KEY ({}) REFERENCES {}(id)",
-                            self.dialect.quote_identifier(&field.column_name),
-                            self.dialect.quote_identifier(&ref_table)
-                        ));
-                    }
-                }
-            }
-
             // For polymorphic fields, add type discriminator column
-            if field.is_polymorphic {
-                let type_col = format!("{}_type", field.column_name);
+            if let Some(type_col) = field.type_column_name() {
                 columns.push(format!(
                     "    {} TEXT",
                     self.dialect.quote_identifier(&type_col)
                 ));
             }
         }
+        for fk in foreign_keys {
+            columns.push(format!("    {}", self.foreign_key_clause(fk)));
+        }
 
         sql.push_str(&columns.join(",\n"));
+        sql.push_str("\n)");
+        sql
+    }
 
-        // Add constraints (optional - can be commented out for SQLite compatibility)
-        if !constraints.is_empty() && matches!(self.dialect.dialect(), SqlDialect::Postgres) {
-            sql.push_str(",\n");
-            sql.push_str(&constraints.join(",\n"));
+    /// Foreign keys for an object's single-target lookups. With a schema,
+    /// parents it doesn't describe are skipped, tables are named as the
+    /// schema names them and single-target Reference fields are included.
+    fn foreign_keys(
+        &self,
+        object: &SObjectDescribe,
+        schema: Option<&SalesforceSchema>,
+    ) -> Vec<ForeignKey> {
+        if !self.options.foreign_keys {
+            return Vec::new();
         }
+        let mut keys = Vec::new();
+        for field in sorted_fields(object) {
+            // Polymorphic fields point at several tables, and without a
+            // schema a plain Reference may name a parent the replica doesn't
+            // hold
+            let lookup = matches!(
+                field.field_type,
+                SalesforceFieldType::Lookup | SalesforceFieldType::MasterDetail
+            );
+            let reference = field.field_type == SalesforceFieldType::Reference && schema.is_some();
+            if field.is_polymorphic || !(lookup || reference) {
+                continue;
+            }
+            let Some([target]) = field.reference_to.as_deref() else {
+                continue;
+            };
+            let (target, table) = match schema {
+                Some(schema) => match schema.get_object(target) {
+                    Some(parent) => (parent.name.clone(), parent.table_name.clone()),
+                    None => continue,
+                },
+                None => (target.clone(), to_snake_case(target)),
+            };
+            let table = match self.dialect.dialect() {
+                // SQLite can't reference a table in another attached database
+                SqlDialect::Sqlite => {
+                    if self.qualifier(&target) != self.qualifier(&object.name) {
+                        continue;
+                    }
+                    self.dialect.quote_identifier(&table)
+                }
+                SqlDialect::Postgres | SqlDialect::Mysql => self
+                    .dialect
+                    .qualified_table(self.qualifier(&target), &table),
+            };
+            keys.push(ForeignKey {
+                column: field.column_name.clone(),
+                target,
+                table,
+            });
+        }
+        keys
+    }
 
-        sql.push_str("\n)");
-        sql
+    fn foreign_key_clause(&self, fk: &ForeignKey) -> String {
+        format!(
+            "FOREIGN KEY ({}) REFERENCES {}(id)",
+            self.dialect.quote_identifier(&fk.column),
+            fk.table
+        )
     }
 
     /// Generate column definition
@@ -96,15 +242,54 @@ impl DdlGenerator {
             col.push_str(" NOT NULL");
         }
 
+        let default = field.default_value.as_ref();
+        if let Some(default) = default.and_then(|default| self.default_literal(field, default)) {
+            col.push_str(" DEFAULT ");
+            col.push_str(&default);
+        }
+
         col
     }
 
+    /// Render a column default as a SQL literal, or `None` for NaN and the
+    /// infinities, which no dialect spells as a numeric literal
+    fn default_literal(&self, field: &FieldDescribe, default: &FieldDefault) -> Option<String> {
+        let dialect = self.dialect.dialect();
+        let literal = match default {
+            FieldDefault::Boolean(b) => self.dialect.boolean_literal(*b).to_string(),
+            FieldDefault::Integer(n) => n.to_string(),
+            FieldDefault::Number(n) if !n.is_finite() => return None,
+            FieldDefault::Number(n) => format_decimal(*n),
+            // MySQL only takes a default on a TEXT column as an expression
+            FieldDefault::Text(s)
+                if dialect == SqlDialect::Mysql && self.column_type(field) == "TEXT" =>
+            {
+                format!("({})", self.dialect.string_literal(s))
+            }
+            FieldDefault::Text(s) => self.dialect.string_literal(s),
+            // SQLite only accepts expressions in DEFAULT when parenthesized,
+            // and MySQL only accepts CURRENT_TIMESTAMP bare, on DATETIME and
+            // TIMESTAMP columns
+            FieldDefault::CurrentTimestamp => match dialect {
+                SqlDialect::Mysql if field.field_type == SalesforceFieldType::Date => {
+                    format!("({})", self.dialect.current_date())
+                }
+                SqlDialect::Postgres | SqlDialect::Mysql => "CURRENT_TIMESTAMP".to_string(),
+                SqlDialect::Sqlite if field.field_type == SalesforceFieldType::Date => {
+                    format!("({})", self.dialect.current_date())
+                }
+                SqlDialect::Sqlite => format!("({})", self.dialect.current_timestamp()),
+            },
+        };
+        Some(literal)
+    }
+
     /// Get SQL column type for a field
     fn column_type(&self, field: &FieldDescribe) -> &'static str {
         match field.field_type {
             SalesforceFieldType::Boolean => {
                 match self.dialect.dialect() {
-                    SqlDialect::Postgres => "BOOLEAN",
+                    SqlDialect::Postgres | SqlDialect::Mysql => "BOOLEAN",
                     SqlDialect::Sqlite => "INTEGER", // SQLite uses 0/1
                 }
             }
@@ -114,18 +299,22 @@ impl DdlGenerator {
             | SalesforceFieldType::Percent => match self.dialect.dialect() {
                 SqlDialect::Postgres => "NUMERIC",
                 SqlDialect::Sqlite => "REAL",
+                SqlDialect::Mysql => "DOUBLE",
             },
             SalesforceFieldType::Date => "DATE",
             SalesforceFieldType::DateTime => {
                 match self.dialect.dialect() {
                     SqlDialect::Postgres => "TIMESTAMP",
                     SqlDialect::Sqlite => "TEXT", // SQLite stores dates as TEXT
+                    SqlDialect::Mysql => "DATETIME",
                 }
             }
             SalesforceFieldType::Time => match self.dialect.dialect() {
-                SqlDialect::Postgres => "TIME",
+                SqlDialect::Postgres | SqlDialect::Mysql => "TIME",
                 SqlDialect::Sqlite => "TEXT",
             },
+            // MySQL can't key or index a TEXT column without a prefix length
+            _ if self.dialect.dialect() == SqlDialect::Mysql => mysql_text_type(field.field_type),
             _ => "TEXT",
         }
     }
@@ -136,25 +325,21 @@ impl DdlGenerator {
         let table = &object.table_name;
 
         for field in object.fields() {
-            // Create indexes for lookup fields
-            if field.is_relationship() {
-                indexes.push(format!(
-                    "CREATE INDEX {} ON {} ({})",
-                    self.dialect
-                        .quote_identifier(&format!("idx_{}_{}", table, field.column_name)),
-                    self.dialect.quote_identifier(table),
-                    self.dialect.quote_identifier(&field.column_name)
-                ));
+            // Create indexes for lookup fields; the primary key needs its own
+            // only when compared case-insensitively
+            if field.is_relationship()
+                || (field.field_type == SalesforceFieldType::Id
+                    && self.id_comparison == IdComparison::CaseInsensitive)
+            {
+                indexes.push(self.create_id_index(object, &field.column_name));
             }
 
             // Create index for Name field (commonly queried)
             if field.name == "Name" {
-                indexes.push(format!(
-                    "CREATE INDEX {} ON {} ({})",
-                    self.dialect
-                        .quote_identifier(&format!("idx_{}_name", table)),
-                    self.dialect.quote_identifier(table),
-                    self.dialect.quote_identifier(&field.column_name)
+                indexes.push(self.create_index(
+                    object,
+                    &format!("idx_{}_name", table),
+                    &field.column_name,
                 ));
             }
 
@@ -163,74 +348,225 @@ impl DdlGenerator {
                 field.name.as_str(),
                 "CreatedDate" | "LastModifiedDate" | "SystemModstamp"
             ) {
-                indexes.push(format!(
-                    "CREATE INDEX {} ON {} ({})",
-                    self.dialect
-                        .quote_identifier(&format!("idx_{}_{}", table, field.column_name)),
-                    self.dialect.quote_identifier(table),
-                    self.dialect.quote_identifier(&field.column_name)
+                indexes.push(self.create_index(
+                    object,
+                    &format!("idx_{}_{}", table, field.column_name),
+                    &field.column_name,
                 ));
             }
         }
 
         // Index for soft delete
         if object.has_field("IsDeleted") {
-            indexes.push(format!(
-                "CREATE INDEX {} ON {} ({})",
-                self.dialect
-                    .quote_identifier(&format!("idx_{}_is_deleted", table)),
-                self.dialect.quote_identifier(table),
-                self.dialect.quote_identifier("is_deleted")
+            indexes.push(self.create_index(
+                object,
+                &format!("idx_{}_is_deleted", table),
+                "is_deleted",
             ));
         }
 
         indexes
     }
 
+    /// Index on an Id or lookup column, over `UPPER(column)` when Ids are
+    /// compared case-insensitively
+    fn create_id_index(&self, object: &SObjectDescribe, column: &str) -> String {
+        let name = format!("idx_{}_{}", object.table_name, column);
+        match self.id_comparison {
+            IdComparison::CaseInsensitive => {
+                let key = format!("UPPER({})", self.dialect.quote_identifier(column));
+                // MySQL only takes an expression key part in its own parentheses
+                let key = match self.dialect.dialect() {
+                    SqlDialect::Mysql => format!("({})", key),
+                    _ => key,
+                };
+                self.create_index_on(object, &format!("{}_upper", name), &key)
+            }
+            IdComparison::Exact | IdComparison::NormalizeTo18 => {
+                self.create_index(object, &name, column)
+            }
+        }
+    }
+
+    /// CREATE INDEX on one column
+    fn create_index(&self, object: &SObjectDescribe, name: &str, column: &str) -> String {
+        self.create_index_on(object, name, &self.dialect.quote_identifier(column))
+    }
+
+    /// CREATE INDEX on a key expression. Postgres and MySQL create the index
+    /// in its table's schema, while SQLite names the attached database on the
+    /// index instead and rejects a qualified table.
+    fn create_index_on(&self, object: &SObjectDescribe, name: &str, key: &str) -> String {
+        let (name, table) = match self.dialect.dialect() {
+            SqlDialect::Postgres | SqlDialect::Mysql => {
+                (self.dialect.quote_identifier(name), self.table_ref(object))
+            }
+            SqlDialect::Sqlite => (
+                self.dialect
+                    .qualified_table(self.qualifier(&object.name), name),
+                self.dialect.quote_identifier(&object.table_name),
+            ),
+        };
+        format!("CREATE INDEX {} ON {} ({})", name, table, key)
+    }
+
     /// Generate complete DDL for a schema
+    ///
+    /// Parent tables are created before their children. A foreign key that
+    /// closes a cycle of lookups is added with `ALTER TABLE ... ADD
+    /// CONSTRAINT` once every table exists; SQLite doesn't check references
+    /// at creation, so it keeps all of them inline.
     pub fn generate_schema(&self, schema: &SalesforceSchema) -> String {
         let mut sql = String::new();
-
-        // Collect objects and sort them to handle dependencies
-        // (parent objects should be created before children)
-        let mut objects: Vec<_> = schema.objects().collect();
-        objects.sort_by(|a, b| a.name.cmp(&b.name));
+        let objects = self.creation_order(schema);
 
         // Create tables
+        let mut created = HashSet::new();
+        let mut deferred = Vec::new();
         for object in &objects {
-            sql.push_str(&self.generate_table(object));
+            let (inline, later): (Vec<_>, Vec<_>) = self
+                .foreign_keys(object, Some(schema))
+                .into_iter()
+                .partition(|fk| {
+                    self.dialect.dialect() == SqlDialect::Sqlite
+                        || fk.target == object.name
+                        || created.contains(&fk.target)
+                });
+            sql.push_str(&self.create_table(object, &inline));
             sql.push_str(";\n\n");
+            created.insert(object.name.clone());
+            deferred.extend(later.into_iter().map(|fk| (*object, fk)));
+        }
+
+        for (object, fk) in &deferred {
+            sql.push_str(&format!(
+                "ALTER TABLE {} ADD CONSTRAINT {} {};\n",
+                self.table_ref(object),
+                self.dialect
+                    .quote_identifier(&format!("fk_{}_{}", object.table_name, fk.column)),
+                self.foreign_key_clause(fk)
+            ));
+        }
+        if !deferred.is_empty() {
+            sql.push('\n');
         }
 
         // Create indexes
-        for object in &objects {
-            for index in self.generate_indexes(object) {
-                sql.push_str(&index);
-                sql.push_str(";\n");
+        if self.options.indexes {
+            for object in &objects {
+                for index in self.generate_indexes(object) {
+                    sql.push_str(&index);
+                    sql.push_str(";\n");
+                }
+                sql.push('\n');
             }
-            sql.push('\n');
         }
 
         sql
     }
 
+    /// Objects with every parent ahead of its children, otherwise by name.
+    /// Within a cycle the first object by name goes first.
+    fn creation_order<'s>(&self, schema: &'s SalesforceSchema) -> Vec<&'s SObjectDescribe> {
+        let mut remaining: Vec<_> = schema.objects().collect();
+        remaining.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut ordered = Vec::with_capacity(remaining.len());
+        let mut created = HashSet::new();
+        while !remaining.is_empty() {
+            let next = remaining
+                .iter()
+                .position(|object| {
+                    self.foreign_keys(object, Some(schema))
+                        .iter()
+                        .all(|fk| fk.target == object.name || created.contains(&fk.target))
+                })
+                .unwrap_or(0);
+            let object = remaining.remove(next);
+            created.insert(object.name.clone());
+            ordered.push(object);
+        }
+        ordered
+    }
+
+    /// Generate an upsert of one row into an object's table, for loading
+    /// replicated records
+    ///
+    /// Columns follow `generate_table`, bound to positional parameters in
+    /// that order. Postgres and SQLite use `INSERT ... ON CONFLICT (...) DO
+    /// UPDATE` (SQLite 3.24+) rather than SQLite's `INSERT OR REPLACE`, which
+    /// deletes the existing row first. The conflict field must be `Id` or
+    /// carry a unique index; MySQL's `ON DUPLICATE KEY UPDATE` can't name it
+    /// and fires on any unique key. Returns `None` when the schema has no
+    /// such object or field.
+    pub fn upsert_statement(
+        &self,
+        object: &str,
+        schema: &SalesforceSchema,
+        conflict_field: &str,
+    ) -> Option<String> {
+        let object = schema.get_object(object)?;
+        let conflict_column = self
+            .dialect
+            .quote_identifier(&object.get_field(conflict_field)?.column_name);
+
+        let mut columns = Vec::new();
+        for field in sorted_fields(object) {
+            columns.push(self.dialect.quote_identifier(&field.column_name));
+            if let Some(type_col) = field.type_column_name() {
+                columns.push(self.dialect.quote_identifier(&type_col));
+            }
+        }
+        let placeholders: Vec<_> = (1..=columns.len())
+            .map(|i| self.dialect.parameter_placeholder(i))
+            .collect();
+        let insert = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            self.table_ref(object),
+            columns.join(", "),
+            placeholders.join(", ")
+        );
+        let updated = columns.iter().filter(|column| **column != conflict_column);
+
+        if self.dialect.dialect() == SqlDialect::Mysql {
+            let mut updates: Vec<_> = updated
+                .map(|column| format!("{} = VALUES({})", column, column))
+                .collect();
+            if updates.is_empty() {
+                updates.push(format!("{} = {}", conflict_column, conflict_column));
+            }
+            return Some(format!(
+                "{} ON DUPLICATE KEY UPDATE {}",
+                insert,
+                updates.join(", ")
+            ));
+        }
+
+        let updates: Vec<_> = updated
+            .map(|column| format!("{} = excluded.{}", column, column))
+            .collect();
+        let action = if updates.is_empty() {
+            "DO NOTHING".to_string()
+        } else {
+            format!("DO UPDATE SET {}", updates.join(", "))
+        };
+        Some(format!(
+            "{} ON CONFLICT ({}) {}",
+            insert, conflict_column, action
+        ))
+    }
+
     /// Generate DROP TABLE statement
     pub fn generate_drop_table(&self, object: &SObjectDescribe) -> String {
-        format!(
-            "DROP TABLE IF EXISTS {}",
-            self.dialect.quote_identifier(&object.table_name)
-        )
+        format!("DROP TABLE IF EXISTS {}", self.table_ref(object))
     }
 
     /// Generate DROP TABLE statements for all objects in schema
     pub fn generate_drop_schema(&self, schema: &SalesforceSchema) -> String {
         let mut sql = String::new();
 
-        // Drop in reverse order to handle foreign key dependencies
-        let mut objects: Vec<_> = schema.objects().collect();
-        objects.sort_by(|a, b| b.name.cmp(&a.name));
-
-        for object in objects {
+        // Drop children before the parents they reference
+        for object in self.creation_order(schema).into_iter().rev() {
             sql.push_str(&self.generate_drop_table(object));
             sql.push_str(";\n");
         }
@@ -239,6 +575,37 @@ impl DdlGenerator {
     }
 }
 
+/// Fields in column order: Id, then Name, then alphabetical
+pub(crate) fn sorted_fields(object: &SObjectDescribe) -> Vec<&FieldDescribe> {
+    let mut fields: Vec<_> = object.fields().collect();
+    fields.sort_by(|a, b| match (a.name.as_str(), b.name.as_str()) {
+        ("Id", _) => std::cmp::Ordering::Less,
+        (_, "Id") => std::cmp::Ordering::Greater,
+        ("Name", _) => std::cmp::Ordering::Less,
+        (_, "Name") => std::cmp::Ordering::Greater,
+        _ => a.name.cmp(&b.name),
+    });
+    fields
+}
+
+/// MySQL column for a text-valued field: Ids and short strings are VARCHAR so
+/// they can be keys and be indexed
+fn mysql_text_type(field_type: SalesforceFieldType) -> &'static str {
+    match field_type {
+        SalesforceFieldType::Id
+        | SalesforceFieldType::Lookup
+        | SalesforceFieldType::MasterDetail
+        | SalesforceFieldType::Reference => "VARCHAR(18)",
+        SalesforceFieldType::TextArea
+        | SalesforceFieldType::LongTextArea
+        | SalesforceFieldType::RichTextArea
+        | SalesforceFieldType::MultiPicklist
+        | SalesforceFieldType::Address
+        | SalesforceFieldType::Location => "TEXT",
+        _ => "VARCHAR(255)",
+    }
+}
+
 /// Convert a Salesforce API name to snake_case for SQL
 fn to_snake_case(s: &str) -> String {
     let mut result = String::with_capacity(s.len() + 4);
@@ -422,4 +789,315 @@ mod tests {
         assert!(ddl.contains("\"what_id\" TEXT"));
         assert!(ddl.contains("\"what_id_type\" TEXT"));
     }
+
+    #[test]
+    fn test_upsert_statement_postgres() {
+        let schema = create_test_schema();
+        let generator = DdlGenerator::new(SqlDialect::Postgres);
+
+        let sql = generator
+            .upsert_statement("Account", &schema, "id")
+            .unwrap();
+
+        assert_eq!(
+            sql,
+            "INSERT INTO \"account\" (\"id\", \"name\", \"annual_revenue\", \"created_date\", \"industry\", \"is_deleted\") \
+             VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (\"id\") DO UPDATE SET \
+             \"name\" = excluded.\"name\", \"annual_revenue\" = excluded.\"annual_revenue\", \
+             \"created_date\" = excluded.\"created_date\", \"industry\" = excluded.\"industry\", \
+             \"is_deleted\" = excluded.\"is_deleted\""
+        );
+        assert!(generator
+            .upsert_statement("Account", &schema, "Missing")
+            .is_none());
+    }
+
+    #[test]
+    fn test_upsert_statement_sqlite() {
+        let schema = create_test_schema();
+        let generator = DdlGenerator::new(SqlDialect::Sqlite);
+
+        let sql = generator
+            .upsert_statement("Account", &schema, "Id")
+            .unwrap();
+
+        assert!(sql.starts_with("INSERT INTO \"account\" (\"id\", \"name\""));
+        assert!(sql.contains("VALUES (?1, ?2, ?3, ?4, ?5, ?6) ON CONFLICT (\"id\") DO UPDATE SET"));
+        assert!(!sql.contains("\"id\" = excluded"));
+    }
+
+    #[test]
+    fn test_upsert_statement_mysql() {
+        let schema = create_test_schema();
+        let generator = DdlGenerator::new(SqlDialect::Mysql);
+
+        let sql = generator
+            .upsert_statement("Account", &schema, "Id")
+            .unwrap();
+
+        assert!(sql.starts_with("INSERT INTO `account` (`id`, `name`"));
+        assert!(sql
+            .contains("VALUES (?, ?, ?, ?, ?, ?) ON DUPLICATE KEY UPDATE `name` = VALUES(`name`)"));
+        assert!(!sql.contains("`id` = VALUES"));
+    }
+
+    fn object_with_defaults() -> SObjectDescribe {
+        let mut product = SObjectDescribe::new("Product2");
+        product.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+        product.add_field(
+            FieldDescribe::new("IsActive", SalesforceFieldType::Boolean).with_default(true),
+        );
+        product
+            .add_field(FieldDescribe::new("Quantity", SalesforceFieldType::Double).with_default(1));
+        product.add_field(
+            FieldDescribe::new("Family", SalesforceFieldType::Picklist).with_default("O'Brien"),
+        );
+        product.add_field(
+            FieldDescribe::new("CreatedDate", SalesforceFieldType::DateTime)
+                .with_default(FieldDefault::CurrentTimestamp),
+        );
+        product
+    }
+
+    #[test]
+    fn test_field_defaults_postgres() {
+        let generator = DdlGenerator::new(SqlDialect::Postgres);
+        let ddl = generator.generate_table(&object_with_defaults());
+
+        assert!(ddl.contains("\"is_active\" BOOLEAN DEFAULT TRUE"));
+        assert!(ddl.contains("\"quantity\" NUMERIC DEFAULT 1"));
+        assert!(ddl.contains("\"family\" TEXT DEFAULT 'O''Brien'"));
+        assert!(ddl.contains("\"created_date\" TIMESTAMP DEFAULT CURRENT_TIMESTAMP"));
+    }
+
+    #[test]
+    fn test_field_defaults_sqlite() {
+        let generator = DdlGenerator::new(SqlDialect::Sqlite);
+        let ddl = generator.generate_table(&object_with_defaults());
+
+        assert!(ddl.contains("\"is_active\" INTEGER DEFAULT 1"));
+        assert!(ddl.contains("\"created_date\" TEXT DEFAULT (datetime('now'))"));
+    }
+
+    #[test]
+    fn test_field_defaults_mysql_escape_backslash() {
+        let mut object = SObjectDescribe::new("Share__c");
+        object.add_field(
+            FieldDescribe::new("Path__c", SalesforceFieldType::String).with_default(r"C:\"),
+        );
+        let generator = DdlGenerator::new(SqlDialect::Mysql);
+        let ddl = generator.generate_table(&object);
+
+        assert!(
+            ddl.contains(r"`path__c` VARCHAR(255) DEFAULT 'C:\\'"),
+            "{}",
+            ddl
+        );
+    }
+
+    #[test]
+    fn test_field_defaults_mysql_expressions() {
+        let mut object = SObjectDescribe::new("Case__c");
+        object.add_field(
+            FieldDescribe::new("Notes__c", SalesforceFieldType::TextArea).with_default("n/a"),
+        );
+        object.add_field(
+            FieldDescribe::new("Opened__c", SalesforceFieldType::Date)
+                .with_default(FieldDefault::CurrentTimestamp),
+        );
+        object.add_field(
+            FieldDescribe::new("Logged__c", SalesforceFieldType::DateTime)
+                .with_default(FieldDefault::CurrentTimestamp),
+        );
+        let generator = DdlGenerator::new(SqlDialect::Mysql);
+        let ddl = generator.generate_table(&object);
+
+        assert!(ddl.contains("`notes__c` TEXT DEFAULT ('n/a')"), "{}", ddl);
+        assert!(
+            ddl.contains("`opened__c` DATE DEFAULT (CURRENT_DATE)"),
+            "{}",
+            ddl
+        );
+        assert!(
+            ddl.contains("`logged__c` DATETIME DEFAULT CURRENT_TIMESTAMP"),
+            "{}",
+            ddl
+        );
+    }
+
+    #[test]
+    fn test_field_defaults_numbers_and_dates_sqlite() {
+        let mut object = SObjectDescribe::new("Visit__c");
+        object.add_field(
+            FieldDescribe::new("Rate__c", SalesforceFieldType::Percent).with_default(2.0),
+        );
+        object.add_field(
+            FieldDescribe::new("Score__c", SalesforceFieldType::Double).with_default(f64::NAN),
+        );
+        object.add_field(
+            FieldDescribe::new("Limit__c", SalesforceFieldType::Double).with_default(f64::INFINITY),
+        );
+        object.add_field(
+            FieldDescribe::new("Visited__c", SalesforceFieldType::Date)
+                .with_default(FieldDefault::CurrentTimestamp),
+        );
+        let generator = DdlGenerator::new(SqlDialect::Sqlite);
+        let ddl = generator.generate_table(&object);
+
+        assert!(ddl.contains("\"rate__c\" REAL DEFAULT 2.0"), "{}", ddl);
+        assert!(ddl.contains("\"score__c\" REAL,"), "{}", ddl);
+        assert!(ddl.contains("\"limit__c\" REAL,"), "{}", ddl);
+        assert!(
+            ddl.contains("\"visited__c\" DATE DEFAULT (date('now'))"),
+            "{}",
+            ddl
+        );
+    }
+
+    /// `Invoice__c` and `Payment__c` look each other up; `Payment__c` also
+    /// points at a parent outside the schema
+    fn lookup_cycle_schema() -> SalesforceSchema {
+        let mut schema = SalesforceSchema::new();
+        let mut invoice = SObjectDescribe::new("Invoice__c");
+        invoice.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+        invoice.add_field(
+            FieldDescribe::new("Last_Payment__c", SalesforceFieldType::Lookup)
+                .with_reference("Payment__c"),
+        );
+        schema.add_object(invoice);
+
+        let mut payment = SObjectDescribe::new("Payment__c");
+        payment.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+        payment.add_field(
+            FieldDescribe::new("Invoice__c", SalesforceFieldType::MasterDetail)
+                .with_reference("Invoice__c"),
+        );
+        payment.add_field(
+            FieldDescribe::new("Gateway__c", SalesforceFieldType::Lookup)
+                .with_reference("Gateway__c"),
+        );
+        schema.add_object(payment);
+        schema
+    }
+
+    #[test]
+    fn test_schema_creates_parents_first() {
+        let mut schema = create_test_schema();
+        let mut activity = SObjectDescribe::new("Activity__c");
+        activity.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+        activity.add_field(
+            FieldDescribe::new("Contact__c", SalesforceFieldType::Lookup).with_reference("Contact"),
+        );
+        schema.add_object(activity);
+
+        let ddl = DdlGenerator::new(SqlDialect::Postgres).generate_schema(&schema);
+        let account = ddl.find("CREATE TABLE \"account\"").unwrap();
+        let contact = ddl.find("CREATE TABLE \"contact\"").unwrap();
+        let activity = ddl.find("CREATE TABLE \"activity__c\"").unwrap();
+        assert!(account < contact && contact < activity);
+        assert!(ddl.contains("FOREIGN KEY (\"contact__c\") REFERENCES \"contact\"(id)"));
+        assert!(!ddl.contains("ALTER TABLE"));
+
+        let drop = DdlGenerator::new(SqlDialect::Postgres).generate_drop_schema(&schema);
+        assert!(drop.find("\"activity__c\"").unwrap() < drop.find("\"account\"").unwrap());
+    }
+
+    #[test]
+    fn test_lookup_cycle_adds_constraint_after_tables() {
+        let ddl = DdlGenerator::new(SqlDialect::Postgres).generate_schema(&lookup_cycle_schema());
+
+        assert!(ddl.contains("FOREIGN KEY (\"invoice__c\") REFERENCES \"invoice__c\"(id)"));
+        let alter = ddl
+            .find(
+                "ALTER TABLE \"invoice__c\" ADD CONSTRAINT \"fk_invoice__c_last_payment__c\" \
+                 FOREIGN KEY (\"last_payment__c\") REFERENCES \"payment__c\"(id);",
+            )
+            .unwrap();
+        assert!(alter > ddl.find("CREATE TABLE \"payment__c\"").unwrap());
+        // Gateway__c isn't in the schema
+        assert!(!ddl.contains("gateway__c\"(id)"));
+    }
+
+    #[test]
+    fn test_lookup_cycle_stays_inline_on_sqlite() {
+        let ddl = DdlGenerator::new(SqlDialect::Sqlite).generate_schema(&lookup_cycle_schema());
+
+        assert!(ddl.contains("FOREIGN KEY (\"last_payment__c\") REFERENCES \"payment__c\"(id)"));
+        assert!(!ddl.contains("ALTER TABLE"));
+    }
+
+    #[test]
+    fn test_foreign_keys_skip_polymorphic_and_multi_target_fields() {
+        let mut event = SObjectDescribe::new("Event");
+        event.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+        event.add_field(
+            FieldDescribe::new("WhatId", SalesforceFieldType::Reference)
+                .with_polymorphic_reference(vec!["Account".to_string(), "Opportunity".to_string()]),
+        );
+        event.add_field(
+            FieldDescribe::new("OwnerId", SalesforceFieldType::Reference).with_reference("User"),
+        );
+        event.add_field(
+            FieldDescribe::new("ParentId", SalesforceFieldType::Lookup).with_reference("Event"),
+        );
+
+        let ddl = DdlGenerator::new(SqlDialect::Mysql).generate_table(&event);
+        assert!(ddl.contains("FOREIGN KEY (`parent_id`) REFERENCES `event`(id)"));
+        assert_eq!(ddl.matches("FOREIGN KEY").count(), 1);
+    }
+
+    #[test]
+    fn test_foreign_keys_disabled() {
+        let generator = DdlGenerator::new(SqlDialect::Postgres).with_options(DdlOptions {
+            foreign_keys: false,
+            ..Default::default()
+        });
+        let ddl = generator.generate_schema(&lookup_cycle_schema());
+
+        assert!(!ddl.contains("FOREIGN KEY"));
+        assert!(!ddl.contains("ALTER TABLE"));
+        assert!(ddl.find("\"invoice__c\" (").unwrap() < ddl.find("\"payment__c\" (").unwrap());
+    }
+
+    #[test]
+    fn test_self_lookup_and_polymorphic_fields_in_schema() {
+        let mut schema = create_test_schema();
+        let account = schema.get_object_mut("Account").unwrap();
+        account.add_field(
+            FieldDescribe::new("ParentId", SalesforceFieldType::Lookup).with_reference("Account"),
+        );
+        let mut task = SObjectDescribe::new("Task");
+        task.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+        task.add_field(
+            FieldDescribe::new("WhatId", SalesforceFieldType::Reference)
+                .with_polymorphic_reference(vec!["Account".to_string(), "Contact".to_string()]),
+        );
+        task.add_field(
+            FieldDescribe::new("AccountId", SalesforceFieldType::Reference)
+                .with_reference("Account"),
+        );
+        schema.add_object(task);
+
+        let ddl = DdlGenerator::new(SqlDialect::Postgres).generate_schema(&schema);
+        assert!(ddl.contains("FOREIGN KEY (\"parent_id\") REFERENCES \"account\"(id)"));
+        assert!(ddl.contains("FOREIGN KEY (\"account_id\") REFERENCES \"account\"(id)"));
+        assert!(!ddl.contains("FOREIGN KEY (\"what_id\")"));
+        assert!(!ddl.contains("ALTER TABLE"));
+        assert!(ddl.contains("CREATE INDEX \"idx_account_parent_id\""));
+        assert!(ddl.contains("CREATE INDEX \"idx_task_what_id\""));
+        assert!(ddl.contains("CREATE INDEX \"idx_task_account_id\""));
+    }
+
+    #[test]
+    fn test_indexes_disabled() {
+        let generator = DdlGenerator::new(SqlDialect::Sqlite).with_indexes(false);
+        let ddl = generator.generate_schema(&lookup_cycle_schema());
+
+        assert!(!ddl.contains("CREATE INDEX"));
+        assert!(ddl.contains("FOREIGN KEY (\"invoice__c\") REFERENCES \"invoice__c\"(id)"));
+        let ddl = generator
+            .with_foreign_keys(false)
+            .generate_schema(&lookup_cycle_schema());
+        assert!(!ddl.contains("FOREIGN KEY"));
+    }
 }
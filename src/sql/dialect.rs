@@ -1,11 +1,51 @@
-//! SQL dialect abstraction for SQLite and PostgreSQL compatibility
+//! SQL dialect abstraction for SQLite, PostgreSQL and MySQL compatibility
+
+use std::collections::HashMap;
+
+use crate::ast::GroupingSets;
 
 /// Supported SQL dialects
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum SqlDialect {
     #[default]
     Postgres,
     Sqlite,
+    /// MySQL 8 and MariaDB
+    Mysql,
+}
+
+/// What a schema qualifier means on SQLite, which has no schemas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum SqliteQualifier {
+    /// Leave table names unqualified (the converter warns once per qualifier)
+    #[default]
+    Ignore,
+    /// Treat the qualifier as the name of an ATTACHed database
+    AttachedDatabase,
+}
+
+/// Schema for an object's table: its entry in `overrides` (keyed by API
+/// name, case-insensitive), else `default`
+pub(crate) fn resolve_qualifier<'a>(
+    default: Option<&'a str>,
+    overrides: &'a HashMap<String, String>,
+    object: &str,
+) -> Option<&'a str> {
+    overrides
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(object))
+        .map(|(_, schema)| schema.as_str())
+        .or(default)
 }
 
 /// Trait for dialect-specific SQL generation
@@ -18,15 +58,43 @@ pub trait SqlDialectImpl {
         format!("\"{}\"", name.replace('"', "\"\""))
     }
 
+    /// Quote a table name, prefixed with its schema when there is one
+    fn qualified_table(&self, qualifier: Option<&str>, table: &str) -> String {
+        match qualifier {
+            Some(qualifier) => format!(
+                "{}.{}",
+                self.quote_identifier(qualifier),
+                self.quote_identifier(table)
+            ),
+            None => self.quote_identifier(table),
+        }
+    }
+
+    /// A string literal holding `value`
+    fn string_literal(&self, value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
     /// Generate parameter placeholder for bind variable
     fn parameter_placeholder(&self, index: usize) -> String;
 
+    /// Whether placeholders are bound by position rather than by number,
+    /// so parameters must be listed in the order they appear in the SQL
+    fn positional_parameters(&self) -> bool {
+        false
+    }
+
     /// Current timestamp function
     fn current_timestamp(&self) -> &str;
 
     /// Current date function
     fn current_date(&self) -> &str;
 
+    /// A date given as `YYYY-MM-DD`, in place of [`current_date`](Self::current_date)
+    fn date_literal(&self, date: &str) -> String {
+        format!("DATE '{}'", date)
+    }
+
     /// Date arithmetic: add interval
     fn date_add(&self, date_expr: &str, amount: i32, unit: DateUnit) -> String;
 
@@ -49,6 +117,40 @@ pub trait SqlDialectImpl {
         "NULLS LAST"
     }
 
+    /// ORDER BY key with explicit placement of nulls
+    fn order_nulls(&self, key: &str, descending: bool, nulls_first: bool) -> String {
+        format!(
+            "{}{} {}",
+            key,
+            if descending { " DESC" } else { "" },
+            if nulls_first {
+                self.nulls_first()
+            } else {
+                self.nulls_last()
+            }
+        )
+    }
+
+    /// Case-insensitive ORDER BY / GROUP BY key for a string expression
+    fn case_insensitive_key(&self, expr: &str) -> String;
+
+    /// ORDER BY / GROUP BY key using a named collation
+    fn collate(&self, expr: &str, collation: &str) -> String {
+        format!("{} COLLATE {}", expr, self.quote_identifier(collation))
+    }
+
+    /// Whether a collated GROUP BY key is an expression the select list
+    /// cannot read the bare column of, so grouped fields go through MIN()
+    fn group_key_wraps_expr(&self) -> bool {
+        false
+    }
+
+    /// GROUP BY list for ROLLUP or CUBE over `keys` (returns None if not
+    /// supported)
+    fn grouping_sets(&self, sets: GroupingSets, keys: &str) -> Option<String> {
+        Some(format!("{} ({})", sets.as_str(), keys))
+    }
+
     /// FOR UPDATE clause (returns None if not supported)
     fn for_update(&self) -> Option<&str>;
 
@@ -61,9 +163,10 @@ pub trait SqlDialectImpl {
     /// String concatenation
     fn concat(&self, exprs: &[String]) -> String;
 
-    /// LIKE escape character (if needed)
+    /// Character named in an ESCAPE clause when a LIKE pattern escapes `%`
+    /// or `_`; `None` when backslash is already LIKE's escape character
     fn like_escape(&self) -> Option<&str> {
-        None
+        Some("\\")
     }
 
     /// LIMIT/OFFSET syntax
@@ -174,6 +277,15 @@ impl SqlDialectImpl for PostgresDialect {
         }
     }
 
+    fn case_insensitive_key(&self, expr: &str) -> String {
+        // Column collations are usually byte-wise ("C") or case-sensitive ICU
+        format!("LOWER({})", expr)
+    }
+
+    fn group_key_wraps_expr(&self) -> bool {
+        true
+    }
+
     fn for_update(&self) -> Option<&str> {
         Some("FOR UPDATE")
     }
@@ -216,6 +328,10 @@ impl SqlDialectImpl for SqliteDialect {
         "date('now')"
     }
 
+    fn date_literal(&self, date: &str) -> String {
+        format!("date('{}')", date)
+    }
+
     fn date_add(&self, date_expr: &str, amount: i32, unit: DateUnit) -> String {
         let (actual_amount, modifier) = match unit {
             DateUnit::Week => (amount * 7, "days"),
@@ -275,6 +391,14 @@ impl SqlDialectImpl for SqliteDialect {
         }
     }
 
+    fn case_insensitive_key(&self, expr: &str) -> String {
+        format!("{} COLLATE NOCASE", expr)
+    }
+
+    fn grouping_sets(&self, _sets: GroupingSets, _keys: &str) -> Option<String> {
+        None
+    }
+
     fn for_update(&self) -> Option<&str> {
         // SQLite doesn't support FOR UPDATE (uses file-level locking)
         None
@@ -295,6 +419,175 @@ impl SqlDialectImpl for SqliteDialect {
     fn concat(&self, exprs: &[String]) -> String {
         exprs.join(" || ")
     }
+
+    fn limit_offset(&self, limit: Option<&str>, offset: Option<&str>) -> String {
+        match (limit, offset) {
+            (Some(l), Some(o)) => format!("LIMIT {} OFFSET {}", l, o),
+            (Some(l), None) => format!("LIMIT {}", l),
+            // OFFSET needs a LIMIT; a negative one means no limit
+            (None, Some(o)) => format!("LIMIT -1 OFFSET {}", o),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// MySQL dialect implementation
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MysqlDialect;
+
+impl SqlDialectImpl for MysqlDialect {
+    fn dialect(&self) -> SqlDialect {
+        SqlDialect::Mysql
+    }
+
+    fn quote_identifier(&self, name: &str) -> String {
+        format!("`{}`", name.replace('`', "``"))
+    }
+
+    fn string_literal(&self, value: &str) -> String {
+        // Backslash starts an escape sequence unless the server runs with
+        // NO_BACKSLASH_ESCAPES, which the default sql_mode leaves off
+        format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+    }
+
+    fn parameter_placeholder(&self, _index: usize) -> String {
+        "?".to_string()
+    }
+
+    fn positional_parameters(&self) -> bool {
+        true
+    }
+
+    fn current_timestamp(&self) -> &str {
+        "CURRENT_TIMESTAMP"
+    }
+
+    fn current_date(&self) -> &str {
+        "CURRENT_DATE"
+    }
+
+    fn date_add(&self, date_expr: &str, amount: i32, unit: DateUnit) -> String {
+        format!(
+            "DATE_ADD({}, INTERVAL {} {})",
+            date_expr,
+            amount,
+            unit.as_str().to_uppercase()
+        )
+    }
+
+    fn date_sub(&self, date_expr: &str, amount: i32, unit: DateUnit) -> String {
+        format!(
+            "DATE_SUB({}, INTERVAL {} {})",
+            date_expr,
+            amount,
+            unit.as_str().to_uppercase()
+        )
+    }
+
+    fn date_trunc(&self, unit: DateUnit, date_expr: &str) -> String {
+        // MySQL has no date_trunc; rebuild the date from its parts
+        match unit {
+            DateUnit::Day => format!("DATE({})", date_expr),
+            DateUnit::Week => {
+                // Start of week (assuming Sunday)
+                format!(
+                    "DATE_SUB(DATE({}), INTERVAL DAYOFWEEK({}) - 1 DAY)",
+                    date_expr, date_expr
+                )
+            }
+            DateUnit::Month => format!(
+                "(MAKEDATE(YEAR({}), 1) + INTERVAL (MONTH({}) - 1) MONTH)",
+                date_expr, date_expr
+            ),
+            DateUnit::Quarter => format!(
+                "(MAKEDATE(YEAR({}), 1) + INTERVAL (QUARTER({}) - 1) QUARTER)",
+                date_expr, date_expr
+            ),
+            DateUnit::Year => format!("MAKEDATE(YEAR({}), 1)", date_expr),
+            DateUnit::Hour => format!(
+                "CAST(DATE_FORMAT({}, '%Y-%m-%d %H:00:00') AS DATETIME)",
+                date_expr
+            ),
+            DateUnit::Minute => format!(
+                "CAST(DATE_FORMAT({}, '%Y-%m-%d %H:%i:00') AS DATETIME)",
+                date_expr
+            ),
+            DateUnit::Second => format!(
+                "CAST(DATE_FORMAT({}, '%Y-%m-%d %H:%i:%s') AS DATETIME)",
+                date_expr
+            ),
+        }
+    }
+
+    fn boolean_literal(&self, value: bool) -> &str {
+        if value {
+            "TRUE"
+        } else {
+            "FALSE"
+        }
+    }
+
+    fn order_nulls(&self, key: &str, descending: bool, nulls_first: bool) -> String {
+        // No NULLS FIRST/LAST: sort on IS NULL (1 for nulls) ahead of the key
+        format!(
+            "{} IS NULL {}, {}{}",
+            key,
+            if nulls_first { "DESC" } else { "ASC" },
+            key,
+            if descending { " DESC" } else { "" }
+        )
+    }
+
+    fn case_insensitive_key(&self, expr: &str) -> String {
+        // The default collations ignore case, but binary ones are common
+        format!("LOWER({})", expr)
+    }
+
+    fn group_key_wraps_expr(&self) -> bool {
+        // ONLY_FULL_GROUP_BY rejects a bare column grouped as LOWER(column)
+        true
+    }
+
+    fn grouping_sets(&self, sets: GroupingSets, keys: &str) -> Option<String> {
+        match sets {
+            GroupingSets::Rollup => Some(format!("{} WITH ROLLUP", keys)),
+            GroupingSets::Cube => None,
+        }
+    }
+
+    fn for_update(&self) -> Option<&str> {
+        Some("FOR UPDATE")
+    }
+
+    fn json_array_agg(&self, inner_expr: &str) -> String {
+        format!("JSON_ARRAYAGG({})", inner_expr)
+    }
+
+    fn json_object(&self, pairs: &[(String, String)]) -> String {
+        let args: Vec<String> = pairs
+            .iter()
+            .flat_map(|(k, v)| vec![format!("'{}'", k), v.clone()])
+            .collect();
+        format!("JSON_OBJECT({})", args.join(", "))
+    }
+
+    fn concat(&self, exprs: &[String]) -> String {
+        format!("CONCAT({})", exprs.join(", "))
+    }
+
+    fn like_escape(&self) -> Option<&str> {
+        None
+    }
+
+    fn limit_offset(&self, limit: Option<&str>, offset: Option<&str>) -> String {
+        match (limit, offset) {
+            (Some(l), Some(o)) => format!("LIMIT {} OFFSET {}", l, o),
+            (Some(l), None) => format!("LIMIT {}", l),
+            // OFFSET is only allowed after a LIMIT; this is the largest one
+            (None, Some(o)) => format!("LIMIT 18446744073709551615 OFFSET {}", o),
+            (None, None) => String::new(),
+        }
+    }
 }
 
 /// Get dialect implementation for a given dialect type
@@ -302,6 +595,7 @@ pub fn get_dialect(dialect: SqlDialect) -> Box<dyn SqlDialectImpl> {
     match dialect {
         SqlDialect::Postgres => Box::new(PostgresDialect),
         SqlDialect::Sqlite => Box::new(SqliteDialect),
+        SqlDialect::Mysql => Box::new(MysqlDialect),
     }
 }
 
@@ -366,5 +660,28 @@ mod tests {
 
         let sqlite = SqliteDialect;
         assert_eq!(sqlite.json_array_agg("row"), "json_group_array(row)");
+
+        let mysql = MysqlDialect;
+        assert_eq!(mysql.json_array_agg("row"), "JSON_ARRAYAGG(row)");
+    }
+
+    #[test]
+    fn test_mysql_dialect() {
+        let dialect = MysqlDialect;
+        assert_eq!(dialect.quote_identifier("weird`name"), "`weird``name`");
+        assert_eq!(dialect.parameter_placeholder(3), "?");
+        assert_eq!(dialect.string_literal(r"it's C:\"), r"'it''s C:\\'");
+        assert_eq!(
+            dialect.date_add("CURRENT_DATE", 30, DateUnit::Day),
+            "DATE_ADD(CURRENT_DATE, INTERVAL 30 DAY)"
+        );
+        assert_eq!(
+            dialect.order_nulls("t0.name", true, false),
+            "t0.name IS NULL ASC, t0.name DESC"
+        );
+        assert_eq!(
+            dialect.limit_offset(None, Some("10")),
+            "LIMIT 18446744073709551615 OFFSET 10"
+        );
     }
 }
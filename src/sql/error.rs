@@ -3,6 +3,7 @@
 use thiserror::Error;
 
 use super::dialect::SqlDialect;
+use crate::lexer::Span;
 
 /// Errors that can occur during SOQL to SQL conversion
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -10,15 +11,31 @@ pub enum ConversionError {
     #[error("Unknown SObject: {0}")]
     UnknownObject(String),
 
+    /// `span` is the field's own in WHERE and HAVING, and the whole query's
+    /// elsewhere, since other clauses keep fields as plain names
     #[error("Unknown field '{field}' on object '{object}'")]
-    UnknownField { object: String, field: String },
+    UnknownField {
+        object: String,
+        field: String,
+        span: Option<Span>,
+    },
 
-    #[error("Field '{0}' is not a relationship field")]
-    NotARelationship(String),
+    #[error("Field '{relationship}' is not a relationship field")]
+    NotARelationship {
+        relationship: String,
+        span: Option<Span>,
+    },
 
     #[error("Field '{0}' is not polymorphic")]
     NotPolymorphic(String),
 
+    #[error("Field '{field}' does not exist on any target of polymorphic relationship '{relationship}' ({})", .targets.join(", "))]
+    PolymorphicFieldNotFound {
+        relationship: String,
+        field: String,
+        targets: Vec<String>,
+    },
+
     #[error("Unknown date literal: {0}")]
     UnknownDateLiteral(String),
 
@@ -34,11 +51,52 @@ pub enum ConversionError {
     #[error("Child relationship '{0}' not found on object '{1}'")]
     UnknownChildRelationship(String, String),
 
+    #[error("Child subquery on '{relationship}' cannot be combined with aggregate {aggregate}() in the same SELECT")]
+    InvalidSelectCombination {
+        relationship: String,
+        aggregate: String,
+    },
+
     #[error("Invalid SOQL expression: {0}")]
     InvalidExpression(String),
 
     #[error("Unsupported SOQL feature: {0}")]
     UnsupportedSoqlFeature(String),
+
+    #[error("Query on '{object}' would be rejected by Salesforce: {reason}")]
+    UnsupportedForObject { object: String, reason: String },
+
+    #[error("Conversion cancelled")]
+    Cancelled,
+
+    /// A warning whose kind [`WarningFilter`](super::WarningFilter) promotes
+    #[error("{0} [{code}]", code = .0.kind())]
+    PromotedWarning(ConversionWarning),
+}
+
+impl ConversionError {
+    /// Where in the Apex source the offending SOQL is, when known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ConversionError::UnknownField { span, .. }
+            | ConversionError::NotARelationship { span, .. } => *span,
+            _ => None,
+        }
+    }
+
+    /// Whether both report the same problem, wherever each occurred
+    pub(crate) fn same_as(&self, other: &ConversionError) -> bool {
+        let unlocated = |error: &ConversionError| {
+            let mut error = error.clone();
+            if let ConversionError::UnknownField { span, .. }
+            | ConversionError::NotARelationship { span, .. } = &mut error
+            {
+                *span = None;
+            }
+            error
+        };
+        unlocated(self) == unlocated(other)
+    }
 }
 
 /// Warnings that may occur during conversion (non-fatal)
@@ -47,13 +105,130 @@ pub enum ConversionWarning {
     /// FOR UPDATE is not supported in SQLite
     ForUpdateNotSupported,
     /// Salesforce-only clause was removed (e.g., FOR VIEW, FOR REFERENCE)
-    SalesforceOnlyClause(String),
+    SalesforceOnlyClause { clause: String },
     /// Polymorphic field accessed without TYPEOF
-    PolymorphicFieldWithoutTypeof(String),
+    PolymorphicFieldWithoutTypeof { field: String },
+    /// Field read through a polymorphic relationship is missing on some targets
+    PolymorphicFieldMissingOnTargets {
+        relationship: String,
+        field: String,
+        targets: Vec<String>,
+    },
     /// Date literal translation may be approximate
-    ApproximateDateLiteral(String),
+    ApproximateDateLiteral { literal: String },
     /// WITH clause (security) was removed
-    SecurityClauseRemoved(String),
+    SecurityClauseRemoved { clause: String },
+    /// Schema qualifier dropped because SQLite has no schemas
+    SchemaQualifierIgnored { schema: String },
+    /// Soft-delete filter not applied to an object missing from the schema
+    DeletedFilterSkipped { object: String },
+    /// Query breaks a big object / external object restriction
+    UnsupportedForObject { object: String, reason: String },
+}
+
+impl ConversionWarning {
+    pub fn kind(&self) -> WarningKind {
+        match self {
+            ConversionWarning::ForUpdateNotSupported => WarningKind::ForUpdateNotSupported,
+            ConversionWarning::SalesforceOnlyClause { .. } => WarningKind::SalesforceOnlyClause,
+            ConversionWarning::PolymorphicFieldWithoutTypeof { .. } => {
+                WarningKind::PolymorphicFieldWithoutTypeof
+            }
+            ConversionWarning::PolymorphicFieldMissingOnTargets { .. } => {
+                WarningKind::PolymorphicFieldMissingOnTargets
+            }
+            ConversionWarning::ApproximateDateLiteral { .. } => WarningKind::ApproximateDateLiteral,
+            ConversionWarning::SecurityClauseRemoved { .. } => WarningKind::SecurityClauseRemoved,
+            ConversionWarning::SchemaQualifierIgnored { .. } => WarningKind::SchemaQualifierIgnored,
+            ConversionWarning::DeletedFilterSkipped { .. } => WarningKind::DeletedFilterSkipped,
+            ConversionWarning::UnsupportedForObject { .. } => WarningKind::UnsupportedForObject,
+        }
+    }
+
+    pub fn severity(&self) -> WarningSeverity {
+        self.kind().severity()
+    }
+}
+
+/// How much a warning matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WarningSeverity {
+    /// The SQL means the same as the SOQL; something Salesforce-specific
+    /// was dropped
+    Info,
+    /// The SQL may return different rows or behave differently
+    Warning,
+}
+
+/// Stable identifier of a [`ConversionWarning`] variant
+///
+/// The codes returned by [`code`](Self::code) are part of the public
+/// interface: they are what build configuration and reports refer to, and
+/// existing codes never change meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WarningKind {
+    ForUpdateNotSupported,
+    SalesforceOnlyClause,
+    PolymorphicFieldWithoutTypeof,
+    PolymorphicFieldMissingOnTargets,
+    ApproximateDateLiteral,
+    SecurityClauseRemoved,
+    SchemaQualifierIgnored,
+    DeletedFilterSkipped,
+    UnsupportedForObject,
+}
+
+impl WarningKind {
+    /// Every kind, in code order
+    pub const ALL: [WarningKind; 9] = [
+        WarningKind::ForUpdateNotSupported,
+        WarningKind::SalesforceOnlyClause,
+        WarningKind::PolymorphicFieldWithoutTypeof,
+        WarningKind::PolymorphicFieldMissingOnTargets,
+        WarningKind::ApproximateDateLiteral,
+        WarningKind::SecurityClauseRemoved,
+        WarningKind::SchemaQualifierIgnored,
+        WarningKind::DeletedFilterSkipped,
+        WarningKind::UnsupportedForObject,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            WarningKind::ForUpdateNotSupported => "for-update-not-supported",
+            WarningKind::SalesforceOnlyClause => "salesforce-only-clause",
+            WarningKind::PolymorphicFieldWithoutTypeof => "polymorphic-field-without-typeof",
+            WarningKind::PolymorphicFieldMissingOnTargets => "polymorphic-field-missing-on-targets",
+            WarningKind::ApproximateDateLiteral => "approximate-date-literal",
+            WarningKind::SecurityClauseRemoved => "security-clause-removed",
+            WarningKind::SchemaQualifierIgnored => "schema-qualifier-ignored",
+            WarningKind::DeletedFilterSkipped => "deleted-filter-skipped",
+            WarningKind::UnsupportedForObject => "unsupported-for-object",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.code() == code)
+    }
+
+    pub fn severity(self) -> WarningSeverity {
+        match self {
+            WarningKind::SalesforceOnlyClause
+            | WarningKind::SecurityClauseRemoved
+            | WarningKind::SchemaQualifierIgnored => WarningSeverity::Info,
+            WarningKind::ForUpdateNotSupported
+            | WarningKind::PolymorphicFieldWithoutTypeof
+            | WarningKind::PolymorphicFieldMissingOnTargets
+            | WarningKind::ApproximateDateLiteral
+            | WarningKind::DeletedFilterSkipped
+            | WarningKind::UnsupportedForObject => WarningSeverity::Warning,
+        }
+    }
+}
+
+impl std::fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
 }
 
 impl std::fmt::Display for ConversionWarning {
@@ -62,22 +237,56 @@ impl std::fmt::Display for ConversionWarning {
             ConversionWarning::ForUpdateNotSupported => {
                 write!(f, "FOR UPDATE is not supported in this SQL dialect")
             }
-            ConversionWarning::SalesforceOnlyClause(clause) => {
+            ConversionWarning::SalesforceOnlyClause { clause } => {
                 write!(f, "Salesforce-only clause removed: {}", clause)
             }
-            ConversionWarning::PolymorphicFieldWithoutTypeof(field) => {
+            ConversionWarning::PolymorphicFieldWithoutTypeof { field } => {
                 write!(
                     f,
                     "Polymorphic field '{}' accessed without TYPEOF",
                     field
                 )
             }
-            ConversionWarning::ApproximateDateLiteral(literal) => {
+            ConversionWarning::PolymorphicFieldMissingOnTargets {
+                relationship,
+                field,
+                targets,
+            } => {
+                write!(
+                    f,
+                    "Field '{}.{}' is not available on {}; those rows read NULL",
+                    relationship,
+                    field,
+                    targets.join(", ")
+                )
+            }
+            ConversionWarning::ApproximateDateLiteral { literal } => {
                 write!(f, "Date literal '{}' translation may be approximate", literal)
             }
-            ConversionWarning::SecurityClauseRemoved(clause) => {
+            ConversionWarning::SecurityClauseRemoved { clause } => {
                 write!(f, "Security clause removed: {}", clause)
             }
+            ConversionWarning::SchemaQualifierIgnored { schema } => {
+                write!(
+                    f,
+                    "Schema qualifier '{}' ignored: SQLite tables are unqualified unless it names an attached database",
+                    schema
+                )
+            }
+            ConversionWarning::DeletedFilterSkipped { object } => {
+                write!(
+                    f,
+                    "Soft-delete filter not applied to '{}': no schema says whether it has IsDeleted",
+                    object
+                )
+            }
+            ConversionWarning::UnsupportedForObject { object, reason } => {
+                write!(
+                    f,
+                    "Query on '{}' would be rejected by Salesforce: {}",
+                    object, reason
+                )
+            }
         }
     }
 }
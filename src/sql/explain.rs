@@ -0,0 +1,236 @@
+//! Query plan inspection
+//!
+//! [`SqlConversion::explain_sql`](super::SqlConversion::explain_sql)
+//! renders an EXPLAIN statement for any dialect. For SQLite, [`ExplainReport`] turns the rows of
+//! `EXPLAIN QUERY PLAN` into a tree that tests can assert on, e.g. that a
+//! lookup filter uses the index [`DdlGenerator`](super::DdlGenerator)
+//! created for it rather than scanning the table.
+//!
+//! With the `test-utils` feature, [`run_sqlite`] executes the statement on a
+//! rusqlite connection and builds the report.
+
+/// How a plan step reads its table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanAccess {
+    /// Every row is visited (`SCAN`)
+    Scan,
+    /// Rows are looked up through an index or key (`SEARCH`)
+    Search,
+    /// Any other step, such as `USE TEMP B-TREE FOR ORDER BY`
+    Other,
+}
+
+/// One row of `EXPLAIN QUERY PLAN` with its child steps
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanNode {
+    /// Row id assigned by SQLite
+    pub id: i64,
+    /// The plan text, e.g. `SEARCH t1 USING INDEX idx_contact_account_id (account_id=?)`
+    pub detail: String,
+    pub access: PlanAccess,
+    /// Table or alias read by a scan or search
+    pub table: Option<String>,
+    /// Index used by a search; `PRIMARY KEY` for rowid lookups
+    pub index: Option<String>,
+    pub children: Vec<PlanNode>,
+}
+
+impl PlanNode {
+    fn new(id: i64, detail: String) -> Self {
+        let (access, rest) = if let Some(rest) = detail.strip_prefix("SCAN ") {
+            (PlanAccess::Scan, Some(rest))
+        } else if let Some(rest) = detail.strip_prefix("SEARCH ") {
+            (PlanAccess::Search, Some(rest))
+        } else {
+            (PlanAccess::Other, None)
+        };
+
+        let (table, index) = match rest {
+            Some(rest) => parse_access(rest),
+            None => (None, None),
+        };
+
+        PlanNode {
+            id,
+            detail,
+            access,
+            table,
+            index,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Split `[TABLE] name [AS alias] [USING ...]` into the table (the alias
+/// when given) and the index
+fn parse_access(rest: &str) -> (Option<String>, Option<String>) {
+    let rest = rest.strip_prefix("TABLE ").unwrap_or(rest);
+    let (target, using) = match rest.split_once(" USING ") {
+        Some((target, using)) => (target, Some(using)),
+        None => (rest, None),
+    };
+    let table = match target.split_once(" AS ") {
+        Some((_, alias)) => alias,
+        None => target.split_whitespace().next().unwrap_or(target),
+    };
+
+    let index = using.and_then(|using| {
+        let using = using.strip_prefix("AUTOMATIC ").unwrap_or(using);
+        let using = using.strip_prefix("COVERING ").unwrap_or(using);
+        if let Some(index) = using.strip_prefix("INDEX ") {
+            index.split_whitespace().next().map(str::to_string)
+        } else if using.contains("PRIMARY KEY") {
+            Some("PRIMARY KEY".to_string())
+        } else {
+            None
+        }
+    });
+
+    (Some(table.to_string()), index)
+}
+
+/// The plan SQLite chose for a converted query
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplainReport {
+    /// The EXPLAIN statement that produced the plan
+    pub sql: String,
+    /// Top-level plan steps
+    pub nodes: Vec<PlanNode>,
+}
+
+impl ExplainReport {
+    /// Build the tree from `(id, parent, detail)` rows in the order SQLite
+    /// returns them; a parent of 0 marks a top-level step
+    pub fn from_rows(
+        sql: impl Into<String>,
+        rows: impl IntoIterator<Item = (i64, i64, String)>,
+    ) -> Self {
+        let mut flat: Vec<(i64, PlanNode)> = rows
+            .into_iter()
+            .map(|(id, parent, detail)| (parent, PlanNode::new(id, detail)))
+            .collect();
+
+        // Children always follow their parent, so attaching from the end
+        // finishes each subtree before its parent moves
+        let mut nodes = Vec::new();
+        while let Some((parent, node)) = flat.pop() {
+            match flat.iter_mut().rev().find(|(_, n)| n.id == parent) {
+                Some((_, parent)) => parent.children.insert(0, node),
+                None => nodes.insert(0, node),
+            }
+        }
+
+        ExplainReport {
+            sql: sql.into(),
+            nodes,
+        }
+    }
+
+    /// All steps, depth first
+    pub fn steps(&self) -> Vec<&PlanNode> {
+        fn walk<'a>(nodes: &'a [PlanNode], out: &mut Vec<&'a PlanNode>) {
+            for node in nodes {
+                out.push(node);
+                walk(&node.children, out);
+            }
+        }
+        let mut out = Vec::new();
+        walk(&self.nodes, &mut out);
+        out
+    }
+
+    /// The scan or search step reading `table` (an alias such as `t1`)
+    pub fn access(&self, table: &str) -> Option<&PlanNode> {
+        self.steps()
+            .into_iter()
+            .find(|node| node.table.as_deref() == Some(table))
+    }
+
+    /// Tables read by a full scan
+    pub fn scanned_tables(&self) -> Vec<&str> {
+        self.steps()
+            .into_iter()
+            .filter(|node| node.access == PlanAccess::Scan)
+            .filter_map(|node| node.table.as_deref())
+            .collect()
+    }
+}
+
+/// Run `EXPLAIN QUERY PLAN` for a SQLite conversion and collect the plan
+#[cfg(feature = "test-utils")]
+pub fn run_sqlite(
+    conversion: &super::SqlConversion,
+    conn: &rusqlite::Connection,
+) -> rusqlite::Result<ExplainReport> {
+    let sql = conversion.explain_sql(false);
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(3)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(ExplainReport::from_rows(sql, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(rows: &[(i64, i64, &str)]) -> Vec<(i64, i64, String)> {
+        rows.iter()
+            .map(|(id, parent, detail)| (*id, *parent, detail.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_tree() {
+        let report = ExplainReport::from_rows(
+            "EXPLAIN QUERY PLAN ...",
+            rows(&[
+                (2, 0, "SCAN t0"),
+                (
+                    5,
+                    0,
+                    "SEARCH t1 USING INDEX idx_contact_account_id (account_id=?)",
+                ),
+                (9, 0, "CORRELATED SCALAR SUBQUERY 1"),
+                (
+                    12,
+                    9,
+                    "SEARCH c USING COVERING INDEX sqlite_autoindex_case_1 (id=?)",
+                ),
+                (20, 0, "USE TEMP B-TREE FOR ORDER BY"),
+            ]),
+        );
+
+        assert_eq!(report.nodes.len(), 4);
+        assert_eq!(report.nodes[2].children.len(), 1);
+        assert_eq!(report.scanned_tables(), ["t0"]);
+
+        let t1 = report.access("t1").unwrap();
+        assert_eq!(t1.access, PlanAccess::Search);
+        assert_eq!(t1.index.as_deref(), Some("idx_contact_account_id"));
+
+        let nested = report.access("c").unwrap();
+        assert_eq!(nested.index.as_deref(), Some("sqlite_autoindex_case_1"));
+        assert_eq!(report.nodes[3].access, PlanAccess::Other);
+    }
+
+    #[test]
+    fn test_legacy_detail_format() {
+        let report = ExplainReport::from_rows(
+            "",
+            rows(&[
+                (3, 0, "SCAN TABLE account AS t0"),
+                (
+                    4,
+                    0,
+                    "SEARCH TABLE contact AS t1 USING INTEGER PRIMARY KEY (rowid=?)",
+                ),
+            ]),
+        );
+        assert_eq!(report.scanned_tables(), ["t0"]);
+        assert_eq!(
+            report.access("t1").unwrap().index.as_deref(),
+            Some("PRIMARY KEY")
+        );
+    }
+}
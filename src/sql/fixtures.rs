@@ -0,0 +1,927 @@
+//! Seed data for tests, written as readable record blocks
+//!
+//! A fixture is a list of records, each an object name and its fields in
+//! braces. Child relationships nest their records in a list and get the
+//! foreign key to the parent filled in. A top-level record can be labelled
+//! with `@label` and referenced by that label from any lookup field:
+//!
+//! ```text
+//! // Comments run to the end of the line
+//! @acme Account {
+//!     Name: 'Acme',
+//!     AnnualRevenue: 1500000.50,
+//!     Contacts: [
+//!         { LastName: 'Smith', Email: 'smith@acme.test' },
+//!         { LastName: 'Jones', Birthdate: 1980-04-12 }
+//!     ]
+//! }
+//! Opportunity { Name: 'Renewal', AccountId: @acme, CloseDate: 2024-06-30 }
+//! ```
+//!
+//! Values are checked against the schema: strings are quoted, numbers,
+//! `true`, `false` and `null` are bare, and dates (`2024-06-30`), datetimes
+//! (`2024-06-30T09:00:00Z`) and times (`09:00:00`) are bare literals. A
+//! record without an `Id` field gets one from [`IdGenerator`].
+//!
+//! With the `test-utils` feature, [`load_sqlite`] inserts a [`Dataset`] into
+//! a database created by [`DdlGenerator`](super::DdlGenerator). Fields
+//! defaulting to the current time, such as `CreatedDate`, take it from a
+//! [`ClockSource`], so a fixed clock seeds the same rows on every run.
+
+use std::collections::{BTreeMap, HashMap};
+
+use thiserror::Error;
+
+use super::ddl::sorted_fields;
+use super::ids::IdGenerator;
+use super::schema::{
+    FieldDefault, FieldDescribe, SObjectDescribe, SalesforceFieldType, SalesforceSchema,
+};
+use crate::clock::ClockSource;
+use crate::lexer::{LineIndex, Span};
+
+/// A field value, typed by the field it was given to
+#[derive(Debug, Clone, PartialEq)]
+pub enum FixtureValue {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Number(f64),
+    Text(String),
+    /// `2024-06-30`
+    Date(String),
+    /// `2024-06-30T09:00:00Z`
+    DateTime(String),
+    /// `09:00:00`
+    Time(String),
+    /// A lookup, with the referenced object when it is known
+    Reference {
+        id: String,
+        object: Option<String>,
+    },
+}
+
+/// One record to insert
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureRecord {
+    /// API name of the object
+    pub object: String,
+    pub id: String,
+    /// `@label` given to the record, if any
+    pub label: Option<String>,
+    /// Values by field API name, as spelled in the schema, without `Id`
+    pub fields: BTreeMap<String, FixtureValue>,
+    /// The record's block in the fixture text
+    pub span: Span,
+}
+
+impl FixtureRecord {
+    /// Value of a field (case-insensitive)
+    pub fn get(&self, field: &str) -> Option<&FixtureValue> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(field))
+            .map(|(_, value)| value)
+    }
+
+    /// Values for the columns of
+    /// [`DdlGenerator::upsert_statement`](super::DdlGenerator::upsert_statement),
+    /// in its order
+    ///
+    /// Fields the record leaves out take their default, with `clock`
+    /// giving the current time, or null.
+    pub fn row(&self, object: &SObjectDescribe, clock: &ClockSource) -> Vec<FixtureValue> {
+        let mut row = Vec::new();
+        for field in sorted_fields(object) {
+            let value = if field.name == "Id" {
+                FixtureValue::Text(self.id.clone())
+            } else {
+                match self.fields.get(&field.name) {
+                    Some(value) => value.clone(),
+                    None => default_value(field, clock),
+                }
+            };
+            if field.type_column_name().is_some() {
+                let object = match &value {
+                    FixtureValue::Reference {
+                        object: Some(object),
+                        ..
+                    } => FixtureValue::Text(object.clone()),
+                    _ => FixtureValue::Null,
+                };
+                row.push(value);
+                row.push(object);
+            } else {
+                row.push(value);
+            }
+        }
+        row
+    }
+}
+
+/// Records parsed from a fixture, each parent ahead of the children nested
+/// in it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dataset {
+    pub records: Vec<FixtureRecord>,
+}
+
+impl Dataset {
+    /// Records of one object (case-insensitive), in fixture order
+    pub fn records_of<'a>(&'a self, object: &'a str) -> impl Iterator<Item = &'a FixtureRecord> {
+        self.records
+            .iter()
+            .filter(move |record| record.object.eq_ignore_ascii_case(object))
+    }
+
+    /// The record labelled `@label`
+    pub fn labelled(&self, label: &str) -> Option<&FixtureRecord> {
+        self.records
+            .iter()
+            .find(|record| record.label.as_deref() == Some(label))
+    }
+}
+
+/// Why a fixture could not be loaded; every error points into the fixture
+/// text
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum FixtureError {
+    #[error("{message} at {span}")]
+    Syntax { message: String, span: Span },
+
+    #[error("Unknown SObject '{object}' at {span}")]
+    UnknownObject { object: String, span: Span },
+
+    #[error("Unknown field '{field}' on object '{object}' at {span}")]
+    UnknownField {
+        object: String,
+        field: String,
+        span: Span,
+    },
+
+    #[error("Child relationship '{relationship}' not found on object '{object}' at {span}")]
+    UnknownRelationship {
+        object: String,
+        relationship: String,
+        span: Span,
+    },
+
+    /// `field` is qualified with its object (`Opportunity.CloseDate`)
+    #[error("Field '{field}' expects {expected}, found {found} at {span}")]
+    TypeMismatch {
+        field: String,
+        expected: String,
+        found: String,
+        span: Span,
+    },
+
+    #[error("Unknown label '@{label}' at {span}")]
+    UnknownLabel { label: String, span: Span },
+
+    #[error("Label '@{label}' is already used at {span}")]
+    DuplicateLabel { label: String, span: Span },
+}
+
+impl FixtureError {
+    /// Where in the fixture text the error is
+    pub fn span(&self) -> Span {
+        match self {
+            FixtureError::Syntax { span, .. }
+            | FixtureError::UnknownObject { span, .. }
+            | FixtureError::UnknownField { span, .. }
+            | FixtureError::UnknownRelationship { span, .. }
+            | FixtureError::TypeMismatch { span, .. }
+            | FixtureError::UnknownLabel { span, .. }
+            | FixtureError::DuplicateLabel { span, .. } => *span,
+        }
+    }
+}
+
+/// Parse a fixture and check it against `schema`
+pub fn parse(source: &str, schema: &SalesforceSchema) -> Result<Dataset, FixtureError> {
+    let nodes = Parser::new(source).parse_fixture()?;
+    let mut builder = Builder {
+        schema,
+        ids: IdGenerator::new(),
+        records: Vec::new(),
+        labels: HashMap::new(),
+        pending: Vec::new(),
+    };
+    for node in &nodes {
+        builder.top_level(node)?;
+    }
+    builder.resolve_labels()?;
+    Ok(Dataset {
+        records: builder.records,
+    })
+}
+
+/// Insert every record of a dataset into a SQLite database whose tables
+/// were created from `schema`
+#[cfg(feature = "test-utils")]
+pub fn load_sqlite(
+    dataset: &Dataset,
+    schema: &SalesforceSchema,
+    conn: &rusqlite::Connection,
+    clock: &ClockSource,
+) -> rusqlite::Result<()> {
+    let generator = super::DdlGenerator::new(super::SqlDialect::Sqlite);
+    for record in &dataset.records {
+        let Some(object) = schema.get_object(&record.object) else {
+            continue;
+        };
+        let sql = generator
+            .upsert_statement(&object.name, schema, "Id")
+            .ok_or_else(|| rusqlite::Error::InvalidColumnName(format!("{}.Id", object.name)))?;
+        conn.prepare_cached(&sql)?
+            .execute(rusqlite::params_from_iter(record.row(object, clock)))?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "test-utils")]
+impl rusqlite::ToSql for FixtureValue {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        use rusqlite::types::{ToSqlOutput, Value};
+        Ok(match self {
+            FixtureValue::Null => ToSqlOutput::Owned(Value::Null),
+            FixtureValue::Boolean(b) => ToSqlOutput::from(*b),
+            FixtureValue::Integer(n) => ToSqlOutput::from(*n),
+            FixtureValue::Number(n) => ToSqlOutput::from(*n),
+            FixtureValue::Text(s)
+            | FixtureValue::Date(s)
+            | FixtureValue::DateTime(s)
+            | FixtureValue::Time(s)
+            | FixtureValue::Reference { id: s, .. } => ToSqlOutput::from(s.as_str()),
+        })
+    }
+}
+
+/// Column default of a field the record leaves out
+fn default_value(field: &FieldDescribe, clock: &ClockSource) -> FixtureValue {
+    match &field.default_value {
+        Some(FieldDefault::Boolean(b)) => FixtureValue::Boolean(*b),
+        Some(FieldDefault::Integer(n)) => FixtureValue::Integer(*n),
+        Some(FieldDefault::Number(n)) => FixtureValue::Number(*n),
+        Some(FieldDefault::Text(s)) => FixtureValue::Text(s.clone()),
+        Some(FieldDefault::CurrentTimestamp) => match field.field_type {
+            SalesforceFieldType::Date => FixtureValue::Date(clock.now().iso_date()),
+            _ => FixtureValue::DateTime(clock.now().iso_datetime()),
+        },
+        None => FixtureValue::Null,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Syntax
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    /// `@label`
+    Label(String),
+    /// Quoted, with escapes resolved
+    Str(String),
+    /// Number, date, datetime or time, as written
+    Literal(String),
+    Punct(char),
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    span: Span,
+}
+
+/// `@label Object { Field: value, ... }`, or `{ ... }` nested in a list
+#[derive(Debug)]
+struct RecordNode {
+    label: Option<(String, Span)>,
+    object: Option<(String, Span)>,
+    fields: Vec<FieldNode>,
+    span: Span,
+}
+
+#[derive(Debug)]
+struct FieldNode {
+    name: String,
+    name_span: Span,
+    value: ValueNode,
+    value_span: Span,
+}
+
+#[derive(Debug)]
+enum ValueNode {
+    Null,
+    Boolean(bool),
+    Text(String),
+    Literal(String),
+    Label(String),
+    Records(Vec<RecordNode>),
+}
+
+impl ValueNode {
+    fn describe(&self) -> String {
+        match self {
+            ValueNode::Null => "null".to_string(),
+            ValueNode::Boolean(_) => "a boolean".to_string(),
+            ValueNode::Text(_) => "a string".to_string(),
+            ValueNode::Literal(raw) => match classify(raw) {
+                Some(LiteralKind::Integer | LiteralKind::Number) => "a number".to_string(),
+                Some(LiteralKind::Date) => "a date".to_string(),
+                Some(LiteralKind::DateTime) => "a datetime".to_string(),
+                Some(LiteralKind::Time) => "a time".to_string(),
+                None => format!("'{}'", raw),
+            },
+            ValueNode::Label(label) => format!("@{}", label),
+            ValueNode::Records(_) => "a list of records".to_string(),
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    index: LineIndex<'a>,
+    pos: usize,
+    current: Token,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            index: LineIndex::new(source),
+            pos: 0,
+            current: Token {
+                kind: TokenKind::Eof,
+                span: Span::new(0, 0),
+            },
+        }
+    }
+
+    fn parse_fixture(&mut self) -> Result<Vec<RecordNode>, FixtureError> {
+        self.advance()?;
+        let mut records = Vec::new();
+        while self.current.kind != TokenKind::Eof {
+            let start = self.current.span;
+            let label = match self.current.kind.clone() {
+                TokenKind::Label(label) => {
+                    let span = self.current.span;
+                    self.advance()?;
+                    Some((label, span))
+                }
+                _ => None,
+            };
+            let object = match self.current.kind.clone() {
+                TokenKind::Ident(name) => {
+                    let span = self.current.span;
+                    self.advance()?;
+                    (name, span)
+                }
+                _ => return Err(self.unexpected("an object name")),
+            };
+            let mut record = self.parse_block(start)?;
+            record.label = label;
+            record.object = Some(object);
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// `{ Field: value, ... }`
+    fn parse_block(&mut self, start: Span) -> Result<RecordNode, FixtureError> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        while self.current.kind != TokenKind::Punct('}') {
+            let (name, name_span) = match self.current.kind.clone() {
+                TokenKind::Ident(name) => (name, self.current.span),
+                _ => return Err(self.unexpected("a field name")),
+            };
+            self.advance()?;
+            self.expect(':')?;
+            let value_start = self.current.span;
+            let value = self.parse_value()?;
+            fields.push(FieldNode {
+                name,
+                name_span,
+                value,
+                value_span: self.spanning(value_start),
+            });
+            if self.current.kind != TokenKind::Punct(',') {
+                break;
+            }
+            self.advance()?;
+        }
+        self.expect('}')?;
+        Ok(RecordNode {
+            label: None,
+            object: None,
+            fields,
+            span: self.spanning(start),
+        })
+    }
+
+    fn parse_value(&mut self) -> Result<ValueNode, FixtureError> {
+        let value = match self.current.kind.clone() {
+            TokenKind::Str(s) => ValueNode::Text(s),
+            TokenKind::Literal(raw) => ValueNode::Literal(raw),
+            TokenKind::Label(label) => ValueNode::Label(label),
+            TokenKind::Ident(word) if word.eq_ignore_ascii_case("null") => ValueNode::Null,
+            TokenKind::Ident(word) if word.eq_ignore_ascii_case("true") => ValueNode::Boolean(true),
+            TokenKind::Ident(word) if word.eq_ignore_ascii_case("false") => {
+                ValueNode::Boolean(false)
+            }
+            TokenKind::Punct('[') => {
+                self.advance()?;
+                let mut records = Vec::new();
+                while self.current.kind != TokenKind::Punct(']') {
+                    let start = self.current.span;
+                    records.push(self.parse_block(start)?);
+                    if self.current.kind != TokenKind::Punct(',') {
+                        break;
+                    }
+                    self.advance()?;
+                }
+                self.expect(']')?;
+                return Ok(ValueNode::Records(records));
+            }
+            _ => return Err(self.unexpected("a value")),
+        };
+        self.advance()?;
+        Ok(value)
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), FixtureError> {
+        if self.current.kind != TokenKind::Punct(punct) {
+            return Err(self.unexpected(&format!("'{}'", punct)));
+        }
+        self.advance()
+    }
+
+    fn advance(&mut self) -> Result<(), FixtureError> {
+        self.current = self.scan();
+        match &self.current.kind {
+            TokenKind::Punct('\'') => Err(FixtureError::Syntax {
+                message: "Unterminated string".to_string(),
+                span: self.current.span,
+            }),
+            TokenKind::Punct(c) if !"{}[]:,".contains(*c) => Err(FixtureError::Syntax {
+                message: format!("Unexpected character '{}'", c),
+                span: self.current.span,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> FixtureError {
+        let found = match &self.current.kind {
+            TokenKind::Ident(s) | TokenKind::Literal(s) => format!("'{}'", s),
+            TokenKind::Label(s) => format!("'@{}'", s),
+            TokenKind::Str(_) => "a string".to_string(),
+            TokenKind::Punct(c) => format!("'{}'", c),
+            TokenKind::Eof => "end of input".to_string(),
+        };
+        FixtureError::Syntax {
+            message: format!("Expected {}, found {}", expected, found),
+            span: self.current.span,
+        }
+    }
+
+    /// From `start` to the end of the last token consumed
+    fn spanning(&self, start: Span) -> Span {
+        let end = self.source[..self.current.span.start].trim_end().len();
+        self.index.span(start.start, end.max(start.start))
+    }
+
+    fn scan(&mut self) -> Token {
+        let bytes = self.source.as_bytes();
+        loop {
+            while self.pos < bytes.len() && bytes[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            if self.source[self.pos..].starts_with("//") {
+                while self.pos < bytes.len() && bytes[self.pos] != b'\n' {
+                    self.pos += 1;
+                }
+                continue;
+            }
+            break;
+        }
+
+        let start = self.pos;
+        let Some(c) = self.source[start..].chars().next() else {
+            return self.token(TokenKind::Eof, start);
+        };
+        let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+        let kind = if c.is_ascii_alphabetic() || c == '_' {
+            while self.pos < bytes.len() && is_word(bytes[self.pos]) {
+                self.pos += 1;
+            }
+            TokenKind::Ident(self.source[start..self.pos].to_string())
+        } else if c == '@' {
+            self.pos += 1;
+            while self.pos < bytes.len() && is_word(bytes[self.pos]) {
+                self.pos += 1;
+            }
+            TokenKind::Label(self.source[start + 1..self.pos].to_string())
+        } else if c.is_ascii_digit()
+            || (c == '-' && bytes.get(start + 1).is_some_and(u8::is_ascii_digit))
+        {
+            self.pos += 1;
+            while self.pos < bytes.len()
+                && (is_word(bytes[self.pos]) || b":.+-".contains(&bytes[self.pos]))
+            {
+                self.pos += 1;
+            }
+            TokenKind::Literal(self.source[start..self.pos].to_string())
+        } else if c == '\'' {
+            return self.scan_string(start);
+        } else {
+            self.pos += c.len_utf8();
+            TokenKind::Punct(c)
+        };
+        self.token(kind, start)
+    }
+
+    /// A quoted string; `\'`, `\\`, `\n` and `\t` are escapes as in Apex
+    fn scan_string(&mut self, start: usize) -> Token {
+        let mut value = String::new();
+        let mut chars = self.source[start + 1..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\'' => {
+                    self.pos = start + 1 + i + 1;
+                    return self.token(TokenKind::Str(value), start);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, escaped)) => value.push(escaped),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        // Unterminated: report the opening quote
+        self.pos = self.source.len();
+        self.token(TokenKind::Punct('\''), start)
+    }
+
+    fn token(&self, kind: TokenKind, start: usize) -> Token {
+        Token {
+            kind,
+            span: self.index.span(start, self.pos),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LiteralKind {
+    Integer,
+    Number,
+    Date,
+    DateTime,
+    Time,
+}
+
+/// What a bare literal is, if it is well formed
+fn classify(raw: &str) -> Option<LiteralKind> {
+    let digits = |s: &str, n: usize| s.len() == n && s.bytes().all(|b| b.is_ascii_digit());
+    let is_date = |s: &str| {
+        let parts: Vec<&str> = s.split('-').collect();
+        matches!(parts[..], [y, m, d] if digits(y, 4) && digits(m, 2) && digits(d, 2))
+    };
+    let is_time = |s: &str| {
+        let s = s.strip_suffix('Z').unwrap_or(s);
+        let (clock, fraction) = s.split_once('.').unwrap_or((s, "0"));
+        let parts: Vec<&str> = clock.split(':').collect();
+        (2..=3).contains(&parts.len())
+            && parts.iter().all(|part| digits(part, 2))
+            && !fraction.is_empty()
+            && fraction.bytes().all(|b| b.is_ascii_digit())
+    };
+
+    let unsigned = raw.strip_prefix('-').unwrap_or(raw);
+    if !unsigned.is_empty() && unsigned.bytes().all(|b| b.is_ascii_digit()) {
+        return Some(LiteralKind::Integer);
+    }
+    if let Some((whole, fraction)) = unsigned.split_once('.') {
+        let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if all_digits(whole) && all_digits(fraction) {
+            return Some(LiteralKind::Number);
+        }
+    }
+    if is_date(raw) {
+        return Some(LiteralKind::Date);
+    }
+    if let Some((date, time)) = raw.split_once('T') {
+        // An offset such as +05:30 or -0800 may replace the Z
+        let time = match time.rfind(['+', '-']) {
+            Some(at) if is_offset(&time[at + 1..]) => &time[..at],
+            _ => time,
+        };
+        if is_date(date) && is_time(time) {
+            return Some(LiteralKind::DateTime);
+        }
+        return None;
+    }
+    if is_time(raw) {
+        return Some(LiteralKind::Time);
+    }
+    None
+}
+
+/// `05:30` or `0530`
+fn is_offset(s: &str) -> bool {
+    let s = s.replacen(':', "", 1);
+    s.len() == 4 && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+// ---------------------------------------------------------------------------
+// Checking against the schema
+// ---------------------------------------------------------------------------
+
+struct Builder<'s> {
+    schema: &'s SalesforceSchema,
+    ids: IdGenerator,
+    records: Vec<FixtureRecord>,
+    /// Label to the index of its record
+    labels: HashMap<String, usize>,
+    pending: Vec<PendingLabel>,
+}
+
+/// A lookup written as `@label`, resolved once every record is known
+struct PendingLabel {
+    record: usize,
+    field: String,
+    targets: Vec<String>,
+    label: String,
+    span: Span,
+}
+
+impl Builder<'_> {
+    fn top_level(&mut self, node: &RecordNode) -> Result<(), FixtureError> {
+        let (name, span) = node
+            .object
+            .as_ref()
+            .expect("top-level records name their object");
+        let object = self
+            .schema
+            .get_object(name)
+            .ok_or_else(|| FixtureError::UnknownObject {
+                object: name.clone(),
+                span: *span,
+            })?;
+        self.record(node, object, None)?;
+        Ok(())
+    }
+
+    /// Add a record and the children nested in it; `parent` is the foreign
+    /// key field and referenced record of a nested child
+    fn record(
+        &mut self,
+        node: &RecordNode,
+        object: &SObjectDescribe,
+        parent: Option<(&str, &FixtureRecord)>,
+    ) -> Result<(), FixtureError> {
+        let id = match node
+            .fields
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case("Id"))
+        {
+            Some(FieldNode {
+                value: ValueNode::Text(id),
+                ..
+            }) => id.clone(),
+            Some(field) => return Err(mismatch(object, "Id", "a string", field)),
+            None => self.ids.next_id(&object.name),
+        };
+        let index = self.records.len();
+        if let Some((label, span)) = &node.label {
+            if self.labels.insert(label.clone(), index).is_some() {
+                return Err(FixtureError::DuplicateLabel {
+                    label: label.clone(),
+                    span: *span,
+                });
+            }
+        }
+
+        let mut record = FixtureRecord {
+            object: object.name.clone(),
+            id,
+            label: node.label.as_ref().map(|(label, _)| label.clone()),
+            fields: BTreeMap::new(),
+            span: node.span,
+        };
+        let mut children = Vec::new();
+        for field in &node.fields {
+            if field.name.eq_ignore_ascii_case("Id") {
+                continue;
+            }
+            if let ValueNode::Records(nested) = &field.value {
+                let relationship = object.get_child_relationship(&field.name).ok_or_else(|| {
+                    FixtureError::UnknownRelationship {
+                        object: object.name.clone(),
+                        relationship: field.name.clone(),
+                        span: field.name_span,
+                    }
+                })?;
+                children.push((relationship, nested));
+                continue;
+            }
+            let describe =
+                object
+                    .get_field(&field.name)
+                    .ok_or_else(|| FixtureError::UnknownField {
+                        object: object.name.clone(),
+                        field: field.name.clone(),
+                        span: field.name_span,
+                    })?;
+            let value = match &field.value {
+                ValueNode::Label(label) => {
+                    let Some(targets) = describe.reference_to.clone() else {
+                        return Err(mismatch(object, &describe.name, expected(describe), field));
+                    };
+                    self.pending.push(PendingLabel {
+                        record: index,
+                        field: describe.name.clone(),
+                        targets,
+                        label: label.clone(),
+                        span: field.value_span,
+                    });
+                    FixtureValue::Null
+                }
+                value => typed_value(describe, value)
+                    .ok_or_else(|| mismatch(object, &describe.name, expected(describe), field))?,
+            };
+            record.fields.insert(describe.name.clone(), value);
+        }
+        if let Some((fk, parent)) = parent {
+            let fk = object.get_field(fk).map_or(fk, |f| f.name.as_str());
+            record.fields.insert(
+                fk.to_string(),
+                FixtureValue::Reference {
+                    id: parent.id.clone(),
+                    object: Some(parent.object.clone()),
+                },
+            );
+        }
+        self.records.push(record);
+
+        for (relationship, nested) in children {
+            let child_object = self
+                .schema
+                .get_object(&relationship.child_object)
+                .ok_or_else(|| FixtureError::UnknownObject {
+                    object: relationship.child_object.clone(),
+                    span: node.span,
+                })?;
+            for child in nested {
+                let parent = self.records[index].clone();
+                self.record(child, child_object, Some((&relationship.field, &parent)))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_labels(&mut self) -> Result<(), FixtureError> {
+        for pending in std::mem::take(&mut self.pending) {
+            let Some(&target) = self.labels.get(&pending.label) else {
+                return Err(FixtureError::UnknownLabel {
+                    label: pending.label,
+                    span: pending.span,
+                });
+            };
+            let target = &self.records[target];
+            if !pending
+                .targets
+                .iter()
+                .any(|object| object.eq_ignore_ascii_case(&target.object))
+            {
+                let record = &self.records[pending.record];
+                return Err(FixtureError::TypeMismatch {
+                    field: format!("{}.{}", record.object, pending.field),
+                    expected: format!("a reference to {}", pending.targets.join(" or ")),
+                    found: format!("@{} ({})", pending.label, target.object),
+                    span: pending.span,
+                });
+            }
+            let value = FixtureValue::Reference {
+                id: target.id.clone(),
+                object: Some(target.object.clone()),
+            };
+            self.records[pending.record]
+                .fields
+                .insert(pending.field, value);
+        }
+        Ok(())
+    }
+}
+
+/// A value written for a field, if it fits the field's type
+fn typed_value(field: &FieldDescribe, value: &ValueNode) -> Option<FixtureValue> {
+    use SalesforceFieldType as T;
+    let literal = match value {
+        ValueNode::Null => return Some(FixtureValue::Null),
+        ValueNode::Literal(raw) => classify(raw).map(|kind| (kind, raw.clone())),
+        _ => None,
+    };
+    match (field.field_type, value, literal) {
+        (T::Boolean, ValueNode::Boolean(b), _) => Some(FixtureValue::Boolean(*b)),
+        (T::Integer, _, Some((LiteralKind::Integer, raw))) => {
+            raw.parse().ok().map(FixtureValue::Integer)
+        }
+        (
+            T::Double | T::Currency | T::Percent,
+            _,
+            Some((LiteralKind::Integer | LiteralKind::Number, raw)),
+        ) => raw.parse().ok().map(FixtureValue::Number),
+        (T::Date, _, Some((LiteralKind::Date, raw))) => Some(FixtureValue::Date(raw)),
+        (T::DateTime, _, Some((LiteralKind::DateTime, raw))) => Some(FixtureValue::DateTime(raw)),
+        (T::Time, _, Some((LiteralKind::Time, raw))) => Some(FixtureValue::Time(raw)),
+        (T::Lookup | T::MasterDetail | T::Reference, ValueNode::Text(id), _) => {
+            Some(FixtureValue::Reference {
+                id: id.clone(),
+                object: None,
+            })
+        }
+        (
+            T::Boolean
+            | T::Integer
+            | T::Double
+            | T::Currency
+            | T::Percent
+            | T::Date
+            | T::DateTime
+            | T::Time
+            | T::Lookup
+            | T::MasterDetail
+            | T::Reference,
+            _,
+            _,
+        ) => None,
+        (_, ValueNode::Text(s), _) => Some(FixtureValue::Text(s.clone())),
+        _ => None,
+    }
+}
+
+/// What a field's values are written as, for type mismatch errors
+fn expected(field: &FieldDescribe) -> &'static str {
+    use SalesforceFieldType as T;
+    match field.field_type {
+        T::Boolean => "a boolean",
+        T::Integer => "an integer",
+        T::Double | T::Currency | T::Percent => "a number",
+        T::Date => "a date (YYYY-MM-DD)",
+        T::DateTime => "a datetime (YYYY-MM-DDThh:mm:ssZ)",
+        T::Time => "a time (hh:mm:ss)",
+        T::Lookup | T::MasterDetail | T::Reference => "an Id or @label",
+        _ => "a string",
+    }
+}
+
+fn mismatch(
+    object: &SObjectDescribe,
+    field: &str,
+    expected: &str,
+    node: &FieldNode,
+) -> FixtureError {
+    FixtureError::TypeMismatch {
+        field: format!("{}.{}", object.name, field),
+        expected: expected.to_string(),
+        found: node.value.describe(),
+        span: node.value_span,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_literals() {
+        assert_eq!(classify("42"), Some(LiteralKind::Integer));
+        assert_eq!(classify("-7"), Some(LiteralKind::Integer));
+        assert_eq!(classify("1500000.50"), Some(LiteralKind::Number));
+        assert_eq!(classify("2024-06-30"), Some(LiteralKind::Date));
+        assert_eq!(
+            classify("2024-06-30T09:00:00Z"),
+            Some(LiteralKind::DateTime)
+        );
+        assert_eq!(
+            classify("2024-06-30T09:00:00.000+0000"),
+            Some(LiteralKind::DateTime)
+        );
+        assert_eq!(
+            classify("2024-06-30T09:00-05:30"),
+            Some(LiteralKind::DateTime)
+        );
+        assert_eq!(classify("09:00:00"), Some(LiteralKind::Time));
+        assert_eq!(classify("09:00:00.250Z"), Some(LiteralKind::Time));
+        assert_eq!(classify("2024-6-30"), None);
+        assert_eq!(classify("1.2.3"), None);
+        assert_eq!(classify("12abc"), None);
+    }
+}
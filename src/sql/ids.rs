@@ -0,0 +1,172 @@
+//! Salesforce record Id normalization
+//!
+//! A 15-character Id is case-sensitive. The 18-character form appends three
+//! characters encoding which of the first fifteen are upper case, so it
+//! survives case-insensitive handling and can be restored to its original
+//! casing.
+
+use std::collections::HashMap;
+
+const SUFFIX_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ012345";
+
+/// The canonical 18-character form of a record Id
+///
+/// A 15-character Id gets its checksum suffix; an 18-character Id in any
+/// casing has its first fifteen characters recased from the suffix, which
+/// is returned upper case. Anything else, including an 18-character value
+/// whose suffix doesn't fit its characters, is not an Id and gives `None`.
+pub fn normalize_id(id: &str) -> Option<String> {
+    if !id.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let base = match id.len() {
+        15 => id.to_string(),
+        18 => recase(&id[..15], &id[15..])?,
+        _ => return None,
+    };
+    let suffix = checksum(&base);
+    Some(base + &suffix)
+}
+
+/// Deterministic record Ids for test data
+///
+/// Each object counts up behind its key prefix (`001` for Account). Objects
+/// without a well-known prefix get `a00`, `a01`, ... in the order they are
+/// first seen. Ids are returned in 18-character form.
+#[derive(Debug, Clone, Default)]
+pub struct IdGenerator {
+    counters: HashMap<String, u64>,
+    custom_prefixes: HashMap<String, String>,
+}
+
+impl IdGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The next unused Id for an object
+    pub fn next_id(&mut self, object: &str) -> String {
+        let key = object.to_lowercase();
+        let prefix = match standard_key_prefix(&key) {
+            Some(prefix) => prefix.to_string(),
+            None => {
+                let n = self.custom_prefixes.len();
+                self.custom_prefixes
+                    .entry(key.clone())
+                    .or_insert_with(|| custom_key_prefix(n))
+                    .clone()
+            }
+        };
+        let counter = self.counters.entry(key).or_insert(0);
+        *counter += 1;
+        let id = format!("{}{:012}", prefix, counter);
+        normalize_id(&id).unwrap_or(id)
+    }
+}
+
+/// Key prefix of a standard object, by lowercased API name
+fn standard_key_prefix(object: &str) -> Option<&'static str> {
+    let prefix = match object {
+        "account" => "001",
+        "note" => "002",
+        "contact" => "003",
+        "user" => "005",
+        "opportunity" => "006",
+        "recordtype" => "012",
+        "opportunitylineitem" => "00k",
+        "attachment" => "00P",
+        "lead" => "00Q",
+        "task" => "00T",
+        "event" => "00U",
+        "pricebook2" => "01s",
+        "product2" => "01t",
+        "pricebookentry" => "01u",
+        "asset" => "02i",
+        "contentversion" => "068",
+        "contentdocument" => "069",
+        "case" => "500",
+        "campaign" => "701",
+        "contract" => "800",
+        "order" => "801",
+        _ => return None,
+    };
+    Some(prefix)
+}
+
+/// `a00`, `a01`, ..., `a0Z`, `a10`, ...
+fn custom_key_prefix(n: usize) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    format!("a{}{}", DIGITS[n / 36 % 36] as char, DIGITS[n % 36] as char)
+}
+
+/// The three suffix characters for a case-sensitive 15-character Id
+fn checksum(id: &str) -> String {
+    id.as_bytes()
+        .chunks(5)
+        .map(|chunk| {
+            let flags = chunk
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| b.is_ascii_uppercase())
+                .fold(0, |flags, (bit, _)| flags | 1 << bit);
+            SUFFIX_ALPHABET[flags] as char
+        })
+        .collect()
+}
+
+/// Restore the casing of the first fifteen characters from the suffix
+fn recase(base: &str, suffix: &str) -> Option<String> {
+    let mut out = String::with_capacity(15);
+    for (chunk, flag) in base.as_bytes().chunks(5).zip(suffix.bytes()) {
+        let flags = SUFFIX_ALPHABET
+            .iter()
+            .position(|&c| c == flag.to_ascii_uppercase())?;
+        for (bit, &b) in chunk.iter().enumerate() {
+            if flags & (1 << bit) == 0 {
+                out.push(b.to_ascii_lowercase() as char);
+            } else if b.is_ascii_alphabetic() {
+                out.push(b.to_ascii_uppercase() as char);
+            } else {
+                return None;
+            }
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_id() {
+        assert_eq!(
+            normalize_id("0015000000Gv7qJ").as_deref(),
+            Some("0015000000Gv7qJAAR")
+        );
+        assert_eq!(
+            normalize_id("0015000000gv7qjaar").as_deref(),
+            Some("0015000000Gv7qJAAR")
+        );
+        assert_eq!(
+            normalize_id("0015000000Gv7qJAAR").as_deref(),
+            Some("0015000000Gv7qJAAR")
+        );
+        assert_eq!(normalize_id("Acme"), None);
+        assert_eq!(normalize_id("0015000000Gv7q'"), None);
+        // Suffix claims an upper-case letter where there is a digit
+        assert_eq!(normalize_id("001500000000000B00"), None);
+    }
+
+    #[test]
+    fn test_id_generator() {
+        let mut ids = IdGenerator::new();
+        assert_eq!(ids.next_id("Account"), "001000000000001AAA");
+        assert_eq!(ids.next_id("account"), "001000000000002AAA");
+        assert_eq!(ids.next_id("Invoice__c"), "a00000000000001AAA");
+        assert_eq!(ids.next_id("Payment__c"), "a01000000000001AAA");
+        assert_eq!(ids.next_id("Invoice__c"), "a00000000000002AAA");
+        // The suffix records the upper-case Q
+        assert_eq!(ids.next_id("Lead"), "00Q000000000001EAA");
+    }
+}
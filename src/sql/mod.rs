@@ -1,7 +1,7 @@
 //! SOQL to SQL conversion module
 //!
 //! This module provides functionality to convert parsed SOQL queries to SQL
-//! compatible with both SQLite and PostgreSQL, along with DDL generation
+//! compatible with SQLite, PostgreSQL and MySQL, along with DDL generation
 //! for modeling Salesforce org schema in relational databases.
 //!
 //! # Overview
@@ -59,49 +59,83 @@
 //! - Basic SELECT with field lists
 //! - WHERE clause with operators (=, !=, <, >, <=, >=, LIKE, IN, NOT IN)
 //! - INCLUDES/EXCLUDES for multi-picklist fields
+//! - Semi-joins and anti-joins (`Id IN (SELECT AccountId FROM Contact)`)
 //! - ORDER BY with ASC/DESC and NULLS FIRST/LAST
 //! - LIMIT and OFFSET
 //! - GROUP BY and HAVING
+//! - GROUP BY ROLLUP and CUBE (PostgreSQL; ROLLUP only on MySQL)
 //! - Aggregate functions (COUNT, SUM, AVG, MIN, MAX)
 //! - Bind variables (:variableName) converted to parameterized queries
-//! - Date literals (TODAY, LAST_N_DAYS, THIS_MONTH, etc.)
+//! - Date literals (TODAY, LAST_N_DAYS, THIS_MONTH, etc.), relative to the
+//!   database's date or to a date pinned by `ConversionConfig::clock`
 //! - Parent relationship queries (Account.Name)
 //! - Child relationship subqueries (SELECT ... FROM Contacts)
 //! - TYPEOF for polymorphic fields
-//! - FOR UPDATE (PostgreSQL only)
+//! - FOR UPDATE (PostgreSQL and MySQL)
 //!
 //! ## SQL Dialects
 //!
 //! - **PostgreSQL**: Full support including FOR UPDATE, TIMESTAMP, BOOLEAN
 //! - **SQLite**: Compatible output using INTEGER for booleans, TEXT for dates
+//! - **MySQL**: Backtick quoting, positional `?` parameters, JSON_ARRAYAGG for
+//!   child subqueries and VARCHAR columns for Ids and short text
+//!
+//! Tables can be qualified with a schema (`"org_a"."account"`) to host several
+//! org replicas in one database; on SQLite the qualifier names an attached
+//! database or is dropped.
 //!
 //! ## Schema Modeling
 //!
 //! The schema model supports:
 //! - All Salesforce field types
-//! - Lookup and Master-Detail relationships
+//! - Lookup and Master-Detail relationships, as FOREIGN KEY constraints
+//!   with parent tables created first (see [`DdlOptions`])
 //! - Polymorphic fields (with type discriminator columns)
 //! - Child relationships for subqueries
 //! - Standard system fields (CreatedDate, LastModifiedDate, etc.)
+//! - Big objects (`__b`) and external objects (`__x`), whose SOQL
+//!   restrictions are enforced or reported as warnings
+//!
+//! ## Seed Data
+//!
+//! [`fixtures::parse`] reads test records written against a schema, with
+//! nested children and `@label` references, into a [`Dataset`].
 
+pub mod capabilities;
 pub mod converter;
 pub mod date_literals;
 pub mod ddl;
 pub mod dialect;
 pub mod error;
+pub mod explain;
+pub mod fixtures;
+pub mod ids;
+pub mod prune;
 pub mod schema;
+#[cfg(feature = "standard-objects")]
 pub mod standard_objects;
 
 // Re-export main types
 pub use converter::{
-    convert_soql, convert_soql_simple, BindVariableMode, ConversionConfig, SecurityMode,
-    SoqlToSqlConverter, SqlConversion, SqlParameter,
+    convert_soql, convert_soql_simple, warning_counts, BindVariableMode, CapabilityMode,
+    CollationMode, ConversionConfig, IdComparison, RecordTypeStrategy, ResultShape, SecurityMode,
+    SoqlToSqlConverter, SqlConversion, SqlParameter, WarningFilter,
+};
+pub use ddl::{DdlGenerator, DdlOptions};
+pub use dialect::{
+    DateUnit, MysqlDialect, PostgresDialect, SqlDialect, SqlDialectImpl, SqliteDialect,
+    SqliteQualifier,
+};
+pub use error::{
+    ConversionError, ConversionResult, ConversionWarning, WarningKind, WarningSeverity,
 };
-pub use ddl::DdlGenerator;
-pub use dialect::{DateUnit, PostgresDialect, SqlDialect, SqlDialectImpl, SqliteDialect};
-pub use error::{ConversionError, ConversionResult, ConversionWarning};
+pub use explain::{ExplainReport, PlanAccess, PlanNode};
+pub use fixtures::{Dataset, FixtureError, FixtureRecord, FixtureValue};
+pub use ids::{normalize_id, IdGenerator};
+pub use prune::SchemaStats;
 pub use schema::{
-    ChildRelationship, FieldDescribe, SObjectDescribe, SalesforceFieldType, SalesforceSchema,
-    SchemaBuilder,
+    ChildRelationship, Completion, CompletionKind, FieldDefault, FieldDescribe, ObjectKind,
+    SObjectDescribe, SalesforceFieldType, SalesforceSchema, SchemaBuilder,
 };
+#[cfg(feature = "standard-objects")]
 pub use standard_objects::create_sales_cloud_schema;
@@ -0,0 +1,300 @@
+//! Schemas cut down to what a set of queries reads
+//!
+//! A full org describe runs to megabytes, too much to ship to the browser
+//! for the wasm converter when the queries being edited touch a handful of
+//! objects. [`SalesforceSchema::prune_to_usage`] keeps only what converting
+//! those queries consults:
+//!
+//! - the FROM object and every field path, following parent relationships
+//!   to their targets
+//! - child relationships used by subqueries, with the child object and the
+//!   lookup the subquery joins on
+//! - for a polymorphic relationship, the targets that declare the field read
+//!   through it, and for TYPEOF the objects its WHEN branches name
+//! - `IsDeleted` on every kept object, which decides the soft-delete filter
+//!
+//! Converting the same queries against the pruned schema gives the same SQL
+//! as against the full one.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::schema::{SObjectDescribe, SalesforceSchema};
+use crate::analysis::soql_context::classify;
+use crate::ast::visitor::{walk_expression, Visitor};
+use crate::ast::{CompilationUnit, Expression, SelectField, SoqlQuery};
+
+/// Size of a schema, for logging what pruning saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaStats {
+    pub objects: usize,
+    pub fields: usize,
+    pub child_relationships: usize,
+}
+
+impl fmt::Display for SchemaStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} objects, {} fields, {} child relationships",
+            self.objects, self.fields, self.child_relationships
+        )
+    }
+}
+
+impl SalesforceSchema {
+    /// The part of this schema that converting `queries` reads
+    pub fn prune_to_usage(&self, queries: &[SoqlQuery]) -> SalesforceSchema {
+        let mut reachable = Reachable::new(self);
+        for query in queries {
+            reachable.query(query, &query.from_clause);
+        }
+        reachable.build()
+    }
+
+    /// The part of this schema that the inline queries of `units`, such as
+    /// the files of a project, read
+    pub fn prune_to_units(&self, units: &[CompilationUnit]) -> SalesforceSchema {
+        let mut reachable = Reachable::new(self);
+        for usage in units.iter().flat_map(classify) {
+            reachable.query(usage.query, &usage.query.from_clause);
+        }
+        reachable.build()
+    }
+
+    /// Object, field and child relationship counts, to log before and
+    /// after pruning
+    pub fn size_stats(&self) -> SchemaStats {
+        SchemaStats {
+            objects: self.objects().count(),
+            fields: self.objects().map(|o| o.fields().count()).sum(),
+            child_relationships: self.objects().map(|o| o.child_relationships.len()).sum(),
+        }
+    }
+}
+
+/// Lowercased names of what is kept of one object
+#[derive(Default)]
+struct Kept {
+    fields: HashSet<String>,
+    child_relationships: HashSet<String>,
+}
+
+struct Reachable<'s> {
+    schema: &'s SalesforceSchema,
+    /// By lowercased object name
+    objects: HashMap<String, Kept>,
+}
+
+impl<'s> Reachable<'s> {
+    fn new(schema: &'s SalesforceSchema) -> Self {
+        Self {
+            schema,
+            objects: HashMap::new(),
+        }
+    }
+
+    /// Keep an object the schema knows
+    fn object(&mut self, name: &str) -> Option<&'s SObjectDescribe> {
+        let describe = self.schema.get_object(name)?;
+        self.objects
+            .entry(describe.name.to_lowercase())
+            .or_insert_with(|| Kept {
+                fields: HashSet::from(["isdeleted".to_string()]),
+                child_relationships: HashSet::new(),
+            });
+        Some(describe)
+    }
+
+    fn kept(&mut self, object: &SObjectDescribe) -> &mut Kept {
+        self.objects.entry(object.name.to_lowercase()).or_default()
+    }
+
+    fn field(&mut self, object: &SObjectDescribe, field: &str) {
+        self.kept(object).fields.insert(field.to_lowercase());
+    }
+
+    fn query(&mut self, query: &SoqlQuery, object: &str) {
+        let describe = self.object(object);
+        for field in &query.select_clause {
+            match field {
+                SelectField::Field(path) => self.path(describe, path),
+                SelectField::AggregateFunction { field, .. } => self.path(describe, field),
+                SelectField::Fields(scope) => {
+                    if let Some(describe) = describe {
+                        for field in describe.fields().filter(|f| scope.includes(&f.name)) {
+                            self.field(describe, &field.name);
+                        }
+                    }
+                }
+                SelectField::TypeOf(typeof_clause) => {
+                    if let Some(describe) = describe {
+                        self.field(describe, &typeof_clause.field);
+                    }
+                    for when in &typeof_clause.when_clauses {
+                        if let Some(target) = self.object(&when.type_name) {
+                            for field in &when.fields {
+                                self.field(target, field);
+                            }
+                        }
+                    }
+                }
+                SelectField::SubQuery(subquery) => {
+                    let Some(relationship) =
+                        describe.and_then(|d| d.get_child_relationship(&subquery.from_clause))
+                    else {
+                        continue;
+                    };
+                    if let Some(parent) = describe {
+                        self.kept(parent)
+                            .child_relationships
+                            .insert(relationship.relationship_name.to_lowercase());
+                    }
+                    if let Some(child) = self.object(&relationship.child_object) {
+                        self.field(child, &relationship.field);
+                    }
+                    self.query(subquery, &relationship.child_object);
+                }
+            }
+        }
+
+        for filter in [&query.where_clause, &query.having_clause]
+            .into_iter()
+            .flatten()
+        {
+            let mut reads = FilterReads::default();
+            walk_expression(&mut reads, filter);
+            for path in reads.paths {
+                self.path(describe, path);
+            }
+            for subquery in reads.subqueries {
+                self.query(subquery, &subquery.from_clause);
+            }
+        }
+        for path in &query.group_by_clause {
+            self.path(describe, path);
+        }
+        for order in &query.order_by_clause {
+            self.path(describe, &order.field);
+        }
+    }
+
+    /// Keep `path` read from `object`: the relationships it follows and the
+    /// field at its end
+    fn path(&mut self, object: Option<&'s SObjectDescribe>, path: &str) {
+        let Some(object) = object else {
+            return;
+        };
+        let parts: Vec<&str> = path.split('.').collect();
+        self.follow(object, &parts);
+    }
+
+    fn follow(&mut self, object: &'s SObjectDescribe, parts: &[&str]) {
+        let [relationship, rest @ ..] = parts else {
+            return;
+        };
+        if rest.is_empty() {
+            self.field(object, relationship);
+            return;
+        }
+
+        // The converter takes the first lookup with this relationship name,
+        // so keep them all
+        let lookups: Vec<_> = object
+            .fields()
+            .filter(|f| {
+                f.relationship_name
+                    .as_deref()
+                    .is_some_and(|r| r.eq_ignore_ascii_case(relationship))
+                    && f.reference_to.as_ref().is_some_and(|t| !t.is_empty())
+            })
+            .collect();
+        for lookup in lookups {
+            self.field(object, &lookup.name);
+            let targets = lookup.reference_to.as_deref().unwrap_or_default();
+            if lookup.is_polymorphic {
+                // Read through, never traversed further; targets without the
+                // field only make the column NULL
+                if let [field] = rest {
+                    for target in targets {
+                        if let Some(target) = self.schema.get_object(target) {
+                            if target.has_field(field) {
+                                self.object(&target.name);
+                                self.field(target, field);
+                            }
+                        }
+                    }
+                }
+            } else if let Some(target) = self.object(&targets[0]) {
+                self.follow(target, rest);
+            }
+        }
+    }
+
+    fn build(self) -> SalesforceSchema {
+        let mut pruned = SalesforceSchema::new();
+        for (name, kept) in self.objects {
+            let Some(object) = self.schema.get_object(&name) else {
+                continue;
+            };
+            let mut copy = SObjectDescribe::new(object.name.clone())
+                .with_table_name(object.table_name.clone())
+                .with_label(object.label.clone())
+                .with_object_kind(object.object_kind)
+                .with_index_fields(object.index_fields.clone());
+            copy.has_record_types = object.has_record_types;
+            for field in object.fields() {
+                if kept.fields.contains(&field.name.to_lowercase()) {
+                    copy.add_field(field.clone());
+                }
+            }
+            copy.child_relationships = object
+                .child_relationships
+                .iter()
+                .filter(|r| {
+                    kept.child_relationships
+                        .contains(&r.relationship_name.to_lowercase())
+                })
+                .cloned()
+                .collect();
+            pruned.add_object(copy);
+        }
+        pruned
+    }
+}
+
+/// Field paths a WHERE or HAVING clause reads from its query's object, and
+/// the subqueries in it, which read from their own
+#[derive(Default)]
+struct FilterReads<'q> {
+    paths: Vec<&'q str>,
+    subqueries: Vec<&'q SoqlQuery>,
+    /// Depth inside subqueries and binds, whose identifiers are not fields
+    /// of the query's object
+    nested: usize,
+}
+
+impl<'q> Visitor<'q> for FilterReads<'q> {
+    fn visit_expression(&mut self, expr: &'q Expression) {
+        match expr {
+            Expression::SoqlSubquery(subquery) => {
+                if self.nested == 0 {
+                    self.subqueries.push(subquery);
+                }
+                self.nested += 1;
+            }
+            Expression::BindExpression(..) => self.nested += 1,
+            Expression::Identifier(name, _, _) if self.nested == 0 => self.paths.push(name),
+            _ => {}
+        }
+    }
+
+    fn leave_expression(&mut self, expr: &'q Expression) {
+        if matches!(
+            expr,
+            Expression::SoqlSubquery(_) | Expression::BindExpression(..)
+        ) {
+            self.nested -= 1;
+        }
+    }
+}
@@ -35,10 +35,121 @@ impl SalesforceSchema {
         self.objects.values()
     }
 
+    /// API names of all objects, sorted
+    pub fn object_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.objects.values().map(|o| o.name.clone()).collect();
+        names.sort();
+        names
+    }
+
     /// Check if an object exists
     pub fn has_object(&self, name: &str) -> bool {
         self.objects.contains_key(&name.to_lowercase())
     }
+
+    /// Names that can follow `object.` in a SOQL path: fields, then parent
+    /// and child relationships, each sorted by name
+    ///
+    /// Empty when the object is not in the schema.
+    pub fn completions_for(&self, object: &str) -> Vec<Completion> {
+        let Some(object) = self.get_object(object) else {
+            return Vec::new();
+        };
+
+        let mut fields: Vec<_> = object
+            .fields()
+            .map(|field| Completion {
+                name: field.name.clone(),
+                kind: CompletionKind::Field(field.field_type),
+            })
+            .collect();
+        let mut relationships: Vec<_> = object
+            .fields()
+            .filter_map(|field| {
+                Some(Completion {
+                    name: field.relationship_name.clone()?,
+                    kind: CompletionKind::Parent {
+                        targets: field.reference_to.clone()?,
+                    },
+                })
+            })
+            .chain(object.child_relationships.iter().map(|rel| Completion {
+                name: rel.relationship_name.clone(),
+                kind: CompletionKind::Child {
+                    object: rel.child_object.clone(),
+                },
+            }))
+            .collect();
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        relationships.sort_by(|a, b| a.name.cmp(&b.name));
+        fields.extend(relationships);
+        fields
+    }
+
+    /// Object a relationship of `object` leads to (case-insensitive)
+    ///
+    /// A parent relationship gives its referenced object, the first one for
+    /// a polymorphic lookup; a child relationship gives the child object.
+    pub fn relationship_target(&self, object: &str, relationship: &str) -> Option<&str> {
+        let object = self.get_object(object)?;
+        if let Some(field) = object.get_parent_relationship(relationship) {
+            return field.reference_to.as_ref()?.first().map(String::as_str);
+        }
+        object
+            .get_child_relationship(relationship)
+            .map(|rel| rel.child_object.as_str())
+    }
+}
+
+/// A name offered by [`SalesforceSchema::completions_for`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// API name as declared (`Name`, `Owner`, `Contacts`)
+    pub name: String,
+    pub kind: CompletionKind,
+}
+
+/// What a [`Completion`] names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompletionKind {
+    Field(SalesforceFieldType),
+    /// Parent relationship (`Owner.Name`), with every object it can point to
+    Parent {
+        targets: Vec<String>,
+    },
+    /// Child relationship, queried in a subquery (`SELECT Id FROM Contacts`)
+    Child {
+        object: String,
+    },
+}
+
+/// Kind of SObject, which decides what SOQL it accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ObjectKind {
+    #[default]
+    Standard,
+    /// `__c` objects
+    Custom,
+    /// `__b` objects: filters must follow the index, no LIKE / NOT IN / INCLUDES
+    BigObject,
+    /// `__x` objects backed by an external data source: no aggregates
+    External,
+}
+
+impl ObjectKind {
+    /// Infer the kind from an API name suffix (`__c`, `__b`, `__x`)
+    pub fn from_api_name(name: &str) -> Self {
+        let lower = name.to_lowercase();
+        if lower.ends_with("__b") {
+            ObjectKind::BigObject
+        } else if lower.ends_with("__x") {
+            ObjectKind::External
+        } else if lower.ends_with("__c") {
+            ObjectKind::Custom
+        } else {
+            ObjectKind::Standard
+        }
+    }
 }
 
 /// Description of a Salesforce SObject
@@ -56,6 +167,10 @@ pub struct SObjectDescribe {
     pub child_relationships: Vec<ChildRelationship>,
     /// Whether this object supports record types
     pub has_record_types: bool,
+    /// Object kind, inferred from the API name suffix unless overridden
+    pub object_kind: ObjectKind,
+    /// Big object index fields, in index order
+    pub index_fields: Vec<String>,
 }
 
 impl SObjectDescribe {
@@ -63,6 +178,7 @@ impl SObjectDescribe {
     pub fn new(name: impl Into<String>) -> Self {
         let name = name.into();
         let table_name = to_snake_case(&name);
+        let object_kind = ObjectKind::from_api_name(&name);
         Self {
             label: name.clone(),
             name,
@@ -70,6 +186,8 @@ impl SObjectDescribe {
             fields: HashMap::new(),
             child_relationships: Vec::new(),
             has_record_types: false,
+            object_kind,
+            index_fields: Vec::new(),
         }
     }
 
@@ -85,6 +203,18 @@ impl SObjectDescribe {
         self
     }
 
+    /// Override the kind inferred from the API name
+    pub fn with_object_kind(mut self, kind: ObjectKind) -> Self {
+        self.object_kind = kind;
+        self
+    }
+
+    /// Set the big object index fields, leading field first
+    pub fn with_index_fields(mut self, fields: Vec<String>) -> Self {
+        self.index_fields = fields;
+        self
+    }
+
     /// Add a field to the object
     pub fn add_field(&mut self, field: FieldDescribe) {
         self.fields.insert(field.name.to_lowercase(), field);
@@ -110,6 +240,17 @@ impl SObjectDescribe {
         self.child_relationships.push(relationship);
     }
 
+    /// Find the lookup field behind a parent relationship name (`Owner` for
+    /// `OwnerId`)
+    pub fn get_parent_relationship(&self, name: &str) -> Option<&FieldDescribe> {
+        self.fields().find(|field| {
+            field
+                .relationship_name
+                .as_deref()
+                .is_some_and(|rel| rel.eq_ignore_ascii_case(name))
+        })
+    }
+
     /// Find a child relationship by name
     pub fn get_child_relationship(&self, name: &str) -> Option<&ChildRelationship> {
         let lower = name.to_lowercase();
@@ -134,6 +275,9 @@ pub struct FieldDescribe {
     pub relationship_name: Option<String>,
     /// Is this a polymorphic field (e.g., OwnerId -> User|Group)
     pub is_polymorphic: bool,
+    /// For polymorphic fields: column holding the target object name
+    /// (defaults to "{column_name}_type")
+    pub type_column: Option<String>,
     /// Field length for strings
     pub length: Option<u32>,
     /// Precision for numeric types
@@ -144,6 +288,8 @@ pub struct FieldDescribe {
     pub nillable: bool,
     /// For picklists: valid values
     pub picklist_values: Option<Vec<String>>,
+    /// Value the database fills in when an insert omits the column
+    pub default_value: Option<FieldDefault>,
 }
 
 impl FieldDescribe {
@@ -158,11 +304,13 @@ impl FieldDescribe {
             reference_to: None,
             relationship_name: None,
             is_polymorphic: false,
+            type_column: None,
             length: None,
             precision: None,
             scale: None,
             nillable: true,
             picklist_values: None,
+            default_value: None,
         }
     }
 
@@ -185,6 +333,12 @@ impl FieldDescribe {
         self
     }
 
+    /// Set the type discriminator column of a polymorphic lookup
+    pub fn with_type_column(mut self, type_column: impl Into<String>) -> Self {
+        self.type_column = Some(type_column.into());
+        self
+    }
+
     /// Set the relationship name
     pub fn with_relationship_name(mut self, name: impl Into<String>) -> Self {
         self.relationship_name = Some(name.into());
@@ -216,10 +370,75 @@ impl FieldDescribe {
         self
     }
 
+    /// Set the column default applied on insert
+    pub fn with_default(mut self, value: impl Into<FieldDefault>) -> Self {
+        self.default_value = Some(value.into());
+        self
+    }
+
     /// Check if this is a relationship field
     pub fn is_relationship(&self) -> bool {
         self.reference_to.is_some()
     }
+
+    /// Column storing which object a polymorphic lookup points to
+    pub fn type_column_name(&self) -> Option<String> {
+        if !self.is_polymorphic {
+            return None;
+        }
+        Some(
+            self.type_column
+                .clone()
+                .unwrap_or_else(|| format!("{}_type", self.column_name)),
+        )
+    }
+}
+
+/// Column default for a field, rendered per dialect by the DDL generator
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldDefault {
+    Boolean(bool),
+    Integer(i64),
+    Number(f64),
+    Text(String),
+    /// Computed on insert (e.g. CreatedDate)
+    CurrentTimestamp,
+}
+
+impl From<bool> for FieldDefault {
+    fn from(value: bool) -> Self {
+        FieldDefault::Boolean(value)
+    }
+}
+
+impl From<i32> for FieldDefault {
+    fn from(value: i32) -> Self {
+        FieldDefault::Integer(value.into())
+    }
+}
+
+impl From<i64> for FieldDefault {
+    fn from(value: i64) -> Self {
+        FieldDefault::Integer(value)
+    }
+}
+
+impl From<f64> for FieldDefault {
+    fn from(value: f64) -> Self {
+        FieldDefault::Number(value)
+    }
+}
+
+impl From<&str> for FieldDefault {
+    fn from(value: &str) -> Self {
+        FieldDefault::Text(value.to_string())
+    }
+}
+
+impl From<String> for FieldDefault {
+    fn from(value: String) -> Self {
+        FieldDefault::Text(value)
+    }
 }
 
 /// Child relationship (for subqueries like SELECT ... FROM Contacts)
@@ -277,6 +496,44 @@ pub enum SalesforceFieldType {
 }
 
 impl SalesforceFieldType {
+    /// Whether values are free text or picklist labels (sorted as strings)
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self,
+            SalesforceFieldType::String
+                | SalesforceFieldType::TextArea
+                | SalesforceFieldType::LongTextArea
+                | SalesforceFieldType::RichTextArea
+                | SalesforceFieldType::Phone
+                | SalesforceFieldType::Email
+                | SalesforceFieldType::Url
+                | SalesforceFieldType::Picklist
+                | SalesforceFieldType::MultiPicklist
+        )
+    }
+
+    /// Whether values are stored as SQL numbers
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            SalesforceFieldType::Integer
+                | SalesforceFieldType::Double
+                | SalesforceFieldType::Currency
+                | SalesforceFieldType::Percent
+        )
+    }
+
+    /// Whether values are record Ids: the Id field itself and lookups
+    pub fn is_id(&self) -> bool {
+        matches!(
+            self,
+            SalesforceFieldType::Id
+                | SalesforceFieldType::Lookup
+                | SalesforceFieldType::MasterDetail
+                | SalesforceFieldType::Reference
+        )
+    }
+
     /// Get the appropriate SQL type for this field type
     pub fn to_sql_type(&self) -> &'static str {
         match self {
@@ -13,6 +13,7 @@ pub fn create_sales_cloud_schema() -> SalesforceSchema {
 
     // Add all standard objects
     schema.add_object(create_user());
+    schema.add_object(create_record_type());
     schema.add_object(create_account());
     schema.add_object(create_contact());
     schema.add_object(create_lead());
@@ -73,6 +74,15 @@ fn add_system_fields(obj: &mut SObjectDescribe) {
     ));
 }
 
+/// Record type lookup for objects with record types
+fn add_record_type_field(obj: &mut SObjectDescribe) {
+    obj.add_field(
+        FieldDescribe::new("RecordTypeId", SalesforceFieldType::Lookup)
+            .with_reference("RecordType")
+            .with_relationship_name("RecordType"),
+    );
+}
+
 /// User object
 fn create_user() -> SObjectDescribe {
     let mut obj = SObjectDescribe::new("User");
@@ -128,10 +138,50 @@ fn create_user() -> SObjectDescribe {
     obj
 }
 
+/// RecordType object - record types of other objects
+///
+/// Unlike most objects it has no `IsDeleted` field.
+fn create_record_type() -> SObjectDescribe {
+    let mut obj = SObjectDescribe::new("RecordType");
+    obj.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+    obj.add_field(FieldDescribe::new("Name", SalesforceFieldType::String).with_nillable(false));
+    obj.add_field(
+        FieldDescribe::new("DeveloperName", SalesforceFieldType::String).with_nillable(false),
+    );
+    obj.add_field(FieldDescribe::new(
+        "SobjectType",
+        SalesforceFieldType::Picklist,
+    ));
+    obj.add_field(FieldDescribe::new("IsActive", SalesforceFieldType::Boolean));
+    obj.add_field(FieldDescribe::new(
+        "Description",
+        SalesforceFieldType::String,
+    ));
+    obj.add_field(FieldDescribe::new(
+        "NamespacePrefix",
+        SalesforceFieldType::String,
+    ));
+    obj.add_field(FieldDescribe::new(
+        "BusinessProcessId",
+        SalesforceFieldType::Lookup,
+    ));
+    obj.add_field(FieldDescribe::new(
+        "CreatedDate",
+        SalesforceFieldType::DateTime,
+    ));
+    obj.add_field(FieldDescribe::new(
+        "LastModifiedDate",
+        SalesforceFieldType::DateTime,
+    ));
+
+    obj
+}
+
 /// Account object - companies and organizations
 fn create_account() -> SObjectDescribe {
     let mut obj = SObjectDescribe::new("Account");
     add_system_fields(&mut obj);
+    add_record_type_field(&mut obj);
 
     // Name and identification
     obj.add_field(FieldDescribe::new("Name", SalesforceFieldType::String).with_nillable(false));
@@ -269,6 +319,7 @@ fn create_account() -> SObjectDescribe {
 fn create_contact() -> SObjectDescribe {
     let mut obj = SObjectDescribe::new("Contact");
     add_system_fields(&mut obj);
+    add_record_type_field(&mut obj);
 
     // Name fields
     obj.add_field(FieldDescribe::new(
@@ -410,6 +461,7 @@ fn create_contact() -> SObjectDescribe {
 fn create_lead() -> SObjectDescribe {
     let mut obj = SObjectDescribe::new("Lead");
     add_system_fields(&mut obj);
+    add_record_type_field(&mut obj);
 
     // Name fields
     obj.add_field(FieldDescribe::new(
@@ -532,6 +584,7 @@ fn create_lead() -> SObjectDescribe {
 fn create_opportunity() -> SObjectDescribe {
     let mut obj = SObjectDescribe::new("Opportunity");
     add_system_fields(&mut obj);
+    add_record_type_field(&mut obj);
 
     // Core fields
     obj.add_field(FieldDescribe::new("Name", SalesforceFieldType::String).with_nillable(false));
@@ -804,6 +857,7 @@ fn create_pricebook_entry() -> SObjectDescribe {
 fn create_case() -> SObjectDescribe {
     let mut obj = SObjectDescribe::new("Case");
     add_system_fields(&mut obj);
+    add_record_type_field(&mut obj);
 
     // Case identification
     obj.add_field(FieldDescribe::new("CaseNumber", SalesforceFieldType::Auto));
@@ -1078,6 +1132,7 @@ fn create_event() -> SObjectDescribe {
 fn create_campaign() -> SObjectDescribe {
     let mut obj = SObjectDescribe::new("Campaign");
     add_system_fields(&mut obj);
+    add_record_type_field(&mut obj);
 
     // Core fields
     obj.add_field(FieldDescribe::new("Name", SalesforceFieldType::String).with_nillable(false));
@@ -1632,6 +1687,7 @@ fn create_attachment() -> SObjectDescribe {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sql::schema::CompletionKind;
 
     #[test]
     fn test_sales_cloud_schema_has_all_objects() {
@@ -1639,6 +1695,7 @@ mod tests {
 
         // Verify all expected objects exist
         assert!(schema.get_object("User").is_some());
+        assert!(schema.get_object("RecordType").is_some());
         assert!(schema.get_object("Account").is_some());
         assert!(schema.get_object("Contact").is_some());
         assert!(schema.get_object("Lead").is_some());
@@ -1682,6 +1739,55 @@ mod tests {
         assert!(account.get_child_relationship("Cases").is_some());
     }
 
+    #[test]
+    fn test_account_completions() {
+        let schema = create_sales_cloud_schema();
+        let completions = schema.completions_for("account");
+
+        let find = |name: &str| completions.iter().find(|c| c.name == name).map(|c| &c.kind);
+        assert_eq!(
+            find("Name"),
+            Some(&CompletionKind::Field(SalesforceFieldType::String))
+        );
+        assert_eq!(
+            find("OwnerId"),
+            Some(&CompletionKind::Field(SalesforceFieldType::Reference))
+        );
+        assert_eq!(
+            find("Owner"),
+            Some(&CompletionKind::Parent {
+                targets: vec!["User".to_string(), "Group".to_string()],
+            })
+        );
+        assert_eq!(
+            find("Contacts"),
+            Some(&CompletionKind::Child {
+                object: "Contact".to_string(),
+            })
+        );
+
+        // Fields come before relationships
+        let first_relationship = completions
+            .iter()
+            .position(|c| !matches!(c.kind, CompletionKind::Field(_)))
+            .unwrap();
+        assert!(completions[first_relationship..]
+            .iter()
+            .all(|c| !matches!(c.kind, CompletionKind::Field(_))));
+
+        assert_eq!(schema.relationship_target("Account", "owner"), Some("User"));
+        assert_eq!(
+            schema.relationship_target("Account", "Parent"),
+            Some("Account")
+        );
+        assert_eq!(
+            schema.relationship_target("Account", "Contacts"),
+            Some("Contact")
+        );
+        assert_eq!(schema.relationship_target("Account", "Name"), None);
+        assert!(schema.completions_for("Widget__c").is_empty());
+    }
+
     #[test]
     fn test_opportunity_has_expected_fields() {
         let schema = create_sales_cloud_schema();
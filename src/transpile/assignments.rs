@@ -0,0 +1,68 @@
+//! Assignments and increments used as values
+//!
+//! Apex and JavaScript agree on most expression-as-value forms, so
+//! `y = x += 2`, `if ((rec = lookup()) != null)`, `values[i++] = v` and
+//! `return total += x` are emitted token for token: both languages evaluate
+//! the target (including any index expression) before the right operand and
+//! yield the assigned value. Two forms need help:
+//!
+//! - String `+=` renders a null operand as `'null'` in both languages, but
+//!   JavaScript also produces `undefined` (a missing `Map.get` key, an
+//!   absent record field), which would append `'undefined'`. Operands that
+//!   may be null go through `$runtime.str`.
+//! - Request-scoped statics are written with `$runtime.statics.set`, which
+//!   returns the Map; used as a value the stored value is read back.
+//!
+//! Deliberately unsupported, emitted as is:
+//!
+//! - Integer `/=` divides without truncating, like `/` elsewhere.
+//! - `+=` on a `Long` with an Integer operand mixes bigint and number, which
+//!   throws a `TypeError`.
+//! - A String declared without an initializer starts as `undefined`, so the
+//!   first `+=` renders `'undefined'` where Apex renders `'null'`.
+
+use crate::ast::{BinaryOp, Expression, TypeRef};
+
+/// Whether a String `+=` needs its operand wrapped in `$runtime.str`
+pub(crate) fn needs_string_coercion(target_type: &TypeRef, value: &Expression) -> bool {
+    target_type.name.eq_ignore_ascii_case("String") && !target_type.is_array && !is_non_null(value)
+}
+
+/// Expressions that can never evaluate to null or undefined
+fn is_non_null(expr: &Expression) -> bool {
+    match expr {
+        Expression::String(..)
+        | Expression::Integer(..)
+        | Expression::Long(..)
+        | Expression::Double(..)
+        | Expression::Boolean(..) => true,
+        // String concatenation and arithmetic always produce a value
+        Expression::Binary(binary) => binary.operator == BinaryOp::Add,
+        Expression::Parenthesized(inner, _, _) => is_non_null(inner),
+        Expression::Ternary(ternary) => {
+            is_non_null(&ternary.then_expr) && is_non_null(&ternary.else_expr)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::NodeId;
+    use crate::lexer::Span;
+
+    #[test]
+    fn test_only_nullable_string_operands_are_coerced() {
+        let string = TypeRef::simple("String", Span::new(0, 0));
+        let literal = Expression::String("done".to_string(), Span::new(0, 0), NodeId::DUMMY);
+        let variable = Expression::Identifier("value".to_string(), Span::new(0, 0), NodeId::DUMMY);
+
+        assert!(!needs_string_coercion(&string, &literal));
+        assert!(needs_string_coercion(&string, &variable));
+        assert!(!needs_string_coercion(
+            &TypeRef::simple("Integer", Span::new(0, 0)),
+            &variable
+        ));
+    }
+}
@@ -1,16 +1,45 @@
 //! Code generation for Apex to TypeScript transpilation
 
+use super::assignments::needs_string_coercion;
+use super::collections::{
+    collection_kind, collection_property, keys_records_by_id, CollectionProperty, IdMapSource,
+};
 use super::context::RUNTIME_INTERFACE;
+use super::dates::{arithmetic_helper, clock_call, date_arithmetic};
+use super::describe::{describe_property, is_get_describe, is_global_describe, sobject_token};
+use super::env_flags::eliminate_branches;
 use super::error::TranspileError;
-use super::TranspileOptions;
+use super::futures::{future_call, future_methods, FutureMethod};
+use super::records::{declared_types, is_sobject_type, is_trigger_record};
+use super::single_record::{
+    list_element_type, optional_record_lists, presence_check, query_of, record_list_use,
+    RecordListUse,
+};
+use super::sorting::{comparable_classes, local_types, sort_comparator};
+use super::split::{can_move, methods_module, unit_module, SplitClass};
+use super::statics::{detect_lazy_init, find_request_scoped_statics, static_field_name};
+use super::strings::{element_type_name, enum_names, enum_of, return_types, value_of_argument};
+use super::triggers::{event_key, handler_name, split_trigger, TriggerSplit};
+use super::{StaticCacheStrategy, TranspileOptions, TranspiledModule};
+use crate::analysis::dynamic_soql::{object_guards, ObjectGuard};
+use crate::analysis::markers::is_deprecated;
+use crate::analysis::soql_context::{classify, SoqlContext};
+use crate::ast::printer;
+use crate::ast::visitor::{walk_block, Visitor};
 use crate::ast::{
-    AccessModifier, AssignmentOp, BinaryOp, Block, ClassDeclaration, ClassMember, CompilationUnit,
-    ConstructorDeclaration, DmlOperation, DmlStatement, DoWhileStatement, EnumDeclaration,
-    Expression, FieldDeclaration, ForEachStatement, ForInit, ForStatement, IfStatement,
-    InterfaceDeclaration, InterfaceMember, LocalVariableDeclaration, MethodDeclaration,
-    PropertyDeclaration, SelectField, SoqlQuery, Statement, SwitchStatement, TriggerDeclaration,
-    TriggerEvent, TryStatement, TypeDeclaration, TypeRef, UnaryOp, WhenValue, WhileStatement,
+    AccessModifier, Annotation, AssignmentOp, BinaryOp, Block, ClassDeclaration, ClassMember,
+    CompilationUnit, ConstructorDeclaration, DmlOperation, DmlStatement, DoWhileStatement,
+    EnumDeclaration, Expression, FieldDeclaration, ForEachStatement, ForInit, ForStatement,
+    IfStatement, InterfaceDeclaration, InterfaceMember, LocalVariableDeclaration, MethodCallExpr,
+    MethodDeclaration, NewExpr, NodeId, PropertyDeclaration, SoqlQuery, Statement, SwitchStatement,
+    TriggerDeclaration, TryStatement, TypeDeclaration, TypeParameter, TypeRef, UnaryOp, WhenValue,
+    WhileStatement,
 };
+use crate::cancel::CancellationToken;
+use crate::lexer::Span;
+
+/// Module constant holding `TranspileOptions::allowed_objects`
+const ALLOWED_OBJECTS: &str = "ALLOWED_OBJECTS";
 
 /// Transpiler converts Apex AST to TypeScript/JavaScript
 pub struct Transpiler {
@@ -23,6 +52,50 @@ pub struct Transpiler {
     current_class: Option<String>,
     /// Static fields in current class
     static_fields: std::collections::HashSet<String>,
+    /// Whether we are inside a trigger body (trigger context is in scope)
+    in_trigger: bool,
+    /// Lazy-init getters in current class (method name -> static field)
+    lazy_inits: std::collections::HashMap<String, String>,
+    /// Statics in current class stored per request in `$runtime.statics`
+    request_statics: std::collections::HashSet<String>,
+    /// Allow-list checks to emit before statements running dynamic SOQL
+    object_guards: Vec<ObjectGuard>,
+    cancellation: Option<CancellationToken>,
+    /// Lowercased names of classes in the unit implementing Comparable
+    comparable_classes: std::collections::HashSet<String>,
+    /// Enums declared in the unit, by lowercased name
+    enums: std::collections::HashMap<String, String>,
+    /// Lowercased names of every type declared in the unit
+    unit_types: std::collections::HashSet<String>,
+    /// Methods of the unit emitted async, as lowercased (class, method);
+    /// calls to them are awaited
+    async_methods: std::collections::HashSet<(String, String)>,
+    /// Return types of the unit's methods by lowercased (class, method)
+    return_types: std::collections::HashMap<(String, String), TypeRef>,
+    /// Methods of the current class by lowercased name, and whether each is
+    /// static, for qualifying calls made without a receiver
+    class_methods: std::collections::HashMap<String, bool>,
+    /// `@future` methods of the unit's classes, enqueued instead of called
+    futures: Vec<FutureMethod>,
+    /// Declared types of the current class's fields and properties
+    field_types: std::collections::HashMap<String, TypeRef>,
+    /// Declared types of the current method's parameters and locals
+    local_types: std::collections::HashMap<String, TypeRef>,
+    /// List locals of the current method emitted as one optional record
+    optional_records: std::collections::HashSet<String>,
+    /// Queries in the unit assigned to a single record, run with `queryOne`
+    single_record_queries: Vec<Span>,
+    /// Whether classes over `split_class_threshold` are split (only when
+    /// emitting modules)
+    splitting: bool,
+    /// Name of the module being emitted, imported by companion modules
+    module_name: String,
+    /// Top-level class currently emitted split
+    split_class: Option<SplitClass>,
+    /// Companion modules of the classes split so far
+    companions: Vec<TranspiledModule>,
+    /// What `this` is emitted as: `self` in a method moved out of its class
+    receiver: &'static str,
 }
 
 impl Transpiler {
@@ -38,27 +111,111 @@ impl Transpiler {
             needs_async: false,
             current_class: None,
             static_fields: std::collections::HashSet::new(),
+            in_trigger: false,
+            lazy_inits: std::collections::HashMap::new(),
+            request_statics: std::collections::HashSet::new(),
+            object_guards: Vec::new(),
+            cancellation: None,
+            comparable_classes: std::collections::HashSet::new(),
+            enums: std::collections::HashMap::new(),
+            unit_types: std::collections::HashSet::new(),
+            async_methods: std::collections::HashSet::new(),
+            return_types: std::collections::HashMap::new(),
+            class_methods: std::collections::HashMap::new(),
+            futures: Vec::new(),
+            field_types: std::collections::HashMap::new(),
+            local_types: std::collections::HashMap::new(),
+            optional_records: std::collections::HashSet::new(),
+            single_record_queries: Vec::new(),
+            splitting: false,
+            module_name: String::new(),
+            split_class: None,
+            companions: Vec::new(),
+            receiver: "this",
         }
     }
 
+    /// Stop with [`TranspileError::Cancelled`] once `token` fires
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Stop with [`TranspileError::Cancelled`] once `timeout` has elapsed
+    pub fn with_timeout(self, timeout: std::time::Duration) -> Self {
+        self.with_cancellation(CancellationToken::with_timeout(timeout))
+    }
+
     /// Transpile a compilation unit to TypeScript
     pub fn transpile(&mut self, unit: &CompilationUnit) -> Result<String, TranspileError> {
         self.output.clear();
 
+        let pruned;
+        let unit = if self.options.env_flags.is_empty() {
+            unit
+        } else {
+            pruned = eliminate_branches(unit.clone(), &self.options.env_flags);
+            &pruned
+        };
+
         // Add header
         if self.options.include_imports {
             self.emit_header();
         }
 
+        self.object_guards = if self.options.allowed_objects.is_some() {
+            object_guards(unit)
+        } else {
+            Vec::new()
+        };
+        if !self.object_guards.is_empty() {
+            self.emit_allowed_objects();
+        }
+        self.comparable_classes = comparable_classes(unit);
+        let project_comparables = self.options.comparable_classes.iter();
+        self.comparable_classes
+            .extend(project_comparables.map(|name| name.to_lowercase()));
+        self.enums = enum_names(unit);
+        self.unit_types = declared_types(unit);
+        self.futures = future_methods(unit)?;
+        self.find_async_methods(unit);
+        self.return_types = return_types(unit);
+        self.single_record_queries = classify(unit)
+            .into_iter()
+            .filter(|usage| matches!(usage.context, SoqlContext::SingleRecordAssignment { .. }))
+            .map(|usage| usage.span)
+            .collect();
+
         // Transpile each declaration
         for decl in &unit.declarations {
             self.transpile_declaration(decl)?;
             self.newline();
         }
+        self.emit_entry_points();
 
         Ok(self.output.clone())
     }
 
+    /// Transpile a compilation unit to its module plus the companion module
+    /// of every class split by `TranspileOptions::split_class_threshold`
+    pub fn transpile_modules(
+        &mut self,
+        unit: &CompilationUnit,
+    ) -> Result<Vec<TranspiledModule>, TranspileError> {
+        self.companions.clear();
+        self.module_name = unit_module(unit);
+        self.splitting = true;
+        let code = self.transpile(unit);
+        self.splitting = false;
+
+        let mut modules = vec![TranspiledModule {
+            name: self.module_name.clone(),
+            code: code?,
+        }];
+        modules.append(&mut self.companions);
+        Ok(modules)
+    }
+
     fn emit_header(&mut self) {
         self.writeln("// Generated by ApexRust Transpiler");
         self.writeln("// Do not edit directly");
@@ -70,22 +227,259 @@ impl Transpiler {
         }
     }
 
+    /// Module constant listing the objects dynamic SOQL may query
+    fn emit_allowed_objects(&mut self) {
+        let names: Vec<String> = self
+            .options
+            .allowed_objects
+            .iter()
+            .flatten()
+            .map(|name| format!("\"{}\"", name))
+            .collect();
+        let type_annotation = if self.options.typescript {
+            ": readonly string[]"
+        } else {
+            ""
+        };
+        self.writeln(&format!(
+            "const {}{} = [{}];",
+            ALLOWED_OBJECTS,
+            type_annotation,
+            names.join(", ")
+        ));
+        self.newline();
+    }
+
+    /// `<Class>_ENTRY_POINTS`, through which the host runs the `@future`
+    /// calls the class enqueued
+    fn emit_entry_points(&mut self) {
+        let futures = self.futures.clone();
+        let args_type = if self.options.typescript {
+            ": any[]"
+        } else {
+            ""
+        };
+        for class in futures.chunk_by(|a, b| a.class == b.class) {
+            self.writeln(&format!(
+                "export const {}_ENTRY_POINTS = {{",
+                class[0].class
+            ));
+            self.indent();
+            for future in class {
+                let args: Vec<String> = (0..future.arity).map(|i| format!("args[{}]", i)).collect();
+                self.write_indent();
+                self.writeln(&format!(
+                    "\"{}\": {{ kind: \"future\", callout: {}, run: (args{}) => {}({}) }},",
+                    future.qualified_name(),
+                    future.callout,
+                    args_type,
+                    future.qualified_name(),
+                    args.join(", ")
+                ));
+            }
+            self.dedent();
+            self.writeln("};");
+            self.newline();
+        }
+    }
+
     // ========================================================================
     // Declaration transpilation
     // ========================================================================
 
     fn transpile_declaration(&mut self, decl: &TypeDeclaration) -> Result<(), TranspileError> {
+        self.check_cancelled()?;
         match decl {
-            TypeDeclaration::Class(class) => self.transpile_class(class),
+            TypeDeclaration::Class(class) => match self.options.split_class_threshold {
+                Some(threshold) if self.splitting => {
+                    let start = self.output.len();
+                    self.transpile_class(class)?;
+                    if self.output.len() - start > threshold {
+                        self.output.truncate(start);
+                        self.transpile_split_class(class)?;
+                    }
+                    Ok(())
+                }
+                _ => self.transpile_class(class),
+            },
             TypeDeclaration::Interface(iface) => self.transpile_interface(iface),
             TypeDeclaration::Enum(enum_decl) => self.transpile_enum(enum_decl),
             TypeDeclaration::Trigger(trigger) => self.transpile_trigger(trigger),
         }
     }
 
+    /// Comparator for a no-argument `sort()` on a list whose declared type
+    /// is known
+    fn list_sort_comparator(&self, call: &MethodCallExpr) -> Option<&'static str> {
+        if !call.name.eq_ignore_ascii_case("sort") || !call.arguments.is_empty() {
+            return None;
+        }
+        let list_type = self.declared_type(call.object.as_ref()?)?;
+        sort_comparator(list_type, &self.comparable_classes)
+    }
+
+    /// A size check or element access on a list local emitted as an
+    /// optional record
+    fn optional_record_use<'e>(&self, expr: &'e Expression) -> Option<(&'e str, RecordListUse)> {
+        record_list_use(expr)
+            .filter(|(name, _)| self.optional_records.contains(&name.to_lowercase()))
+    }
+
+    /// A whole size check on a list local emitted as an optional record
+    fn optional_presence<'e>(&self, expr: &'e Expression) -> Option<(&'e str, bool)> {
+        presence_check(expr)
+            .filter(|(name, _)| self.optional_records.contains(&name.to_lowercase()))
+    }
+
+    /// The `@future` method `call` enqueues
+    fn enqueued_future(&self, call: &MethodCallExpr) -> Option<&FutureMethod> {
+        future_call(call, self.current_class.as_deref(), &self.futures)
+    }
+
+    fn is_future(&self, method: &MethodDeclaration) -> bool {
+        self.current_class.as_deref().is_some_and(|class| {
+            self.futures.iter().any(|future| {
+                future.class.eq_ignore_ascii_case(class)
+                    && future.name.eq_ignore_ascii_case(&method.name)
+                    && future.arity == method.parameters.len()
+            })
+        })
+    }
+
+    /// Declared type of a local, parameter, field or `this.field`
+    fn declared_type(&self, expr: &Expression) -> Option<&TypeRef> {
+        match expr {
+            Expression::Identifier(name, _, _) => {
+                let name = name.to_lowercase();
+                self.local_types
+                    .get(&name)
+                    .or_else(|| self.field_types.get(&name))
+            }
+            Expression::FieldAccess(access) if matches!(access.object, Expression::This(..)) => {
+                self.field_types.get(&access.field.to_lowercase())
+            }
+            _ => None,
+        }
+    }
+
+    /// Name of the type of `expr` when known: a declared variable, an
+    /// element of a declared list, set or array (`seasons[0]`,
+    /// `seasons.get(0)`), or what a method of the unit returns
+    fn value_type_name(&self, expr: &Expression) -> Option<&str> {
+        match expr {
+            Expression::ArrayAccess(access) => {
+                element_type_name(self.declared_type(&access.array)?)
+            }
+            Expression::MethodCall(call)
+                if call.name.eq_ignore_ascii_case("get")
+                    && call.arguments.len() == 1
+                    && self.receiver_type(call).is_some() =>
+            {
+                element_type_name(self.receiver_type(call)?)
+            }
+            Expression::MethodCall(call) => {
+                let key = (self.callee_class(call)?, call.name.to_lowercase());
+                let returns = self.return_types.get(&key)?;
+                (!returns.is_array).then_some(returns.name.as_str())
+            }
+            other => {
+                let declared = self.declared_type(other)?;
+                (!declared.is_array).then_some(declared.name.as_str())
+            }
+        }
+    }
+
+    /// Property replacing a no-argument collection method call such as
+    /// `list.size()`, given the receiver's declared type
+    fn collection_property(&self, call: &MethodCallExpr) -> Option<CollectionProperty> {
+        match call.object {
+            Some(ref object) if call.arguments.is_empty() => {
+                let receiver = self.declared_type(object).and_then(collection_kind);
+                collection_property(receiver, &call.name)
+            }
+            _ => None,
+        }
+    }
+
+    /// How `new Map<Id, T>(x)` builds its entries, given the declared type
+    /// of `x`
+    fn keys_records_by_id(&self, new_expr: &NewExpr) -> Option<IdMapSource> {
+        let argument_type = new_expr
+            .arguments
+            .first()
+            .and_then(|argument| self.declared_type(argument));
+        keys_records_by_id(new_expr, argument_type)
+    }
+
+    /// Declared type of the receiver of `call`
+    fn receiver_type(&self, call: &MethodCallExpr) -> Option<&TypeRef> {
+        self.declared_type(call.object.as_ref()?)
+    }
+
+    /// Declared type of the receiver of an `addX()` call, looking through
+    /// earlier `addX()` calls in a chain such as `due.addDays(1).addHours(2)`
+    fn temporal_receiver_type(&self, call: &MethodCallExpr) -> Option<&TypeRef> {
+        match call.object.as_ref()? {
+            Expression::MethodCall(inner) if arithmetic_helper(&inner.name).is_some() => {
+                self.temporal_receiver_type(inner)
+            }
+            receiver => self.declared_type(receiver),
+        }
+    }
+
+    /// Declared type of `token` in `token.getDescribe().getLabel()`
+    fn describe_token_type(&self, call: &MethodCallExpr) -> Option<&TypeRef> {
+        match &call.object {
+            Some(Expression::MethodCall(describe)) => self.receiver_type(describe),
+            _ => None,
+        }
+    }
+
+    /// Whether `expr` is an SObject record, or a field of one
+    fn is_record(&self, expr: &Expression) -> bool {
+        let sobject = |expr: &Expression| {
+            is_trigger_record(expr)
+                || self
+                    .declared_type(expr)
+                    .is_some_and(|type_ref| is_sobject_type(type_ref, &self.unit_types))
+        };
+        match expr {
+            Expression::FieldAccess(access) if !matches!(access.object, Expression::This(..)) => {
+                sobject(&access.object)
+            }
+            _ => sobject(expr),
+        }
+    }
+
+    fn check_cancelled(&self) -> Result<(), TranspileError> {
+        match &self.cancellation {
+            Some(token) if token.is_cancelled() => Err(TranspileError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
     fn transpile_class(&mut self, class: &ClassDeclaration) -> Result<(), TranspileError> {
-        // Export modifier
-        let export = if class.modifiers.access == AccessModifier::Public {
+        self.check_cancelled()?;
+
+        // Classes can't nest in JavaScript: inner types are emitted first
+        // and the class refers to them through static members
+        for member in &class.members {
+            let hoisted = match member {
+                ClassMember::InnerClass(inner) => self.transpile_class(inner),
+                ClassMember::InnerInterface(iface) => self.transpile_interface(iface),
+                ClassMember::InnerEnum(enum_decl) => self.transpile_enum(enum_decl),
+                _ => continue,
+            };
+            hoisted?;
+            self.newline();
+        }
+
+        self.write_deprecated(&class.annotations);
+
+        // Export modifier (a split class is imported by its companion)
+        let export = if class.modifiers.access == AccessModifier::Public
+            || self.is_split_class(&class.name)
+        {
             "export "
         } else {
             ""
@@ -105,8 +499,8 @@ impl Transpiler {
             self.write(&format!(" extends {}", self.type_ref_to_ts(extends)));
         }
 
-        // Implements
-        if !class.implements.is_empty() {
+        // Implements (interfaces are type-only)
+        if !class.implements.is_empty() && self.options.typescript {
             let impls: Vec<String> = class
                 .implements
                 .iter()
@@ -121,8 +515,34 @@ impl Transpiler {
         // Track current class and collect static fields
         let old_class = self.current_class.take();
         let old_static_fields = std::mem::take(&mut self.static_fields);
+        let old_lazy_inits = std::mem::take(&mut self.lazy_inits);
+        let old_request_statics = std::mem::take(&mut self.request_statics);
+        let old_field_types = std::mem::take(&mut self.field_types);
+        let old_class_methods = std::mem::take(&mut self.class_methods);
 
         self.current_class = Some(class.name.clone());
+        for member in &class.members {
+            if let ClassMember::Method(method) = member {
+                self.class_methods
+                    .insert(method.name.to_lowercase(), method.modifiers.is_static);
+            }
+        }
+
+        for member in &class.members {
+            match member {
+                ClassMember::Field(field) => {
+                    for declarator in &field.declarators {
+                        self.field_types
+                            .insert(declarator.name.to_lowercase(), field.type_ref.clone());
+                    }
+                }
+                ClassMember::Property(prop) => {
+                    self.field_types
+                        .insert(prop.name.to_lowercase(), prop.type_ref.clone());
+                }
+                _ => {}
+            }
+        }
 
         // First pass: collect static field names
         for member in &class.members {
@@ -135,14 +555,31 @@ impl Transpiler {
             }
         }
 
+        // Second pass: static cache analysis
+        for member in &class.members {
+            if let ClassMember::Method(method) = member {
+                if let Some(field) = detect_lazy_init(method, &class.name, &self.static_fields) {
+                    self.lazy_inits.insert(method.name.clone(), field);
+                }
+            }
+        }
+        if self.options.static_cache_strategy == StaticCacheStrategy::Request {
+            self.request_statics = find_request_scoped_statics(class, &self.static_fields);
+        }
+
         // Transpile members
         for member in &class.members {
             self.transpile_class_member(member)?;
         }
+        self.transpile_to_json(class);
 
         // Restore previous context
         self.current_class = old_class;
         self.static_fields = old_static_fields;
+        self.lazy_inits = old_lazy_inits;
+        self.request_statics = old_request_statics;
+        self.field_types = old_field_types;
+        self.class_methods = old_class_methods;
 
         self.dedent();
         self.writeln("}");
@@ -150,6 +587,108 @@ impl Transpiler {
         Ok(())
     }
 
+    /// Emit `toJSON()` for a class with transient instance fields, so
+    /// `JSON.stringify` leaves them out as `JSON.serialize` does in Apex
+    fn transpile_to_json(&mut self, class: &ClassDeclaration) {
+        let transient: Vec<&str> = class
+            .members
+            .iter()
+            .flat_map(|member| match member {
+                ClassMember::Field(field)
+                    if field.modifiers.is_transient && !field.modifiers.is_static =>
+                {
+                    field.declarators.iter().map(|d| d.name.as_str()).collect()
+                }
+                ClassMember::Property(prop)
+                    if prop.modifiers.is_transient && !prop.modifiers.is_static =>
+                {
+                    vec![prop.name.as_str()]
+                }
+                _ => Vec::new(),
+            })
+            .collect();
+        if transient.is_empty() {
+            return;
+        }
+
+        let json_type = if self.options.typescript {
+            ": Record<string, unknown>"
+        } else {
+            ""
+        };
+        self.write_indent();
+        self.writeln(&format!("toJSON(){} {{", json_type));
+        self.indent();
+        self.write_indent();
+        self.writeln(&format!("const json{} = {{ ...this }};", json_type));
+        for name in transient {
+            self.write_indent();
+            self.writeln(&format!("delete json.{};", name));
+        }
+        self.write_indent();
+        self.writeln("return json;");
+        self.dedent();
+        self.write_indent();
+        self.writeln("}");
+        self.newline();
+    }
+
+    /// Emit a class with its method bodies moved to a companion module (see
+    /// the `split` module)
+    fn transpile_split_class(&mut self, class: &ClassDeclaration) -> Result<(), TranspileError> {
+        let alias = match self.companions.len() {
+            0 => "__m".to_string(),
+            n => format!("__m{}", n + 1),
+        };
+        let module = methods_module(&class.name);
+        self.writeln(&format!("import * as {} from \"./{}\";", alias, module));
+        self.newline();
+
+        self.split_class = Some(SplitClass {
+            name: class.name.clone(),
+            alias,
+            functions: String::new(),
+        });
+        let result = self.transpile_class(class);
+        let split = self.split_class.take();
+        result?;
+
+        let main = std::mem::take(&mut self.output);
+        if self.options.include_imports {
+            self.emit_header();
+        }
+        if !self.object_guards.is_empty() {
+            self.emit_allowed_objects();
+        }
+        self.writeln(&format!(
+            "import {{ {} }} from \"./{}\";",
+            class.name, self.module_name
+        ));
+        self.newline();
+        if let Some(split) = split {
+            self.output.push_str(&split.functions);
+        }
+        let code = std::mem::replace(&mut self.output, main);
+        self.companions
+            .push(TranspiledModule { name: module, code });
+        Ok(())
+    }
+
+    /// Whether `class` is the class currently emitted split
+    fn is_split_class(&self, class: &str) -> bool {
+        self.split_class
+            .as_ref()
+            .is_some_and(|split| split.name == class)
+    }
+
+    /// Whether members of the class being emitted may be accessed by
+    /// functions of its companion module
+    fn in_split_class(&self) -> bool {
+        self.current_class
+            .as_ref()
+            .is_some_and(|class| self.is_split_class(class))
+    }
+
     fn transpile_class_member(&mut self, member: &ClassMember) -> Result<(), TranspileError> {
         match member {
             ClassMember::Field(field) => self.transpile_field(field),
@@ -157,9 +696,14 @@ impl Transpiler {
             ClassMember::Constructor(ctor) => self.transpile_constructor(ctor),
             ClassMember::Property(prop) => self.transpile_property(prop),
             ClassMember::StaticBlock(block) => self.transpile_static_block(block),
-            ClassMember::InnerClass(class) => self.transpile_class(class),
-            ClassMember::InnerInterface(iface) => self.transpile_interface(iface),
-            ClassMember::InnerEnum(enum_decl) => self.transpile_enum(enum_decl),
+            // Emitted ahead of the class; `Outer.Inner` still resolves
+            ClassMember::InnerClass(ClassDeclaration { name, .. })
+            | ClassMember::InnerEnum(EnumDeclaration { name, .. }) => {
+                self.write_indent();
+                self.writeln(&format!("static {} = {};", name, name));
+                Ok(())
+            }
+            ClassMember::InnerInterface(_) => Ok(()),
         }
     }
 
@@ -178,6 +722,29 @@ impl Transpiler {
         let ts_type = self.type_ref_to_ts(&field.type_ref);
 
         for declarator in &field.declarators {
+            if field.modifiers.is_static && self.request_statics.contains(&declarator.name) {
+                self.write_indent();
+                self.writeln(&format!(
+                    "// static {}: stored per request in $runtime.statics",
+                    declarator.name
+                ));
+                continue;
+            }
+
+            if field.modifiers.is_static
+                && self.options.async_database
+                && self.lazy_inits.values().any(|f| f == &declarator.name)
+            {
+                // In-flight initialization shared by concurrent callers
+                self.write_indent();
+                self.write(&format!("{}static __{}Pending", access, declarator.name));
+                if self.options.typescript {
+                    self.write(": Promise<void> | null");
+                }
+                self.writeln(" = null;");
+            }
+
+            self.write_deprecated(&field.annotations);
             self.write_indent();
             self.write(&format!(
                 "{}{}{}{}",
@@ -200,10 +767,45 @@ impl Transpiler {
     }
 
     fn transpile_method(&mut self, method: &MethodDeclaration) -> Result<(), TranspileError> {
+        self.check_cancelled()?;
+        // An abstract method is only a signature, which JavaScript lacks
+        if method.body.is_none() && !self.options.typescript {
+            return Ok(());
+        }
         // Check if method body contains SOQL/DML
         self.needs_async = false;
+        self.local_types.clear();
+        self.optional_records.clear();
         if let Some(ref body) = method.body {
             self.scan_for_async_needs(body);
+            self.local_types = local_types(&method.parameters, body);
+            self.optional_records = optional_record_lists(body);
+        }
+        // The host awaits an enqueued future to the end of its job
+        if self.is_future(method) {
+            self.needs_async = true;
+        }
+
+        // compareTo(Object other) on a Comparable class only ever receives
+        // instances of that class, which the body casts `other` to
+        let compare_to_class = self.current_class.clone().filter(|class| {
+            method.name.eq_ignore_ascii_case("compareTo")
+                && method.parameters.len() == 1
+                && method.parameters[0]
+                    .type_ref
+                    .name
+                    .eq_ignore_ascii_case("Object")
+                && self.comparable_classes.contains(&class.to_lowercase())
+        });
+
+        // Lazy-init getters await a shared initialization promise
+        let lazy_field = if self.options.async_database {
+            self.lazy_inits.get(&method.name).cloned()
+        } else {
+            None
+        };
+        if lazy_field.is_some() {
+            self.needs_async = true;
         }
 
         let access = self.access_modifier_to_ts(&method.modifiers.access);
@@ -223,40 +825,69 @@ impl Transpiler {
             ""
         };
 
-        self.write_indent();
-        self.write(&format!(
-            "{}{}{}{}{}",
-            access, static_mod, abstract_mod, async_mod, method.name
-        ));
-
         // Parameters
-        self.write("(");
         let params: Vec<String> = method
             .parameters
             .iter()
-            .map(|p| {
-                if self.options.typescript {
+            .map(|p| match &compare_to_class {
+                Some(class) if self.options.typescript => format!("{}: {}", p.name, class),
+                _ if self.options.typescript => {
                     format!("{}: {}", p.name, self.type_ref_to_ts(&p.type_ref))
-                } else {
-                    p.name.clone()
                 }
+                _ => p.name.clone(),
             })
             .collect();
-        self.write(&params.join(", "));
-        self.write(")");
 
         // Return type
-        if self.options.typescript {
-            let ret_type = self.type_ref_to_ts(&method.return_type);
-            if self.needs_async && self.options.async_database {
-                self.write(&format!(": Promise<{}>", ret_type));
-            } else {
-                self.write(&format!(": {}", ret_type));
-            }
+        let ret_type = if !self.options.typescript {
+            String::new()
+        } else if self.needs_async && self.options.async_database {
+            format!(": Promise<{}>", self.type_ref_to_ts(&method.return_type))
+        } else {
+            format!(": {}", self.type_ref_to_ts(&method.return_type))
+        };
+
+        self.write_deprecated(&method.annotations);
+        self.write_indent();
+
+        if let Some(body) = method
+            .body
+            .as_ref()
+            .filter(|body| self.in_split_class() && can_move(body))
+        {
+            self.write(&format!(
+                "{}{}{}{}({}){}",
+                access,
+                static_mod,
+                method.name,
+                self.type_parameters_to_ts(&method.type_parameters),
+                params.join(", "),
+                ret_type
+            ));
+            return self.transpile_moved_method(method, body, &params, &ret_type, &lazy_field);
         }
 
+        self.write(&format!(
+            "{}{}{}{}{}{}({}){}",
+            access,
+            static_mod,
+            abstract_mod,
+            async_mod,
+            method.name,
+            self.type_parameters_to_ts(&method.type_parameters),
+            params.join(", "),
+            ret_type
+        ));
+
         // Body
-        if let Some(ref body) = method.body {
+        if let (Some(ref body), Some(ref field)) = (&method.body, &lazy_field) {
+            self.writeln(" {");
+            self.indent();
+            self.transpile_lazy_init_body(body, field)?;
+            self.dedent();
+            self.write_indent();
+            self.writeln("}");
+        } else if let Some(ref body) = method.body {
             self.writeln(" {");
             self.indent();
             self.transpile_block(body)?;
@@ -271,15 +902,263 @@ impl Transpiler {
         Ok(())
     }
 
+    /// Finish a method of a split class whose signature has been written:
+    /// its body delegates to a function of the companion module, which gets
+    /// the original body
+    fn transpile_moved_method(
+        &mut self,
+        method: &MethodDeclaration,
+        body: &Block,
+        params: &[String],
+        ret_type: &str,
+        lazy_field: &Option<String>,
+    ) -> Result<(), TranspileError> {
+        let Some(split) = self.split_class.as_mut() else {
+            return Ok(());
+        };
+        let alias = split.alias.clone();
+        let class_name = split.name.clone();
+        let functions = std::mem::take(&mut split.functions);
+
+        let mut args: Vec<&str> = method.parameters.iter().map(|p| p.name.as_str()).collect();
+        let mut fn_params = params.to_vec();
+        if !method.modifiers.is_static {
+            args.insert(0, "this");
+            fn_params.insert(
+                0,
+                if self.options.typescript {
+                    format!("self: {}", class_name)
+                } else {
+                    "self".to_string()
+                },
+            );
+        }
+        self.writeln(" {");
+        self.indent();
+        self.write_indent();
+        self.writeln(&format!(
+            "return {}.{}({});",
+            alias,
+            method.name,
+            args.join(", ")
+        ));
+        self.dedent();
+        self.write_indent();
+        self.writeln("}");
+        self.newline();
+
+        let class_output = std::mem::replace(&mut self.output, functions);
+        let indent_level = std::mem::replace(&mut self.indent_level, 0);
+        self.receiver = "self";
+
+        let async_mod = if self.needs_async && self.options.async_database {
+            "async "
+        } else {
+            ""
+        };
+        self.write_deprecated(&method.annotations);
+        let generics = self.type_parameters_to_ts(&method.type_parameters);
+        self.writeln(&format!(
+            "export {}function {}{}({}){} {{",
+            async_mod,
+            method.name,
+            generics,
+            fn_params.join(", "),
+            ret_type
+        ));
+        self.indent();
+        let result = match lazy_field {
+            Some(field) => self.transpile_lazy_init_body(body, field),
+            None => self.transpile_block(body),
+        };
+        self.dedent();
+        self.writeln("}");
+        self.newline();
+
+        self.receiver = "this";
+        self.indent_level = indent_level;
+        let functions = std::mem::replace(&mut self.output, class_output);
+        if let Some(split) = self.split_class.as_mut() {
+            split.functions = functions;
+        }
+        result
+    }
+
+    /// Emit a detected lazy-init getter with a promise-memoization guard so
+    /// concurrent callers share one in-flight initialization:
+    ///
+    /// ```text
+    /// if (Cls.cache == null) {
+    ///   if (!Cls.__cachePending) {
+    ///     Cls.__cachePending = (async () => { ...init... })().finally(() => { Cls.__cachePending = null; });
+    ///   }
+    ///   await Cls.__cachePending;
+    /// }
+    /// return Cls.cache;
+    /// ```
+    fn transpile_lazy_init_body(
+        &mut self,
+        body: &Block,
+        field: &str,
+    ) -> Result<(), TranspileError> {
+        let (Statement::If(if_stmt), Statement::Return(_)) =
+            (&body.statements[0], &body.statements[1])
+        else {
+            return Err(TranspileError::InvalidAst(
+                "lazy-init getter must be an if followed by a return".to_string(),
+            ));
+        };
+
+        let class_name = self.current_class.clone().unwrap_or_default();
+        let value = self.static_ref(field);
+        let (pending_get, pending_set, pending_clear) = if self.request_statics.contains(field) {
+            let key = format!("{}.{}:pending", class_name, field);
+            (
+                format!("$runtime.statics.get(\"{}\")", key),
+                format!("$runtime.statics.set(\"{}\", ", key),
+                format!("$runtime.statics.delete(\"{}\");", key),
+            )
+        } else {
+            let pending = format!("{}.__{}Pending", class_name, field);
+            (
+                pending.clone(),
+                format!("{} = ", pending),
+                format!("{} = null;", pending),
+            )
+        };
+
+        self.write_indent();
+        self.writeln(&format!("if ({} == null) {{", value));
+        self.indent();
+
+        self.write_indent();
+        self.writeln(&format!("if (!{}) {{", pending_get));
+        self.indent();
+        self.write_indent();
+        self.writeln(&format!("{}(async () => {{", pending_set));
+        self.indent();
+        self.transpile_body(&if_stmt.then_branch)?;
+        self.dedent();
+        self.write_indent();
+        self.writeln("})().finally(() => {");
+        self.indent();
+        self.write_indent();
+        self.writeln(&pending_clear);
+        self.dedent();
+        self.write_indent();
+        if pending_set.ends_with("= ") {
+            self.writeln("});");
+        } else {
+            self.writeln("}));");
+        }
+        self.dedent();
+        self.write_indent();
+        self.writeln("}");
+
+        self.write_indent();
+        self.writeln(&format!("await {};", pending_get));
+        self.dedent();
+        self.write_indent();
+        self.writeln("}");
+
+        self.write_indent();
+        self.writeln(&format!("return {};", value));
+        Ok(())
+    }
+
+    /// Expression that reads a static of the current class
+    fn static_ref(&self, field: &str) -> String {
+        let class_name = self.current_class.as_deref().unwrap_or_default();
+        if self.request_statics.contains(field) {
+            format!("$runtime.statics.get(\"{}.{}\")", class_name, field)
+        } else {
+            format!("{}.{}", class_name, field)
+        }
+    }
+
+    /// Resolve an expression to a request-scoped static of the current class
+    fn request_static_target(&self, expr: &Expression) -> Option<String> {
+        if self.request_statics.is_empty() {
+            return None;
+        }
+        let class_name = self.current_class.as_deref()?;
+        static_field_name(expr, class_name, &self.static_fields)
+            .filter(|name| self.request_statics.contains(name))
+    }
+
+    /// Emit an assignment or increment of a request-scoped static as a
+    /// `$runtime.statics.set` call, returning false for any other
+    /// expression
+    ///
+    /// `set` returns the Map, so where the result is used as a value the
+    /// stored value is read back with `.get`; postfix forms then undo the
+    /// step to yield the previous value, as in Apex.
+    fn transpile_static_update(
+        &mut self,
+        expr: &Expression,
+        as_value: bool,
+    ) -> Result<bool, TranspileError> {
+        let target = match expr {
+            Expression::Assignment(assign) => &assign.target,
+            Expression::PreIncrement(e, _, _)
+            | Expression::PreDecrement(e, _, _)
+            | Expression::PostIncrement(e, _, _)
+            | Expression::PostDecrement(e, _, _) => e,
+            _ => return Ok(false),
+        };
+        let Some(field) = self.request_static_target(target) else {
+            return Ok(false);
+        };
+        let key = format!(
+            "{}.{}",
+            self.current_class.as_deref().unwrap_or_default(),
+            field
+        );
+
+        self.write(&format!("$runtime.statics.set(\"{}\", ", key));
+        match expr {
+            Expression::Assignment(assign) => {
+                if assign.operator != AssignmentOp::Assign {
+                    // x += v -> set(key, get(key) + v)
+                    let op = self.assignment_op_to_ts(&assign.operator);
+                    self.write(&format!(
+                        "{} {} ",
+                        self.static_ref(&field),
+                        op.trim_end_matches('=')
+                    ));
+                }
+                self.transpile_expression(&assign.value)?;
+            }
+            Expression::PreIncrement(..) | Expression::PostIncrement(..) => {
+                self.write(&format!("{} + 1", self.static_ref(&field)));
+            }
+            _ => self.write(&format!("{} - 1", self.static_ref(&field))),
+        }
+        self.write(")");
+
+        if as_value {
+            self.write(&format!(".get(\"{}\")", key));
+            match expr {
+                Expression::PostIncrement(..) => self.write(" - 1"),
+                Expression::PostDecrement(..) => self.write(" + 1"),
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+
     fn transpile_constructor(
         &mut self,
         ctor: &ConstructorDeclaration,
     ) -> Result<(), TranspileError> {
         self.needs_async = false;
         self.scan_for_async_needs(&ctor.body);
+        self.local_types = local_types(&ctor.parameters, &ctor.body);
+        self.optional_records = optional_record_lists(&ctor.body);
 
         let access = self.access_modifier_to_ts(&ctor.modifiers.access);
 
+        self.write_deprecated(&ctor.annotations);
         self.write_indent();
         self.write(&format!("{}constructor(", access));
 
@@ -337,6 +1216,7 @@ impl Transpiler {
             // Use getter/setter syntax
             if let Some(ref getter) = prop.getter {
                 if let Some(ref body) = getter.body {
+                    self.write_deprecated(&prop.annotations);
                     self.write_indent();
                     self.write(&format!("{}{}get {}()", access, static_mod, prop.name));
                     if self.options.typescript {
@@ -353,6 +1233,7 @@ impl Transpiler {
 
             if let Some(ref setter) = prop.setter {
                 if let Some(ref body) = setter.body {
+                    self.write_deprecated(&prop.annotations);
                     self.write_indent();
                     self.write(&format!("{}{}set {}(value", access, static_mod, prop.name));
                     if self.options.typescript {
@@ -368,6 +1249,7 @@ impl Transpiler {
             }
         } else {
             // Simple field
+            self.write_deprecated(&prop.annotations);
             self.write_indent();
             self.write(&format!("{}{}{}", access, static_mod, prop.name));
             if self.options.typescript {
@@ -391,12 +1273,17 @@ impl Transpiler {
     }
 
     fn transpile_interface(&mut self, iface: &InterfaceDeclaration) -> Result<(), TranspileError> {
+        // Interfaces are type-only
+        if !self.options.typescript {
+            return Ok(());
+        }
         let export = if iface.access == AccessModifier::Public {
             "export "
         } else {
             ""
         };
 
+        self.write_deprecated(&iface.annotations);
         self.write(&format!("{}interface {}", export, iface.name));
 
         if !iface.extends.is_empty() {
@@ -414,6 +1301,7 @@ impl Transpiler {
         for member in &iface.members {
             match member {
                 InterfaceMember::Method(sig) => {
+                    self.write_deprecated(&sig.annotations);
                     self.write_indent();
                     self.write(&format!("{}(", sig.name));
                     let params: Vec<String> = sig
@@ -450,6 +1338,11 @@ impl Transpiler {
             ""
         };
 
+        self.write_deprecated(&enum_decl.annotations);
+        if !self.options.typescript {
+            self.transpile_enum_object(enum_decl, export);
+            return Ok(());
+        }
         self.writeln(&format!("{}enum {} {{", export, enum_decl.name));
         self.indent();
 
@@ -465,10 +1358,101 @@ impl Transpiler {
 
         self.dedent();
         self.writeln("}");
+        self.transpile_enum_methods(enum_decl, export);
 
         Ok(())
     }
 
+    /// A JavaScript enum: a frozen object with the ordinals a TypeScript
+    /// enum compiles to, the reverse mapping back to names, and the methods
+    /// [`Self::transpile_enum_methods`] adds
+    fn transpile_enum_object(&mut self, enum_decl: &EnumDeclaration, export: &str) {
+        let name = &enum_decl.name;
+        self.writeln(&format!("{}const {} = Object.freeze({{", export, name));
+        self.indent();
+        for (ordinal, value) in enum_decl.values.iter().enumerate() {
+            self.write_indent();
+            self.writeln(&format!("{}: {},", value, ordinal));
+        }
+        for (ordinal, value) in enum_decl.values.iter().enumerate() {
+            self.write_indent();
+            self.writeln(&format!("{}: {:?},", ordinal, value));
+        }
+        self.write_enum_functions(enum_decl, "");
+        self.dedent();
+        self.writeln("});");
+    }
+
+    /// `values()` and `valueOf(name)`, merged into the enum as a namespace
+    fn transpile_enum_methods(&mut self, enum_decl: &EnumDeclaration, export: &str) {
+        self.writeln(&format!("{}namespace {} {{", export, enum_decl.name));
+        self.indent();
+        self.write_enum_functions(enum_decl, "export function ");
+        self.dedent();
+        self.writeln("}");
+    }
+
+    /// The bodies of `values()` and `valueOf(name)`, each introduced by
+    /// `keyword` (`export function ` in a namespace, nothing in an object)
+    fn write_enum_functions(&mut self, enum_decl: &EnumDeclaration, keyword: &str) {
+        let name = &enum_decl.name;
+        let (list_type, name_param, returns) = if self.options.typescript {
+            (
+                format!(": {}[]", name),
+                "name: string",
+                format!(": {}", name),
+            )
+        } else {
+            (String::new(), "name", String::new())
+        };
+        let members: Vec<String> = enum_decl
+            .values
+            .iter()
+            .map(|value| format!("{}.{}", name, value))
+            .collect();
+        let names: Vec<String> = enum_decl
+            .values
+            .iter()
+            .map(|value| format!("{:?}", value))
+            .collect();
+        // Object members are separated by commas
+        let close = if keyword.is_empty() { "}," } else { "}" };
+
+        self.write_indent();
+        self.writeln(&format!("{}values(){} {{", keyword, list_type));
+        self.indent();
+        self.write_indent();
+        self.writeln(&format!("return [{}];", members.join(", ")));
+        self.dedent();
+        self.write_indent();
+        self.writeln(close);
+
+        self.write_indent();
+        self.writeln(&format!("{}valueOf({}){} {{", keyword, name_param, returns));
+        self.indent();
+        self.write_indent();
+        self.writeln(&format!(
+            "const index = [{}].indexOf(name);",
+            names.join(", ")
+        ));
+        self.write_indent();
+        self.writeln("if (index < 0) {");
+        self.indent();
+        self.write_indent();
+        self.writeln(&format!(
+            "throw new Error(\"No enum constant {}.\" + name);",
+            name
+        ));
+        self.dedent();
+        self.write_indent();
+        self.writeln("}");
+        self.write_indent();
+        self.writeln("return index;");
+        self.dedent();
+        self.write_indent();
+        self.writeln(close);
+    }
+
     fn transpile_trigger(&mut self, trigger: &TriggerDeclaration) -> Result<(), TranspileError> {
         // Triggers become exported functions
         self.writeln(&format!(
@@ -476,19 +1460,9 @@ impl Transpiler {
             trigger.name, trigger.object
         ));
 
-        let events: Vec<&str> = trigger
-            .events
-            .iter()
-            .map(|e| match e {
-                TriggerEvent::BeforeInsert => "beforeInsert",
-                TriggerEvent::AfterInsert => "afterInsert",
-                TriggerEvent::BeforeUpdate => "beforeUpdate",
-                TriggerEvent::AfterUpdate => "afterUpdate",
-                TriggerEvent::BeforeDelete => "beforeDelete",
-                TriggerEvent::AfterDelete => "afterDelete",
-                TriggerEvent::AfterUndelete => "afterUndelete",
-            })
-            .collect();
+        let events: Vec<&str> = trigger.events.iter().map(|e| event_key(*e)).collect();
+        self.field_types.clear();
+        self.local_types = local_types(&[], &trigger.body);
 
         self.writeln(&format!(
             "export const {}_EVENTS = {:?};",
@@ -496,6 +1470,15 @@ impl Transpiler {
         ));
         self.newline();
 
+        if self.options.split_trigger_events {
+            if let Some(split) = split_trigger(&trigger.body, &trigger.events) {
+                self.in_trigger = true;
+                let result = self.transpile_trigger_handlers(trigger, &split);
+                self.in_trigger = false;
+                return result;
+            }
+        }
+
         self.needs_async = false;
         self.scan_for_async_needs(&trigger.body);
 
@@ -503,14 +1486,17 @@ impl Transpiler {
 
         self.write(&format!("export {}function {}(", async_mod, trigger.name));
         if self.options.typescript {
-            self.write("context: TriggerContext");
+            self.write("ctx: TriggerContext");
         } else {
-            self.write("context");
+            self.write("ctx");
         }
         self.writeln(") {");
 
         self.indent();
-        self.transpile_block(&trigger.body)?;
+        self.in_trigger = true;
+        let result = self.transpile_block(&trigger.body);
+        self.in_trigger = false;
+        result?;
         self.dedent();
 
         self.writeln("}");
@@ -518,6 +1504,105 @@ impl Transpiler {
         Ok(())
     }
 
+    /// One exported function per event of a split trigger, a shared helper
+    /// for the declarations they all compute the same way, and the
+    /// `<Trigger>_HANDLERS` registration table
+    fn transpile_trigger_handlers(
+        &mut self,
+        trigger: &TriggerDeclaration,
+        split: &TriggerSplit,
+    ) -> Result<(), TranspileError> {
+        let ctx_param = if self.options.typescript {
+            "ctx: TriggerContext"
+        } else {
+            "ctx"
+        };
+        let as_block = |statements: Vec<Statement>| Block {
+            statements,
+            span: trigger.body.span,
+            id: NodeId::DUMMY,
+        };
+        let declarations = |decls: &[&LocalVariableDeclaration]| -> Vec<Statement> {
+            decls
+                .iter()
+                .map(|d| Statement::LocalVariable((*d).clone()))
+                .collect()
+        };
+
+        let shared_fn = format!("{}_shared", trigger.name);
+        let shared_names: Vec<&str> = split
+            .shared
+            .iter()
+            .flat_map(|d| d.declarators.iter().map(|v| v.name.as_str()))
+            .collect();
+        let mut shared_async = false;
+        if !split.shared.is_empty() {
+            let block = as_block(declarations(&split.shared));
+            self.needs_async = false;
+            self.scan_for_async_needs(&block);
+            shared_async = self.needs_async;
+
+            let async_mod = if shared_async { "async " } else { "" };
+            self.writeln(&format!(
+                "{}function {}({}) {{",
+                async_mod, shared_fn, ctx_param
+            ));
+            self.indent();
+            self.transpile_block(&block)?;
+            self.write_indent();
+            self.writeln(&format!("return {{ {} }};", shared_names.join(", ")));
+            self.dedent();
+            self.writeln("}");
+            self.newline();
+        }
+
+        for (event, statements) in &split.handlers {
+            let mut body = declarations(&split.duplicated);
+            body.extend(statements.iter().cloned());
+            let block = as_block(body);
+            self.needs_async = shared_async;
+            self.scan_for_async_needs(&block);
+
+            let async_mod = if self.needs_async { "async " } else { "" };
+            self.writeln(&format!(
+                "export {}function {}({}) {{",
+                async_mod,
+                handler_name(&trigger.name, *event),
+                ctx_param
+            ));
+            self.indent();
+            if !split.shared.is_empty() {
+                let await_prefix = if shared_async { "await " } else { "" };
+                self.write_indent();
+                self.writeln(&format!(
+                    "let {{ {} }} = {}{}(ctx);",
+                    shared_names.join(", "),
+                    await_prefix,
+                    shared_fn
+                ));
+            }
+            self.transpile_block(&block)?;
+            self.dedent();
+            self.writeln("}");
+            self.newline();
+        }
+
+        self.writeln(&format!("export const {}_HANDLERS = {{", trigger.name));
+        self.indent();
+        for (event, _) in &split.handlers {
+            self.write_indent();
+            self.writeln(&format!(
+                "{}: {},",
+                event_key(*event),
+                handler_name(&trigger.name, *event)
+            ));
+        }
+        self.dedent();
+        self.writeln("};");
+
+        Ok(())
+    }
+
     // ========================================================================
     // Statement transpilation
     // ========================================================================
@@ -529,7 +1614,33 @@ impl Transpiler {
         Ok(())
     }
 
+    /// Emit the statements of a body that the caller already wraps in braces
+    fn transpile_body(&mut self, body: &Statement) -> Result<(), TranspileError> {
+        match body {
+            Statement::Block(block) => self.transpile_block(block),
+            other => self.transpile_statement(other),
+        }
+    }
+
     fn transpile_statement(&mut self, stmt: &Statement) -> Result<(), TranspileError> {
+        let span = stmt.span();
+        if let Some(guard) = self.object_guards.iter().find(|g| g.statement == span) {
+            let checks: Vec<String> = guard
+                .variables
+                .iter()
+                .map(|v| {
+                    format!(
+                        "$runtime.soql.assertValidObject({}, {});",
+                        v, ALLOWED_OBJECTS
+                    )
+                })
+                .collect();
+            for check in checks {
+                self.write_indent();
+                self.writeln(&check);
+            }
+        }
+
         match stmt {
             Statement::Block(block) => {
                 self.write_indent();
@@ -545,7 +1656,9 @@ impl Transpiler {
             }
             Statement::Expression(expr) => {
                 self.write_indent();
-                self.transpile_expression(&expr.expression)?;
+                if !self.transpile_static_update(&expr.expression, false)? {
+                    self.transpile_expression(&expr.expression)?;
+                }
                 self.writeln(";");
             }
             Statement::If(if_stmt) => {
@@ -581,13 +1694,24 @@ impl Transpiler {
                 self.transpile_expression(&throw.exception)?;
                 self.writeln(";");
             }
-            Statement::Break(_) => {
+            Statement::Break(brk) => {
                 self.write_indent();
-                self.writeln("break;");
+                match &brk.label {
+                    Some(label) => self.writeln(&format!("break {};", label)),
+                    None => self.writeln("break;"),
+                }
             }
-            Statement::Continue(_) => {
+            Statement::Continue(cont) => {
                 self.write_indent();
-                self.writeln("continue;");
+                match &cont.label {
+                    Some(label) => self.writeln(&format!("continue {};", label)),
+                    None => self.writeln("continue;"),
+                }
+            }
+            Statement::Labeled(labeled) => {
+                self.write_indent();
+                self.writeln(&format!("{}:", labeled.label));
+                self.transpile_statement(&labeled.body)?;
             }
             Statement::Try(try_stmt) => {
                 self.transpile_try(try_stmt)?;
@@ -595,7 +1719,21 @@ impl Transpiler {
             Statement::Dml(dml) => {
                 self.transpile_dml(dml)?;
             }
-            Statement::Empty(_) => {
+            Statement::RunAs(run_as) => {
+                // The runtime has no user switching; the block runs as is
+                self.write_indent();
+                self.write("// System.runAs(");
+                self.transpile_expression(&run_as.user)?;
+                self.writeln(")");
+                self.write_indent();
+                self.writeln("{");
+                self.indent();
+                self.transpile_block(&run_as.body)?;
+                self.dedent();
+                self.write_indent();
+                self.writeln("}");
+            }
+            Statement::Empty(..) => {
                 self.write_indent();
                 self.writeln(";");
             }
@@ -614,13 +1752,27 @@ impl Transpiler {
             self.write_indent();
             self.write(&format!("{} {}", keyword, declarator.name));
 
+            let optional = self
+                .optional_records
+                .contains(&declarator.name.to_lowercase());
             if self.options.typescript {
-                self.write(&format!(": {}", ts_type));
+                match list_element_type(&var.type_ref).filter(|_| optional) {
+                    Some(element) => {
+                        self.write(&format!(": {} | null", self.type_ref_to_ts(&element)))
+                    }
+                    None => self.write(&format!(": {}", ts_type)),
+                }
             }
 
             if let Some(ref init) = declarator.initializer {
                 self.write(" = ");
-                self.transpile_expression(init)?;
+                match query_of(init) {
+                    Some(query) if optional => self.transpile_soql_call(query, "queryOptional")?,
+                    Some(query) if self.single_record_queries.contains(&query.span) => {
+                        self.transpile_soql_call(query, "queryOne")?
+                    }
+                    _ => self.transpile_expression(init)?,
+                }
             }
 
             self.writeln(";");
@@ -644,8 +1796,13 @@ impl Transpiler {
 
         if let Some(ref else_branch) = if_stmt.else_branch {
             self.write(" else ");
-            // Check if it's an else-if
-            if matches!(**else_branch, Statement::If(_)) {
+            // Check if it's an else-if; one needing an allow-list check
+            // first is nested in a block to make room for it
+            let needs_guard = self
+                .object_guards
+                .iter()
+                .any(|g| g.statement == else_branch.span());
+            if matches!(**else_branch, Statement::If(_)) && !needs_guard {
                 self.transpile_statement(else_branch)?;
             } else {
                 self.writeln("{");
@@ -673,11 +1830,13 @@ impl Transpiler {
                     let keyword = if var.is_final { "const" } else { "let" };
                     let ts_type = self.type_ref_to_ts(&var.type_ref);
 
+                    // One declaration list: `let i = 0, j = n`
+                    self.write(keyword);
                     for (i, decl) in var.declarators.iter().enumerate() {
                         if i > 0 {
-                            self.write(", ");
+                            self.write(",");
                         }
-                        self.write(&format!("{} {}", keyword, decl.name));
+                        self.write(&format!(" {}", decl.name));
                         if self.options.typescript {
                             self.write(&format!(": {}", ts_type));
                         }
@@ -715,7 +1874,7 @@ impl Transpiler {
 
         self.writeln(") {");
         self.indent();
-        self.transpile_statement(&for_stmt.body)?;
+        self.transpile_body(&for_stmt.body)?;
         self.dedent();
         self.write_indent();
         self.writeln("}");
@@ -811,11 +1970,11 @@ impl Transpiler {
                     .filter_map(|expr| {
                         let mut s = String::from("__switchVal === ");
                         match expr {
-                            Expression::Integer(n, _) => s.push_str(&n.to_string()),
-                            Expression::String(str, _) => s.push_str(&format!("\"{}\"", str)),
-                            Expression::Null(_) => s.push_str("null"),
-                            Expression::Boolean(b, _) => s.push_str(&b.to_string()),
-                            Expression::Identifier(name, _) => s.push_str(name),
+                            Expression::Integer(_, raw, _, _) => s.push_str(&js_number(raw)),
+                            Expression::String(str, _, _) => s.push_str(&format!("\"{}\"", str)),
+                            Expression::Null(..) => s.push_str("null"),
+                            Expression::Boolean(b, _, _) => s.push_str(&b.to_string()),
+                            Expression::Identifier(name, _, _) => s.push_str(name),
                             _ => return None,
                         }
                         Some(s)
@@ -857,12 +2016,15 @@ impl Transpiler {
             self.indent();
 
             // Add type check if specific exception type
-            if catch.exception_type.name != "Exception" {
+            if catch.exception_types().all(|t| t.name != "Exception") {
+                let checks: Vec<String> = catch
+                    .exception_types()
+                    .map(|t| format!("{} instanceof {}", catch.variable, self.type_ref_to_ts(t)))
+                    .collect();
                 self.write_indent();
                 self.writeln(&format!(
-                    "if (!({} instanceof {})) throw {};",
-                    catch.variable,
-                    self.type_ref_to_ts(&catch.exception_type),
+                    "if (!({})) throw {};",
+                    checks.join(" || "),
                     catch.variable
                 ));
             }
@@ -938,14 +2100,24 @@ impl Transpiler {
 
     fn transpile_expression(&mut self, expr: &Expression) -> Result<(), TranspileError> {
         match expr {
-            Expression::Null(_) => self.write("null"),
-            Expression::Boolean(b, _) => self.write(&b.to_string()),
-            Expression::Integer(n, _) => self.write(&n.to_string()),
-            Expression::Long(n, _) => self.write(&format!("{}n", n)), // BigInt
-            Expression::Double(n, _) => self.write(&n.to_string()),
-            Expression::String(s, _) => self.write(&format!("\"{}\"", s.replace('\"', "\\\""))),
-
-            Expression::Identifier(name, _) => {
+            Expression::Null(..) => self.write("null"),
+            Expression::Boolean(b, _, _) => self.write(&b.to_string()),
+            Expression::Integer(_, raw, _, _) => self.write(&js_number(raw)),
+            Expression::Long(_, raw, _, _) => {
+                // BigInt
+                self.write(&format!("{}n", js_number(raw.trim_end_matches(['l', 'L']))))
+            }
+            Expression::Double(_, raw, _, _) => self.write(raw),
+            Expression::String(s, _, _) => self.write(&format!("\"{}\"", s.replace('\"', "\\\""))),
+
+            Expression::Identifier(..) | Expression::FieldAccess(_)
+                if self.request_static_target(expr).is_some() =>
+            {
+                let field = self.request_static_target(expr).unwrap();
+                self.write(&self.static_ref(&field));
+            }
+
+            Expression::Identifier(name, _, _) => {
                 // Check if this is a static field reference that needs class prefix
                 if self.static_fields.contains(name) {
                     if let Some(ref class_name) = self.current_class {
@@ -957,35 +2129,209 @@ impl Transpiler {
                     self.write(name);
                 }
             }
-            Expression::This(_) => self.write("this"),
-            Expression::Super(_) => self.write("super"),
+            Expression::This(..) => self.write(self.receiver),
+            Expression::Super(..) => self.write("super"),
+
+            Expression::FieldAccess(_) if sobject_token(expr).is_some() => {
+                // Account.SObjectType -> $runtime.describe("Account")
+                let sobject = sobject_token(expr).unwrap_or_default();
+                self.write(&format!("$runtime.describe(\"{}\")", sobject));
+            }
 
             Expression::FieldAccess(access) => {
-                self.transpile_expression(&access.object)?;
+                self.transpile_receiver(&access.object)?;
                 self.write(&format!(".{}", access.field));
             }
 
+            Expression::ArrayAccess(_) | Expression::MethodCall(_)
+                if self.optional_record_use(expr).is_some() =>
+            {
+                if let Some((name, kind)) = self.optional_record_use(expr) {
+                    match kind {
+                        RecordListUse::Size => self.write(&format!("({} === null ? 0 : 1)", name)),
+                        RecordListUse::IsEmpty => self.write(&format!("({} === null)", name)),
+                        RecordListUse::First => self.write(name),
+                    }
+                }
+            }
+
             Expression::ArrayAccess(access) => {
-                self.transpile_expression(&access.array)?;
+                self.transpile_receiver(&access.array)?;
                 self.write("[");
                 self.transpile_expression(&access.index)?;
                 self.write("]");
             }
 
             Expression::SafeNavigation(nav) => {
-                self.transpile_expression(&nav.object)?;
+                self.transpile_receiver(&nav.object)?;
                 self.write(&format!("?.{}", nav.field));
             }
 
+            Expression::MethodCall(call) if let Some(method) = clock_call(call) => {
+                self.write(&format!("$runtime.{}()", method));
+            }
+
+            Expression::MethodCall(call)
+                if let Some(helper) = date_arithmetic(call, self.temporal_receiver_type(call)) =>
+            {
+                // d.addDays(n) -> $runtime.date.addDays(d, n)
+                self.write(&format!("$runtime.date.{}(", helper));
+                if let Some(ref date) = call.object {
+                    self.transpile_expression(date)?;
+                }
+                self.write(", ");
+                self.transpile_expression(&call.arguments[0])?;
+                self.write(")");
+            }
+
+            Expression::MethodCall(call) if self.enqueued_future(call).is_some() => {
+                // Notifier.send(ids) -> $runtime.jobs.enqueueFuture("Notifier.send", [ids])
+                let name = self
+                    .enqueued_future(call)
+                    .map(FutureMethod::qualified_name)
+                    .unwrap_or_default();
+                if self.options.async_database {
+                    self.write("await ");
+                }
+                self.write(&format!("$runtime.jobs.enqueueFuture(\"{}\", [", name));
+                for (i, arg) in call.arguments.iter().enumerate() {
+                    if i > 0 {
+                        self.write(", ");
+                    }
+                    self.transpile_expression(arg)?;
+                }
+                self.write("])");
+            }
+
+            Expression::MethodCall(call) if value_of_argument(call).is_some() => {
+                // String.valueOf(x) -> String(x); enums -> Season[x]
+                if let Some(value) = value_of_argument(call) {
+                    let value_type = self.value_type_name(value);
+                    match enum_of(value, value_type, &self.enums).map(str::to_string) {
+                        Some(enum_name) => {
+                            self.write(&format!("{}[", enum_name));
+                            self.transpile_expression(value)?;
+                            self.write("]");
+                        }
+                        None => {
+                            self.write("String(");
+                            self.transpile_expression(value)?;
+                            self.write(")");
+                        }
+                    }
+                }
+            }
+
+            Expression::MethodCall(call) if is_global_describe(call) => {
+                self.write("$runtime.globalDescribe()");
+            }
+
+            Expression::MethodCall(call) if is_get_describe(call, self.receiver_type(call)) => {
+                // Runtime tokens are already describe results
+                if let Some(ref token) = call.object {
+                    self.transpile_expression(token)?;
+                }
+            }
+
+            Expression::MethodCall(call)
+                if describe_property(call, self.describe_token_type(call)).is_some() =>
+            {
+                // getDescribe().getLabel() -> .label
+                if let Some(ref describe) = call.object {
+                    self.transpile_expression(describe)?;
+                }
+                let property =
+                    describe_property(call, self.describe_token_type(call)).unwrap_or_default();
+                self.write(&format!(".{}", property));
+            }
+
+            Expression::MethodCall(call) if is_running_test(call) => {
+                if self.options.test_context {
+                    self.write("$runtime.isTest()");
+                } else {
+                    self.write("false");
+                }
+            }
+
+            Expression::MethodCall(call) if savepoint_call(call).is_some() => {
+                // Database.setSavepoint() -> await $runtime.setSavepoint()
+                let method = savepoint_call(call).unwrap_or_default();
+                if self.options.async_database {
+                    self.write("await ");
+                }
+                self.write(&format!("$runtime.{}(", method));
+                for (i, arg) in call.arguments.iter().enumerate() {
+                    if i > 0 {
+                        self.write(", ");
+                    }
+                    self.transpile_expression(arg)?;
+                }
+                self.write(")");
+            }
+
+            Expression::MethodCall(call)
+                if call.name.eq_ignore_ascii_case("addError")
+                    && call.object.as_ref().is_some_and(|r| self.is_record(r)) =>
+            {
+                // record.addError(msg) -> ctx.addError(record, msg) so the runtime
+                // can aggregate validation errors for the whole batch
+                let target = if self.in_trigger { "ctx" } else { "$runtime" };
+                self.write(&format!("{}.addError(", target));
+                if let Some(ref record) = call.object {
+                    self.transpile_expression(record)?;
+                }
+                for arg in &call.arguments {
+                    self.write(", ");
+                    self.transpile_expression(arg)?;
+                }
+                self.write(")");
+            }
+
+            Expression::MethodCall(call)
+                if matches!(&call.object, Some(Expression::Identifier(class, _, _))
+                    if class.eq_ignore_ascii_case("Limits")) =>
+            {
+                // Limits.getQueries() -> $runtime.limits.queries(); the fixed
+                // Limits.getLimitX() maximums are inlined as constants
+                let counter = strip_prefix_ignore_case(&call.name, "get").unwrap_or(&call.name);
+                match strip_prefix_ignore_case(counter, "Limit").and_then(governor_limit) {
+                    Some((_, max)) => self.write(&max.to_string()),
+                    None => {
+                        // Known counters get their canonical casing back
+                        let counter = governor_limit(counter).map_or(counter, |(name, _)| name);
+                        self.write(&format!("$runtime.limits.{}()", lower_first(counter)));
+                    }
+                }
+            }
+
+            Expression::MethodCall(call) if self.list_sort_comparator(call).is_some() => {
+                // list.sort() -> list.sort(comparator) with Apex ordering
+                let comparator = self.list_sort_comparator(call).unwrap_or_default();
+                if let Some(ref list) = call.object {
+                    self.transpile_expression(list)?;
+                }
+                self.write(&format!(".sort({})", comparator));
+            }
+
+            Expression::MethodCall(call)
+                if self.collection_property(call) == Some(CollectionProperty::RuntimeIsEmpty) =>
+            {
+                // isEmpty() on a receiver of unknown type
+                self.write("$runtime.isEmpty(");
+                if let Some(ref object) = call.object {
+                    self.transpile_expression(object)?;
+                }
+                self.write(")");
+            }
+
             Expression::MethodCall(call) => {
                 // Handle Apex methods that map to JS properties
-                let is_property = call.object.is_some()
-                    && call.arguments.is_empty()
-                    && matches!(call.name.as_str(), "length" | "size");
+                let property = self.collection_property(call);
 
-                // Handle async methods that need await (Http.send, etc.)
-                let needs_await =
-                    self.options.async_database && matches!(call.name.as_str(), "send");
+                // Handle async methods that need await (Http.send, the unit's
+                // own async methods)
+                let needs_await = self.options.async_database
+                    && (call.name == "send" || self.calls_async_method(call));
 
                 // Map Apex method names to JavaScript equivalents
                 let js_method_name = match call.name.as_str() {
@@ -994,25 +2340,39 @@ impl Transpiler {
                     "remove" if call.object.is_some() => "delete", // Map.remove() -> Map.delete()
                     "add" if call.arguments.len() == 1 => "add",   // Set.add() stays add()
                     "contains" => "has",                           // Set.contains() -> Set.has()
-                    "isEmpty" => "size === 0 ||", // Will be handled specially below
                     _ => &call.name,
                 };
 
+                if let Some(CollectionProperty::Compare(_)) = property {
+                    self.write("(");
+                }
                 if needs_await {
                     self.write("await ");
                 }
 
-                if let Some(ref obj) = call.object {
-                    self.transpile_expression(obj)?;
-                    self.write(".");
+                match (
+                    &call.object,
+                    self.class_methods.get(&call.name.to_lowercase()),
+                ) {
+                    (Some(obj), _) => {
+                        self.transpile_receiver(obj)?;
+                        self.write(".");
+                    }
+                    // A method of the current class called without a receiver
+                    (None, Some(true)) => {
+                        let class = self.current_class.clone().unwrap_or_default();
+                        self.write(&format!("{}.", class));
+                    }
+                    (None, Some(false)) => self.write(&format!("{}.", self.receiver)),
+                    (None, None) => {}
                 }
 
-                if is_property {
-                    // length() -> .length, size() -> .length (for strings/arrays)
-                    self.write("length");
-                } else if call.name == "isEmpty" && call.arguments.is_empty() {
-                    // isEmpty() -> .size === 0 (for Map/Set) or .length === 0 (for Array)
-                    self.write("size === 0");
+                if let Some(CollectionProperty::Read(property)) = property {
+                    // size() -> .length / .size
+                    self.write(property);
+                } else if let Some(CollectionProperty::Compare(comparison)) = property {
+                    // isEmpty() -> (list.length === 0) / (set.size === 0)
+                    self.write(&format!("{})", comparison));
                 } else {
                     self.write(&format!("{}(", js_method_name));
                     for (i, arg) in call.arguments.iter().enumerate() {
@@ -1025,8 +2385,52 @@ impl Transpiler {
                 }
             }
 
+            // An empty list is an array literal; `new T[]()` isn't valid
+            Expression::New(new_expr)
+                if new_expr.arguments.is_empty() && is_list_type(&new_expr.type_ref) =>
+            {
+                self.write("[]");
+            }
+
+            Expression::New(new_expr)
+                if self.keys_records_by_id(new_expr) == Some(IdMapSource::Unknown) =>
+            {
+                // A list or a map, depending on what the runtime finds
+                self.write(&format!(
+                    "new {}($runtime.idEntries(",
+                    self.constructed_type(&new_expr.type_ref)
+                ));
+                self.transpile_expression(&new_expr.arguments[0])?;
+                self.write("))");
+            }
+
+            Expression::New(new_expr)
+                if self.keys_records_by_id(new_expr) == Some(IdMapSource::Records) =>
+            {
+                let records = &new_expr.arguments[0];
+                let bare = matches!(
+                    records,
+                    Expression::Identifier(..) | Expression::FieldAccess(..)
+                );
+                self.write(&format!(
+                    "new {}(",
+                    self.constructed_type(&new_expr.type_ref)
+                ));
+                if !bare {
+                    self.write("(");
+                }
+                self.transpile_expression(records)?;
+                if !bare {
+                    self.write(")");
+                }
+                self.write(".map((record) => [record.Id, record]))");
+            }
+
             Expression::New(new_expr) => {
-                self.write(&format!("new {}(", self.type_ref_to_ts(&new_expr.type_ref)));
+                self.write(&format!(
+                    "new {}(",
+                    self.constructed_type(&new_expr.type_ref)
+                ));
                 for (i, arg) in new_expr.arguments.iter().enumerate() {
                     if i > 0 {
                         self.write(", ");
@@ -1074,6 +2478,15 @@ impl Transpiler {
                 self.write(")");
             }
 
+            Expression::Binary(_) | Expression::Unary(_)
+                if self.optional_presence(expr).is_some() =>
+            {
+                if let Some((name, present)) = self.optional_presence(expr) {
+                    let op = if present { "!==" } else { "===" };
+                    self.write(&format!("{} {} null", name, op));
+                }
+            }
+
             Expression::Unary(unary) => {
                 let op = self.unary_op_to_ts(&unary.operator);
                 // All unary ops in Apex are prefix
@@ -1096,9 +2509,22 @@ impl Transpiler {
             }
 
             Expression::NullCoalesce(nc) => {
-                self.transpile_expression(&nc.left)?;
-                self.write(" ?? ");
-                self.transpile_expression(&nc.right)?;
+                // JavaScript rejects `??` beside an unparenthesized `&&`/`||`,
+                // which Apex parses as the tighter-binding operand
+                for (i, side) in [&nc.left, &nc.right].into_iter().enumerate() {
+                    if i > 0 {
+                        self.write(" ?? ");
+                    }
+                    let logical = matches!(side, Expression::Binary(b)
+                        if matches!(b.operator, BinaryOp::And | BinaryOp::Or));
+                    if logical {
+                        self.write("(");
+                        self.transpile_expression(side)?;
+                        self.write(")");
+                    } else {
+                        self.transpile_expression(side)?;
+                    }
+                }
             }
 
             Expression::Instanceof(inst) => {
@@ -1119,25 +2545,46 @@ impl Transpiler {
                 }
             }
 
+            Expression::Assignment(_)
+            | Expression::PreIncrement(..)
+            | Expression::PreDecrement(..)
+            | Expression::PostIncrement(..)
+            | Expression::PostDecrement(..)
+                if self.transpile_static_update(expr, true)? => {}
+
             Expression::Assignment(assign) => {
                 self.transpile_expression(&assign.target)?;
                 self.write(&format!(" {} ", self.assignment_op_to_ts(&assign.operator)));
-                self.transpile_expression(&assign.value)?;
+                let coerce = assign.operator == AssignmentOp::AddAssign
+                    && self
+                        .declared_type(&assign.target)
+                        .is_some_and(|ty| needs_string_coercion(ty, &assign.value));
+                let single_record = query_of(&assign.value)
+                    .filter(|q| self.single_record_queries.contains(&q.span));
+                if let Some(query) = single_record {
+                    self.transpile_soql_call(query, "queryOne")?;
+                } else if coerce {
+                    self.write("$runtime.str(");
+                    self.transpile_expression(&assign.value)?;
+                    self.write(")");
+                } else {
+                    self.transpile_expression(&assign.value)?;
+                }
             }
 
-            Expression::PostIncrement(e, _) => {
+            Expression::PostIncrement(e, _, _) => {
                 self.transpile_expression(e)?;
                 self.write("++");
             }
-            Expression::PostDecrement(e, _) => {
+            Expression::PostDecrement(e, _, _) => {
                 self.transpile_expression(e)?;
                 self.write("--");
             }
-            Expression::PreIncrement(e, _) => {
+            Expression::PreIncrement(e, _, _) => {
                 self.write("++");
                 self.transpile_expression(e)?;
             }
-            Expression::PreDecrement(e, _) => {
+            Expression::PreDecrement(e, _, _) => {
                 self.write("--");
                 self.transpile_expression(e)?;
             }
@@ -1151,18 +2598,25 @@ impl Transpiler {
                 self.write("/* SOSL not supported */ []");
             }
 
-            Expression::BindVariable(name, _) => {
+            Expression::BindVariable(name, _, _) => {
                 // Bind variables in expressions - shouldn't happen outside SOQL
                 self.write(name);
             }
 
-            Expression::Parenthesized(inner, _) => {
+            Expression::BindExpression(bound, _, _) => self.transpile_expression(bound)?,
+
+            Expression::SoqlSubquery(query) => {
+                // Semi-joins only appear inside a query's text
+                self.write(&format!("({})", printer::soql(query)));
+            }
+
+            Expression::Parenthesized(inner, _, _) => {
                 self.write("(");
                 self.transpile_expression(inner)?;
                 self.write(")");
             }
 
-            Expression::ListLiteral(items, _) => {
+            Expression::ListLiteral(items, ..) => {
                 self.write("[");
                 for (i, item) in items.iter().enumerate() {
                     if i > 0 {
@@ -1173,7 +2627,7 @@ impl Transpiler {
                 self.write("]");
             }
 
-            Expression::SetLiteral(items, _) => {
+            Expression::SetLiteral(items, ..) => {
                 self.write("new Set([");
                 for (i, item) in items.iter().enumerate() {
                     if i > 0 {
@@ -1184,7 +2638,7 @@ impl Transpiler {
                 self.write("])");
             }
 
-            Expression::MapLiteral(pairs, _) => {
+            Expression::MapLiteral(pairs, ..) => {
                 self.write("new Map([");
                 for (i, (k, v)) in pairs.iter().enumerate() {
                     if i > 0 {
@@ -1199,7 +2653,7 @@ impl Transpiler {
                 self.write("])");
             }
 
-            Expression::TypeLiteral(type_ref, _) => {
+            Expression::TypeLiteral(type_ref, _, _) => {
                 // Type literals like List<Account>.class or String.class
                 // In JavaScript/TypeScript, we represent these as the constructor function
                 // or a string representation for reflection purposes
@@ -1212,6 +2666,16 @@ impl Transpiler {
     }
 
     fn transpile_soql(&mut self, query: &SoqlQuery) -> Result<(), TranspileError> {
+        self.transpile_soql_call(query, "query")
+    }
+
+    /// Emit `query` as a call to the runtime method `method`: `query` for a
+    /// list, `queryOne` or `queryOptional` for a single record
+    fn transpile_soql_call(
+        &mut self,
+        query: &SoqlQuery,
+        method: &str,
+    ) -> Result<(), TranspileError> {
         // Convert SOQL to a runtime query call
         let await_prefix = if self.options.async_database {
             "await "
@@ -1219,21 +2683,33 @@ impl Transpiler {
             ""
         };
 
-        // Reconstruct SOQL string (simplified - ideally we'd use a proper printer)
-        let soql = self.soql_to_string(query);
+        let soql = printer::soql(query);
 
         // Extract bind variables
         let binds = self.extract_bind_variables(query);
 
         if binds.is_empty() {
-            self.write(&format!("{}$runtime.query(\"{}\")", await_prefix, soql));
+            self.write(&format!(
+                "{}$runtime.{}(\"{}\")",
+                await_prefix, method, soql
+            ));
         } else {
-            self.write(&format!("{}$runtime.query(\"{}\", {{ ", await_prefix, soql));
-            for (i, bind) in binds.iter().enumerate() {
+            self.write(&format!(
+                "{}$runtime.{}(\"{}\", {{ ",
+                await_prefix, method, soql
+            ));
+            for (i, (bind, bound)) in binds.iter().enumerate() {
                 if i > 0 {
                     self.write(", ");
                 }
-                self.write(&format!("{}: {}", bind, bind));
+                match bound {
+                    // Keyed by the bind's text in the query, `"ids.keySet()"`
+                    Some(bound) => {
+                        self.write(&format!("{:?}: ", bind));
+                        self.transpile_expression(bound)?;
+                    }
+                    None => self.write(&format!("{}: {}", bind, bind)),
+                }
             }
             self.write(" })");
         }
@@ -1241,136 +2717,24 @@ impl Transpiler {
         Ok(())
     }
 
-    fn soql_to_string(&self, query: &SoqlQuery) -> String {
-        let mut s = String::from("SELECT ");
-
-        // Fields
-        let fields: Vec<String> = query
-            .select_clause
-            .iter()
-            .map(|item| match item {
-                SelectField::Field(f) => f.clone(),
-                SelectField::SubQuery(_) => "(subquery)".to_string(),
-                SelectField::TypeOf(_) => "TYPEOF ...".to_string(),
-                SelectField::AggregateFunction { name, field, alias } => {
-                    if let Some(a) = alias {
-                        format!("{}({}) {}", name, field, a)
-                    } else {
-                        format!("{}({})", name, field)
-                    }
-                }
-            })
-            .collect();
-        s.push_str(&fields.join(", "));
-
-        s.push_str(&format!(" FROM {}", query.from_clause));
-
-        // WHERE clause
-        if let Some(ref where_expr) = query.where_clause {
-            s.push_str(" WHERE ");
-            s.push_str(&self.expr_to_soql(where_expr));
-        }
-
-        // ORDER BY clause
-        if !query.order_by_clause.is_empty() {
-            s.push_str(" ORDER BY ");
-            let order_fields: Vec<String> = query
-                .order_by_clause
-                .iter()
-                .map(|f| {
-                    let mut field_str = f.field.clone();
-                    if !f.ascending {
-                        field_str.push_str(" DESC");
-                    }
-                    if let Some(nulls_first) = f.nulls_first {
-                        if nulls_first {
-                            field_str.push_str(" NULLS FIRST");
-                        } else {
-                            field_str.push_str(" NULLS LAST");
-                        }
-                    }
-                    field_str
-                })
-                .collect();
-            s.push_str(&order_fields.join(", "));
-        }
-
-        // LIMIT clause
-        if let Some(ref limit_expr) = query.limit_clause {
-            s.push_str(" LIMIT ");
-            s.push_str(&self.expr_to_soql(limit_expr));
-        }
-
-        // OFFSET clause
-        if let Some(ref offset_expr) = query.offset_clause {
-            s.push_str(" OFFSET ");
-            s.push_str(&self.expr_to_soql(offset_expr));
-        }
-
-        s
-    }
-
-    /// Convert an expression to SOQL string representation
-    fn expr_to_soql(&self, expr: &Expression) -> String {
-        match expr {
-            Expression::Null(_) => "null".to_string(),
-            Expression::Boolean(b, _) => b.to_string(),
-            Expression::Integer(n, _) => n.to_string(),
-            Expression::Long(n, _) => n.to_string(),
-            Expression::Double(n, _) => n.to_string(),
-            Expression::String(s, _) => format!("'{}'", s.replace('\'', "\\'")),
-            Expression::Identifier(name, _) => name.clone(),
-            Expression::BindVariable(name, _) => format!(":{}", name),
-            Expression::FieldAccess(fa) => {
-                format!("{}.{}", self.expr_to_soql(&fa.object), fa.field)
-            }
-            Expression::Binary(b) => {
-                let left = self.expr_to_soql(&b.left);
-                let right = self.expr_to_soql(&b.right);
-                let op = match b.operator {
-                    BinaryOp::Equal => "=",
-                    BinaryOp::NotEqual => "!=",
-                    BinaryOp::LessThan => "<",
-                    BinaryOp::LessOrEqual => "<=",
-                    BinaryOp::GreaterThan => ">",
-                    BinaryOp::GreaterOrEqual => ">=",
-                    BinaryOp::And => "AND",
-                    BinaryOp::Or => "OR",
-                    BinaryOp::Like => "LIKE",
-                    BinaryOp::In => "IN",
-                    BinaryOp::NotIn => "NOT IN",
-                    _ => "?",
-                };
-                format!("{} {} {}", left, op, right)
-            }
-            Expression::Unary(u) => {
-                let operand = self.expr_to_soql(&u.operand);
-                match u.operator {
-                    UnaryOp::Not => format!("NOT {}", operand),
-                    UnaryOp::Negate => format!("-{}", operand),
-                    _ => operand,
-                }
-            }
-            Expression::Parenthesized(inner, _) => {
-                format!("({})", self.expr_to_soql(inner))
-            }
-            Expression::ListLiteral(items, _) => {
-                let item_strs: Vec<String> = items.iter().map(|i| self.expr_to_soql(i)).collect();
-                format!("({})", item_strs.join(", "))
-            }
-            _ => "?".to_string(),
-        }
-    }
-
-    fn extract_bind_variables(&self, query: &SoqlQuery) -> Vec<String> {
+    /// Names of the variables `query` binds, and for a bind expression its
+    /// text with the expression to evaluate
+    fn extract_bind_variables<'q>(
+        &self,
+        query: &'q SoqlQuery,
+    ) -> Vec<(String, Option<&'q Expression>)> {
         let mut binds = Vec::new();
         self.collect_binds_from_query(query, &mut binds);
-        binds.sort();
-        binds.dedup();
+        binds.sort_by(|a, b| a.0.cmp(&b.0));
+        binds.dedup_by(|a, b| a.0 == b.0);
         binds
     }
 
-    fn collect_binds_from_query(&self, query: &SoqlQuery, binds: &mut Vec<String>) {
+    fn collect_binds_from_query<'q>(
+        &self,
+        query: &'q SoqlQuery,
+        binds: &mut Vec<(String, Option<&'q Expression>)>,
+    ) {
         // Collect from WHERE clause
         if let Some(ref where_clause) = query.where_clause {
             self.collect_binds_from_expr(where_clause, binds);
@@ -1385,10 +2749,17 @@ impl Transpiler {
         }
     }
 
-    fn collect_binds_from_expr(&self, expr: &Expression, binds: &mut Vec<String>) {
+    fn collect_binds_from_expr<'q>(
+        &self,
+        expr: &'q Expression,
+        binds: &mut Vec<(String, Option<&'q Expression>)>,
+    ) {
         match expr {
-            Expression::BindVariable(name, _) => {
-                binds.push(name.clone());
+            Expression::BindVariable(name, _, _) => {
+                binds.push((name.clone(), None));
+            }
+            Expression::BindExpression(bound, _, _) => {
+                binds.push((printer::expression(bound), Some(bound)));
             }
             Expression::Binary(b) => {
                 self.collect_binds_from_expr(&b.left, binds);
@@ -1397,11 +2768,15 @@ impl Transpiler {
             Expression::Unary(u) => {
                 self.collect_binds_from_expr(&u.operand, binds);
             }
-            Expression::ListLiteral(items, _) => {
+            Expression::Parenthesized(inner, _, _) => {
+                self.collect_binds_from_expr(inner, binds);
+            }
+            Expression::ListLiteral(items, ..) => {
                 for item in items {
                     self.collect_binds_from_expr(item, binds);
                 }
             }
+            Expression::SoqlSubquery(subquery) => self.collect_binds_from_query(subquery, binds),
             _ => {}
         }
     }
@@ -1410,120 +2785,155 @@ impl Transpiler {
     // Helper methods
     // ========================================================================
 
-    fn scan_for_async_needs(&mut self, block: &Block) {
-        for stmt in &block.statements {
-            self.scan_statement_for_async(stmt);
-            if self.needs_async {
-                return;
+    /// Collect the unit's async methods: lazy-init getters, futures and
+    /// every method [`Self::scan_for_async_needs`] flags, which includes the
+    /// callers of async methods, until no more are found
+    fn find_async_methods(&mut self, unit: &CompilationUnit) {
+        fn collect<'u>(
+            class: &'u ClassDeclaration,
+            methods: &mut Vec<(&'u str, &'u MethodDeclaration, bool)>,
+        ) {
+            let static_fields: std::collections::HashSet<String> = class
+                .members
+                .iter()
+                .filter_map(|member| match member {
+                    ClassMember::Field(field) if field.modifiers.is_static => Some(field),
+                    _ => None,
+                })
+                .flat_map(|field| field.declarators.iter().map(|d| d.name.clone()))
+                .collect();
+            for member in &class.members {
+                match member {
+                    ClassMember::Method(method) => {
+                        let lazy = detect_lazy_init(method, &class.name, &static_fields).is_some();
+                        methods.push((&class.name, method, lazy));
+                    }
+                    ClassMember::InnerClass(inner) => collect(inner, methods),
+                    _ => {}
+                }
             }
         }
-    }
 
-    fn scan_statement_for_async(&mut self, stmt: &Statement) {
-        match stmt {
-            Statement::Dml(_) => {
-                self.needs_async = true;
-            }
-            Statement::Expression(expr) => {
-                self.scan_expression_for_async(&expr.expression);
+        self.async_methods.clear();
+        if !self.options.async_database {
+            return;
+        }
+        let mut methods = Vec::new();
+        for declaration in &unit.declarations {
+            if let TypeDeclaration::Class(class) = declaration {
+                collect(class, &mut methods);
             }
-            Statement::LocalVariable(var) => {
-                for decl in &var.declarators {
-                    if let Some(ref init) = decl.initializer {
-                        self.scan_expression_for_async(init);
-                    }
+        }
+
+        let outer_class = self.current_class.take();
+        loop {
+            let mut found = false;
+            for (class, method, lazy) in &methods {
+                let key = (class.to_lowercase(), method.name.to_lowercase());
+                if self.async_methods.contains(&key) {
+                    continue;
                 }
-            }
-            Statement::If(if_stmt) => {
-                self.scan_expression_for_async(&if_stmt.condition);
-                self.scan_statement_for_async(&if_stmt.then_branch);
-                if let Some(ref else_b) = if_stmt.else_branch {
-                    self.scan_statement_for_async(else_b);
-                }
-            }
-            Statement::Block(b) => self.scan_for_async_needs(b),
-            Statement::For(f) => {
-                if let Some(ref init) = f.init {
-                    match init {
-                        ForInit::Variables(v) => {
-                            for d in &v.declarators {
-                                if let Some(ref i) = d.initializer {
-                                    self.scan_expression_for_async(i);
-                                }
-                            }
-                        }
-                        ForInit::Expressions(exprs) => {
-                            for e in exprs {
-                                self.scan_expression_for_async(e);
-                            }
-                        }
-                    }
+                self.current_class = Some(class.to_string());
+                self.needs_async = *lazy || self.is_future(method);
+                if let Some(body) = &method.body {
+                    self.scan_for_async_needs(body);
                 }
-                self.scan_statement_for_async(&f.body);
-            }
-            Statement::ForEach(f) => {
-                self.scan_expression_for_async(&f.iterable);
-                self.scan_statement_for_async(&f.body);
-            }
-            Statement::While(w) => {
-                self.scan_expression_for_async(&w.condition);
-                self.scan_statement_for_async(&w.body);
-            }
-            Statement::DoWhile(d) => {
-                self.scan_statement_for_async(&d.body);
-                self.scan_expression_for_async(&d.condition);
-            }
-            Statement::Return(r) => {
-                if let Some(ref v) = r.value {
-                    self.scan_expression_for_async(v);
+                if self.needs_async {
+                    self.async_methods.insert(key);
+                    found = true;
                 }
             }
-            Statement::Try(t) => {
-                self.scan_for_async_needs(&t.try_block);
-                for c in &t.catch_clauses {
-                    self.scan_for_async_needs(&c.block);
-                }
-                if let Some(ref f) = t.finally_block {
-                    self.scan_for_async_needs(f);
-                }
+            if !found {
+                break;
             }
-            _ => {}
         }
+        self.current_class = outer_class;
+        self.needs_async = false;
     }
 
-    fn scan_expression_for_async(&mut self, expr: &Expression) {
-        match expr {
-            Expression::Soql(_) => {
-                self.needs_async = true;
-            }
-            Expression::MethodCall(call) => {
-                // Http.send() is async
-                if call.name == "send" {
-                    self.needs_async = true;
-                }
-                if let Some(ref obj) = call.object {
-                    self.scan_expression_for_async(obj);
-                }
-                for arg in &call.arguments {
-                    self.scan_expression_for_async(arg);
+    /// Whether `call` invokes an async method of the unit: `load()` or
+    /// `this.load()` in its class, or `Loader.load()`
+    fn calls_async_method(&self, call: &MethodCallExpr) -> bool {
+        self.callee_class(call).is_some_and(|class| {
+            self.async_methods
+                .contains(&(class, call.name.to_lowercase()))
+        })
+    }
+
+    /// Lowercased class whose method `call` may invoke: the current class
+    /// for `load()` or `this.load()`, `loader` for `Loader.load()`
+    fn callee_class(&self, call: &MethodCallExpr) -> Option<String> {
+        match &call.object {
+            None | Some(Expression::This(..)) => self
+                .current_class
+                .as_ref()
+                .map(|class| class.to_lowercase()),
+            Some(Expression::Identifier(class, _, _)) => Some(class.to_lowercase()),
+            Some(_) => None,
+        }
+    }
+
+    /// Whether `expr` is emitted as an `await`, which a receiver must
+    /// parenthesize: `(await Cache.load()).get(id)`
+    fn is_awaited(&self, expr: &Expression) -> bool {
+        self.options.async_database
+            && match expr {
+                Expression::Soql(_) => true,
+                Expression::MethodCall(call) => {
+                    self.calls_async_method(call) || self.enqueued_future(call).is_some()
                 }
+                _ => false,
             }
-            Expression::Binary(b) => {
-                self.scan_expression_for_async(&b.left);
-                self.scan_expression_for_async(&b.right);
-            }
-            Expression::Unary(u) => {
-                self.scan_expression_for_async(&u.operand);
+    }
+
+    /// Emit the receiver of a member access or call
+    fn transpile_receiver(&mut self, expr: &Expression) -> Result<(), TranspileError> {
+        if self.is_awaited(expr) {
+            self.write("(");
+            self.transpile_expression(expr)?;
+            self.write(")");
+            Ok(())
+        } else {
+            self.transpile_expression(expr)
+        }
+    }
+
+    /// Mark the method being emitted async if `block` runs SOQL or DML,
+    /// sends a callout, uses a savepoint, enqueues a future or calls an
+    /// async method of the unit, anywhere in its statements and expressions
+    fn scan_for_async_needs(&mut self, block: &Block) {
+        struct Scan<'t> {
+            transpiler: &'t Transpiler,
+            found: bool,
+        }
+
+        impl<'ast> Visitor<'ast> for Scan<'_> {
+            fn visit_soql_query(&mut self, _query: &'ast SoqlQuery) {
+                self.found = true;
             }
-            Expression::Ternary(t) => {
-                self.scan_expression_for_async(&t.condition);
-                self.scan_expression_for_async(&t.then_expr);
-                self.scan_expression_for_async(&t.else_expr);
+
+            fn visit_dml(&mut self, _stmt: &'ast DmlStatement) {
+                self.found = true;
             }
-            Expression::Assignment(a) => {
-                self.scan_expression_for_async(&a.value);
+
+            fn visit_method_call(&mut self, call: &'ast MethodCallExpr) {
+                if call.name == "send"
+                    || savepoint_call(call).is_some()
+                    || self.transpiler.enqueued_future(call).is_some()
+                    || self.transpiler.calls_async_method(call)
+                {
+                    self.found = true;
+                }
             }
-            _ => {}
+        }
+
+        let mut scan = Scan {
+            transpiler: self,
+            found: false,
+        };
+        walk_block(&mut scan, block);
+        if scan.found {
+            self.needs_async = true;
         }
     }
 
@@ -1538,28 +2948,26 @@ impl Transpiler {
             "date" | "datetime" | "time" => "Date".to_string(),
             "id" => "string".to_string(),
             "object" | "sobject" => "Record<string, any>".to_string(),
-            "list" => {
-                if let Some(first) = type_ref.type_arguments.first() {
-                    return format!("{}[]", self.type_ref_to_ts(first));
-                }
-                "any[]".to_string()
-            }
-            "set" => {
-                if let Some(first) = type_ref.type_arguments.first() {
-                    return format!("Set<{}>", self.type_ref_to_ts(first));
-                }
-                "Set<any>".to_string()
-            }
-            "map" => {
-                if type_ref.type_arguments.len() >= 2 {
-                    return format!(
-                        "Map<{}, {}>",
-                        self.type_ref_to_ts(&type_ref.type_arguments[0]),
-                        self.type_ref_to_ts(&type_ref.type_arguments[1])
-                    );
-                }
-                "Map<any, any>".to_string()
-            }
+            "savepoint" | "system.savepoint" => "Savepoint".to_string(),
+            "sobjecttype"
+            | "schema.sobjecttype"
+            | "describesobjectresult"
+            | "schema.describesobjectresult" => "SObjectDescribe".to_string(),
+            // Element types recurse, so `List<List<Integer>>` is `number[][]`
+            "list" => match type_ref.type_arguments.first() {
+                Some(first) => format!("{}[]", self.type_ref_to_ts(first)),
+                None => "any[]".to_string(),
+            },
+            "set" => match type_ref.type_arguments.first() {
+                Some(first) => format!("Set<{}>", self.type_ref_to_ts(first)),
+                None => "Set<any>".to_string(),
+            },
+            "map" if type_ref.type_arguments.len() >= 2 => format!(
+                "Map<{}, {}>",
+                self.type_ref_to_ts(&type_ref.type_arguments[0]),
+                self.type_ref_to_ts(&type_ref.type_arguments[1])
+            ),
+            "map" => "Map<any, any>".to_string(),
             // Salesforce types
             "account" | "contact" | "opportunity" | "lead" | "case" | "task" | "event" => {
                 type_ref.name.clone()
@@ -1574,12 +2982,34 @@ impl Transpiler {
         }
     }
 
+    /// Type named by `new`: `Map<string, number>`, or just `Map` in
+    /// JavaScript
+    fn constructed_type(&self, type_ref: &TypeRef) -> String {
+        let name = self.type_ref_to_ts(type_ref);
+        match name.find('<') {
+            Some(generics) if !self.options.typescript => name[..generics].to_string(),
+            _ => name,
+        }
+    }
+
+    /// `<K, V>` for a generic method; nothing in JavaScript
+    fn type_parameters_to_ts(&self, params: &[TypeParameter]) -> String {
+        if params.is_empty() || !self.options.typescript {
+            return String::new();
+        }
+        let names: Vec<&str> = params.iter().map(|p| p.name.as_str()).collect();
+        format!("<{}>", names.join(", "))
+    }
+
     fn access_modifier_to_ts(&self, access: &AccessModifier) -> &'static str {
         if !self.options.typescript {
             // JavaScript doesn't support access modifiers on class members
             return "";
         }
         match access {
+            AccessModifier::Private | AccessModifier::Protected if self.in_split_class() => {
+                "/** @internal */ "
+            }
             AccessModifier::Private => "private ",
             AccessModifier::Protected => "protected ",
             AccessModifier::Public => "public ",
@@ -1649,6 +3079,14 @@ impl Transpiler {
     // Output helpers
     // ========================================================================
 
+    /// JSDoc tag so TypeScript tooling flags uses of `@Deprecated` members
+    fn write_deprecated(&mut self, annotations: &[Annotation]) {
+        if is_deprecated(annotations) {
+            self.write_indent();
+            self.writeln("/** @deprecated */");
+        }
+    }
+
     fn write(&mut self, s: &str) {
         self.output.push_str(s);
     }
@@ -1679,6 +3117,84 @@ impl Transpiler {
     }
 }
 
+/// JavaScript spelling of an Apex integer literal in its original base;
+/// legacy octal (`017`) is a syntax error in modules, so it becomes `0o17`
+fn js_number(raw: &str) -> String {
+    let is_octal = raw.len() > 1 && raw.starts_with('0') && raw.bytes().all(|b| b.is_ascii_digit());
+    if is_octal {
+        format!("0o{}", &raw[1..])
+    } else {
+        raw.to_string()
+    }
+}
+
+/// Synchronous per-transaction governor limits reported by `Limits.getLimitX()`,
+/// with the counter's canonical name
+fn governor_limit(name: &str) -> Option<(&'static str, u64)> {
+    const LIMITS: &[(&str, u64)] = &[
+        ("Queries", 100),
+        ("QueryRows", 50_000),
+        ("AggregateQueries", 300),
+        ("SoslQueries", 20),
+        ("DmlStatements", 150),
+        ("DmlRows", 10_000),
+        ("HeapSize", 6_000_000),
+        ("CpuTime", 10_000),
+        ("Callouts", 100),
+        ("FutureCalls", 50),
+        ("QueueableJobs", 50),
+        ("EmailInvocations", 10),
+        ("QueryLocatorRows", 10_000),
+    ];
+    LIMITS
+        .iter()
+        .find(|(counter, _)| counter.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+fn strip_prefix_ignore_case<'a>(name: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = name.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &name[prefix.len()..])
+}
+
+/// `DmlRows` -> `dmlRows`
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Runtime transaction method for `Database.setSavepoint()` and
+/// `Database.rollback(sp)`
+fn savepoint_call(call: &MethodCallExpr) -> Option<&'static str> {
+    let Some(Expression::Identifier(class, _, _)) = &call.object else {
+        return None;
+    };
+    if !class.eq_ignore_ascii_case("Database") {
+        return None;
+    }
+    match (call.name.to_lowercase().as_str(), call.arguments.len()) {
+        ("setsavepoint", 0) => Some("setSavepoint"),
+        ("rollback", 1) => Some("rollback"),
+        _ => None,
+    }
+}
+
+/// `Test.isRunningTest()`
+fn is_running_test(call: &MethodCallExpr) -> bool {
+    matches!(&call.object, Some(Expression::Identifier(class, _, _)) if class.eq_ignore_ascii_case("Test"))
+        && call.name.eq_ignore_ascii_case("isRunningTest")
+        && call.arguments.is_empty()
+}
+
+/// `List<T>` or `T[]`, both of which are JavaScript arrays
+fn is_list_type(type_ref: &TypeRef) -> bool {
+    type_ref.is_array || type_ref.name.eq_ignore_ascii_case("List")
+}
+
 impl Default for Transpiler {
     fn default() -> Self {
         Self::new()
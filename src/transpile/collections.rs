@@ -0,0 +1,163 @@
+//! Apex collection methods that become JavaScript properties
+//!
+//! Lists are emitted as arrays and Sets and Maps as their JavaScript
+//! counterparts, which expose their element count differently: `length` on
+//! arrays, `size` on `Set` and `Map`. Neither has an `isEmpty()`. Codegen
+//! looks up the declared type of the receiver and rewrites the call to the
+//! matching property; `isEmpty()` on a receiver of unknown type goes through
+//! `$runtime.isEmpty(x)`, which handles all three.
+//!
+//! `new Map<Id, Account>(accounts)` keys a list of records by Id, which a
+//! JavaScript `Map` built from the array would not do; codegen maps each
+//! record to an `[Id, record]` entry first. When the argument's type is not
+//! known it may be a list or a map to copy, so `$runtime.idEntries(x)`
+//! decides at run time.
+
+use super::futures::is_primitive;
+use crate::ast::{Expression, NewExpr, TypeRef};
+
+/// JavaScript shape of an Apex collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CollectionKind {
+    /// `List<T>` or `T[]`, emitted as an array
+    Array,
+    /// `Set<T>` or `Map<K, V>`, emitted as `Set` or `Map`
+    Sized,
+}
+
+/// Collection kind of a declared type, if it is a collection
+pub(crate) fn collection_kind(type_ref: &TypeRef) -> Option<CollectionKind> {
+    if type_ref.is_array || type_ref.name.eq_ignore_ascii_case("List") {
+        Some(CollectionKind::Array)
+    } else if type_ref.name.eq_ignore_ascii_case("Set") || type_ref.name.eq_ignore_ascii_case("Map")
+    {
+        Some(CollectionKind::Sized)
+    } else {
+        None
+    }
+}
+
+/// JavaScript replacing a no-argument collection method call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CollectionProperty {
+    /// A property read, `list.length`
+    Read(&'static str),
+    /// A comparison on a property, `list.length === 0`, parenthesized so it
+    /// stays one operand of the surrounding expression
+    Compare(&'static str),
+    /// `$runtime.isEmpty(x)`, for a receiver of unknown type
+    RuntimeIsEmpty,
+}
+
+/// Replacement for a no-argument `method()` call on a receiver of the given
+/// kind (`None` when its type is not known)
+pub(crate) fn collection_property(
+    kind: Option<CollectionKind>,
+    method: &str,
+) -> Option<CollectionProperty> {
+    match (kind, method.to_lowercase().as_str()) {
+        (Some(CollectionKind::Sized), "size") => Some(CollectionProperty::Read("size")),
+        (Some(CollectionKind::Sized), "isempty") => Some(CollectionProperty::Compare("size === 0")),
+        (Some(CollectionKind::Array), "isempty") => {
+            Some(CollectionProperty::Compare("length === 0"))
+        }
+        // Strings and arrays
+        (_, "size" | "length") => Some(CollectionProperty::Read("length")),
+        (None, "isempty") => Some(CollectionProperty::RuntimeIsEmpty),
+        _ => None,
+    }
+}
+
+/// What the argument of `new Map<Id, T>(x)` holds, when it is not simply
+/// a map to copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IdMapSource {
+    /// A list of records, keyed by Id
+    Records,
+    /// A list of records or a map, told apart by `$runtime.idEntries(x)`
+    Unknown,
+}
+
+/// Whether `new_expr` is `new Map<Id, T>(records)`, keying a list of
+/// records by Id, rather than a copy of another map; `argument_type` is the
+/// declared type of the argument when codegen knows it
+pub(crate) fn keys_records_by_id(
+    new_expr: &NewExpr,
+    argument_type: Option<&TypeRef>,
+) -> Option<IdMapSource> {
+    let map = &new_expr.type_ref;
+    let [key, value] = &map.type_arguments[..] else {
+        return None;
+    };
+    let [argument] = &new_expr.arguments[..] else {
+        return None;
+    };
+    if !map.name.eq_ignore_ascii_case("Map") || !key.name.eq_ignore_ascii_case("Id") {
+        return None;
+    }
+    let is_list = match argument {
+        Expression::Soql(_) | Expression::NewArray(_) => true,
+        Expression::New(new_expr) => {
+            collection_kind(&new_expr.type_ref) == Some(CollectionKind::Array)
+        }
+        Expression::NewMap(_) => false,
+        _ => match argument_type {
+            Some(type_ref) => collection_kind(type_ref) == Some(CollectionKind::Array),
+            // Only records can be keyed by Id; a map of anything else is copied
+            None if is_primitive(value) || collection_kind(value).is_some() => false,
+            None => return Some(IdMapSource::Unknown),
+        },
+    };
+    is_list.then_some(IdMapSource::Records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::NodeId;
+    use crate::lexer::Span;
+
+    #[test]
+    fn test_is_empty_by_receiver_type() {
+        let list = TypeRef {
+            name: "List".to_string(),
+            type_arguments: vec![TypeRef::simple("Account", Span::new(0, 0))],
+            is_array: false,
+            span: Span::new(0, 0),
+            id: NodeId::DUMMY,
+        };
+        let set = TypeRef::simple("Set", Span::new(0, 0));
+
+        assert_eq!(
+            collection_property(collection_kind(&list), "isEmpty"),
+            Some(CollectionProperty::Compare("length === 0"))
+        );
+        assert_eq!(
+            collection_property(collection_kind(&set), "isEmpty"),
+            Some(CollectionProperty::Compare("size === 0"))
+        );
+        assert_eq!(
+            collection_property(collection_kind(&set), "size"),
+            Some(CollectionProperty::Read("size"))
+        );
+        assert_eq!(collection_property(collection_kind(&list), "add"), None);
+    }
+
+    #[test]
+    fn test_collection_methods_ignore_case() {
+        let set = TypeRef::simple("Set", Span::new(0, 0));
+
+        assert_eq!(
+            collection_property(collection_kind(&set), "ISEMPTY"),
+            Some(CollectionProperty::Compare("size === 0"))
+        );
+        assert_eq!(
+            collection_property(None, "Size"),
+            Some(CollectionProperty::Read("length"))
+        );
+        assert_eq!(
+            collection_property(None, "isempty"),
+            Some(CollectionProperty::RuntimeIsEmpty)
+        );
+    }
+}
@@ -30,6 +30,11 @@ impl Default for RuntimeContext {
 /// interface ApexRuntime {
 ///   // Database operations
 ///   query<T>(soql: string, binds?: Record<string, any>): Promise<T[]>;
+///   // Query assigned to a single record: throws QueryException 'List has no
+///   // rows for assignment to SObject' when nothing matches
+///   queryOne<T>(soql: string, binds?: Record<string, any>): Promise<T>;
+///   // Primary-key fetch whose result the Apex code size-checks; null if absent
+///   queryOptional<T>(soql: string, binds?: Record<string, any>): Promise<T | null>;
 ///   insert(sobject: string, records: Record<string, any>[]): Promise<string[]>;
 ///   update(sobject: string, records: Record<string, any>[]): Promise<void>;
 ///   upsert(sobject: string, records: Record<string, any>[], externalIdField?: string): Promise<void>;
@@ -37,18 +42,110 @@ impl Default for RuntimeContext {
 ///
 ///   // System operations
 ///   debug(message: string): void;
+///   // The only clock generated code reads; pin both for reproducible runs
 ///   now(): Date;
 ///   today(): Date;
+///   // Test.isRunningTest(), with TranspileOptions::test_context
+///   isTest(): boolean;
 ///
 ///   // User context
 ///   getUserId(): string;
 ///   getUserName(): string;
+///
+///   // Validation errors raised outside trigger bodies
+///   addError(record: Record<string, any>, message: string, escape?: boolean): void;
+///
+///   // Request-scoped storage for statics (StaticCacheStrategy::Request)
+///   statics: Map<string, any>;
+///
+///   // Governor usage counters for Limits.getX(), e.g. limits.queries()
+///   limits: Record<string, () => number>;
+///
+///   // Schema metadata (Account.SObjectType, Schema.getGlobalDescribe())
+///   describe(sobject: string): SObjectDescribe;
+///   globalDescribe(): Map<string, SObjectDescribe>;
+///
+///   // Calendar arithmetic for Date/Datetime addX(), e.g. date.addDays(d, 7)
+///   date: DateMath;
+///
+///   // Allow-list check for object names spliced into dynamic SOQL
+///   soql: DynamicSoql;
+///
+///   // Transaction control (Database.setSavepoint / Database.rollback)
+///   setSavepoint(): Promise<Savepoint>;
+///   rollback(savepoint: Savepoint): Promise<void>;
+///
+///   // Apex string rendering for concatenation: null and undefined -> 'null'
+///   str(value: unknown): string;
+///
+///   // isEmpty() on a receiver of unknown type: arrays, strings, Sets, Maps
+///   isEmpty(value: { length: number } | { size: number }): boolean;
+///
+///   // Background work: @future calls, run later through <Class>_ENTRY_POINTS
+///   jobs: Jobs;
+/// }
+///
+/// interface Jobs {
+///   // Arguments are primitives or collections of them, safe to serialize
+///   enqueueFuture(method: string, args: unknown[]): Promise<void>;
+/// }
+///
+/// interface Savepoint {
+///   readonly id: string;
+/// }
+///
+/// interface DateMath {
+///   addYears(date: Date, years: number): Date;
+///   addMonths(date: Date, months: number): Date;
+///   addDays(date: Date, days: number): Date;
+///   addHours(date: Date, hours: number): Date;
+///   addMinutes(date: Date, minutes: number): Date;
+///   addSeconds(date: Date, seconds: number): Date;
+/// }
+///
+/// interface DynamicSoql {
+///   // Throws unless objectName is in allowed (case-insensitive)
+///   assertValidObject(objectName: string, allowed: readonly string[]): void;
+/// }
+///
+/// interface SObjectDescribe {
+///   name: string;
+///   localName: string;
+///   label: string;
+///   labelPlural: string;
+///   keyPrefix: string | null;
+///   custom: boolean;
+///   accessible: boolean;
+///   createable: boolean;
+///   updateable: boolean;
+///   deletable: boolean;
+///   queryable: boolean;
+///   searchable: boolean;
+/// }
+///
+/// interface TriggerContext {
+///   new: Record<string, any>[];
+///   old: Record<string, any>[];
+///   newMap: Map<string, Record<string, any>>;
+///   oldMap: Map<string, Record<string, any>>;
+///   // Marks a record as failed; the runtime aggregates errors and rolls back
+///   addError(record: Record<string, any>, message: string, escape?: boolean): void;
+/// }
+///
+/// // Apex Comparable; list.sort() on these calls a.compareTo(b)
+/// interface Comparable {
+///   compareTo(other: any): number;
 /// }
 /// ```
 pub const RUNTIME_INTERFACE: &str = r#"
 export interface ApexRuntime {
   // Database operations
   query<T = Record<string, any>>(soql: string, binds?: Record<string, any>): Promise<T[]>;
+  // Query assigned to a single record: throws QueryException 'List has no
+  // rows for assignment to SObject' when nothing matches
+  queryOne<T = Record<string, any>>(soql: string, binds?: Record<string, any>): Promise<T>;
+  // Primary-key fetch whose result the Apex code size-checks; null if absent
+  queryOptional<T = Record<string, any>>(soql: string, binds?: Record<string, any>): Promise<T | null>;
   insert(sobject: string, records: Record<string, any>[]): Promise<string[]>;
   update(sobject: string, records: Record<string, any>[]): Promise<void>;
   upsert(sobject: string, records: Record<string, any>[], externalIdField?: string): Promise<void>;
@@ -56,12 +153,102 @@ export interface ApexRuntime {
 
   // System operations
   debug(message: string): void;
+  // The only clock generated code reads; pin both for reproducible runs
   now(): Date;
   today(): Date;
+  // Test.isRunningTest(), with TranspileOptions::test_context
+  isTest(): boolean;
 
   // User context
   getUserId(): string;
   getUserName(): string;
+
+  // Validation errors raised outside trigger bodies
+  addError(record: Record<string, any>, message: string, escape?: boolean): void;
+
+  // Request-scoped storage for statics (StaticCacheStrategy::Request)
+  statics: Map<string, any>;
+
+  // Governor usage counters for Limits.getX(), e.g. limits.queries()
+  limits: Record<string, () => number>;
+
+  // Schema metadata (Account.SObjectType, Schema.getGlobalDescribe())
+  describe(sobject: string): SObjectDescribe;
+  globalDescribe(): Map<string, SObjectDescribe>;
+
+  // Calendar arithmetic for Date/Datetime addX(), e.g. date.addDays(d, 7)
+  date: DateMath;
+
+  // Allow-list check for object names spliced into dynamic SOQL
+  soql: DynamicSoql;
+
+  // Transaction control (Database.setSavepoint / Database.rollback)
+  setSavepoint(): Promise<Savepoint>;
+  rollback(savepoint: Savepoint): Promise<void>;
+
+  // Apex string rendering for concatenation: null and undefined -> 'null'
+  str(value: unknown): string;
+
+  // isEmpty() on a receiver of unknown type: arrays, strings, Sets, Maps
+  isEmpty(value: { length: number } | { size: number }): boolean;
+
+  // Entries for new Map<Id, T>(x) when x may be a list of records or a map
+  idEntries<T>(value: T[] | Map<string, T>): Iterable<[string, T]>;
+
+  // Background work: @future calls, run later through <Class>_ENTRY_POINTS
+  jobs: Jobs;
+}
+
+export interface Jobs {
+  // Arguments are primitives or collections of them, safe to serialize
+  enqueueFuture(method: string, args: unknown[]): Promise<void>;
+}
+
+export interface Savepoint {
+  readonly id: string;
+}
+
+export interface DateMath {
+  addYears(date: Date, years: number): Date;
+  addMonths(date: Date, months: number): Date;
+  addDays(date: Date, days: number): Date;
+  addHours(date: Date, hours: number): Date;
+  addMinutes(date: Date, minutes: number): Date;
+  addSeconds(date: Date, seconds: number): Date;
+}
+
+export interface DynamicSoql {
+  // Throws unless objectName is in allowed (case-insensitive)
+  assertValidObject(objectName: string, allowed: readonly string[]): void;
+}
+
+export interface SObjectDescribe {
+  name: string;
+  localName: string;
+  label: string;
+  labelPlural: string;
+  keyPrefix: string | null;
+  custom: boolean;
+  accessible: boolean;
+  createable: boolean;
+  updateable: boolean;
+  deletable: boolean;
+  queryable: boolean;
+  searchable: boolean;
+}
+
+export interface TriggerContext {
+  new: Record<string, any>[];
+  old: Record<string, any>[];
+  newMap: Map<string, Record<string, any>>;
+  oldMap: Map<string, Record<string, any>>;
+  // Marks a record as failed; the runtime aggregates errors and rolls back
+  addError(record: Record<string, any>, message: string, escape?: boolean): void;
+}
+
+// Apex Comparable; list.sort() on these calls a.compareTo(b)
+export interface Comparable {
+  compareTo(other: any): number;
 }
 
 // Global runtime instance injected at execution time
@@ -0,0 +1,155 @@
+//! Recognition of Apex date and time calls
+//!
+//! JavaScript `Date` arithmetic is easy to get wrong (month overflow, DST,
+//! mutation in place), so the current time and the `addX()` methods are
+//! routed through the runtime: `Date.today().addDays(7)` becomes
+//! `$runtime.date.addDays($runtime.today(), 7)`.
+//!
+//! Generated code reads the time only through `$runtime.now()` and
+//! `$runtime.today()`, never `new Date()` or `Date.now()`, so a runtime that
+//! pins those two gets the same results on every run, as
+//! [`ClockSource::Fixed`](crate::ClockSource::Fixed) does for SQL.
+//!
+//! The `addX()` methods are only rewritten on a receiver declared as a
+//! `Date`, `Datetime` or `Time`, or on an expression that evidently yields
+//! one (`Date.today()`, `Date.newInstance(...)`, another `addX()`), so a user
+//! class with an `addDays()` of its own keeps its method call.
+
+use crate::ast::{Expression, MethodCallExpr, TypeRef};
+
+/// Runtime clock call for `System.now()`, `Datetime.now()`,
+/// `System.today()` and `Date.today()`
+pub(crate) fn clock_call(call: &MethodCallExpr) -> Option<&'static str> {
+    let Some(Expression::Identifier(class, _, _)) = &call.object else {
+        return None;
+    };
+    if !call.arguments.is_empty() {
+        return None;
+    }
+    let class = class.to_lowercase();
+    match call.name.to_lowercase().as_str() {
+        "now" if matches!(class.as_str(), "system" | "datetime") => Some("now"),
+        "today" if matches!(class.as_str(), "system" | "date") => Some("today"),
+        _ => None,
+    }
+}
+
+/// Runtime helper for a `Date`/`Datetime`/`Time` `addX(n)` call, where
+/// `receiver_type` is the declared type of the receiver
+pub(crate) fn date_arithmetic(
+    call: &MethodCallExpr,
+    receiver_type: Option<&TypeRef>,
+) -> Option<&'static str> {
+    let receiver = call.object.as_ref()?;
+    if call.arguments.len() != 1
+        || !(receiver_type.is_some_and(is_temporal_type) || is_temporal_value(receiver))
+    {
+        return None;
+    }
+    arithmetic_helper(&call.name)
+}
+
+/// `Date`, `Datetime` or `Time`
+pub(crate) fn is_temporal_type(type_ref: &TypeRef) -> bool {
+    type_ref.type_arguments.is_empty()
+        && !type_ref.is_array
+        && matches!(
+            type_ref.name.to_lowercase().as_str(),
+            "date" | "datetime" | "time"
+        )
+}
+
+/// Whether `expr` evidently yields a date or time without knowing any
+/// declared type: a clock call, a `Date`/`Datetime`/`Time` factory, or an
+/// `addX()` call on one of those
+fn is_temporal_value(expr: &Expression) -> bool {
+    let Expression::MethodCall(call) = expr else {
+        return false;
+    };
+    if clock_call(call).is_some() {
+        return true;
+    }
+    match &call.object {
+        Some(Expression::Identifier(class, _, _)) => {
+            matches!(class.to_lowercase().as_str(), "date" | "datetime" | "time")
+                && matches!(
+                    call.name.to_lowercase().as_str(),
+                    "newinstance" | "newinstancegmt" | "valueof" | "valueofgmt" | "parse"
+                )
+        }
+        Some(receiver) => arithmetic_helper(&call.name).is_some() && is_temporal_value(receiver),
+        None => false,
+    }
+}
+
+/// Runtime helper named by an `addX` method name
+pub(crate) fn arithmetic_helper(name: &str) -> Option<&'static str> {
+    let helper = match name.to_lowercase().as_str() {
+        "addyears" => "addYears",
+        "addmonths" => "addMonths",
+        "adddays" => "addDays",
+        "addhours" => "addHours",
+        "addminutes" => "addMinutes",
+        "addseconds" => "addSeconds",
+        _ => return None,
+    };
+    Some(helper)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transpile::test_support::parse_call;
+
+    #[test]
+    fn test_clock_calls() {
+        assert_eq!(clock_call(&parse_call("System.now()")), Some("now"));
+        assert_eq!(clock_call(&parse_call("DateTime.now()")), Some("now"));
+        assert_eq!(clock_call(&parse_call("Date.today()")), Some("today"));
+        assert_eq!(clock_call(&parse_call("Date.now()")), None);
+        assert_eq!(clock_call(&parse_call("clock.now()")), None);
+    }
+
+    #[test]
+    fn test_date_arithmetic_requires_temporal_receiver() {
+        let call = parse_call("due.addDays(3)");
+        let date = TypeRef::simple("Date", call.span);
+        let schedule = TypeRef::simple("Schedule", call.span);
+
+        assert_eq!(date_arithmetic(&call, Some(&date)), Some("addDays"));
+        assert_eq!(date_arithmetic(&call, Some(&schedule)), None);
+        assert_eq!(date_arithmetic(&call, None), None);
+        assert_eq!(date_arithmetic(&parse_call("addDays(3)"), None), None);
+        assert_eq!(date_arithmetic(&parse_call("this.addDays(3)"), None), None);
+        assert_eq!(
+            date_arithmetic(&parse_call("due.addDays(3, 4)"), Some(&date)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_date_arithmetic_on_temporal_values() {
+        assert_eq!(
+            date_arithmetic(&parse_call("Date.today().addDays(3)"), None),
+            Some("addDays")
+        );
+        assert_eq!(
+            date_arithmetic(
+                &parse_call("Datetime.now().addHours(1).addMinutes(5)"),
+                None
+            ),
+            Some("addMinutes")
+        );
+        assert_eq!(
+            date_arithmetic(
+                &parse_call("Date.newInstance(2024, 1, 31).addMonths(1)"),
+                None
+            ),
+            Some("addMonths")
+        );
+        assert_eq!(
+            date_arithmetic(&parse_call("schedule.next().addDays(1)"), None),
+            None
+        );
+    }
+}
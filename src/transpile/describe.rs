@@ -0,0 +1,143 @@
+//! Recognition of Apex schema describe chains
+//!
+//! Off-platform there are no `Schema.SObjectType` tokens, so the runtime
+//! hands out plain describe objects instead: `Account.SObjectType` becomes
+//! `$runtime.describe("Account")`, `getDescribe()` on it is a no-op, and the
+//! describe getters become property reads. `getDescribe()` is only dropped
+//! on a type token or a variable declared as a token or describe result, so
+//! a user class's own `getDescribe()` is still called; getters called on a
+//! describe held in a variable are emitted unchanged.
+
+use crate::ast::{Expression, MethodCallExpr, TypeRef};
+
+/// The SObject named by a type token expression:
+/// `Account.SObjectType`, `Schema.Account.SObjectType` or
+/// `Schema.SObjectType.Account`
+pub(crate) fn sobject_token(expr: &Expression) -> Option<&str> {
+    let Expression::FieldAccess(access) = expr else {
+        return None;
+    };
+
+    if access.field.eq_ignore_ascii_case("SObjectType") {
+        return match &access.object {
+            Expression::Identifier(name, _, _) if !name.eq_ignore_ascii_case("Schema") => {
+                Some(name)
+            }
+            Expression::FieldAccess(inner) if is_schema(&inner.object) => Some(&inner.field),
+            _ => None,
+        };
+    }
+
+    match &access.object {
+        Expression::FieldAccess(inner)
+            if inner.field.eq_ignore_ascii_case("SObjectType") && is_schema(&inner.object) =>
+        {
+            Some(&access.field)
+        }
+        _ => None,
+    }
+}
+
+/// `Schema.getGlobalDescribe()`
+pub(crate) fn is_global_describe(call: &MethodCallExpr) -> bool {
+    call.arguments.is_empty()
+        && call.name.eq_ignore_ascii_case("getGlobalDescribe")
+        && call.object.as_ref().is_some_and(is_schema)
+}
+
+/// `<token>.getDescribe()`, where `receiver_type` is the declared type of
+/// the receiver when it is a variable
+pub(crate) fn is_get_describe(call: &MethodCallExpr, receiver_type: Option<&TypeRef>) -> bool {
+    let Some(receiver) = &call.object else {
+        return false;
+    };
+    call.arguments.is_empty()
+        && call.name.eq_ignore_ascii_case("getDescribe")
+        && (sobject_token(receiver).is_some() || receiver_type.is_some_and(is_describe_type))
+}
+
+/// Property read for a getter called directly on `getDescribe()`, e.g.
+/// `getLabel()` -> `label`; `token_type` is the declared type of the
+/// receiver of `getDescribe()`
+pub(crate) fn describe_property(
+    call: &MethodCallExpr,
+    token_type: Option<&TypeRef>,
+) -> Option<&'static str> {
+    let Some(Expression::MethodCall(receiver)) = &call.object else {
+        return None;
+    };
+    if !call.arguments.is_empty() || !is_get_describe(receiver, token_type) {
+        return None;
+    }
+    let property = match call.name.to_lowercase().as_str() {
+        "getname" => "name",
+        "getlocalname" => "localName",
+        "getlabel" => "label",
+        "getlabelplural" => "labelPlural",
+        "getkeyprefix" => "keyPrefix",
+        "iscustom" => "custom",
+        "isaccessible" => "accessible",
+        "iscreateable" => "createable",
+        "isupdateable" => "updateable",
+        "isdeletable" => "deletable",
+        "isqueryable" => "queryable",
+        "issearchable" => "searchable",
+        _ => return None,
+    };
+    Some(property)
+}
+
+/// `SObjectType` or `DescribeSObjectResult`, with or without `Schema.`
+fn is_describe_type(type_ref: &TypeRef) -> bool {
+    let name = type_ref.name.to_lowercase();
+    let name = name.strip_prefix("schema.").unwrap_or(&name);
+    name == "sobjecttype" || name == "describesobjectresult"
+}
+
+fn is_schema(expr: &Expression) -> bool {
+    matches!(expr, Expression::Identifier(name, _, _) if name.eq_ignore_ascii_case("Schema"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transpile::test_support::{parse_call, parse_expr};
+
+    #[test]
+    fn test_sobject_token_forms() {
+        for source in [
+            "Account.SObjectType",
+            "Schema.Account.SObjectType",
+            "Schema.SObjectType.Account",
+        ] {
+            assert_eq!(
+                sobject_token(&parse_expr(source)),
+                Some("Account"),
+                "{}",
+                source
+            );
+        }
+        assert_eq!(sobject_token(&parse_expr("acc.Name")), None);
+        assert_eq!(sobject_token(&parse_expr("Schema.SObjectType")), None);
+    }
+
+    #[test]
+    fn test_describe_property_requires_get_describe_receiver() {
+        let call = parse_call("Account.SObjectType.getDescribe().getLabel()");
+        assert_eq!(describe_property(&call, None), Some("label"));
+
+        let call = parse_call("contact.getLabel()");
+        assert_eq!(describe_property(&call, None), None);
+    }
+
+    #[test]
+    fn test_get_describe_requires_token_receiver() {
+        let call = parse_call("report.getDescribe()");
+        let token = TypeRef::simple("Schema.SObjectType", call.span);
+        let report = TypeRef::simple("ReportBuilder", call.span);
+
+        assert!(is_get_describe(&call, Some(&token)));
+        assert!(!is_get_describe(&call, Some(&report)));
+        assert!(!is_get_describe(&call, None));
+    }
+}
@@ -0,0 +1,162 @@
+//! Branches on platform predicates fixed by `TranspileOptions::env_flags`
+//!
+//! A deployment target knows whether it runs tests, batches or queueables,
+//! so `if (System.isBatch()) { ... } else { ... }` keeps only the branch the
+//! flag selects. The unit is rewritten before code generation, so code in a
+//! dropped branch never makes a method async, adds an object guard or marks
+//! a future as a callout.
+//!
+//! A condition is decided only when evaluating it cannot skip anything
+//! else: `System.isBatch() && ready` is false when the flag is false, but
+//! `ready && System.isBatch()` is left alone because `ready` may have side
+//! effects. Predicates outside decided conditions become `true`/`false`.
+
+use std::collections::HashMap;
+
+use crate::ast::fold::{self, Folder};
+use crate::ast::{
+    BinaryOp, Block, CompilationUnit, Expression, MethodCallExpr, Statement, UnaryOp,
+};
+
+/// Predicates a deployment target usually fixes; any other zero-argument
+/// call can be given a flag by its qualified name as well
+pub const PLATFORM_PREDICATES: &[&str] = &[
+    "Test.isRunningTest",
+    "System.isBatch",
+    "System.isFuture",
+    "System.isQueueable",
+];
+
+/// Rewrite `unit` with every branch decided by `flags` folded away
+pub(super) fn eliminate_branches(
+    unit: CompilationUnit,
+    flags: &HashMap<String, bool>,
+) -> CompilationUnit {
+    let mut folder = EnvFlags {
+        flags: flags
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), *value))
+            .collect(),
+    };
+    folder.fold_compilation_unit(unit)
+}
+
+struct EnvFlags {
+    /// Lowercased qualified name to value
+    flags: HashMap<String, bool>,
+}
+
+impl EnvFlags {
+    /// Flag given for a zero-argument call such as `System.isBatch()`
+    fn flag(&self, call: &MethodCallExpr) -> Option<bool> {
+        if !call.arguments.is_empty() {
+            return None;
+        }
+        let qualifier = qualified_name(call.object.as_ref()?)?;
+        let name = format!("{}.{}", qualifier, call.name).to_lowercase();
+        self.flags.get(&name).copied()
+    }
+
+    /// Value of a condition, if the flags decide it
+    fn value(&self, expr: &Expression) -> Option<bool> {
+        match expr {
+            Expression::MethodCall(call) => self.flag(call),
+            Expression::Parenthesized(inner, _, _) => self.value(inner),
+            Expression::Unary(unary) if unary.operator == UnaryOp::Not => {
+                self.value(&unary.operand).map(|value| !value)
+            }
+            Expression::Binary(binary) => match (binary.operator, self.value(&binary.left)) {
+                (BinaryOp::And, Some(false)) => Some(false),
+                (BinaryOp::Or, Some(true)) => Some(true),
+                (BinaryOp::And | BinaryOp::Or, Some(_)) => self.value(&binary.right),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether `stmt` is an `if` the flags decide
+    fn decides(&self, stmt: &Statement) -> bool {
+        matches!(stmt, Statement::If(if_stmt) if self.value(&if_stmt.condition).is_some())
+    }
+}
+
+impl Folder for EnvFlags {
+    fn fold_block(&mut self, mut block: Block) -> Block {
+        let mut statements = Vec::with_capacity(block.statements.len());
+        for stmt in block.statements {
+            let decided = self.decides(&stmt);
+            match self.fold_statement(stmt) {
+                Statement::Empty(..) if decided => {}
+                // The kept branch's statements join the enclosing block
+                // unless it declares variables, which must stay scoped to it
+                Statement::Block(kept) if decided && !declares_locals(&kept) => {
+                    statements.extend(kept.statements)
+                }
+                folded => statements.push(folded),
+            }
+        }
+        block.statements = statements;
+        block
+    }
+
+    fn fold_statement(&mut self, stmt: Statement) -> Statement {
+        let Statement::If(mut if_stmt) = stmt else {
+            return fold::fold_statement(self, stmt);
+        };
+        match self.value(&if_stmt.condition) {
+            Some(true) => self.fold_statement(*if_stmt.then_branch),
+            Some(false) => match if_stmt.else_branch {
+                Some(else_branch) => self.fold_statement(*else_branch),
+                None => Statement::Empty(if_stmt.span, if_stmt.id),
+            },
+            None => {
+                // `else if (flag) ...` that folds to nothing drops the `else`
+                let drop_else = if_stmt.else_branch.as_deref().is_some_and(|else_branch| {
+                    matches!(else_branch, Statement::If(nested)
+                        if nested.else_branch.is_none()
+                            && self.value(&nested.condition) == Some(false))
+                });
+                if drop_else {
+                    if_stmt.else_branch = None;
+                }
+                fold::fold_statement(self, Statement::If(if_stmt))
+            }
+        }
+    }
+
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        match expr {
+            Expression::Ternary(ternary) => match self.value(&ternary.condition) {
+                Some(true) => self.fold_expression(ternary.then_expr),
+                Some(false) => self.fold_expression(ternary.else_expr),
+                None => fold::fold_expression(self, Expression::Ternary(ternary)),
+            },
+            Expression::MethodCall(call) => match self.flag(&call) {
+                Some(value) => Expression::Boolean(value, call.span, call.id),
+                None => fold::fold_expression(self, Expression::MethodCall(call)),
+            },
+            other => fold::fold_expression(self, other),
+        }
+    }
+}
+
+/// `System` or `ns.Util` in front of a call
+fn qualified_name(expr: &Expression) -> Option<String> {
+    match expr {
+        Expression::Identifier(name, _, _) => Some(name.clone()),
+        Expression::FieldAccess(access) => Some(format!(
+            "{}.{}",
+            qualified_name(&access.object)?,
+            access.field
+        )),
+        _ => None,
+    }
+}
+
+fn declares_locals(block: &Block) -> bool {
+    block
+        .statements
+        .iter()
+        .any(|stmt| matches!(stmt, Statement::LocalVariable(_)))
+}
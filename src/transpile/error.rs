@@ -11,6 +11,8 @@ pub enum TranspileError {
     InvalidAst(String),
     /// Type conversion error
     TypeError(String),
+    /// The cancellation token fired or its deadline passed
+    Cancelled,
 }
 
 impl fmt::Display for TranspileError {
@@ -25,6 +27,7 @@ impl fmt::Display for TranspileError {
             TranspileError::TypeError(msg) => {
                 write!(f, "Type error: {}", msg)
             }
+            TranspileError::Cancelled => write!(f, "Transpilation cancelled"),
         }
     }
 }
@@ -0,0 +1,145 @@
+//! `@future` methods
+//!
+//! A future method runs later in its own transaction, so a call to it is
+//! not a call: `Notifier.send(ids)` becomes
+//! `await $runtime.jobs.enqueueFuture("Notifier.send", [ids])`, and the
+//! class exports a `Notifier_ENTRY_POINTS` table through which the host
+//! runs the enqueued job. The arguments are serialized between the two, which
+//! is why the platform only accepts primitives and collections of them as
+//! parameters; the same check is made here, with the compiler's message.
+
+use super::error::TranspileError;
+use crate::ast::printer;
+use crate::ast::{
+    AsyncKind, ClassDeclaration, ClassMember, CompilationUnit, Expression, MethodCallExpr,
+    TypeDeclaration, TypeRef,
+};
+
+/// A `@future` method of a top-level class
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FutureMethod {
+    pub class: String,
+    pub name: String,
+    pub callout: bool,
+    pub arity: usize,
+}
+
+impl FutureMethod {
+    /// Name the host dispatches on, `Notifier.send`
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.class, self.name)
+    }
+}
+
+/// The future methods of the unit's top-level classes, or an error for the
+/// first one whose signature the platform rejects
+pub(crate) fn future_methods(unit: &CompilationUnit) -> Result<Vec<FutureMethod>, TranspileError> {
+    let mut futures = Vec::new();
+    for declaration in &unit.declarations {
+        if let TypeDeclaration::Class(class) = declaration {
+            collect(class, &mut futures)?;
+        }
+    }
+    Ok(futures)
+}
+
+fn collect(
+    class: &ClassDeclaration,
+    futures: &mut Vec<FutureMethod>,
+) -> Result<(), TranspileError> {
+    for member in &class.members {
+        let ClassMember::Method(method) = member else {
+            continue;
+        };
+        let Some(AsyncKind::Future { callout }) = method.async_kind(&class.implements) else {
+            continue;
+        };
+        let qualified = format!("{}.{}", class.name, method.name);
+        if !method.modifiers.is_static {
+            return Err(TranspileError::TypeError(format!(
+                "{}: Future methods must be static",
+                qualified
+            )));
+        }
+        if !method.return_type.name.eq_ignore_ascii_case("void") {
+            return Err(TranspileError::TypeError(format!(
+                "{}: Future methods must return void",
+                qualified
+            )));
+        }
+        if let Some(param) = method
+            .parameters
+            .iter()
+            .find(|p| !is_serializable(&p.type_ref))
+        {
+            return Err(TranspileError::TypeError(format!(
+                "{}: Unsupported parameter type {} for parameter {}",
+                qualified,
+                printer::type_ref(&param.type_ref),
+                param.name
+            )));
+        }
+        futures.push(FutureMethod {
+            class: class.name.clone(),
+            name: method.name.clone(),
+            callout,
+            arity: method.parameters.len(),
+        });
+    }
+    Ok(())
+}
+
+/// A primitive, or an array or collection of primitives
+fn is_serializable(type_ref: &TypeRef) -> bool {
+    let collection = matches!(
+        type_ref.name.to_lowercase().as_str(),
+        "list" | "set" | "map"
+    );
+    if collection {
+        !type_ref.is_array
+            && !type_ref.type_arguments.is_empty()
+            && type_ref.type_arguments.iter().all(is_primitive)
+    } else {
+        type_ref.type_arguments.is_empty() && is_primitive_name(&type_ref.name)
+    }
+}
+
+pub(super) fn is_primitive(type_ref: &TypeRef) -> bool {
+    !type_ref.is_array && type_ref.type_arguments.is_empty() && is_primitive_name(&type_ref.name)
+}
+
+fn is_primitive_name(name: &str) -> bool {
+    matches!(
+        name.to_lowercase().as_str(),
+        "blob"
+            | "boolean"
+            | "date"
+            | "datetime"
+            | "decimal"
+            | "double"
+            | "id"
+            | "integer"
+            | "long"
+            | "string"
+            | "time"
+    )
+}
+
+/// The future method `call` invokes, from code in `current_class`: either
+/// `send(...)` within the class or `Notifier.send(...)`
+pub(crate) fn future_call<'f>(
+    call: &MethodCallExpr,
+    current_class: Option<&str>,
+    futures: &'f [FutureMethod],
+) -> Option<&'f FutureMethod> {
+    let class = match &call.object {
+        None => current_class?,
+        Some(Expression::Identifier(class, _, _)) => class.as_str(),
+        Some(_) => return None,
+    };
+    futures.iter().find(|future| {
+        future.class.eq_ignore_ascii_case(class)
+            && future.name.eq_ignore_ascii_case(&call.name)
+            && future.arity == call.arguments.len()
+    })
+}
@@ -9,13 +9,32 @@
 //! SOQL queries are converted to async calls against an injected database context.
 //! DML statements (insert/update/delete) become database mutations.
 
+mod assignments;
 mod codegen;
+mod collections;
 pub mod context;
+mod dates;
+mod describe;
+mod env_flags;
 mod error;
+mod futures;
+mod records;
+mod single_record;
+mod sorting;
+mod split;
+mod statics;
+mod strings;
+#[cfg(test)]
+mod test_support;
+mod triggers;
 
 pub use codegen::Transpiler;
 pub use context::{RuntimeContext, RUNTIME_INTERFACE};
+pub use env_flags::PLATFORM_PREDICATES;
 pub use error::TranspileError;
+pub use sorting::comparable_classes;
+
+use std::collections::HashMap;
 
 use crate::ast::CompilationUnit;
 
@@ -34,6 +53,28 @@ pub fn transpile_with_options(
     transpiler.transpile(unit)
 }
 
+/// Transpile to one or more modules, splitting classes larger than
+/// `TranspileOptions::split_class_threshold`
+///
+/// The unit's own module comes first, followed by one `<Class>.methods`
+/// module per split class.
+pub fn transpile_modules(
+    unit: &CompilationUnit,
+    options: TranspileOptions,
+) -> Result<Vec<TranspiledModule>, TranspileError> {
+    let mut transpiler = Transpiler::with_options(options);
+    transpiler.transpile_modules(unit)
+}
+
+/// One module of [`transpile_modules`] output
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranspiledModule {
+    /// Module name without extension, e.g. `AccountService` or
+    /// `AccountService.methods`; modules import each other as `./<name>`
+    pub name: String,
+    pub code: String,
+}
+
 /// Options for transpilation
 #[derive(Debug, Clone)]
 pub struct TranspileOptions {
@@ -45,6 +86,56 @@ pub struct TranspileOptions {
     pub indent: String,
     /// Generate async methods for SOQL/DML
     pub async_database: bool,
+    /// Where static fields holding database results are stored
+    pub static_cache_strategy: StaticCacheStrategy,
+    /// Objects dynamic SOQL may query, e.g. from
+    /// `SalesforceSchema::object_names()`. When set, object names spliced
+    /// into `Database.query()` strings are checked against this list at run
+    /// time.
+    pub allowed_objects: Option<Vec<String>>,
+    /// Emit one handler per trigger event (`<Trigger>_onBeforeInsert`, ...) for
+    /// trigger bodies that branch on `Trigger.isX` flags or
+    /// `Trigger.operationType`, instead of a single trigger function
+    pub split_trigger_events: bool,
+    /// Emitted size in bytes above which [`transpile_modules`] moves a
+    /// top-level class's method bodies into a companion module, keeping
+    /// each module under edge-runtime script-size limits. [`transpile`]
+    /// always emits a single module.
+    pub split_class_threshold: Option<usize>,
+    /// Whether the output may run under tests: `Test.isRunningTest()`
+    /// asks `$runtime.isTest()` when set and is the constant `false` for
+    /// production transpiles otherwise
+    pub test_context: bool,
+    /// Values of platform predicates known for the deployment target, by
+    /// qualified name: the [`PLATFORM_PREDICATES`] or any other
+    /// zero-argument call (`FeatureFlags.isEnabled`). `if` statements and
+    /// ternaries they decide keep only the live branch; predicates without
+    /// a flag are left as they are.
+    pub env_flags: HashMap<String, bool>,
+    /// Classes declared in other units of the project that implement
+    /// `Comparable`, as collected by [`comparable_classes`], so `sort()` on
+    /// a list of them goes through `compareTo`. Classes in the unit being
+    /// transpiled are found without this.
+    pub comparable_classes: Vec<String>,
+}
+
+/// Storage strategy for Apex static fields
+///
+/// Apex statics only live for one transaction. In long-lived isolates a
+/// module-level static outlives the request that populated it, so statics
+/// loaded from SOQL/DML can instead be kept on the runtime per request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum StaticCacheStrategy {
+    /// Statics are class fields shared for the lifetime of the module
+    #[default]
+    Module,
+    /// Statics assigned from SOQL/DML results live in `$runtime.statics`
+    Request,
 }
 
 impl Default for TranspileOptions {
@@ -54,6 +145,13 @@ impl Default for TranspileOptions {
             include_imports: true,
             indent: "  ".to_string(),
             async_database: true,
+            static_cache_strategy: StaticCacheStrategy::Module,
+            allowed_objects: None,
+            split_trigger_events: false,
+            split_class_threshold: None,
+            test_context: true,
+            env_flags: HashMap::new(),
+            comparable_classes: Vec::new(),
         }
     }
 }
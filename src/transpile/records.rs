@@ -0,0 +1,99 @@
+//! Which receivers are SObject records
+//!
+//! A few rewrites only apply to records: `acc.addError(msg)` reports a
+//! validation error through the runtime, but a user class may declare an
+//! `addError` of its own that must stay a plain method call. Codegen knows
+//! only declared types, so a type counts as an SObject when it is `SObject`,
+//! carries a custom object suffix (`Invoice__c`, `Setting__mdt`), or is an
+//! unqualified name that is neither an Apex primitive or collection nor a
+//! type declared in the unit.
+
+use std::collections::HashSet;
+
+use super::collections::collection_kind;
+use super::futures::is_primitive;
+use crate::ast::{
+    ClassDeclaration, ClassMember, CompilationUnit, Expression, TypeDeclaration, TypeRef,
+};
+
+/// Suffixes of custom objects, custom metadata, platform events, external
+/// and big objects
+const CUSTOM_SUFFIXES: &[&str] = &["__c", "__mdt", "__e", "__x", "__b"];
+
+/// Lowercased names of every class, interface and enum declared in `unit`,
+/// inner types included
+pub(crate) fn declared_types(unit: &CompilationUnit) -> HashSet<String> {
+    fn collect(class: &ClassDeclaration, names: &mut HashSet<String>) {
+        names.insert(class.name.to_lowercase());
+        for member in &class.members {
+            match member {
+                ClassMember::InnerClass(inner) => collect(inner, names),
+                ClassMember::InnerInterface(iface) => {
+                    names.insert(iface.name.to_lowercase());
+                }
+                ClassMember::InnerEnum(e) => {
+                    names.insert(e.name.to_lowercase());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut names = HashSet::new();
+    for declaration in &unit.declarations {
+        match declaration {
+            TypeDeclaration::Class(class) => collect(class, &mut names),
+            TypeDeclaration::Interface(iface) => {
+                names.insert(iface.name.to_lowercase());
+            }
+            TypeDeclaration::Enum(e) => {
+                names.insert(e.name.to_lowercase());
+            }
+            TypeDeclaration::Trigger(_) => {}
+        }
+    }
+    names
+}
+
+/// Whether a declared type is an SObject, given the unit's own types
+pub(crate) fn is_sobject_type(type_ref: &TypeRef, unit_types: &HashSet<String>) -> bool {
+    if type_ref.is_array || !type_ref.type_arguments.is_empty() {
+        return false;
+    }
+    let name = type_ref.name.to_lowercase();
+    if name == "sobject" || CUSTOM_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+        return true;
+    }
+    !name.contains('.')
+        && !is_primitive(type_ref)
+        && collection_kind(type_ref).is_none()
+        && !matches!(name.as_str(), "object" | "void")
+        && !unit_types.contains(&name)
+}
+
+/// Whether `expr` reads a record out of the trigger context:
+/// `Trigger.new[i]`, `Trigger.old[i]` or `Trigger.newMap.get(id)`
+pub(crate) fn is_trigger_record(expr: &Expression) -> bool {
+    let context_collection = |expr: &Expression, names: &[&str]| match expr {
+        Expression::FieldAccess(access) => {
+            matches!(&access.object, Expression::Identifier(trigger, _, _)
+                if trigger.eq_ignore_ascii_case("Trigger"))
+                && names
+                    .iter()
+                    .any(|name| access.field.eq_ignore_ascii_case(name))
+        }
+        _ => false,
+    };
+    match expr {
+        Expression::ArrayAccess(access) => context_collection(&access.array, &["new", "old"]),
+        Expression::MethodCall(call) => {
+            call.name.eq_ignore_ascii_case("get")
+                && call.arguments.len() == 1
+                && call
+                    .object
+                    .as_ref()
+                    .is_some_and(|map| context_collection(map, &["newMap", "oldMap"]))
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,237 @@
+//! Queries assigned to a single record
+//!
+//! A query assigned to an SObject variable rather than a List yields that
+//! one record in Apex, and throws `System.QueryException: List has no rows
+//! for assignment to SObject` when nothing matches. Codegen emits `$runtime.queryOne`, which throws
+//! the same way, instead of `$runtime.query` for the queries
+//! [`classify`](crate::analysis::soql_context::classify) reports as
+//! single-record assignments.
+//!
+//! The usual way to avoid that exception is to fetch by Id into a list and
+//! check its size before taking the first element:
+//!
+//! ```apex
+//! List<Account> accounts = [SELECT Name FROM Account WHERE Id = :accountId LIMIT 1];
+//! if (!accounts.isEmpty()) {
+//!     update accounts[0];
+//! }
+//! ```
+//!
+//! When a list local holds such a primary-key query and is used only for
+//! `size()`/`isEmpty()` checks and element 0, it is emitted as the record
+//! itself via `$runtime.queryOptional` (the record or `null`), and the checks
+//! are rewritten against `null`. Any other use of the list keeps it a list.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::visitor::{walk_block, Visitor};
+use crate::ast::{BinaryOp, Block, Expression, SoqlQuery, Statement, TypeRef, UnaryOp};
+
+/// How an optional-record list local is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RecordListUse {
+    /// `list.size()`: 1 when the record was found, 0 otherwise
+    Size,
+    /// `list.isEmpty()`
+    IsEmpty,
+    /// `list[0]` or `list.get(0)`
+    First,
+}
+
+/// Element type of a `List<T>` or `T[]`
+pub(crate) fn list_element_type(list_type: &TypeRef) -> Option<TypeRef> {
+    if list_type.is_array {
+        Some(TypeRef {
+            is_array: false,
+            ..list_type.clone()
+        })
+    } else if list_type.name.eq_ignore_ascii_case("List") {
+        list_type.type_arguments.first().cloned()
+    } else {
+        None
+    }
+}
+
+/// The query in `expr`, looking through parentheses
+pub(crate) fn query_of(expr: &Expression) -> Option<&SoqlQuery> {
+    match expr {
+        Expression::Soql(query) => Some(query),
+        Expression::Parenthesized(inner, _, _) => query_of(inner),
+        _ => None,
+    }
+}
+
+/// Recognize `name.size()`, `name.isEmpty()`, `name[0]` and `name.get(0)`,
+/// returning the variable name
+pub(crate) fn record_list_use(expr: &Expression) -> Option<(&str, RecordListUse)> {
+    let is_zero = |index: &Expression| matches!(index, Expression::Integer(0, _, _, _));
+    let (object, kind) = match expr {
+        Expression::ArrayAccess(access) if is_zero(&access.index) => {
+            (&access.array, RecordListUse::First)
+        }
+        Expression::MethodCall(call) => {
+            let kind = match (call.name.to_lowercase().as_str(), call.arguments.as_slice()) {
+                ("size", []) => RecordListUse::Size,
+                ("isempty", []) => RecordListUse::IsEmpty,
+                ("get", [index]) if is_zero(index) => RecordListUse::First,
+                _ => return None,
+            };
+            (call.object.as_ref()?, kind)
+        }
+        _ => return None,
+    };
+    match object {
+        Expression::Identifier(name, _, _) => Some((name, kind)),
+        _ => None,
+    }
+}
+
+/// Recognize a whole size check, `name.size() > 0`, `name.size() == 0`,
+/// `!name.isEmpty()` and the like, returning the variable name and whether
+/// the check passes when the record was found
+///
+/// These are emitted as a plain `!== null` / `=== null` comparison so
+/// TypeScript narrows the record inside the guarded block.
+pub(crate) fn presence_check(expr: &Expression) -> Option<(&str, bool)> {
+    match expr {
+        Expression::Binary(binary) => {
+            let (name, RecordListUse::Size) = record_list_use(&binary.left)? else {
+                return None;
+            };
+            let Expression::Integer(count, _, _, _) = binary.right else {
+                return None;
+            };
+            let present = match (binary.operator, count) {
+                (BinaryOp::GreaterThan, 0)
+                | (BinaryOp::GreaterOrEqual, 1)
+                | (BinaryOp::NotEqual | BinaryOp::ExactNotEqual, 0)
+                | (BinaryOp::Equal | BinaryOp::ExactEqual, 1) => true,
+                (BinaryOp::Equal | BinaryOp::ExactEqual, 0)
+                | (BinaryOp::LessThan, 1)
+                | (BinaryOp::LessOrEqual, 0) => false,
+                _ => return None,
+            };
+            Some((name, present))
+        }
+        Expression::Unary(unary) if unary.operator == UnaryOp::Not => {
+            match record_list_use(&unary.operand)? {
+                (name, RecordListUse::IsEmpty) => Some((name, true)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Lowercased names of the list locals in `body` that can be emitted as a
+/// single optional record (see the module docs)
+pub(crate) fn optional_record_lists(body: &Block) -> HashSet<String> {
+    /// List locals initialized with a query by Id
+    #[derive(Default)]
+    struct Candidates(HashSet<String>);
+
+    impl<'ast> Visitor<'ast> for Candidates {
+        fn visit_statement(&mut self, stmt: &'ast Statement) {
+            let Statement::LocalVariable(local) = stmt else {
+                return;
+            };
+            if !local.type_ref.is_array && !local.type_ref.name.eq_ignore_ascii_case("List") {
+                return;
+            }
+            for declarator in &local.declarators {
+                let by_id = declarator
+                    .initializer
+                    .as_ref()
+                    .and_then(query_of)
+                    .is_some_and(|query| query.is_single_record_by_id());
+                if by_id {
+                    self.0.insert(declarator.name.to_lowercase());
+                }
+            }
+        }
+    }
+
+    /// Each recognized use contains exactly one mention of the variable, so
+    /// equal counts mean it appears nowhere else; at least one use must be
+    /// a size check
+    #[derive(Default)]
+    struct Uses {
+        mentions: HashMap<String, usize>,
+        recognized: HashMap<String, usize>,
+        guarded: HashSet<String>,
+    }
+
+    impl<'ast> Visitor<'ast> for Uses {
+        fn visit_expression(&mut self, expr: &'ast Expression) {
+            if let Expression::Identifier(name, _, _) = expr {
+                *self.mentions.entry(name.to_lowercase()).or_default() += 1;
+            } else if let Some((name, kind)) = record_list_use(expr) {
+                let name = name.to_lowercase();
+                *self.recognized.entry(name.clone()).or_default() += 1;
+                if kind != RecordListUse::First {
+                    self.guarded.insert(name);
+                }
+            }
+        }
+    }
+
+    let mut candidates = Candidates::default();
+    walk_block(&mut candidates, body);
+    let mut uses = Uses::default();
+    walk_block(&mut uses, body);
+
+    candidates
+        .0
+        .into_iter()
+        .filter(|name| {
+            uses.guarded.contains(name)
+                && uses.mentions.get(name).copied().unwrap_or(0)
+                    == uses.recognized.get(name).copied().unwrap_or(0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{ClassMember, TypeDeclaration};
+    use crate::parser::parse;
+
+    fn method_body(source: &str) -> Block {
+        let unit = parse(source).unwrap();
+        let TypeDeclaration::Class(ref class) = unit.declarations[0] else {
+            panic!("expected a class");
+        };
+        let ClassMember::Method(ref method) = class.members[0] else {
+            panic!("expected a method");
+        };
+        method.body.clone().unwrap()
+    }
+
+    #[test]
+    fn test_only_guarded_by_id_lists_are_optional() {
+        let body = method_body(
+            r#"
+            public class Sample {
+                public void run(Id accountId) {
+                    List<Account> guarded = [SELECT Name FROM Account WHERE Id = :accountId LIMIT 1];
+                    if (guarded.size() > 0) {
+                        System.debug(guarded[0].Name);
+                    }
+                    List<Account> looped = [SELECT Name FROM Account WHERE Id = :accountId];
+                    if (!looped.isEmpty()) {
+                        for (Account a : looped) { System.debug(a); }
+                    }
+                    List<Account> unguarded = [SELECT Name FROM Account WHERE Id = :accountId];
+                    System.debug(unguarded.get(0));
+                }
+            }
+            "#,
+        );
+
+        assert_eq!(
+            optional_record_lists(&body),
+            HashSet::from(["guarded".to_string()])
+        );
+    }
+}
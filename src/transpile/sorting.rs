@@ -0,0 +1,136 @@
+//! Comparators for `List.sort()`
+//!
+//! JavaScript's default `Array.prototype.sort` compares elements as strings,
+//! so `[10, 9].sort()` leaves the list as is and objects all compare equal
+//! as `"[object Object]"`. Apex sorts numbers numerically, strings by
+//! character code and `Comparable` wrappers through `compareTo`, so codegen
+//! passes an explicit comparator whenever the declared element type of the
+//! list is known. Both sorts are stable. `Comparable` classes declared in
+//! other units are only known through
+//! [`TranspileOptions::comparable_classes`](super::TranspileOptions::comparable_classes).
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::visitor::{walk_block, Visitor};
+use crate::ast::{
+    Block, ClassDeclaration, ClassMember, CompilationUnit, ForEachStatement,
+    LocalVariableDeclaration, Parameter, TypeDeclaration, TypeRef,
+};
+
+/// Lowercased names of the classes in `unit` (inner classes included) that
+/// implement `Comparable` directly
+pub fn comparable_classes(unit: &CompilationUnit) -> HashSet<String> {
+    fn collect(class: &ClassDeclaration, names: &mut HashSet<String>) {
+        if class.implements.iter().any(is_comparable) {
+            names.insert(class.name.to_lowercase());
+        }
+        for member in &class.members {
+            if let ClassMember::InnerClass(inner) = member {
+                collect(inner, names);
+            }
+        }
+    }
+
+    let mut names = HashSet::new();
+    for declaration in &unit.declarations {
+        if let TypeDeclaration::Class(class) = declaration {
+            collect(class, &mut names);
+        }
+    }
+    names
+}
+
+/// `Comparable` or `System.Comparable`
+fn is_comparable(type_ref: &TypeRef) -> bool {
+    let name = type_ref.name.to_lowercase();
+    name == "comparable" || name == "system.comparable"
+}
+
+/// Declared types of a method's parameters and of every local and loop
+/// variable in its body, keyed by lowercased name
+pub(crate) fn local_types(parameters: &[Parameter], body: &Block) -> HashMap<String, TypeRef> {
+    struct Types(HashMap<String, TypeRef>);
+
+    impl<'ast> Visitor<'ast> for Types {
+        fn visit_local_variable(&mut self, local: &'ast LocalVariableDeclaration) {
+            for declarator in &local.declarators {
+                self.0
+                    .insert(declarator.name.to_lowercase(), local.type_ref.clone());
+            }
+        }
+
+        fn visit_for_each(&mut self, foreach: &'ast ForEachStatement) {
+            self.0
+                .insert(foreach.variable.to_lowercase(), foreach.type_ref.clone());
+        }
+    }
+
+    let mut types = Types(
+        parameters
+            .iter()
+            .map(|p| (p.name.to_lowercase(), p.type_ref.clone()))
+            .collect(),
+    );
+    walk_block(&mut types, body);
+    types.0
+}
+
+/// Comparator for sorting a list declared as `list_type`, or `None` when the
+/// element type has no Apex ordering codegen knows about
+pub(crate) fn sort_comparator(
+    list_type: &TypeRef,
+    comparable: &HashSet<String>,
+) -> Option<&'static str> {
+    let element = if list_type.is_array {
+        list_type.name.to_lowercase()
+    } else if list_type.name.eq_ignore_ascii_case("List") {
+        list_type.type_arguments.first()?.name.to_lowercase()
+    } else {
+        return None;
+    };
+
+    match element.as_str() {
+        "integer" | "double" | "decimal" => Some("(a, b) => a - b"),
+        // bigint differences cannot be returned as a comparator result
+        "long" | "string" | "id" => Some("(a, b) => (a < b ? -1 : a > b ? 1 : 0)"),
+        "date" | "datetime" | "time" => Some("(a, b) => a.getTime() - b.getTime()"),
+        name if comparable.contains(name) => Some("(a, b) => a.compareTo(b)"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::NodeId;
+    use crate::lexer::Span;
+
+    fn list_of(element: &str) -> TypeRef {
+        TypeRef {
+            name: "List".to_string(),
+            type_arguments: vec![TypeRef::simple(element, Span::new(0, 0))],
+            is_array: false,
+            span: Span::new(0, 0),
+            id: NodeId::DUMMY,
+        }
+    }
+
+    #[test]
+    fn test_sort_comparator_by_element_type() {
+        let comparable = HashSet::from(["oppwrapper".to_string()]);
+
+        assert_eq!(
+            sort_comparator(&list_of("Integer"), &comparable),
+            Some("(a, b) => a - b")
+        );
+        assert_eq!(
+            sort_comparator(&list_of("OppWrapper"), &comparable),
+            Some("(a, b) => a.compareTo(b)")
+        );
+        assert_eq!(sort_comparator(&list_of("Account"), &comparable), None);
+        assert_eq!(
+            sort_comparator(&TypeRef::simple("Set", Span::new(0, 0)), &comparable),
+            None
+        );
+    }
+}
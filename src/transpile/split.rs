@@ -0,0 +1,80 @@
+//! Splitting large classes across modules
+//!
+//! Edge runtimes cap the size of each module, and a transpiled 6,000-line
+//! Apex class easily exceeds it. With `TranspileOptions::split_class_threshold`,
+//! [`transpile_modules`](super::transpile_modules) emits any top-level class
+//! whose output is larger than the threshold a second time, moving its
+//! method bodies into a companion `<Class>.methods` module of standalone
+//! functions that take the instance as an explicit `self`:
+//!
+//! ```text
+//! // AccountService
+//! import * as __m from "./AccountService.methods";
+//!
+//! export class AccountService {
+//!   getActiveAccounts(limit: number): Promise<Account[]> {
+//!     return __m.getActiveAccounts(this, limit);
+//!   }
+//! }
+//!
+//! // AccountService.methods
+//! import { AccountService } from "./AccountService";
+//!
+//! export async function getActiveAccounts(self: AccountService, limit: number): Promise<Account[]> {
+//!   ...
+//! }
+//! ```
+//!
+//! Inside the moved bodies `this` becomes `self`; static members are
+//! already addressed through the class name, which the companion imports.
+//! The two modules import each other, which ES modules allow since neither
+//! touches the other's exports while being evaluated. Private and protected
+//! members become public members marked `@internal` so the companion
+//! functions may access them. Methods calling `super` stay in the class.
+
+use crate::ast::visitor::{walk_block, Visitor};
+use crate::ast::{Block, CompilationUnit, Expression, TypeDeclaration};
+
+/// A top-level class being emitted split, and its companion module so far
+#[derive(Debug)]
+pub(crate) struct SplitClass {
+    pub name: String,
+    /// Namespace the class imports its companion module as
+    pub alias: String,
+    /// Body of the companion module
+    pub functions: String,
+}
+
+/// Name of the companion module holding a class's method bodies
+pub(crate) fn methods_module(class: &str) -> String {
+    format!("{}.methods", class)
+}
+
+/// Name of the main module of a unit: its first declaration
+pub(crate) fn unit_module(unit: &CompilationUnit) -> String {
+    let name = match unit.declarations.first() {
+        Some(TypeDeclaration::Class(class)) => &class.name,
+        Some(TypeDeclaration::Interface(iface)) => &iface.name,
+        Some(TypeDeclaration::Enum(enum_decl)) => &enum_decl.name,
+        Some(TypeDeclaration::Trigger(trigger)) => &trigger.name,
+        None => "module",
+    };
+    name.to_string()
+}
+
+/// Whether a method body can move out of its class: `super` only resolves
+/// inside a class body
+pub(crate) fn can_move(body: &Block) -> bool {
+    #[derive(Default)]
+    struct UsesSuper(bool);
+
+    impl<'ast> Visitor<'ast> for UsesSuper {
+        fn visit_expression(&mut self, expr: &'ast Expression) {
+            self.0 |= matches!(expr, Expression::Super(..));
+        }
+    }
+
+    let mut uses_super = UsesSuper::default();
+    walk_block(&mut uses_super, body);
+    !uses_super.0
+}
@@ -0,0 +1,393 @@
+//! Static field analysis for the transpiler
+//!
+//! Apex statics live for one transaction, while module-level JavaScript
+//! statics live as long as the isolate. This module finds the statics whose
+//! lifetime matters (values loaded from the database) and the lazy-init
+//! getters that populate them, so codegen can scope and guard them.
+
+use std::collections::HashSet;
+
+use crate::ast::visitor::{walk_block, walk_expression, Visitor};
+use crate::ast::{
+    AssignmentExpr, AssignmentOp, BinaryOp, Block, ClassDeclaration, ClassMember, Expression,
+    MethodDeclaration, Statement,
+};
+
+/// Detect the lazy-initialization idiom in a static method:
+///
+/// ```apex
+/// if (cache == null) { cache = buildFromSoql(); }
+/// return cache;
+/// ```
+///
+/// Returns the name of the static field being initialized.
+pub(crate) fn detect_lazy_init(
+    method: &MethodDeclaration,
+    class_name: &str,
+    static_fields: &HashSet<String>,
+) -> Option<String> {
+    if !method.modifiers.is_static {
+        return None;
+    }
+    let body = method.body.as_ref()?;
+    let [Statement::If(if_stmt), Statement::Return(ret)] = body.statements.as_slice() else {
+        return None;
+    };
+    if if_stmt.else_branch.is_some() {
+        return None;
+    }
+
+    let field = null_checked_static(&if_stmt.condition, class_name, static_fields)?;
+    let returned = static_field_name(ret.value.as_ref()?, class_name, static_fields)?;
+    if returned != field {
+        return None;
+    }
+
+    let branch: &[Statement] = match if_stmt.then_branch.as_ref() {
+        Statement::Block(block) => &block.statements,
+        other => std::slice::from_ref(other),
+    };
+    let assigns_field = branch.iter().any(|stmt| match stmt {
+        Statement::Expression(expr_stmt) => match &expr_stmt.expression {
+            Expression::Assignment(assign) => {
+                assign.operator == AssignmentOp::Assign
+                    && static_field_name(&assign.target, class_name, static_fields).as_deref()
+                        == Some(field.as_str())
+            }
+            _ => false,
+        },
+        _ => false,
+    });
+
+    assigns_field.then_some(field)
+}
+
+/// Find statics that hold database results and should be scoped to a request.
+///
+/// A static qualifies when it is assigned a value containing SOQL, a
+/// `Database.*` call, or a call to a method of this class that itself runs
+/// SOQL/DML. Statics with a non-null initializer are left module-level since
+/// their initial value is not request-specific.
+pub(crate) fn find_request_scoped_statics(
+    class: &ClassDeclaration,
+    static_fields: &HashSet<String>,
+) -> HashSet<String> {
+    let db_methods: HashSet<String> = class
+        .members
+        .iter()
+        .filter_map(|member| match member {
+            ClassMember::Method(method) => method
+                .body
+                .as_ref()
+                .filter(|body| block_touches_database(body))
+                .map(|_| method.name.to_lowercase()),
+            _ => None,
+        })
+        .collect();
+
+    let initialized: HashSet<&str> = class
+        .members
+        .iter()
+        .filter_map(|member| match member {
+            ClassMember::Field(field) if field.modifiers.is_static => Some(field),
+            _ => None,
+        })
+        .flat_map(|field| field.declarators.iter())
+        .filter(
+            |d| matches!(d.initializer, Some(ref init) if !matches!(init, Expression::Null(..))),
+        )
+        .map(|d| d.name.as_str())
+        .collect();
+
+    #[derive(Default)]
+    struct Assignments<'a>(Vec<&'a AssignmentExpr>);
+
+    impl<'a> Visitor<'a> for Assignments<'a> {
+        fn visit_assignment(&mut self, assign: &'a AssignmentExpr) {
+            self.0.push(assign);
+        }
+    }
+
+    let mut assignments = Assignments::default();
+    for member in &class.members {
+        match member {
+            ClassMember::Method(method) => {
+                if let Some(ref body) = method.body {
+                    walk_block(&mut assignments, body);
+                }
+            }
+            ClassMember::Constructor(ctor) => walk_block(&mut assignments, &ctor.body),
+            ClassMember::StaticBlock(block) => walk_block(&mut assignments, block),
+            ClassMember::Property(prop) => {
+                for accessor in [&prop.getter, &prop.setter].into_iter().flatten() {
+                    if let Some(ref body) = accessor.body {
+                        walk_block(&mut assignments, body);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    assignments
+        .0
+        .into_iter()
+        .filter_map(|assign| {
+            let name = static_field_name(&assign.target, &class.name, static_fields)?;
+            let scoped = !initialized.contains(name.as_str())
+                && loads_from_database(&assign.value, &class.name, &db_methods);
+            scoped.then_some(name)
+        })
+        .collect()
+}
+
+/// Resolve `field` or `ClassName.field` to a static field name of the class
+pub(crate) fn static_field_name(
+    expr: &Expression,
+    class_name: &str,
+    static_fields: &HashSet<String>,
+) -> Option<String> {
+    match expr {
+        Expression::Identifier(name, _, _) if static_fields.contains(name) => Some(name.clone()),
+        Expression::FieldAccess(access) => match &access.object {
+            Expression::Identifier(owner, _, _)
+                if owner.eq_ignore_ascii_case(class_name)
+                    && static_fields.contains(&access.field) =>
+            {
+                Some(access.field.clone())
+            }
+            _ => None,
+        },
+        Expression::Parenthesized(inner, _, _) => {
+            static_field_name(inner, class_name, static_fields)
+        }
+        _ => None,
+    }
+}
+
+/// Match `field == null` / `null == field` against a static field
+fn null_checked_static(
+    cond: &Expression,
+    class_name: &str,
+    static_fields: &HashSet<String>,
+) -> Option<String> {
+    match cond {
+        Expression::Parenthesized(inner, _, _) => {
+            null_checked_static(inner, class_name, static_fields)
+        }
+        Expression::Binary(binary)
+            if matches!(binary.operator, BinaryOp::Equal | BinaryOp::ExactEqual) =>
+        {
+            match (&binary.left, &binary.right) {
+                (other, Expression::Null(..)) | (Expression::Null(..), other) => {
+                    static_field_name(other, class_name, static_fields)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn loads_from_database(expr: &Expression, class_name: &str, db_methods: &HashSet<String>) -> bool {
+    struct Loads<'c> {
+        class_name: &'c str,
+        db_methods: &'c HashSet<String>,
+        found: bool,
+    }
+
+    impl<'ast> Visitor<'ast> for Loads<'_> {
+        fn visit_expression(&mut self, e: &'ast Expression) {
+            self.found |= match e {
+                Expression::Soql(_) => true,
+                Expression::MethodCall(call) => match &call.object {
+                    None | Some(Expression::This(..)) => {
+                        self.db_methods.contains(&call.name.to_lowercase())
+                    }
+                    Some(Expression::Identifier(owner, _, _)) => {
+                        owner.eq_ignore_ascii_case("Database")
+                            || (owner.eq_ignore_ascii_case(self.class_name)
+                                && self.db_methods.contains(&call.name.to_lowercase()))
+                    }
+                    _ => false,
+                },
+                _ => false,
+            };
+        }
+    }
+
+    let mut loads = Loads {
+        class_name,
+        db_methods,
+        found: false,
+    };
+    walk_expression(&mut loads, expr);
+    loads.found
+}
+
+fn block_touches_database(block: &Block) -> bool {
+    #[derive(Default)]
+    struct Queries(bool);
+
+    impl<'ast> Visitor<'ast> for Queries {
+        fn visit_statement(&mut self, stmt: &'ast Statement) {
+            self.0 |= matches!(stmt, Statement::Dml(_));
+        }
+
+        fn visit_expression(&mut self, e: &'ast Expression) {
+            self.0 |= match e {
+                Expression::Soql(_) => true,
+                Expression::MethodCall(call) => matches!(
+                    &call.object,
+                    Some(Expression::Identifier(owner, _, _)) if owner.eq_ignore_ascii_case("Database")
+                ),
+                _ => false,
+            };
+        }
+    }
+
+    let mut queries = Queries::default();
+    walk_block(&mut queries, block);
+    queries.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::TypeDeclaration;
+    use crate::parser::parse;
+
+    fn parse_class(source: &str) -> ClassDeclaration {
+        match parse(source).expect("Parse failed").declarations.remove(0) {
+            TypeDeclaration::Class(class) => class,
+            _ => panic!("expected a class"),
+        }
+    }
+
+    fn statics_of(class: &ClassDeclaration) -> HashSet<String> {
+        class
+            .members
+            .iter()
+            .filter_map(|m| match m {
+                ClassMember::Field(f) if f.modifiers.is_static => Some(f),
+                _ => None,
+            })
+            .flat_map(|f| f.declarators.iter().map(|d| d.name.clone()))
+            .collect()
+    }
+
+    fn method<'a>(class: &'a ClassDeclaration, name: &str) -> &'a MethodDeclaration {
+        class
+            .members
+            .iter()
+            .find_map(|m| match m {
+                ClassMember::Method(method) if method.name == name => Some(method),
+                _ => None,
+            })
+            .expect("method not found")
+    }
+
+    const CACHE_CLASS: &str = r#"
+        public class AccountCache {
+            private static Map<Id, Account> cache;
+            private static Integer hits;
+            public static Map<Id, Account> getCache() {
+                if (cache == null) {
+                    cache = buildFromSoql();
+                }
+                return cache;
+            }
+            public static Integer getHits() {
+                if (null == AccountCache.hits) hits = 0;
+                return hits;
+            }
+            public static Map<Id, Account> peek() {
+                if (cache == null) {
+                    return null;
+                }
+                return cache;
+            }
+            private static Map<Id, Account> buildFromSoql() {
+                return new Map<Id, Account>([SELECT Id FROM Account]);
+            }
+        }
+    "#;
+
+    #[test]
+    fn test_detect_lazy_init() {
+        let class = parse_class(CACHE_CLASS);
+        let statics = statics_of(&class);
+
+        assert_eq!(
+            detect_lazy_init(method(&class, "getCache"), &class.name, &statics),
+            Some("cache".to_string())
+        );
+        // Qualified null check and unbraced branch
+        assert_eq!(
+            detect_lazy_init(method(&class, "getHits"), &class.name, &statics),
+            Some("hits".to_string())
+        );
+    }
+
+    #[test]
+    fn test_detect_lazy_init_requires_assignment() {
+        let class = parse_class(CACHE_CLASS);
+        let statics = statics_of(&class);
+
+        assert_eq!(
+            detect_lazy_init(method(&class, "peek"), &class.name, &statics),
+            None
+        );
+    }
+
+    #[test]
+    fn test_request_scoped_statics() {
+        let class = parse_class(CACHE_CLASS);
+        let statics = statics_of(&class);
+        let scoped = find_request_scoped_statics(&class, &statics);
+
+        // cache is assigned from a method running SOQL; hits is a plain counter
+        assert!(scoped.contains("cache"));
+        assert!(!scoped.contains("hits"));
+    }
+
+    #[test]
+    fn test_nested_dml_scopes_static_to_request() {
+        let class = parse_class(
+            r#"
+            public class Ledger {
+                private static Integer saved;
+                static Integer flush(Integer mode) {
+                    switch on mode {
+                        when 1 { do { insert new Account(Name = 'x'); } while (mode > 1); }
+                    }
+                    return 1;
+                }
+                public static void run() {
+                    saved = flush(1);
+                }
+            }
+            "#,
+        );
+        let statics = statics_of(&class);
+
+        assert!(find_request_scoped_statics(&class, &statics).contains("saved"));
+    }
+
+    #[test]
+    fn test_initialized_static_stays_module_level() {
+        let class = parse_class(
+            r#"
+            public class Config {
+                private static List<Account> rows = new List<Account>();
+                public static void load() {
+                    rows = [SELECT Id FROM Account];
+                }
+            }
+            "#,
+        );
+        let statics = statics_of(&class);
+
+        assert!(find_request_scoped_statics(&class, &statics).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+//! `String.valueOf()`
+//!
+//! `String.valueOf(x)` becomes `String(x)`, which formats numbers, booleans
+//! and records much as Apex does. Apex enums are emitted as TypeScript
+//! numeric enums, so `String(Season.WINTER)` would give `"0"`; for an enum
+//! argument the name comes from the enum's reverse mapping instead:
+//! `Season[Season.WINTER]`.
+
+use std::collections::HashMap;
+
+use crate::ast::{
+    ClassDeclaration, ClassMember, CompilationUnit, Expression, MethodCallExpr, TypeDeclaration,
+    TypeRef,
+};
+
+/// Enums declared in `unit` (inner enums included), by lowercased name
+pub(crate) fn enum_names(unit: &CompilationUnit) -> HashMap<String, String> {
+    fn collect(class: &ClassDeclaration, names: &mut HashMap<String, String>) {
+        for member in &class.members {
+            match member {
+                ClassMember::InnerEnum(e) => {
+                    names.insert(e.name.to_lowercase(), e.name.clone());
+                }
+                ClassMember::InnerClass(inner) => collect(inner, names),
+                _ => {}
+            }
+        }
+    }
+
+    let mut names = HashMap::new();
+    for declaration in &unit.declarations {
+        match declaration {
+            TypeDeclaration::Enum(e) => {
+                names.insert(e.name.to_lowercase(), e.name.clone());
+            }
+            TypeDeclaration::Class(class) => collect(class, &mut names),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// Return types of the methods of the unit's classes (inner classes
+/// included), by lowercased (class, method); a method whose overloads
+/// return different types is left out
+pub(crate) fn return_types(unit: &CompilationUnit) -> HashMap<(String, String), TypeRef> {
+    fn collect(class: &ClassDeclaration, types: &mut HashMap<(String, String), Option<TypeRef>>) {
+        for member in &class.members {
+            match member {
+                ClassMember::Method(method) => {
+                    let key = (class.name.to_lowercase(), method.name.to_lowercase());
+                    let returns = &method.return_type;
+                    types
+                        .entry(key)
+                        .and_modify(|known| {
+                            let same = known.as_ref().is_some_and(|known| {
+                                known.name.eq_ignore_ascii_case(&returns.name)
+                                    && known.is_array == returns.is_array
+                            });
+                            if !same {
+                                *known = None;
+                            }
+                        })
+                        .or_insert_with(|| Some(returns.clone()));
+                }
+                ClassMember::InnerClass(inner) => collect(inner, types),
+                _ => {}
+            }
+        }
+    }
+
+    let mut types = HashMap::new();
+    for declaration in &unit.declarations {
+        if let TypeDeclaration::Class(class) = declaration {
+            collect(class, &mut types);
+        }
+    }
+    types
+        .into_iter()
+        .filter_map(|(key, returns)| Some((key, returns?)))
+        .collect()
+}
+
+/// Type name of the elements of `collection`: `Season` for `Season[]`,
+/// `List<Season>` or `Set<Season>`
+pub(crate) fn element_type_name(collection: &TypeRef) -> Option<&str> {
+    if collection.is_array {
+        return Some(&collection.name);
+    }
+    let simple = collection.name.rsplit('.').next()?;
+    match collection.type_arguments.as_slice() {
+        [element]
+            if !element.is_array
+                && (simple.eq_ignore_ascii_case("List") || simple.eq_ignore_ascii_case("Set")) =>
+        {
+            Some(&element.name)
+        }
+        _ => None,
+    }
+}
+
+/// The argument of a `String.valueOf(x)` call
+pub(crate) fn value_of_argument(call: &MethodCallExpr) -> Option<&Expression> {
+    match (&call.object, call.arguments.as_slice()) {
+        (Some(Expression::Identifier(class, _, _)), [value])
+            if class.eq_ignore_ascii_case("String")
+                && call.name.eq_ignore_ascii_case("valueOf") =>
+        {
+            Some(value)
+        }
+        _ => None,
+    }
+}
+
+/// Enum `value` belongs to, given the name of its type if known: either an
+/// enum constant (`Season.WINTER`) or a value of an enum type
+pub(crate) fn enum_of<'n>(
+    value: &Expression,
+    value_type: Option<&str>,
+    enums: &'n HashMap<String, String>,
+) -> Option<&'n str> {
+    let type_name = match (value, value_type) {
+        (_, Some(type_name)) => type_name,
+        (Expression::FieldAccess(access), _) => match &access.object {
+            Expression::Identifier(name, _, _) => name.as_str(),
+            Expression::FieldAccess(inner) => inner.field.as_str(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    // `Outer.Season` is emitted as `Season`
+    let simple = type_name.rsplit('.').next().unwrap_or(type_name);
+    enums.get(&simple.to_lowercase()).map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::NodeId;
+    use crate::parse;
+    use crate::transpile::test_support::{class_method, first_initializer};
+
+    const SOURCE: &str = "public class Calendar {
+        public enum Season { WINTER, SUMMER }
+        void m(Season s) { Object x = String.valueOf(Calendar.Season.SUMMER); }
+    }";
+
+    #[test]
+    fn test_enum_constants_and_typed_values() {
+        let unit = parse(SOURCE).unwrap();
+        let enums = enum_names(&unit);
+        assert_eq!(enums.get("season").map(String::as_str), Some("Season"));
+
+        let method = class_method(&unit, 1);
+        let Expression::MethodCall(call) = first_initializer(method) else {
+            panic!("expected method call");
+        };
+        let value = value_of_argument(call).expect("String.valueOf argument");
+        assert_eq!(enum_of(value, None, &enums), Some("Season"));
+
+        let param = &method.parameters[0];
+        let s = Expression::Identifier("s".to_string(), param.span, NodeId::DUMMY);
+        assert_eq!(
+            enum_of(&s, Some(&param.type_ref.name), &enums),
+            Some("Season")
+        );
+        assert_eq!(enum_of(&s, None, &enums), None);
+    }
+
+    #[test]
+    fn test_return_and_element_types() {
+        let unit = parse(
+            "public class Calendar {
+                Season current() { return null; }
+                Integer count() { return 0; }
+                Long count(Integer year) { return 0; }
+                void take(List<Season> seasons, Season[] all, Set<Season> seen,
+                    Map<Id, Season> byId) {}
+            }",
+        )
+        .unwrap();
+        let returns = return_types(&unit);
+        let current = &returns[&("calendar".to_string(), "current".to_string())];
+        assert_eq!(current.name, "Season");
+        assert!(!returns.contains_key(&("calendar".to_string(), "count".to_string())));
+
+        let element_types: Vec<_> = class_method(&unit, 3)
+            .parameters
+            .iter()
+            .map(|param| element_type_name(&param.type_ref))
+            .collect();
+        assert_eq!(
+            element_types,
+            [Some("Season"), Some("Season"), Some("Season"), None]
+        );
+    }
+}
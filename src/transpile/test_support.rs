@@ -0,0 +1,45 @@
+//! Helpers shared by the transpiler's unit tests
+
+use crate::ast::{
+    ClassMember, CompilationUnit, Expression, MethodCallExpr, MethodDeclaration, Statement,
+    TypeDeclaration,
+};
+use crate::parse;
+
+/// Method declared as member `index` of the first class in `unit`
+pub(crate) fn class_method(unit: &CompilationUnit, index: usize) -> &MethodDeclaration {
+    let TypeDeclaration::Class(class) = &unit.declarations[0] else {
+        panic!("expected class");
+    };
+    let ClassMember::Method(method) = &class.members[index] else {
+        panic!("expected method");
+    };
+    method
+}
+
+/// Initializer of the local variable declared by the first statement of
+/// `method`
+pub(crate) fn first_initializer(method: &MethodDeclaration) -> &Expression {
+    let Statement::LocalVariable(local) = &method.body.as_ref().unwrap().statements[0] else {
+        panic!("expected local variable");
+    };
+    local.declarators[0]
+        .initializer
+        .as_ref()
+        .expect("expected initializer")
+}
+
+/// Parse `expr` as the initializer of a local variable
+pub(crate) fn parse_expr(expr: &str) -> Expression {
+    let source = format!("class T {{ void m() {{ Object x = {}; }} }}", expr);
+    let unit = parse(&source).unwrap();
+    first_initializer(class_method(&unit, 0)).clone()
+}
+
+/// Parse `expr`, a method call, as the initializer of a local variable
+pub(crate) fn parse_call(expr: &str) -> MethodCallExpr {
+    let Expression::MethodCall(call) = parse_expr(expr) else {
+        panic!("expected method call");
+    };
+    *call
+}
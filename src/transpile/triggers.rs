@@ -0,0 +1,383 @@
+//! Splitting trigger bodies into per-event handlers
+//!
+//! Triggers often declare several events and branch on the context flags:
+//!
+//! ```apex
+//! trigger AccountTrigger on Account (before insert, before update, after insert) {
+//!     Set<String> blocked = new Set<String>{ 'Test' };
+//!     List<Account> records = Trigger.new;
+//!     if (Trigger.isBefore && Trigger.isInsert) { ... }
+//!     else if (Trigger.isBefore) { ... }
+//!     else { ... }
+//! }
+//! ```
+//!
+//! With `TranspileOptions::split_trigger_events`, a body made of local
+//! declarations followed by one if/else-if chain over `Trigger.isX` flags, or
+//! one `switch on Trigger.operationType`, becomes one exported function per
+//! handled event (`AccountTrigger_onBeforeInsert`, ...), prefixed with the
+//! trigger name so two triggers in one module don't collide. Every branch
+//! condition is evaluated
+//! for every declared event, so `Trigger.isInsert || Trigger.isUpdate` and a
+//! trailing `else` are assigned the events they actually cover. Any other
+//! body shape keeps the single handler.
+//!
+//! The leading declarations run in every handler. Those whose initializers
+//! never touch `Trigger` (directly or through an earlier declaration that
+//! does) are moved into one shared helper each handler calls first; the rest
+//! are repeated at the top of each handler, after the shared values. Moving
+//! a declaration ahead of an event-dependent one only reorders initializers
+//! that cannot observe each other.
+
+use std::collections::HashSet;
+
+use crate::ast::visitor::{walk_expression, Visitor};
+use crate::ast::{
+    BinaryOp, Block, Expression, LocalVariableDeclaration, NodeId, Statement, TriggerEvent,
+    UnaryOp, WhenValue,
+};
+use crate::lexer::Span;
+
+/// A trigger body split into per-event handlers
+#[derive(Debug)]
+pub(crate) struct TriggerSplit<'a> {
+    /// Leading declarations without event dependence, computed by the
+    /// shared helper
+    pub shared: Vec<&'a LocalVariableDeclaration>,
+    /// Leading declarations repeated at the top of every handler
+    pub duplicated: Vec<&'a LocalVariableDeclaration>,
+    /// Statements run for each handled event, in declaration order
+    pub handlers: Vec<(TriggerEvent, &'a [Statement])>,
+}
+
+/// Key of an event in the trigger metadata, e.g. `beforeInsert`
+pub(crate) fn event_key(event: TriggerEvent) -> &'static str {
+    match event {
+        TriggerEvent::BeforeInsert => "beforeInsert",
+        TriggerEvent::AfterInsert => "afterInsert",
+        TriggerEvent::BeforeUpdate => "beforeUpdate",
+        TriggerEvent::AfterUpdate => "afterUpdate",
+        TriggerEvent::BeforeDelete => "beforeDelete",
+        TriggerEvent::AfterDelete => "afterDelete",
+        TriggerEvent::AfterUndelete => "afterUndelete",
+    }
+}
+
+/// Name of the generated handler for an event of `trigger`, e.g.
+/// `AccountTrigger_onBeforeInsert`
+pub(crate) fn handler_name(trigger: &str, event: TriggerEvent) -> String {
+    let handler = match event {
+        TriggerEvent::BeforeInsert => "onBeforeInsert",
+        TriggerEvent::AfterInsert => "onAfterInsert",
+        TriggerEvent::BeforeUpdate => "onBeforeUpdate",
+        TriggerEvent::AfterUpdate => "onAfterUpdate",
+        TriggerEvent::BeforeDelete => "onBeforeDelete",
+        TriggerEvent::AfterDelete => "onAfterDelete",
+        TriggerEvent::AfterUndelete => "onAfterUndelete",
+    };
+    format!("{}_{}", trigger, handler)
+}
+
+/// Split `body` by event, or `None` when it does not have the shape
+/// described in the module docs
+pub(crate) fn split_trigger<'a>(
+    body: &'a Block,
+    events: &[TriggerEvent],
+) -> Option<TriggerSplit<'a>> {
+    let (dispatch, prefix) = body.statements.split_last()?;
+    let declarations = prefix
+        .iter()
+        .map(|stmt| match stmt {
+            Statement::LocalVariable(local) => Some(local),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let branches = match dispatch {
+        Statement::If(_) => if_chain_branches(dispatch, events)?,
+        Statement::Switch(switch) if is_trigger_property(&switch.expression, "operationType") => {
+            let mut remaining = events.to_vec();
+            let mut branches = Vec::new();
+            for clause in &switch.when_clauses {
+                let covered = match clause.values {
+                    WhenValue::Literals(ref values) => {
+                        let named = values
+                            .iter()
+                            .map(operation_event)
+                            .collect::<Option<Vec<_>>>()?;
+                        remaining
+                            .iter()
+                            .copied()
+                            .filter(|e| named.contains(e))
+                            .collect()
+                    }
+                    WhenValue::Else => remaining.clone(),
+                    WhenValue::Type { .. } => return None,
+                };
+                remaining.retain(|e| !covered.contains(e));
+                branches.push((covered, clause.block.statements.as_slice()));
+            }
+            branches
+        }
+        _ => return None,
+    };
+
+    let handlers: Vec<_> = events
+        .iter()
+        .filter_map(|event| {
+            branches
+                .iter()
+                .find(|(covered, _)| covered.contains(event))
+                .map(|(_, statements)| (*event, *statements))
+        })
+        .collect();
+    if handlers.is_empty() {
+        return None;
+    }
+
+    let (shared, duplicated) = partition_declarations(&declarations);
+    Some(TriggerSplit {
+        shared,
+        duplicated,
+        handlers,
+    })
+}
+
+/// Events covered by each branch of an if/else-if chain, in chain order
+fn if_chain_branches<'a>(
+    mut stmt: &'a Statement,
+    events: &[TriggerEvent],
+) -> Option<Vec<(Vec<TriggerEvent>, &'a [Statement])>> {
+    let mut remaining = events.to_vec();
+    let mut branches = Vec::new();
+    loop {
+        let Statement::If(if_stmt) = stmt else {
+            // Trailing else
+            branches.push((remaining, statements_of(stmt)));
+            return Some(branches);
+        };
+        let mut covered = Vec::new();
+        for event in &remaining {
+            if flag_value(&if_stmt.condition, *event)? {
+                covered.push(*event);
+            }
+        }
+        remaining.retain(|e| !covered.contains(e));
+        branches.push((covered, statements_of(&if_stmt.then_branch)));
+        match if_stmt.else_branch {
+            Some(ref else_branch) => stmt = else_branch,
+            None => return Some(branches),
+        }
+    }
+}
+
+fn statements_of(stmt: &Statement) -> &[Statement] {
+    match stmt {
+        Statement::Block(block) => &block.statements,
+        _ => std::slice::from_ref(stmt),
+    }
+}
+
+/// Value of a condition over `Trigger.isX` flags for one event, or `None`
+/// when it tests anything else
+fn flag_value(condition: &Expression, event: TriggerEvent) -> Option<bool> {
+    use TriggerEvent::*;
+    match condition {
+        Expression::FieldAccess(access) if is_trigger(&access.object) => {
+            let value = match access.field.to_lowercase().as_str() {
+                "isbefore" => matches!(event, BeforeInsert | BeforeUpdate | BeforeDelete),
+                "isafter" => !matches!(event, BeforeInsert | BeforeUpdate | BeforeDelete),
+                "isinsert" => matches!(event, BeforeInsert | AfterInsert),
+                "isupdate" => matches!(event, BeforeUpdate | AfterUpdate),
+                "isdelete" => matches!(event, BeforeDelete | AfterDelete),
+                "isundelete" => event == AfterUndelete,
+                _ => return None,
+            };
+            Some(value)
+        }
+        Expression::Binary(binary) if binary.operator == BinaryOp::And => {
+            Some(flag_value(&binary.left, event)? && flag_value(&binary.right, event)?)
+        }
+        Expression::Binary(binary) if binary.operator == BinaryOp::Or => {
+            Some(flag_value(&binary.left, event)? || flag_value(&binary.right, event)?)
+        }
+        Expression::Unary(unary) if unary.operator == UnaryOp::Not => {
+            Some(!flag_value(&unary.operand, event)?)
+        }
+        Expression::Parenthesized(inner, _, _) => flag_value(inner, event),
+        _ => None,
+    }
+}
+
+/// Event named by a `when` value of `switch on Trigger.operationType`,
+/// e.g. `BEFORE_INSERT` or `System.TriggerOperation.BEFORE_INSERT`
+fn operation_event(value: &Expression) -> Option<TriggerEvent> {
+    let name = match value {
+        Expression::Identifier(name, _, _) => name,
+        Expression::FieldAccess(access) => &access.field,
+        _ => return None,
+    };
+    let event = match name.to_uppercase().as_str() {
+        "BEFORE_INSERT" => TriggerEvent::BeforeInsert,
+        "AFTER_INSERT" => TriggerEvent::AfterInsert,
+        "BEFORE_UPDATE" => TriggerEvent::BeforeUpdate,
+        "AFTER_UPDATE" => TriggerEvent::AfterUpdate,
+        "BEFORE_DELETE" => TriggerEvent::BeforeDelete,
+        "AFTER_DELETE" => TriggerEvent::AfterDelete,
+        "AFTER_UNDELETE" => TriggerEvent::AfterUndelete,
+        _ => return None,
+    };
+    Some(event)
+}
+
+fn is_trigger(expr: &Expression) -> bool {
+    matches!(expr, Expression::Identifier(name, _, _) if name.eq_ignore_ascii_case("Trigger"))
+}
+
+fn is_trigger_property(expr: &Expression, property: &str) -> bool {
+    matches!(expr, Expression::FieldAccess(access)
+        if is_trigger(&access.object) && access.field.eq_ignore_ascii_case(property))
+}
+
+/// Split leading declarations into shared (no event dependence) and
+/// duplicated ones, each keeping source order
+fn partition_declarations<'a>(
+    declarations: &[&'a LocalVariableDeclaration],
+) -> (
+    Vec<&'a LocalVariableDeclaration>,
+    Vec<&'a LocalVariableDeclaration>,
+) {
+    /// Whether an initializer reads `Trigger` or an event-dependent local,
+    /// in a query bind too
+    struct Reads<'n> {
+        dependent_names: &'n HashSet<String>,
+        dependent: bool,
+    }
+
+    impl Reads<'_> {
+        fn read(&mut self, name: &str) {
+            self.dependent |= name.eq_ignore_ascii_case("Trigger")
+                || self.dependent_names.contains(&name.to_lowercase());
+        }
+    }
+
+    impl<'ast> Visitor<'ast> for Reads<'_> {
+        fn visit_identifier(&mut self, name: &'ast str, _: Span, _: NodeId) {
+            self.read(name);
+        }
+
+        fn visit_bind_variable(&mut self, path: &'ast str, _: Span, _: NodeId) {
+            self.read(path.split('.').next().unwrap_or_default());
+        }
+    }
+
+    let mut dependent_names: HashSet<String> = HashSet::new();
+    let mut shared = Vec::new();
+    let mut duplicated = Vec::new();
+    for declaration in declarations {
+        let mut reads = Reads {
+            dependent_names: &dependent_names,
+            dependent: false,
+        };
+        for init in declaration.declarators.iter().flat_map(|d| &d.initializer) {
+            walk_expression(&mut reads, init);
+        }
+        if reads.dependent {
+            dependent_names.extend(
+                declaration
+                    .declarators
+                    .iter()
+                    .map(|d| d.name.to_lowercase()),
+            );
+            duplicated.push(*declaration);
+        } else {
+            shared.push(*declaration);
+        }
+    }
+    (shared, duplicated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::TypeDeclaration;
+    use crate::parser::parse;
+
+    fn split_names(source: &str) -> Option<(Vec<String>, Vec<String>, Vec<TriggerEvent>)> {
+        let unit = parse(source).unwrap();
+        let TypeDeclaration::Trigger(ref trigger) = unit.declarations[0] else {
+            panic!("expected a trigger");
+        };
+        let names = |decls: &[&LocalVariableDeclaration]| {
+            decls
+                .iter()
+                .flat_map(|d| d.declarators.iter().map(|v| v.name.clone()))
+                .collect()
+        };
+        split_trigger(&trigger.body, &trigger.events).map(|split| {
+            (
+                names(&split.shared),
+                names(&split.duplicated),
+                split.handlers.iter().map(|(e, _)| *e).collect(),
+            )
+        })
+    }
+
+    #[test]
+    fn test_event_dependence_is_transitive() {
+        let split = split_names(
+            r#"
+            trigger T on Account (before insert, after update) {
+                Integer limitCount = 10;
+                List<Account> records = Trigger.new;
+                Integer total = records.size() + limitCount;
+                if (Trigger.isInsert) { } else { }
+            }
+            "#,
+        );
+
+        assert_eq!(
+            split,
+            Some((
+                vec!["limitCount".to_string()],
+                vec!["records".to_string(), "total".to_string()],
+                vec![TriggerEvent::BeforeInsert, TriggerEvent::AfterUpdate],
+            ))
+        );
+    }
+
+    #[test]
+    fn test_trigger_read_in_a_query_bind_is_event_dependent() {
+        let split = split_names(
+            r#"
+            trigger T on Account (before update, after update) {
+                List<Contact> contacts = [SELECT Id FROM Contact WHERE AccountId IN :Trigger.newMap.keySet()];
+                List<Contact> bound = [SELECT Id FROM Contact WHERE Id IN :contacts];
+                if (Trigger.isBefore) { } else { }
+            }
+            "#,
+        );
+
+        assert_eq!(
+            split,
+            Some((
+                vec![],
+                vec!["contacts".to_string(), "bound".to_string()],
+                vec![TriggerEvent::BeforeUpdate, TriggerEvent::AfterUpdate],
+            ))
+        );
+    }
+
+    #[test]
+    fn test_conditions_on_other_values_do_not_split() {
+        let split = split_names(
+            r#"
+            trigger T on Account (before insert, after insert) {
+                if (Trigger.isBefore && Trigger.new.size() > 1) { } else { }
+            }
+            "#,
+        );
+
+        assert_eq!(split, None);
+    }
+}
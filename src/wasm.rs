@@ -43,9 +43,20 @@ fn to_js_value<T: Serialize>(value: &T) -> JsValue {
     value.serialize(&serializer).unwrap_or(JsValue::NULL)
 }
 
+/// `{ line, column }` of a parse error, or null when it has no position
+fn error_position(error: &parser::ParseError) -> serde_json::Value {
+    match error.span() {
+        Some(span) if span.line > 0 => serde_json::json!({
+            "line": span.line,
+            "column": span.column,
+        }),
+        _ => serde_json::Value::Null,
+    }
+}
+
 use crate::ast::{
     Block, ClassDeclaration, ClassMember, Expression, ForInit, InterfaceDeclaration,
-    MethodDeclaration, PropertyDeclaration, SoqlQuery, Statement, TypeDeclaration,
+    MethodDeclaration, PropertyDeclaration, Statement, TypeDeclaration,
 };
 use crate::parser;
 use crate::sql::converter::{ConversionConfig, SoqlToSqlConverter};
@@ -53,7 +64,7 @@ use crate::sql::dialect::SqlDialect;
 use crate::sql::schema::{
     ChildRelationship, FieldDescribe, SObjectDescribe, SalesforceFieldType, SalesforceSchema,
 };
-use crate::transpile::{TranspileOptions, Transpiler};
+use crate::transpile::{StaticCacheStrategy, TranspileOptions, Transpiler};
 
 /// Initialize panic hook for better error messages in browser console
 #[wasm_bindgen(start)]
@@ -68,6 +79,7 @@ pub fn init() {
 /// - `ast`: the parsed AST (if successful)
 /// - `soqlQueries`: array of SOQL queries found in the code
 /// - `error`: error message (if failed)
+/// - `position`: `{ line, column }` of the error, both from 1 (if failed)
 #[wasm_bindgen(js_name = parseApex)]
 pub fn parse_apex(source: &str) -> JsValue {
     match parser::parse(source) {
@@ -87,6 +99,7 @@ pub fn parse_apex(source: &str) -> JsValue {
         Err(e) => to_js_value(&serde_json::json!({
             "success": false,
             "error": e.to_string(),
+            "position": error_position(&e),
         })),
     }
 }
@@ -94,35 +107,15 @@ pub fn parse_apex(source: &str) -> JsValue {
 /// Parse a single SOQL query and return JSON result
 #[wasm_bindgen(js_name = parseSoql)]
 pub fn parse_soql(source: &str) -> JsValue {
-    // Wrap the SOQL in a minimal Apex context to parse it
-    let apex_wrapper = format!(
-        "public class Q {{ public void q() {{ List<SObject> r = [{}]; }} }}",
-        source
-    );
-
-    match parser::parse(&apex_wrapper) {
-        Ok(unit) => {
-            // Extract the SOQL query from the parsed AST
-            let mut queries = Vec::new();
-            for decl in &unit.declarations {
-                extract_soql_from_type_declaration(decl, &mut queries);
-            }
-
-            if queries.is_empty() {
-                return to_js_value(&serde_json::json!({
-                    "success": false,
-                    "error": "No SOQL query found",
-                }));
-            }
-
-            to_js_value(&serde_json::json!({
-                "success": true,
-                "query": queries[0],
-            }))
-        }
+    match parser::parse_soql(source) {
+        Ok(query) => to_js_value(&serde_json::json!({
+            "success": true,
+            "query": format!("{:?}", query),
+        })),
         Err(e) => to_js_value(&serde_json::json!({
             "success": false,
             "error": e.to_string(),
+            "position": error_position(&e),
         })),
     }
 }
@@ -262,13 +255,14 @@ impl Default for WasmSchema {
 /// # Arguments
 /// * `soql` - The SOQL query string
 /// * `schema` - The Salesforce schema
-/// * `dialect` - Either "sqlite" or "postgres"
+/// * `dialect` - One of "sqlite", "postgres" or "mysql"
 ///
 /// # Returns
 /// JSON object with:
 /// - `success`: boolean
 /// - `sql`: the converted SQL (if successful)
 /// - `parameters`: array of parameter info objects
+/// - `debugSql`: the SQL with bind placeholders inlined, for display only
 /// - `warnings`: array of warning messages
 /// - `error`: error message (if failed)
 #[wasm_bindgen(js_name = convertSoqlToSql)]
@@ -276,39 +270,18 @@ pub fn convert_soql_to_sql(soql: &str, schema: &WasmSchema, dialect: &str) -> Js
     let sql_dialect = match dialect.to_lowercase().as_str() {
         "postgres" | "postgresql" => SqlDialect::Postgres,
         "sqlite" | "sqlite3" => SqlDialect::Sqlite,
+        "mysql" | "mariadb" => SqlDialect::Mysql,
         _ => {
             let result = serde_json::json!({
                 "success": false,
-                "error": format!("Unknown dialect '{}'. Use 'sqlite' or 'postgres'.", dialect),
+                "error": format!("Unknown dialect '{}'. Use 'sqlite', 'postgres' or 'mysql'.", dialect),
             });
             return to_js_value(&result);
         }
     };
 
-    // Parse the SOQL by wrapping in Apex
-    let apex_wrapper = format!(
-        "public class Q {{ public void q() {{ List<SObject> r = [{}]; }} }}",
-        soql
-    );
-
-    let query = match parser::parse(&apex_wrapper) {
-        Ok(unit) => {
-            // Extract the SOQL query
-            let mut queries: Vec<&SoqlQuery> = Vec::new();
-            for decl in &unit.declarations {
-                extract_soql_refs_from_type_declaration(decl, &mut queries);
-            }
-
-            if queries.is_empty() {
-                let result = serde_json::json!({
-                    "success": false,
-                    "error": "No SOQL query found in input",
-                });
-                return to_js_value(&result);
-            }
-
-            queries[0].clone()
-        }
+    let query = match parser::parse_soql(soql) {
+        Ok(query) => query,
         Err(e) => {
             let result = serde_json::json!({
                 "success": false,
@@ -344,6 +317,7 @@ pub fn convert_soql_to_sql(soql: &str, schema: &WasmSchema, dialect: &str) -> Js
                 "success": true,
                 "sql": result.sql,
                 "parameters": params,
+                "debugSql": result.to_debug_sql(),
                 "warnings": warnings,
             });
             to_js_value(&json_result)
@@ -362,7 +336,7 @@ pub fn convert_soql_to_sql(soql: &str, schema: &WasmSchema, dialect: &str) -> Js
 ///
 /// # Arguments
 /// * `schema` - The Salesforce schema
-/// * `dialect` - Either "sqlite" or "postgres"
+/// * `dialect` - One of "sqlite", "postgres" or "mysql"
 ///
 /// # Returns
 /// JSON object with:
@@ -374,10 +348,11 @@ pub fn generate_ddl(schema: &WasmSchema, dialect: &str) -> JsValue {
     let sql_dialect = match dialect.to_lowercase().as_str() {
         "postgres" | "postgresql" => SqlDialect::Postgres,
         "sqlite" | "sqlite3" => SqlDialect::Sqlite,
+        "mysql" | "mariadb" => SqlDialect::Mysql,
         _ => {
             let result = serde_json::json!({
                 "success": false,
-                "error": format!("Unknown dialect '{}'. Use 'sqlite' or 'postgres'.", dialect),
+                "error": format!("Unknown dialect '{}'. Use 'sqlite', 'postgres' or 'mysql'.", dialect),
             });
             return to_js_value(&result);
         }
@@ -408,6 +383,7 @@ pub fn generate_ddl(schema: &WasmSchema, dialect: &str) -> JsValue {
 /// - `asyncDatabase`: boolean - Generate async/await for SOQL/DML (default: true)
 /// - `includeImports`: boolean - Include runtime import statement (default: true)
 /// - `indent`: string - Indentation string (default: "  ")
+/// - `staticCacheStrategy`: "module" | "request" - Where statics loaded from SOQL/DML live (default: "module")
 ///
 /// # Returns
 /// JSON object with:
@@ -415,6 +391,7 @@ pub fn generate_ddl(schema: &WasmSchema, dialect: &str) -> JsValue {
 /// - `typescript`: the generated TypeScript/JavaScript code (if successful)
 /// - `runtimeInterface`: TypeScript interface definition for the runtime
 /// - `error`: error message (if failed)
+/// - `position`: `{ line, column }` of the error (if parsing failed)
 #[wasm_bindgen(js_name = transpileApex)]
 pub fn transpile_apex(source: &str, options: JsValue) -> JsValue {
     // Parse options if provided
@@ -436,6 +413,15 @@ pub fn transpile_apex(source: &str, options: JsValue) -> JsValue {
                 if let Some(indent) = opts_json.get("indent").and_then(|v| v.as_str()) {
                     opts.indent = indent.to_string();
                 }
+                if let Some(strategy) = opts_json
+                    .get("staticCacheStrategy")
+                    .and_then(|v| v.as_str())
+                {
+                    opts.static_cache_strategy = match strategy.to_lowercase().as_str() {
+                        "request" => StaticCacheStrategy::Request,
+                        _ => StaticCacheStrategy::Module,
+                    };
+                }
                 opts
             }
             Err(_) => TranspileOptions::default(),
@@ -449,6 +435,7 @@ pub fn transpile_apex(source: &str, options: JsValue) -> JsValue {
             return to_js_value(&serde_json::json!({
                 "success": false,
                 "error": format!("Parse error: {}", e),
+                "position": error_position(&e),
             }));
         }
     };
@@ -689,6 +676,12 @@ fn extract_soql_from_statement(stmt: &Statement, queries: &mut Vec<String>) {
         Statement::Block(block) => {
             extract_soql_from_block(block, queries);
         }
+        Statement::RunAs(run_as) => {
+            extract_soql_from_block(&run_as.body, queries);
+        }
+        Statement::Labeled(labeled) => {
+            extract_soql_from_statement(&labeled.body, queries);
+        }
         Statement::Switch(switch) => {
             extract_soql_from_expression(&switch.expression, queries);
             for when_clause in &switch.when_clauses {
@@ -701,7 +694,7 @@ fn extract_soql_from_statement(stmt: &Statement, queries: &mut Vec<String>) {
         Statement::Dml(dml) => {
             extract_soql_from_expression(&dml.expression, queries);
         }
-        Statement::Break(_) | Statement::Continue(_) | Statement::Empty(_) => {}
+        Statement::Break(_) | Statement::Continue(_) | Statement::Empty(..) => {}
     }
 }
 
@@ -787,7 +780,7 @@ fn extract_soql_from_expression(expr: &Expression, queries: &mut Vec<String>) {
         Expression::Instanceof(inst) => {
             extract_soql_from_expression(&inst.expression, queries);
         }
-        Expression::Parenthesized(inner, _) => {
+        Expression::Parenthesized(inner, _, _) => {
             extract_soql_from_expression(inner, queries);
         }
         Expression::SafeNavigation(nav) => {
@@ -797,292 +790,38 @@ fn extract_soql_from_expression(expr: &Expression, queries: &mut Vec<String>) {
             extract_soql_from_expression(&nc.left, queries);
             extract_soql_from_expression(&nc.right, queries);
         }
-        Expression::ListLiteral(items, _) | Expression::SetLiteral(items, _) => {
+        Expression::ListLiteral(items, ..) | Expression::SetLiteral(items, ..) => {
             for item in items {
                 extract_soql_from_expression(item, queries);
             }
         }
-        Expression::MapLiteral(pairs, _) => {
+        Expression::MapLiteral(pairs, ..) => {
             for (k, v) in pairs {
                 extract_soql_from_expression(k, queries);
                 extract_soql_from_expression(v, queries);
             }
         }
-        Expression::PostIncrement(e, _)
-        | Expression::PostDecrement(e, _)
-        | Expression::PreIncrement(e, _)
-        | Expression::PreDecrement(e, _) => {
+        Expression::PostIncrement(e, _, _)
+        | Expression::PostDecrement(e, _, _)
+        | Expression::PreIncrement(e, _, _)
+        | Expression::PreDecrement(e, _, _) => {
             extract_soql_from_expression(e, queries);
         }
         // Type literal (e.g., List<Account>.class)
-        Expression::TypeLiteral(_, _) => {}
+        Expression::TypeLiteral(..) => {}
         // Literals and simple expressions
-        Expression::Null(_)
-        | Expression::Boolean(_, _)
-        | Expression::Integer(_, _)
-        | Expression::Long(_, _)
-        | Expression::Double(_, _)
-        | Expression::String(_, _)
-        | Expression::Identifier(_, _)
-        | Expression::This(_)
-        | Expression::Super(_)
-        | Expression::BindVariable(_, _)
-        | Expression::Sosl(_) => {}
-    }
-}
-
-// ============================================================================
-// Helper to extract SOQL query references (not strings)
-// ============================================================================
-
-fn extract_soql_refs_from_type_declaration<'a>(
-    decl: &'a TypeDeclaration,
-    queries: &mut Vec<&'a SoqlQuery>,
-) {
-    match decl {
-        TypeDeclaration::Class(class) => {
-            extract_soql_refs_from_class(class, queries);
-        }
-        TypeDeclaration::Interface(_) => {}
-        TypeDeclaration::Trigger(trigger) => {
-            extract_soql_refs_from_block(&trigger.body, queries);
-        }
-        TypeDeclaration::Enum(_) => {}
-    }
-}
-
-fn extract_soql_refs_from_class<'a>(class: &'a ClassDeclaration, queries: &mut Vec<&'a SoqlQuery>) {
-    for member in &class.members {
-        match member {
-            ClassMember::Method(method) => {
-                if let Some(ref body) = method.body {
-                    extract_soql_refs_from_block(body, queries);
-                }
-            }
-            ClassMember::Constructor(ctor) => {
-                extract_soql_refs_from_block(&ctor.body, queries);
-            }
-            ClassMember::Property(prop) => {
-                if let Some(ref getter) = prop.getter {
-                    if let Some(ref body) = getter.body {
-                        extract_soql_refs_from_block(body, queries);
-                    }
-                }
-                if let Some(ref setter) = prop.setter {
-                    if let Some(ref body) = setter.body {
-                        extract_soql_refs_from_block(body, queries);
-                    }
-                }
-            }
-            ClassMember::StaticBlock(block) => {
-                extract_soql_refs_from_block(block, queries);
-            }
-            ClassMember::InnerClass(inner) => {
-                extract_soql_refs_from_class(inner, queries);
-            }
-            ClassMember::Field(field) => {
-                for declarator in &field.declarators {
-                    if let Some(ref init) = declarator.initializer {
-                        extract_soql_refs_from_expression(init, queries);
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
-}
-
-fn extract_soql_refs_from_block<'a>(block: &'a Block, queries: &mut Vec<&'a SoqlQuery>) {
-    for stmt in &block.statements {
-        extract_soql_refs_from_statement(stmt, queries);
-    }
-}
-
-fn extract_soql_refs_from_statement<'a>(stmt: &'a Statement, queries: &mut Vec<&'a SoqlQuery>) {
-    match stmt {
-        Statement::Expression(expr_stmt) => {
-            extract_soql_refs_from_expression(&expr_stmt.expression, queries);
-        }
-        Statement::LocalVariable(var_decl) => {
-            for declarator in &var_decl.declarators {
-                if let Some(ref init) = declarator.initializer {
-                    extract_soql_refs_from_expression(init, queries);
-                }
-            }
-        }
-        Statement::If(if_stmt) => {
-            extract_soql_refs_from_expression(&if_stmt.condition, queries);
-            extract_soql_refs_from_statement(&if_stmt.then_branch, queries);
-            if let Some(ref else_branch) = if_stmt.else_branch {
-                extract_soql_refs_from_statement(else_branch, queries);
-            }
-        }
-        Statement::For(for_stmt) => {
-            if let Some(ref init) = for_stmt.init {
-                extract_soql_refs_from_for_init(init, queries);
-            }
-            if let Some(ref condition) = for_stmt.condition {
-                extract_soql_refs_from_expression(condition, queries);
-            }
-            for update in &for_stmt.update {
-                extract_soql_refs_from_expression(update, queries);
-            }
-            extract_soql_refs_from_statement(&for_stmt.body, queries);
-        }
-        Statement::ForEach(foreach_stmt) => {
-            extract_soql_refs_from_expression(&foreach_stmt.iterable, queries);
-            extract_soql_refs_from_statement(&foreach_stmt.body, queries);
-        }
-        Statement::While(while_stmt) => {
-            extract_soql_refs_from_expression(&while_stmt.condition, queries);
-            extract_soql_refs_from_statement(&while_stmt.body, queries);
-        }
-        Statement::DoWhile(do_stmt) => {
-            extract_soql_refs_from_statement(&do_stmt.body, queries);
-            extract_soql_refs_from_expression(&do_stmt.condition, queries);
-        }
-        Statement::Return(ret) => {
-            if let Some(ref expr) = ret.value {
-                extract_soql_refs_from_expression(expr, queries);
-            }
-        }
-        Statement::Try(try_stmt) => {
-            extract_soql_refs_from_block(&try_stmt.try_block, queries);
-            for catch in &try_stmt.catch_clauses {
-                extract_soql_refs_from_block(&catch.block, queries);
-            }
-            if let Some(ref finally) = try_stmt.finally_block {
-                extract_soql_refs_from_block(finally, queries);
-            }
-        }
-        Statement::Block(block) => {
-            extract_soql_refs_from_block(block, queries);
-        }
-        Statement::Switch(switch) => {
-            extract_soql_refs_from_expression(&switch.expression, queries);
-            for when_clause in &switch.when_clauses {
-                extract_soql_refs_from_block(&when_clause.block, queries);
-            }
-        }
-        Statement::Throw(throw) => {
-            extract_soql_refs_from_expression(&throw.exception, queries);
-        }
-        Statement::Dml(dml) => {
-            extract_soql_refs_from_expression(&dml.expression, queries);
-        }
-        Statement::Break(_) | Statement::Continue(_) | Statement::Empty(_) => {}
-    }
-}
-
-fn extract_soql_refs_from_for_init<'a>(init: &'a ForInit, queries: &mut Vec<&'a SoqlQuery>) {
-    match init {
-        ForInit::Variables(var_decl) => {
-            for declarator in &var_decl.declarators {
-                if let Some(ref expr) = declarator.initializer {
-                    extract_soql_refs_from_expression(expr, queries);
-                }
-            }
-        }
-        ForInit::Expressions(exprs) => {
-            for expr in exprs {
-                extract_soql_refs_from_expression(expr, queries);
-            }
-        }
-    }
-}
-
-fn extract_soql_refs_from_expression<'a>(expr: &'a Expression, queries: &mut Vec<&'a SoqlQuery>) {
-    match expr {
-        Expression::Soql(query) => {
-            queries.push(query);
-        }
-        Expression::Binary(binary) => {
-            extract_soql_refs_from_expression(&binary.left, queries);
-            extract_soql_refs_from_expression(&binary.right, queries);
-        }
-        Expression::Unary(unary) => {
-            extract_soql_refs_from_expression(&unary.operand, queries);
-        }
-        Expression::MethodCall(call) => {
-            if let Some(ref object) = call.object {
-                extract_soql_refs_from_expression(object, queries);
-            }
-            for arg in &call.arguments {
-                extract_soql_refs_from_expression(arg, queries);
-            }
-        }
-        Expression::FieldAccess(access) => {
-            extract_soql_refs_from_expression(&access.object, queries);
-        }
-        Expression::ArrayAccess(arr) => {
-            extract_soql_refs_from_expression(&arr.array, queries);
-            extract_soql_refs_from_expression(&arr.index, queries);
-        }
-        Expression::Ternary(cond) => {
-            extract_soql_refs_from_expression(&cond.condition, queries);
-            extract_soql_refs_from_expression(&cond.then_expr, queries);
-            extract_soql_refs_from_expression(&cond.else_expr, queries);
-        }
-        Expression::Assignment(assign) => {
-            extract_soql_refs_from_expression(&assign.target, queries);
-            extract_soql_refs_from_expression(&assign.value, queries);
-        }
-        Expression::New(new_obj) => {
-            for arg in &new_obj.arguments {
-                extract_soql_refs_from_expression(arg, queries);
-            }
-        }
-        Expression::NewArray(new_arr) => {
-            if let Some(ref size) = new_arr.size {
-                extract_soql_refs_from_expression(size, queries);
-            }
-            if let Some(ref init) = new_arr.initializer {
-                for item in init {
-                    extract_soql_refs_from_expression(item, queries);
-                }
-            }
-        }
-        Expression::NewMap(new_map) => {
-            if let Some(ref init) = new_map.initializer {
-                for (k, v) in init {
-                    extract_soql_refs_from_expression(k, queries);
-                    extract_soql_refs_from_expression(v, queries);
-                }
-            }
-        }
-        Expression::Cast(cast) => {
-            extract_soql_refs_from_expression(&cast.expression, queries);
-        }
-        Expression::Instanceof(inst) => {
-            extract_soql_refs_from_expression(&inst.expression, queries);
-        }
-        Expression::Parenthesized(inner, _) => {
-            extract_soql_refs_from_expression(inner, queries);
-        }
-        Expression::SafeNavigation(nav) => {
-            extract_soql_refs_from_expression(&nav.object, queries);
-        }
-        Expression::NullCoalesce(nc) => {
-            extract_soql_refs_from_expression(&nc.left, queries);
-            extract_soql_refs_from_expression(&nc.right, queries);
-        }
-        Expression::ListLiteral(items, _) | Expression::SetLiteral(items, _) => {
-            for item in items {
-                extract_soql_refs_from_expression(item, queries);
-            }
-        }
-        Expression::MapLiteral(pairs, _) => {
-            for (k, v) in pairs {
-                extract_soql_refs_from_expression(k, queries);
-                extract_soql_refs_from_expression(v, queries);
-            }
-        }
-        Expression::PostIncrement(e, _)
-        | Expression::PostDecrement(e, _)
-        | Expression::PreIncrement(e, _)
-        | Expression::PreDecrement(e, _) => {
-            extract_soql_refs_from_expression(e, queries);
-        }
-        _ => {}
+        Expression::Null(..)
+        | Expression::Boolean(..)
+        | Expression::Integer(..)
+        | Expression::Long(..)
+        | Expression::Double(..)
+        | Expression::String(..)
+        | Expression::Identifier(..)
+        | Expression::This(..)
+        | Expression::Super(..)
+        | Expression::BindVariable(..)
+        | Expression::BindExpression(..)
+        | Expression::Sosl(_)
+        | Expression::SoqlSubquery(_) => {}
     }
 }
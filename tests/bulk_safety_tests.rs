@@ -0,0 +1,164 @@
+//! Tests for queries and DML run once per loop iteration
+
+use apexrust::analysis::bulk_safety::{
+    find_loop_operations, BulkSafetyDiagnostic, BulkSafetyOptions, LoopOperation,
+};
+use apexrust::parse;
+
+fn diagnostics(source: &str, call_depth: usize) -> Vec<BulkSafetyDiagnostic> {
+    let unit = parse(source).expect("Parse failed");
+    find_loop_operations(&unit, &BulkSafetyOptions { call_depth })
+}
+
+const HELPERS: &str = r#"
+    public class ContactService {
+        public void run(List<Contact> contacts) {
+            for (Contact c : contacts) {
+                enrich(c);
+                ContactService.audit(c);
+            }
+            while (contacts.isEmpty()) {
+                insert contacts;
+            }
+        }
+
+        private void enrich(Contact c) {
+            Account acc = [SELECT Id FROM Account WHERE Id = :c.AccountId];
+        }
+
+        public static void audit(Contact c) {
+            log(c);
+        }
+
+        private static void log(Contact c) {
+            Database.insert(new Task(WhoId = c.Id));
+        }
+    }
+"#;
+
+#[test]
+fn test_calls_followed_to_configured_depth() {
+    let direct = diagnostics(HELPERS, 0);
+    assert_eq!(direct.len(), 1);
+    assert_eq!(direct[0].operation, LoopOperation::Dml);
+    assert!(direct[0].call_chain.is_empty());
+    assert_eq!(direct[0].method, "run");
+
+    let one_deep = diagnostics(HELPERS, 1);
+    let chains: Vec<&[String]> = one_deep.iter().map(|d| &d.call_chain[..]).collect();
+    assert_eq!(chains, [&["enrich".to_string()][..], &[]]);
+    assert_eq!(one_deep[0].operation, LoopOperation::Query);
+    assert_eq!(
+        one_deep[0].message,
+        "call to enrich() inside a loop runs a query once per iteration"
+    );
+    assert!(one_deep[0].suggestion.is_none());
+
+    let two_deep = diagnostics(HELPERS, 2);
+    assert_eq!(two_deep.len(), 3);
+    assert_eq!(two_deep[1].call_chain, ["audit", "log"]);
+    assert_eq!(two_deep[1].operation, LoopOperation::Dml);
+    assert_eq!(
+        two_deep[1].message,
+        "call to audit() -> log() inside a loop runs a DML statement once per iteration"
+    );
+}
+
+#[test]
+fn test_suggests_collected_keys_query() {
+    let found = diagnostics(
+        r#"
+        trigger ContactTrigger on Contact (before insert) {
+            for (Contact c : Trigger.new) {
+                Account acc = [SELECT Id, Name FROM Account WHERE Id = :c.AccountId];
+                c.Description = acc.Name;
+            }
+        }
+        "#,
+        1,
+    );
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].message,
+        "query inside a loop runs once per iteration"
+    );
+    let suggestion = found[0].suggestion.as_ref().expect("suggestion");
+
+    assert_eq!(
+        suggestion.collect,
+        "Set<Id> accountIds = new Set<Id>();\n\
+         for (Contact c : Trigger.new) {\n    accountIds.add(c.AccountId);\n}"
+    );
+    assert_eq!(
+        suggestion.query,
+        "[SELECT Id, Name FROM Account WHERE Id IN :accountIds]"
+    );
+    assert_eq!(
+        suggestion.index,
+        "Map<Id, Account> accountsById = new Map<Id, Account>(\
+         [SELECT Id, Name FROM Account WHERE Id IN :accountIds]);"
+    );
+    assert_eq!(suggestion.lookup, "accountsById.get(c.AccountId)");
+    assert_snippets_parse(&suggestion.collect, &suggestion.index);
+}
+
+#[test]
+fn test_suggestion_groups_by_non_id_field() {
+    let found = diagnostics(
+        r#"
+        public class Matcher {
+            public void run(List<Lead> leads) {
+                for (Lead l : leads) {
+                    List<Contact> matches = [SELECT Id FROM Contact
+                        WHERE Email = :l.Email AND IsDeleted = false];
+                }
+            }
+        }
+        "#,
+        1,
+    );
+    let suggestion = found[0].suggestion.as_ref().expect("suggestion");
+    assert_eq!(suggestion.key_set, "emails");
+    assert_eq!(suggestion.result_map, "contactsByEmail");
+    assert_eq!(
+        suggestion.query,
+        "[SELECT Id, Email FROM Contact WHERE Email IN :emails AND IsDeleted = false]"
+    );
+    assert!(suggestion
+        .index
+        .starts_with("Map<String, List<Contact>> contactsByEmail"));
+    assert_snippets_parse(&suggestion.collect, &suggestion.index);
+}
+
+#[test]
+fn test_no_suggestion_for_other_patterns() {
+    let found = diagnostics(
+        r#"
+        public class Loops {
+            public void run(List<Contact> contacts, Integer n) {
+                for (Contact c : contacts) {
+                    Account primary = [SELECT Id FROM Account WHERE Id = :c.AccountId LIMIT 1];
+                    List<Account> named = [SELECT Id FROM Account
+                        WHERE Id = :c.AccountId OR Name = :c.LastName];
+                }
+                for (Integer i = 0; i < n; i++) {
+                    Account acc = [SELECT Id FROM Account WHERE NumberOfEmployees = :i];
+                }
+                for (Account acc : [SELECT Id FROM Account]) { }
+            }
+        }
+        "#,
+        1,
+    );
+    assert_eq!(found.len(), 3);
+    assert!(found.iter().all(|d| d.suggestion.is_none()));
+}
+
+/// The suggested statements form a valid method body
+fn assert_snippets_parse(collect: &str, index: &str) {
+    let source = format!(
+        "public class Snippet {{ void run(List<SObject> records) {{\n{}\n{}\n}} }}",
+        collect, index
+    );
+    parse(&source).unwrap_or_else(|e| panic!("{}\n{}", e, source));
+}
@@ -0,0 +1,127 @@
+//! Tests for cooperative cancellation of parse, convert and transpile
+
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+use apexrust::sql::{ConversionConfig, ConversionError, SoqlToSqlConverter};
+use apexrust::transpile::{TranspileError, Transpiler};
+use apexrust::{
+    parse, CancellationToken, ClassMember, Expression, ParseError, Parser, SoqlQuery, Statement,
+    TypeDeclaration,
+};
+
+/// A class of `methods` methods, each with `statements` assignments
+fn generated_source(methods: usize, statements: usize) -> String {
+    let mut source = String::from("public class Generated {\n");
+    for m in 0..methods {
+        source.push_str(&format!("    public void run{}() {{\n", m));
+        source.push_str("        Integer total = 0;\n");
+        for i in 0..statements {
+            source.push_str(&format!("        total = total + {};\n", i));
+        }
+        source.push_str("    }\n");
+    }
+    source.push_str("}\n");
+    source
+}
+
+#[test]
+fn test_parse_cancelled_from_another_thread() {
+    let source = generated_source(2, 10);
+
+    let token = CancellationToken::new();
+    let ready = Arc::new(Barrier::new(2));
+    let worker = {
+        let token = token.clone();
+        let ready = Arc::clone(&ready);
+        thread::spawn(move || {
+            let mut parser = Parser::new(&source).with_cancellation(token);
+            // Parse only once the main thread has cancelled
+            ready.wait();
+            parser.parse()
+        })
+    };
+
+    token.cancel();
+    ready.wait();
+    let result = worker.join().unwrap();
+
+    assert!(
+        matches!(result, Err(ParseError::Cancelled(_))),
+        "{:?}",
+        result.map(|_| ())
+    );
+}
+
+#[test]
+fn test_parse_timeout() {
+    let source = generated_source(2, 10);
+
+    let err = Parser::new(&source)
+        .with_timeout(Duration::ZERO)
+        .parse()
+        .unwrap_err();
+    assert!(matches!(err, ParseError::Cancelled(_)));
+
+    let unit = Parser::new(&source)
+        .with_timeout(Duration::from_secs(3600))
+        .parse()
+        .unwrap();
+    assert_eq!(unit, parse(&source).unwrap());
+}
+
+/// The SOQL query initializing the first local of the first method
+fn extract_soql(source: &str) -> SoqlQuery {
+    let full_source = format!(
+        "class Test {{ void test() {{ List<SObject> x = [{}]; }} }}",
+        source
+    );
+    let cu = parse(&full_source).expect("Parse failed");
+    if let TypeDeclaration::Class(class) = &cu.declarations[0] {
+        if let ClassMember::Method(method) = &class.members[0] {
+            if let Some(block) = &method.body {
+                if let Statement::LocalVariable(lv) = &block.statements[0] {
+                    if let Some(Expression::Soql(soql)) = &lv.declarators[0].initializer {
+                        return (**soql).clone();
+                    }
+                }
+            }
+        }
+    }
+    panic!("Could not extract SOQL query");
+}
+
+#[test]
+fn test_convert_cancelled() {
+    let soql = extract_soql("SELECT Id FROM Account");
+    let token = CancellationToken::new();
+    let mut converter = SoqlToSqlConverter::new_without_schema(ConversionConfig::default())
+        .with_cancellation(token.clone());
+    assert!(converter.convert(&soql).is_ok());
+
+    token.cancel();
+    assert_eq!(
+        converter.convert(&soql).unwrap_err(),
+        ConversionError::Cancelled
+    );
+}
+
+#[test]
+fn test_transpile_cancelled() {
+    let unit = parse(&generated_source(3, 10)).unwrap();
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let err = Transpiler::new()
+        .with_cancellation(token)
+        .transpile(&unit)
+        .unwrap_err();
+    assert!(matches!(err, TranspileError::Cancelled));
+
+    let ts = Transpiler::new()
+        .with_timeout(Duration::from_secs(3600))
+        .transpile(&unit)
+        .unwrap();
+    assert!(ts.contains("class Generated"));
+}
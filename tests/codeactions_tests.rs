@@ -0,0 +1,216 @@
+//! Tests for quick fixes offered for diagnostics
+
+use apexrust::analysis::bulk_safety::{find_loop_operations, BulkSafetyOptions};
+use apexrust::analysis::field_usage::check_field_usage;
+use apexrust::codeactions::{
+    apply_edits, ActionContext, ActionProvider, CodeAction, CodeActions, Diagnostic, TextEdit,
+    BULK_SAFETY,
+};
+use apexrust::parse;
+use apexrust::sql::{FieldDescribe, SObjectDescribe, SalesforceFieldType, SalesforceSchema};
+
+const LOOP_QUERY: &str = r#"public class ContactService {
+    public void enrich(List<Contact> contacts) {
+        for (Contact c : contacts) {
+            Account acc = [SELECT Id, Name FROM Account WHERE Id = :c.AccountId];
+            c.Description = acc.Name;
+        }
+    }
+}
+"#;
+
+const UNSELECTED: &str = r#"public class Greeter {
+	public String greet(Id accountId) {
+		Account acc = [SELECT Id FROM Account WHERE Id = :accountId];
+		String name = acc.Owner.Name;
+		System.debug(acc.Name);
+		return name;
+	}
+}
+"#;
+
+fn schema() -> SalesforceSchema {
+    let mut schema = SalesforceSchema::new();
+    let mut account = SObjectDescribe::new("Account");
+    account.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id));
+    account.add_field(FieldDescribe::new("Name", SalesforceFieldType::String));
+    account.add_field(
+        FieldDescribe::new("OwnerId", SalesforceFieldType::Lookup)
+            .with_reference("User")
+            .with_relationship_name("Owner"),
+    );
+    schema.add_object(account);
+    let mut user = SObjectDescribe::new("User");
+    user.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id));
+    user.add_field(FieldDescribe::new("Name", SalesforceFieldType::String));
+    schema.add_object(user);
+    schema
+}
+
+/// The fixed source for the action titled `title`
+fn fix(source: &str, diagnostic: &Diagnostic, title: &str) -> String {
+    let unit = parse(source).expect("Parse failed");
+    let actions = CodeActions::default().actions(source, &unit, diagnostic);
+    let action = actions
+        .iter()
+        .find(|a| a.title == title)
+        .unwrap_or_else(|| panic!("no {:?} among {:?}", title, actions));
+    let fixed = apply_edits(source, &action.edits);
+    parse(&fixed).expect("fixed source parses");
+    fixed
+}
+
+fn unselected_diagnostics(source: &str) -> Vec<Diagnostic> {
+    let unit = parse(source).expect("Parse failed");
+    check_field_usage(&unit, &schema())
+        .iter()
+        .map(Diagnostic::from)
+        .collect()
+}
+
+#[test]
+fn test_wrap_in_try_catch() {
+    let unit = parse(LOOP_QUERY).unwrap();
+    let diagnostic =
+        Diagnostic::from(&find_loop_operations(&unit, &BulkSafetyOptions::default())[0]);
+
+    let fixed = fix(LOOP_QUERY, &diagnostic, "Wrap in try-catch");
+    assert!(fixed.contains(
+        "        for (Contact c : contacts) {
+            try {
+                Account acc = [SELECT Id, Name FROM Account WHERE Id = :c.AccountId];
+            } catch (Exception e) {
+                System.debug(LoggingLevel.ERROR, e.getMessage());
+            }
+            c.Description = acc.Name;"
+    ));
+}
+
+#[test]
+fn test_try_catch_avoids_names_in_use() {
+    let source = "public class A {\n  void run(List<Log__c> e) {\n    for (Log__c log : e) {\n      insert log;\n    }\n  }\n}\n";
+    let unit = parse(source).unwrap();
+    let diagnostic =
+        Diagnostic::from(&find_loop_operations(&unit, &BulkSafetyOptions::default())[0]);
+
+    let fixed = fix(source, &diagnostic, "Wrap in try-catch");
+    assert!(
+        fixed.contains("      } catch (Exception e1) {\n        System.debug"),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn test_null_guard_splits_declaration() {
+    let diagnostics = unselected_diagnostics(UNSELECTED);
+    assert_eq!(diagnostics.len(), 2);
+
+    let fixed = fix(UNSELECTED, &diagnostics[0], "Add null check");
+    assert!(fixed.contains(
+        "\t\tString name;
+\t\tif (acc != null && acc.Owner != null) {
+\t\t\tname = acc.Owner.Name;
+\t\t}
+\t\tSystem.debug(acc.Name);"
+    ));
+
+    let fixed = fix(UNSELECTED, &diagnostics[1], "Add null check");
+    assert!(fixed.contains(
+        "\t\tif (acc != null) {
+\t\t\tSystem.debug(acc.Name);
+\t\t}"
+    ));
+}
+
+#[test]
+fn test_bulkify_query_in_loop() {
+    let unit = parse(LOOP_QUERY).unwrap();
+    let diagnostic =
+        Diagnostic::from(&find_loop_operations(&unit, &BulkSafetyOptions::default())[0]);
+
+    let fixed = fix(LOOP_QUERY, &diagnostic, "Query once before the loop");
+    assert_eq!(
+        fixed,
+        r#"public class ContactService {
+    public void enrich(List<Contact> contacts) {
+        Set<Id> accountIds = new Set<Id>();
+        for (Contact c : contacts) {
+            accountIds.add(c.AccountId);
+        }
+        Map<Id, Account> accountsById = new Map<Id, Account>([SELECT Id, Name FROM Account WHERE Id IN :accountIds]);
+        for (Contact c : contacts) {
+            Account acc = accountsById.get(c.AccountId);
+            c.Description = acc.Name;
+        }
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn test_actions_for_rule_come_first() {
+    let unit = parse(LOOP_QUERY).unwrap();
+    let diagnostic =
+        Diagnostic::from(&find_loop_operations(&unit, &BulkSafetyOptions::default())[0]);
+
+    let titles: Vec<String> = CodeActions::default()
+        .actions(LOOP_QUERY, &unit, &diagnostic)
+        .into_iter()
+        .map(|a| a.title)
+        .collect();
+    assert_eq!(titles, ["Query once before the loop", "Wrap in try-catch"]);
+}
+
+struct Broken;
+
+impl ActionProvider for Broken {
+    fn title(&self) -> String {
+        "Delete the opening brace".to_string()
+    }
+
+    fn edits(&self, context: &ActionContext<'_>) -> Option<Vec<TextEdit>> {
+        let brace = context.source.find('{')?;
+        Some(vec![TextEdit {
+            range: brace..brace + 1,
+            new_text: String::new(),
+        }])
+    }
+}
+
+struct Overlapping;
+
+impl ActionProvider for Overlapping {
+    fn title(&self) -> String {
+        "Overlap".to_string()
+    }
+
+    fn edits(&self, _: &ActionContext<'_>) -> Option<Vec<TextEdit>> {
+        Some(vec![
+            TextEdit {
+                range: 0..10,
+                new_text: "public class".to_string(),
+            },
+            TextEdit {
+                range: 5..12,
+                new_text: String::new(),
+            },
+        ])
+    }
+}
+
+#[test]
+fn test_registered_actions_are_validated() {
+    let unit = parse(LOOP_QUERY).unwrap();
+    let diagnostic =
+        Diagnostic::from(&find_loop_operations(&unit, &BulkSafetyOptions::default())[0]);
+
+    let mut actions = CodeActions::new();
+    actions.register(BULK_SAFETY, Broken);
+    actions.register(BULK_SAFETY, Overlapping);
+    assert_eq!(
+        actions.actions(LOOP_QUERY, &unit, &diagnostic),
+        Vec::<CodeAction>::new()
+    );
+}
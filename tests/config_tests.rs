@@ -0,0 +1,304 @@
+//! Tests for apexion.toml project configuration
+
+use std::fs;
+use std::path::PathBuf;
+
+use apexrust::cancel::CancellationToken;
+use apexrust::config::{parse_project, Config, ConfigError, ConfigWarning};
+use apexrust::sql::{
+    BindVariableMode, CollationMode, IdComparison, RecordTypeStrategy, SqlDialect,
+};
+use apexrust::transpile::StaticCacheStrategy;
+
+const PROJECT_CONFIG: &str = r#"
+[parser]
+recovery = true
+
+[lint.dynamic-soql]
+enabled = false
+
+[sql]
+dialect = "sqlite"
+filter_deleted = false
+schema = "org"
+
+[sql.schema_overrides]
+Account = "crm"
+
+[transpile]
+typescript = true
+static_cache = "request"
+
+[transpile.env_flags]
+"System.isBatch" = false
+
+[files]
+include = ["src/**/*.cls"]
+exclude = ["**/*Test.cls"]
+"#;
+
+/// Fresh empty directory under the system temp dir
+fn temp_project(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("apexion-config-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    root
+}
+
+#[test]
+fn test_config_file_settings() {
+    let (config, warnings) = Config::from_toml_str(PROJECT_CONFIG).unwrap();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+
+    assert_eq!(config.parser.recovery, Some(true));
+    assert!(!config.lint_enabled("dynamic-soql"));
+    assert!(config.lint_enabled("todo"));
+
+    let conversion = config.conversion_config();
+    assert_eq!(conversion.dialect, SqlDialect::Sqlite);
+    assert!(!conversion.filter_deleted);
+    assert_eq!(conversion.schema_qualifier.as_deref(), Some("org"));
+    assert_eq!(
+        conversion
+            .schema_overrides
+            .get("Account")
+            .map(String::as_str),
+        Some("crm")
+    );
+
+    let options = config.transpile_options();
+    assert!(options.typescript);
+    assert_eq!(options.static_cache_strategy, StaticCacheStrategy::Request);
+    assert_eq!(options.env_flags.get("System.isBatch"), Some(&false));
+}
+
+#[test]
+fn test_layering_file_then_env_then_cli() {
+    let (file, _) = Config::from_toml_str(PROJECT_CONFIG).unwrap();
+    let env = Config::from_env_vars([
+        ("APEXION_SQL_DIALECT", "postgres"),
+        ("APEXION_TRANSPILE_TYPESCRIPT", "false"),
+        ("PATH", "/usr/bin"),
+    ])
+    .unwrap();
+    let (cli, _) = Config::from_toml_str("[transpile]\ntypescript = true\n").unwrap();
+
+    let config = file.merge(env).merge(cli);
+
+    // env beats the file
+    assert_eq!(config.sql.dialect, Some(SqlDialect::Postgres));
+    // the CLI beats env
+    assert_eq!(config.transpile.typescript, Some(true));
+    // settings no later layer mentions survive
+    assert_eq!(config.sql.schema.as_deref(), Some("org"));
+    assert_eq!(config.sql.filter_deleted, Some(false));
+    assert!(!config.lint_enabled("dynamic-soql"));
+}
+
+#[test]
+fn test_invalid_env_value_names_variable() {
+    let error = Config::from_env_vars([("APEXION_SQL_DIALECT", "oracle")]).unwrap_err();
+    assert!(
+        matches!(error, ConfigError::InvalidEnv { ref variable, .. } if variable == "APEXION_SQL_DIALECT"),
+        "{:?}",
+        error
+    );
+}
+
+#[test]
+fn test_unknown_keys_are_warnings() {
+    let source = r#"
+[sql]
+dialect = "postgres"
+dialetc = "sqlite"
+
+[lint.no-such-rule]
+enabled = true
+
+[formatter]
+width = 100
+"#;
+    let (config, warnings) = Config::from_toml_str(source).unwrap();
+
+    assert_eq!(config.sql.dialect, Some(SqlDialect::Postgres));
+    assert_eq!(
+        warnings,
+        vec![
+            ConfigWarning::UnknownKey("formatter".to_string()),
+            ConfigWarning::UnknownLintRule("no-such-rule".to_string()),
+            ConfigWarning::UnknownKey("sql.dialetc".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_value_is_an_error() {
+    let error = Config::from_toml_str("[sql]\ndialect = \"oracle\"\n").unwrap_err();
+    assert!(matches!(error, ConfigError::Invalid(_)), "{:?}", error);
+}
+
+#[test]
+fn test_bind_mode_setting() {
+    let (config, warnings) = Config::from_toml_str("[sql]\nbind_mode = \"placeholder\"\n").unwrap();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    assert_eq!(
+        config.conversion_config().bind_mode,
+        BindVariableMode::Placeholder
+    );
+
+    let env = Config::from_env_vars([("APEXION_SQL_BIND_MODE", "parameterized")]).unwrap();
+    let merged = config.merge(env);
+    assert_eq!(
+        merged.conversion_config().bind_mode,
+        BindVariableMode::Parameterized
+    );
+}
+
+#[test]
+fn test_string_collation_setting() {
+    let (config, warnings) =
+        Config::from_toml_str("[sql]\nstring_collation = { explicit = \"und-x-icu\" }\n").unwrap();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    assert_eq!(
+        config.conversion_config().string_collation,
+        CollationMode::Explicit("und-x-icu".to_string())
+    );
+
+    let env =
+        Config::from_env_vars([("APEXION_SQL_STRING_COLLATION", "case-insensitive")]).unwrap();
+    let merged = config.merge(env);
+    assert_eq!(
+        merged.conversion_config().string_collation,
+        CollationMode::CaseInsensitive
+    );
+}
+
+#[test]
+fn test_record_type_strategy_setting() {
+    let source = r#"
+[sql.record_type_strategy.denormalized-column]
+column = "record_type_name"
+"#;
+    let (config, warnings) = Config::from_toml_str(source).unwrap();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    assert_eq!(
+        config.conversion_config().record_type_strategy,
+        RecordTypeStrategy::DenormalizedColumn {
+            column: "record_type_name".to_string()
+        }
+    );
+
+    let (join, _) = Config::from_toml_str("[sql]\nrecord_type_strategy = \"join\"\n").unwrap();
+    let merged = config.merge(join);
+    assert_eq!(
+        merged.conversion_config().record_type_strategy,
+        RecordTypeStrategy::Join
+    );
+}
+
+#[test]
+fn test_id_comparison_setting() {
+    let (config, warnings) =
+        Config::from_toml_str("[sql]\nid_comparison = \"normalize-to-18\"\n").unwrap();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    assert_eq!(
+        config.conversion_config().id_comparison,
+        IdComparison::NormalizeTo18
+    );
+
+    let env = Config::from_env_vars([("APEXION_SQL_ID_COMPARISON", "case-insensitive")]).unwrap();
+    let merged = config.merge(env);
+    assert_eq!(
+        merged.conversion_config().id_comparison,
+        IdComparison::CaseInsensitive
+    );
+}
+
+#[test]
+fn test_file_globs_select_parsed_files() {
+    let root = temp_project("globs");
+    fs::create_dir_all(root.join("src/classes")).unwrap();
+    fs::create_dir_all(root.join("scripts")).unwrap();
+    fs::write(root.join("apexion.toml"), PROJECT_CONFIG).unwrap();
+    fs::write(
+        root.join("src/classes/AccountService.cls"),
+        "public class AccountService { }",
+    )
+    .unwrap();
+    fs::write(
+        root.join("src/classes/AccountServiceTest.cls"),
+        "@isTest private class AccountServiceTest { }",
+    )
+    .unwrap();
+    fs::write(root.join("scripts/Setup.cls"), "public class Setup { }").unwrap();
+
+    let files = parse_project(&root, None, None).unwrap();
+    let names: Vec<_> = files
+        .iter()
+        .map(|f| f.path.strip_prefix(&root).unwrap().to_path_buf())
+        .collect();
+    assert_eq!(names, vec![PathBuf::from("src/classes/AccountService.cls")]);
+    assert!(files[0].errors.is_empty());
+
+    // Without a configuration every class is selected
+    let all = Config::default().select_files(&root).unwrap();
+    assert_eq!(all.len(), 3);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_loop_is_not_followed() {
+    let root = temp_project("symlink");
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/A.cls"), "public class A { }").unwrap();
+    std::os::unix::fs::symlink("..", root.join("src/link")).unwrap();
+
+    let files = Config::default().select_files(&root).unwrap();
+    assert_eq!(files, vec![root.join("src/A.cls")]);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_recovery_reports_every_syntax_error() {
+    let root = temp_project("recovery");
+    fs::write(
+        root.join("Broken.cls"),
+        "public class Broken {
+            public void first() { Integer x = ; }
+            public void second() { return 1 +; }
+            public void fine() { }
+        }",
+    )
+    .unwrap();
+
+    let strict = parse_project(&root, Some(&Config::default()), None).unwrap();
+    assert_eq!(strict[0].errors.len(), 1);
+    assert!(strict[0].unit.declarations.is_empty());
+
+    let (config, _) = Config::from_toml_str("[parser]\nrecovery = true\n").unwrap();
+    let recovered = parse_project(&root, Some(&config), None).unwrap();
+    assert_eq!(recovered[0].errors.len(), 2, "{:?}", recovered[0].errors);
+    assert_eq!(recovered[0].unit.declarations.len(), 1);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_parse_project_stops_when_cancelled() {
+    let root = temp_project("cancel");
+    fs::write(root.join("First.cls"), "public class First { }").unwrap();
+    fs::write(root.join("Second.cls"), "public class Second { }").unwrap();
+
+    let token = CancellationToken::new();
+    let files = parse_project(&root, Some(&Config::default()), Some(&token)).unwrap();
+    assert_eq!(files.len(), 2);
+
+    token.cancel();
+    let result = parse_project(&root, Some(&Config::default()), Some(&token));
+    assert!(matches!(result, Err(ConfigError::Cancelled)));
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,176 @@
+//! AST shapes for constructs that failed to parse in a large anonymized org
+//! export; the fixture is `tests/apex_files/CorpusShapes.cls`
+
+use apexrust::{
+    parse, Block, ClassMember, DmlOperation, Expression, SelectField, Statement, TypeDeclaration,
+};
+
+const SOURCE: &str = include_str!("apex_files/CorpusShapes.cls");
+
+/// The body of a method in the fixture class
+fn method_body(name: &str) -> Block {
+    let unit = parse(SOURCE).expect("Parse failed");
+    let TypeDeclaration::Class(class) = &unit.declarations[0] else {
+        panic!("expected a class");
+    };
+    class
+        .members
+        .iter()
+        .find_map(|member| match member {
+            ClassMember::Method(method) if method.name == name => method.body.clone(),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no method {}", name))
+}
+
+fn returned(body: &Block) -> &Expression {
+    match body.statements.last() {
+        Some(Statement::Return(ret)) => ret.value.as_ref().expect("return value"),
+        other => panic!("expected return, got {:?}", other),
+    }
+}
+
+/// `Account.SObjectType.fields.OwnerId` -> "Account.SObjectType.fields.OwnerId"
+fn dotted(expr: &Expression) -> String {
+    match expr {
+        Expression::Identifier(name, _, _) => name.clone(),
+        Expression::FieldAccess(access) => format!("{}.{}", dotted(&access.object), access.field),
+        other => panic!("expected a field path, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_map_literal_of_field_tokens() {
+    let body = method_body("fieldMap");
+    let Expression::NewMap(map) = returned(&body) else {
+        panic!("expected a map literal");
+    };
+    assert_eq!(map.type_ref.type_arguments[1].name, "Schema.SObjectField");
+
+    let entries: Vec<(String, String)> = map
+        .initializer
+        .as_ref()
+        .expect("initializer")
+        .iter()
+        .map(|(key, value)| match key {
+            Expression::String(key, _, _) => (key.clone(), dotted(value)),
+            other => panic!("expected a string key, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("name".to_string(), "Account.Name".to_string()),
+            (
+                "industry".to_string(),
+                "Schema.Account.Industry".to_string()
+            ),
+            (
+                "owner".to_string(),
+                "Account.SObjectType.fields.OwnerId".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_calls_chained_on_new() {
+    let body = method_body("chainedOnNew");
+
+    // new DescribeCache().load()[0]
+    let Statement::LocalVariable(local) = &body.statements[0] else {
+        panic!("expected a declaration");
+    };
+    let Some(Expression::ArrayAccess(access)) = &local.declarators[0].initializer else {
+        panic!("expected an index");
+    };
+    let Expression::MethodCall(load) = &access.array else {
+        panic!("expected a call");
+    };
+    assert_eq!(load.name, "load");
+    assert!(
+        matches!(&load.object, Some(Expression::New(new)) if new.type_ref.name == "DescribeCache")
+    );
+
+    // new DescribeCache<Account>().load()[0].refresh();
+    let Statement::Expression(stmt) = &body.statements[1] else {
+        panic!("expected an expression statement");
+    };
+    let Expression::MethodCall(refresh) = &stmt.expression else {
+        panic!("expected a call");
+    };
+    assert_eq!(refresh.name, "refresh");
+    assert!(matches!(&refresh.object, Some(Expression::ArrayAccess(_))));
+
+    // new Map<String, List<Integer>>().get('a')[0]
+    let Expression::ArrayAccess(access) = returned(&body) else {
+        panic!("expected an index");
+    };
+    let Expression::MethodCall(get) = &access.array else {
+        panic!("expected a call");
+    };
+    let Some(Expression::New(new)) = &get.object else {
+        panic!("expected a constructor");
+    };
+    assert_eq!(new.type_ref.type_arguments[1].name, "List");
+    assert_eq!(
+        new.type_ref.type_arguments[1].type_arguments[0].name,
+        "Integer"
+    );
+}
+
+#[test]
+fn test_nested_run_as_with_finally() {
+    let body = method_body("nestedRunAs");
+    let Statement::RunAs(outer) = &body.statements[1] else {
+        panic!("expected runAs, got {:?}", body.statements[1]);
+    };
+    assert!(matches!(&outer.user, Expression::Identifier(name, _, _) if name == "admin"));
+
+    let Statement::Try(outer_try) = &outer.body.statements[0] else {
+        panic!("expected try");
+    };
+    assert!(outer_try.catch_clauses.is_empty());
+    assert!(outer_try.finally_block.is_some());
+
+    let Statement::RunAs(inner) = &outer_try.try_block.statements[0] else {
+        panic!("expected nested runAs");
+    };
+    assert!(matches!(inner.user, Expression::New(_)));
+
+    let Statement::Try(inner_try) = &inner.body.statements[0] else {
+        panic!("expected nested try");
+    };
+    assert!(matches!(
+        &inner_try.try_block.statements[0],
+        Statement::Dml(dml) if dml.operation == DmlOperation::Insert
+    ));
+    let finally = inner_try.finally_block.as_ref().expect("finally");
+    assert!(matches!(
+        &finally.statements[0],
+        Statement::Dml(dml) if dml.operation == DmlOperation::Delete
+    ));
+}
+
+#[test]
+fn test_subquery_as_first_select_item() {
+    let body = method_body("subqueryFirst");
+    let Expression::Soql(query) = returned(&body) else {
+        panic!("expected a query");
+    };
+    assert_eq!(query.from_clause, "Account");
+    assert_eq!(query.select_clause.len(), 3);
+
+    let SelectField::SubQuery(subquery) = &query.select_clause[0] else {
+        panic!("expected a subquery first");
+    };
+    assert_eq!(subquery.from_clause, "Contacts");
+    assert_eq!(subquery.select_clause.len(), 2);
+    assert_eq!(
+        &query.select_clause[1..],
+        [
+            SelectField::Field("Id".to_string()),
+            SelectField::Field("Name".to_string())
+        ]
+    );
+}
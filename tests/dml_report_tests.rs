@@ -0,0 +1,160 @@
+//! DML sites, their target objects and their guards
+
+use apexrust::analysis::dml_report::{analyze, DmlReport, DmlTarget};
+use apexrust::{parse, DmlOperation, SharingModifier};
+
+fn report(source: &str) -> DmlReport {
+    analyze(&parse(source).expect("Parse failed"), None)
+}
+
+const SERVICE: &str = r#"
+public with sharing class AccountService {
+    private List<Account> accounts;
+
+    public void rename(String name) {
+        if (!Schema.sObjectType.Account.isUpdateable()) {
+            throw new NoAccessException();
+        }
+        for (Account acc : accounts) {
+            acc.Name = name;
+        }
+        update accounts;
+    }
+
+    public void purge(Set<Id> ids) {
+        List<Contact> contacts = [SELECT Id FROM Contact WHERE AccountId IN :ids];
+        delete contacts;
+    }
+
+    public void save(List<SObject> records) {
+        update records;
+        insert loadRecords();
+    }
+}
+"#;
+
+#[test]
+fn test_guarded_update() {
+    let report = report(SERVICE);
+    let site = &report.sites[0];
+    assert_eq!(site.class, "AccountService");
+    assert_eq!(site.method, "rename");
+    assert_eq!(site.operation, DmlOperation::Update);
+    assert_eq!(site.target, DmlTarget::Object("Account".to_string()));
+    assert!(site.permission_checked);
+    assert!(!site.conditional);
+    assert_eq!(site.sharing, Some(SharingModifier::WithSharing));
+    assert_eq!(site.line, 12);
+}
+
+#[test]
+fn test_unguarded_delete() {
+    let report = report(SERVICE);
+    let site = &report.sites[1];
+    assert_eq!(site.method, "purge");
+    assert_eq!(site.operation, DmlOperation::Delete);
+    assert_eq!(site.target, DmlTarget::Object("Contact".to_string()));
+    assert!(!site.permission_checked);
+    assert!(!site.user_mode);
+
+    let deletes: Vec<_> = report.unconditional_deletes().collect();
+    assert_eq!(deletes, [site]);
+    assert!(report.unguarded().any(|s| s == site));
+}
+
+#[test]
+fn test_untyped_targets_are_unknown() {
+    let report = report(SERVICE);
+    assert_eq!(report.sites.len(), 4);
+    assert_eq!(report.sites[2].target, DmlTarget::Unknown);
+    assert_eq!(report.sites[3].target, DmlTarget::Unknown);
+    assert_eq!(
+        report
+            .objects(DmlOperation::Update)
+            .into_iter()
+            .collect::<Vec<_>>(),
+        ["Account"]
+    );
+}
+
+#[test]
+fn test_check_for_another_object_does_not_guard() {
+    let report = report(
+        r#"
+public class Cleanup {
+    public void run(List<Lead> leads) {
+        if (Schema.sObjectType.Contact.isDeletable()) {
+            delete leads;
+        }
+    }
+}
+"#,
+    );
+    let site = &report.sites[0];
+    assert_eq!(site.target, DmlTarget::Object("Lead".to_string()));
+    assert!(!site.permission_checked);
+    assert!(site.conditional);
+    assert_eq!(site.sharing, None);
+}
+
+#[test]
+fn test_user_mode_reads_and_database_calls() {
+    let report = report(
+        r#"
+public inherited sharing class Sync {
+    public void run() {
+        List<Case> cases = [SELECT Id FROM Case WITH USER_MODE];
+        update cases;
+        Database.insert(new Task(), AccessLevel.USER_MODE);
+        Database.delete(cases);
+    }
+}
+"#,
+    );
+    let modes: Vec<_> = report
+        .sites
+        .iter()
+        .map(|s| (s.operation, s.user_mode))
+        .collect();
+    assert_eq!(
+        modes,
+        [
+            (DmlOperation::Update, true),
+            (DmlOperation::Insert, true),
+            (DmlOperation::Delete, true),
+        ]
+    );
+    assert_eq!(
+        report.sites[1].target,
+        DmlTarget::Object("Task".to_string())
+    );
+}
+
+#[test]
+fn test_trigger_records_take_the_trigger_object() {
+    let report = report(
+        r#"
+trigger OpportunityTrigger on Opportunity (after insert) {
+    Schema.DescribeSObjectResult describe = Opportunity.sObjectType.getDescribe();
+    if (describe.isUpdateable()) {
+        update Trigger.new;
+    }
+}
+"#,
+    );
+    let site = &report.sites[0];
+    assert_eq!(site.class, "OpportunityTrigger");
+    assert_eq!(site.target, DmlTarget::Object("Opportunity".to_string()));
+    assert!(site.permission_checked);
+}
+
+#[test]
+fn test_display_one_line_per_site() {
+    let rendered = report(SERVICE).to_string();
+    let first = rendered.lines().next().unwrap();
+    assert_eq!(
+        first,
+        "AccountService.rename line 12: update Account, permission checked, unconditional, with sharing"
+    );
+    assert_eq!(rendered.lines().count(), 4);
+}
@@ -412,7 +412,7 @@ fn test_dreamhouse_query_execution() {
         );
     }
 
-    assert!(rows.len() > 0, "Expected to find some properties");
+    assert!(!rows.is_empty(), "Expected to find some properties");
     // All results should be under $1M and have 3+ beds
     for (_, _, _, price, beds) in &rows {
         assert!(*price <= 1000000.0);
@@ -497,7 +497,7 @@ fn test_dreamhouse_tags_search() {
     for (id, name, tags) in &rows {
         println!("  {} - {} [{}]", id, name, tags);
     }
-    assert!(rows.len() > 0);
+    assert!(!rows.is_empty());
 }
 
 #[test]
@@ -733,5 +733,5 @@ public with sharing class PropertyController {
     println!("========================================");
 
     assert!(total_count > 0);
-    assert!(results.len() > 0);
+    assert!(!results.is_empty());
 }
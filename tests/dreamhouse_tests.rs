@@ -424,42 +424,3 @@ public class Test {
     }
     assert!(result.is_ok(), "Failed to parse type literal as argument: {:?}", result.err());
 }
-
-// Test transpilation of PagedResult
-#[test]
-fn test_transpile_paged_result() {
-    use apexrust::transpile::Transpiler;
-    
-    let mut parser = Parser::new(PAGED_RESULT);
-    let ast = parser.parse().expect("Parse failed");
-    
-    let mut transpiler = Transpiler::new();
-    let ts = transpiler.transpile(&ast).expect("Transpile failed");
-    
-    println!("Transpiled PagedResult:\n{}", ts);
-    
-    // Check that properties are transpiled correctly
-    assert!(ts.contains("pageSize"), "Should contain pageSize property");
-    assert!(ts.contains("pageNumber"), "Should contain pageNumber property");
-    assert!(ts.contains("totalItemCount"), "Should contain totalItemCount property");
-    assert!(ts.contains("records"), "Should contain records property");
-}
-
-// Test transpilation of PropertyController
-#[test]
-fn test_transpile_property_controller() {
-    use apexrust::transpile::Transpiler;
-    
-    let mut parser = Parser::new(PROPERTY_CONTROLLER);
-    let ast = parser.parse().expect("Parse failed");
-    
-    let mut transpiler = Transpiler::new();
-    let ts = transpiler.transpile(&ast).expect("Transpile failed");
-    
-    println!("Transpiled PropertyController:\n{}", ts);
-    
-    // Check key features
-    assert!(ts.contains("async"), "Methods with SOQL should be async");
-    assert!(ts.contains("$runtime.query"), "SOQL should become runtime.query");
-    assert!(ts.contains("??"), "Null coalescing should be preserved");
-}
@@ -0,0 +1,204 @@
+//! Tests for the dynamic SOQL object/field name lint
+
+use apexrust::analysis::dynamic_soql::{find_dynamic_soql, DynamicSoqlReport, NamePosition};
+use apexrust::parse;
+
+fn report(source: &str) -> DynamicSoqlReport {
+    find_dynamic_soql(&parse(source).expect("Parse failed"))
+}
+
+/// `(variable, validated)` for each name spliced into the query in `method`
+fn names(report: &DynamicSoqlReport, method: &str) -> Vec<(String, bool)> {
+    report
+        .queries
+        .iter()
+        .filter(|q| q.method == method)
+        .flat_map(|q| &q.names)
+        .map(|n| (n.variable.clone(), n.validated))
+        .collect()
+}
+
+#[test]
+fn test_guard_clause_validates_object_name() {
+    let report = report(
+        r#"
+        public class Browser {
+            public List<SObject> recent(String objectName) {
+                if (!Schema.getGlobalDescribe().containsKey(objectName)) {
+                    throw new IllegalArgumentException('Unknown object');
+                }
+                String soql = 'SELECT Id FROM ' + objectName;
+                soql += ' LIMIT 10';
+                return Database.query(soql);
+            }
+        }
+    "#,
+    );
+
+    assert_eq!(report.queries.len(), 1);
+    assert_eq!(report.queries[0].class, "Browser");
+    assert_eq!(report.queries[0].names[0].position, NamePosition::Object);
+    assert_eq!(
+        names(&report, "recent"),
+        vec![("objectName".to_string(), true)]
+    );
+    assert!(report.diagnostics.is_empty());
+}
+
+#[test]
+fn test_unvalidated_object_name_is_reported() {
+    let report = report(
+        r#"
+        public class Browser {
+            public List<SObject> load(String objectName) {
+                return Database.query('SELECT Id FROM ' + objectName);
+            }
+        }
+    "#,
+    );
+
+    assert_eq!(
+        names(&report, "load"),
+        vec![("objectName".to_string(), false)]
+    );
+    assert_eq!(report.diagnostics.len(), 1);
+    assert_eq!(report.diagnostics[0].method, "load");
+    assert_eq!(
+        report.diagnostics[0].message,
+        "object name 'objectName' is spliced into dynamic SOQL without being validated first"
+    );
+}
+
+#[test]
+fn test_check_must_run_before_the_query() {
+    let report = report(
+        r#"
+        public class Browser {
+            public List<SObject> late(String objectName) {
+                List<SObject> rows = Database.query('SELECT Id FROM ' + objectName);
+                if (!Schema.getGlobalDescribe().containsKey(objectName)) {
+                    throw new IllegalArgumentException('Unknown object');
+                }
+                return rows;
+            }
+
+            public List<SObject> branch(String objectName, Boolean strict) {
+                if (strict) {
+                    if (!Schema.getGlobalDescribe().containsKey(objectName)) {
+                        return null;
+                    }
+                }
+                return Database.query('SELECT Id FROM ' + objectName);
+            }
+
+            public List<SObject> informational(String objectName) {
+                if (Schema.getGlobalDescribe().containsKey(objectName)) {
+                    System.debug('known object');
+                }
+                return Database.query('SELECT Id FROM ' + objectName);
+            }
+        }
+    "#,
+    );
+
+    assert_eq!(
+        names(&report, "late"),
+        vec![("objectName".to_string(), false)]
+    );
+    assert_eq!(
+        names(&report, "branch"),
+        vec![("objectName".to_string(), false)]
+    );
+    assert_eq!(
+        names(&report, "informational"),
+        vec![("objectName".to_string(), false)]
+    );
+    assert_eq!(report.diagnostics.len(), 3);
+}
+
+#[test]
+fn test_enclosing_condition_validates_its_branch() {
+    let report = report(
+        r#"
+        public class Browser {
+            private static final Set<String> SORTABLE = new Set<String>{'Name', 'CreatedDate'};
+
+            public List<SObject> sorted(String sortField) {
+                if (SORTABLE.contains(sortField)) {
+                    return Database.query('SELECT Id FROM Account ORDER BY ' + sortField);
+                } else {
+                    return Database.query('SELECT Id FROM Account ORDER BY ' + sortField);
+                }
+            }
+
+            public List<SObject> fallback(String objectName) {
+                if (objectName != 'Account' && objectName != 'Contact') {
+                    return null;
+                }
+                if (!(objectName == 'Account' || objectName == 'Contact')) {
+                    return null;
+                } else {
+                    return Database.query('SELECT Id FROM ' + objectName);
+                }
+            }
+        }
+    "#,
+    );
+
+    let sorted: Vec<bool> = report
+        .queries
+        .iter()
+        .filter(|q| q.method == "sorted")
+        .map(|q| q.names[0].validated)
+        .collect();
+    assert_eq!(sorted, vec![true, false]);
+    assert_eq!(report.queries[0].names[0].position, NamePosition::Field);
+    assert_eq!(
+        names(&report, "fallback"),
+        vec![("objectName".to_string(), true)]
+    );
+}
+
+#[test]
+fn test_assertion_and_describe_variable_validate() {
+    let report = report(
+        r#"
+        public class Browser {
+            public Integer count(String objectName, String field) {
+                Map<String, Schema.SObjectType> describe = Schema.getGlobalDescribe();
+                System.assert(describe.containsKey(objectName.toLowerCase()));
+                String soql = 'SELECT ' + field + ' FROM ' + objectName;
+                return Database.countQuery(soql);
+            }
+        }
+    "#,
+    );
+
+    assert_eq!(
+        names(&report, "count"),
+        vec![
+            ("field".to_string(), false),
+            ("objectName".to_string(), true)
+        ]
+    );
+    assert_eq!(report.diagnostics.len(), 1);
+    assert_eq!(report.diagnostics[0].variable, "field");
+}
+
+#[test]
+fn test_static_and_value_only_queries_are_ignored() {
+    let report = report(
+        r#"
+        public class Browser {
+            public List<SObject> load(String name) {
+                List<Account> a = [SELECT Id FROM Account];
+                List<SObject> b = Database.query('SELECT Id FROM Account');
+                return Database.query('SELECT Id FROM Account WHERE Name = \'' + name + '\'');
+            }
+        }
+    "#,
+    );
+
+    assert!(report.queries.is_empty());
+    assert!(report.diagnostics.is_empty());
+}
@@ -0,0 +1,101 @@
+//! Query plans for converted SOQL against SQLite tables built from
+//! generated DDL
+
+use apexrust::sql::explain::{run_sqlite, PlanAccess};
+use apexrust::sql::{
+    ConversionConfig, DdlGenerator, FieldDescribe, SObjectDescribe, SalesforceFieldType,
+    SalesforceSchema, SoqlToSqlConverter, SqlDialect,
+};
+use apexrust::{parse, ClassMember, Expression, SoqlQuery, Statement, TypeDeclaration};
+use rusqlite::Connection;
+
+fn create_schema() -> SalesforceSchema {
+    let mut schema = SalesforceSchema::new();
+
+    let mut account = SObjectDescribe::new("Account");
+    account.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+    account.add_field(FieldDescribe::new("Name", SalesforceFieldType::String));
+    account.add_field(FieldDescribe::new(
+        "Industry",
+        SalesforceFieldType::Picklist,
+    ));
+    schema.add_object(account);
+
+    let mut contact = SObjectDescribe::new("Contact");
+    contact.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+    contact.add_field(FieldDescribe::new("LastName", SalesforceFieldType::String));
+    contact.add_field(
+        FieldDescribe::new("AccountId", SalesforceFieldType::Lookup)
+            .with_reference("Account")
+            .with_relationship_name("Account"),
+    );
+    schema.add_object(contact);
+
+    schema
+}
+
+fn extract_soql(soql: &str) -> SoqlQuery {
+    let source = format!("class T {{ void m() {{ List<SObject> x = [{}]; }} }}", soql);
+    let unit = parse(&source).expect("Parse failed");
+    if let TypeDeclaration::Class(class) = &unit.declarations[0] {
+        if let ClassMember::Method(method) = &class.members[0] {
+            if let Statement::LocalVariable(local) = &method.body.as_ref().unwrap().statements[0] {
+                if let Some(Expression::Soql(query)) = &local.declarators[0].initializer {
+                    return (**query).clone();
+                }
+            }
+        }
+    }
+    panic!("Could not extract SOQL query");
+}
+
+fn setup(schema: &SalesforceSchema) -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    let ddl = DdlGenerator::new(SqlDialect::Sqlite).generate_schema(schema);
+    conn.execute_batch(&ddl).unwrap();
+    conn
+}
+
+fn convert(schema: &SalesforceSchema, soql: &str) -> apexrust::sql::SqlConversion {
+    let config = ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        ..Default::default()
+    };
+    SoqlToSqlConverter::new(schema, config)
+        .convert(&extract_soql(soql))
+        .unwrap()
+}
+
+#[test]
+fn test_lookup_filter_searches_lookup_index() {
+    let schema = create_schema();
+    let conn = setup(&schema);
+    let conversion = convert(
+        &schema,
+        "SELECT Id, Account.Name FROM Contact WHERE AccountId = :accountId",
+    );
+
+    let report = run_sqlite(&conversion, &conn).unwrap();
+    assert!(report.sql.starts_with("EXPLAIN QUERY PLAN SELECT"));
+
+    let contact = report.access("t0").expect("contact step");
+    assert_eq!(contact.access, PlanAccess::Search, "{:#?}", report);
+    assert_eq!(contact.index.as_deref(), Some("idx_contact_account_id"));
+
+    let account = report.access("t1").expect("account join step");
+    assert_eq!(account.access, PlanAccess::Search, "{:#?}", report);
+}
+
+#[test]
+fn test_unindexed_filter_scans() {
+    let schema = create_schema();
+    let conn = setup(&schema);
+    let conversion = convert(
+        &schema,
+        "SELECT Id FROM Account WHERE Industry IN :industries",
+    );
+
+    let report = run_sqlite(&conversion, &conn).unwrap();
+    assert_eq!(report.scanned_tables(), ["t0"]);
+    assert_eq!(report.access("t0").unwrap().index, None);
+}
@@ -0,0 +1,200 @@
+//! Tests for unselected field reads and mistyped field literals
+
+use apexrust::analysis::field_usage::{check_field_usage, FieldUsageDiagnostic, FieldUsageKind};
+use apexrust::parse;
+use apexrust::sql::{FieldDescribe, SObjectDescribe, SalesforceFieldType, SalesforceSchema};
+
+fn schema() -> SalesforceSchema {
+    let mut schema = SalesforceSchema::new();
+
+    let mut account = SObjectDescribe::new("Account");
+    account.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id));
+    account.add_field(FieldDescribe::new("Name", SalesforceFieldType::String));
+    account.add_field(FieldDescribe::new(
+        "Industry",
+        SalesforceFieldType::Picklist,
+    ));
+    account.add_field(FieldDescribe::new(
+        "NumberOfEmployees",
+        SalesforceFieldType::Integer,
+    ));
+    account.add_field(
+        FieldDescribe::new("OwnerId", SalesforceFieldType::Lookup)
+            .with_reference("User")
+            .with_relationship_name("Owner"),
+    );
+    schema.add_object(account);
+
+    let mut opportunity = SObjectDescribe::new("Opportunity");
+    opportunity.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id));
+    opportunity.add_field(FieldDescribe::new("CloseDate", SalesforceFieldType::Date));
+    opportunity.add_field(FieldDescribe::new("Amount", SalesforceFieldType::Currency));
+    opportunity.add_field(FieldDescribe::new(
+        "IsPrivate",
+        SalesforceFieldType::Boolean,
+    ));
+    schema.add_object(opportunity);
+
+    schema
+}
+
+fn check(body: &str) -> Vec<FieldUsageDiagnostic> {
+    let source = format!(
+        "public class Service {{ public void run(Account param) {{ {} }} }}",
+        body
+    );
+    let unit = parse(&source).expect("Parse failed");
+    check_field_usage(&unit, &schema())
+}
+
+fn unselected(diagnostics: &[FieldUsageDiagnostic]) -> Vec<String> {
+    diagnostics
+        .iter()
+        .filter_map(|d| match &d.kind {
+            FieldUsageKind::UnselectedField { variable, field } => {
+                Some(format!("{}.{}", variable, field))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_unselected_field_read() {
+    let diagnostics = check(
+        "Account acc = [SELECT Name FROM Account LIMIT 1];
+         String name = acc.Name;
+         String industry = acc.Industry;",
+    );
+    assert_eq!(unselected(&diagnostics), ["acc.Industry"]);
+    assert_eq!(diagnostics[0].class, "Service");
+    assert_eq!(diagnostics[0].method, "run");
+    assert!(diagnostics[0].message.contains("acc.Industry"));
+}
+
+#[test]
+fn test_id_and_parent_paths_are_selected() {
+    let diagnostics = check(
+        "Account acc = [SELECT Name, Owner.Name FROM Account LIMIT 1];
+         Id accountId = acc.Id;
+         String owner = acc.Owner.Name.toUpperCase();
+         Id ownerId = acc.Owner.Id;
+         String email = acc.Owner.Email;",
+    );
+    assert_eq!(unselected(&diagnostics), ["acc.Owner.Email"]);
+}
+
+#[test]
+fn test_for_each_over_query_and_list() {
+    let diagnostics = check(
+        "for (Account a : [SELECT Name FROM Account]) {
+             System.debug(a.Name + a.Industry);
+         }
+         List<Account> accounts = [SELECT Industry FROM Account];
+         for (Account b : accounts) {
+             System.debug(b.Industry + b.Name);
+         }",
+    );
+    assert_eq!(unselected(&diagnostics), ["a.Industry", "b.Name"]);
+}
+
+#[test]
+fn test_written_fields_can_be_read() {
+    let diagnostics = check(
+        "Account acc = [SELECT Name FROM Account LIMIT 1];
+         acc.Industry = 'Energy';
+         String industry = acc.Industry;",
+    );
+    assert!(unselected(&diagnostics).is_empty(), "{:#?}", diagnostics);
+}
+
+#[test]
+fn test_reassignment_and_calls_clear_knowledge() {
+    let diagnostics = check(
+        "Account acc = [SELECT Name FROM Account LIMIT 1];
+         acc = param;
+         String industry = acc.Industry;
+         Account other = [SELECT Name FROM Account LIMIT 1];
+         enrich(other);
+         industry = other.Industry;
+         Account looped = [SELECT Name FROM Account LIMIT 1];
+         for (Integer i = 0; i < 3; i++) {
+             industry = looped.Industry;
+             looped = param;
+         }",
+    );
+    assert!(unselected(&diagnostics).is_empty(), "{:#?}", diagnostics);
+}
+
+#[test]
+fn test_put_clears_knowledge() {
+    let diagnostics = check(
+        "Account acc = [SELECT Name FROM Account LIMIT 1];
+         acc.put('Industry', 'Energy');
+         String industry = acc.Industry;
+         Account other = [SELECT Name FROM Account LIMIT 1];
+         other.put(Account.Industry, 'Energy');
+         industry = other.Industry;
+         Account looped = [SELECT Name FROM Account LIMIT 1];
+         for (Integer i = 0; i < 3; i++) {
+             industry = looped.Industry;
+             looped.put('Industry', 'Energy');
+         }",
+    );
+    assert!(unselected(&diagnostics).is_empty(), "{:#?}", diagnostics);
+}
+
+#[test]
+fn test_branches_keep_common_knowledge() {
+    let diagnostics = check(
+        "Account acc = [SELECT Name FROM Account LIMIT 1];
+         if (param == null) {
+             acc.Industry = 'Energy';
+         } else {
+             acc = param;
+         }
+         String industry = acc.Industry;
+         Account kept = [SELECT Name FROM Account LIMIT 1];
+         if (param == null) {
+             kept.Industry = 'Energy';
+         }
+         industry = kept.Industry;",
+    );
+    assert_eq!(unselected(&diagnostics), ["kept.Industry"]);
+}
+
+#[test]
+fn test_literal_type_mismatch() {
+    let diagnostics = check(
+        "Opportunity opp = new Opportunity(Amount = 'a lot', CloseDate = null);
+         opp.CloseDate = 'tomorrow';
+         opp.IsPrivate = 1;
+         opp.Amount = -5;
+         param.NumberOfEmployees = 10;
+         param.Name = 42;",
+    );
+    let mismatches: Vec<_> = diagnostics
+        .iter()
+        .filter_map(|d| match &d.kind {
+            FieldUsageKind::TypeMismatch {
+                object,
+                field,
+                expected,
+            } => Some((object.as_str(), field.as_str(), *expected)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        mismatches,
+        [
+            ("Opportunity", "Amount", SalesforceFieldType::Currency),
+            ("Opportunity", "CloseDate", SalesforceFieldType::Date),
+            ("Opportunity", "IsPrivate", SalesforceFieldType::Boolean),
+            ("Account", "Name", SalesforceFieldType::String),
+        ]
+    );
+    assert_eq!(
+        diagnostics[1].message,
+        "String literal assigned to Opportunity.CloseDate, a Date field"
+    );
+}
@@ -0,0 +1,401 @@
+//! Seed-data fixtures: parsing against a schema and loading into SQLite
+
+use apexrust::sql::fixtures::{self, load_sqlite};
+use apexrust::sql::{
+    ChildRelationship, ConversionConfig, DdlGenerator, FieldDefault, FieldDescribe, FixtureError,
+    FixtureValue, SObjectDescribe, SalesforceFieldType, SalesforceSchema, SoqlToSqlConverter,
+    SqlDialect,
+};
+use apexrust::{
+    parse, ClassMember, ClockSource, Expression, SoqlQuery, Statement, Timestamp, TypeDeclaration,
+};
+use rusqlite::Connection;
+
+fn create_schema() -> SalesforceSchema {
+    let mut schema = SalesforceSchema::new();
+
+    let mut account = SObjectDescribe::new("Account");
+    account.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+    account.add_field(FieldDescribe::new("Name", SalesforceFieldType::String));
+    account.add_field(FieldDescribe::new(
+        "AnnualRevenue",
+        SalesforceFieldType::Currency,
+    ));
+    account.add_field(
+        FieldDescribe::new("IsActive__c", SalesforceFieldType::Boolean).with_default(true),
+    );
+    account.add_child_relationship(ChildRelationship::new("Contacts", "Contact", "AccountId"));
+    account.add_child_relationship(ChildRelationship::new(
+        "Opportunities",
+        "Opportunity",
+        "AccountId",
+    ));
+    schema.add_object(account);
+
+    let mut contact = SObjectDescribe::new("Contact");
+    contact.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+    contact.add_field(FieldDescribe::new("LastName", SalesforceFieldType::String));
+    contact.add_field(FieldDescribe::new("Birthdate", SalesforceFieldType::Date));
+    contact.add_field(
+        FieldDescribe::new("AccountId", SalesforceFieldType::Lookup)
+            .with_reference("Account")
+            .with_relationship_name("Account"),
+    );
+    schema.add_object(contact);
+
+    let mut opportunity = SObjectDescribe::new("Opportunity");
+    opportunity.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+    opportunity.add_field(FieldDescribe::new("Name", SalesforceFieldType::String));
+    opportunity.add_field(FieldDescribe::new("CloseDate", SalesforceFieldType::Date));
+    opportunity.add_field(FieldDescribe::new(
+        "Probability",
+        SalesforceFieldType::Percent,
+    ));
+    opportunity.add_field(
+        FieldDescribe::new("AccountId", SalesforceFieldType::Lookup)
+            .with_reference("Account")
+            .with_relationship_name("Account"),
+    );
+    schema.add_object(opportunity);
+
+    let mut task = SObjectDescribe::new("Task");
+    task.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+    task.add_field(FieldDescribe::new("Subject", SalesforceFieldType::String));
+    task.add_field(FieldDescribe::new(
+        "ActivityDateTime",
+        SalesforceFieldType::DateTime,
+    ));
+    task.add_field(
+        FieldDescribe::new("CreatedDate", SalesforceFieldType::DateTime)
+            .with_default(FieldDefault::CurrentTimestamp),
+    );
+    task.add_field(
+        FieldDescribe::new("WhatId", SalesforceFieldType::Reference)
+            .with_polymorphic_reference(vec!["Account".to_string(), "Opportunity".to_string()])
+            .with_relationship_name("What"),
+    );
+    schema.add_object(task);
+
+    schema
+}
+
+fn extract_soql(soql: &str) -> SoqlQuery {
+    let source = format!("class T {{ void m() {{ List<SObject> x = [{}]; }} }}", soql);
+    let unit = parse(&source).expect("Parse failed");
+    if let TypeDeclaration::Class(class) = &unit.declarations[0] {
+        if let ClassMember::Method(method) = &class.members[0] {
+            if let Statement::LocalVariable(local) = &method.body.as_ref().unwrap().statements[0] {
+                if let Some(Expression::Soql(query)) = &local.declarators[0].initializer {
+                    return (**query).clone();
+                }
+            }
+        }
+    }
+    panic!("Could not extract SOQL query");
+}
+
+fn parse_error(source: &str) -> FixtureError {
+    fixtures::parse(source, &create_schema()).unwrap_err()
+}
+
+#[test]
+fn test_typed_values() {
+    let dataset = fixtures::parse(
+        "Account { Name: 'O\\'Brien & Co', AnnualRevenue: 1500000.50, IsActive__c: false }
+         Opportunity { Name: 'Renewal', CloseDate: 2024-06-30, Probability: 75, AccountId: null }
+         Task { Subject: 'Call', ActivityDateTime: 2024-06-30T09:00:00Z }",
+        &create_schema(),
+    )
+    .unwrap();
+
+    let account = dataset.records_of("Account").next().unwrap();
+    assert_eq!(
+        account.get("name"),
+        Some(&FixtureValue::Text("O'Brien & Co".to_string()))
+    );
+    assert_eq!(
+        account.get("AnnualRevenue"),
+        Some(&FixtureValue::Number(1500000.5))
+    );
+    assert_eq!(
+        account.get("IsActive__c"),
+        Some(&FixtureValue::Boolean(false))
+    );
+
+    let opportunity = dataset.records_of("Opportunity").next().unwrap();
+    assert_eq!(
+        opportunity.get("CloseDate"),
+        Some(&FixtureValue::Date("2024-06-30".to_string()))
+    );
+    assert_eq!(
+        opportunity.get("Probability"),
+        Some(&FixtureValue::Number(75.0))
+    );
+    assert_eq!(opportunity.get("AccountId"), Some(&FixtureValue::Null));
+
+    let task = dataset.records_of("Task").next().unwrap();
+    assert_eq!(
+        task.get("ActivityDateTime"),
+        Some(&FixtureValue::DateTime("2024-06-30T09:00:00Z".to_string()))
+    );
+}
+
+#[test]
+fn test_generated_and_explicit_ids() {
+    let dataset = fixtures::parse(
+        "Account { Name: 'A' }
+         Account { Id: '001000000000042', Name: 'B' }
+         Contact { LastName: 'C' }",
+        &create_schema(),
+    )
+    .unwrap();
+
+    let ids: Vec<_> = dataset.records.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids[0], "001000000000001AAA");
+    assert_eq!(ids[1], "001000000000042");
+    assert_eq!(ids[2], "003000000000001AAA");
+    assert!(dataset.records[1].get("Id").is_none());
+}
+
+#[test]
+fn test_nested_children_get_parent_foreign_key() {
+    let dataset = fixtures::parse(
+        "// One account with two contacts and an opportunity
+         Account {
+             Name: 'Acme',
+             Contacts: [
+                 { LastName: 'Smith' },
+                 { LastName: 'Jones', Birthdate: 1980-04-12 },
+             ],
+             Opportunities: [{ Name: 'Renewal' }]
+         }",
+        &create_schema(),
+    )
+    .unwrap();
+
+    let objects: Vec<_> = dataset.records.iter().map(|r| r.object.as_str()).collect();
+    assert_eq!(objects, ["Account", "Contact", "Contact", "Opportunity"]);
+
+    let account_id = dataset.records[0].id.clone();
+    for child in &dataset.records[1..] {
+        assert_eq!(
+            child.get("AccountId"),
+            Some(&FixtureValue::Reference {
+                id: account_id.clone(),
+                object: Some("Account".to_string()),
+            })
+        );
+    }
+}
+
+#[test]
+fn test_labels_reference_top_level_records() {
+    let dataset = fixtures::parse(
+        "Task { Subject: 'Follow up', WhatId: @renewal }
+         @acme Account { Name: 'Acme' }
+         @renewal Opportunity { Name: 'Renewal', AccountId: @acme }",
+        &create_schema(),
+    )
+    .unwrap();
+
+    let acme = dataset.labelled("acme").unwrap();
+    let renewal = dataset.labelled("renewal").unwrap();
+    assert_eq!(
+        renewal.get("AccountId"),
+        Some(&FixtureValue::Reference {
+            id: acme.id.clone(),
+            object: Some("Account".to_string()),
+        })
+    );
+
+    // Forward reference through a polymorphic lookup
+    let task = dataset.records_of("Task").next().unwrap();
+    assert_eq!(
+        task.get("WhatId"),
+        Some(&FixtureValue::Reference {
+            id: renewal.id.clone(),
+            object: Some("Opportunity".to_string()),
+        })
+    );
+}
+
+#[test]
+fn test_syntax_error() {
+    let err = parse_error("Account {\n    Name 'Acme'\n}");
+    assert!(matches!(err, FixtureError::Syntax { .. }), "{err:?}");
+    assert_eq!(err.to_string(), "Expected ':', found a string at 2:10");
+
+    let err = parse_error("Account { Name: 'Acme }");
+    assert_eq!(err.to_string(), "Unterminated string at 1:17");
+
+    let err = parse_error("Account { Name: 'Acme'");
+    assert_eq!(err.to_string(), "Expected '}', found end of input at 1:23");
+}
+
+#[test]
+fn test_unknown_object() {
+    let err = parse_error("Account { Name: 'A' }\nWidget__c { Name: 'B' }");
+    assert_eq!(
+        err,
+        FixtureError::UnknownObject {
+            object: "Widget__c".to_string(),
+            span: err.span(),
+        }
+    );
+    assert_eq!((err.span().line, err.span().column), (2, 1));
+}
+
+#[test]
+fn test_unknown_field() {
+    let err = parse_error("Account { Name: 'A', Contacts: [{ Phone: '555' }] }");
+    assert!(
+        matches!(&err, FixtureError::UnknownField { object, field, .. } if object == "Contact" && field == "Phone"),
+        "{err:?}"
+    );
+    assert_eq!(err.span().column, 35);
+}
+
+#[test]
+fn test_unknown_relationship() {
+    let err = parse_error("Account { Name: 'A', Cases: [{ Subject: 'Broken' }] }");
+    assert!(
+        matches!(&err, FixtureError::UnknownRelationship { relationship, .. } if relationship == "Cases"),
+        "{err:?}"
+    );
+    assert_eq!(err.span().column, 22);
+}
+
+#[test]
+fn test_type_mismatch() {
+    let err = parse_error("Opportunity { Name: 'R', CloseDate: '2024-06-30' }");
+    assert_eq!(
+        err.to_string(),
+        "Field 'Opportunity.CloseDate' expects a date (YYYY-MM-DD), found a string at 1:37"
+    );
+
+    let err = parse_error("Account { AnnualRevenue: true }");
+    assert!(
+        matches!(&err, FixtureError::TypeMismatch { found, .. } if found == "a boolean"),
+        "{err:?}"
+    );
+
+    let err = parse_error("Opportunity { Name: 2024-06-30 }");
+    assert!(
+        matches!(&err, FixtureError::TypeMismatch { expected, found, .. } if expected == "a string" && found == "a date"),
+        "{err:?}"
+    );
+
+    // A label must name a record the lookup can point to
+    let err = parse_error("@c Contact { LastName: 'C' }\nOpportunity { AccountId: @c }");
+    assert_eq!(
+        err.to_string(),
+        "Field 'Opportunity.AccountId' expects a reference to Account, found @c (Contact) at 2:26"
+    );
+}
+
+#[test]
+fn test_unknown_and_duplicate_labels() {
+    let err = parse_error("Opportunity { AccountId: @missing }");
+    assert_eq!(
+        err,
+        FixtureError::UnknownLabel {
+            label: "missing".to_string(),
+            span: err.span(),
+        }
+    );
+    assert_eq!(err.span().column, 26);
+
+    let err = parse_error("@a Account { Name: 'A' }\n@a Account { Name: 'B' }");
+    assert!(
+        matches!(err, FixtureError::DuplicateLabel { .. }),
+        "{err:?}"
+    );
+    assert_eq!((err.span().line, err.span().column), (2, 1));
+}
+
+#[test]
+fn test_load_into_sqlite_and_query() {
+    let schema = create_schema();
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&DdlGenerator::new(SqlDialect::Sqlite).generate_schema(&schema))
+        .unwrap();
+
+    let dataset = fixtures::parse(
+        "@acme Account {
+             Name: 'Acme',
+             Contacts: [{ LastName: 'Smith' }, { LastName: 'Jones' }]
+         }
+         Account { Name: 'Globex', IsActive__c: false, Contacts: [{ LastName: 'Brown' }] }
+         Task { Subject: 'Call', WhatId: @acme }",
+        &schema,
+    )
+    .unwrap();
+    load_sqlite(&dataset, &schema, &conn, &ClockSource::System).unwrap();
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        ..Default::default()
+    };
+    let conversion = SoqlToSqlConverter::new(&schema, config)
+        .convert(&extract_soql(
+            "SELECT LastName FROM Contact WHERE Account.IsActive__c = true ORDER BY LastName",
+        ))
+        .unwrap();
+    let mut stmt = conn.prepare(&conversion.sql).unwrap();
+    let names: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(names, ["Jones", "Smith"]);
+
+    let what_type: String = conn
+        .query_row("SELECT what_id_type FROM task", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(what_type, "Account");
+}
+
+#[test]
+fn test_fixed_clock_seeds_created_date_for_today_queries() {
+    let schema = create_schema();
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&DdlGenerator::new(SqlDialect::Sqlite).generate_schema(&schema))
+        .unwrap();
+    let clock = ClockSource::Fixed(Timestamp::from_ymd_hms(2024, 6, 30, 9, 0, 0).unwrap());
+
+    let dataset = fixtures::parse(
+        "Task { Subject: 'Seeded now' }
+         Task { Subject: 'Seeded earlier', CreatedDate: 2024-06-29T23:00:00Z }",
+        &schema,
+    )
+    .unwrap();
+    load_sqlite(&dataset, &schema, &conn, &clock).unwrap();
+
+    let created: String = conn
+        .query_row(
+            "SELECT created_date FROM task WHERE subject = 'Seeded now'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(created, "2024-06-30T09:00:00Z");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        clock,
+        ..Default::default()
+    };
+    let conversion = SoqlToSqlConverter::new(&schema, config)
+        .convert(&extract_soql(
+            "SELECT Subject FROM Task WHERE CreatedDate = TODAY",
+        ))
+        .unwrap();
+    let subjects: Vec<String> = conn
+        .prepare(&conversion.sql)
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(subjects, ["Seeded now"]);
+}
@@ -0,0 +1,102 @@
+//! Rewriting units with ast::fold before transpiling
+
+use apexrust::ast::fold::{self, Folder};
+use apexrust::transpile::transpile;
+use apexrust::{parse, ArrayAccessExpr, ClassMember, Expression, NodeId};
+
+/// Renames the field `from` of the class: its declaration, `this.from`,
+/// bare `from` and binds reading it
+struct RenameField {
+    from: &'static str,
+    to: &'static str,
+}
+
+impl Folder for RenameField {
+    fn fold_class_member(&mut self, member: ClassMember) -> ClassMember {
+        match fold::fold_class_member(self, member) {
+            ClassMember::Field(mut field) => {
+                for declarator in &mut field.declarators {
+                    if declarator.name == self.from {
+                        declarator.name = self.to.to_string();
+                    }
+                }
+                ClassMember::Field(field)
+            }
+            other => other,
+        }
+    }
+
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        match fold::fold_expression(self, expr) {
+            Expression::Identifier(name, span, id) if name == self.from => {
+                Expression::Identifier(self.to.to_string(), span, id)
+            }
+            Expression::FieldAccess(mut access)
+                if access.field == self.from && matches!(access.object, Expression::This(..)) =>
+            {
+                access.field = self.to.to_string();
+                Expression::FieldAccess(access)
+            }
+            other => other,
+        }
+    }
+}
+
+const SOURCE: &str = r#"
+public class Sizing {
+    private Integer threshold = 50;
+
+    public List<Account> large() {
+        this.threshold = threshold + 1;
+        return [SELECT Id FROM Account WHERE NumberOfEmployees > :threshold];
+    }
+}
+"#;
+
+#[test]
+fn test_rename_field_reaches_binds_and_transpiled_output() {
+    let unit = parse(SOURCE).expect("Parse failed");
+    let before = transpile(&unit).unwrap();
+    assert!(before.contains("threshold"));
+
+    let renamed = RenameField {
+        from: "threshold",
+        to: "minimumEmployees",
+    }
+    .fold_compilation_unit(unit);
+    let ts = transpile(&renamed).unwrap();
+
+    assert!(!ts.contains("threshold"), "{}", ts);
+    assert!(ts.contains("minimumEmployees: number = 50;"));
+    assert!(ts.contains("NumberOfEmployees > :minimumEmployees"));
+}
+
+#[test]
+fn test_rewrite_turning_bind_path_into_expression() {
+    /// `acc` becomes `accounts[0]`
+    struct FirstAccount;
+
+    impl Folder for FirstAccount {
+        fn fold_expression(&mut self, expr: Expression) -> Expression {
+            match fold::fold_expression(self, expr) {
+                Expression::Identifier(name, span, id) if name == "acc" => {
+                    Expression::ArrayAccess(Box::new(ArrayAccessExpr {
+                        array: Expression::Identifier("accounts".to_string(), span, NodeId::DUMMY),
+                        index: Expression::Integer(0, "0".to_string(), span, NodeId::DUMMY),
+                        span,
+                        id,
+                    }))
+                }
+                other => other,
+            }
+        }
+    }
+
+    let unit = parse(
+        "public class C { List<Contact> m() { return [SELECT Id FROM Contact WHERE AccountId = :acc.Id]; } }",
+    )
+    .unwrap();
+    let folded = FirstAccount.fold_compilation_unit(unit);
+    let ts = transpile(&folded).unwrap();
+    assert!(ts.contains("AccountId = :accounts[0].Id"), "{}", ts);
+}
@@ -0,0 +1,50 @@
+//! Property tests over the lexer's keyword table: every contextual keyword
+//! names fields, methods and variables, and no reserved keyword does
+
+use apexrust::lexer::{TokenClass, KEYWORDS};
+use apexrust::{parse, tokenize, ParseError};
+
+fn template(name: &str) -> String {
+    format!("public class C {{ Integer {name}; void {name}() {{ {name} = 1; this.{name}(); }} }}")
+}
+
+#[test]
+fn test_keyword_table_matches_lexer() {
+    for (kind, text, _) in KEYWORDS {
+        for spelling in [text.to_string(), text.to_uppercase()] {
+            let tokens = tokenize(&spelling);
+            assert_eq!(tokens.len(), 2, "{spelling}: {tokens:?}");
+            assert_eq!(&tokens[0].kind, kind, "{spelling}");
+        }
+    }
+}
+
+#[test]
+fn test_contextual_keywords_are_names() {
+    let failures: Vec<String> = KEYWORDS
+        .iter()
+        .filter(|(_, _, class)| *class == TokenClass::ContextualKeyword)
+        .filter_map(|(_, text, _)| parse(&template(text)).err().map(|e| format!("{text}: {e}")))
+        .collect();
+    assert!(failures.is_empty(), "{failures:#?}");
+}
+
+#[test]
+fn test_reserved_keywords_are_not_names() {
+    for (_, text, _) in KEYWORDS
+        .iter()
+        .filter(|(_, _, class)| *class == TokenClass::ReservedAlways)
+    {
+        match parse(&template(text)) {
+            Err(ParseError::UnexpectedToken { .. }) => {}
+            other => panic!("{text}: expected UnexpectedToken, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_reserved_keywords_name_members_after_dot() {
+    let unit =
+        parse("public class C { void run() { Object a = Trigger.new; Type t = Account.class; } }");
+    assert!(unit.is_ok(), "{:?}", unit.err());
+}
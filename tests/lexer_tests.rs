@@ -1,4 +1,7 @@
-use apexrust::{tokenize, TokenKind};
+// Literal values like 3.14 are lexer inputs, not approximations of PI.
+#![allow(clippy::approx_constant)]
+
+use apexrust::{tokenize, tokenize_with_trivia, Lexer, LineIndex, TokenKind};
 
 // ==================== Keyword Tests ====================
 
@@ -530,3 +533,141 @@ fn test_nested_generic_tokens() {
     assert_eq!(tokens[6].kind, TokenKind::Integer);
     assert_eq!(tokens[7].kind, TokenKind::GtGt); // This is tokenized as >> not > >
 }
+
+// ==================== Streaming Tests ====================
+
+#[test]
+fn test_token_stream_matches_next_token() {
+    let source = include_str!("apex_files/fflib_QueryFactory.cls");
+
+    let mut lexer = Lexer::new(source);
+    let mut expected = Vec::new();
+    loop {
+        let token = lexer.next_token();
+        let is_eof = token.kind == TokenKind::Eof;
+        expected.push(token);
+        if is_eof {
+            break;
+        }
+    }
+
+    let streamed: Vec<_> = Lexer::new(source).tokens().collect();
+    assert!(streamed.len() > 1000);
+    assert_eq!(streamed, expected);
+    assert_eq!(tokenize(source), expected);
+}
+
+#[test]
+fn test_token_stream_ends_after_one_eof() {
+    let mut tokens = Lexer::new("Integer x;").tokens();
+    let kinds: Vec<_> = tokens.by_ref().map(|t| t.kind).collect();
+    assert_eq!(kinds.last(), Some(&TokenKind::Eof));
+    assert_eq!(kinds.iter().filter(|k| **k == TokenKind::Eof).count(), 1);
+    assert!(tokens.next().is_none());
+}
+
+#[test]
+fn test_token_stream_adapters() {
+    let source = "public class A { Integer x = 1; } // trailing";
+    let before_body: Vec<_> = Lexer::new(source)
+        .tokens()
+        .take_while(|t| t.kind != TokenKind::LBrace)
+        .map(|t| t.kind)
+        .collect();
+    assert_eq!(
+        before_body,
+        [
+            TokenKind::Public,
+            TokenKind::Class,
+            TokenKind::Identifier("A".to_string())
+        ]
+    );
+
+    let integers = Lexer::new(source)
+        .tokens()
+        .filter(|t| matches!(t.kind, TokenKind::IntegerLiteral(_)))
+        .count();
+    assert_eq!(integers, 1);
+}
+
+// ==================== Position Tests ====================
+
+/// (line, column) of every token
+fn positions(source: &str) -> Vec<(usize, usize)> {
+    tokenize(source)
+        .iter()
+        .map(|t| (t.span.line, t.span.column))
+        .collect()
+}
+
+#[test]
+fn test_token_positions() {
+    let source = "class A {\n    Integer x;\n}";
+    assert_eq!(
+        positions(source),
+        [
+            (1, 1),
+            (1, 7),
+            (1, 9),
+            (2, 5),
+            (2, 13),
+            (2, 14),
+            (3, 1),
+            (3, 2)
+        ]
+    );
+}
+
+#[test]
+fn test_positions_with_crlf_and_tabs() {
+    // \r belongs to the line it ends; a tab is one column
+    let source = "class A {\r\n\tInteger x;\r\n}";
+    assert_eq!(
+        positions(source),
+        [
+            (1, 1),
+            (1, 7),
+            (1, 9),
+            (2, 2),
+            (2, 10),
+            (2, 11),
+            (3, 1),
+            (3, 2)
+        ]
+    );
+}
+
+#[test]
+fn test_positions_after_multiline_comment() {
+    let source = "/* one\r\n   two */ Integer\r\n  /**\n   * doc\n   */ x;";
+    assert_eq!(positions(source), [(2, 11), (5, 7), (5, 8), (5, 9)]);
+
+    let tokens = tokenize_with_trivia(source);
+    let spans: Vec<_> = tokens
+        .iter()
+        .map(|t| (t.span.line, t.span.column))
+        .collect();
+    assert_eq!(spans[..3], [(1, 1), (2, 11), (3, 3)]);
+}
+
+#[test]
+fn test_positions_count_characters() {
+    let source = "String s = 'é€'; x";
+    let last = &tokenize(source)[5];
+    assert_eq!(last.kind, TokenKind::Identifier("x".to_string()));
+    assert_eq!((last.span.line, last.span.column), (1, 18));
+}
+
+#[test]
+fn test_line_index_matches_lexer() {
+    let source = include_str!("apex_files/fflib_QueryFactory.cls");
+    let index = LineIndex::new(source);
+    for token in tokenize_with_trivia(source) {
+        assert_eq!(
+            index.position(token.span),
+            (token.span.line, token.span.column),
+            "{:?}",
+            token
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! Tests for deprecation and TODO markers
+
+use apexrust::analysis::markers::{find_todos, DeprecatedKind, MarkerReport, TodoKind};
+
+fn report(sources: &[&str]) -> MarkerReport {
+    MarkerReport::from_sources(sources).expect("Parse failed")
+}
+
+// ==================== Deprecations ====================
+
+#[test]
+fn test_deprecated_method_call_sites() {
+    let legacy = r#"
+        public class LegacyPricing {
+            @Deprecated
+            public static Decimal discount(Decimal amount) { return amount * 0.9; }
+
+            public static Decimal total(Decimal amount) {
+                return discount(amount);
+            }
+        }
+    "#;
+    let caller = r#"
+        public class Checkout {
+            public Decimal price(Decimal amount) {
+                return LegacyPricing.discount(amount) + LegacyPricing.total(amount);
+            }
+        }
+    "#;
+    let report = report(&[legacy, caller]);
+
+    assert_eq!(report.deprecated.len(), 1);
+    assert_eq!(report.deprecated[0].kind, DeprecatedKind::Method);
+    assert_eq!(report.deprecated[0].name, "LegacyPricing.discount(Decimal)");
+
+    let sites: Vec<(usize, &str)> = report
+        .diagnostics
+        .iter()
+        .map(|d| (d.unit, d.message.as_str()))
+        .collect();
+    assert_eq!(
+        sites,
+        [
+            (
+                0,
+                "call to deprecated method LegacyPricing.discount(Decimal)"
+            ),
+            (
+                1,
+                "call to deprecated method LegacyPricing.discount(Decimal)"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_deprecated_overload_only_flags_matching_calls() {
+    let source = r#"
+        public class Formatter {
+            @Deprecated
+            public String format(Integer value) { return String.valueOf(value); }
+            public String format(String value) { return value; }
+            public String format(String value, Integer width) { return value; }
+
+            public void run(Formatter other) {
+                String label = 'x';
+                format(42);
+                format(label);
+                format('a', 3);
+                other.format(7);
+                this.format('b');
+            }
+        }
+    "#;
+    let report = report(&[source]);
+
+    let spans: Vec<&str> = report
+        .diagnostics
+        .iter()
+        .map(|d| source[d.span.start..d.span.end].trim_end_matches(';'))
+        .collect();
+    assert_eq!(spans, ["format(42)", "other.format(7)"]);
+    assert!(report
+        .diagnostics
+        .iter()
+        .all(|d| d.target == "Formatter.format(Integer)"));
+}
+
+#[test]
+fn test_receiver_types_resolve_through_variables() {
+    let source = r#"
+        public class Service {
+            @Deprecated
+            public void sync() {}
+        }
+
+        public class Job {
+            private Service field;
+
+            public void execute(List<Service> services) {
+                Service local = new Service();
+                local.sync();
+                field.sync();
+                for (Service s : services) {
+                    s.sync();
+                }
+                new Service().sync();
+                unknownThing.sync();
+            }
+        }
+    "#;
+    let report = report(&[source]);
+
+    assert_eq!(report.diagnostics.len(), 4);
+}
+
+#[test]
+fn test_deprecated_call_in_query_bind() {
+    let source = r#"
+        public class Accounts {
+            @Deprecated
+            public static Set<Id> activeIds() { return new Set<Id>(); }
+
+            public List<Account> active() {
+                return [SELECT Id FROM Account WHERE Id IN :Accounts.activeIds()];
+            }
+        }
+    "#;
+    let report = report(&[source]);
+
+    let spans: Vec<&str> = report
+        .diagnostics
+        .iter()
+        .map(|d| source[d.span.start..d.span.end].trim_end_matches(']'))
+        .collect();
+    assert_eq!(spans, ["Accounts.activeIds()"]);
+}
+
+#[test]
+fn test_deprecated_class_instantiation() {
+    let source = r#"
+        @Deprecated
+        public class OldApi {
+            public void call() {}
+        }
+
+        public class Client {
+            public void go() {
+                OldApi api = new OldApi();
+                api.call();
+            }
+        }
+    "#;
+    let report = report(&[source]);
+
+    assert_eq!(report.deprecated[0].kind, DeprecatedKind::Class);
+    assert_eq!(report.diagnostics.len(), 1);
+    assert_eq!(
+        report.diagnostics[0].message,
+        "use of deprecated class OldApi"
+    );
+}
+
+// ==================== TODO Comments ====================
+
+#[test]
+fn test_todo_extraction_with_owner() {
+    let source = r#"
+        public class Work {
+            // TODO(jsmith): batch these queries
+            public void run() {
+                String s = '// TODO not a comment';
+                // FIXME: handle nulls
+                /*
+                 * TODO(ops team) move to custom metadata
+                 */
+                // todo lowercase prose is ignored
+                // TODOS are not markers
+            }
+        }
+    "#;
+    let todos = find_todos(source);
+
+    assert_eq!(todos.len(), 3);
+
+    assert_eq!(todos[0].kind, TodoKind::Todo);
+    assert_eq!(todos[0].owner.as_deref(), Some("jsmith"));
+    assert_eq!(todos[0].text, "batch these queries");
+    assert_eq!(
+        &source[todos[0].span.start..todos[0].span.end],
+        "TODO(jsmith): batch these queries"
+    );
+
+    assert_eq!(todos[1].kind, TodoKind::Fixme);
+    assert_eq!(todos[1].owner, None);
+    assert_eq!(todos[1].text, "handle nulls");
+
+    assert_eq!(todos[2].owner.as_deref(), Some("ops team"));
+    assert_eq!(todos[2].text, "move to custom metadata");
+}
+
+#[test]
+fn test_todo_in_doc_comment() {
+    let source = "/** TODO: describe the service */\npublic class Service { }";
+    let todos = find_todos(source);
+
+    assert_eq!(todos.len(), 1);
+    assert_eq!(
+        &source[todos[0].span.start..todos[0].span.end],
+        "TODO: describe the service"
+    );
+}
+
+#[test]
+fn test_report_tags_todos_with_unit() {
+    let report = report(&["public class A {}", "public class B { /* TODO: split */ }"]);
+
+    assert_eq!(report.todos.len(), 1);
+    assert_eq!(report.todos[0].unit, 1);
+    assert_eq!(report.todos[0].text, "split");
+}
@@ -12,6 +12,7 @@ fn parses_ok(source: &str) -> bool {
 }
 
 /// Wrap code in a class for testing
+#[allow(dead_code)]
 fn wrap_in_class(code: &str) -> String {
     format!("public class Test {{ {} }}", code)
 }
@@ -116,6 +117,16 @@ fn test_soql_having() {
     assert!(parses_ok(&wrap_in_method("AggregateResult[] results = [SELECT Industry, COUNT(Id) cnt FROM Account GROUP BY Industry HAVING cnt > 5];")));
 }
 
+#[test]
+fn test_soql_group_by_rollup_and_cube() {
+    assert!(parses_ok(&wrap_in_method("AggregateResult[] results = [SELECT Industry, Type, GROUPING(Industry) grpIndustry, COUNT(Id) FROM Account GROUP BY ROLLUP(Industry, Type)];")));
+    assert!(parses_ok(&wrap_in_method("AggregateResult[] results = [SELECT Industry, Type, COUNT(Id) FROM Account GROUP BY CUBE(Industry, Type)];")));
+    // Neither keyword is reserved
+    assert!(parses_ok(&wrap_in_method(
+        "AggregateResult[] results = [SELECT Rollup__c, COUNT(Id) FROM Account GROUP BY Cube];"
+    )));
+}
+
 // ==================== SOQL Aggregate Function Tests ====================
 
 #[test]
@@ -194,6 +205,13 @@ fn test_soql_for_reference() {
     assert!(parses_ok(&wrap_in_method("List<Account> accs = [SELECT Id FROM Account FOR REFERENCE];")));
 }
 
+#[test]
+fn test_soql_using_scope() {
+    assert!(parses_ok(&wrap_in_method(
+        "List<Account> accs = [SELECT Id FROM Account USING SCOPE mine WHERE Name != null];"
+    )));
+}
+
 // ==================== SOQL Date Literal Tests ====================
 
 #[test]
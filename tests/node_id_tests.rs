@@ -0,0 +1,187 @@
+//! Node ids assigned by the parser, kept through rewrites, keying side tables
+
+use std::collections::HashSet;
+
+use apexrust::ast::fold::{self, Folder};
+use apexrust::ast::visitor::{walk_compilation_unit, Visitor};
+use apexrust::{parse, Block, Expression, MethodCallExpr, NodeId, SideTable, Statement};
+
+const SOURCE: &str = r#"
+public class Loader {
+    private Integer size = Limits.getLimitQueryRows();
+
+    public List<Contact> load(Set<Id> ids) {
+        List<Contact> contacts = [SELECT Id FROM Contact WHERE AccountId IN :ids];
+        for (Contact c : contacts) {
+            System.debug(c.Name);
+        }
+        refresh();
+        return contacts;
+    }
+}
+"#;
+
+/// Ids of every statement and expression, including those in queries
+#[derive(Default)]
+struct Ids(Vec<NodeId>);
+
+impl<'ast> Visitor<'ast> for Ids {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        self.0.push(stmt.id());
+    }
+
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        self.0.push(expr.id());
+    }
+}
+
+/// Method call names by the call's id
+#[derive(Default)]
+struct CallNames(SideTable<String>);
+
+impl<'ast> Visitor<'ast> for CallNames {
+    fn visit_method_call(&mut self, call: &'ast MethodCallExpr) {
+        self.0.insert(call.id, call.name.clone());
+    }
+}
+
+/// Every id a fold reaches
+#[derive(Default)]
+struct FoldedIds(Vec<NodeId>);
+
+impl Folder for FoldedIds {
+    fn fold_node_id(&mut self, id: NodeId) -> NodeId {
+        self.0.push(id);
+        id
+    }
+}
+
+/// Runs every expression statement twice, in a block the rewrite builds
+struct Twice;
+
+impl Folder for Twice {
+    fn fold_statement(&mut self, stmt: Statement) -> Statement {
+        match fold::fold_statement(self, stmt) {
+            Statement::Expression(stmt) => Statement::Block(Block {
+                span: stmt.span,
+                statements: vec![
+                    Statement::Expression(stmt.clone()),
+                    Statement::Expression(stmt),
+                ],
+                id: NodeId::DUMMY,
+            }),
+            other => other,
+        }
+    }
+}
+
+#[test]
+fn test_parsed_ids_are_unique() {
+    let unit = parse(SOURCE).expect("Parse failed");
+    let mut ids = Ids::default();
+    walk_compilation_unit(&mut ids, &unit);
+
+    assert_eq!(ids.0.len(), 19);
+    let unique: HashSet<_> = ids.0.iter().copied().collect();
+    assert_eq!(unique.len(), ids.0.len());
+    assert!(ids.0.iter().all(|id| *id < unit.next_id));
+    assert!(!unique.contains(&NodeId::DUMMY));
+
+    let mut renumbered = unit.clone();
+    assert!(renumbered.renumber().is_empty());
+    assert_eq!(renumbered, unit);
+}
+
+#[test]
+fn test_fold_reaches_every_id() {
+    let unit = parse(SOURCE).expect("Parse failed");
+    let mut ids = FoldedIds::default();
+    let folded = ids.fold_compilation_unit(unit.clone());
+    assert_eq!(folded, unit);
+
+    // The nodes a bind path expands into while folding have no ids
+    let reached: Vec<_> = ids
+        .0
+        .into_iter()
+        .filter(|id| *id != NodeId::DUMMY)
+        .collect();
+    let unique: HashSet<_> = reached.iter().copied().collect();
+    assert_eq!(unique.len(), reached.len());
+    assert!(reached.iter().all(|id| *id < unit.next_id));
+
+    // Type references, parameters and declarators besides every statement
+    // and expression
+    let mut visited = Ids::default();
+    walk_compilation_unit(&mut visited, &unit);
+    assert!(visited.0.iter().all(|id| unique.contains(id)));
+    assert_eq!(reached.len(), 34);
+}
+
+#[test]
+fn test_synthesized_nodes_get_fresh_ids() {
+    let mut unit = parse(SOURCE).expect("Parse failed");
+    let next = unit.next_id;
+    assert_eq!(unit.next_node_id(), next);
+    assert_eq!(unit.next_node_id(), NodeId(next.0 + 1));
+    assert_eq!(unit.next_id, NodeId(next.0 + 2));
+}
+
+#[test]
+fn test_fold_with_ids_maps_copies_to_originals() {
+    let unit = parse(SOURCE).expect("Parse failed");
+    let mut names = CallNames::default();
+    walk_compilation_unit(&mut names, &unit);
+    let before = unit.next_id;
+
+    let (unit, copies) = fold::fold_with_ids(&mut Twice, unit);
+
+    // `System.debug(c.Name)` and `refresh()`: the statement, the call and
+    // everything below each call are copied once
+    assert!(!copies.is_empty());
+    assert!(copies.keys().all(|copy| *copy >= before));
+    assert!(copies.values().all(|original| *original < before));
+
+    let mut ids = Ids::default();
+    walk_compilation_unit(&mut ids, &unit);
+    let unique: HashSet<_> = ids.0.iter().copied().collect();
+    assert_eq!(unique.len(), ids.0.len());
+    assert!(!unique.contains(&NodeId::DUMMY));
+
+    // The copied calls find the names recorded for their originals
+    let mut table = names.0.clone();
+    table.extend_to_copies(&copies);
+    let mut after = CallNames::default();
+    walk_compilation_unit(&mut after, &unit);
+    assert_eq!(after.0.len(), names.0.len() + 2);
+    for (id, name) in after.0.iter() {
+        assert_eq!(table.get(id), Some(name));
+    }
+}
+
+#[test]
+fn test_side_table_round_trip() {
+    let mut table = SideTable::new();
+    assert!(table.is_empty());
+    assert_eq!(table.insert(NodeId(7), "seven"), None);
+    assert_eq!(table.insert(NodeId(2), "two"), None);
+    assert_eq!(table.insert(NodeId(7), "SEVEN"), Some("seven"));
+    assert_eq!(table.len(), 2);
+
+    assert_eq!(table.get(NodeId(7)), Some(&"SEVEN"));
+    assert_eq!(table.get(NodeId(3)), None);
+    assert_eq!(table.get(NodeId(100)), None);
+    *table.get_mut(NodeId(2)).unwrap() = "deux";
+    assert_eq!(
+        table.iter().collect::<Vec<_>>(),
+        [(NodeId(2), &"deux"), (NodeId(7), &"SEVEN")]
+    );
+
+    assert_eq!(table.remove(NodeId(2)), Some("deux"));
+    assert_eq!(table.remove(NodeId(2)), None);
+    assert!(!table.contains(NodeId(2)));
+    assert_eq!(table.values().collect::<Vec<_>>(), [&"SEVEN"]);
+
+    let collected: SideTable<_> = table.iter().map(|(id, v)| (id, v.len())).collect();
+    assert_eq!(collected.get(NodeId(7)), Some(&5));
+    assert_eq!(collected.len(), 1);
+}
@@ -1,4 +1,6 @@
-use apexrust::{parse, TypeDeclaration, ClassMember};
+use apexrust::{
+    parse, parse_with_recovery, AsyncKind, ClassMember, ParseError, Statement, TypeDeclaration,
+};
 
 /// Helper to check if parsing succeeds
 fn parses_ok(source: &str) -> bool {
@@ -355,6 +357,60 @@ fn test_webservice_method() {
     assert!(parses_ok("global class Test { webservice static String doSomething() { return ''; } }"));
 }
 
+/// The first method declared by the first class in `source`
+fn first_method(source: &str) -> apexrust::MethodDeclaration {
+    let cu = parse(source).expect("Parse failed");
+    let TypeDeclaration::Class(class) = &cu.declarations[0] else {
+        panic!("expected a class");
+    };
+    class
+        .members
+        .iter()
+        .find_map(|member| match member {
+            ClassMember::Method(method) => Some(method.clone()),
+            _ => None,
+        })
+        .expect("no method")
+}
+
+#[test]
+fn test_generic_static_method() {
+    let method = first_method(
+        "public class Lists { public static <K, V> Map<K, List<V>> groupBy(List<V> items, K key) { return null; } }",
+    );
+    let names: Vec<_> = method
+        .type_parameters
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect();
+    assert_eq!(names, ["K", "V"]);
+    assert!(method.modifiers.is_static);
+    assert_eq!(method.name, "groupBy");
+    assert_eq!(method.return_type.name, "Map");
+    assert_eq!(method.parameters.len(), 2);
+}
+
+#[test]
+fn test_generic_instance_method() {
+    let method = first_method(
+        "public class Repo { public <TRecord> TRecord firstOrNull(List<TRecord> items) { return items.isEmpty() ? null : items[0]; } }",
+    );
+    assert_eq!(method.type_parameters.len(), 1);
+    assert_eq!(method.type_parameters[0].name, "TRecord");
+    assert!(!method.modifiers.is_static);
+    assert_eq!(method.return_type.name, "TRecord");
+    assert_eq!(
+        method.parameters[0].type_ref.type_arguments[0].name,
+        "TRecord"
+    );
+    assert!(method.body.is_some());
+}
+
+#[test]
+fn test_type_parameters_require_method() {
+    assert!(!parses_ok("public class Test { <T> T value; }"));
+}
+
 // ==================== Constructor Declaration Tests ====================
 
 #[test]
@@ -563,6 +619,133 @@ fn test_class_interface_enum() {
     assert_eq!(result.declarations.len(), 3);
 }
 
+#[test]
+fn test_triggers_and_class_in_one_source() {
+    let source = r#"
+        trigger AccountTrigger on Account (before insert) { }
+        trigger ContactTrigger on Contact (after update) { }
+        public class Helper { }
+    "#;
+    let result = parse(source).unwrap();
+    assert_eq!(result.declarations.len(), 3);
+    assert!(matches!(
+        result.declarations[0],
+        TypeDeclaration::Trigger(_)
+    ));
+    assert!(matches!(
+        result.declarations[1],
+        TypeDeclaration::Trigger(_)
+    ));
+    assert!(matches!(result.declarations[2], TypeDeclaration::Class(_)));
+}
+
+#[test]
+fn test_trailing_tokens_after_last_declaration() {
+    let source = "trigger AccountTrigger on Account (before insert) { }\n}\nupdate accounts;";
+    let err = parse(source).unwrap_err();
+    let ParseError::TrailingTokens { span, .. } = err else {
+        panic!("expected TrailingTokens, got {:?}", err);
+    };
+    assert_eq!(&source[span.start..span.end], "}");
+    assert!(err.to_string().contains("after the last declaration"));
+}
+
+#[test]
+fn test_error_message_has_line_and_column() {
+    let source = "public class A {\r\n    void m() {\r\n\t\tInteger x = 1\r\n\t}\r\n}";
+    let err = parse(source).unwrap_err();
+    let span = err.span().expect("span");
+    assert_eq!((span.line, span.column), (4, 2));
+    assert!(err.to_string().ends_with("found RBrace at 4:2"), "{}", err);
+}
+
+#[test]
+fn test_split_shift_token_keeps_column() {
+    // The second `>` of `>>` closes the outer type and the third is stray
+    let err = parse("class A { Map<String, List<Integer>>> m; }").unwrap_err();
+    let span = err.span().expect("span");
+    assert_eq!((span.line, span.column), (1, 37));
+    assert_eq!(span.start, 36);
+}
+
+// ==================== Recovery Tests ====================
+
+#[test]
+fn test_recovery_reports_each_error() {
+    let source = r#"
+        public class Broken {
+            public void first() {
+                Integer x = ;
+                System.debug('still here');
+            }
+            public Integer count
+            public void skipped() { }
+            public String name;
+        }
+        public class Valid {
+            public void ok() { }
+        }
+        public class AlsoBroken {
+            public Integer second() {
+                return 1 2;
+            }
+            public void last() { }
+        }
+    "#;
+    let (unit, errors) = parse_with_recovery(source);
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(parse(source).is_err());
+
+    let classes: Vec<_> = unit
+        .declarations
+        .iter()
+        .map(|declaration| match declaration {
+            TypeDeclaration::Class(class) => class,
+            other => panic!("expected a class, got {:?}", other),
+        })
+        .collect();
+    let names: Vec<&str> = classes.iter().map(|class| class.name.as_str()).collect();
+    assert_eq!(names, ["Broken", "Valid", "AlsoBroken"]);
+
+    // The bad statement is dropped, the rest of its block is kept
+    let ClassMember::Method(first) = &classes[0].members[0] else {
+        panic!("expected a method");
+    };
+    let statements = &first.body.as_ref().unwrap().statements;
+    assert_eq!(statements.len(), 1);
+    assert!(matches!(statements[0], Statement::Expression(_)));
+
+    // The unterminated field swallows the member after it
+    assert!(
+        matches!(&classes[0].members[1], ClassMember::Field(field) if field.declarators[0].name == "name")
+    );
+    assert_eq!(classes[0].members.len(), 2);
+    assert_eq!(classes[2].members.len(), 2);
+}
+
+#[test]
+fn test_recovery_skips_to_next_declaration() {
+    let source = "public class A extends { void m() { } }\npublic class B { }";
+    let (unit, errors) = parse_with_recovery(source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(unit.declarations.len(), 1);
+    assert!(matches!(&unit.declarations[0], TypeDeclaration::Class(class) if class.name == "B"));
+}
+
+#[test]
+fn test_recovery_without_errors_matches_parse() {
+    let source = r#"
+        public enum Status { ACTIVE, INACTIVE }
+        public class Service {
+            private Integer count = 0;
+            public void run() { count++; }
+        }
+    "#;
+    let (unit, errors) = parse_with_recovery(source);
+    assert!(errors.is_empty());
+    assert_eq!(unit, parse(source).unwrap());
+}
+
 // ==================== Complex Class Tests ====================
 
 #[test]
@@ -722,3 +905,90 @@ fn test_queueable_class() {
     "#;
     assert!(parses_ok(source));
 }
+
+#[test]
+fn test_method_async_kind() {
+    let source = r#"
+        public class Notifier implements Queueable {
+            @future
+            public static void recalculate(Set<Id> ids) { }
+
+            @future(callout=true)
+            public static void sync(Set<Id> ids) { }
+
+            public void execute(QueueableContext context) { }
+
+            public void notify() { }
+        }
+    "#;
+    let cu = parse(source).expect("Failed to parse");
+    let TypeDeclaration::Class(class) = &cu.declarations[0] else {
+        panic!("Expected class");
+    };
+    let kinds: Vec<Option<AsyncKind>> = class
+        .members
+        .iter()
+        .filter_map(|m| match m {
+            ClassMember::Method(method) => Some(method.async_kind(&class.implements)),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            Some(AsyncKind::Future { callout: false }),
+            Some(AsyncKind::Future { callout: true }),
+            Some(AsyncKind::Queueable),
+            None,
+        ]
+    );
+
+    let batch = parse(
+        "public class Cleanup implements Database.Batchable<sObject> { public void finish(Database.BatchableContext bc) { } }",
+    )
+    .expect("Failed to parse");
+    let TypeDeclaration::Class(class) = &batch.declarations[0] else {
+        panic!("Expected class");
+    };
+    let ClassMember::Method(finish) = &class.members[0] else {
+        panic!("Expected method");
+    };
+    assert_eq!(finish.async_kind(&class.implements), Some(AsyncKind::Batch));
+}
+
+#[test]
+fn test_method_async_kind_batchable_and_schedulable() {
+    let source = r#"
+        public class Nightly implements Database.Batchable<SObject>, Schedulable {
+            public Database.QueryLocator start(Database.BatchableContext bc) { return null; }
+            public void execute(Database.BatchableContext bc, List<SObject> scope) { }
+            public void execute(SchedulableContext sc) { }
+            public void finish(Database.BatchableContext bc) { }
+            public void execute(String label) { }
+        }
+    "#;
+    let cu = parse(source).expect("Failed to parse");
+    let TypeDeclaration::Class(class) = &cu.declarations[0] else {
+        panic!("Expected class");
+    };
+    let kinds: Vec<Option<AsyncKind>> = class
+        .members
+        .iter()
+        .filter_map(|m| match m {
+            ClassMember::Method(method) => Some(method.async_kind(&class.implements)),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            Some(AsyncKind::Batch),
+            Some(AsyncKind::Batch),
+            Some(AsyncKind::Schedulable),
+            Some(AsyncKind::Batch),
+            None,
+        ]
+    );
+}
@@ -1,3 +1,6 @@
+// Literal values like 3.14 are lexer inputs, not approximations of PI.
+#![allow(clippy::approx_constant)]
+
 use apexrust::{parse, Expression, Statement, TypeDeclaration, ClassMember};
 
 /// Helper to parse a single expression within a method
@@ -29,58 +32,73 @@ fn parses_ok(source: &str) -> bool {
 #[test]
 fn test_null_literal() {
     let expr = parse_expr("null");
-    assert!(matches!(expr, Expression::Null(_)));
+    assert!(matches!(expr, Expression::Null(..)));
 }
 
 #[test]
 fn test_boolean_literals() {
     let expr = parse_expr("true");
-    assert!(matches!(expr, Expression::Boolean(true, _)));
+    assert!(matches!(expr, Expression::Boolean(true, _, _)));
 
     let expr = parse_expr("false");
-    assert!(matches!(expr, Expression::Boolean(false, _)));
+    assert!(matches!(expr, Expression::Boolean(false, _, _)));
 }
 
 #[test]
 fn test_integer_literals() {
     let expr = parse_expr("0");
-    assert!(matches!(expr, Expression::Integer(0, _)));
+    assert!(matches!(expr, Expression::Integer(0, ..)));
 
     let expr = parse_expr("42");
-    assert!(matches!(expr, Expression::Integer(42, _)));
+    assert!(matches!(expr, Expression::Integer(42, ..)));
 
     let expr = parse_expr("999999");
-    assert!(matches!(expr, Expression::Integer(999999, _)));
+    assert!(matches!(expr, Expression::Integer(999999, ..)));
 }
 
 #[test]
 fn test_long_literals() {
     let expr = parse_expr("0L");
-    assert!(matches!(expr, Expression::Long(0, _)));
+    assert!(matches!(expr, Expression::Long(0, ..)));
 
     let expr = parse_expr("42L");
-    assert!(matches!(expr, Expression::Long(42, _)));
+    assert!(matches!(expr, Expression::Long(42, ..)));
 }
 
 #[test]
 fn test_double_literals() {
     let expr = parse_expr("3.14");
-    assert!(matches!(expr, Expression::Double(n, _) if (n - 3.14).abs() < 0.001));
+    assert!(matches!(expr, Expression::Double(n, ..) if (n - 3.14).abs() < 0.001));
 
     let expr = parse_expr("0.0");
-    assert!(matches!(expr, Expression::Double(n, _) if n.abs() < 0.001));
+    assert!(matches!(expr, Expression::Double(n, ..) if n.abs() < 0.001));
+}
+
+#[test]
+fn test_numeric_literals_keep_source_text() {
+    let expr = parse_expr("0x1F");
+    assert!(matches!(expr, Expression::Integer(31, ref raw, _, _) if raw == "0x1F"));
+
+    let expr = parse_expr("017");
+    assert!(matches!(expr, Expression::Integer(15, ref raw, _, _) if raw == "017"));
+
+    let expr = parse_expr("10L");
+    assert!(matches!(expr, Expression::Long(10, ref raw, _, _) if raw == "10L"));
+
+    let expr = parse_expr("1.50");
+    assert!(matches!(expr, Expression::Double(_, ref raw, _, _) if raw == "1.50"));
 }
 
 #[test]
 fn test_string_literals() {
     let expr = parse_expr("'hello'");
-    assert!(matches!(expr, Expression::String(ref s, _) if s == "hello"));
+    assert!(matches!(expr, Expression::String(ref s, _, _) if s == "hello"));
 
     let expr = parse_expr("'hello world'");
-    assert!(matches!(expr, Expression::String(ref s, _) if s == "hello world"));
+    assert!(matches!(expr, Expression::String(ref s, _, _) if s == "hello world"));
 
     let expr = parse_expr("''");
-    assert!(matches!(expr, Expression::String(ref s, _) if s.is_empty()));
+    assert!(matches!(expr, Expression::String(ref s, _, _) if s.is_empty()));
 }
 
 // ==================== Identifier and Access Tests ====================
@@ -88,7 +106,7 @@ fn test_string_literals() {
 #[test]
 fn test_simple_identifier() {
     let expr = parse_expr("myVar");
-    assert!(matches!(expr, Expression::Identifier(ref s, _) if s == "myVar"));
+    assert!(matches!(expr, Expression::Identifier(ref s, _, _) if s == "myVar"));
 }
 
 #[test]
@@ -109,8 +127,8 @@ fn test_super_expression() {
 fn test_addition() {
     let expr = parse_expr("1 + 2");
     if let Expression::Binary(bin) = expr {
-        assert!(matches!(bin.left, Expression::Integer(1, _)));
-        assert!(matches!(bin.right, Expression::Integer(2, _)));
+        assert!(matches!(bin.left, Expression::Integer(1, ..)));
+        assert!(matches!(bin.right, Expression::Integer(2, ..)));
     } else {
         panic!("Expected binary expression");
     }
@@ -120,8 +138,8 @@ fn test_addition() {
 fn test_subtraction() {
     let expr = parse_expr("5 - 3");
     if let Expression::Binary(bin) = expr {
-        assert!(matches!(bin.left, Expression::Integer(5, _)));
-        assert!(matches!(bin.right, Expression::Integer(3, _)));
+        assert!(matches!(bin.left, Expression::Integer(5, ..)));
+        assert!(matches!(bin.right, Expression::Integer(3, ..)));
     } else {
         panic!("Expected binary expression");
     }
@@ -131,8 +149,8 @@ fn test_subtraction() {
 fn test_multiplication() {
     let expr = parse_expr("4 * 5");
     if let Expression::Binary(bin) = expr {
-        assert!(matches!(bin.left, Expression::Integer(4, _)));
-        assert!(matches!(bin.right, Expression::Integer(5, _)));
+        assert!(matches!(bin.left, Expression::Integer(4, ..)));
+        assert!(matches!(bin.right, Expression::Integer(5, ..)));
     } else {
         panic!("Expected binary expression");
     }
@@ -142,8 +160,8 @@ fn test_multiplication() {
 fn test_division() {
     let expr = parse_expr("10 / 2");
     if let Expression::Binary(bin) = expr {
-        assert!(matches!(bin.left, Expression::Integer(10, _)));
-        assert!(matches!(bin.right, Expression::Integer(2, _)));
+        assert!(matches!(bin.left, Expression::Integer(10, ..)));
+        assert!(matches!(bin.right, Expression::Integer(2, ..)));
     } else {
         panic!("Expected binary expression");
     }
@@ -154,7 +172,7 @@ fn test_operator_precedence_mult_over_add() {
     // 1 + 2 * 3 should be parsed as 1 + (2 * 3)
     let expr = parse_expr("1 + 2 * 3");
     if let Expression::Binary(bin) = expr {
-        assert!(matches!(bin.left, Expression::Integer(1, _)));
+        assert!(matches!(bin.left, Expression::Integer(1, ..)));
         assert!(matches!(bin.right, Expression::Binary(_)));
     } else {
         panic!("Expected binary expression");
@@ -166,8 +184,8 @@ fn test_operator_precedence_with_parens() {
     // (1 + 2) * 3 should parse correctly
     let expr = parse_expr("(1 + 2) * 3");
     if let Expression::Binary(bin) = expr {
-        assert!(matches!(bin.left, Expression::Parenthesized(_, _)));
-        assert!(matches!(bin.right, Expression::Integer(3, _)));
+        assert!(matches!(bin.left, Expression::Parenthesized(..)));
+        assert!(matches!(bin.right, Expression::Integer(3, ..)));
     } else {
         panic!("Expected binary expression");
     }
@@ -485,6 +503,26 @@ fn test_map_access() {
     assert!(parses_ok(source));
 }
 
+#[test]
+fn test_array_element_assignment_statement() {
+    let source = "public class Test { public void test() { values[i++] = v; values[0] += 1; String[] names; } }";
+    let cu = parse(source).expect("Failed to parse");
+    if let TypeDeclaration::Class(class) = &cu.declarations[0] {
+        if let ClassMember::Method(method) = &class.members[0] {
+            let statements = &method.body.as_ref().unwrap().statements;
+            assert!(
+                matches!(&statements[0], Statement::Expression(e) if matches!(e.expression, Expression::Assignment(_)))
+            );
+            assert!(matches!(&statements[1], Statement::Expression(_)));
+            assert!(
+                matches!(&statements[2], Statement::LocalVariable(var) if var.type_ref.is_array)
+            );
+            return;
+        }
+    }
+    panic!("Could not extract statements");
+}
+
 // ==================== Object Creation Tests ====================
 
 #[test]
@@ -647,6 +685,30 @@ fn test_soql_complex() {
     assert!(parses_ok(source));
 }
 
+#[test]
+fn test_soql_bind_expression() {
+    let expr =
+        parse_expr("[SELECT Id FROM Account WHERE Id IN :byId.keySet() AND Name = :acc.Name]");
+    let Expression::Soql(query) = expr else {
+        panic!("Expected SOQL query");
+    };
+    let Some(Expression::Binary(and)) = &query.where_clause else {
+        panic!("Expected AND");
+    };
+    let (Expression::Binary(in_ids), Expression::Binary(by_name)) = (&and.left, &and.right) else {
+        panic!("Expected two comparisons");
+    };
+    match &in_ids.right {
+        Expression::BindExpression(bound, _, _) => {
+            assert!(
+                matches!(bound.as_ref(), Expression::MethodCall(call) if call.name == "keySet")
+            );
+        }
+        other => panic!("Expected bind expression, got {:?}", other),
+    }
+    assert!(matches!(&by_name.right, Expression::BindVariable(name, _, _) if name == "acc.Name"));
+}
+
 // ==================== String Concatenation Tests ====================
 
 #[test]
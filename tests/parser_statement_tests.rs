@@ -144,6 +144,13 @@ fn test_for_loop_multiple_init() {
     assert!(parses_ok(&wrap_statements("for (Integer i = 0, j = 10; i < j; i++, j--) { }")));
 }
 
+#[test]
+fn test_for_loop_expression_init() {
+    assert!(parses_ok(&wrap_statements(
+        "for (i = 0, j = 10; i < j; i++, j--) { }"
+    )));
+}
+
 #[test]
 fn test_for_loop_complex_condition() {
     assert!(parses_ok(&wrap_statements("for (Integer i = 0; i < 10 && flag; i++) { }")));
@@ -257,6 +264,57 @@ fn test_switch_with_type_check() {
     assert!(parses_ok(&wrap_statements("switch on obj { when Account a { } when Contact c { } when else { } }")));
 }
 
+#[test]
+fn test_switch_on_object_with_when_null() {
+    let stmt = first_statement(
+        "switch on obj { when Account a { a.Name = 'x'; update a; } when null { skip(); } when else { log(obj); fail(); } }",
+    );
+    let apexrust::Statement::Switch(switch) = stmt else {
+        panic!("expected switch, got {:?}", stmt);
+    };
+    assert_eq!(switch.when_clauses.len(), 3);
+    assert!(matches!(
+        &switch.when_clauses[0].values,
+        apexrust::WhenValue::Type { variable, .. } if variable == "a"
+    ));
+    assert_eq!(switch.when_clauses[0].block.statements.len(), 2);
+    assert!(matches!(
+        &switch.when_clauses[1].values,
+        apexrust::WhenValue::Literals(values) if matches!(values[..], [apexrust::Expression::Null(..)])
+    ));
+    assert_eq!(switch.when_clauses[1].block.statements.len(), 1);
+    assert_eq!(switch.when_clauses[2].values, apexrust::WhenValue::Else);
+    assert_eq!(switch.when_clauses[2].block.statements.len(), 2);
+}
+
+#[test]
+fn test_switch_null_in_value_list() {
+    let stmt = first_statement(
+        "switch on count { when 1, null { a(); } when null, 2, 3 { b(); } when SPRING, null { c(); } when else { } }",
+    );
+    let apexrust::Statement::Switch(switch) = stmt else {
+        panic!("expected switch, got {:?}", stmt);
+    };
+    let nulls: Vec<Vec<bool>> = switch.when_clauses[..3]
+        .iter()
+        .map(|when| match &when.values {
+            apexrust::WhenValue::Literals(values) => values
+                .iter()
+                .map(|v| matches!(v, apexrust::Expression::Null(..)))
+                .collect(),
+            other => panic!("expected literals, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        nulls,
+        [
+            vec![false, true],
+            vec![true, false, false],
+            vec![false, true]
+        ]
+    );
+}
+
 // ==================== Try-Catch-Finally Tests ====================
 
 #[test]
@@ -295,6 +353,60 @@ fn test_try_catch_with_throw() {
     )));
 }
 
+/// Catch clauses of the try statement in `stmts`
+fn catch_clauses(stmts: &str) -> Vec<apexrust::CatchClause> {
+    let cu = parse(&wrap_statements(stmts)).expect("Parse failed");
+    let apexrust::TypeDeclaration::Class(class) = &cu.declarations[0] else {
+        panic!("expected a class");
+    };
+    let apexrust::ClassMember::Method(method) = &class.members[0] else {
+        panic!("expected a method");
+    };
+    match &method.body.as_ref().unwrap().statements[0] {
+        apexrust::Statement::Try(try_stmt) => try_stmt.catch_clauses.clone(),
+        other => panic!("expected try, got {:?}", other),
+    }
+}
+
+fn caught_types(clause: &apexrust::CatchClause) -> Vec<&str> {
+    clause.exception_types().map(|t| t.name.as_str()).collect()
+}
+
+#[test]
+fn test_single_type_catch_has_no_alternatives() {
+    let clauses = catch_clauses("try { doSomething(); } catch (DmlException e) { }");
+    assert_eq!(clauses[0].exception_type.name, "DmlException");
+    assert!(clauses[0].alternative_types.is_empty());
+    assert_eq!(clauses[0].variable, "e");
+}
+
+#[test]
+fn test_two_type_union_catch() {
+    let clauses = catch_clauses(
+        "try { doSomething(); } catch (DmlException | QueryException e) { } catch (Exception other) { }"
+    );
+    assert_eq!(clauses.len(), 2);
+    assert_eq!(clauses[0].exception_type.name, "DmlException");
+    assert_eq!(
+        caught_types(&clauses[0]),
+        ["DmlException", "QueryException"]
+    );
+    assert_eq!(clauses[0].variable, "e");
+    assert_eq!(caught_types(&clauses[1]), ["Exception"]);
+}
+
+#[test]
+fn test_three_type_union_catch() {
+    let clauses = catch_clauses(
+        "try { doSomething(); } catch (DmlException | System.QueryException | CalloutException ex) { throw ex; }"
+    );
+    assert_eq!(
+        caught_types(&clauses[0]),
+        ["DmlException", "System.QueryException", "CalloutException"]
+    );
+    assert_eq!(clauses[0].variable, "ex");
+}
+
 // ==================== Return Statement Tests ====================
 
 #[test]
@@ -367,6 +479,99 @@ fn test_continue_in_foreach() {
     assert!(parses_ok(&wrap_statements("for (String s : items) { if (s == null) continue; }")));
 }
 
+fn first_statement(stmts: &str) -> apexrust::Statement {
+    let cu = parse(&wrap_statements(stmts)).expect("Parse failed");
+    let apexrust::TypeDeclaration::Class(class) = &cu.declarations[0] else {
+        panic!("expected a class");
+    };
+    let apexrust::ClassMember::Method(method) = &class.members[0] else {
+        panic!("expected a method");
+    };
+    method.body.as_ref().unwrap().statements[0].clone()
+}
+
+/// The statements of a loop's braced body
+fn loop_body(stmt: &apexrust::Statement) -> Vec<apexrust::Statement> {
+    let body = match stmt {
+        apexrust::Statement::For(s) => &s.body,
+        apexrust::Statement::ForEach(s) => &s.body,
+        apexrust::Statement::While(s) => &s.body,
+        other => panic!("expected a loop, got {:?}", other),
+    };
+    match body.as_ref() {
+        apexrust::Statement::Block(block) => block.statements.clone(),
+        other => panic!("expected a block, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_labeled_for_with_labeled_break() {
+    let stmt = first_statement(
+        "outer: for (Integer i = 0; i < 10; i++) { \
+         for (Integer j = 0; j < i; j++) { if (j == 5) break outer; } }",
+    );
+    let apexrust::Statement::Labeled(labeled) = stmt else {
+        panic!("expected a labeled statement, got {:?}", stmt);
+    };
+    assert_eq!(labeled.label, "outer");
+
+    let inner = &loop_body(&labeled.body)[0];
+    let apexrust::Statement::If(if_stmt) = &loop_body(inner)[0] else {
+        panic!("expected if");
+    };
+    assert!(matches!(
+        if_stmt.then_branch.as_ref(),
+        apexrust::Statement::Break(brk) if brk.label.as_deref() == Some("outer")
+    ));
+}
+
+#[test]
+fn test_labeled_while_with_labeled_continue() {
+    let stmt = first_statement(
+        "rows: while (hasNext()) { for (String s : items) { if (s == null) continue rows; } }",
+    );
+    let apexrust::Statement::Labeled(labeled) = stmt else {
+        panic!("expected a labeled statement, got {:?}", stmt);
+    };
+    assert_eq!(labeled.label, "rows");
+    assert!(matches!(
+        labeled.body.as_ref(),
+        apexrust::Statement::While(_)
+    ));
+
+    let foreach = &loop_body(&labeled.body)[0];
+    let apexrust::Statement::If(if_stmt) = &loop_body(foreach)[0] else {
+        panic!("expected if");
+    };
+    assert!(matches!(
+        if_stmt.then_branch.as_ref(),
+        apexrust::Statement::Continue(cont) if cont.label.as_deref() == Some("rows")
+    ));
+}
+
+#[test]
+fn test_unlabeled_break_has_no_label() {
+    let stmt = first_statement("while (true) { break; }");
+    assert!(matches!(
+        &loop_body(&stmt)[0],
+        apexrust::Statement::Break(brk) if brk.label.is_none()
+    ));
+}
+
+#[test]
+fn test_ternary_colon_is_not_a_label() {
+    let stmt = first_statement("x = flag ? a : b;");
+    assert!(matches!(stmt, apexrust::Statement::Expression(_)));
+    assert!(parses_ok(&wrap_statements("String s = flag ? one : two;")));
+    assert!(parses_ok(&wrap_statements("call(flag ? one : two);")));
+}
+
+#[test]
+fn test_label_requires_loop_or_block() {
+    assert!(!parses_ok(&wrap_statements("outer: x = 1;")));
+    assert!(parses_ok(&wrap_statements("outer: { break outer; }")));
+}
+
 // ==================== DML Statement Tests ====================
 
 #[test]
@@ -0,0 +1,549 @@
+//! Operator-precedence parity between Apex, emitted TypeScript and SQL
+//!
+//! Each [`Case`] is an Apex expression and the value it has under
+//! [`bindings`]. [`check`] runs it three ways:
+//!
+//! 1. the expression is parsed and evaluated by [`evaluate`], a reference
+//!    interpreter with Apex semantics, so a parser that groups it wrongly
+//!    yields the wrong value
+//! 2. the transpiled TypeScript is compared with [`emit_ts`], which prints
+//!    the parsed tree with exactly the parentheses JavaScript precedence
+//!    needs; the transpiler keeps only the source's parentheses, so the
+//!    strings differ wherever the two languages group differently
+//! 3. when [`emit_soql`] can write it as a SOQL condition, the condition is
+//!    converted to SQL and run against a one-row SQLite table holding the
+//!    bindings; the row comes back exactly when the condition is true
+//!
+//! A failure names the leg and shows both sides, which pins down the
+//! operator whose grouping was lost.
+
+use std::collections::HashMap;
+
+use apexrust::sql::{
+    ConversionConfig, DdlGenerator, FieldDescribe, SObjectDescribe, SalesforceFieldType,
+    SalesforceSchema, SoqlToSqlConverter, SqlDialect,
+};
+use apexrust::transpile::transpile;
+use apexrust::{
+    parse, BinaryOp, ClassMember, Expression, SoqlQuery, Statement, TypeDeclaration, UnaryOp,
+};
+use rusqlite::Connection;
+
+/// An Apex value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i32),
+    Bool(bool),
+    Str(String),
+    Null,
+}
+
+/// An expression and its value under [`bindings`]
+pub struct Case {
+    pub apex: &'static str,
+    pub expected: Value,
+}
+
+/// The variables every case may read: Integers `a`, `b`, `c`, `neg`,
+/// Booleans `t`, `f`, String `s`, null Integer `n`, and Integers `x`, `y`
+/// for assignments
+pub fn bindings() -> HashMap<String, Value> {
+    [
+        ("a", Value::Int(1)),
+        ("b", Value::Int(2)),
+        ("c", Value::Int(3)),
+        ("neg", Value::Int(-8)),
+        ("t", Value::Bool(true)),
+        ("f", Value::Bool(false)),
+        ("s", Value::Str("x".to_string())),
+        ("n", Value::Null),
+        ("x", Value::Null),
+        ("y", Value::Null),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect()
+}
+
+const PARAMETERS: &str =
+    "Integer a, Integer b, Integer c, Integer neg, Boolean t, Boolean f, String s, Integer n";
+
+/// Apex holding the case as a return value
+fn wrap(apex: &str) -> String {
+    format!(
+        "public class Precedence {{\n    public static Object eval({}) {{\n        Integer x;\n        Integer y;\n        return {};\n    }}\n}}\n",
+        PARAMETERS, apex
+    )
+}
+
+/// What running a case found
+pub struct Checked {
+    /// One line per leg that disagreed
+    pub failures: Vec<String>,
+    /// Whether the case was expressible in SOQL and ran against SQLite
+    pub ran_sql: bool,
+}
+
+/// Run every leg of `case`
+pub fn check(case: &Case, database: &Database) -> Checked {
+    let mut failures = Vec::new();
+    let unit = match parse(&wrap(case.apex)) {
+        Ok(unit) => unit,
+        Err(e) => {
+            return Checked {
+                failures: vec![format!("{}: does not parse: {}", case.apex, e)],
+                ran_sql: false,
+            }
+        }
+    };
+    let expr = returned_expression(&unit.declarations[0]);
+
+    let value = evaluate(expr, &mut bindings());
+    if value != case.expected {
+        failures.push(format!(
+            "{}: parsed tree evaluates to {:?}, expected {:?}",
+            case.apex, value, case.expected
+        ));
+    }
+
+    let ts = transpile(&unit).expect("Transpile failed");
+    let emitted = returned_ts(&ts);
+    let reference = emit_ts(expr);
+    if emitted != reference {
+        failures.push(format!(
+            "{}: transpiled to `{}`, JavaScript precedence needs `{}`",
+            case.apex, emitted, reference
+        ));
+    }
+
+    let condition = emit_soql(expr);
+    if let Some(ref condition) = condition {
+        let expected = case.expected == Value::Bool(true);
+        match database.matches(condition) {
+            Ok(matched) if matched == expected => {}
+            Ok(matched) => failures.push(format!(
+                "{}: SOQL `{}` matched {}, expected {}",
+                case.apex, condition, matched, expected
+            )),
+            Err(e) => failures.push(format!("{}: SOQL `{}`: {}", case.apex, condition, e)),
+        }
+    }
+    Checked {
+        failures,
+        ran_sql: condition.is_some(),
+    }
+}
+
+fn returned_expression(declaration: &TypeDeclaration) -> &Expression {
+    let TypeDeclaration::Class(class) = declaration else {
+        panic!("expected class");
+    };
+    let ClassMember::Method(method) = &class.members[0] else {
+        panic!("expected method");
+    };
+    let body = method.body.as_ref().expect("method body");
+    match body.statements.last() {
+        Some(Statement::Return(ret)) => ret.value.as_ref().expect("return value"),
+        other => panic!("expected return, found {:?}", other),
+    }
+}
+
+/// The expression of the `return` the transpiled method ends with
+fn returned_ts(ts: &str) -> String {
+    let start = ts.rfind("return ").expect("return emitted") + "return ".len();
+    let end = start + ts[start..].find(";\n").expect("return ends");
+    ts[start..end].to_string()
+}
+
+// ==================== Reference Interpreter ====================
+
+/// Evaluate `expr` with Apex semantics: 32-bit Integers, `+` concatenating
+/// when either side is a String, short-circuit `&&`/`||`
+pub fn evaluate(expr: &Expression, env: &mut HashMap<String, Value>) -> Value {
+    match expr {
+        Expression::Null(..) => Value::Null,
+        Expression::Boolean(b, _, _) => Value::Bool(*b),
+        Expression::Integer(value, _, _, _) => Value::Int(*value as i32),
+        Expression::String(s, _, _) => Value::Str(s.clone()),
+        Expression::Identifier(name, _, _) => env
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| panic!("unbound {}", name)),
+        Expression::Parenthesized(inner, _, _) => evaluate(inner, env),
+        Expression::Unary(unary) => match (unary.operator, evaluate(&unary.operand, env)) {
+            (UnaryOp::Not, Value::Bool(b)) => Value::Bool(!b),
+            (UnaryOp::Negate, Value::Int(i)) => Value::Int(i.wrapping_neg()),
+            (UnaryOp::BitwiseNot, Value::Int(i)) => Value::Int(!i),
+            (op, value) => panic!("{:?} applied to {:?}", op, value),
+        },
+        Expression::Binary(binary) => match binary.operator {
+            BinaryOp::And => {
+                let left = truth(evaluate(&binary.left, env));
+                Value::Bool(left && truth(evaluate(&binary.right, env)))
+            }
+            BinaryOp::Or => {
+                let left = truth(evaluate(&binary.left, env));
+                Value::Bool(left || truth(evaluate(&binary.right, env)))
+            }
+            operator => {
+                let left = evaluate(&binary.left, env);
+                let right = evaluate(&binary.right, env);
+                apply(operator, left, right)
+            }
+        },
+        Expression::Ternary(ternary) => {
+            if truth(evaluate(&ternary.condition, env)) {
+                evaluate(&ternary.then_expr, env)
+            } else {
+                evaluate(&ternary.else_expr, env)
+            }
+        }
+        Expression::NullCoalesce(coalesce) => match evaluate(&coalesce.left, env) {
+            Value::Null => evaluate(&coalesce.right, env),
+            value => value,
+        },
+        Expression::Assignment(assignment) => {
+            let Expression::Identifier(ref name, _, _) = assignment.target else {
+                panic!("assignment to {:?}", assignment.target);
+            };
+            let value = evaluate(&assignment.value, env);
+            env.insert(name.clone(), value.clone());
+            value
+        }
+        other => panic!("no reference semantics for {:?}", other),
+    }
+}
+
+fn truth(value: Value) -> bool {
+    match value {
+        Value::Bool(b) => b,
+        other => panic!("{:?} is not a Boolean", other),
+    }
+}
+
+fn apply(operator: BinaryOp, left: Value, right: Value) -> Value {
+    use Value::{Bool, Int, Str};
+
+    match (operator, left, right) {
+        (BinaryOp::Add, Str(l), r) => Str(l + &text(r)),
+        (BinaryOp::Add, l, Str(r)) => Str(text(l) + &r),
+        (BinaryOp::Add, Int(l), Int(r)) => Int(l.wrapping_add(r)),
+        (BinaryOp::Subtract, Int(l), Int(r)) => Int(l.wrapping_sub(r)),
+        (BinaryOp::Multiply, Int(l), Int(r)) => Int(l.wrapping_mul(r)),
+        (BinaryOp::Divide, Int(l), Int(r)) => Int(l / r),
+        (BinaryOp::Equal | BinaryOp::ExactEqual, l, r) => Bool(l == r),
+        (BinaryOp::NotEqual | BinaryOp::ExactNotEqual, l, r) => Bool(l != r),
+        (BinaryOp::LessThan, Int(l), Int(r)) => Bool(l < r),
+        (BinaryOp::LessOrEqual, Int(l), Int(r)) => Bool(l <= r),
+        (BinaryOp::GreaterThan, Int(l), Int(r)) => Bool(l > r),
+        (BinaryOp::GreaterOrEqual, Int(l), Int(r)) => Bool(l >= r),
+        (BinaryOp::BitwiseAnd, Int(l), Int(r)) => Int(l & r),
+        (BinaryOp::BitwiseOr, Int(l), Int(r)) => Int(l | r),
+        (BinaryOp::BitwiseXor, Int(l), Int(r)) => Int(l ^ r),
+        (BinaryOp::LeftShift, Int(l), Int(r)) => Int(l.wrapping_shl(r as u32)),
+        (BinaryOp::RightShift, Int(l), Int(r)) => Int(l.wrapping_shr(r as u32)),
+        (BinaryOp::UnsignedRightShift, Int(l), Int(r)) => {
+            Int((l as u32).wrapping_shr(r as u32) as i32)
+        }
+        (operator, l, r) => panic!("{:?} applied to {:?} and {:?}", operator, l, r),
+    }
+}
+
+fn text(value: Value) -> String {
+    match value {
+        Value::Int(i) => i.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Str(s) => s,
+        Value::Null => "null".to_string(),
+    }
+}
+
+// ==================== Reference TypeScript Emitter ====================
+
+/// JavaScript binding strength, loosest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Js {
+    Assignment,
+    Conditional,
+    Coalesce,
+    Or,
+    And,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseAnd,
+    Equality,
+    Relational,
+    Shift,
+    Additive,
+    Multiplicative,
+    Prefix,
+    Postfix,
+    Primary,
+}
+
+fn js_binary(operator: BinaryOp) -> (Js, &'static str) {
+    match operator {
+        BinaryOp::Or => (Js::Or, "||"),
+        BinaryOp::And => (Js::And, "&&"),
+        BinaryOp::BitwiseOr => (Js::BitwiseOr, "|"),
+        BinaryOp::BitwiseXor => (Js::BitwiseXor, "^"),
+        BinaryOp::BitwiseAnd => (Js::BitwiseAnd, "&"),
+        BinaryOp::Equal | BinaryOp::ExactEqual => (Js::Equality, "==="),
+        BinaryOp::NotEqual | BinaryOp::ExactNotEqual => (Js::Equality, "!=="),
+        BinaryOp::LessThan => (Js::Relational, "<"),
+        BinaryOp::LessOrEqual => (Js::Relational, "<="),
+        BinaryOp::GreaterThan => (Js::Relational, ">"),
+        BinaryOp::GreaterOrEqual => (Js::Relational, ">="),
+        BinaryOp::LeftShift => (Js::Shift, "<<"),
+        BinaryOp::RightShift => (Js::Shift, ">>"),
+        BinaryOp::UnsignedRightShift => (Js::Shift, ">>>"),
+        BinaryOp::Add => (Js::Additive, "+"),
+        BinaryOp::Subtract => (Js::Additive, "-"),
+        BinaryOp::Multiply => (Js::Multiplicative, "*"),
+        BinaryOp::Divide => (Js::Multiplicative, "/"),
+        BinaryOp::Modulo => (Js::Multiplicative, "%"),
+        other => panic!("{:?} is SOQL-only", other),
+    }
+}
+
+/// `expr` as JavaScript with the parentheses written in the source plus
+/// those JavaScript precedence needs to keep the parsed grouping
+pub fn emit_ts(expr: &Expression) -> String {
+    ts(expr).0
+}
+
+fn ts(expr: &Expression) -> (String, Js) {
+    match expr {
+        Expression::Null(..) => ("null".to_string(), Js::Primary),
+        Expression::Boolean(b, _, _) => (b.to_string(), Js::Primary),
+        Expression::Integer(_, raw, _, _) => (raw.clone(), Js::Primary),
+        Expression::String(s, _, _) => (format!("\"{}\"", s), Js::Primary),
+        Expression::Identifier(name, _, _) => (name.clone(), Js::Primary),
+        Expression::Parenthesized(inner, _, _) => (format!("({})", ts(inner).0), Js::Primary),
+        Expression::Unary(unary) => {
+            let op = match unary.operator {
+                UnaryOp::Not => "!",
+                UnaryOp::Negate => "-",
+                UnaryOp::BitwiseNot => "~",
+            };
+            (
+                format!("{}{}", op, operand(&unary.operand, Js::Prefix)),
+                Js::Prefix,
+            )
+        }
+        Expression::Binary(binary) => {
+            let (level, op) = js_binary(binary.operator);
+            let left = operand(&binary.left, level);
+            // Left-associative: an equal-strength right operand needs parens
+            let right = strictly_operand(&binary.right, level);
+            (format!("{} {} {}", left, op, right), level)
+        }
+        Expression::NullCoalesce(coalesce) => {
+            // `??` may not share an operand with `&&` or `||` unparenthesized
+            let side = |e: &Expression, strict: bool| {
+                let (text, level) = ts(e);
+                let mixes = matches!(level, Js::Or | Js::And);
+                if mixes || level < Js::Coalesce || (strict && level == Js::Coalesce) {
+                    format!("({})", text)
+                } else {
+                    text
+                }
+            };
+            let text = format!(
+                "{} ?? {}",
+                side(&coalesce.left, false),
+                side(&coalesce.right, true)
+            );
+            (text, Js::Coalesce)
+        }
+        Expression::Ternary(ternary) => {
+            let condition = strictly_operand(&ternary.condition, Js::Conditional);
+            let then = operand(&ternary.then_expr, Js::Assignment);
+            let otherwise = operand(&ternary.else_expr, Js::Assignment);
+            (
+                format!("{} ? {} : {}", condition, then, otherwise),
+                Js::Conditional,
+            )
+        }
+        Expression::Assignment(assignment) => {
+            let target = operand(&assignment.target, Js::Postfix);
+            let value = operand(&assignment.value, Js::Assignment);
+            (format!("{} = {}", target, value), Js::Assignment)
+        }
+        other => panic!("no reference emission for {:?}", other),
+    }
+}
+
+/// `expr` parenthesized unless it binds at least as tightly as `level`
+fn operand(expr: &Expression, level: Js) -> String {
+    let (text, own) = ts(expr);
+    if own < level {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
+
+/// `expr` parenthesized unless it binds more tightly than `level`
+fn strictly_operand(expr: &Expression, level: Js) -> String {
+    let (text, own) = ts(expr);
+    if own <= level {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
+
+// ==================== Reference SOQL Emitter ====================
+
+/// SOQL binding strength, loosest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Soql {
+    Or,
+    And,
+    Not,
+    Comparison,
+}
+
+/// `expr` as a SOQL condition on the bindings table, or `None` when SOQL
+/// cannot say it: only comparisons of a binding with a literal, Boolean
+/// bindings, `NOT`, `AND` and `OR` carry over
+pub fn emit_soql(expr: &Expression) -> Option<String> {
+    soql(expr).map(|(text, _)| text)
+}
+
+fn soql(expr: &Expression) -> Option<(String, Soql)> {
+    match expr {
+        Expression::Identifier(name, _, _) if matches!(name.as_str(), "t" | "f") => {
+            Some((format!("{} = TRUE", name), Soql::Comparison))
+        }
+        Expression::Parenthesized(inner, _, _) => {
+            Some((format!("({})", soql(inner)?.0), Soql::Comparison))
+        }
+        Expression::Unary(unary) if unary.operator == UnaryOp::Not => {
+            let (text, level) = soql(&unary.operand)?;
+            let text = if level < Soql::Not {
+                format!("({})", text)
+            } else {
+                text
+            };
+            Some((format!("NOT {}", text), Soql::Not))
+        }
+        Expression::Binary(binary) => {
+            let logical = match binary.operator {
+                BinaryOp::And => Some((Soql::And, "AND")),
+                BinaryOp::Or => Some((Soql::Or, "OR")),
+                _ => None,
+            };
+            if let Some((level, keyword)) = logical {
+                // SOQL rejects AND and OR side by side without parentheses
+                let side = |e: &Expression| -> Option<String> {
+                    let (text, own) = soql(e)?;
+                    let mixes = matches!(own, Soql::And | Soql::Or) && own != level;
+                    Some(if mixes { format!("({})", text) } else { text })
+                };
+                let text = format!(
+                    "{} {} {}",
+                    side(&binary.left)?,
+                    keyword,
+                    side(&binary.right)?
+                );
+                return Some((text, level));
+            }
+            let op = match binary.operator {
+                BinaryOp::Equal => "=",
+                BinaryOp::NotEqual => "!=",
+                BinaryOp::LessThan => "<",
+                BinaryOp::LessOrEqual => "<=",
+                BinaryOp::GreaterThan => ">",
+                BinaryOp::GreaterOrEqual => ">=",
+                _ => return None,
+            };
+            let Expression::Identifier(ref field, _, _) = binary.left else {
+                return None;
+            };
+            let value = match &binary.right {
+                Expression::Integer(_, raw, _, _) => raw.clone(),
+                Expression::Boolean(b, _, _) => b.to_string().to_uppercase(),
+                Expression::String(s, _, _) => format!("'{}'", s),
+                _ => return None,
+            };
+            Some((format!("{} {} {}", field, op, value), Soql::Comparison))
+        }
+        _ => None,
+    }
+}
+
+// ==================== SQLite Rig ====================
+
+/// An in-memory SQLite database whose `Bindings` table has one row holding
+/// [`bindings`]
+pub struct Database {
+    schema: SalesforceSchema,
+    connection: Connection,
+}
+
+impl Database {
+    pub fn new() -> Self {
+        let mut bindings = SObjectDescribe::new("Bindings");
+        bindings.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+        for name in ["a", "b", "c", "neg"] {
+            bindings.add_field(FieldDescribe::new(name, SalesforceFieldType::Integer));
+        }
+        for name in ["t", "f"] {
+            bindings.add_field(FieldDescribe::new(name, SalesforceFieldType::Boolean));
+        }
+        bindings.add_field(FieldDescribe::new("s", SalesforceFieldType::String));
+        let mut schema = SalesforceSchema::new();
+        schema.add_object(bindings);
+
+        let connection = Connection::open_in_memory().unwrap();
+        let ddl = DdlGenerator::new(SqlDialect::Sqlite).generate_schema(&schema);
+        for statement in ddl.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            connection.execute(statement, []).unwrap();
+        }
+        connection
+            .execute(
+                "INSERT INTO \"bindings\" (id, a, b, c, neg, t, f, s) VALUES ('a0B000000000001', 1, 2, 3, -8, 1, 0, 'x')",
+                [],
+            )
+            .unwrap();
+        Self { schema, connection }
+    }
+
+    /// Whether the row satisfies the SOQL `condition`
+    pub fn matches(&self, condition: &str) -> Result<bool, String> {
+        let query = soql_query(&format!("SELECT Id FROM Bindings WHERE {}", condition))?;
+        let config = ConversionConfig {
+            dialect: SqlDialect::Sqlite,
+            ..Default::default()
+        };
+        let conversion = SoqlToSqlConverter::new(&self.schema, config)
+            .convert(&query)
+            .map_err(|e| e.to_string())?;
+        let mut statement = self
+            .connection
+            .prepare(&conversion.sql)
+            .map_err(|e| format!("{} in {}", e, conversion.sql))?;
+        let rows = statement
+            .query_map([], |_| Ok(()))
+            .map_err(|e| e.to_string())?
+            .count();
+        Ok(rows == 1)
+    }
+}
+
+fn soql_query(soql: &str) -> Result<SoqlQuery, String> {
+    let source = format!("public class Q {{ Object q = [{}]; }}", soql);
+    let unit = parse(&source).map_err(|e| e.to_string())?;
+    let TypeDeclaration::Class(class) = &unit.declarations[0] else {
+        unreachable!("parsed a class");
+    };
+    let ClassMember::Field(field) = &class.members[0] else {
+        unreachable!("parsed a field");
+    };
+    match &field.declarators[0].initializer {
+        Some(Expression::Soql(query)) => Ok(*query.clone()),
+        other => Err(format!("not a query: {:?}", other)),
+    }
+}
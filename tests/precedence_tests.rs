@@ -0,0 +1,119 @@
+//! Golden semantics for operator precedence; see `tests/precedence/mod.rs`
+//! for the three ways each case is checked
+
+mod precedence;
+
+use precedence::{check, Case, Database, Value};
+
+fn int(value: i32) -> Value {
+    Value::Int(value)
+}
+
+fn boolean(value: bool) -> Value {
+    Value::Bool(value)
+}
+
+fn string(value: &str) -> Value {
+    Value::Str(value.to_string())
+}
+
+/// Bindings: a = 1, b = 2, c = 3, neg = -8, t = true, f = false, s = 'x',
+/// n = null; x and y are null Integers to assign to
+fn cases() -> Vec<Case> {
+    let case = |apex, expected| Case { apex, expected };
+    vec![
+        // Arithmetic
+        case("a + b * c", int(7)),
+        case("(a + b) * c", int(9)),
+        case("c - b - a", int(0)),
+        case("c - (b - a)", int(2)),
+        case("c * b / a - c", int(3)),
+        case("-a + c", int(2)),
+        case("-(a + c)", int(-4)),
+        case("c / b * b", int(2)),
+        // Strings
+        case("s + a + b", string("x12")),
+        case("a + b + s", string("3x")),
+        case("s + (a + b)", string("x3")),
+        // Comparison against arithmetic
+        case("a + b == c", boolean(true)),
+        case("a < b == t", boolean(true)),
+        case("a * c > b + a", boolean(false)),
+        // Shifts and bitwise operators
+        case("a << b + a", int(8)),
+        case("(a << b) + a", int(5)),
+        case("neg >> a", int(-4)),
+        case("neg >>> 28", int(15)),
+        case("a | c & b", int(3)),
+        case("(a | c) & b", int(2)),
+        case("c ^ a | b", int(2)),
+        case("b & c ^ c", int(1)),
+        case("~a & c", int(2)),
+        case("(c & a) == a", boolean(true)),
+        // Boolean logic
+        case("t || f && f", boolean(true)),
+        case("(t || f) && f", boolean(false)),
+        case("f && t || t", boolean(true)),
+        case("f && (t || t)", boolean(false)),
+        case("!f && f", boolean(false)),
+        case("!(f && f)", boolean(true)),
+        case("a == 1 && b == 3 || c == 3", boolean(true)),
+        case("a == 1 && (b == 3 || c == 3)", boolean(true)),
+        case("a == 1 || b == 2 && c == 4", boolean(true)),
+        case("(a == 2 || b == 2) && c == 3", boolean(true)),
+        case("!(a == 1) || !(b == 2 && t)", boolean(false)),
+        case("!(a == 1 || b == 1) || s == 'x'", boolean(true)),
+        case("t && !(f || c > 2)", boolean(false)),
+        // Ternaries nest to the right and bind loosest
+        case("t ? a : f ? b : c", int(1)),
+        case("f ? a : f ? b : c", int(3)),
+        case("(f ? t : f) ? a : b", int(2)),
+        case("f || t ? a + b : c", int(3)),
+        case("a + (t ? b : c) * c", int(7)),
+        // Null coalescing binds looser than ||
+        case("n ?? a + b", int(3)),
+        case("n ?? a ?? b", int(1)),
+        case("(n ?? f) || t", boolean(true)),
+        case("n ?? f || t", boolean(true)),
+        // Assignments chain to the right
+        case("x = y = c", int(3)),
+        case("x = t ? a : b", int(1)),
+        case("(x = b) * c", int(6)),
+    ]
+}
+
+#[test]
+fn test_precedence_parity() {
+    let database = Database::new();
+    let mut failures = Vec::new();
+    let mut through_sqlite = 0;
+    for case in cases() {
+        let checked = check(&case, &database);
+        failures.extend(checked.failures);
+        through_sqlite += usize::from(checked.ran_sql);
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    // The SQLite leg must cover the Boolean logic, not a case or two
+    assert!(
+        through_sqlite >= 10,
+        "only {} cases reached SQLite",
+        through_sqlite
+    );
+}
+
+#[test]
+fn test_wrong_grouping_is_reported() {
+    let database = Database::new();
+    // What `t || f && f` would be if || bound tighter than &&
+    let checked = check(
+        &Case {
+            apex: "t || f && f",
+            expected: boolean(false),
+        },
+        &database,
+    );
+
+    assert_eq!(checked.failures.len(), 2, "{:?}", checked.failures);
+    assert!(checked.failures[0].contains("evaluates to Bool(true)"));
+    assert!(checked.failures[1].contains("SOQL `t = TRUE OR (f = TRUE AND f = TRUE)` matched true"));
+}
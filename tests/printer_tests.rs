@@ -0,0 +1,359 @@
+//! Printing parsed units back to Apex with ast::printer
+
+use apexrust::ast::printer::{to_apex, BraceStyle, PrintOptions};
+use apexrust::{parse, CompilationUnit};
+
+/// The unit's Debug text without spans, which printing moves
+fn without_spans(unit: &CompilationUnit) -> String {
+    let debug = format!("{:?}", unit);
+    let mut out = String::with_capacity(debug.len());
+    let mut rest = debug.as_str();
+    while let Some(start) = rest.find("Span {") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find('}').expect("Span without closing brace");
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Parse, print and parse again, returning the printed source
+fn assert_round_trip(source: &str, options: &PrintOptions) -> String {
+    let unit = parse(source).expect("Parse failed");
+    let printed = to_apex(&unit, options);
+    let reparsed = match parse(&printed) {
+        Ok(reparsed) => reparsed,
+        Err(e) => panic!("printed source does not parse: {}\n{}", e, printed),
+    };
+    assert_eq!(
+        without_spans(&unit),
+        without_spans(&reparsed),
+        "\n{}",
+        printed
+    );
+    printed
+}
+
+/// The demo class from `src/main.rs`
+const DEMO: &str = r#"
+@isTest
+public class AccountService {
+    // Fields
+    private static final String DEFAULT_NAME = 'Unknown';
+    public List<Account> accounts;
+
+    // Constructor
+    public AccountService() {
+        this.accounts = new List<Account>();
+    }
+
+    // Method with SOQL
+    public List<Account> getActiveAccounts() {
+        return [SELECT Id, Name, Industry FROM Account WHERE IsActive__c = true LIMIT 100];
+    }
+
+    // Method with DML
+    public void archive() {
+        if (!Schema.sObjectType.Account.isUpdateable()) {
+            throw new NoAccessException();
+        }
+        update accounts;
+    }
+
+    // Method with control flow
+    public void processAccounts(List<Account> accs) {
+        for (Account acc : accs) {
+            if (acc.Name == null) {
+                acc.Name = DEFAULT_NAME;
+            }
+        }
+        update accs;
+    }
+
+    // Method with try-catch
+    public void safeInsert(Account acc) {
+        try {
+            insert acc;
+        } catch (DmlException e) {
+            System.debug('Error: ' + e.getMessage());
+        }
+    }
+
+    // Property
+    public Integer AccountCount {
+        get { return accounts.size(); }
+    }
+}
+
+public enum AccountType {
+    CUSTOMER,
+    PARTNER,
+    VENDOR
+}
+
+public interface IAccountProcessor {
+    void process(Account acc);
+    Boolean validate(Account acc);
+}
+"#;
+
+#[test]
+fn test_demo_class_round_trips() {
+    let printed = assert_round_trip(DEMO, &PrintOptions::default());
+    assert!(printed.starts_with("@isTest\npublic class AccountService {\n"));
+    assert!(printed.contains("    static final String DEFAULT_NAME = 'Unknown';\n"));
+    assert!(printed.contains(
+        "        return [SELECT Id, Name, Industry FROM Account WHERE IsActive__c = true LIMIT 100];"
+    ));
+    assert!(printed.contains("        } catch (DmlException e) {\n"));
+    assert!(printed.contains(
+        "    public Integer AccountCount {\n        get {\n            return accounts.size();\n        }\n    }"
+    ));
+    assert!(
+        printed.contains("public enum AccountType {\n    CUSTOMER,\n    PARTNER,\n    VENDOR\n}")
+    );
+}
+
+const CONSTRUCTS: &str = r#"
+global with sharing abstract class Inventory<T> extends Base implements Comparable, Queueable {
+    @AuraEnabled(cacheable=true)
+    public static Map<Id, Account> byId = new Map<Id, Account>{'001' => new Account(Name = 'A')};
+    public String label { get; protected set; }
+    transient Integer a = 1, b;
+    public Integer total {
+        get { return total ?? 0; }
+        set { total = value < 0 ? -value : value; }
+    }
+
+    static {
+        byId.clear();
+    }
+
+    public Inventory(Integer a) {
+        this(a, null);
+    }
+
+    public Inventory(Integer a, String b) {
+        super(b);
+    }
+
+    @InvocableMethod(label='Run it' description='It\'s \\ fine')
+    global static List<String> run(final List<Id> ids) {
+        Set<String> names = new Set<String>{'a', 'b'};
+        List<Integer> nums = new List<Integer>{1, 2, 3};
+        String[] words = new String[3];
+        Type t = List<Account>.class;
+        Object o = (Object) names;
+        String owner = ids?.get(0)?.toString();
+        Integer n = acc?.Owner?.Name?.length();
+        outer: for (Integer i = 0, j = 10; i < j; i++, j--) {
+            if (i == 2) continue outer; else if (i > 5) { break outer; } else i += 1;
+        }
+        for (;;) {
+            break;
+        }
+        do {
+            n--;
+        } while (n > 0 && !(n == 3 || n == -(- 1)));
+        while (n < 3) n++;
+        switch on n {
+            when 1, 2 {
+                n = ~n << 2 >>> 1;
+            }
+            when null {
+                n = 0;
+            }
+            when else {
+                n = n instanceof Integer ? 1 : 0;
+            }
+        }
+        switch on o {
+            when Account acct {
+                System.debug(acct);
+            }
+        }
+        try {
+            upsert as user names;
+        } catch (DmlException | QueryException e) {
+            throw e;
+        } finally {
+            ;
+        }
+        System.runAs(u) {
+            delete [SELECT Id FROM Contact WHERE AccountId IN :ids.keySet() AND (Email != null OR Phone LIKE '555%')];
+            delete [SELECT Id FROM Contact WHERE AccountId NOT IN (SELECT Id FROM Account WHERE Name = :name)];
+        }
+        List<AggregateResult> groups = [
+            SELECT Industry, COUNT(Id) total, (SELECT Id FROM Contacts ORDER BY LastName DESC NULLS LAST LIMIT 5),
+                TYPEOF What WHEN Account THEN Name, Phone ELSE Name END
+            FROM Account USING SCOPE mine
+            WHERE CreatedDate = LAST_N_DAYS:30 AND Type NOT IN ('Partner', 'Other') AND NOT Name = :this.label
+            WITH USER_MODE
+            GROUP BY Industry HAVING COUNT(Id) > 1
+            ORDER BY Industry NULLS FIRST LIMIT :limitSize OFFSET 10 FOR UPDATE
+        ];
+        List<Account> everything = [SELECT FIELDS(STANDARD), (SELECT FIELDS(ALL) FROM Contacts LIMIT 200) FROM Account LIMIT 200];
+        List<AggregateResult> subtotals = [SELECT Industry, GROUPING(Industry) grp, SUM(AnnualRevenue) FROM Account GROUP BY ROLLUP(Industry, Type)];
+        List<List<SObject>> found = [FIND 'Acme*' IN NAME FIELDS RETURNING Account(Id, Name WHERE Industry = 'Tech' ORDER BY Name LIMIT 10), Contact LIMIT 20];
+        return new List<String>{'x'};
+    }
+
+    public <K> K first(List<K> items) {
+        return items[0];
+    }
+
+    abstract void hook();
+
+    private class Node {
+        Node next;
+    }
+
+    public enum Color { RED, GREEN }
+
+    interface Visitor extends Base.Visitor {
+        @Deprecated
+        void visit(Node n);
+    }
+}
+
+trigger AccountTrigger on Account (before insert, after update, after undelete) {
+    for (Account acc : Trigger.new) {
+        acc.Name = acc.Name?.trim();
+    }
+}
+"#;
+
+#[test]
+fn test_supported_constructs_round_trip() {
+    let printed = assert_round_trip(CONSTRUCTS, &PrintOptions::default());
+    assert!(printed.contains("    @AuraEnabled(cacheable=true)\n"));
+    assert!(
+        printed.contains("    @InvocableMethod(label='Run it' description='It\\'s \\\\ fine')\n")
+    );
+    assert!(printed.contains("    public String label { get; protected set; }\n"));
+    assert!(printed.contains("String owner = ids?.get(0)?.toString();"));
+    assert!(printed.contains("new Map<Id, Account>{'001' => new Account(Name = 'A')}"));
+    assert!(printed.contains("Set<String> names = new Set<String>{'a', 'b'};"));
+    assert!(printed.contains("return new List<String>{'x'};"));
+    assert!(printed.contains(
+        "trigger AccountTrigger on Account (before insert, after update, after undelete) {"
+    ));
+    assert!(printed.contains(
+        "WHERE CreatedDate = LAST_N_DAYS:30 AND Type NOT IN ('Partner', 'Other') AND NOT Name = :this.label WITH USER_MODE"
+    ));
+}
+
+#[test]
+fn test_collection_literals_keep_their_types() {
+    let printed = assert_round_trip(
+        "public class C { Object m() { return new Map<String, List<Integer>>{'a' => new List<Integer>{1, 2}, 'b' => new List<Integer>{}}; } }",
+        &PrintOptions::default(),
+    );
+    assert!(printed.contains(
+        "return new Map<String, List<Integer>>{'a' => new List<Integer>{1, 2}, 'b' => new List<Integer>{}};"
+    ));
+}
+
+#[test]
+fn test_accessors_keep_their_access() {
+    let printed = assert_round_trip(
+        "public class C { public Integer x { get; private set; } Integer y { get; set; } global String z { protected get { return ''; } set; } }",
+        &PrintOptions::default(),
+    );
+    assert!(
+        printed.contains("public Integer x { get; private set; }"),
+        "{}",
+        printed
+    );
+    assert!(printed.contains("Integer y { get; set; }"), "{}", printed);
+    assert!(
+        printed.contains("global String z {\n        protected get {"),
+        "{}",
+        printed
+    );
+    assert!(printed.contains("\n        set;\n"), "{}", printed);
+}
+
+#[test]
+fn test_like_patterns_keep_their_escapes() {
+    let printed = assert_round_trip(
+        r"public class C { List<Account> m() { return [SELECT Id FROM Account WHERE Name LIKE 'C:\\%' OR Name LIKE '50\%\_it\'s']; } }",
+        &PrintOptions::default(),
+    );
+    assert!(printed.contains(r"WHERE Name LIKE 'C:\\%' OR Name LIKE '50\%\_it\'s'"));
+}
+
+#[test]
+fn test_indent_and_brace_style_options() {
+    let unit = parse(
+        "public class C { void m(Boolean b) { if (b) { return; } else { b = true; } try { m(b); } catch (Exception e) { } } }",
+    )
+    .unwrap();
+    let options = PrintOptions {
+        indent: "\t".to_string(),
+        brace_style: BraceStyle::NextLine,
+    };
+    let printed = to_apex(&unit, &options);
+
+    assert_eq!(
+        printed,
+        "public class C\n{\n\tvoid m(Boolean b)\n\t{\n\t\tif (b)\n\t\t{\n\t\t\treturn;\n\t\t}\n\t\telse\n\t\t{\n\t\t\tb = true;\n\t\t}\n\t\ttry\n\t\t{\n\t\t\tm(b);\n\t\t}\n\t\tcatch (Exception e)\n\t\t{\n\t\t}\n\t}\n}\n"
+    );
+    assert_round_trip(&printed, &options);
+}
+
+#[test]
+fn test_printing_is_idempotent() {
+    let unit = parse(DEMO).unwrap();
+    for options in [
+        PrintOptions::default(),
+        PrintOptions {
+            indent: "  ".to_string(),
+            brace_style: BraceStyle::NextLine,
+        },
+    ] {
+        let once = to_apex(&unit, &options);
+        let twice = to_apex(&parse(&once).unwrap(), &options);
+        assert_eq!(once, twice);
+    }
+}
+
+#[test]
+fn test_every_statement_kind_round_trips() {
+    let source = r#"
+public class Statements {
+    void all(List<Integer> values, User u) {
+        final Integer limit = 3, count;
+        {
+            count = 0;
+        }
+        ;
+        if (limit > 1) count++; else if (limit < 0) { count--; } else ;
+        for (count = 0, i = 1; count < limit; count++) {}
+        for (; count < limit;) count++;
+        for (Integer v : values) { continue; }
+        scan: while (count > 0) { count--; break scan; }
+        block: { count = 1; }
+        do count++; while (count < limit);
+        switch on values.size() { when 0 { return; } when else { count = 2; } }
+        try { insert as system values; } catch (Exception e) { throw e; }
+        System.runAs(u) { delete values[0]; }
+        undelete values;
+        return;
+    }
+}
+"#;
+    let printed = assert_round_trip(source, &PrintOptions::default());
+    for line in [
+        "        final Integer limit = 3, count;\n        {\n            count = 0;\n        }\n        ;\n",
+        "        if (limit > 1)\n            count++;\n        else if (limit < 0) {\n            count--;\n        } else\n            ;\n",
+        "        for (count = 0, i = 1; count < limit; count++) {\n        }\n",
+        "        for (; count < limit;)\n            count++;\n",
+        "        scan: while (count > 0) {\n",
+        "        block: {\n            count = 1;\n        }\n",
+        "        do\n            count++;\n        while (count < limit);\n",
+        "        insert as system values;\n",
+    ] {
+        assert!(printed.contains(line), "missing {:?} in\n{}", line, printed);
+    }
+}
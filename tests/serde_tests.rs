@@ -0,0 +1,59 @@
+//! JSON round trips of parsed units (`serde` feature)
+
+use apexrust::{parse, CompilationUnit, Span, TypeDeclaration};
+
+const SOURCE: &str = r#"
+@IsTest
+public with sharing class AccountService implements Queueable {
+    public enum Tier { BRONZE, GOLD }
+    private static final Decimal RATE = 1.50;
+    public Integer count { get; private set; }
+
+    public AccountService(Integer count) {
+        this.count = count;
+    }
+
+    public void execute(QueueableContext context) {
+        Map<Id, Account> byId = new Map<Id, Account>([SELECT Id, (SELECT Id FROM Contacts) FROM Account LIMIT :count]);
+        for (Account acc : byId.values()) {
+            switch on acc.Type {
+                when 'Partner', null { acc.Rating = 'Hot'; }
+                when else { acc.Rating = acc?.Rating ?? 'Cold'; }
+            }
+        }
+        try {
+            update as user byId.values();
+        } catch (DmlException | QueryException e) {
+            throw new AuraHandledException(e.getMessage());
+        }
+        List<List<SObject>> found = [FIND 'Acme' IN NAME FIELDS RETURNING Account(Name WHERE Id IN :byId.keySet())];
+    }
+}
+"#;
+
+#[test]
+fn test_unit_round_trips_through_json() {
+    let unit = parse(SOURCE).expect("Parse failed");
+
+    let json = serde_json::to_string(&unit).unwrap();
+    let back: CompilationUnit = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(back, unit);
+}
+
+#[test]
+fn test_span_is_start_and_end() {
+    let unit = parse("public class A {}").unwrap();
+    let TypeDeclaration::Class(ref class) = unit.declarations[0] else {
+        panic!("expected class");
+    };
+    let span = class.span;
+
+    let json = serde_json::to_value(span).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "start": span.start, "end": span.end })
+    );
+    let back: Span = serde_json::from_value(json).unwrap();
+    assert_eq!((back.line, back.column), (0, 0));
+}
@@ -0,0 +1,147 @@
+//! Line-based unified diff for snapshot failures
+
+/// Lines of unchanged context shown around each change
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Render a unified diff from `expected` to `actual`
+///
+/// Returns an empty string when the texts are identical.
+pub fn unified_diff(
+    expected: &str,
+    actual: &str,
+    expected_name: &str,
+    actual_name: &str,
+) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+    let ops = diff_ops(&old, &new);
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Group changes whose context windows touch into hunks
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &i in &changes {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", expected_name, actual_name);
+    for (start, end) in hunks {
+        let hunk = &ops[start..end];
+        let (old_start, new_start) = hunk_origin(&ops, start);
+        let old_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            old_len,
+            new_start + 1,
+            new_len
+        ));
+        for op in hunk {
+            match *op {
+                Op::Equal(i, _) => out.push_str(&format!(" {}\n", old[i])),
+                Op::Delete(i) => out.push_str(&format!("-{}\n", old[i])),
+                Op::Insert(j) => out.push_str(&format!("+{}\n", new[j])),
+            }
+        }
+    }
+    out
+}
+
+/// Line numbers (0-based) in the old and new text where op `index` starts
+fn hunk_origin(ops: &[Op], index: usize) -> (usize, usize) {
+    ops[..index].iter().fold((0, 0), |(old, new), op| match op {
+        Op::Equal(..) => (old + 1, new + 1),
+        Op::Delete(_) => (old + 1, new),
+        Op::Insert(_) => (old, new + 1),
+    })
+}
+
+/// Edit script via longest common subsequence
+///
+/// Quadratic, which is fine for snapshot-sized files.
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            ops.push(Op::Equal(i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(Op::Delete(i));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(j));
+            j += 1;
+        }
+    }
+    ops.extend((i..n).map(Op::Delete));
+    ops.extend((j..m).map(Op::Insert));
+    ops
+}
+
+#[test]
+fn test_identical_texts_have_no_diff() {
+    assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new"), "");
+}
+
+#[test]
+fn test_changed_line_is_shown_with_context() {
+    let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+    let new = "1\n2\n3\n4\nfive\n6\n7\n8\n9\n";
+
+    assert_eq!(
+        unified_diff(old, new, "old", "new"),
+        "--- old\n+++ new\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+    );
+}
+
+#[test]
+fn test_distant_changes_get_separate_hunks() {
+    let old = "a\n1\n2\n3\n4\n5\n6\n7\n8\nb\n";
+    let new = "A\n1\n2\n3\n4\n5\n6\n7\n8\nB\n";
+    let diff = unified_diff(old, new, "old", "new");
+
+    assert_eq!(diff.matches("@@ ").count(), 2);
+    assert!(diff.contains("@@ -1,4 +1,4 @@\n-a\n+A\n"));
+    assert!(diff.contains("@@ -7,4 +7,4 @@\n 6\n 7\n 8\n-b\n+B\n"));
+}
@@ -0,0 +1,340 @@
+//! Golden-file snapshots of transpiler output
+//!
+//! Every fixture in `tests/transpile_fixtures/*.cls` starts with a matrix
+//! line naming the option sets (see [`options_for`]) it is transpiled under:
+//!
+//! ```text
+//! // snapshot: default, javascript
+//! ```
+//!
+//! Each fixture/option-set pair is compared against
+//! `tests/transpile_fixtures/snapshots/<fixture>@<set>.<ts|js>`.
+//!
+//! - `APEXRUST_BLESS=1` rewrites snapshots from the current output and
+//!   deletes snapshots no fixture produces any more.
+//! - `APEXRUST_TSC=1` additionally type-checks the TypeScript snapshots with
+//!   `tsc --noEmit` when `tsc` is on the PATH.
+//!
+//! Whenever `node` is on the PATH, every output is also syntax-checked as an
+//! ES module with `node --check`: JavaScript snapshots as they are, and
+//! TypeScript ones through the JavaScript the same options produce, so an
+//! `await` outside an `async` function fails the run in either mode.
+
+mod diff;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use apexrust::parse;
+use apexrust::transpile::{
+    transpile_with_options, StaticCacheStrategy, TranspileOptions, RUNTIME_INTERFACE,
+};
+
+const MATRIX_PREFIX: &str = "// snapshot:";
+
+/// Stands in for the runtime interface header so snapshots don't repeat it
+const RUNTIME_MARKER: &str = "// <runtime interface>";
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/transpile_fixtures")
+}
+
+fn snapshot_dir() -> PathBuf {
+    fixture_dir().join("snapshots")
+}
+
+fn env_flag(name: &str) -> bool {
+    env::var(name).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Transpiler options for a named option set
+fn options_for(set: &str) -> Option<TranspileOptions> {
+    let defaults = TranspileOptions::default();
+    let options = match set {
+        "default" => defaults,
+        "javascript" => TranspileOptions {
+            typescript: false,
+            ..defaults
+        },
+        "sync" => TranspileOptions {
+            async_database: false,
+            ..defaults
+        },
+        "request-statics" => TranspileOptions {
+            static_cache_strategy: StaticCacheStrategy::Request,
+            ..defaults
+        },
+        "allowed-objects" => TranspileOptions {
+            allowed_objects: Some(vec!["Account".to_string(), "Contact".to_string()]),
+            ..defaults
+        },
+        "split-triggers" => TranspileOptions {
+            split_trigger_events: true,
+            ..defaults
+        },
+        _ => return None,
+    };
+    Some(options)
+}
+
+/// An Apex fixture and the option sets it is snapshotted under
+struct Fixture {
+    name: String,
+    source: String,
+    option_sets: Vec<String>,
+}
+
+impl Fixture {
+    fn load(path: &Path) -> Fixture {
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let source = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+        let option_sets = source
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix(MATRIX_PREFIX))
+            .unwrap_or_else(|| {
+                panic!(
+                    "{} must start with a `{} <set>, ...` line",
+                    path.display(),
+                    MATRIX_PREFIX
+                )
+            })
+            .split(',')
+            .map(|set| set.trim().to_string())
+            .filter(|set| !set.is_empty())
+            .collect();
+        Fixture {
+            name,
+            source,
+            option_sets,
+        }
+    }
+
+    fn snapshot_path(&self, set: &str, options: &TranspileOptions) -> PathBuf {
+        let ext = if options.typescript { "ts" } else { "js" };
+        snapshot_dir().join(format!("{}@{}.{}", self.name, set, ext))
+    }
+}
+
+fn load_fixtures() -> Vec<Fixture> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(fixture_dir())
+        .expect("missing tests/transpile_fixtures")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "cls"))
+        .collect();
+    paths.sort();
+    paths.iter().map(|path| Fixture::load(path)).collect()
+}
+
+/// Canonical form stored on disk
+///
+/// Line endings and trailing whitespace are normalized and the (large,
+/// option-independent) runtime interface header is replaced by a marker.
+fn normalize(output: &str) -> String {
+    let output = output
+        .replace("\r\n", "\n")
+        .replace(RUNTIME_INTERFACE, RUNTIME_MARKER);
+    let mut lines: Vec<&str> = output.lines().map(str::trim_end).collect();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    let mut normalized = lines.join("\n");
+    normalized.push('\n');
+    normalized
+}
+
+/// Expand a stored snapshot back into compilable output
+fn denormalize(snapshot: &str) -> String {
+    snapshot.replace(RUNTIME_MARKER, RUNTIME_INTERFACE)
+}
+
+/// Transpile every fixture under each of its option sets and compare (or
+/// bless) the snapshots, panicking with a report of all failures
+pub fn run_all() {
+    let bless = env_flag("APEXRUST_BLESS");
+    let fixtures = load_fixtures();
+    assert!(!fixtures.is_empty(), "no transpiler fixtures found");
+
+    let mut failures = Vec::new();
+    let mut produced = Vec::new();
+    let mut syntax_checked = Vec::new();
+
+    for fixture in &fixtures {
+        let unit = parse(&fixture.source)
+            .unwrap_or_else(|e| panic!("{}.cls failed to parse: {:?}", fixture.name, e));
+
+        for set in &fixture.option_sets {
+            let Some(options) = options_for(set) else {
+                failures.push(format!(
+                    "{}.cls: unknown option set '{}'",
+                    fixture.name, set
+                ));
+                continue;
+            };
+            let path = fixture.snapshot_path(set, &options);
+            produced.push(path.clone());
+
+            let javascript = TranspileOptions {
+                typescript: false,
+                ..options.clone()
+            };
+            match transpile_with_options(&unit, javascript) {
+                Ok(code) => syntax_checked.push((format!("{}@{}", fixture.name, set), code)),
+                Err(e) => {
+                    failures.push(format!("{}@{}: transpile failed: {}", fixture.name, set, e))
+                }
+            }
+
+            let actual = match transpile_with_options(&unit, options) {
+                Ok(output) => normalize(&output),
+                Err(e) => {
+                    failures.push(format!("{}@{}: transpile failed: {}", fixture.name, set, e));
+                    continue;
+                }
+            };
+
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            match fs::read_to_string(&path) {
+                Ok(expected) if expected == actual => {}
+                _ if bless => {
+                    fs::create_dir_all(snapshot_dir()).unwrap();
+                    fs::write(&path, &actual).unwrap();
+                }
+                Ok(expected) => failures.push(diff::unified_diff(
+                    &expected,
+                    &actual,
+                    &format!("snapshots/{}", name),
+                    "transpiler output",
+                )),
+                Err(_) => failures.push(format!("{}: snapshot missing", name)),
+            }
+        }
+    }
+
+    // Snapshots whose fixture or option set was removed
+    if let Ok(entries) = fs::read_dir(snapshot_dir()) {
+        for path in entries.map(|entry| entry.unwrap().path()) {
+            if produced.contains(&path) {
+                continue;
+            }
+            if bless {
+                fs::remove_file(&path).unwrap();
+            } else {
+                failures.push(format!("{}: stale snapshot", path.display()));
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        failures.extend(syntax_check(&syntax_checked));
+        failures.extend(check_with_tsc(&produced));
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} snapshot failure(s); rerun with APEXRUST_BLESS=1 to accept the new output\n\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+/// Syntax-check JavaScript modules, given as name and code, with
+/// `node --check`; one failure per module node rejects
+///
+/// Skipped when `node` is not installed.
+fn syntax_check(modules: &[(String, String)]) -> Vec<String> {
+    if Command::new("node").arg("--version").output().is_err() {
+        eprintln!("node is not on the PATH; skipping the syntax check of transpiler output");
+        return Vec::new();
+    }
+
+    let out_dir = env::temp_dir().join(format!("apexrust-syntax-check-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&out_dir);
+    fs::create_dir_all(&out_dir).unwrap();
+
+    let mut failures = Vec::new();
+    for (name, code) in modules {
+        // `.mjs`: a `.js` file with module syntax is checked as a script
+        let target = out_dir.join(format!("{}.mjs", name));
+        fs::write(&target, code).unwrap();
+        let output = Command::new("node")
+            .arg("--check")
+            .arg(&target)
+            .output()
+            .expect("failed to run node");
+        if !output.status.success() {
+            failures.push(format!(
+                "{} is not valid JavaScript:\n{}",
+                name,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+    }
+    let _ = fs::remove_dir_all(&out_dir);
+    failures
+}
+
+/// Type-check the TypeScript snapshots (see [`type_check`])
+fn check_with_tsc(snapshots: &[PathBuf]) -> Option<String> {
+    let modules: Vec<(String, String)> = snapshots
+        .iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ts"))
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            (name, denormalize(&fs::read_to_string(path).unwrap()))
+        })
+        .collect();
+    type_check("apexrust-transpile-snapshots", &modules)
+        .map(|errors| format!("tsc rejected the snapshots:\n{}", errors))
+}
+
+/// Type-check TypeScript modules, given as file name and code, in a fresh
+/// temporary directory so they can import each other
+///
+/// Returns tsc's diagnostics, or `None` when they pass, `APEXRUST_TSC` is
+/// not set or `tsc` is not installed.
+pub fn type_check(dir_name: &str, modules: &[(String, String)]) -> Option<String> {
+    if !env_flag("APEXRUST_TSC") {
+        return None;
+    }
+    if Command::new("tsc").arg("--version").output().is_err() {
+        eprintln!("APEXRUST_TSC is set but tsc is not on the PATH; skipping type-check");
+        return None;
+    }
+
+    let out_dir = env::temp_dir().join(dir_name);
+    let _ = fs::remove_dir_all(&out_dir);
+    fs::create_dir_all(&out_dir).unwrap();
+
+    let mut files = Vec::new();
+    for (name, code) in modules {
+        let target = out_dir.join(name);
+        fs::write(&target, code).unwrap();
+        files.push(target);
+    }
+
+    let output = Command::new("tsc")
+        .args(["--noEmit", "--target", "es2022", "--skipLibCheck"])
+        .args(&files)
+        .output()
+        .expect("failed to run tsc");
+    if output.status.success() {
+        None
+    } else {
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[test]
+fn test_normalize_strips_runtime_header_and_whitespace() {
+    let output = format!("// header\r\n{}\nclass A {{  \n}}\n\n\n", RUNTIME_INTERFACE);
+
+    assert_eq!(
+        normalize(&output),
+        format!("// header\n{}\nclass A {{\n}}\n", RUNTIME_MARKER)
+    );
+    assert!(denormalize(&normalize(&output)).contains("export interface ApexRuntime"));
+}
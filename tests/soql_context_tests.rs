@@ -0,0 +1,107 @@
+//! Tests for SOQL query context classification
+
+use apexrust::analysis::soql_context::{classify, SoqlContext};
+use apexrust::parse;
+
+const FIXTURE: &str = r#"
+    public class AccountService {
+        private Account primary;
+
+        public List<Account> run(Id accountId) {
+            for (Contact c : [SELECT Id FROM Contact]) {
+                System.debug(c);
+            }
+            List<Opportunity> opps = [SELECT Id FROM Opportunity];
+            Account acc = ([SELECT Id FROM Account WHERE Id = :accountId]);
+            this.primary = [SELECT Id FROM Account LIMIT 1];
+            Database.delete([SELECT Id FROM Task]);
+            Map<Id, Lead> leads = new Map<Id, Lead>([SELECT Id FROM Lead]);
+            Integer total = [SELECT COUNT() FROM Case];
+            return [SELECT Id FROM Account];
+        }
+    }
+"#;
+
+fn contexts(source: &str) -> Vec<(String, SoqlContext)> {
+    let unit = parse(source).expect("Parse failed");
+    classify(&unit)
+        .into_iter()
+        .map(|usage| (usage.query.from_clause.clone(), usage.context))
+        .collect()
+}
+
+#[test]
+fn test_classifies_every_context() {
+    let var = |name: &str| name.to_string();
+
+    assert_eq!(
+        contexts(FIXTURE),
+        [
+            ("Contact".to_string(), SoqlContext::ForEachIteration),
+            (
+                "Opportunity".to_string(),
+                SoqlContext::ListAssignment { var: var("opps") }
+            ),
+            (
+                "Account".to_string(),
+                SoqlContext::SingleRecordAssignment { var: var("acc") }
+            ),
+            (
+                "Account".to_string(),
+                SoqlContext::SingleRecordAssignment {
+                    var: var("primary")
+                }
+            ),
+            (
+                "Task".to_string(),
+                SoqlContext::MethodArgument {
+                    callee: var("Database.delete")
+                }
+            ),
+            (
+                "Lead".to_string(),
+                SoqlContext::MethodArgument {
+                    callee: var("new Map")
+                }
+            ),
+            ("Case".to_string(), SoqlContext::Other),
+            ("Account".to_string(), SoqlContext::ReturnValue),
+        ]
+    );
+}
+
+#[test]
+fn test_field_initializers_and_triggers() {
+    let class = contexts(
+        r#"
+        public class Defaults {
+            private List<User> admins = [SELECT Id FROM User];
+            private Integer count = [SELECT Id FROM Group].size();
+        }
+        "#,
+    );
+    assert_eq!(
+        class,
+        [
+            (
+                "User".to_string(),
+                SoqlContext::ListAssignment {
+                    var: "admins".to_string()
+                }
+            ),
+            ("Group".to_string(), SoqlContext::Other),
+        ]
+    );
+
+    let trigger = contexts(
+        r#"
+        trigger AccountTrigger on Account (before insert) {
+            for (User u : [SELECT Id FROM User]) { }
+        }
+        "#,
+    );
+    assert_eq!(
+        trigger,
+        [("User".to_string(), SoqlContext::ForEachIteration)]
+    );
+}
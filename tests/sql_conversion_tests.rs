@@ -2,10 +2,13 @@
 
 use apexrust::parse;
 use apexrust::sql::{
-    ChildRelationship, ConversionConfig, DdlGenerator, FieldDescribe, SObjectDescribe,
-    SalesforceFieldType, SalesforceSchema, SoqlToSqlConverter, SqlDialect,
+    CapabilityMode, ChildRelationship, CollationMode, ConversionConfig, ConversionError,
+    ConversionWarning, DdlGenerator, FieldDescribe, IdComparison, ObjectKind, ResultShape,
+    SObjectDescribe, SalesforceFieldType, SalesforceSchema, SoqlToSqlConverter, SqlDialect,
+    SqliteQualifier, WarningFilter, WarningKind, WarningSeverity,
 };
-use apexrust::SoqlQuery;
+use apexrust::{ClockSource, SoqlQuery, Timestamp};
+use std::collections::HashMap;
 
 /// Helper to extract SOQL from a test wrapper class
 fn extract_soql(source: &str) -> SoqlQuery {
@@ -111,6 +114,10 @@ fn create_test_schema() -> SalesforceSchema {
     user.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
     user.add_field(FieldDescribe::new("Name", SalesforceFieldType::String));
     user.add_field(FieldDescribe::new("Email", SalesforceFieldType::Email));
+    user.add_field(FieldDescribe::new(
+        "IsDeleted",
+        SalesforceFieldType::Boolean,
+    ));
     schema.add_object(user);
 
     schema
@@ -154,6 +161,21 @@ fn test_simple_select_sqlite() {
     assert!(result.sql.contains("\"account\""));
 }
 
+#[test]
+fn test_simple_select_mysql() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Name FROM Account");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Mysql,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert_eq!(result.sql, "SELECT t0.id, t0.name\nFROM `account` t0");
+}
+
 #[test]
 fn test_select_all_field_types() {
     let schema = create_test_schema();
@@ -171,6 +193,71 @@ fn test_select_all_field_types() {
     assert!(result.sql.to_lowercase().contains("website"));
 }
 
+/// The test schema with a custom field on Account
+fn schema_with_custom_account_field() -> SalesforceSchema {
+    let mut schema = create_test_schema();
+    schema
+        .get_object_mut("Account")
+        .unwrap()
+        .add_field(FieldDescribe::new(
+            "Region__c",
+            SalesforceFieldType::Picklist,
+        ));
+    schema
+}
+
+#[test]
+fn test_select_fields_all() {
+    let schema = schema_with_custom_account_field();
+    let soql = extract_soql("SELECT FIELDS(ALL) FROM Account LIMIT 200");
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.starts_with(
+        "SELECT t0.id, t0.name, t0.annual_revenue, t0.created_date, t0.industry, \
+         t0.is_deleted, t0.number_of_employees, t0.owner_id, t0.region__c, t0.website\n"
+    ));
+    assert_eq!(result.column_map.len(), 10);
+}
+
+#[test]
+fn test_select_fields_standard() {
+    let schema = schema_with_custom_account_field();
+    let soql = extract_soql("SELECT fields(standard) FROM Account");
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.starts_with(
+        "SELECT t0.id, t0.name, t0.annual_revenue, t0.created_date, t0.industry, \
+         t0.is_deleted, t0.number_of_employees, t0.owner_id, t0.website\n"
+    ));
+    assert!(!result.column_map.contains_key("Region__c"));
+}
+
+#[test]
+fn test_select_fields_custom() {
+    let schema = schema_with_custom_account_field();
+    let soql = extract_soql("SELECT Id, FIELDS(CUSTOM) FROM Account LIMIT 200");
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result
+        .sql
+        .starts_with("SELECT t0.id, t0.region__c\nFROM \"account\" t0"));
+}
+
+#[test]
+fn test_select_fields_requires_schema() {
+    let soql = extract_soql("SELECT FIELDS(ALL) FROM Account LIMIT 200");
+
+    let result = SoqlToSqlConverter::new_without_schema(ConversionConfig::default()).convert(&soql);
+
+    assert!(matches!(result, Err(ConversionError::SchemaRequired(_))));
+}
+
 // =============================================================================
 // WHERE clause tests
 // =============================================================================
@@ -200,6 +287,33 @@ fn test_where_not_equals() {
     assert!(result.sql.contains("!= 'Acme'"));
 }
 
+#[test]
+fn test_where_equals_null() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account WHERE Name = null");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("WHERE t0.name IS NULL"));
+    assert!(!result.sql.contains("= NULL"));
+}
+
+#[test]
+fn test_where_not_equals_null() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account WHERE Name != null AND Industry <> null");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result
+        .sql
+        .contains("WHERE t0.name IS NOT NULL AND t0.industry IS NOT NULL"));
+}
+
 #[test]
 fn test_where_comparison_operators() {
     let schema = create_test_schema();
@@ -216,6 +330,73 @@ fn test_where_comparison_operators() {
     assert!(result.sql.contains("AND"));
 }
 
+#[test]
+fn test_where_keeps_parentheses() {
+    let schema = create_test_schema();
+    let soql = extract_soql(
+        "SELECT Id FROM Account WHERE (Name = 'A' OR Name = 'B') AND Industry = 'Tech'",
+    );
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result
+        .sql
+        .contains("WHERE (t0.name = 'A' OR t0.name = 'B') AND t0.industry = 'Tech'"));
+}
+
+#[test]
+fn test_where_bare_boolean_field_compares_to_true() {
+    let schema = create_test_schema();
+    let mut soql = extract_soql("SELECT Id FROM Account WHERE IsDeleted = true AND Name = 'Acme'");
+
+    // SOQL requires the comparison, so strip it the way a rewrite might
+    let Some(apexrust::Expression::Binary(and)) = &mut soql.where_clause else {
+        panic!("expected AND in WHERE");
+    };
+    let apexrust::Expression::Binary(equal) = &and.left else {
+        panic!("expected comparison on the left of AND");
+    };
+    and.left = equal.left.clone();
+    assert!(matches!(and.left, apexrust::Expression::Identifier(..)));
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result
+        .sql
+        .contains("WHERE t0.is_deleted = TRUE AND t0.name = 'Acme'"));
+}
+
+#[test]
+fn test_where_decimal_keeps_source_text() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account WHERE AnnualRevenue > 1.50");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("> 1.50"));
+}
+
+#[test]
+fn test_where_double_has_no_exponent() {
+    let schema = create_test_schema();
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+
+    let soql = extract_soql("SELECT Id FROM Account WHERE AnnualRevenue = 1000000.0");
+    let sql = converter.convert(&soql).unwrap().sql;
+    assert!(sql.contains("t0.annual_revenue = 1000000.0"), "{}", sql);
+
+    let soql = extract_soql("SELECT Id FROM Account WHERE AnnualRevenue > 2.5e7");
+    let sql = converter.convert(&soql).unwrap().sql;
+    assert!(sql.contains("t0.annual_revenue > 25000000.0"), "{}", sql);
+}
+
 #[test]
 fn test_where_like() {
     let schema = create_test_schema();
@@ -229,6 +410,71 @@ fn test_where_like() {
     assert!(result.sql.contains("'Acme%'"));
 }
 
+#[test]
+fn test_where_like_escaped_wildcard() {
+    let schema = create_test_schema();
+    let soql = extract_soql(r"SELECT Id FROM Account WHERE Name LIKE '50\%%'");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains(r"t0.name LIKE '50\%%' ESCAPE '\'"));
+}
+
+#[test]
+fn test_where_like_escaped_backslash() {
+    let schema = create_test_schema();
+    let soql = extract_soql(r"SELECT Id FROM Account WHERE Name LIKE 'C:\\%'");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // A literal backslash, then a wildcard
+    assert!(result.sql.contains(r"t0.name LIKE 'C:\\%' ESCAPE '\'"));
+}
+
+#[test]
+fn test_where_like_escaped_wildcard_mysql() {
+    let schema = create_test_schema();
+    let soql = extract_soql(r"SELECT Id FROM Account WHERE Name LIKE '100\% %'");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Mysql,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // Backslash is already the escape character of MySQL's LIKE; the
+    // string literal doubles it so the pattern still holds `\%`
+    assert!(result.sql.ends_with(r"WHERE t0.name LIKE '100\\% %'"));
+}
+
+#[test]
+fn test_where_backslash_literal_mysql() {
+    let schema = create_test_schema();
+    let soql =
+        extract_soql(r"SELECT Id FROM Account WHERE Name = 'a\\' AND Industry IN ('b\\', 'c')");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Mysql,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // A lone backslash before the closing quote would escape it
+    assert!(
+        result
+            .sql
+            .ends_with(r"WHERE t0.name = 'a\\' AND t0.industry IN ('b\\', 'c')"),
+        "{}",
+        result.sql
+    );
+}
+
 #[test]
 fn test_where_in_list() {
     let schema = create_test_schema();
@@ -308,6 +554,183 @@ fn test_bind_variable_sqlite() {
     assert_eq!(result.parameters[0].placeholder, "?1");
 }
 
+#[test]
+fn test_bind_variables_mysql_follow_sql_order() {
+    let schema = create_test_schema();
+    let soql = extract_soql(
+        "SELECT Id, (SELECT Id FROM Contacts LIMIT :perAccount) FROM Account \
+         WHERE Name = :name AND Industry = :industry LIMIT :maxRecords",
+    );
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Mysql,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("LIMIT ?) AS `Contacts`"));
+    assert!(result
+        .sql
+        .contains("WHERE t0.name = ? AND t0.industry = ?\nLIMIT ?"));
+    let bound: Vec<_> = result
+        .parameters
+        .iter()
+        .map(|p| {
+            (
+                p.name.as_str(),
+                p.original_name.as_str(),
+                p.placeholder.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        bound,
+        vec![
+            ("p1", "perAccount", "?"),
+            ("p2", "name", "?"),
+            ("p3", "industry", "?"),
+            ("p4", "maxRecords", "?"),
+        ]
+    );
+    assert!(result
+        .to_debug_sql()
+        .contains("t0.name = '<name>' /* :name */ AND t0.industry = '<industry>' /* :industry */"));
+}
+
+/// Debug SQL for a query comparing Name with the literal `name` and
+/// Industry with a bind
+fn debug_sql_with_literal(dialect: SqlDialect, name: &str) -> String {
+    let schema = create_test_schema();
+    let query = format!(
+        "SELECT Id FROM Account WHERE Name = '{}' AND Industry = :industry",
+        name
+    );
+    let soql = extract_soql(&query);
+    let config = ConversionConfig {
+        dialect,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    converter.convert(&soql).unwrap().to_debug_sql()
+}
+
+#[test]
+fn test_debug_sql_leaves_placeholders_in_literals() {
+    assert!(debug_sql_with_literal(SqlDialect::Postgres, "cost $1")
+        .ends_with("t0.name = 'cost $1' AND t0.industry = '<industry>' /* :industry */"));
+    assert!(debug_sql_with_literal(SqlDialect::Sqlite, r"it\'s ?1")
+        .ends_with("t0.name = 'it''s ?1' AND t0.industry = '<industry>' /* :industry */"));
+    assert!(debug_sql_with_literal(SqlDialect::Mysql, r"why?\\")
+        .ends_with(r"t0.name = 'why?\\' AND t0.industry = '<industry>' /* :industry */"));
+}
+
+#[test]
+fn test_id_equals_bind_postgres() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Name FROM Account WHERE Id = :recordId");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Postgres,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("WHERE t0.id = $1"));
+    assert_eq!(result.parameters.len(), 1);
+    assert_eq!(result.parameters[0].original_name, "recordId");
+    assert_eq!(result.parameters[0].placeholder, "$1");
+}
+
+#[test]
+fn test_id_equals_bind_sqlite() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Name FROM Account WHERE Id = :recordId");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("WHERE t0.id = ?1"));
+    assert_eq!(result.parameters.len(), 1);
+    assert_eq!(result.parameters[0].original_name, "recordId");
+    assert_eq!(result.parameters[0].placeholder, "?1");
+}
+
+#[test]
+fn test_id_equals_bind_is_single_record_by_pk() {
+    let schema = create_test_schema();
+
+    for source in [
+        "SELECT Id, Name FROM Account WHERE Id = :recordId",
+        "SELECT Id, Name FROM Account WHERE Id = :recordId LIMIT 1",
+        "SELECT Id FROM Account WHERE (Id = '001000000000001AAA')",
+    ] {
+        let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+        let result = converter.convert(&extract_soql(source)).unwrap();
+        assert_eq!(
+            result.result_shape,
+            ResultShape::SingleRecordByPk,
+            "{}",
+            source
+        );
+    }
+
+    for source in [
+        "SELECT Id FROM Account WHERE Name = :recordId",
+        "SELECT Id FROM Account WHERE Id = :recordId AND Industry = 'Tech'",
+        "SELECT Id FROM Account WHERE Id = :recordId LIMIT 5",
+        "SELECT Id FROM Account",
+    ] {
+        let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+        let result = converter.convert(&extract_soql(source)).unwrap();
+        assert_eq!(result.result_shape, ResultShape::Rows, "{}", source);
+    }
+}
+
+#[test]
+fn test_count_and_aggregate_result_shapes() {
+    let schema = create_test_schema();
+    let convert = |source: &str| {
+        let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+        converter.convert(&extract_soql(source)).unwrap()
+    };
+
+    for source in [
+        "SELECT COUNT() FROM Account",
+        "SELECT count() FROM Account WHERE Industry = 'Tech' LIMIT 100",
+        "SELECT COUNT() FROM Account WHERE Id = :recordId",
+    ] {
+        let result = convert(source);
+        assert_eq!(result.result_shape, ResultShape::Scalar, "{}", source);
+        assert!(
+            result.sql.starts_with("SELECT COUNT(*)\nFROM"),
+            "{}",
+            result.sql
+        );
+        assert!(result.column_map.is_empty(), "{}", source);
+    }
+
+    for source in [
+        "SELECT COUNT(Id) FROM Account",
+        "SELECT COUNT(Id) total, MAX(AnnualRevenue) FROM Account",
+        "SELECT Industry, COUNT(Id) FROM Account GROUP BY Industry",
+        "SELECT Industry FROM Account GROUP BY Industry",
+        "SELECT COUNT() FROM Account GROUP BY Industry",
+    ] {
+        assert_eq!(
+            convert(source).result_shape,
+            ResultShape::Aggregate,
+            "{}",
+            source
+        );
+    }
+}
+
 #[test]
 fn test_multiple_bind_variables() {
     let schema = create_test_schema();
@@ -325,6 +748,60 @@ fn test_multiple_bind_variables() {
     assert!(result.sql.contains("$3"));
 }
 
+#[test]
+fn test_bind_expression_parameter() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account WHERE Id IN :byId.keySet() LIMIT :limits[0]");
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let result = converter.convert(&soql).unwrap();
+
+    assert_eq!(result.parameters.len(), 2);
+    assert_eq!(result.parameters[0].original_name, "byId.keySet()");
+    assert!(result.parameters[0].collection);
+    assert_eq!(result.parameters[1].original_name, "limits[0]");
+}
+
+#[test]
+fn test_explain_sql_substitutes_samples() {
+    let schema = create_test_schema();
+    let soql = extract_soql(
+        "SELECT Id FROM Account WHERE Name = :name AND NumberOfEmployees > :size \
+         AND IsDeleted = :deleted AND Id IN :ids LIMIT :maxRecords",
+    );
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let result = converter.convert(&soql).unwrap();
+    let param = |name: &str| {
+        result
+            .parameters
+            .iter()
+            .find(|p| p.original_name == name)
+            .unwrap()
+    };
+    assert_eq!(param("size").value_type, Some(SalesforceFieldType::Integer));
+    assert!(param("ids").collection);
+    assert!(!param("name").collection);
+
+    let explain = result.explain_sql(true);
+    assert!(explain.starts_with("EXPLAIN (ANALYZE, BUFFERS) SELECT t0.id\n"));
+    assert!(explain.contains("t0.name = '…' /* sample for :name */"));
+    assert!(explain.contains("t0.number_of_employees > 0 /* sample for :size */"));
+    assert!(explain.contains("t0.is_deleted = TRUE /* sample for :deleted */"));
+    assert!(explain.contains("t0.id IN ('…') /* sample for :ids */"));
+    assert!(explain.contains("LIMIT 0 /* sample for :maxRecords */"));
+    assert!(result.explain_sql(false).starts_with("EXPLAIN SELECT"));
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let explain = converter.convert(&soql).unwrap().explain_sql(true);
+    assert!(explain.starts_with("EXPLAIN QUERY PLAN SELECT"));
+    assert!(explain.contains("t0.is_deleted = 1 /* sample for :deleted */"));
+}
+
 // =============================================================================
 // ORDER BY tests
 // =============================================================================
@@ -365,6 +842,24 @@ fn test_order_by_nulls() {
     assert!(result.sql.contains("NULLS LAST"));
 }
 
+#[test]
+fn test_order_by_nulls_mysql() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account ORDER BY Name DESC NULLS FIRST");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Mysql,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // MySQL has no NULLS FIRST/LAST
+    assert!(result
+        .sql
+        .ends_with("ORDER BY t0.name IS NULL DESC, t0.name DESC"));
+}
+
 #[test]
 fn test_order_by_multiple() {
     let schema = create_test_schema();
@@ -380,348 +875,1673 @@ fn test_order_by_multiple() {
     assert!(order_clause.contains("DESC"));
 }
 
-// =============================================================================
-// LIMIT and OFFSET tests
-// =============================================================================
-
 #[test]
-fn test_limit() {
+fn test_order_by_parent_field_not_selected() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id FROM Account LIMIT 10");
+    let soql = extract_soql("SELECT Id FROM Contact ORDER BY Account.Name, Account.Industry DESC");
 
     let config = ConversionConfig::default();
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("LIMIT 10"));
+    assert_eq!(
+        result.sql,
+        "SELECT t0.id\nFROM \"contact\" t0\n\
+         LEFT JOIN \"account\" t1 ON t0.account_id = t1.id\n\
+         ORDER BY t1.name, t1.industry DESC"
+    );
+    assert_eq!(result.column_map.len(), 1);
 }
 
 #[test]
-fn test_offset() {
+fn test_subquery_order_by_parent_field_not_selected() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id FROM Account LIMIT 10 OFFSET 20");
+    let soql =
+        extract_soql("SELECT Id, (SELECT Id FROM Contacts ORDER BY Account.Name) FROM Account");
 
     let config = ConversionConfig::default();
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("LIMIT 10"));
-    assert!(result.sql.contains("OFFSET 20"));
+    // The join belongs to the subquery, where its child alias is in scope
+    assert!(result
+        .sql
+        .contains("FROM \"contact\" t1 LEFT JOIN \"account\" t2 ON t1.account_id = t2.id WHERE"));
+    assert!(result.sql.contains("ORDER BY t2.name)"));
+    assert!(result.sql.ends_with("FROM \"account\" t0"));
 }
 
 // =============================================================================
-// Aggregate function tests
+// Id comparison tests
 // =============================================================================
 
+fn id_comparison_config(id_comparison: IdComparison) -> ConversionConfig {
+    ConversionConfig {
+        id_comparison,
+        ..Default::default()
+    }
+}
+
 #[test]
-fn test_count_star() {
+fn test_case_insensitive_id_comparison_joins() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT COUNT() FROM Account");
+    let soql = extract_soql(
+        "SELECT Id, Account.Name FROM Contact \
+         WHERE AccountId = '0015000000gv7qj' AND LastName != :name",
+    );
 
-    let config = ConversionConfig::default();
+    let config = id_comparison_config(IdComparison::CaseInsensitive);
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("COUNT("));
-}
-
-#[test]
-fn test_count_field() {
-    let schema = create_test_schema();
-    let soql = extract_soql("SELECT COUNT(Id) FROM Account");
+    assert!(result
+        .sql
+        .contains("LEFT JOIN \"account\" t1 ON UPPER(t0.account_id) = UPPER(t1.id)"));
+    assert!(result
+        .sql
+        .contains("WHERE UPPER(t0.account_id) = UPPER('0015000000gv7qj') AND t0.last_name != $1"));
+    assert_eq!(result.parameters[0].id_comparison, IdComparison::Exact);
 
-    let config = ConversionConfig::default();
+    let soql = extract_soql("SELECT Id, (SELECT Id FROM Contacts) FROM Account WHERE Id IN :ids");
+    let config = id_comparison_config(IdComparison::CaseInsensitive);
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("COUNT("));
+    assert!(result
+        .sql
+        .contains("WHERE UPPER(t1.account_id) = UPPER(t0.id)"));
+    assert!(result.sql.ends_with("WHERE UPPER(t0.id) IN $1"));
+    assert_eq!(
+        result.parameters[0].id_comparison,
+        IdComparison::CaseInsensitive
+    );
 }
 
 #[test]
-fn test_aggregate_with_alias() {
+fn test_normalize_to_18_id_literals() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT COUNT(Id) total FROM Account");
+    let soql = extract_soql(
+        "SELECT Id, Account.Name FROM Contact \
+         WHERE Id = '0035000000Gv7qJ' AND AccountId IN ('0015000000gv7qjaar', 'nonsense') \
+         AND Id != :excluded AND LastName = '0035000000Gv7qJ'",
+    );
 
-    let config = ConversionConfig::default();
+    let config = id_comparison_config(IdComparison::NormalizeTo18);
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("AS \"total\""));
+    assert!(result.sql.contains(
+        "WHERE t0.id = '0035000000Gv7qJAAR' \
+         AND t0.account_id IN ('0015000000Gv7qJAAR', 'nonsense')"
+    ));
+    // Only Id-typed fields are normalized; joins are left as they are
+    assert!(result.sql.contains("t0.last_name = '0035000000Gv7qJ'"));
+    assert!(result.sql.contains("ON t0.account_id = t1.id"));
+    assert_eq!(
+        result.parameters[0].id_comparison,
+        IdComparison::NormalizeTo18
+    );
 }
 
 #[test]
-fn test_sum_avg_min_max() {
+fn test_ddl_case_insensitive_id_indexes() {
     let schema = create_test_schema();
-    let soql = extract_soql(
-        "SELECT SUM(AnnualRevenue) s, AVG(NumberOfEmployees) a FROM Account GROUP BY Industry",
-    );
+    let contact = schema.get_object("Contact").unwrap();
 
-    let config = ConversionConfig::default();
-    let mut converter = SoqlToSqlConverter::new(&schema, config);
-    let result = converter.convert(&soql).unwrap();
+    let indexes = DdlGenerator::new(SqlDialect::Postgres)
+        .with_id_comparison(IdComparison::CaseInsensitive)
+        .generate_indexes(contact);
 
-    assert!(result.sql.contains("SUM("));
-    assert!(result.sql.contains("AVG("));
+    assert!(indexes.contains(
+        &"CREATE INDEX \"idx_contact_account_id_upper\" ON \"contact\" (UPPER(\"account_id\"))"
+            .to_string()
+    ));
+    assert!(indexes.contains(
+        &"CREATE INDEX \"idx_contact_id_upper\" ON \"contact\" (UPPER(\"id\"))".to_string()
+    ));
+    assert!(!indexes.contains(
+        &"CREATE INDEX \"idx_contact_account_id\" ON \"contact\" (\"account_id\")".to_string()
+    ));
 }
 
 // =============================================================================
-// GROUP BY and HAVING tests
+// LIMIT and OFFSET tests
 // =============================================================================
 
 #[test]
-fn test_group_by() {
+fn test_limit() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Industry, COUNT(Id) FROM Account GROUP BY Industry");
+    let soql = extract_soql("SELECT Id FROM Account LIMIT 10");
 
     let config = ConversionConfig::default();
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("GROUP BY"));
+    assert!(result.sql.contains("LIMIT 10"));
 }
 
 #[test]
-fn test_having() {
+fn test_offset() {
     let schema = create_test_schema();
-    // Use a simpler HAVING clause that the parser supports
-    let soql = extract_soql(
-        "SELECT Industry, COUNT(Id) cnt FROM Account GROUP BY Industry HAVING cnt > 5",
-    );
+    let soql = extract_soql("SELECT Id FROM Account LIMIT 10 OFFSET 20");
 
     let config = ConversionConfig::default();
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("HAVING"));
+    assert!(result.sql.contains("LIMIT 10"));
+    assert!(result.sql.contains("OFFSET 20"));
 }
 
-// =============================================================================
-// Relationship query tests
-// =============================================================================
-
 #[test]
-fn test_parent_relationship() {
+fn test_offset_without_limit_sqlite() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id, Account.Name FROM Contact");
+    let soql = extract_soql("SELECT Id FROM Account ORDER BY Name OFFSET 20");
 
-    let config = ConversionConfig::default();
+    let config = ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        ..Default::default()
+    };
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("LEFT JOIN"));
-    assert!(result.sql.contains("\"account\""));
+    assert!(result.sql.ends_with("LIMIT -1 OFFSET 20"), "{}", result.sql);
 }
 
 #[test]
-fn test_child_subquery() {
+fn test_offset_without_limit_postgres() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id, Name, (SELECT Id, Email FROM Contacts) FROM Account");
+    let soql = extract_soql("SELECT Id FROM Account ORDER BY Name OFFSET 20");
 
-    let config = ConversionConfig::default();
+    let config = ConversionConfig {
+        dialect: SqlDialect::Postgres,
+        ..Default::default()
+    };
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    // Should use JSON aggregation for subquery
-    assert!(result.sql.contains("json_agg") || result.sql.contains("json_group_array"));
+    assert!(result.sql.ends_with("OFFSET 20"), "{}", result.sql);
+    assert!(!result.sql.contains("LIMIT"));
 }
 
 // =============================================================================
-// FOR clause tests
+// Aggregate function tests
 // =============================================================================
 
 #[test]
-fn test_for_update_postgres() {
+fn test_count_star() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id FROM Account FOR UPDATE");
+    let soql = extract_soql("SELECT COUNT() FROM Account");
 
-    let config = ConversionConfig {
-        dialect: SqlDialect::Postgres,
-        ..Default::default()
-    };
+    let config = ConversionConfig::default();
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("FOR UPDATE"));
+    assert!(result.sql.contains("COUNT("));
 }
 
 #[test]
-fn test_for_update_sqlite_warning() {
+fn test_count_field() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id FROM Account FOR UPDATE");
+    let soql = extract_soql("SELECT COUNT(Id) FROM Account");
 
-    let config = ConversionConfig {
-        dialect: SqlDialect::Sqlite,
-        ..Default::default()
-    };
+    let config = ConversionConfig::default();
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    // SQLite doesn't support FOR UPDATE
-    assert!(!result.sql.contains("FOR UPDATE"));
-    assert!(!result.warnings.is_empty());
+    assert!(result.sql.contains("COUNT("));
 }
 
-// =============================================================================
-// Date literal tests
-// =============================================================================
-
 #[test]
-fn test_date_literal_today_postgres() {
+fn test_aggregate_with_alias() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id FROM Account WHERE CreatedDate = TODAY");
+    let soql = extract_soql("SELECT COUNT(Id) total FROM Account");
 
-    let config = ConversionConfig {
-        dialect: SqlDialect::Postgres,
-        ..Default::default()
-    };
+    let config = ConversionConfig::default();
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("CURRENT_DATE"));
+    assert!(result.sql.contains("AS \"total\""));
 }
 
 #[test]
-fn test_date_literal_today_sqlite() {
+fn test_unaliased_aggregates_named_like_salesforce() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id FROM Account WHERE CreatedDate = TODAY");
+    let soql = extract_soql(
+        "SELECT Industry, SUM(AnnualRevenue), COUNT(Id) total, MAX(NumberOfEmployees) \
+         FROM Account GROUP BY Industry",
+    );
 
-    let config = ConversionConfig {
-        dialect: SqlDialect::Sqlite,
-        ..Default::default()
-    };
+    let config = ConversionConfig::default();
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    assert!(result.sql.contains("date('now')"));
+    assert!(result.sql.contains("SUM(t0.annual_revenue) AS \"expr0\""));
+    assert!(result.sql.contains("COUNT(t0.id) AS \"total\""));
+    assert!(result
+        .sql
+        .contains("MAX(t0.number_of_employees) AS \"expr1\""));
+    assert_eq!(result.column_map["expr0"], "expr0");
+    assert_eq!(result.column_map["expr1"], "expr1");
+    assert_eq!(result.column_map["total"], "total");
 }
 
 #[test]
-fn test_date_literal_last_n_days() {
+fn test_count_without_field_has_no_alias() {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id FROM Account WHERE CreatedDate = LAST_N_DAYS:30");
+    let soql = extract_soql("SELECT COUNT() FROM Account");
 
     let config = ConversionConfig::default();
     let mut converter = SoqlToSqlConverter::new(&schema, config);
     let result = converter.convert(&soql).unwrap();
 
-    // Should expand to a range comparison
-    assert!(result.sql.contains(">="));
-    assert!(result.sql.contains("<"));
+    assert!(result.sql.starts_with("SELECT COUNT(*)\n"));
+    assert!(result.column_map.is_empty());
 }
 
-// =============================================================================
-// DDL generation tests
-// =============================================================================
-
 #[test]
-fn test_ddl_create_table_postgres() {
+fn test_sum_avg_min_max() {
     let schema = create_test_schema();
-    let generator = DdlGenerator::new(SqlDialect::Postgres);
+    let soql = extract_soql(
+        "SELECT SUM(AnnualRevenue) s, AVG(NumberOfEmployees) a FROM Account GROUP BY Industry",
+    );
 
-    let account = schema.get_object("Account").unwrap();
-    let ddl = generator.generate_table(account);
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
 
-    assert!(ddl.contains("CREATE TABLE \"account\""));
-    assert!(ddl.contains("\"id\" TEXT PRIMARY KEY"));
-    assert!(ddl.contains("\"name\" TEXT"));
-    assert!(ddl.contains("\"annual_revenue\" NUMERIC"));
-    assert!(ddl.contains("\"is_deleted\" BOOLEAN"));
+    assert!(result.sql.contains("SUM("));
+    assert!(result.sql.contains("AVG("));
 }
 
+// =============================================================================
+// GROUP BY and HAVING tests
+// =============================================================================
+
 #[test]
-fn test_ddl_create_table_sqlite() {
+fn test_group_by() {
     let schema = create_test_schema();
-    let generator = DdlGenerator::new(SqlDialect::Sqlite);
+    let soql = extract_soql("SELECT Industry, COUNT(Id) FROM Account GROUP BY Industry");
 
-    let account = schema.get_object("Account").unwrap();
-    let ddl = generator.generate_table(account);
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
 
-    assert!(ddl.contains("CREATE TABLE \"account\""));
-    // SQLite uses INTEGER for boolean
-    assert!(ddl.contains("\"is_deleted\" INTEGER"));
+    assert!(result.sql.contains("GROUP BY"));
 }
 
 #[test]
-fn test_ddl_polymorphic_field() {
+fn test_having() {
     let schema = create_test_schema();
-    let generator = DdlGenerator::new(SqlDialect::Postgres);
+    // Use a simpler HAVING clause that the parser supports
+    let soql = extract_soql(
+        "SELECT Industry, COUNT(Id) cnt FROM Account GROUP BY Industry HAVING cnt > 5",
+    );
 
-    let account = schema.get_object("Account").unwrap();
-    let ddl = generator.generate_table(account);
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
 
-    // Polymorphic OwnerId should have both ID and type columns
-    assert!(ddl.contains("\"owner_id\" TEXT"));
-    assert!(ddl.contains("\"owner_id_type\" TEXT"));
+    assert!(result.sql.contains("HAVING"));
 }
 
 #[test]
-fn test_ddl_foreign_key() {
+fn test_having_on_grouping_key() {
     let schema = create_test_schema();
-    let generator = DdlGenerator::new(SqlDialect::Postgres);
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
 
-    let contact = schema.get_object("Contact").unwrap();
-    let ddl = generator.generate_table(contact);
+    let soql = extract_soql(
+        "SELECT Industry, COUNT(Id) FROM Account GROUP BY Industry HAVING Industry != null",
+    );
+    let sql = converter.convert(&soql).unwrap().sql;
+    assert!(
+        sql.ends_with("GROUP BY t0.industry\nHAVING t0.industry IS NOT NULL"),
+        "{}",
+        sql
+    );
 
-    assert!(ddl.contains("FOREIGN KEY"));
-    assert!(ddl.contains("REFERENCES \"account\"(id)"));
+    let soql = extract_soql(
+        "SELECT Industry, COUNT(Id) FROM Account GROUP BY Industry \
+         HAVING Industry != null AND COUNT(Id) > 1",
+    );
+    let sql = converter.convert(&soql).unwrap().sql;
+    assert!(
+        sql.contains("HAVING t0.industry IS NOT NULL AND COUNT(t0.id) > 1"),
+        "{}",
+        sql
+    );
 }
 
 #[test]
-fn test_ddl_indexes() {
+fn test_group_by_rollup_and_cube() {
     let schema = create_test_schema();
-    let generator = DdlGenerator::new(SqlDialect::Postgres);
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
 
-    let contact = schema.get_object("Contact").unwrap();
-    let indexes = generator.generate_indexes(contact);
+    let soql = extract_soql(
+        "SELECT Industry, Type, GROUPING(Industry) grpIndustry, COUNT(Id) cnt \
+         FROM Account GROUP BY ROLLUP(Industry, Type)",
+    );
+    let sql = converter.convert(&soql).unwrap().sql;
+    assert!(
+        sql.contains("GROUPING(t0.industry) AS \"grpIndustry\""),
+        "{}",
+        sql
+    );
+    assert!(
+        sql.ends_with("GROUP BY ROLLUP (t0.industry, t0.type)"),
+        "{}",
+        sql
+    );
 
-    // Should have index for AccountId (lookup field)
-    assert!(indexes.iter().any(|i| i.contains("account_id")));
+    let soql =
+        extract_soql("SELECT Industry, Type, COUNT(Id) FROM Account GROUP BY CUBE(Industry, Type)");
+    let sql = converter.convert(&soql).unwrap().sql;
+    assert!(
+        sql.ends_with("GROUP BY CUBE (t0.industry, t0.type)"),
+        "{}",
+        sql
+    );
 }
 
 #[test]
-fn test_ddl_full_schema() {
+fn test_group_by_rollup_by_dialect() {
     let schema = create_test_schema();
-    let generator = DdlGenerator::new(SqlDialect::Postgres);
+    let convert = |source: &str, dialect: SqlDialect| {
+        let config = ConversionConfig {
+            dialect,
+            ..Default::default()
+        };
+        SoqlToSqlConverter::new(&schema, config).convert(&extract_soql(source))
+    };
 
-    let ddl = generator.generate_schema(&schema);
+    let rollup = "SELECT Industry, COUNT(Id) FROM Account GROUP BY ROLLUP(Industry)";
+    let sql = convert(rollup, SqlDialect::Mysql).unwrap().sql;
+    assert!(sql.ends_with("GROUP BY t0.industry WITH ROLLUP"), "{}", sql);
+    assert!(matches!(
+        convert(rollup, SqlDialect::Sqlite),
+        Err(ConversionError::UnsupportedSoqlFeature(_))
+    ));
 
-    assert!(ddl.contains("CREATE TABLE \"account\""));
-    assert!(ddl.contains("CREATE TABLE \"contact\""));
-    assert!(ddl.contains("CREATE TABLE \"opportunity\""));
-    assert!(ddl.contains("CREATE TABLE \"user\""));
-    assert!(ddl.contains("CREATE INDEX"));
+    let cube = "SELECT Industry, COUNT(Id) FROM Account GROUP BY CUBE(Industry)";
+    assert!(matches!(
+        convert(cube, SqlDialect::Mysql),
+        Err(ConversionError::UnsupportedSoqlFeature(_))
+    ));
 }
 
 // =============================================================================
-// Configuration tests
+// Collation tests
 // =============================================================================
 
-#[test]
-fn test_filter_deleted() {
+fn convert_with_collation(soql: &str, dialect: SqlDialect, mode: CollationMode) -> String {
     let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id FROM Account");
-
     let config = ConversionConfig {
-        filter_deleted: true,
+        dialect,
+        string_collation: mode,
         ..Default::default()
     };
     let mut converter = SoqlToSqlConverter::new(&schema, config);
-    let result = converter.convert(&soql).unwrap();
+    converter.convert(&extract_soql(soql)).unwrap().sql
+}
 
-    assert!(result.sql.contains("is_deleted"));
-    assert!(result.sql.contains("FALSE") || result.sql.contains("false"));
+#[test]
+fn test_case_insensitive_order_by_postgres() {
+    let sql = convert_with_collation(
+        "SELECT Id FROM Account ORDER BY Name, AnnualRevenue DESC",
+        SqlDialect::Postgres,
+        CollationMode::CaseInsensitive,
+    );
+
+    assert!(sql.contains("ORDER BY LOWER(t0.name), t0.annual_revenue DESC"));
 }
 
 #[test]
-fn test_security_mode_warning() {
-    let schema = create_test_schema();
-    let soql = extract_soql("SELECT Id FROM Account WITH SECURITY_ENFORCED");
+fn test_case_insensitive_order_by_sqlite() {
+    let sql = convert_with_collation(
+        "SELECT Id FROM Account ORDER BY Name, AnnualRevenue DESC",
+        SqlDialect::Sqlite,
+        CollationMode::CaseInsensitive,
+    );
 
-    let config = ConversionConfig::default();
-    let mut converter = SoqlToSqlConverter::new(&schema, config);
-    let result = converter.convert(&soql).unwrap();
+    assert!(sql.contains("ORDER BY t0.name COLLATE NOCASE, t0.annual_revenue DESC"));
+}
 
-    // Security clause should be removed but recorded
-    assert!(result.security_mode.is_some());
-    assert!(!result.warnings.is_empty());
+#[test]
+fn test_explicit_collation_order_by() {
+    let sql = convert_with_collation(
+        "SELECT Id FROM Account ORDER BY Name, AnnualRevenue",
+        SqlDialect::Postgres,
+        CollationMode::Explicit("und-x-icu".to_string()),
+    );
+
+    assert!(sql.contains("ORDER BY t0.name COLLATE \"und-x-icu\", t0.annual_revenue"));
+}
+
+#[test]
+fn test_collation_follows_parent_relationship() {
+    let sql = convert_with_collation(
+        "SELECT Id FROM Contact ORDER BY Account.Name",
+        SqlDialect::Sqlite,
+        CollationMode::CaseInsensitive,
+    );
+
+    assert!(sql.contains("ORDER BY t1.name COLLATE NOCASE"));
+}
+
+#[test]
+fn test_collation_leaves_where_alone() {
+    let sql = convert_with_collation(
+        "SELECT Id FROM Account WHERE Name = 'Acme' ORDER BY Name",
+        SqlDialect::Postgres,
+        CollationMode::CaseInsensitive,
+    );
+
+    assert!(sql.contains("WHERE t0.name = 'Acme'"));
+    assert!(sql.contains("ORDER BY LOWER(t0.name)"));
+}
+
+#[test]
+fn test_case_insensitive_group_by() {
+    let soql = "SELECT Industry, COUNT(Id) c FROM Account GROUP BY Industry";
+
+    let postgres =
+        convert_with_collation(soql, SqlDialect::Postgres, CollationMode::CaseInsensitive);
+    assert!(postgres.contains("SELECT MIN(t0.industry) AS \"industry\""));
+    assert!(postgres.contains("GROUP BY LOWER(t0.industry)"));
+
+    let sqlite = convert_with_collation(soql, SqlDialect::Sqlite, CollationMode::CaseInsensitive);
+    assert!(sqlite.contains("SELECT t0.industry,"));
+    assert!(sqlite.contains("GROUP BY t0.industry COLLATE NOCASE"));
+}
+
+#[test]
+fn test_case_insensitive_group_by_mysql() {
+    let sql = convert_with_collation(
+        "SELECT Industry, COUNT(Id) c FROM Account GROUP BY Industry",
+        SqlDialect::Mysql,
+        CollationMode::CaseInsensitive,
+    );
+
+    assert!(
+        sql.contains("SELECT MIN(t0.industry) AS `industry`"),
+        "{}",
+        sql
+    );
+    assert!(sql.contains("GROUP BY LOWER(t0.industry)"), "{}", sql);
+}
+
+#[test]
+fn test_grouping_uses_collated_key() {
+    let sql = convert_with_collation(
+        "SELECT Industry, GROUPING(Industry) g FROM Account GROUP BY ROLLUP(Industry)",
+        SqlDialect::Postgres,
+        CollationMode::CaseInsensitive,
+    );
+
+    assert!(
+        sql.contains("GROUPING(LOWER(t0.industry)) AS \"g\""),
+        "{}",
+        sql
+    );
+    assert!(
+        sql.ends_with("GROUP BY ROLLUP (LOWER(t0.industry))"),
+        "{}",
+        sql
+    );
+}
+
+// =============================================================================
+// Relationship query tests
+// =============================================================================
+
+#[test]
+fn test_parent_relationship() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Account.Name FROM Contact");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("LEFT JOIN"));
+    assert!(result.sql.contains("\"account\""));
+}
+
+#[test]
+fn test_parent_relationship_in_where() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Contact WHERE Account.Industry = 'Tech'");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // WHERE-only relationships still get a LEFT JOIN; the comparison
+    // against the NULL parent columns is what drops orphaned rows
+    assert_eq!(result.sql.matches("JOIN").count(), 1);
+    assert!(result
+        .sql
+        .contains("LEFT JOIN \"account\" t1 ON t0.account_id = t1.id"));
+    assert!(result.sql.contains("WHERE t1.industry = 'Tech'"));
+}
+
+#[test]
+fn test_parent_relationship_join_reused_across_clauses() {
+    let schema = create_test_schema();
+    let soql = extract_soql(
+        "SELECT Id, Account.Name FROM Contact WHERE Account.Industry = 'Tech' ORDER BY Account.Name",
+    );
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert_eq!(result.sql.matches("LEFT JOIN").count(), 1);
+    assert!(result.sql.contains("SELECT t0.id, t1.name"));
+    assert!(result.sql.contains("WHERE t1.industry = 'Tech'"));
+    assert!(result.sql.contains("ORDER BY t1.name"));
+}
+
+#[test]
+fn test_parent_relationship_with_semi_join() {
+    let mut schema = create_test_schema();
+    let mut member = SObjectDescribe::new("CampaignMember");
+    member.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+    member.add_field(
+        FieldDescribe::new("ContactId", SalesforceFieldType::Lookup)
+            .with_reference("Contact")
+            .with_relationship_name("Contact"),
+    );
+    schema.add_object(member);
+    let soql = extract_soql(
+        "SELECT Id FROM Contact WHERE Account.Industry = 'Tech' AND Id IN (SELECT ContactId FROM CampaignMember)",
+    );
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // The parent JOIN belongs to the outer query only; the semi-join is a
+    // self-contained subquery beside it in the same WHERE
+    assert_eq!(result.sql.matches("JOIN").count(), 1);
+    assert!(result
+        .sql
+        .contains("LEFT JOIN \"account\" t1 ON t0.account_id = t1.id"));
+    assert!(result
+        .sql
+        .contains("WHERE t1.industry = 'Tech' AND t0.id IN (SELECT"));
+    assert!(result.sql.contains("contact_id"));
+    assert!(result.sql.contains("\"campaign_member\""));
+}
+
+#[test]
+fn test_anti_join_with_parent_filter() {
+    let schema = create_test_schema();
+    let soql = extract_soql(
+        "SELECT Id FROM Account WHERE Id NOT IN \
+         (SELECT AccountId FROM Contact WHERE Email != null AND Account.Industry = :industry) \
+         AND Name = :name",
+    );
+
+    let config = ConversionConfig {
+        filter_deleted: true,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert_eq!(
+        result.sql,
+        "SELECT t0.id\nFROM \"account\" t0\n\
+         WHERE (t0.id NOT IN (SELECT s0.account_id FROM \"contact\" s0 \
+         LEFT JOIN \"account\" s1 ON s0.account_id = s1.id AND s1.\"is_deleted\" = FALSE \
+         WHERE (s0.email IS NOT NULL AND s1.industry = $2) \
+         AND s0.account_id IS NOT NULL AND s0.\"is_deleted\" = FALSE) \
+         AND t0.name = $1) AND t0.\"is_deleted\" = FALSE"
+    );
+    assert_eq!(result.parameters[1].original_name, "industry");
+}
+
+#[test]
+fn test_anti_join_keeps_written_null_check() {
+    let schema = create_test_schema();
+    let soql = extract_soql(
+        "SELECT Id FROM Account WHERE Id NOT IN \
+         (SELECT AccountId FROM Contact WHERE Email != null AND AccountId != null)",
+    );
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let result = converter.convert(&soql).unwrap();
+
+    assert_eq!(
+        result.sql,
+        "SELECT t0.id\nFROM \"account\" t0\n\
+         WHERE t0.id NOT IN (SELECT s0.account_id FROM \"contact\" s0 \
+         WHERE s0.email IS NOT NULL AND s0.account_id IS NOT NULL)"
+    );
+}
+
+#[test]
+fn test_or_across_relationships() {
+    let schema = create_test_schema();
+    let soql = extract_soql(
+        "SELECT Id FROM Contact WHERE Account.Industry = 'Tech' OR Account.Owner.Name = 'Alice'",
+    );
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // Both sides of the OR must see rows whose other relationship is
+    // missing, so neither join may become an INNER JOIN
+    assert!(!result.sql.contains("INNER JOIN"));
+    assert_eq!(result.sql.matches("LEFT JOIN").count(), 2);
+    assert!(result
+        .sql
+        .contains("LEFT JOIN \"account\" t1 ON t0.account_id = t1.id"));
+    assert!(result
+        .sql
+        .contains("LEFT JOIN \"user\" t2 ON t1.owner_id = t2.id AND t1.owner_id_type = 'User'"));
+    assert!(result
+        .sql
+        .contains("WHERE t1.industry = 'Tech' OR t2.name = 'Alice'"));
+}
+
+#[test]
+fn test_same_relationship_name_on_different_objects() {
+    let mut schema = create_test_schema();
+    schema.get_object_mut("Contact").unwrap().add_field(
+        FieldDescribe::new("OwnerId", SalesforceFieldType::Lookup)
+            .with_reference("User")
+            .with_relationship_name("Owner"),
+    );
+    let mut group = SObjectDescribe::new("Group");
+    group.add_field(FieldDescribe::new("Id", SalesforceFieldType::Id).with_nillable(false));
+    group.add_field(FieldDescribe::new("Name", SalesforceFieldType::String));
+    schema.add_object(group);
+    let soql = extract_soql("SELECT Owner.Name, Account.Owner.Name FROM Contact");
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let result = converter.convert(&soql).unwrap();
+
+    // Contact.Owner is a plain User lookup; Account.Owner is polymorphic
+    // (User|Group) and joins each target through the Account join
+    assert!(result
+        .sql
+        .contains("LEFT JOIN \"user\" t1 ON t0.owner_id = t1.id"));
+    assert!(result
+        .sql
+        .contains("LEFT JOIN \"account\" t2 ON t0.account_id = t2.id"));
+    assert!(result
+        .sql
+        .contains("LEFT JOIN \"user\" t3 ON t2.owner_id = t3.id AND t2.owner_id_type = 'User'"));
+    assert!(result
+        .sql
+        .contains("LEFT JOIN \"group\" t4 ON t2.owner_id = t4.id AND t2.owner_id_type = 'Group'"));
+    assert!(result
+        .sql
+        .starts_with("SELECT t1.name, COALESCE(t3.name, t4.name) AS \"Account.Owner.Name\"\n"));
+    assert_ne!(
+        result.column_map["Owner.Name"],
+        result.column_map["Account.Owner.Name"]
+    );
+}
+
+#[test]
+fn test_child_subquery() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Name, (SELECT Id, Email FROM Contacts) FROM Account");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // Should use JSON aggregation for subquery
+    assert!(result.sql.contains("json_agg") || result.sql.contains("json_group_array"));
+}
+
+#[test]
+fn test_child_subquery_mysql() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Name, (SELECT Id, Email FROM Contacts) FROM Account");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Mysql,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains(
+        "(SELECT JSON_ARRAYAGG(JSON_OBJECT('Id', t1.id, 'Email', t1.email)) \
+         FROM `contact` t1 WHERE t1.account_id = t0.id) AS `Contacts`"
+    ));
+}
+
+#[test]
+fn test_child_subquery_with_aggregate_is_rejected() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT COUNT(Id), (SELECT Id FROM Contacts) FROM Account");
+
+    let err = SoqlToSqlConverter::new(&schema, ConversionConfig::default())
+        .convert(&soql)
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        ConversionError::InvalidSelectCombination {
+            relationship: "Contacts".to_string(),
+            aggregate: "COUNT".to_string(),
+        }
+    );
+    assert!(err.to_string().contains("'Contacts'"));
+}
+
+#[test]
+fn test_convert_collecting_reports_every_unknown_field() {
+    let schema = create_test_schema();
+    let soql = extract_soql(
+        "SELECT Id, Nmae, Account.Revenue__c, (SELECT Id FROM Cases) FROM Contact WHERE Bogus.Name = 'x' AND Nmae = 'y'",
+    );
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let (conversion, errors) = converter.convert_collecting(&soql);
+
+    assert!(conversion.is_none());
+    // SELECT fields have no span of their own; WHERE fields do
+    let query = Some(soql.span);
+    assert_eq!(
+        errors,
+        vec![
+            ConversionError::UnknownField {
+                object: "Contact".to_string(),
+                field: "Nmae".to_string(),
+                span: query,
+            },
+            ConversionError::UnknownField {
+                object: "Account".to_string(),
+                field: "Revenue__c".to_string(),
+                span: query,
+            },
+            ConversionError::UnknownChildRelationship("Cases".to_string(), "Contact".to_string()),
+            ConversionError::NotARelationship {
+                relationship: "Bogus".to_string(),
+                span: errors[3].span(),
+            },
+        ]
+    );
+    assert_ne!(errors[3].span(), query);
+}
+
+#[test]
+fn test_convert_collecting_valid_query() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Account.Name FROM Contact WHERE LastName = 'Smith'");
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let (conversion, errors) = converter.convert_collecting(&soql);
+
+    assert!(errors.is_empty());
+    assert!(conversion.unwrap().sql.contains("t0.last_name = 'Smith'"));
+
+    // Plain convert still stops at the first bad relationship
+    let soql = extract_soql("SELECT Bogus.Name, Other.Name FROM Contact");
+    assert_eq!(
+        converter.convert(&soql).unwrap_err(),
+        ConversionError::NotARelationship {
+            relationship: "Bogus".to_string(),
+            span: Some(soql.span),
+        }
+    );
+}
+
+#[test]
+fn test_unknown_field_in_where_has_its_span() {
+    let schema = create_test_schema();
+    let source = "class Test {\n    void test() {\n        List<Contact> x = [SELECT Id FROM Contact\n            WHERE Nmae = 'y'];\n    }\n}";
+    let unit = parse(source).expect("Parse failed");
+    let apexrust::TypeDeclaration::Class(class) = &unit.declarations[0] else {
+        panic!("expected a class");
+    };
+    let apexrust::ClassMember::Method(method) = &class.members[0] else {
+        panic!("expected a method");
+    };
+    let apexrust::Statement::LocalVariable(local) = &method.body.as_ref().unwrap().statements[0]
+    else {
+        panic!("expected a local variable");
+    };
+    let Some(apexrust::Expression::Soql(query)) = &local.declarators[0].initializer else {
+        panic!("expected a query");
+    };
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let (_, errors) = converter.convert_collecting(query);
+
+    let span = errors[0].span().expect("span");
+    assert!(source[span.start..].starts_with("Nmae = 'y'"));
+    assert_eq!((span.line, span.column), (4, 19));
+}
+
+// =============================================================================
+// FOR clause tests
+// =============================================================================
+
+#[test]
+fn test_for_update_postgres() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account FOR UPDATE");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Postgres,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("FOR UPDATE"));
+}
+
+#[test]
+fn test_for_update_sqlite_warning() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account FOR UPDATE");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // SQLite doesn't support FOR UPDATE
+    assert!(!result.sql.contains("FOR UPDATE"));
+    assert!(!result.warnings.is_empty());
+}
+
+#[test]
+fn test_for_update_mysql() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account FOR UPDATE");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Mysql,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.ends_with("\nFOR UPDATE"));
+    assert!(result.warnings.is_empty());
+}
+
+// =============================================================================
+// Date literal tests
+// =============================================================================
+
+#[test]
+fn test_date_literal_today_postgres() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account WHERE CreatedDate = TODAY");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Postgres,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("CURRENT_DATE"));
+}
+
+#[test]
+fn test_date_literal_today_sqlite() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account WHERE CreatedDate = TODAY");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("date('now')"));
+}
+
+#[test]
+fn test_date_literal_last_n_days_mysql() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account WHERE CreatedDate = LAST_N_DAYS:30");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Mysql,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result
+        .sql
+        .contains("t0.created_date >= DATE_SUB(CURRENT_DATE, INTERVAL 30 DAY)"));
+}
+
+#[test]
+fn test_date_literal_last_n_days() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account WHERE CreatedDate = LAST_N_DAYS:30");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // Should expand to a range comparison
+    assert!(result.sql.contains(">="));
+    assert!(result.sql.contains("<"));
+}
+
+#[test]
+fn test_date_literals_with_fixed_clock() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account WHERE CreatedDate = TODAY");
+    let convert = |dialect: SqlDialect, date: (i32, u32, u32)| {
+        let config = ConversionConfig {
+            dialect,
+            clock: ClockSource::Fixed(Timestamp::from_ymd(date.0, date.1, date.2).unwrap()),
+            ..Default::default()
+        };
+        SoqlToSqlConverter::new(&schema, config)
+            .convert(&soql)
+            .unwrap()
+            .sql
+    };
+
+    let first = convert(SqlDialect::Sqlite, (2024, 6, 30));
+    assert_eq!(first, convert(SqlDialect::Sqlite, (2024, 6, 30)));
+    assert!(
+        first.ends_with("WHERE DATE(t0.created_date) = date('2024-06-30')"),
+        "{}",
+        first
+    );
+    assert!(!first.contains("'now'"));
+    assert!(convert(SqlDialect::Sqlite, (2025, 1, 1)).contains("date('2025-01-01')"));
+    assert!(convert(SqlDialect::Postgres, (2024, 6, 30)).contains("= DATE '2024-06-30'"));
+    assert!(convert(SqlDialect::Mysql, (2024, 6, 30)).contains("= DATE '2024-06-30'"));
+}
+
+// =============================================================================
+// DDL generation tests
+// =============================================================================
+
+#[test]
+fn test_ddl_create_table_postgres() {
+    let schema = create_test_schema();
+    let generator = DdlGenerator::new(SqlDialect::Postgres);
+
+    let account = schema.get_object("Account").unwrap();
+    let ddl = generator.generate_table(account);
+
+    assert!(ddl.contains("CREATE TABLE \"account\""));
+    assert!(ddl.contains("\"id\" TEXT PRIMARY KEY"));
+    assert!(ddl.contains("\"name\" TEXT"));
+    assert!(ddl.contains("\"annual_revenue\" NUMERIC"));
+    assert!(ddl.contains("\"is_deleted\" BOOLEAN"));
+}
+
+#[test]
+fn test_ddl_create_table_sqlite() {
+    let schema = create_test_schema();
+    let generator = DdlGenerator::new(SqlDialect::Sqlite);
+
+    let account = schema.get_object("Account").unwrap();
+    let ddl = generator.generate_table(account);
+
+    assert!(ddl.contains("CREATE TABLE \"account\""));
+    // SQLite uses INTEGER for boolean
+    assert!(ddl.contains("\"is_deleted\" INTEGER"));
+}
+
+#[test]
+fn test_ddl_create_table_mysql() {
+    let schema = create_test_schema();
+    let generator = DdlGenerator::new(SqlDialect::Mysql);
+
+    let account = schema.get_object("Account").unwrap();
+    let ddl = generator.generate_table(account);
+
+    assert!(ddl.contains("CREATE TABLE `account`"));
+    // TEXT can't be a key or indexed without a prefix length
+    assert!(ddl.contains("`id` VARCHAR(18) PRIMARY KEY"));
+    assert!(ddl.contains("`name` VARCHAR(255)"));
+    assert!(ddl.contains("`annual_revenue` DOUBLE"));
+    assert!(ddl.contains("`created_date` DATETIME"));
+    assert!(ddl.contains("`is_deleted` BOOLEAN"));
+}
+
+#[test]
+fn test_ddl_polymorphic_field() {
+    let schema = create_test_schema();
+    let generator = DdlGenerator::new(SqlDialect::Postgres);
+
+    let account = schema.get_object("Account").unwrap();
+    let ddl = generator.generate_table(account);
+
+    // Polymorphic OwnerId should have both ID and type columns
+    assert!(ddl.contains("\"owner_id\" TEXT"));
+    assert!(ddl.contains("\"owner_id_type\" TEXT"));
+}
+
+#[test]
+fn test_ddl_foreign_key() {
+    let schema = create_test_schema();
+    let generator = DdlGenerator::new(SqlDialect::Postgres);
+
+    let contact = schema.get_object("Contact").unwrap();
+    let ddl = generator.generate_table(contact);
+
+    assert!(ddl.contains("FOREIGN KEY"));
+    assert!(ddl.contains("REFERENCES \"account\"(id)"));
+}
+
+#[test]
+fn test_ddl_indexes() {
+    let schema = create_test_schema();
+    let generator = DdlGenerator::new(SqlDialect::Postgres);
+
+    let contact = schema.get_object("Contact").unwrap();
+    let indexes = generator.generate_indexes(contact);
+
+    // Should have index for AccountId (lookup field)
+    assert!(indexes.iter().any(|i| i.contains("account_id")));
+}
+
+#[test]
+fn test_ddl_full_schema() {
+    let schema = create_test_schema();
+    let generator = DdlGenerator::new(SqlDialect::Postgres);
+
+    let ddl = generator.generate_schema(&schema);
+
+    assert!(ddl.contains("CREATE TABLE \"account\""));
+    assert!(ddl.contains("CREATE TABLE \"contact\""));
+    assert!(ddl.contains("CREATE TABLE \"opportunity\""));
+    assert!(ddl.contains("CREATE TABLE \"user\""));
+    assert!(ddl.contains("CREATE INDEX"));
+}
+
+#[test]
+fn test_ddl_contact_references_account() {
+    let schema = create_test_schema();
+    for (dialect, q) in [
+        (SqlDialect::Postgres, '"'),
+        (SqlDialect::Sqlite, '"'),
+        (SqlDialect::Mysql, '`'),
+    ] {
+        let ddl = DdlGenerator::new(dialect).generate_schema(&schema);
+        let contact = ddl
+            .split(";\n")
+            .map(str::trim_start)
+            .find(|stmt| stmt.starts_with(&format!("CREATE TABLE {q}contact{q}")))
+            .unwrap();
+        let constraint = format!("FOREIGN KEY ({q}account_id{q}) REFERENCES {q}account{q}(id)");
+        assert!(contact.contains(&constraint), "{:?}: {}", dialect, contact);
+
+        // The parent exists by the time the reference is declared
+        let account = ddl.find(&format!("CREATE TABLE {q}account{q}")).unwrap();
+        assert!(account < ddl.find(contact).unwrap());
+    }
+}
+
+// =============================================================================
+// Schema qualification tests
+// =============================================================================
+
+#[test]
+fn test_schema_qualifier_applies_to_joins_and_subqueries() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Owner.Name, (SELECT Id, Email FROM Contacts) FROM Account");
+
+    let config = ConversionConfig {
+        schema_qualifier: Some("org_a".to_string()),
+        schema_overrides: HashMap::from([("user".to_string(), "shared".to_string())]),
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("FROM \"org_a\".\"account\" t0"));
+    assert!(result.sql.contains("LEFT JOIN \"shared\".\"user\" t1 ON"));
+    assert!(result.sql.contains("FROM \"org_a\".\"contact\" t2 WHERE"));
+    // Aliases and JSON keys stay unqualified
+    assert!(result.sql.contains("'Email', t2.email"));
+    assert!(result.sql.contains("AS \"Contacts\""));
+}
+
+#[test]
+fn test_schema_qualifier_is_quoted() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account");
+
+    let config = ConversionConfig {
+        schema_qualifier: Some("org\"a".to_string()),
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("FROM \"org\"\"a\".\"account\" t0"));
+}
+
+#[test]
+fn test_schema_qualifier_sqlite_modes() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Account.Name FROM Contact");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        schema_qualifier: Some("org_a".to_string()),
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config.clone());
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("FROM \"contact\" t0"));
+    assert!(result.sql.contains("LEFT JOIN \"account\" t1"));
+    assert_eq!(
+        result.warnings,
+        vec![ConversionWarning::SchemaQualifierIgnored {
+            schema: "org_a".to_string()
+        }]
+    );
+
+    let config = ConversionConfig {
+        sqlite_qualifier: SqliteQualifier::AttachedDatabase,
+        ..config
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("FROM \"org_a\".\"contact\" t0"));
+    assert!(result.sql.contains("LEFT JOIN \"org_a\".\"account\" t1"));
+    assert!(result.warnings.is_empty());
+}
+
+#[test]
+fn test_ddl_schema_qualifier() {
+    let schema = create_test_schema();
+    let generator = DdlGenerator::new(SqlDialect::Postgres)
+        .with_schema_qualifier("org_a")
+        .with_schema_override("User", "shared");
+
+    let contact = schema.get_object("Contact").unwrap();
+    let ddl = generator.generate_table(contact);
+    assert!(ddl.contains("CREATE TABLE \"org_a\".\"contact\""));
+    assert!(ddl.contains("REFERENCES \"org_a\".\"account\"(id)"));
+
+    let indexes = generator.generate_indexes(contact);
+    assert!(indexes.contains(
+        &"CREATE INDEX \"idx_contact_account_id\" ON \"org_a\".\"contact\" (\"account_id\")"
+            .to_string()
+    ));
+
+    let user = schema.get_object("User").unwrap();
+    assert!(generator
+        .generate_table(user)
+        .contains("CREATE TABLE \"shared\".\"user\""));
+    assert_eq!(
+        generator.generate_drop_table(user),
+        "DROP TABLE IF EXISTS \"shared\".\"user\""
+    );
+}
+
+#[test]
+fn test_ddl_schema_qualifier_sqlite_attached() {
+    let schema = create_test_schema();
+    let contact = schema.get_object("Contact").unwrap();
+
+    let generator = DdlGenerator::new(SqlDialect::Sqlite).with_schema_qualifier("org_a");
+    assert!(generator
+        .generate_table(contact)
+        .contains("CREATE TABLE \"contact\""));
+
+    let generator = generator.with_sqlite_qualifier(SqliteQualifier::AttachedDatabase);
+    assert!(generator
+        .generate_table(contact)
+        .contains("CREATE TABLE \"org_a\".\"contact\""));
+    // SQLite qualifies the index name; the table must stay bare
+    assert!(generator.generate_indexes(contact).contains(
+        &"CREATE INDEX \"org_a\".\"idx_contact_account_id\" ON \"contact\" (\"account_id\")"
+            .to_string()
+    ));
+}
+
+// =============================================================================
+// Configuration tests
+// =============================================================================
+
+#[test]
+fn test_filter_deleted() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account");
+
+    let config = ConversionConfig {
+        filter_deleted: true,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("is_deleted"));
+    assert!(result.sql.contains("FALSE") || result.sql.contains("false"));
+}
+
+#[test]
+fn test_filter_deleted_applies_to_joins_and_subqueries() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Owner.Name, (SELECT Id, Email FROM Contacts) FROM Account");
+
+    let config = ConversionConfig {
+        filter_deleted: true,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // Root table: t0, joined parent (User): t1, child subquery: t2
+    let join_line = result
+        .sql
+        .lines()
+        .find(|l| l.starts_with("LEFT JOIN"))
+        .expect("expected a parent join");
+    assert!(
+        join_line.ends_with("AND t1.\"is_deleted\" = FALSE"),
+        "soft-delete filter should be part of the JOIN ON condition: {}",
+        join_line
+    );
+    assert!(result
+        .sql
+        .contains("WHERE t2.account_id = t0.id AND t2.\"is_deleted\" = FALSE"));
+    assert!(result.sql.contains("\nWHERE t0.\"is_deleted\" = FALSE"));
+}
+
+#[test]
+fn test_filter_deleted_disabled_removes_all_filters() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Owner.Name, (SELECT Id, Email FROM Contacts) FROM Account");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(!result.sql.contains("is_deleted"));
+}
+
+#[test]
+fn test_filter_deleted_custom_column() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id, Account.Name FROM Contact WHERE Email != null");
+
+    let config = ConversionConfig {
+        filter_deleted: true,
+        deleted_column: "_fivetran_deleted".to_string(),
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("t1.\"_fivetran_deleted\" = FALSE"));
+    assert!(result.sql.contains("AND t0.\"_fivetran_deleted\" = FALSE"));
+    assert!(!result.sql.contains("is_deleted"));
+}
+
+#[test]
+fn test_filter_deleted_quotes_mixed_case_column() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account");
+
+    let config = ConversionConfig {
+        dialect: SqlDialect::Mysql,
+        filter_deleted: true,
+        deleted_column: "IsDeleted".to_string(),
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(
+        result.sql.ends_with("WHERE t0.`IsDeleted` = FALSE"),
+        "{}",
+        result.sql
+    );
+}
+
+#[test]
+fn test_filter_deleted_skips_objects_without_is_deleted() {
+    let schema = create_test_schema();
+    // Opportunity has no IsDeleted field in the test schema
+    let soql = extract_soql("SELECT Id, Account.Name FROM Opportunity");
+
+    let config = ConversionConfig {
+        filter_deleted: true,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(!result.sql.contains("t0.is_deleted"));
+    assert!(result.sql.contains("AND t1.\"is_deleted\" = FALSE"));
+    assert!(result.warnings.is_empty());
+}
+
+#[test]
+fn test_filter_deleted_without_schema_warns() {
+    let soql = extract_soql("SELECT Id FROM Account");
+
+    let config = ConversionConfig {
+        filter_deleted: true,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new_without_schema(config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(!result.sql.contains("is_deleted"));
+    assert_eq!(
+        result.warnings,
+        vec![ConversionWarning::DeletedFilterSkipped {
+            object: "Account".to_string()
+        }]
+    );
+}
+
+#[test]
+fn test_security_mode_warning() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account WITH SECURITY_ENFORCED");
+
+    let config = ConversionConfig::default();
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    // Security clause should be removed but recorded
+    assert!(result.security_mode.is_some());
+    assert!(!result.warnings.is_empty());
+}
+
+#[test]
+fn test_using_scope_warning() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account USING SCOPE mine WHERE Name = 'Acme'");
+    assert_eq!(soql.using_scope.as_deref(), Some("mine"));
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("WHERE t0.name = 'Acme'"));
+    assert!(!result.sql.to_uppercase().contains("SCOPE"));
+    assert_eq!(
+        result.warnings,
+        vec![ConversionWarning::SalesforceOnlyClause {
+            clause: "USING SCOPE".to_string()
+        }]
+    );
+}
+
+// =============================================================================
+// Big object and external object tests
+// =============================================================================
+
+/// Schema with a `Game_Play__b` big object indexed on
+/// (Player__c, Platform__c, Played_At__c)
+fn create_big_object_schema() -> SalesforceSchema {
+    let mut schema = SalesforceSchema::new();
+    let mut plays = SObjectDescribe::new("Game_Play__b").with_index_fields(vec![
+        "Player__c".to_string(),
+        "Platform__c".to_string(),
+        "Played_At__c".to_string(),
+    ]);
+    plays.add_field(FieldDescribe::new("Player__c", SalesforceFieldType::String));
+    plays.add_field(FieldDescribe::new(
+        "Platform__c",
+        SalesforceFieldType::String,
+    ));
+    plays.add_field(FieldDescribe::new(
+        "Played_At__c",
+        SalesforceFieldType::DateTime,
+    ));
+    plays.add_field(FieldDescribe::new("Score__c", SalesforceFieldType::Double));
+    schema.add_object(plays);
+    schema
+}
+
+#[test]
+fn test_object_kind_from_suffix() {
+    assert_eq!(
+        SObjectDescribe::new("Account").object_kind,
+        ObjectKind::Standard
+    );
+    assert_eq!(
+        SObjectDescribe::new("Invoice__c").object_kind,
+        ObjectKind::Custom
+    );
+    assert_eq!(
+        SObjectDescribe::new("Game_Play__b").object_kind,
+        ObjectKind::BigObject
+    );
+    assert_eq!(
+        SObjectDescribe::new("Order__x").object_kind,
+        ObjectKind::External
+    );
+    assert_eq!(
+        SObjectDescribe::new("Legacy_Orders")
+            .with_object_kind(ObjectKind::External)
+            .object_kind,
+        ObjectKind::External
+    );
+}
+
+#[test]
+fn test_big_object_filter_must_follow_index() {
+    let schema = create_big_object_schema();
+
+    let soql = extract_soql(
+        "SELECT Score__c FROM Game_Play__b WHERE Player__c = 'p1' AND Platform__c = 'PS5'",
+    );
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    assert!(converter.convert(&soql).is_ok());
+
+    let soql = extract_soql("SELECT Score__c FROM Game_Play__b WHERE Platform__c = 'PS5'");
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    match converter.convert(&soql) {
+        Err(ConversionError::UnsupportedForObject { object, reason }) => {
+            assert_eq!(object, "Game_Play__b");
+            assert!(
+                reason.contains("skips index field 'Player__c'"),
+                "{}",
+                reason
+            );
+        }
+        other => panic!("Expected UnsupportedForObject, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_big_object_unsupported_operator_warns() {
+    let schema = create_big_object_schema();
+    let soql = extract_soql("SELECT Score__c FROM Game_Play__b WHERE Player__c LIKE 'p%'");
+
+    let config = ConversionConfig {
+        capability_mode: CapabilityMode::Warning,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let result = converter.convert(&soql).unwrap();
+
+    assert!(result.sql.contains("WHERE t0.player__c LIKE 'p%'"));
+    assert_eq!(
+        result.warnings,
+        vec![ConversionWarning::UnsupportedForObject {
+            object: "Game_Play__b".to_string(),
+            reason: "LIKE is not supported on big objects".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_external_object_aggregate() {
+    let soql = extract_soql("SELECT COUNT(Id) FROM Order__x");
+
+    let mut converter = SoqlToSqlConverter::new_without_schema(ConversionConfig::default());
+    match converter.convert(&soql) {
+        Err(ConversionError::UnsupportedForObject { object, reason }) => {
+            assert_eq!(object, "Order__x");
+            assert_eq!(
+                reason,
+                "aggregate COUNT() is not supported on external objects"
+            );
+        }
+        other => panic!("Expected UnsupportedForObject, got {:?}", other),
+    }
+
+    let config = ConversionConfig {
+        capability_mode: CapabilityMode::Warning,
+        ..Default::default()
+    };
+    let mut converter = SoqlToSqlConverter::new_without_schema(config);
+    let result = converter.convert(&soql).unwrap();
+    assert_eq!(result.warnings.len(), 1);
+}
+
+// =============================================================================
+// Warning filter tests
+// =============================================================================
+
+fn sqlite_config(warning_filter: WarningFilter) -> ConversionConfig {
+    ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        warning_filter,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_warning_kinds_and_severity() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account FOR UPDATE");
+    let result = SoqlToSqlConverter::new(&schema, sqlite_config(WarningFilter::default()))
+        .convert(&soql)
+        .unwrap();
+
+    let warnings: Vec<_> = result
+        .warnings_of_kind(WarningKind::ForUpdateNotSupported)
+        .collect();
+    assert_eq!(warnings, [&ConversionWarning::ForUpdateNotSupported]);
+    assert_eq!(warnings[0].severity(), WarningSeverity::Warning);
+    assert_eq!(
+        result
+            .warnings_of_kind(WarningKind::SalesforceOnlyClause)
+            .count(),
+        0
+    );
+
+    for kind in WarningKind::ALL {
+        assert_eq!(WarningKind::from_code(kind.code()), Some(kind));
+    }
+    assert_eq!(
+        WarningKind::SalesforceOnlyClause.severity(),
+        WarningSeverity::Info
+    );
+}
+
+#[test]
+fn test_promoted_warning_fails_conversion() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account FOR UPDATE");
+    let filter = WarningFilter::default().promote(WarningKind::ForUpdateNotSupported);
+
+    let err = SoqlToSqlConverter::new(&schema, sqlite_config(filter))
+        .convert(&soql)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ConversionError::PromotedWarning(ConversionWarning::ForUpdateNotSupported)
+    );
+    assert!(err.to_string().ends_with("[for-update-not-supported]"));
+
+    // Postgres supports FOR UPDATE, so nothing is promoted
+    let config = ConversionConfig {
+        warning_filter: WarningFilter::default().promote(WarningKind::ForUpdateNotSupported),
+        ..Default::default()
+    };
+    assert!(SoqlToSqlConverter::new(&schema, config)
+        .convert(&soql)
+        .is_ok());
+}
+
+#[test]
+fn test_suppressed_warnings_are_dropped() {
+    let schema = create_test_schema();
+    let soql = extract_soql("SELECT Id FROM Account USING SCOPE mine FOR UPDATE");
+    let filter = WarningFilter::default().suppress(WarningKind::SalesforceOnlyClause);
+
+    let result = SoqlToSqlConverter::new(&schema, sqlite_config(filter))
+        .convert(&soql)
+        .unwrap();
+    assert_eq!(result.warnings, [ConversionWarning::ForUpdateNotSupported]);
+}
+
+#[test]
+fn test_warning_counts_over_batch() {
+    let schema = create_test_schema();
+    let filter = WarningFilter::default().suppress(WarningKind::SecurityClauseRemoved);
+    let mut converter = SoqlToSqlConverter::new(&schema, sqlite_config(filter));
+    let conversions: Vec<_> = [
+        "SELECT Id FROM Account FOR UPDATE",
+        "SELECT Id FROM Account FOR VIEW",
+        "SELECT Id FROM Contact USING SCOPE mine FOR REFERENCE",
+        "SELECT Id FROM Contact WITH SECURITY_ENFORCED",
+        "SELECT Id, Name FROM Contact",
+    ]
+    .iter()
+    .map(|soql| converter.convert(&extract_soql(soql)).unwrap())
+    .collect();
+
+    let counts = apexrust::sql::warning_counts(&conversions);
+    assert_eq!(
+        counts.into_iter().collect::<Vec<_>>(),
+        [
+            (WarningKind::ForUpdateNotSupported, 1),
+            (WarningKind::SalesforceOnlyClause, 3),
+        ]
+    );
+}
+
+// =============================================================================
+// Query builder tests
+// =============================================================================
+
+#[test]
+fn test_built_query_converts_like_parsed_query() {
+    use apexrust::soql_builder::{bind, SoqlQueryBuilder};
+
+    let schema = create_test_schema();
+    let built = SoqlQueryBuilder::new()
+        .select(&["Id", "Name"])
+        .from("Account")
+        .where_eq("Industry", bind("ind"))
+        .where_op("Owner.Name", apexrust::BinaryOp::Like, bind("owner"))
+        .order_by("Name", true)
+        .limit(10)
+        .build();
+    let parsed = extract_soql(
+        "SELECT Id, Name FROM Account WHERE Industry = :ind AND Owner.Name LIKE :owner \
+         ORDER BY Name LIMIT 10",
+    );
+
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let from_builder = converter.convert(&built).unwrap();
+    let mut converter = SoqlToSqlConverter::new(&schema, ConversionConfig::default());
+    let from_source = converter.convert(&parsed).unwrap();
+
+    assert_eq!(from_builder.sql, from_source.sql);
+    assert_eq!(from_builder.parameters, from_source.parameters);
+    assert!(from_builder.sql.contains("LIMIT 10"));
 }
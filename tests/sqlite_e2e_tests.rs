@@ -9,7 +9,9 @@ use apexrust::sql::{
     ChildRelationship, ConversionConfig, DdlGenerator, FieldDescribe, SObjectDescribe,
     SalesforceFieldType, SalesforceSchema, SoqlToSqlConverter, SqlDialect,
 };
-use apexrust::{parse, ClassMember, Expression, SoqlQuery, Statement, TypeDeclaration};
+use apexrust::{
+    parse, ClassMember, ClockSource, Expression, SoqlQuery, Statement, Timestamp, TypeDeclaration,
+};
 use rusqlite::{Connection, Result as SqliteResult};
 
 /// Create a comprehensive Salesforce schema for testing
@@ -624,6 +626,41 @@ fn test_e2e_junction_query() {
     assert_eq!(row_count, 1); // One junction record
 }
 
+#[test]
+fn test_e2e_or_across_relationships() {
+    let schema = create_salesforce_schema();
+    let conn = setup_database(&schema).expect("Failed to set up database");
+
+    // Junctions with only one parent set; an INNER JOIN on either
+    // relationship would drop them even when the other side matches
+    conn.execute(
+        "INSERT INTO \"junction__c\" (id, parent2__c) VALUES ('a00000000000002', '001000000000004')",
+        [],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO \"junction__c\" (id, parent1__c) VALUES ('a00000000000003', '001000000000002')",
+        [],
+    )
+    .unwrap();
+
+    let source = r#"
+    class Test {
+        List<Junction__c> getMatchingJunctions() {
+            return [SELECT Id FROM Junction__c
+                    WHERE parent1__r.Industry = 'Technology' OR Parent2__r.Industry = 'Retail'];
+        }
+    }
+    "#;
+
+    let queries = extract_soql_queries(source);
+    let (method_name, soql) = &queries[0];
+    let row_count =
+        convert_and_execute(&conn, &schema, soql, method_name).expect("Failed to execute query");
+
+    assert_eq!(row_count, 2); // Acme parent1 and Kansas Co (Retail) parent2
+}
+
 #[test]
 fn test_e2e_soql_recipes_file() {
     let schema = create_salesforce_schema();
@@ -667,6 +704,53 @@ fn test_e2e_soql_recipes_file() {
     );
 }
 
+#[test]
+fn test_e2e_date_literals_follow_fixed_clock() {
+    let schema = create_salesforce_schema();
+    let conn = setup_database(&schema).expect("Failed to set up database");
+    let queries = extract_soql_queries(
+        r#"
+        class Test {
+            List<Opportunity> closingThisMonth() {
+                return [SELECT Name FROM Opportunity WHERE CloseDate = THIS_MONTH];
+            }
+            List<Opportunity> closedRecently() {
+                return [SELECT Name FROM Opportunity WHERE CloseDate = LAST_N_DAYS:30];
+            }
+        }
+        "#,
+    );
+    let names_on = |query: &SoqlQuery, year: i32, month: u32, day: u32| -> Vec<String> {
+        let config = ConversionConfig {
+            dialect: SqlDialect::Sqlite,
+            clock: ClockSource::Fixed(Timestamp::from_ymd(year, month, day).unwrap()),
+            ..Default::default()
+        };
+        let sql = SoqlToSqlConverter::new(&schema, config)
+            .convert(query)
+            .unwrap()
+            .sql;
+        conn.prepare(&sql)
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<SqliteResult<_>>()
+            .unwrap()
+    };
+
+    // Close dates are 2024-01-15, 2024-03-01 and 2024-06-01
+    let this_month = &queries[0].1;
+    assert_eq!(names_on(this_month, 2024, 1, 31), ["Big Deal"]);
+    assert_eq!(names_on(this_month, 2024, 3, 1), ["Medium Deal"]);
+    assert!(names_on(this_month, 2024, 2, 29).is_empty());
+
+    let last_30_days = &queries[1].1;
+    assert_eq!(names_on(last_30_days, 2024, 6, 30), ["Small Deal"]);
+    assert_eq!(names_on(last_30_days, 2024, 3, 2), ["Medium Deal"]);
+    // The range ends before today
+    assert!(names_on(last_30_days, 2024, 6, 1).is_empty());
+}
+
 #[test]
 fn test_ddl_creates_valid_schema() {
     let schema = create_salesforce_schema();
@@ -682,7 +766,7 @@ fn test_ddl_creates_valid_schema() {
         let trimmed = statement.trim();
         if !trimmed.is_empty() {
             conn.execute(trimmed, [])
-                .expect(&format!("Failed to execute DDL: {}", trimmed));
+                .unwrap_or_else(|e| panic!("Failed to execute DDL: {}: {}", trimmed, e));
         }
     }
 
@@ -702,3 +786,40 @@ fn test_ddl_creates_valid_schema() {
     assert!(tables.contains(&"opportunity".to_string()));
     assert!(tables.contains(&"junction__c".to_string()));
 }
+
+#[test]
+fn test_upsert_statement_updates_existing_row() {
+    use rusqlite::types::Value;
+
+    let schema = create_salesforce_schema();
+    let conn = setup_database(&schema).expect("Failed to set up database");
+    let sql = DdlGenerator::new(SqlDialect::Sqlite)
+        .upsert_statement("Account", &schema, "id")
+        .unwrap();
+
+    // Columns start with id and name; leave the rest NULL
+    let upsert = |id: &str, name: &str| {
+        let mut values = vec![Value::Null; sql.matches('?').count()];
+        values[0] = Value::Text(id.to_string());
+        values[1] = Value::Text(name.to_string());
+        conn.execute(&sql, rusqlite::params_from_iter(values))
+            .unwrap_or_else(|e| panic!("Failed to execute upsert: {}: {}", sql, e));
+    };
+    let count = || -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM account", [], |row| row.get(0))
+            .unwrap()
+    };
+    let before = count();
+    upsert("001000000000001", "Acme Renamed");
+    upsert("001000000000099", "New Account");
+
+    let name: String = conn
+        .query_row(
+            "SELECT name FROM account WHERE id = '001000000000001'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(name, "Acme Renamed");
+    assert_eq!(count(), before + 1);
+}
@@ -0,0 +1,129 @@
+//! Parsing SOQL and SOSL strings on their own, outside Apex
+
+use apexrust::{
+    parse, parse_soql, parse_sosl, ClassMember, Expression, ParseError, SoqlQuery, SoslQuery,
+    Statement, TypeDeclaration,
+};
+
+/// The query's Debug text without spans and node ids, which differ
+/// between the routes
+fn without_spans(debug: String) -> String {
+    let mut out = String::with_capacity(debug.len());
+    let mut rest = debug.as_str();
+    while let Some((start, close)) = [("Span {", '}'), ("NodeId(", ')')]
+        .iter()
+        .filter_map(|(open, close)| Some((rest.find(open)?, *close)))
+        .min()
+    {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find(close).expect("unclosed Span or NodeId");
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The query in `x = [...]`, parsed as part of a class
+fn embedded(query: &str) -> Expression {
+    let source = format!("class T {{ void m() {{ Object x = [{}]; }} }}", query);
+    let unit = parse(&source).expect("Parse failed");
+    if let TypeDeclaration::Class(class) = &unit.declarations[0] {
+        if let ClassMember::Method(method) = &class.members[0] {
+            if let Statement::LocalVariable(local) = &method.body.as_ref().unwrap().statements[0] {
+                if let Some(initializer) = &local.declarators[0].initializer {
+                    return initializer.clone();
+                }
+            }
+        }
+    }
+    panic!("Could not extract query");
+}
+
+fn embedded_soql(query: &str) -> SoqlQuery {
+    match embedded(query) {
+        Expression::Soql(query) => *query,
+        other => panic!("expected SOQL, got {:?}", other),
+    }
+}
+
+fn embedded_sosl(query: &str) -> SoslQuery {
+    match embedded(query) {
+        Expression::Sosl(query) => *query,
+        other => panic!("expected SOSL, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_soql_matches_embedded_query() {
+    let queries = [
+        "SELECT Id, Name, Account.Name FROM Contact WHERE Name LIKE 'A%' ORDER BY Name LIMIT 10",
+        "SELECT Id, (SELECT Id FROM Contacts WHERE Email != null) FROM Account WITH SECURITY_ENFORCED",
+        "SELECT Industry, COUNT(Id) total FROM Account GROUP BY Industry HAVING COUNT(Id) > 1",
+        "SELECT Id FROM Opportunity WHERE CloseDate = LAST_N_DAYS:30 AND AccountId IN :accountIds",
+        "SELECT Id FROM Account WHERE Id NOT IN (SELECT AccountId FROM Contact) FOR UPDATE",
+    ];
+    for query in queries {
+        let standalone = parse_soql(query).unwrap();
+        assert_eq!(
+            without_spans(format!("{:?}", standalone)),
+            without_spans(format!("{:?}", embedded_soql(query))),
+            "{}",
+            query
+        );
+    }
+}
+
+#[test]
+fn test_parse_sosl_matches_embedded_query() {
+    let query = "FIND 'Acme*' IN NAME FIELDS RETURNING Account(Id, Name WHERE Industry = 'Tech'), Contact LIMIT 20";
+    let standalone = parse_sosl(query).unwrap();
+    assert_eq!(
+        without_spans(format!("{:?}", standalone)),
+        without_spans(format!("{:?}", embedded_sosl(query)))
+    );
+}
+
+#[test]
+fn test_parse_soql_whitespace_and_keyword_case() {
+    let query = parse_soql(
+        "\n   select Id, Name from Account where Name != null order by Name desc limit 5  \n\t",
+    )
+    .unwrap();
+    assert_eq!(query.from_clause, "Account");
+    assert!(query.limit_clause.is_some());
+    assert_eq!(
+        without_spans(format!("{:?}", query)),
+        without_spans(format!(
+            "{:?}",
+            parse_soql(
+                "SELECT Id, Name FROM Account WHERE Name != null ORDER BY Name DESC LIMIT 5"
+            )
+            .unwrap()
+        ))
+    );
+}
+
+#[test]
+fn test_parse_soql_spans_are_relative_to_query() {
+    let query = parse_soql("  SELECT Id FROM Account").unwrap();
+    assert_eq!(query.span.start, 2);
+    assert_eq!((query.span.line, query.span.column), (1, 3));
+}
+
+#[test]
+fn test_parse_soql_rejects_trailing_tokens() {
+    let err = parse_soql("SELECT Id FROM Account; DELETE").unwrap_err();
+    let ParseError::UnexpectedToken { expected, span, .. } = &err else {
+        panic!("expected UnexpectedToken, got {:?}", err);
+    };
+    assert_eq!(expected, "end of query");
+    assert_eq!(span.start, 22);
+}
+
+#[test]
+fn test_parse_soql_rejects_brackets_and_other_input() {
+    assert!(parse_soql("[SELECT Id FROM Account]").is_err());
+    assert!(parse_soql("FIND 'x' RETURNING Account").is_err());
+    assert!(parse_soql("").is_err());
+    assert!(parse_sosl("SELECT Id FROM Account").is_err());
+}
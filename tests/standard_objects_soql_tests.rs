@@ -4,7 +4,8 @@
 //! with Account, Contact, Opportunity, Lead, Case, Task, Event, Campaign, etc.
 
 use apexrust::sql::{
-    create_sales_cloud_schema, ConversionConfig, DdlGenerator, SoqlToSqlConverter, SqlDialect,
+    create_sales_cloud_schema, ConversionConfig, DdlGenerator, RecordTypeStrategy,
+    SoqlToSqlConverter, SqlDialect,
 };
 use apexrust::{parse, ClassMember, Expression, SoqlQuery, Statement, TypeDeclaration};
 use rusqlite::{Connection, Result as SqliteResult};
@@ -210,25 +211,25 @@ fn insert_sample_sales_data(conn: &Connection) -> SqliteResult<()> {
 
     // Tasks
     conn.execute(
-        "INSERT INTO \"task\" (id, subject, status, priority, activity_date, what_id, who_id, owner_id, is_closed)
-         VALUES ('00T000000000001', 'Follow up call', 'Not Started', 'Normal', '2024-02-15', '001000000000001', '003000000000001', '005000000000001', 0)",
+        "INSERT INTO \"task\" (id, subject, status, priority, activity_date, what_id, what_id_type, who_id, who_id_type, owner_id, is_closed)
+         VALUES ('00T000000000001', 'Follow up call', 'Not Started', 'Normal', '2024-02-15', '001000000000001', 'Account', '003000000000001', 'Contact', '005000000000001', 0)",
         [],
     )?;
     conn.execute(
-        "INSERT INTO \"task\" (id, subject, status, priority, activity_date, what_id, who_id, owner_id, is_closed)
-         VALUES ('00T000000000002', 'Send proposal', 'Completed', 'High', '2024-01-10', '006000000000002', '003000000000003', '005000000000002', 1)",
+        "INSERT INTO \"task\" (id, subject, status, priority, activity_date, what_id, what_id_type, who_id, who_id_type, owner_id, is_closed)
+         VALUES ('00T000000000002', 'Send proposal', 'Completed', 'High', '2024-01-10', '006000000000002', 'Opportunity', '003000000000003', 'Contact', '005000000000002', 1)",
         [],
     )?;
     conn.execute(
-        "INSERT INTO \"task\" (id, subject, status, priority, activity_date, what_id, who_id, owner_id, is_closed)
-         VALUES ('00T000000000003', 'Demo scheduled', 'In Progress', 'Normal', '2024-02-20', '006000000000003', '003000000000005', '005000000000001', 0)",
+        "INSERT INTO \"task\" (id, subject, status, priority, activity_date, what_id, what_id_type, who_id, who_id_type, owner_id, is_closed)
+         VALUES ('00T000000000003', 'Demo scheduled', 'In Progress', 'Normal', '2024-02-20', '006000000000003', 'Opportunity', '003000000000005', 'Contact', '005000000000001', 0)",
         [],
     )?;
 
     // Events
     conn.execute(
-        "INSERT INTO \"event\" (id, subject, start_date_time, end_date_time, duration_in_minutes, location, what_id, who_id, owner_id, is_all_day_event)
-         VALUES ('00U000000000001', 'Quarterly Business Review', '2024-02-15 10:00:00', '2024-02-15 12:00:00', 120, 'Acme HQ', '001000000000001', '003000000000001', '005000000000001', 0)",
+        "INSERT INTO \"event\" (id, subject, start_date_time, end_date_time, duration_in_minutes, location, what_id, what_id_type, who_id, who_id_type, owner_id, is_all_day_event)
+         VALUES ('00U000000000001', 'Quarterly Business Review', '2024-02-15 10:00:00', '2024-02-15 12:00:00', 120, 'Acme HQ', '001000000000001', 'Account', '003000000000001', 'Contact', '005000000000001', 0)",
         [],
     )?;
     conn.execute(
@@ -442,6 +443,50 @@ fn test_account_aggregate_by_state() {
     assert!(count >= 1);
 }
 
+#[test]
+fn test_account_semi_join_on_contacts() {
+    let conn = setup_sales_cloud_db().unwrap();
+    let (count, _sql) = execute_soql(
+        &conn,
+        "SELECT Id, Name FROM Account WHERE Id IN (SELECT AccountId FROM Contact WHERE Department = 'Executive')",
+    )
+    .unwrap();
+    assert_eq!(count, 2); // Global Tech and Small Biz
+}
+
+#[test]
+fn test_account_anti_join_on_contacts() {
+    let conn = setup_sales_cloud_db().unwrap();
+    let (count, _sql) = execute_soql(
+        &conn,
+        "SELECT Id, Name FROM Account WHERE Id NOT IN (SELECT AccountId FROM Contact)",
+    )
+    .unwrap();
+    assert_eq!(count, 2); // Acme West and Healthcare Systems
+}
+
+#[test]
+fn test_contact_semi_join_on_lookup() {
+    let conn = setup_sales_cloud_db().unwrap();
+    let (count, _sql) = execute_soql(
+        &conn,
+        "SELECT Id FROM Contact WHERE AccountId IN (SELECT Id FROM Account WHERE Industry = 'Technology')",
+    )
+    .unwrap();
+    assert_eq!(count, 4); // Acme and Global Tech contacts
+}
+
+#[test]
+fn test_account_filtered_anti_join_on_contacts() {
+    let conn = setup_sales_cloud_db().unwrap();
+    let (count, _sql) = execute_soql(
+        &conn,
+        "SELECT Id FROM Account WHERE Id NOT IN (SELECT AccountId FROM Contact WHERE Department = 'Executive')",
+    )
+    .unwrap();
+    assert_eq!(count, 3); // Acme, Acme West and Healthcare Systems
+}
+
 // =============================================================================
 // Contact Queries
 // =============================================================================
@@ -730,6 +775,105 @@ fn test_task_aggregate_by_status() {
     assert!(count >= 1);
 }
 
+#[test]
+fn test_task_polymorphic_what_and_who() {
+    let schema = create_sales_cloud_schema();
+    let soql = extract_soql("SELECT Id, What.Name, Who.Email FROM Task ORDER BY Id");
+    let config = ConversionConfig {
+        dialect: SqlDialect::Sqlite,
+        ..Default::default()
+    };
+    let result = SoqlToSqlConverter::new(&schema, config)
+        .convert(&soql)
+        .unwrap();
+
+    // One join per target declaring the field, keyed by the discriminator
+    assert!(result.sql.contains(
+        "LEFT JOIN \"account\" t1 ON t0.what_id = t1.id AND t0.what_id_type = 'Account'"
+    ));
+    assert!(result.sql.contains(
+        "LEFT JOIN \"opportunity\" t2 ON t0.what_id = t2.id AND t0.what_id_type = 'Opportunity'"
+    ));
+    assert!(result
+        .sql
+        .contains("LEFT JOIN \"lead\" t5 ON t0.who_id = t5.id AND t0.who_id_type = 'Lead'"));
+    assert!(result.sql.contains(
+        "COALESCE(t1.name, t2.name, t3.name) AS \"What.Name\", \
+         COALESCE(t4.email, t5.email) AS \"Who.Email\""
+    ));
+    assert_eq!(result.column_map["What.Name"], "What.Name");
+
+    // Each row reads the name from the table its discriminator points at
+    let conn = setup_sales_cloud_db().unwrap();
+    let mut stmt = conn.prepare(&result.sql).unwrap();
+    assert_eq!(stmt.column_names(), ["id", "What.Name", "Who.Email"]);
+    let names: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<SqliteResult<_>>()
+        .unwrap();
+    assert_eq!(
+        names,
+        vec![
+            (
+                "00T000000000001".to_string(),
+                "Acme Corporation".to_string()
+            ),
+            (
+                "00T000000000002".to_string(),
+                "Global Tech Expansion".to_string()
+            ),
+            (
+                "00T000000000003".to_string(),
+                "Small Biz Starter".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_task_polymorphic_joins_shared_across_fields() {
+    let conn = setup_sales_cloud_db().unwrap();
+    let (count, sql) = execute_soql(
+        &conn,
+        "SELECT What.Name FROM Task WHERE What.Name LIKE 'Global%' ORDER BY What.Name",
+    )
+    .unwrap();
+
+    assert_eq!(count, 1);
+    assert_eq!(sql.matches("LEFT JOIN \"account\"").count(), 1);
+    assert_eq!(sql.matches("LEFT JOIN \"opportunity\"").count(), 1);
+}
+
+#[test]
+fn test_task_polymorphic_field_missing_on_some_targets() {
+    let schema = create_sales_cloud_schema();
+    let soql = extract_soql("SELECT What.Industry FROM Task");
+    let result = SoqlToSqlConverter::new(&schema, ConversionConfig::default())
+        .convert(&soql)
+        .unwrap();
+
+    // Only Account has Industry, so there is nothing to COALESCE
+    assert!(result.sql.starts_with("SELECT t1.industry\n"));
+    assert!(!result.sql.contains("\"opportunity\""));
+    assert_eq!(result.warnings.len(), 1);
+    let warning = result.warnings[0].to_string();
+    assert!(warning.contains("What.Industry"));
+    assert!(warning.contains("Opportunity, Campaign, Case, Contract"));
+}
+
+#[test]
+fn test_task_polymorphic_field_missing_on_all_targets() {
+    let schema = create_sales_cloud_schema();
+    let soql = extract_soql("SELECT What.Email FROM Task");
+    let err = SoqlToSqlConverter::new(&schema, ConversionConfig::default())
+        .convert(&soql)
+        .unwrap_err();
+
+    assert!(err.to_string().contains("'Email'"));
+    assert!(err.to_string().contains("'What'"));
+}
+
 // =============================================================================
 // Event Queries
 // =============================================================================
@@ -816,6 +960,66 @@ fn test_pricebook_entry_with_product() {
     assert_eq!(count, 3);
 }
 
+// =============================================================================
+// Record Type Queries
+// =============================================================================
+
+const RECORD_TYPE_QUERY: &str = "SELECT Id, Name, RecordType.Name FROM Account \
+     WHERE RecordType.DeveloperName = 'Partner' OR RecordTypeId = :rtId";
+
+#[test]
+fn test_record_type_join() {
+    let conn = setup_sales_cloud_db().unwrap();
+    conn.execute_batch(
+        "INSERT INTO \"record_type\" (id, name, developer_name, sobject_type, is_active)
+         VALUES ('012000000000001', 'Partner Account', 'Partner', 'Account', 1);
+         UPDATE \"account\" SET record_type_id = '012000000000001'
+         WHERE id = '001000000000002';",
+    )
+    .unwrap();
+
+    let (count, sql) = execute_soql(
+        &conn,
+        "SELECT Id, Name, RecordType.Name FROM Account WHERE RecordType.DeveloperName = 'Partner'",
+    )
+    .unwrap();
+    assert_eq!(count, 1);
+    assert!(sql.contains("LEFT JOIN \"record_type\" t1 ON t0.record_type_id = t1.id"));
+    assert!(sql.contains("t1.developer_name = 'Partner'"));
+}
+
+#[test]
+fn test_record_type_denormalized_column() {
+    let schema = create_sales_cloud_schema();
+    let config = ConversionConfig {
+        record_type_strategy: RecordTypeStrategy::DenormalizedColumn {
+            column: "record_type_devname".to_string(),
+        },
+        ..Default::default()
+    };
+
+    let mut converter = SoqlToSqlConverter::new(&schema, config.clone());
+    let sql = converter
+        .convert(&extract_soql(RECORD_TYPE_QUERY))
+        .unwrap()
+        .sql;
+    assert!(!sql.contains("JOIN"), "{}", sql);
+    assert!(sql.starts_with("SELECT t0.id, t0.name, t0.record_type_devname\n"));
+    assert!(sql.contains("t0.record_type_devname = 'Partner' OR t0.record_type_id = $1"));
+
+    // Only the RecordType hop is replaced on parent paths
+    let mut converter = SoqlToSqlConverter::new(&schema, config);
+    let sql = converter
+        .convert(&extract_soql(
+            "SELECT Id FROM Contact WHERE Account.RecordType.DeveloperName = 'Partner'",
+        ))
+        .unwrap()
+        .sql;
+    assert!(sql.contains("LEFT JOIN \"account\" t1"));
+    assert!(!sql.contains("record_type\""));
+    assert!(sql.contains("t1.record_type_devname = 'Partner'"));
+}
+
 // =============================================================================
 // Complex Multi-Object Queries
 // =============================================================================
@@ -908,6 +1112,80 @@ fn test_nested_or_and() {
     assert_eq!(count, 3); // 2 Hot Tech + 1 Healthcare
 }
 
+// =============================================================================
+// Schema Pruning Tests
+// =============================================================================
+
+const PRUNED_QUERIES: [&str; 7] = [
+    "SELECT Id, Name, Account.Name, Account.Owner.Name FROM Contact WHERE Account.Industry = :industry",
+    "SELECT Name, (SELECT LastName, Email FROM Contacts ORDER BY LastName) FROM Account",
+    "SELECT Id, What.Name, Who.Email FROM Task WHERE What.Name LIKE 'Global%'",
+    "SELECT COUNT() FROM Lead WHERE CreatedDate = LAST_N_DAYS:30",
+    "SELECT StageName, SUM(Amount) total FROM Opportunity GROUP BY StageName HAVING SUM(Amount) > 1000",
+    "SELECT Id FROM Case WHERE Owner.Name = 'Queue' ORDER BY CreatedDate DESC LIMIT 5",
+    "SELECT Id FROM Account WHERE Id IN (SELECT AccountId FROM Opportunity WHERE IsClosed = false)",
+];
+
+#[test]
+fn test_pruned_schema_converts_queries_identically() {
+    let full = create_sales_cloud_schema();
+    let queries: Vec<SoqlQuery> = PRUNED_QUERIES.iter().map(|q| extract_soql(q)).collect();
+    let pruned = full.prune_to_usage(&queries);
+
+    for (source, query) in PRUNED_QUERIES.iter().zip(&queries) {
+        let expected = SoqlToSqlConverter::new(&full, ConversionConfig::default())
+            .convert(query)
+            .unwrap();
+        let actual = SoqlToSqlConverter::new(&pruned, ConversionConfig::default())
+            .convert(query)
+            .unwrap();
+        assert_eq!(actual.sql, expected.sql, "{}", source);
+        assert_eq!(actual.parameters, expected.parameters, "{}", source);
+        assert_eq!(actual.warnings, expected.warnings, "{}", source);
+    }
+
+    let (before, after) = (full.size_stats(), pruned.size_stats());
+    assert!(after.objects < before.objects, "{} -> {}", before, after);
+    assert!(after.fields * 4 < before.fields, "{} -> {}", before, after);
+    assert_eq!(after.child_relationships, 1);
+
+    // Only what the queries reach, with the lookups they join on
+    assert!(!pruned.has_object("Pricebook2"));
+    let contact = pruned.get_object("Contact").unwrap();
+    assert!(contact.has_field("AccountId"));
+    assert!(contact.has_field("LastName"));
+    assert!(!contact.has_field("Phone"));
+    assert!(pruned.get_object("Account").unwrap().has_field("Industry"));
+    assert!(pruned
+        .get_object("Opportunity")
+        .unwrap()
+        .has_field("IsClosed"));
+}
+
+#[test]
+fn test_prune_to_units_collects_inline_queries() {
+    let full = create_sales_cloud_schema();
+    let unit = parse(
+        r#"
+        public class Pipeline {
+            public List<Opportunity> open() {
+                return [SELECT Name, Account.Name FROM Opportunity WHERE IsClosed = false];
+            }
+        }
+        "#,
+    )
+    .unwrap();
+
+    let pruned = full.prune_to_units(&[unit]);
+    let mut names = pruned.object_names();
+    names.sort();
+    assert_eq!(names, ["Account", "Opportunity"]);
+    assert!(pruned
+        .get_object("Opportunity")
+        .unwrap()
+        .has_field("IsClosed"));
+}
+
 // =============================================================================
 // DDL Generation Test
 // =============================================================================
@@ -937,7 +1215,7 @@ fn test_ddl_generates_all_tables() {
 
 #[test]
 fn test_print_sample_queries() {
-    let conn = setup_sales_cloud_db().unwrap();
+    let _conn = setup_sales_cloud_db().unwrap();
     let schema = create_sales_cloud_schema();
 
     let queries = vec![
@@ -0,0 +1,153 @@
+//! Tests for test class/method discovery
+
+use apexrust::analysis::test_inventory::{find_tests, TestInventory, TestMarker};
+use apexrust::parse;
+
+fn inventory(source: &str) -> TestInventory {
+    find_tests(&parse(source).expect("Parse failed"))
+}
+
+// ==================== Modifier Ordering ====================
+
+#[test]
+fn test_testmethod_modifier_orderings() {
+    let source = r#"
+        @isTest
+        private class LegacyTest {
+            static testMethod void staticFirst() {}
+            testMethod static void testMethodFirst() {}
+            public static testMethod void accessFirst() {}
+            static public testMethod void accessMiddle() {}
+            testMethod public static void accessLast() {}
+        }
+    "#;
+    let inv = inventory(source);
+    let class = inv.get_class("LegacyTest").unwrap();
+
+    let names: Vec<&str> = class.methods.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "staticFirst",
+            "testMethodFirst",
+            "accessFirst",
+            "accessMiddle",
+            "accessLast"
+        ]
+    );
+    assert!(class
+        .methods
+        .iter()
+        .all(|m| m.marker == TestMarker::Modifier));
+    assert!(inv.diagnostics.is_empty(), "{:?}", inv.diagnostics);
+}
+
+#[test]
+fn test_annotation_and_modifier_markers() {
+    let source = r#"
+        @isTest
+        private class MixedTest {
+            @isTest static void annotated() {}
+            static testMethod void legacy() {}
+        }
+    "#;
+    let class = inventory(source).get_class("MixedTest").unwrap().clone();
+
+    assert_eq!(class.methods[0].marker, TestMarker::Annotation);
+    assert_eq!(class.methods[1].marker, TestMarker::Modifier);
+}
+
+#[test]
+fn test_helpers_in_test_class_are_not_tests() {
+    let source = r#"
+        @isTest
+        private class HelperTest {
+            static Account makeAccount() { return new Account(); }
+            static void setUp() {}
+            @isTest static void realTest() {}
+        }
+    "#;
+    let class = inventory(source).get_class("HelperTest").unwrap().clone();
+
+    assert_eq!(class.methods.len(), 1);
+    assert_eq!(class.methods[0].name, "realTest");
+}
+
+#[test]
+fn test_non_test_class_is_not_listed() {
+    let inv = inventory("public class Service { public static void run() {} }");
+    assert!(inv.classes.is_empty());
+}
+
+// ==================== @isTest Parameters ====================
+
+#[test]
+fn test_is_parallel_extraction() {
+    let source = r#"
+        @isTest(isParallel=true)
+        private class ParallelTest {
+            @isTest static void runs() {}
+        }
+
+        @isTest(seeAllData=false)
+        private class SerialTest {
+            @isTest(SeeAllData=true) static void readsOrgData() {}
+        }
+    "#;
+    let inv = inventory(source);
+
+    let parallel = inv.get_class("ParallelTest").unwrap();
+    assert!(parallel.is_test_class);
+    assert!(parallel.is_parallel);
+    assert!(!parallel.see_all_data);
+
+    let serial = inv.get_class("SerialTest").unwrap();
+    assert!(!serial.is_parallel);
+    assert!(!serial.see_all_data);
+    assert!(serial.methods[0].see_all_data);
+}
+
+#[test]
+fn test_inner_test_class_uses_dotted_name() {
+    let source = r#"
+        public class Outer {
+            @isTest(isParallel=true)
+            class InnerTest {
+                @isTest static void works() {}
+            }
+        }
+    "#;
+    let inv = inventory(source);
+
+    assert_eq!(inv.classes.len(), 1);
+    assert!(inv.get_class("Outer.InnerTest").unwrap().is_parallel);
+}
+
+// ==================== Diagnostics ====================
+
+#[test]
+fn test_invalid_signature_diagnostics() {
+    let source = r#"
+        @isTest
+        private class BrokenTest {
+            @isTest void notStatic() {}
+            @isTest static Integer returnsValue() { return 1; }
+            testMethod static void takesArgs(Integer n) {}
+            @isTest static void fine() {}
+        }
+    "#;
+    let inv = inventory(source);
+
+    let messages: Vec<&str> = inv.diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "test method 'notStatic' must be static",
+            "test method 'returnsValue' must return void",
+            "test method 'takesArgs' must not take parameters",
+        ]
+    );
+    assert!(inv.diagnostics.iter().all(|d| d.class == "BrokenTest"));
+    // Invalid methods are still inventoried
+    assert_eq!(inv.get_class("BrokenTest").unwrap().methods.len(), 4);
+}
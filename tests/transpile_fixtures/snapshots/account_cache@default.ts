@@ -0,0 +1,32 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class AccountCache {
+  private static __cachePending: Promise<void> | null = null;
+  private static cache: Map<string, Account>;
+  private static calls: number;
+  public static async getCache(): Promise<Map<string, Account>> {
+    if (AccountCache.cache == null) {
+      if (!AccountCache.__cachePending) {
+        AccountCache.__cachePending = (async () => {
+          AccountCache.cache = await AccountCache.buildFromSoql();
+        })().finally(() => {
+          AccountCache.__cachePending = null;
+        });
+      }
+      await AccountCache.__cachePending;
+    }
+    return AccountCache.cache;
+  }
+
+  public static track(): void {
+    AccountCache.calls = 1;
+  }
+
+  private static async buildFromSoql(): Promise<Map<string, Account>> {
+    return new Map<string, Account>((await $runtime.query("SELECT Id, Name FROM Account")).map((record) => [record.Id, record]));
+  }
+
+}
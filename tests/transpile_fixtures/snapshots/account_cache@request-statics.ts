@@ -0,0 +1,31 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class AccountCache {
+  // static cache: stored per request in $runtime.statics
+  private static calls: number;
+  public static async getCache(): Promise<Map<string, Account>> {
+    if ($runtime.statics.get("AccountCache.cache") == null) {
+      if (!$runtime.statics.get("AccountCache.cache:pending")) {
+        $runtime.statics.set("AccountCache.cache:pending", (async () => {
+          $runtime.statics.set("AccountCache.cache", await AccountCache.buildFromSoql());
+        })().finally(() => {
+          $runtime.statics.delete("AccountCache.cache:pending");
+        }));
+      }
+      await $runtime.statics.get("AccountCache.cache:pending");
+    }
+    return $runtime.statics.get("AccountCache.cache");
+  }
+
+  public static track(): void {
+    AccountCache.calls = 1;
+  }
+
+  private static async buildFromSoql(): Promise<Map<string, Account>> {
+    return new Map<string, Account>((await $runtime.query("SELECT Id, Name FROM Account")).map((record) => [record.Id, record]));
+  }
+
+}
@@ -0,0 +1,26 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class AccountCache {
+  private static cache: Map<string, Account>;
+  private static calls: number;
+  public static getCache(): Map<string, Account> {
+    if (AccountCache.cache === null) {
+      {
+        AccountCache.cache = AccountCache.buildFromSoql();
+      }
+    }
+    return AccountCache.cache;
+  }
+
+  public static track(): void {
+    AccountCache.calls = 1;
+  }
+
+  private static buildFromSoql(): Map<string, Account> {
+    return new Map<string, Account>(($runtime.query("SELECT Id, Name FROM Account")).map((record) => [record.Id, record]));
+  }
+
+}
@@ -0,0 +1,85 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class Tally {
+  static calls = 0;
+  static loaded;
+  log = "";
+  labels = new Map();
+  chain(x) {
+    let y;
+    y = x += 2;
+    return y;
+  }
+
+  firstMatch(name) {
+    let rec;
+    if ((rec = this.lookup(name)) !== null) {
+      {
+        return rec;
+      }
+    }
+    return null;
+  }
+
+  drain() {
+    let total = 0;
+    let n;
+    while ((n = this.nextBatch()) > 0) {
+      {
+        total += n;
+      }
+    }
+    return total;
+  }
+
+  fill(values, v) {
+    let i = 0;
+    values[i++] = v;
+    values[++i] = values[i - 1];
+  }
+
+  bump(total, x) {
+    this.record(total++);
+    return total += x;
+  }
+
+  describe(key, value, count) {
+    let s = "k=";
+    s += $runtime.str(key);
+    s += $runtime.str(value);
+    s += "done";
+    s += " n=" + count;
+    s += $runtime.str(this.labels.get(key));
+    this.log += $runtime.str(key);
+    return s;
+  }
+
+  count() {
+    Tally.calls++;
+    let next = Tally.calls += 1;
+    return next;
+  }
+
+  static async load() {
+    return Tally.loaded = await $runtime.query("SELECT COUNT() FROM Account");
+  }
+
+  static advance() {
+    Tally.loaded++;
+    let previous = Tally.loaded++;
+    return previous + (Tally.loaded -= 1);
+  }
+
+  lookup(name) {
+    return null;
+  }
+
+  nextBatch() {
+    return 0;
+  }
+
+  record(n) {
+  }
+
+}
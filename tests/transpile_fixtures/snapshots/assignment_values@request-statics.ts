@@ -0,0 +1,87 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class Tally {
+  private static calls: number = 0;
+  // static loaded: stored per request in $runtime.statics
+  private log: string = "";
+  private labels: Map<string, string> = new Map<string, string>();
+  public chain(x: number): number {
+    let y: number;
+    y = x += 2;
+    return y;
+  }
+
+  public firstMatch(name: string): Account {
+    let rec: Account;
+    if ((rec = this.lookup(name)) !== null) {
+      {
+        return rec;
+      }
+    }
+    return null;
+  }
+
+  public drain(): number {
+    let total: number = 0;
+    let n: number;
+    while ((n = this.nextBatch()) > 0) {
+      {
+        total += n;
+      }
+    }
+    return total;
+  }
+
+  public fill(values: number[], v: number): void {
+    let i: number = 0;
+    values[i++] = v;
+    values[++i] = values[i - 1];
+  }
+
+  public bump(total: number, x: number): number {
+    this.record(total++);
+    return total += x;
+  }
+
+  public describe(key: string, value: Record<string, any>, count: number): string {
+    let s: string = "k=";
+    s += $runtime.str(key);
+    s += $runtime.str(value);
+    s += "done";
+    s += " n=" + count;
+    s += $runtime.str(this.labels.get(key));
+    this.log += $runtime.str(key);
+    return s;
+  }
+
+  public count(): number {
+    Tally.calls++;
+    let next: number = Tally.calls += 1;
+    return next;
+  }
+
+  public static async load(): Promise<number> {
+    return $runtime.statics.set("Tally.loaded", await $runtime.query("SELECT COUNT() FROM Account")).get("Tally.loaded");
+  }
+
+  public static advance(): number {
+    $runtime.statics.set("Tally.loaded", $runtime.statics.get("Tally.loaded") + 1);
+    let previous: number = $runtime.statics.set("Tally.loaded", $runtime.statics.get("Tally.loaded") + 1).get("Tally.loaded") - 1;
+    return previous + ($runtime.statics.set("Tally.loaded", $runtime.statics.get("Tally.loaded") - 1).get("Tally.loaded"));
+  }
+
+  private lookup(name: string): Account {
+    return null;
+  }
+
+  private nextBatch(): number {
+    return 0;
+  }
+
+  private record(n: number): void {
+  }
+
+}
@@ -0,0 +1,56 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class CollectionUtils {
+  public static countWords(words: string[]): Map<string, number> {
+    let counts: Map<string, number> = new Map<string, number>();
+    for (const word of words) {
+      {
+        if (counts.has(word)) {
+          {
+            counts.set(word, counts.get(word) + 1);
+          }
+        } else {
+          {
+            counts.set(word, 1);
+          }
+        }
+      }
+    }
+    return counts;
+  }
+
+  public static unique(values: string[]): Set<string> {
+    let seen: Set<string> = new Set<string>();
+    for (const value of values) {
+      {
+        if (!seen.has(value)) {
+          {
+            seen.add(value);
+          }
+        }
+      }
+    }
+    return seen;
+  }
+
+  public static total(values: number[]): number {
+    let sum: number = 0;
+    if ((values.length === 0)) {
+      {
+        return sum;
+      }
+    }
+    for (let i: number = 0; i < values.length; i++) {
+      sum += values[i];
+    }
+    return sum;
+  }
+
+  public static defaults(): string[] {
+    return ["a", "b", "c"];
+  }
+
+}
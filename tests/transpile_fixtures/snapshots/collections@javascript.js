@@ -0,0 +1,54 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class CollectionUtils {
+  static countWords(words) {
+    let counts = new Map();
+    for (const word of words) {
+      {
+        if (counts.has(word)) {
+          {
+            counts.set(word, counts.get(word) + 1);
+          }
+        } else {
+          {
+            counts.set(word, 1);
+          }
+        }
+      }
+    }
+    return counts;
+  }
+
+  static unique(values) {
+    let seen = new Set();
+    for (const value of values) {
+      {
+        if (!seen.has(value)) {
+          {
+            seen.add(value);
+          }
+        }
+      }
+    }
+    return seen;
+  }
+
+  static total(values) {
+    let sum = 0;
+    if ((values.length === 0)) {
+      {
+        return sum;
+      }
+    }
+    for (let i = 0; i < values.length; i++) {
+      sum += values[i];
+    }
+    return sum;
+  }
+
+  static defaults() {
+    return ["a", "b", "c"];
+  }
+
+}
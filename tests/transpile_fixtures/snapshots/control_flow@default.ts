@@ -0,0 +1,79 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class Grader {
+  public static grade(score: number): string {
+    let result: string;
+    if (score >= 90) {
+      {
+        result = "A";
+      }
+    } else     if (score >= 75) {
+      {
+        result = "B";
+      }
+    } else {
+      {
+        result = "C";
+      }
+    }
+    return result;
+  }
+
+  public static sumTo(n: number): number {
+    let total: number = 0;
+    for (let i: number = 0; i < n; i++) {
+      if (Math.mod(i, 2) === 0) {
+        {
+          continue;
+        }
+      }
+      total += i;
+    }
+    while (total > 100) {
+      {
+        total -= 100;
+      }
+    }
+    do {
+      {
+        total++;
+      }
+    } while (total < 10);
+    return total;
+  }
+
+  public static describe(code: number): string {
+    // switch statement
+    const __switchVal = code;
+    if (__switchVal === 1) {
+      return "one";
+    } else if (__switchVal === 2 || __switchVal === 3) {
+      return "few";
+    } else if (true) {
+      return "many";
+    }
+  }
+
+  public static firstMultiple(start: number, factor: number): number {
+    let candidate: number = start;
+    do {
+      {
+        if (Math.mod(candidate, factor) === 0) {
+          {
+            break;
+          }
+        }
+        candidate++;
+      }
+    } while (candidate < start + factor);
+    return candidate;
+  }
+
+  public static isPositive(value: number): boolean {
+    return value === null ? false : value > 0;
+  }
+
+}
@@ -0,0 +1,77 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class Grader {
+  static grade(score) {
+    let result;
+    if (score >= 90) {
+      {
+        result = "A";
+      }
+    } else     if (score >= 75) {
+      {
+        result = "B";
+      }
+    } else {
+      {
+        result = "C";
+      }
+    }
+    return result;
+  }
+
+  static sumTo(n) {
+    let total = 0;
+    for (let i = 0; i < n; i++) {
+      if (Math.mod(i, 2) === 0) {
+        {
+          continue;
+        }
+      }
+      total += i;
+    }
+    while (total > 100) {
+      {
+        total -= 100;
+      }
+    }
+    do {
+      {
+        total++;
+      }
+    } while (total < 10);
+    return total;
+  }
+
+  static describe(code) {
+    // switch statement
+    const __switchVal = code;
+    if (__switchVal === 1) {
+      return "one";
+    } else if (__switchVal === 2 || __switchVal === 3) {
+      return "few";
+    } else if (true) {
+      return "many";
+    }
+  }
+
+  static firstMultiple(start, factor) {
+    let candidate = start;
+    do {
+      {
+        if (Math.mod(candidate, factor) === 0) {
+          {
+            break;
+          }
+        }
+        candidate++;
+      }
+    } while (candidate < start + factor);
+    return candidate;
+  }
+
+  static isPositive(value) {
+    return value === null ? false : value > 0;
+  }
+
+}
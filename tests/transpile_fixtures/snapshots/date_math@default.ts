@@ -0,0 +1,28 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class RenewalScheduler {
+  public static nextReview(): Date {
+    return $runtime.date.addDays($runtime.today(), 7);
+  }
+
+  public static reminderAt(): Date {
+    return $runtime.date.addMinutes($runtime.date.addHours($runtime.now(), 2), 30);
+  }
+
+  public static renewalDate(startDate: Date, termMonths: number): Date {
+    let renewal: Date = $runtime.date.addMonths(startDate, termMonths);
+    return $runtime.date.addDays(renewal, -1);
+  }
+
+  public static isExpired(expiresAt: Date): boolean {
+    return expiresAt < $runtime.now();
+  }
+
+  public static fiscalYearEnd(): Date {
+    return $runtime.date.addYears($runtime.today(), 1);
+  }
+
+}
@@ -0,0 +1,26 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class RenewalScheduler {
+  static nextReview() {
+    return $runtime.date.addDays($runtime.today(), 7);
+  }
+
+  static reminderAt() {
+    return $runtime.date.addMinutes($runtime.date.addHours($runtime.now(), 2), 30);
+  }
+
+  static renewalDate(startDate, termMonths) {
+    let renewal = $runtime.date.addMonths(startDate, termMonths);
+    return $runtime.date.addDays(renewal, -1);
+  }
+
+  static isExpired(expiresAt) {
+    return expiresAt < $runtime.now();
+  }
+
+  static fiscalYearEnd() {
+    return $runtime.date.addYears($runtime.today(), 1);
+  }
+
+}
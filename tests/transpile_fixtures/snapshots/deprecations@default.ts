@@ -0,0 +1,26 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+/** @deprecated */
+export class LegacyPricing {
+  /** @deprecated */
+  public static readonly RATE: number = 0.9;
+  /** @deprecated */
+  public region: string;
+  /** @deprecated */
+  public constructor() {
+    region = "EMEA";
+  }
+
+  /** @deprecated */
+  public static discount(amount: number): number {
+    return amount * LegacyPricing.RATE;
+  }
+
+  public static total(amount: number): number {
+    return LegacyPricing.discount(amount);
+  }
+
+}
@@ -0,0 +1,24 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+/** @deprecated */
+export class LegacyPricing {
+  /** @deprecated */
+  static RATE = 0.9;
+  /** @deprecated */
+  region;
+  /** @deprecated */
+  constructor() {
+    region = "EMEA";
+  }
+
+  /** @deprecated */
+  static discount(amount) {
+    return amount * LegacyPricing.RATE;
+  }
+
+  static total(amount) {
+    return LegacyPricing.discount(amount);
+  }
+
+}
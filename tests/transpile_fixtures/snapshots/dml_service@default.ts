@@ -0,0 +1,30 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class ContactService {
+  public static async findByAccount(accountId: string, maxRows: number): Promise<Contact[]> {
+    return await $runtime.query("SELECT Id, FirstName, LastName, Email FROM Contact WHERE AccountId = :accountId ORDER BY LastName LIMIT :maxRows", { accountId: accountId, maxRows: maxRows });
+  }
+
+  public static async rename(contacts: Contact[], suffix: string): Promise<void> {
+    for (const c of contacts) {
+      {
+        c.LastName = c.LastName + suffix;
+      }
+    }
+    await $runtime.update(contacts);
+  }
+
+  public static async create(lastName: string): Promise<string> {
+    let c: Contact = new Contact(LastName = lastName);
+    await $runtime.insert(c);
+    return c.Id;
+  }
+
+  public static async purge(contacts: Contact[]): Promise<void> {
+    await $runtime.delete(contacts);
+  }
+
+}
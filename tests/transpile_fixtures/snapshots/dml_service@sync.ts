@@ -0,0 +1,30 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class ContactService {
+  public static findByAccount(accountId: string, maxRows: number): Contact[] {
+    return $runtime.query("SELECT Id, FirstName, LastName, Email FROM Contact WHERE AccountId = :accountId ORDER BY LastName LIMIT :maxRows", { accountId: accountId, maxRows: maxRows });
+  }
+
+  public static rename(contacts: Contact[], suffix: string): void {
+    for (const c of contacts) {
+      {
+        c.LastName = c.LastName + suffix;
+      }
+    }
+    $runtime.update(contacts);
+  }
+
+  public static create(lastName: string): string {
+    let c: Contact = new Contact(LastName = lastName);
+    $runtime.insert(c);
+    return c.Id;
+  }
+
+  public static purge(contacts: Contact[]): void {
+    $runtime.delete(contacts);
+  }
+
+}
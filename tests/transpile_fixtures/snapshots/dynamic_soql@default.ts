@@ -0,0 +1,41 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class RecordBrowser {
+  private static readonly SORTABLE: Set<string> = new Set(["Name", "CreatedDate"]);
+  public recent(objectName: string): Record<string, any>[] {
+    if (!$runtime.globalDescribe().has(objectName)) {
+      {
+        throw new IllegalArgumentException("Unknown object " + objectName);
+      }
+    }
+    let soql: string = "SELECT Id FROM " + objectName;
+    soql += " ORDER BY CreatedDate DESC LIMIT 10";
+    return Database.query(soql);
+  }
+
+  public sorted(objectName: string, sortField: string): Record<string, any>[] {
+    if (RecordBrowser.SORTABLE.has(sortField)) {
+      {
+        return Database.query("SELECT Id FROM " + objectName + " ORDER BY " + sortField);
+      }
+    }
+    return [];
+  }
+
+  public countAll(objectName: string): number {
+    if (objectName === null) {
+      {
+        return 0;
+      }
+    } else     if (Database.countQuery("SELECT COUNT() FROM " + objectName) > 0) {
+      {
+        return 1;
+      }
+    }
+    return -1;
+  }
+
+}
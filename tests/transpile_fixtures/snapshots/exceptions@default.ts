@@ -0,0 +1,32 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class ParseException extends Exception {
+}
+
+export class SafeParser {
+  static ParseException = ParseException;
+  public static parse(value: string): number {
+    try {
+      return Integer.valueOf(value);
+    } catch (e: any) {
+      if (!(e instanceof TypeException)) throw e;
+      System.debug("Bad number: " + e.getMessage());
+      throw new ParseException("Cannot parse " + value);
+    } finally {
+      System.debug("parse attempted");
+    }
+  }
+
+  public static parseOrZero(value: string): number {
+    try {
+      return Integer.valueOf(value);
+    } catch (e: any) {
+      if (!(e instanceof TypeException || e instanceof NullPointerException)) throw e;
+      return 0;
+    }
+  }
+
+}
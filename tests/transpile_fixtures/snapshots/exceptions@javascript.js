@@ -0,0 +1,30 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class ParseException extends Exception {
+}
+
+export class SafeParser {
+  static ParseException = ParseException;
+  static parse(value) {
+    try {
+      return Integer.valueOf(value);
+    } catch (e) {
+      if (!(e instanceof TypeException)) throw e;
+      System.debug("Bad number: " + e.getMessage());
+      throw new ParseException("Cannot parse " + value);
+    } finally {
+      System.debug("parse attempted");
+    }
+  }
+
+  static parseOrZero(value) {
+    try {
+      return Integer.valueOf(value);
+    } catch (e) {
+      if (!(e instanceof TypeException || e instanceof NullPointerException)) throw e;
+      return 0;
+    }
+  }
+
+}
@@ -0,0 +1,28 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class Counters {
+  public static sumTo(n: number): number {
+    let total: number = 0;
+    for (let i: number = 0; i < n; i++) {
+      total += i;
+    }
+    return total;
+  }
+
+  public static meetInMiddle(n: number): number {
+    let steps: number = 0;
+    for (let lo: number = 0, hi: number = n; lo < hi; lo++, hi--) {
+      steps++;
+    }
+    let i: number;
+    let j: number;
+    for (i = 0, j = n; i < j; i += 2, j -= 1) {
+      steps++;
+    }
+    return steps;
+  }
+
+}
@@ -0,0 +1,26 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class Counters {
+  static sumTo(n) {
+    let total = 0;
+    for (let i = 0; i < n; i++) {
+      total += i;
+    }
+    return total;
+  }
+
+  static meetInMiddle(n) {
+    let steps = 0;
+    for (let lo = 0, hi = n; lo < hi; lo++, hi--) {
+      steps++;
+    }
+    let i;
+    let j;
+    for (i = 0, j = n; i < j; i += 2, j -= 1) {
+      steps++;
+    }
+    return steps;
+  }
+
+}
@@ -0,0 +1,25 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class Guard {
+  public static nearLimit(): boolean {
+    let used: number = $runtime.limits.queries();
+    let dml: number = $runtime.limits.dmlStatements();
+    return used + $runtime.limits.heapSize() > 0;
+  }
+
+  public static remaining(): number {
+    return 100 - $runtime.limits.queries();
+  }
+
+  public static dmlLimit(): number {
+    return 150;
+  }
+
+  public static shouted(): number {
+    return 100 - $runtime.limits.queries() - $runtime.limits.dmlRows();
+  }
+
+}
@@ -0,0 +1,23 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class Guard {
+  static nearLimit() {
+    let used = $runtime.limits.queries();
+    let dml = $runtime.limits.dmlStatements();
+    return used + $runtime.limits.heapSize() > 0;
+  }
+
+  static remaining() {
+    return 100 - $runtime.limits.queries();
+  }
+
+  static dmlLimit() {
+    return 150;
+  }
+
+  static shouted() {
+    return 100 - $runtime.limits.queries() - $runtime.limits.dmlRows();
+  }
+
+}
@@ -0,0 +1,11 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class PagedResult {
+  public pageSize: number;
+  public pageNumber: number;
+  public totalItemCount: number;
+  public records: Record<string, any>[];
+}
@@ -0,0 +1,9 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class PagedResult {
+  pageSize;
+  pageNumber;
+  totalItemCount;
+  records;
+}
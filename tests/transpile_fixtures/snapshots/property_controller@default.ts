@@ -0,0 +1,41 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class PropertyController {
+  private static readonly DEFAULT_MAX_PRICE: number = 9999999;
+  private static readonly DEFAULT_PAGE_SIZE: number = 9;
+  public static async getPagedPropertyList(searchKey: string, maxPrice: number, minBedrooms: number, minBathrooms: number, pageSize: number, pageNumber: number): Promise<PagedResult> {
+    let safeMaxPrice: number = maxPrice ?? PropertyController.DEFAULT_MAX_PRICE;
+    let safeMinBedrooms: number = minBedrooms ?? 0;
+    let safeMinBathrooms: number = minBathrooms ?? 0;
+    let safePageSize: number = pageSize ?? PropertyController.DEFAULT_PAGE_SIZE;
+    let safePageNumber: number = pageNumber ?? 1;
+    let searchPattern: string = "%" + searchKey + "%";
+    let offset: number = (safePageNumber - 1) * safePageSize;
+    let result: PagedResult = new PagedResult();
+    result.pageSize = safePageSize;
+    result.pageNumber = safePageNumber;
+    result.totalItemCount = await $runtime.query("SELECT COUNT() FROM Property__c WHERE (Name LIKE :searchPattern OR City__c LIKE :searchPattern OR Tags__c LIKE :searchPattern) AND Price__c <= :safeMaxPrice AND Beds__c >= :safeMinBedrooms AND Baths__c >= :safeMinBathrooms", { safeMaxPrice: safeMaxPrice, safeMinBathrooms: safeMinBathrooms, safeMinBedrooms: safeMinBedrooms, searchPattern: searchPattern });
+    result.records = await $runtime.query("SELECT Id, Name, Address__c, City__c, State__c, Description__c, Price__c, Baths__c, Beds__c, Thumbnail__c, Location__Latitude__s, Location__Longitude__s FROM Property__c WHERE (Name LIKE :searchPattern OR City__c LIKE :searchPattern OR Tags__c LIKE :searchPattern) AND Price__c <= :safeMaxPrice AND Beds__c >= :safeMinBedrooms AND Baths__c >= :safeMinBathrooms WITH USER_MODE ORDER BY Price__c LIMIT :safePageSize OFFSET :offset", { offset: offset, safeMaxPrice: safeMaxPrice, safeMinBathrooms: safeMinBathrooms, safeMinBedrooms: safeMinBedrooms, safePageSize: safePageSize, searchPattern: searchPattern });
+    return result;
+  }
+
+  public static async getPictures(propertyId: string): Promise<ContentVersion[]> {
+    let links: ContentDocumentLink[] = await $runtime.query("SELECT Id, LinkedEntityId, ContentDocument.Title FROM ContentDocumentLink WHERE LinkedEntityId = :propertyId AND ContentDocument.FileType IN ('PNG', 'JPG', 'GIF') WITH USER_MODE", { propertyId: propertyId });
+    if ((links.length === 0)) {
+      {
+        return null;
+      }
+    }
+    let contentIds: Set<string> = new Set<string>();
+    for (const link of links) {
+      {
+        contentIds.add(link.ContentDocumentId);
+      }
+    }
+    return await $runtime.query("SELECT Id, Title FROM ContentVersion WHERE ContentDocumentId IN :contentIds AND IsLatest = true WITH USER_MODE ORDER BY CreatedDate", { contentIds: contentIds });
+  }
+
+}
@@ -0,0 +1,15 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class RecordValidator {
+  public static validate(acc: Account): void {
+    if (acc.Name === null) {
+      {
+        $runtime.addError(acc, "Invalid", false);
+      }
+    }
+  }
+
+}
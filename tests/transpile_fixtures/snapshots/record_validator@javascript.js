@@ -0,0 +1,13 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class RecordValidator {
+  static validate(acc) {
+    if (acc.Name === null) {
+      {
+        $runtime.addError(acc, "Invalid", false);
+      }
+    }
+  }
+
+}
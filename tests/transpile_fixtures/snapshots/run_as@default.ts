@@ -0,0 +1,15 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+class RunAsTest {
+  private static async insertsAsAdmin(): Promise<void> {
+    let admin: User = await $runtime.queryOne("SELECT Id FROM User WHERE Alias = 'admin' LIMIT 1");
+    // System.runAs(admin)
+    {
+      await $runtime.insert(new Account(Name = "Test"));
+    }
+  }
+
+}
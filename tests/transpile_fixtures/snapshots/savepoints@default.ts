@@ -0,0 +1,19 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class OrderService {
+  public async placeOrder(order: Order__c, items: OrderItem__c[]): Promise<void> {
+    let sp: Savepoint = await $runtime.setSavepoint();
+    try {
+      await $runtime.insert(order);
+      await $runtime.insert(items);
+    } catch (e: any) {
+      if (!(e instanceof DmlException)) throw e;
+      await $runtime.rollback(sp);
+      throw e;
+    }
+  }
+
+}
@@ -0,0 +1,17 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class OrderService {
+  async placeOrder(order, items) {
+    let sp = await $runtime.setSavepoint();
+    try {
+      await $runtime.insert(order);
+      await $runtime.insert(items);
+    } catch (e) {
+      if (!(e instanceof DmlException)) throw e;
+      await $runtime.rollback(sp);
+      throw e;
+    }
+  }
+
+}
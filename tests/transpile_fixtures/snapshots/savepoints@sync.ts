@@ -0,0 +1,19 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class OrderService {
+  public placeOrder(order: Order__c, items: OrderItem__c[]): void {
+    let sp: Savepoint = $runtime.setSavepoint();
+    try {
+      $runtime.insert(order);
+      $runtime.insert(items);
+    } catch (e: any) {
+      if (!(e instanceof DmlException)) throw e;
+      $runtime.rollback(sp);
+      throw e;
+    }
+  }
+
+}
@@ -0,0 +1,21 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class SchemaInfo {
+  public static accountLabel(): string {
+    return $runtime.describe("Account").label;
+  }
+
+  public static canCreate(objectName: string): boolean {
+    let token: SObjectDescribe = $runtime.globalDescribe().get(objectName);
+    return token !== null && token.createable;
+  }
+
+  public static contactPrefix(): string {
+    let result: SObjectDescribe = $runtime.describe("Contact");
+    return result.getKeyPrefix();
+  }
+
+}
@@ -0,0 +1,19 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class SchemaInfo {
+  static accountLabel() {
+    return $runtime.describe("Account").label;
+  }
+
+  static canCreate(objectName) {
+    let token = $runtime.globalDescribe().get(objectName);
+    return token !== null && token.createable;
+  }
+
+  static contactPrefix() {
+    let result = $runtime.describe("Contact");
+    return result.getKeyPrefix();
+  }
+
+}
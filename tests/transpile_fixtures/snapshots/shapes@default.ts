@@ -0,0 +1,43 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export enum Kind {
+  CIRCLE,
+  SQUARE
+}
+export namespace Kind {
+  export function values(): Kind[] {
+    return [Kind.CIRCLE, Kind.SQUARE];
+  }
+  export function valueOf(name: string): Kind {
+    const index = ["CIRCLE", "SQUARE"].indexOf(name);
+    if (index < 0) {
+      throw new Error("No enum constant Kind." + name);
+    }
+    return index;
+  }
+}
+
+export abstract class Shape implements Comparable {
+  static Kind = Kind;
+  protected kind: Kind;
+  public label: string;
+  public constructor(kind: Kind) {
+    this.kind = kind;
+  }
+
+  public abstract area(): number;
+
+  public compareTo(other: Shape): number {
+    let that: Shape = (other as Shape);
+    if (this.area() === that.area()) {
+      {
+        return 0;
+      }
+    }
+    return this.area() > that.area() ? 1 : -1;
+  }
+
+}
@@ -0,0 +1,39 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export const Kind = Object.freeze({
+  CIRCLE: 0,
+  SQUARE: 1,
+  0: "CIRCLE",
+  1: "SQUARE",
+  values() {
+    return [Kind.CIRCLE, Kind.SQUARE];
+  },
+  valueOf(name) {
+    const index = ["CIRCLE", "SQUARE"].indexOf(name);
+    if (index < 0) {
+      throw new Error("No enum constant Kind." + name);
+    }
+    return index;
+  },
+});
+
+export class Shape {
+  static Kind = Kind;
+  kind;
+  label;
+  constructor(kind) {
+    this.kind = kind;
+  }
+
+  compareTo(other) {
+    let that = other;
+    if (this.area() === that.area()) {
+      {
+        return 0;
+      }
+    }
+    return this.area() > that.area() ? 1 : -1;
+  }
+
+}
@@ -0,0 +1,39 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class OpportunityLoader {
+  public async accountName(accountId: string): Promise<string> {
+    let acc: Account = await $runtime.queryOne("SELECT Name FROM Account WHERE Id = :accountId", { accountId: accountId });
+    return acc.Name;
+  }
+
+  public async primaryContact(accountId: string): Promise<Contact> {
+    let primary: Contact;
+    primary = await $runtime.queryOne("SELECT Id, Email FROM Contact WHERE AccountId = :accountId LIMIT 1", { accountId: accountId });
+    return primary;
+  }
+
+  public async closeIfFound(opportunityId: string): Promise<void> {
+    let opps: Opportunity | null = await $runtime.queryOptional("SELECT Id, StageName FROM Opportunity WHERE Id = :opportunityId LIMIT 1", { opportunityId: opportunityId });
+    if (opps !== null) {
+      {
+        opps.StageName = "Closed Won";
+        await $runtime.update(opps);
+      }
+    }
+  }
+
+  public async openCount(accountId: string): Promise<number> {
+    let opps: Opportunity[] = await $runtime.query("SELECT Id FROM Opportunity WHERE Id = :accountId", { accountId: accountId });
+    for (const opp of opps) {
+      {
+        System.debug(opp.Id);
+      }
+    }
+    let total: number = await $runtime.query("SELECT COUNT() FROM Opportunity WHERE AccountId = :accountId", { accountId: accountId });
+    return (opps.length === 0) ? total : opps.length;
+  }
+
+}
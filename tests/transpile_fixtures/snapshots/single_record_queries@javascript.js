@@ -0,0 +1,37 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class OpportunityLoader {
+  async accountName(accountId) {
+    let acc = await $runtime.queryOne("SELECT Name FROM Account WHERE Id = :accountId", { accountId: accountId });
+    return acc.Name;
+  }
+
+  async primaryContact(accountId) {
+    let primary;
+    primary = await $runtime.queryOne("SELECT Id, Email FROM Contact WHERE AccountId = :accountId LIMIT 1", { accountId: accountId });
+    return primary;
+  }
+
+  async closeIfFound(opportunityId) {
+    let opps = await $runtime.queryOptional("SELECT Id, StageName FROM Opportunity WHERE Id = :opportunityId LIMIT 1", { opportunityId: opportunityId });
+    if (opps !== null) {
+      {
+        opps.StageName = "Closed Won";
+        await $runtime.update(opps);
+      }
+    }
+  }
+
+  async openCount(accountId) {
+    let opps = await $runtime.query("SELECT Id FROM Opportunity WHERE Id = :accountId", { accountId: accountId });
+    for (const opp of opps) {
+      {
+        System.debug(opp.Id);
+      }
+    }
+    let total = await $runtime.query("SELECT COUNT() FROM Opportunity WHERE AccountId = :accountId", { accountId: accountId });
+    return (opps.length === 0) ? total : opps.length;
+  }
+
+}
@@ -0,0 +1,52 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class OppWrapper implements Comparable {
+  public opp: Opportunity;
+  public constructor(opp: Opportunity) {
+    this.opp = opp;
+  }
+
+  public compareTo(other: OppWrapper): number {
+    let that: OppWrapper = (other as OppWrapper);
+    if (opp.Amount === that.opp.Amount) {
+      {
+        return opp.Name < that.opp.Name ? -1 : 1;
+      }
+    }
+    return opp.Amount > that.opp.Amount ? -1 : 1;
+  }
+
+}
+
+export class OpportunityRanking {
+  static OppWrapper = OppWrapper;
+  private stageNames: string[] = [];
+  public rank(opps: Opportunity[]): OppWrapper[] {
+    let wrappers: OppWrapper[] = [];
+    for (const opp of opps) {
+      {
+        wrappers.add(new OppWrapper(opp));
+      }
+    }
+    wrappers.sort((a, b) => a.compareTo(b));
+    return wrappers;
+  }
+
+  public sortedProbabilities(probabilities: number[]): number[] {
+    probabilities.sort((a, b) => a - b);
+    return probabilities;
+  }
+
+  public sortStages(): void {
+    stageNames.sort((a, b) => (a < b ? -1 : a > b ? 1 : 0));
+    this.stageNames.sort((a, b) => (a < b ? -1 : a > b ? 1 : 0));
+  }
+
+  public sortRecords(opps: Opportunity[]): void {
+    opps.sort();
+  }
+
+}
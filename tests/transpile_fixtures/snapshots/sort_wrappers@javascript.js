@@ -0,0 +1,50 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class OppWrapper {
+  opp;
+  constructor(opp) {
+    this.opp = opp;
+  }
+
+  compareTo(other) {
+    let that = other;
+    if (opp.Amount === that.opp.Amount) {
+      {
+        return opp.Name < that.opp.Name ? -1 : 1;
+      }
+    }
+    return opp.Amount > that.opp.Amount ? -1 : 1;
+  }
+
+}
+
+export class OpportunityRanking {
+  static OppWrapper = OppWrapper;
+  stageNames = [];
+  rank(opps) {
+    let wrappers = [];
+    for (const opp of opps) {
+      {
+        wrappers.add(new OppWrapper(opp));
+      }
+    }
+    wrappers.sort((a, b) => a.compareTo(b));
+    return wrappers;
+  }
+
+  sortedProbabilities(probabilities) {
+    probabilities.sort((a, b) => a - b);
+    return probabilities;
+  }
+
+  sortStages() {
+    stageNames.sort((a, b) => (a < b ? -1 : a > b ? 1 : 0));
+    this.stageNames.sort((a, b) => (a < b ? -1 : a > b ? 1 : 0));
+  }
+
+  sortRecords(opps) {
+    opps.sort();
+  }
+
+}
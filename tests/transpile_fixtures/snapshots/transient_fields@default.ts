@@ -0,0 +1,23 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+export class CartState {
+  public cartId: string;
+  public itemCount: number = 0;
+  private priceCache: Map<string, number>;
+  public renderCount: number;
+  private static instances: number = 0;
+  public priceOf(productId: string): number {
+    return priceCache.get(productId);
+  }
+
+  toJSON(): Record<string, unknown> {
+    const json: Record<string, unknown> = { ...this };
+    delete json.priceCache;
+    delete json.renderCount;
+    return json;
+  }
+
+}
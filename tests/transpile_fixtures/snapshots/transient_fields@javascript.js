@@ -0,0 +1,21 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+export class CartState {
+  cartId;
+  itemCount = 0;
+  priceCache;
+  renderCount;
+  static instances = 0;
+  priceOf(productId) {
+    return priceCache.get(productId);
+  }
+
+  toJSON() {
+    const json = { ...this };
+    delete json.priceCache;
+    delete json.renderCount;
+    return json;
+  }
+
+}
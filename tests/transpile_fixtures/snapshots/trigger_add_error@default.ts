@@ -0,0 +1,19 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+// Trigger: AccountTrigger on Account
+export const AccountTrigger_EVENTS = ["beforeInsert"];
+
+export function AccountTrigger(ctx: TriggerContext) {
+  for (const acc of Trigger.new) {
+    {
+      if (acc.Name === null) {
+        {
+          ctx.addError(acc, "Invalid");
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,17 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// Trigger: AccountTrigger on Account
+export const AccountTrigger_EVENTS = ["beforeInsert"];
+
+export function AccountTrigger(ctx) {
+  for (const acc of Trigger.new) {
+    {
+      if (acc.Name === null) {
+        {
+          ctx.addError(acc, "Invalid");
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,34 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+// Trigger: OpportunityTrigger on Opportunity
+export const OpportunityTrigger_EVENTS = ["beforeInsert", "beforeUpdate", "afterInsert"];
+
+export async function OpportunityTrigger(ctx: TriggerContext) {
+  let closedStages: Set<string> = new Set(["Closed Won", "Closed Lost"]);
+  let records: Opportunity[] = Trigger.new;
+  let count: number = records.length;
+  if (Trigger.isBefore && Trigger.isInsert) {
+    {
+      for (const opp of records) {
+        {
+          if (closedStages.has(opp.StageName)) {
+            {
+              ctx.addError(opp, "Cannot create a closed opportunity");
+            }
+          }
+        }
+      }
+    }
+  } else   if (Trigger.isBefore) {
+    {
+      System.debug(count);
+    }
+  } else {
+    {
+      await $runtime.insert(new Task(Subject = "Follow up"));
+    }
+  }
+}
@@ -0,0 +1,47 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+// Trigger: OpportunityTrigger on Opportunity
+export const OpportunityTrigger_EVENTS = ["beforeInsert", "beforeUpdate", "afterInsert"];
+
+function OpportunityTrigger_shared(ctx: TriggerContext) {
+  let closedStages: Set<string> = new Set(["Closed Won", "Closed Lost"]);
+  return { closedStages };
+}
+
+export function OpportunityTrigger_onBeforeInsert(ctx: TriggerContext) {
+  let { closedStages } = OpportunityTrigger_shared(ctx);
+  let records: Opportunity[] = Trigger.new;
+  let count: number = records.length;
+  for (const opp of records) {
+    {
+      if (closedStages.has(opp.StageName)) {
+        {
+          ctx.addError(opp, "Cannot create a closed opportunity");
+        }
+      }
+    }
+  }
+}
+
+export function OpportunityTrigger_onBeforeUpdate(ctx: TriggerContext) {
+  let { closedStages } = OpportunityTrigger_shared(ctx);
+  let records: Opportunity[] = Trigger.new;
+  let count: number = records.length;
+  System.debug(count);
+}
+
+export async function OpportunityTrigger_onAfterInsert(ctx: TriggerContext) {
+  let { closedStages } = OpportunityTrigger_shared(ctx);
+  let records: Opportunity[] = Trigger.new;
+  let count: number = records.length;
+  await $runtime.insert(new Task(Subject = "Follow up"));
+}
+
+export const OpportunityTrigger_HANDLERS = {
+  beforeInsert: OpportunityTrigger_onBeforeInsert,
+  beforeUpdate: OpportunityTrigger_onBeforeUpdate,
+  afterInsert: OpportunityTrigger_onAfterInsert,
+};
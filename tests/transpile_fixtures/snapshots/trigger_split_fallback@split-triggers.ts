@@ -0,0 +1,16 @@
+// Generated by ApexRust Transpiler
+// Do not edit directly
+
+// <runtime interface>
+
+// Trigger: ContactTrigger on Contact
+export const ContactTrigger_EVENTS = ["beforeInsert", "afterInsert"];
+
+export function ContactTrigger(ctx: TriggerContext) {
+  if (Trigger.isBefore && Trigger.new.length > 100) {
+    {
+      System.debug("bulk");
+    }
+  }
+  System.debug(Trigger.isInsert);
+}
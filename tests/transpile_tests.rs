@@ -0,0 +1,728 @@
+//! Tests for Apex to TypeScript transpilation
+//!
+//! Output-shape tests live as golden-file fixtures in
+//! `tests/transpile_fixtures/`; see `tests/snapshot/mod.rs` for the matrix
+//! format and the `APEXRUST_BLESS=1` workflow.
+
+mod snapshot;
+
+use apexrust::parse;
+use apexrust::transpile::{
+    comparable_classes, transpile, transpile_modules, transpile_with_options, TranspileOptions,
+};
+
+/// Helper to parse and transpile Apex source with default options
+fn transpile_source(source: &str) -> String {
+    let cu = parse(source).expect("Parse failed");
+    transpile(&cu).expect("Transpile failed")
+}
+
+// ==================== Snapshot Tests ====================
+
+#[test]
+fn test_transpile_snapshots() {
+    snapshot::run_all();
+}
+
+// ==================== Runtime Interface Tests ====================
+
+#[test]
+fn test_runtime_interface_declares_trigger_context() {
+    let ts = transpile_source("public class Empty {}");
+
+    assert!(ts.contains("export interface TriggerContext"));
+    assert!(ts.contains("addError(record: Record<string, any>, message: string"));
+}
+
+// ==================== addError Tests ====================
+
+#[test]
+fn test_add_error_only_routes_records_through_runtime() {
+    let ts = transpile_source(
+        r#"
+        public class Validation {
+            public class Collector {
+                public void addError(String message) {}
+            }
+            public void check(Account acct, Collector errors, Invoice__c invoice) {
+                errors.addError('collected');
+                acct.addError('record');
+                acct.Name.addError('field');
+                invoice.addError('custom');
+            }
+        }
+        "#,
+    );
+
+    assert!(ts.contains("errors.addError(\"collected\");"));
+    assert!(ts.contains("$runtime.addError(acct, \"record\");"));
+    assert!(ts.contains("$runtime.addError(acct.Name, \"field\");"));
+    assert!(ts.contains("$runtime.addError(invoice, \"custom\");"));
+}
+
+#[test]
+fn test_add_error_on_trigger_context_records() {
+    let ts = transpile_source(
+        r#"
+        trigger Guard on Account (before update) {
+            Trigger.new[0].addError('first');
+            Trigger.oldMap.get(Trigger.new[0].Id).addError('old');
+        }
+        "#,
+    );
+
+    assert!(ts.contains("ctx.addError(Trigger.new[0], \"first\");"));
+    assert!(ts.contains("ctx.addError(Trigger.oldMap.get(Trigger.new[0].Id), \"old\");"));
+}
+
+// ==================== Trigger Split Tests ====================
+
+#[test]
+fn test_split_trigger_handlers_are_prefixed_with_the_trigger() {
+    let source = r#"
+        trigger AccountTrigger on Account (before insert) {
+            if (Trigger.isBefore) { System.debug('account'); }
+        }
+        trigger ContactTrigger on Contact (before insert) {
+            if (Trigger.isBefore) { System.debug('contact'); }
+        }
+        "#;
+    let cu = parse(source).expect("Parse failed");
+    let options = TranspileOptions {
+        split_trigger_events: true,
+        ..TranspileOptions::default()
+    };
+    let ts = transpile_with_options(&cu, options).expect("Transpile failed");
+
+    assert!(ts.contains("export function AccountTrigger_onBeforeInsert(ctx: TriggerContext) {"));
+    assert!(ts.contains("export function ContactTrigger_onBeforeInsert(ctx: TriggerContext) {"));
+    assert!(ts.contains("beforeInsert: AccountTrigger_onBeforeInsert,"));
+    assert!(ts.contains("beforeInsert: ContactTrigger_onBeforeInsert,"));
+}
+
+#[test]
+fn test_sort_uses_comparable_classes_from_other_units() {
+    let wrapper = parse(
+        "public class OppWrapper implements Comparable {
+            public Integer compareTo(Object other) { return 0; }
+        }",
+    )
+    .expect("Parse failed");
+    let service = parse(
+        "public class OppService {
+            public void rank(List<OppWrapper> wrappers) { wrappers.sort(); }
+        }",
+    )
+    .expect("Parse failed");
+
+    let alone = transpile(&service).expect("Transpile failed");
+    assert!(alone.contains("wrappers.sort();"), "{}", alone);
+
+    let options = TranspileOptions {
+        comparable_classes: comparable_classes(&wrapper).into_iter().collect(),
+        ..TranspileOptions::default()
+    };
+    let ts = transpile_with_options(&service, options).expect("Transpile failed");
+    assert!(
+        ts.contains("wrappers.sort((a, b) => a.compareTo(b));"),
+        "{}",
+        ts
+    );
+}
+
+// ==================== Describe Tests ====================
+
+#[test]
+fn test_get_describe_on_user_class_stays_a_call() {
+    let ts = transpile_source(
+        r#"
+        public class Reports {
+            public class Builder {
+                public String getDescribe() { return 'summary'; }
+            }
+            public String label(Builder builder, Schema.SObjectType token) {
+                String summary = builder.getDescribe();
+                return summary + token.getDescribe().getLabel();
+            }
+        }
+        "#,
+    );
+
+    assert!(ts.contains("let summary: string = builder.getDescribe();"));
+    assert!(ts.contains("token.label"));
+}
+
+// ==================== Literal Tests ====================
+
+#[test]
+fn test_numeric_literals_keep_source_radix() {
+    let ts = transpile_source(
+        "public class Masks { Integer hex = 0x1F; Integer octal = 017; Long big = 0xFFL; }",
+    );
+
+    assert!(ts.contains("= 0x1F"));
+    assert!(ts.contains("= 0o17"));
+    assert!(ts.contains("= 0xFFn"));
+}
+
+// ==================== Collection Method Tests ====================
+
+#[test]
+fn test_is_empty_by_receiver_type() {
+    let ts = transpile_source(
+        r#"
+        public class Emptiness {
+            public Boolean check(List<Account> accts, Map<Id, Account> byId, Set<String> names) {
+                return accts.isEmpty() || !byId.isEmpty() || names.isEmpty();
+            }
+        }
+        "#,
+    );
+
+    assert!(ts.contains("accts.length === 0"));
+    assert!(ts.contains("!(byId.size === 0)"));
+    assert!(ts.contains("names.size === 0"));
+}
+
+#[test]
+fn test_is_empty_in_any_context() {
+    let ts = transpile_source(
+        r#"
+        public class Checks {
+            public Boolean check(List<Integer> items, Set<Id> ids, Boolean flag) {
+                Boolean same = flag == items.isEmpty();
+                Boolean none = !ids.ISEMPTY() && items.Size() > 0;
+                return helper().isEmpty();
+            }
+            private List<String> helper() {
+                return new List<String>();
+            }
+        }
+        "#,
+    );
+
+    assert!(ts.contains("let same: boolean = flag === (items.length === 0);"));
+    assert!(ts.contains("let none: boolean = !(ids.size === 0) && items.length > 0;"));
+    assert!(ts.contains("return $runtime.isEmpty(this.helper());"));
+}
+
+#[test]
+fn test_two_dimensional_list() {
+    let ts = transpile_source(
+        r#"
+        public class Grid {
+            public List<List<Integer>> cells = new List<List<Integer>>{ new List<Integer>{1, 2}, new List<Integer>{3} };
+            public List<Integer>[] rows;
+            public void reset() {
+                List<List<Integer>> blank = new List<List<Integer>>();
+            }
+        }
+        "#,
+    );
+
+    assert!(ts.contains("public cells: number[][] = [[1, 2], [3]];"));
+    assert!(ts.contains("public rows: number[][];"));
+    assert!(ts.contains("let blank: number[][] = [];"));
+}
+
+#[test]
+fn test_map_of_lists_literal() {
+    let ts = transpile_source(
+        r#"
+        public class Territories {
+            public Map<String, List<Account>> byRegion = new Map<String, List<Account>>{
+                'west' => new List<Account>{ acct },
+                'east' => new List<Account>()
+            };
+        }
+        "#,
+    );
+
+    assert!(ts.contains(
+        r#"public byRegion: Map<string, Account[]> = new Map([["west", [acct]], ["east", []]]);"#
+    ));
+}
+
+#[test]
+fn test_map_by_id_from_record_list() {
+    let ts = transpile_source(
+        r#"
+        public class Index {
+            public Map<Id, Account> byId(List<Account> accts, Map<Id, Account> known) {
+                Map<Id, Account> copy = new Map<Id, Account>(known);
+                Map<Id, Contact> contacts = new Map<Id, Contact>([SELECT Id FROM Contact]);
+                Map<Id, String> names = new Map<Id, String>(labels);
+                Map<Id, Account> loaded = new Map<Id, Account>(Cache.accounts());
+                return new Map<Id, Account>(accts);
+            }
+        }
+        "#,
+    );
+
+    assert!(
+        ts.contains("return new Map<string, Account>(accts.map((record) => [record.Id, record]));")
+    );
+    assert!(ts.contains(
+        r#"new Map<string, Contact>((await $runtime.query("SELECT Id FROM Contact")).map((record) => [record.Id, record]))"#
+    ));
+    // A list or a map, decided at run time
+    assert!(ts.contains("new Map<string, Account>($runtime.idEntries(Cache.accounts()));"));
+    // Other maps are copied
+    assert!(ts.contains("new Map<string, Account>(known);"));
+    assert!(ts.contains("new Map<string, string>(labels);"));
+}
+
+// ==================== Async Method Tests ====================
+
+#[test]
+fn test_calls_to_async_methods_are_qualified_and_awaited() {
+    let source = r#"
+        public class Loader {
+            public static List<Account> load() {
+                return [SELECT Id FROM Account];
+            }
+            public static Integer count() {
+                return load().size();
+            }
+            public Integer total() {
+                return Loader.count() + offset();
+            }
+            private Integer offset() {
+                return 1;
+            }
+        }
+        "#;
+    let ts = transpile_source(source);
+
+    assert!(ts.contains("public static async count(): Promise<number> {"));
+    assert!(ts.contains("return (await Loader.load()).length;"));
+    assert!(ts.contains("public async total(): Promise<number> {"));
+    assert!(ts.contains("return await Loader.count() + this.offset();"));
+    assert!(ts.contains("private offset(): number {"));
+
+    let options = TranspileOptions {
+        async_database: false,
+        ..Default::default()
+    };
+    let sync = transpile_with_options(&parse(source).unwrap(), options).unwrap();
+    assert!(sync.contains("return Loader.load().length;"));
+    assert!(!sync.contains("await"));
+}
+
+// ==================== String Conversion Tests ====================
+
+#[test]
+fn test_string_value_of() {
+    let ts = transpile_source(
+        r#"
+        public class Labels {
+            public enum Tier { BRONZE, GOLD }
+            public String describe(Tier tier, Account acc) {
+                return String.valueOf(tier) + String.valueOf(Tier.GOLD)
+                    + String.valueOf(123) + String.valueOf(acc);
+            }
+        }
+        "#,
+    );
+
+    assert!(ts.contains("Tier[tier] + Tier[Tier.GOLD] + String(123) + String(acc)"));
+}
+
+#[test]
+fn test_string_value_of_enum_elements_and_results() {
+    let ts = transpile_source(
+        r#"
+        public class Labels {
+            public enum Tier { BRONZE, GOLD }
+            private Tier[] history;
+            public Tier current() { return Tier.GOLD; }
+            public String describe(List<Tier> tiers, Integer count) {
+                return String.valueOf(tiers[0]) + String.valueOf(tiers.get(1))
+                    + String.valueOf(history[0]) + String.valueOf(current())
+                    + String.valueOf(this.current()) + String.valueOf(count);
+            }
+        }
+        "#,
+    );
+
+    assert!(ts.contains(
+        "Tier[tiers[0]] + Tier[tiers.get(1)] + Tier[history[0]] + Tier[this.current()] \
+         + Tier[this.current()] + String(count)"
+    ));
+}
+
+// ==================== Date Tests ====================
+
+#[test]
+fn test_date_arithmetic_only_on_date_receivers() {
+    let ts = transpile_source(
+        r#"
+        public class Billing {
+            public class Schedule {
+                public Schedule addDays(Integer days) { return this; }
+            }
+            public Date renew(Schedule plan, Date due) {
+                plan.addDays(30);
+                return due.addDays(30).addMonths(1);
+            }
+        }
+        "#,
+    );
+
+    assert!(ts.contains("plan.addDays(30);"));
+    assert!(ts.contains("$runtime.date.addMonths($runtime.date.addDays(due, 30), 1)"));
+}
+
+// ==================== SOQL Tests ====================
+
+#[test]
+fn test_soql_bind_expression_is_evaluated() {
+    let ts = transpile_source(
+        r#"
+        public class Contacts {
+            public List<Contact> forAccounts(Map<Id, Account> byId) {
+                return [SELECT Id FROM Contact WHERE AccountId IN :byId.keySet()];
+            }
+        }
+        "#,
+    );
+
+    assert!(ts.contains("WHERE AccountId IN :byId.keySet()\", { \"byId.keySet()\": byId"));
+}
+
+// ==================== Enum Tests ====================
+
+#[test]
+fn test_enum_values_and_value_of() {
+    let ts = transpile_source(
+        r#"
+        public enum AccountType { CUSTOMER, PARTNER, RESELLER }
+        "#,
+    );
+
+    assert!(ts.contains("export namespace AccountType {"));
+    assert!(ts.contains(
+        "export function values(): AccountType[] {\n    return [AccountType.CUSTOMER, AccountType.PARTNER, AccountType.RESELLER];"
+    ));
+    assert!(ts.contains("const index = [\"CUSTOMER\", \"PARTNER\", \"RESELLER\"].indexOf(name);"));
+
+    let ts = transpile_source(
+        r#"
+        public class Types {
+            public Integer count() {
+                return AccountType.values().size();
+            }
+        }
+        "#,
+    );
+    assert!(ts.contains("AccountType.values().length"));
+}
+
+// ==================== Switch Tests ====================
+
+#[test]
+fn test_switch_when_null_compares_to_null() {
+    let ts = transpile_source(
+        r#"
+        public class Sizes {
+            public String label(Integer size) {
+                switch on size {
+                    when 1, null {
+                        return 'small';
+                    }
+                    when else {
+                        return 'large';
+                    }
+                }
+            }
+        }
+        "#,
+    );
+
+    assert!(ts.contains("if (__switchVal === 1 || __switchVal === null) {"));
+}
+
+// ==================== Future Method Tests ====================
+
+const NOTIFIER: &str = r#"
+public class Notifier {
+    @future(callout=true)
+    public static void send(Set<Id> ids, String channel) {
+        Http http = new Http();
+    }
+
+    public static void notifyAll(List<Account> accounts) {
+        Set<Id> ids = new Map<Id, Account>(accounts).keySet();
+        send(ids, 'email');
+        Notifier.send(ids, 'sms');
+    }
+}
+"#;
+
+#[test]
+fn test_future_calls_are_enqueued() {
+    let ts = transpile_source(NOTIFIER);
+
+    assert!(ts.contains("public static async send(ids: Set<string>, channel: string)"));
+    assert!(ts.contains("public static async notifyAll("));
+    assert!(ts.contains("await $runtime.jobs.enqueueFuture(\"Notifier.send\", [ids, \"email\"]);"));
+    assert!(ts.contains("await $runtime.jobs.enqueueFuture(\"Notifier.send\", [ids, \"sms\"]);"));
+}
+
+#[test]
+fn test_future_methods_are_registered_as_entry_points() {
+    let ts = transpile_source(NOTIFIER);
+
+    assert!(ts.contains(
+        "export const Notifier_ENTRY_POINTS = {\n  \"Notifier.send\": { kind: \"future\", callout: true, run: (args: any[]) => Notifier.send(args[0], args[1]) },\n};"
+    ));
+}
+
+#[test]
+fn test_future_parameters_must_be_primitive() {
+    let unit = parse(
+        r#"
+        public class Sync {
+            @future
+            public static void push(Id accountId, List<Account> accounts) {}
+        }
+        "#,
+    )
+    .unwrap();
+
+    let error = apexrust::transpile::transpile(&unit).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Type error: Sync.push: Unsupported parameter type List<Account> for parameter accounts"
+    );
+}
+
+// ==================== Test Context Tests ====================
+
+#[test]
+fn test_is_running_test_follows_test_context() {
+    let source = r#"
+        public class Mailer {
+            public Boolean shouldSend() {
+                return !Test.isRunningTest();
+            }
+        }
+    "#;
+    assert!(transpile_source(source).contains("return !$runtime.isTest();"));
+
+    let options = TranspileOptions {
+        test_context: false,
+        ..Default::default()
+    };
+    let cu = parse(source).unwrap();
+    let ts = apexrust::transpile::transpile_with_options(&cu, options).unwrap();
+    assert!(ts.contains("return !false;"));
+}
+
+// ==================== Environment Flag Tests ====================
+
+const SYNC_SOURCE: &str = r#"
+    public class Sync {
+        public Integer batchSize() {
+            return System.isBatch() ? 200 : 10;
+        }
+
+        public String fetch(HttpRequest req) {
+            if (Test.isRunningTest()) {
+                return 'stub';
+            } else if (Features.useNewPricing()) {
+                HttpResponse res = new Http().send(req);
+                return res.getBody();
+            }
+            return null;
+        }
+
+        public void log(Boolean ready) {
+            if (!System.isFuture()) {
+                Integer attempts = 1;
+                System.debug(attempts);
+            }
+            if (ready && System.isQueueable()) {
+                System.debug('queued');
+            }
+        }
+    }
+"#;
+
+fn transpile_with_flags(source: &str, flags: &[(&str, bool)]) -> String {
+    let options = TranspileOptions {
+        env_flags: flags
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect(),
+        ..Default::default()
+    };
+    let cu = parse(source).unwrap();
+    apexrust::transpile::transpile_with_options(&cu, options).unwrap()
+}
+
+#[test]
+fn test_env_flags_drop_dead_branches() {
+    let ts = transpile_with_flags(
+        SYNC_SOURCE,
+        &[
+            ("Test.isRunningTest", true),
+            ("System.isBatch", false),
+            ("System.isFuture", false),
+        ],
+    );
+    assert!(ts.contains("return 10;"), "{}", ts);
+    assert!(!ts.contains("200"));
+    assert!(ts.contains("return \"stub\";"));
+    assert!(!ts.contains("useNewPricing"));
+    // The kept branch declares a variable, so it stays a block
+    assert!(ts.contains("{\n      let attempts: number = 1;"), "{}", ts);
+    assert!(!ts.contains("isFuture"));
+}
+
+#[test]
+fn test_env_flags_remove_async_from_dead_callout() {
+    // Under tests the callout branch is never reached
+    let flags = [("Test.isRunningTest", true)];
+    let ts = transpile_with_flags(SYNC_SOURCE, &flags);
+    assert!(
+        ts.contains("public fetch(req: HttpRequest): string {"),
+        "{}",
+        ts
+    );
+
+    let ts = transpile_with_flags(SYNC_SOURCE, &[("features.useNewPricing", true)]);
+    assert!(ts.contains("public async fetch("), "{}", ts);
+    assert!(ts.contains("$runtime.isTest()"));
+}
+
+#[test]
+fn test_env_flags_leave_unknown_predicates() {
+    let ts = transpile_with_flags(SYNC_SOURCE, &[("System.isBatch", true)]);
+    assert!(ts.contains("return 200;"));
+    assert!(ts.contains("System.isFuture()"), "{}", ts);
+    assert!(ts.contains("public async fetch("));
+
+    // `ready` must still be evaluated, so the condition is not folded
+    let ts = transpile_with_flags(SYNC_SOURCE, &[("System.isQueueable", false)]);
+    assert!(ts.contains("if (ready && false)"), "{}", ts);
+
+    assert_eq!(
+        transpile_with_flags(SYNC_SOURCE, &[]),
+        transpile_source(SYNC_SOURCE)
+    );
+}
+
+// ==================== Generic Method Tests ====================
+
+#[test]
+fn test_generic_method_keeps_type_parameters() {
+    let source =
+        "public class Lists { public static <T> T firstOrNull(List<T> items) { return null; } }";
+    let ts = transpile_source(source);
+    assert!(ts.contains("public static firstOrNull<T>(items: T[]): T {"));
+
+    let options = TranspileOptions {
+        typescript: false,
+        ..Default::default()
+    };
+    let cu = parse(source).unwrap();
+    let js = apexrust::transpile::transpile_with_options(&cu, options).unwrap();
+    assert!(js.contains("static firstOrNull(items) {"));
+}
+
+// ==================== Labeled Loop Tests ====================
+
+#[test]
+fn test_labeled_loops_keep_labels() {
+    let ts = transpile_source(
+        "public class Grid { public void scan(List<List<Integer>> rows) { \
+         outer: for (List<Integer> row : rows) { for (Integer cell : row) { \
+         if (cell == 0) continue outer; if (cell < 0) break outer; } } } }",
+    );
+    assert!(ts.contains("outer:\n"), "{}", ts);
+    assert!(ts.contains("continue outer;"));
+    assert!(ts.contains("break outer;"));
+}
+
+// ==================== Serialization Tests ====================
+
+#[test]
+fn test_to_json_omits_transient_fields() {
+    let ts = transpile_source(
+        "public class Cart { public String id; transient Integer hits; public Integer total; }",
+    );
+
+    let to_json = &ts[ts.find("toJSON()").expect("toJSON emitted")..];
+    assert!(to_json.contains("delete json.hits;"));
+    assert!(!to_json.contains("json.id"));
+    assert!(!to_json.contains("json.total"));
+
+    let ts = transpile_source("public class Plain { public String id; }");
+    assert!(!ts.contains("toJSON"));
+}
+
+// ==================== Module Splitting Tests ====================
+
+/// Options splitting classes whose output exceeds 4 KB
+fn split_options() -> TranspileOptions {
+    TranspileOptions {
+        split_class_threshold: Some(4096),
+        ..TranspileOptions::default()
+    }
+}
+
+#[test]
+fn test_large_class_moves_methods_to_companion_module() {
+    let mut source = String::from(
+        "public class BigService {\n    private Integer calls = 0;\n    private static String prefix = 'A';\n",
+    );
+    for i in 0..40 {
+        source.push_str(&format!(
+            "    public List<Account> load{i}(Integer n) {{\n        this.calls = this.calls + n;\n        return [SELECT Id, Name FROM Account LIMIT :n];\n    }}\n    private String label{i}(String name) {{\n        return BigService.prefix + name + this.calls;\n    }}\n"
+        ));
+    }
+    source.push_str("    public static Integer twice(Integer x) { return x * 2; }\n}\n");
+    let unit = parse(&source).expect("Parse failed");
+
+    let modules = transpile_modules(&unit, split_options()).expect("Transpile failed");
+
+    let names: Vec<&str> = modules.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["BigService", "BigService.methods"]);
+    let (class, methods) = (&modules[0].code, &modules[1].code);
+    assert!(class.contains("import * as __m from \"./BigService.methods\";"));
+    assert!(class
+        .contains("public load0(n: number): Promise<Account[]> {\n    return __m.load0(this, n);"));
+    assert!(class.contains("/** @internal */ label0(name: string): string {"));
+    assert!(class.contains("/** @internal */ calls: number = 0;"));
+    assert!(class.contains("return __m.twice(x);"));
+    assert!(methods.contains("import { BigService } from \"./BigService\";"));
+    assert!(methods.contains(
+        "export async function load0(self: BigService, n: number): Promise<Account[]> {\n  self.calls = self.calls + n;"
+    ));
+    assert!(methods.contains("return BigService.prefix + name + self.calls;"));
+    assert!(methods.contains("export function twice(x: number): number {"));
+
+    let files: Vec<(String, String)> = modules
+        .iter()
+        .map(|m| (format!("{}.ts", m.name), m.code.clone()))
+        .collect();
+    if let Some(errors) = snapshot::type_check("apexrust-split-modules", &files) {
+        panic!("tsc rejected the split modules:\n{}", errors);
+    }
+}
+
+#[test]
+fn test_small_class_stays_in_one_module() {
+    let unit = parse(
+        "public class Small { private Integer n = 0; public Integer next() { return this.n + 1; } }",
+    )
+    .expect("Parse failed");
+
+    let modules = transpile_modules(&unit, split_options()).expect("Transpile failed");
+
+    assert_eq!(modules.len(), 1);
+    assert_eq!(modules[0].name, "Small");
+    assert_eq!(modules[0].code, transpile(&unit).unwrap());
+    assert!(modules[0].code.contains("private n: number = 0;"));
+}
@@ -0,0 +1,201 @@
+//! Whole-unit traversal with ast::visitor
+
+use apexrust::ast::visitor::{walk_compilation_unit, walk_expression, Visitor};
+use apexrust::{
+    parse, ClassMember, Expression, MethodCallExpr, MethodDeclaration, NodeId, SoqlQuery, Span,
+    Statement, TypeDeclaration,
+};
+
+const SOURCE: &str = r#"
+public class AccountLoader {
+    private Integer size = Limits.getLimitQueryRows();
+
+    public String label {
+        get { return name.toUpperCase(); }
+    }
+
+    public AccountLoader() {
+        this(Defaults.pageSize());
+    }
+
+    public AccountLoader(Integer size) {}
+
+    public List<Contact> load(Map<Id, Account> byId) {
+        List<Contact> contacts = [
+            SELECT Id, (SELECT Id FROM Cases WHERE Subject = :subject.trim())
+            FROM Contact
+            WHERE AccountId IN :byId.keySet() AND Name = :acc.Name
+        ];
+        System.debug(contacts.size());
+        return contacts;
+    }
+}
+"#;
+
+#[derive(Default)]
+struct Counts<'ast> {
+    calls: Vec<&'ast str>,
+    queries: usize,
+    binds: Vec<&'ast str>,
+    methods: usize,
+}
+
+impl<'ast> Visitor<'ast> for Counts<'ast> {
+    fn visit_method_call(&mut self, call: &'ast MethodCallExpr) {
+        self.calls.push(&call.name);
+    }
+
+    fn visit_soql_query(&mut self, _: &'ast SoqlQuery) {
+        self.queries += 1;
+    }
+
+    fn visit_bind_variable(&mut self, name: &'ast str, _: Span, _: NodeId) {
+        self.binds.push(name);
+    }
+
+    fn visit_method(&mut self, _: &'ast MethodDeclaration) {
+        self.methods += 1;
+    }
+}
+
+#[test]
+fn test_counts_method_calls_everywhere() {
+    let unit = parse(SOURCE).expect("Parse failed");
+    let mut counts = Counts::default();
+    walk_compilation_unit(&mut counts, &unit);
+
+    assert_eq!(
+        counts.calls,
+        [
+            "getLimitQueryRows",
+            "toUpperCase",
+            "pageSize",
+            "trim",
+            "keySet",
+            "debug",
+            "size"
+        ]
+    );
+    assert_eq!(counts.queries, 2);
+    assert_eq!(counts.binds, ["acc.Name"]);
+    assert_eq!(counts.methods, 1);
+}
+
+#[test]
+fn test_walk_from_an_expression() {
+    #[derive(Default)]
+    struct Identifiers(Vec<String>);
+
+    impl<'ast> Visitor<'ast> for Identifiers {
+        fn visit_identifier(&mut self, name: &'ast str, _: Span, _: NodeId) {
+            self.0.push(name.to_string());
+        }
+    }
+
+    let unit = parse("public class A { Object o = a ? b.c : new List<Object>{ d, e[f] }; }")
+        .expect("Parse failed");
+    let TypeDeclaration::Class(class) = &unit.declarations[0] else {
+        panic!("expected class");
+    };
+    let ClassMember::Field(field) = &class.members[0] else {
+        panic!("expected field");
+    };
+    let initializer: &Expression = field.declarators[0].initializer.as_ref().unwrap();
+
+    let mut identifiers = Identifiers::default();
+    walk_expression(&mut identifiers, initializer);
+    assert_eq!(identifiers.0, ["a", "b", "d", "e", "f"]);
+}
+
+#[test]
+fn test_counts_soql_expressions() {
+    #[derive(Default)]
+    struct SoqlCount(usize);
+
+    impl<'ast> apexrust::ast::Visitor<'ast> for SoqlCount {
+        fn visit_expression(&mut self, expr: &'ast Expression) {
+            if let Expression::Soql(_) = expr {
+                self.0 += 1;
+            }
+        }
+    }
+
+    let unit = parse(
+        r#"
+public class Queries {
+    private static final List<User> ADMINS = [SELECT Id FROM User WHERE Profile.Name = 'Admin'];
+
+    public Integer run() {
+        for (Account acc : [SELECT Id, (SELECT Id FROM Contacts) FROM Account]) {
+            update acc;
+        }
+        return [SELECT COUNT() FROM Lead];
+    }
+}
+"#,
+    )
+    .expect("Parse failed");
+
+    let mut count = SoqlCount::default();
+    walk_compilation_unit(&mut count, &unit);
+    // The Contacts subquery is a select item, not an expression
+    assert_eq!(count.0, 3);
+}
+
+#[test]
+fn test_leave_hooks_track_enclosing_nodes() {
+    /// Enclosing statements and expressions of every call
+    #[derive(Default)]
+    struct Depths {
+        depth: usize,
+        calls: Vec<(String, usize)>,
+    }
+
+    impl<'ast> Visitor<'ast> for Depths {
+        fn visit_statement(&mut self, _: &'ast Statement) {
+            self.depth += 1;
+        }
+
+        fn leave_statement(&mut self, _: &'ast Statement) {
+            self.depth -= 1;
+        }
+
+        fn visit_expression(&mut self, expr: &'ast Expression) {
+            if let Expression::MethodCall(call) = expr {
+                self.calls.push((call.name.clone(), self.depth));
+            }
+            self.depth += 1;
+        }
+
+        fn leave_expression(&mut self, _: &'ast Expression) {
+            self.depth -= 1;
+        }
+    }
+
+    let unit = parse(
+        r#"
+public class A {
+    void m() {
+        if (ready()) {
+            load([SELECT Id FROM Account WHERE Id IN :ids.keySet()]);
+        }
+    }
+}
+"#,
+    )
+    .expect("Parse failed");
+
+    let mut depths = Depths::default();
+    walk_compilation_unit(&mut depths, &unit);
+    // keySet() sits in the if, its block, the expression statement, load(),
+    // the query, the IN comparison and the bind
+    assert_eq!(
+        depths.calls,
+        [
+            ("ready".to_string(), 1),
+            ("load".to_string(), 3),
+            ("keySet".to_string(), 7),
+        ]
+    );
+    assert_eq!(depths.depth, 0);
+}